@@ -1,9 +1,14 @@
 use crate::config::Db;
-use crate::rest::ApplicationController;
+use crate::rest::{
+    ApplicationController, ApplicationOrchestrationController,
+    ApplicationOrchestrationRouteController, ApplicationRouteController,
+    ApplicationWorkflowController, CertificateController, ConsumerApiKeyController,
+    ConsumerController, UpstreamController, UpstreamTargetController,
+};
 use std::{net::SocketAddr, sync::Arc, str::FromStr};
 
 use axum::{Json, Router};
-use common::{exception, model};
+use common::{exception, model, notification, secret, transcoding};
 use hyper::StatusCode;
 use opentelemetry_otlp::WithExportConfig;
 use serde_json::{json, Value};
@@ -53,6 +58,60 @@ async fn main() {
                 .routes(Arc::clone(&pg_pool))
                 .fallback(api_fallback),
         )
+        .nest(
+            "/workflow",
+            ApplicationWorkflowController::new()
+                .routes(Arc::clone(&pg_pool))
+                .fallback(api_fallback),
+        )
+        .nest(
+            "/route",
+            ApplicationRouteController::new()
+                .routes(Arc::clone(&pg_pool))
+                .fallback(api_fallback),
+        )
+        .nest(
+            "/orchestration",
+            ApplicationOrchestrationController::new()
+                .routes(Arc::clone(&pg_pool))
+                .fallback(api_fallback),
+        )
+        .nest(
+            "/orchestration-route",
+            ApplicationOrchestrationRouteController::new()
+                .routes(Arc::clone(&pg_pool))
+                .fallback(api_fallback),
+        )
+        .nest(
+            "/upstream",
+            UpstreamController::new()
+                .routes(Arc::clone(&pg_pool))
+                .fallback(api_fallback),
+        )
+        .nest(
+            "/upstream-target",
+            UpstreamTargetController::new()
+                .routes(Arc::clone(&pg_pool))
+                .fallback(api_fallback),
+        )
+        .nest(
+            "/consumer",
+            ConsumerController::new()
+                .routes(Arc::clone(&pg_pool))
+                .fallback(api_fallback),
+        )
+        .nest(
+            "/consumer-api-key",
+            ConsumerApiKeyController::new()
+                .routes(Arc::clone(&pg_pool))
+                .fallback(api_fallback),
+        )
+        .nest(
+            "/certificate",
+            CertificateController::new()
+                .routes(Arc::clone(&pg_pool))
+                .fallback(api_fallback),
+        )
         .layer(TraceLayer::new_for_http());
 
     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
@@ -5,8 +5,9 @@ use chrono::Utc;
 use sqlx::PgPool;
 
 use crate::{
-    exception::{ApiError, APP_ERR_FINDING_PAGINATED, APP_ERR_FIND_BY_ID, APP_ERR_INSERTING, APP_ERR_UPDATING, APP_ERR_DELETE, APP_ERR_FIND_BY_PATH},
+    exception::{ApiError, APP_ERR_FINDING_PAGINATED, APP_ERR_FIND_BY_ID, APP_ERR_INSERTING, APP_ERR_UPDATING, APP_ERR_DELETE, APP_ERR_FIND_BY_PATH, APP_ERR_PATH_ALREADY_EXISTS, APP_ERR_UPSTREAM_NOT_FOUND},
     model::{Application, ApplicationReq, Pagination, PaginationResponse},
+    notification::RoutingNotification,
 };
 
 #[cfg_attr(test, mockall::automock)]
@@ -19,7 +20,12 @@ pub trait ApplicationRepositoryTrait: std::fmt::Debug {
 
     async fn find_by_id(&self, id: i64) -> Result<Option<Application>, ApiError>;
 
-    async fn save(&self, entity: ApplicationReq) -> Result<Application, ApiError>;
+    async fn find_by_path(&self, path: &str) -> Result<Option<Application>, ApiError>;
+
+    async fn next_id(&self) -> Result<i64, ApiError>;
+
+    // the secrets of the auth come sealed.
+    async fn save(&self, id: i64, entity: ApplicationReq) -> Result<Application, ApiError>;
 
     async fn update(&self, entity: Application) -> Result<Application, ApiError>;
 
@@ -88,33 +94,92 @@ impl ApplicationRepositoryTrait for ApplicationRepository {
         Ok(application)
     }
 
-    async fn save(&self, entity: ApplicationReq) -> Result<Application, ApiError> {
-        let application = sqlx::query_as("insert into anothergtw.tb_application(name, created_at, updated_at) values ($1, $2, $3) returning *;")
+    async fn find_by_path(&self, path: &str) -> Result<Option<Application>, ApiError> {
+        let application = sqlx::query_as!(
+            Application,
+            r#"select * from anothergtw.tb_application where path = $1"#,
+            path
+        )
+        .fetch_optional(&*self.pg_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error when finding an application by path: {}", e);
+            ApiError::new(APP_ERR_FIND_BY_PATH)
+        })?;
+
+        Ok(application)
+    }
+
+    async fn next_id(&self) -> Result<i64, ApiError> {
+        sqlx::query_scalar("select nextval('anothergtw.tb_application_id_seq')")
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when reserving the id of an application: {}", e);
+                ApiError::new(APP_ERR_INSERTING)
+            })
+    }
+
+    async fn save(&self, id: i64, entity: ApplicationReq) -> Result<Application, ApiError> {
+        let application: Application = sqlx::query_as("insert into anothergtw.tb_application(id, name, path, url_destination, preserve_host, websocket_max_connections, upstream_policy, id_upstream, rate_limit, auth, created_at, updated_at) values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) returning *;")
+            .bind(id)
             .bind(entity.name.unwrap())
+            .bind(entity.path.unwrap())
+            .bind(entity.url_destination.unwrap())
+            .bind(entity.preserve_host.unwrap_or_default())
+            .bind(entity.websocket_max_connections)
+            .bind(entity.upstream_policy)
+            .bind(entity.id_upstream)
+            .bind(entity.rate_limit)
+            .bind(entity.auth)
             .bind(Utc::now())
             .bind(Utc::now())
             .fetch_one(&*self.pg_pool)
             .await
             .map_err(|e| {
                 tracing::info!("Error when inserting an application: {}", e);
-                ApiError::new(APP_ERR_INSERTING)
+                match violated_constraint(&e) {
+                    Some("uq_ta_path") => ApiError::new(APP_ERR_PATH_ALREADY_EXISTS),
+                    Some("fk_ta_id_upstream") => ApiError::new(APP_ERR_UPSTREAM_NOT_FOUND),
+                    _ => ApiError::new(APP_ERR_INSERTING),
+                }
             })?;
 
+        RoutingNotification::new("application", application.id, "INSERT")
+            .publish(&self.pg_pool)
+            .await;
+
         Ok(application)
     }
 
     async fn update(&self, entity: Application) -> Result<Application, ApiError> {
-        let application = sqlx::query_as("update anothergtw.tb_application set name = $1, updated_at = $2 where id = $3 returning *;")
+        let application: Application = sqlx::query_as("update anothergtw.tb_application set name = $1, path = $2, url_destination = $3, preserve_host = $4, websocket_max_connections = $5, upstream_policy = $6, id_upstream = $7, rate_limit = $8, auth = $9, updated_at = $10 where id = $11 returning *;")
             .bind(entity.name)
+            .bind(entity.path)
+            .bind(entity.url_destination)
+            .bind(entity.preserve_host)
+            .bind(entity.websocket_max_connections)
+            .bind(entity.upstream_policy)
+            .bind(entity.id_upstream)
+            .bind(entity.rate_limit)
+            .bind(entity.auth)
             .bind(Utc::now())
             .bind(entity.id)
             .fetch_one(&*self.pg_pool)
             .await
             .map_err(|e| {
                 tracing::info!("Error when updating an application: {}", e);
-                ApiError::new(APP_ERR_UPDATING)
+                match violated_constraint(&e) {
+                    Some("uq_ta_path") => ApiError::new(APP_ERR_PATH_ALREADY_EXISTS),
+                    Some("fk_ta_id_upstream") => ApiError::new(APP_ERR_UPSTREAM_NOT_FOUND),
+                    _ => ApiError::new(APP_ERR_UPDATING),
+                }
             })?;
 
+        RoutingNotification::new("application", application.id, "UPDATE")
+            .publish(&self.pg_pool)
+            .await;
+
         Ok(application)
     }
 
@@ -128,6 +193,15 @@ impl ApplicationRepositoryTrait for ApplicationRepository {
                 ApiError::new(APP_ERR_DELETE)
             })?;
 
+        RoutingNotification::new("application", id, "DELETE")
+            .publish(&self.pg_pool)
+            .await;
+
         Ok(())
     }
 }
+
+fn violated_constraint(e: &sqlx::Error) -> Option<&str> {
+    e.as_database_error()
+        .and_then(|db_error| db_error.constraint())
+}
@@ -0,0 +1,228 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{
+        ApiError, ROU_ERR_DELETE, ROU_ERR_FINDING_PAGINATED, ROU_ERR_FIND_BY_ID, ROU_ERR_INSERTING,
+        ROU_ERR_UPDATING, ROU_ERR_UPSTREAM_NOT_FOUND, ROU_ERR_WORKFLOW_NOT_FOUND,
+    },
+    model::{ApplicationRoute, ApplicationRouteReq, Pagination, PaginationResponse},
+    notification::RoutingNotification,
+};
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait ApplicationRouteRepositoryTrait: std::fmt::Debug {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ApplicationRoute>, ApiError>;
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<ApplicationRoute>, ApiError>;
+
+    async fn next_id(&self) -> Result<i64, ApiError>;
+
+    // the secrets of the auth come sealed.
+    async fn save(
+        &self,
+        id: i64,
+        entity: ApplicationRouteReq,
+    ) -> Result<ApplicationRoute, ApiError>;
+
+    async fn update(&self, entity: ApplicationRoute) -> Result<ApplicationRoute, ApiError>;
+
+    async fn update_descriptor(
+        &self,
+        id: i64,
+        grpc_descriptor: Option<Vec<u8>>,
+    ) -> Result<ApplicationRoute, ApiError>;
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError>;
+}
+
+#[derive(Debug)]
+pub struct ApplicationRouteRepository {
+    pub pg_pool: Arc<PgPool>,
+}
+
+#[async_trait]
+impl ApplicationRouteRepositoryTrait for ApplicationRouteRepository {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ApplicationRoute>, ApiError> {
+        let total =
+            sqlx::query_scalar("select count(*) as count from anothergtw.tb_application_route")
+                .fetch_one(&*self.pg_pool)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Error when finding routes: {}", e);
+                    ApiError::new(ROU_ERR_FINDING_PAGINATED)
+                })?;
+
+        let mut response = PaginationResponse {
+            page: pagination.page.unwrap(),
+            page_size: pagination.page_size.unwrap(),
+            total,
+            elements: Vec::new(),
+        };
+
+        if total > 0 {
+            let routes = sqlx::query_as!(
+                ApplicationRoute,
+                r#"select * from anothergtw.tb_application_route order by id limit $1 offset $2"#,
+                pagination.page_size.unwrap(),
+                pagination.offset()
+            )
+            .fetch_all(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("Error when finding routes: {}", e);
+                ApiError::new(ROU_ERR_FINDING_PAGINATED)
+            })?;
+
+            response.elements = routes;
+        }
+
+        Ok(response)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<ApplicationRoute>, ApiError> {
+        let route = sqlx::query_as!(
+            ApplicationRoute,
+            r#"select * from anothergtw.tb_application_route where id = $1"#,
+            id
+        )
+        .fetch_optional(&*self.pg_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error when finding a route by id: {}", e);
+            ApiError::new(ROU_ERR_FIND_BY_ID)
+        })?;
+
+        Ok(route)
+    }
+
+    async fn next_id(&self) -> Result<i64, ApiError> {
+        sqlx::query_scalar("select nextval('anothergtw.tb_application_route_id_seq')")
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when reserving the id of a route: {}", e);
+                ApiError::new(ROU_ERR_INSERTING)
+            })
+    }
+
+    async fn save(
+        &self,
+        id: i64,
+        entity: ApplicationRouteReq,
+    ) -> Result<ApplicationRoute, ApiError> {
+        let route: ApplicationRoute = sqlx::query_as("insert into anothergtw.tb_application_route(id, id_application_workflow, path, forward_to, priority, streaming, upstream_policy, id_upstream, rate_limit, auth, created_at, updated_at) values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) returning *;")
+            .bind(id)
+            .bind(entity.id_application_workflow)
+            .bind(entity.path.unwrap())
+            .bind(entity.forward_to)
+            .bind(entity.priority.unwrap_or_default())
+            .bind(entity.streaming.unwrap_or_default())
+            .bind(entity.upstream_policy)
+            .bind(entity.id_upstream)
+            .bind(entity.rate_limit)
+            .bind(entity.auth)
+            .bind(Utc::now())
+            .bind(Utc::now())
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when inserting a route: {}", e);
+                match violated_constraint(&e) {
+                    Some("fk_tar_id_application_workflow") => ApiError::new(ROU_ERR_WORKFLOW_NOT_FOUND),
+                    Some("fk_tar_id_upstream") => ApiError::new(ROU_ERR_UPSTREAM_NOT_FOUND),
+                    _ => ApiError::new(ROU_ERR_INSERTING),
+                }
+            })?;
+
+        RoutingNotification::new("route", route.id, "INSERT")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(route)
+    }
+
+    async fn update(&self, entity: ApplicationRoute) -> Result<ApplicationRoute, ApiError> {
+        let route: ApplicationRoute = sqlx::query_as("update anothergtw.tb_application_route set path = $1, forward_to = $2, priority = $3, streaming = $4, upstream_policy = $5, id_upstream = $6, rate_limit = $7, auth = $8, updated_at = $9 where id = $10 returning *;")
+            .bind(entity.path)
+            .bind(entity.forward_to)
+            .bind(entity.priority)
+            .bind(entity.streaming)
+            .bind(entity.upstream_policy)
+            .bind(entity.id_upstream)
+            .bind(entity.rate_limit)
+            .bind(entity.auth)
+            .bind(Utc::now())
+            .bind(entity.id)
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when updating a route: {}", e);
+                match violated_constraint(&e) {
+                    Some("fk_tar_id_upstream") => ApiError::new(ROU_ERR_UPSTREAM_NOT_FOUND),
+                    _ => ApiError::new(ROU_ERR_UPDATING),
+                }
+            })?;
+
+        RoutingNotification::new("route", route.id, "UPDATE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(route)
+    }
+
+    async fn update_descriptor(
+        &self,
+        id: i64,
+        grpc_descriptor: Option<Vec<u8>>,
+    ) -> Result<ApplicationRoute, ApiError> {
+        let route: ApplicationRoute = sqlx::query_as("update anothergtw.tb_application_route set grpc_descriptor = $1, updated_at = $2 where id = $3 returning *;")
+            .bind(grpc_descriptor)
+            .bind(Utc::now())
+            .bind(id)
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when updating the descriptor of a route: {}", e);
+                ApiError::new(ROU_ERR_UPDATING)
+            })?;
+
+        RoutingNotification::new("route", route.id, "UPDATE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(route)
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError> {
+        sqlx::query("delete from anothergtw.tb_application_route where id = $1")
+            .bind(id)
+            .execute(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when deleting a route: {}", e);
+                ApiError::new(ROU_ERR_DELETE)
+            })?;
+
+        RoutingNotification::new("route", id, "DELETE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(())
+    }
+}
+
+fn violated_constraint(e: &sqlx::Error) -> Option<&str> {
+    e.as_database_error()
+        .and_then(|db_error| db_error.constraint())
+}
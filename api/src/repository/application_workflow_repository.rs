@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{
+        ApiError, WF_ERR_APPLICATION_NOT_FOUND, WF_ERR_DELETE, WF_ERR_FINDING_PAGINATED,
+        WF_ERR_FIND_BY_ID, WF_ERR_INSERTING, WF_ERR_PATH_ALREADY_EXISTS, WF_ERR_UPDATING,
+    },
+    model::{ApplicationWorkflow, ApplicationWorkflowReq, Pagination, PaginationResponse},
+    notification::RoutingNotification,
+};
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait ApplicationWorkflowRepositoryTrait: std::fmt::Debug {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ApplicationWorkflow>, ApiError>;
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<ApplicationWorkflow>, ApiError>;
+
+    async fn save(&self, entity: ApplicationWorkflowReq) -> Result<ApplicationWorkflow, ApiError>;
+
+    async fn update(&self, entity: ApplicationWorkflow) -> Result<ApplicationWorkflow, ApiError>;
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError>;
+}
+
+#[derive(Debug)]
+pub struct ApplicationWorkflowRepository {
+    pub pg_pool: Arc<PgPool>,
+}
+
+#[async_trait]
+impl ApplicationWorkflowRepositoryTrait for ApplicationWorkflowRepository {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ApplicationWorkflow>, ApiError> {
+        let total =
+            sqlx::query_scalar("select count(*) as count from anothergtw.tb_application_workflow")
+                .fetch_one(&*self.pg_pool)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Error when finding workflows: {}", e);
+                    ApiError::new(WF_ERR_FINDING_PAGINATED)
+                })?;
+
+        let mut response = PaginationResponse {
+            page: pagination.page.unwrap(),
+            page_size: pagination.page_size.unwrap(),
+            total,
+            elements: Vec::new(),
+        };
+
+        if total > 0 {
+            let workflows = sqlx::query_as!(
+                ApplicationWorkflow,
+                r#"select * from anothergtw.tb_application_workflow order by id limit $1 offset $2"#,
+                pagination.page_size.unwrap(),
+                pagination.offset()
+            )
+            .fetch_all(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("Error when finding workflows: {}", e);
+                ApiError::new(WF_ERR_FINDING_PAGINATED)
+            })?;
+
+            response.elements = workflows;
+        }
+
+        Ok(response)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<ApplicationWorkflow>, ApiError> {
+        let workflow = sqlx::query_as!(
+            ApplicationWorkflow,
+            r#"select * from anothergtw.tb_application_workflow where id = $1"#,
+            id
+        )
+        .fetch_optional(&*self.pg_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error when finding a workflow by id: {}", e);
+            ApiError::new(WF_ERR_FIND_BY_ID)
+        })?;
+
+        Ok(workflow)
+    }
+
+    async fn save(&self, entity: ApplicationWorkflowReq) -> Result<ApplicationWorkflow, ApiError> {
+        let workflow: ApplicationWorkflow = sqlx::query_as("insert into anothergtw.tb_application_workflow(id_application, path, forward_to, status, priority, upstream_policy, created_at, updated_at) values ($1, $2, $3, $4, $5, $6, $7, $8) returning *;")
+            .bind(entity.id_application.unwrap())
+            .bind(entity.path.unwrap())
+            .bind(entity.forward_to.unwrap())
+            .bind(entity.status.unwrap())
+            .bind(entity.priority.unwrap_or_default())
+            .bind(entity.upstream_policy)
+            .bind(Utc::now())
+            .bind(Utc::now())
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when inserting a workflow: {}", e);
+                match violated_constraint(&e) {
+                    Some("uq_taw_id_application_path") => ApiError::new(WF_ERR_PATH_ALREADY_EXISTS),
+                    Some("fk_taw_id_application") => ApiError::new(WF_ERR_APPLICATION_NOT_FOUND),
+                    _ => ApiError::new(WF_ERR_INSERTING),
+                }
+            })?;
+
+        RoutingNotification::new("workflow", workflow.id, "INSERT")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(workflow)
+    }
+
+    async fn update(&self, entity: ApplicationWorkflow) -> Result<ApplicationWorkflow, ApiError> {
+        let workflow: ApplicationWorkflow = sqlx::query_as("update anothergtw.tb_application_workflow set path = $1, forward_to = $2, status = $3, priority = $4, upstream_policy = $5, updated_at = $6 where id = $7 returning *;")
+            .bind(entity.path)
+            .bind(entity.forward_to)
+            .bind(entity.status)
+            .bind(entity.priority)
+            .bind(entity.upstream_policy)
+            .bind(Utc::now())
+            .bind(entity.id)
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when updating a workflow: {}", e);
+                match violated_constraint(&e) {
+                    Some("uq_taw_id_application_path") => ApiError::new(WF_ERR_PATH_ALREADY_EXISTS),
+                    _ => ApiError::new(WF_ERR_UPDATING),
+                }
+            })?;
+
+        RoutingNotification::new("workflow", workflow.id, "UPDATE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(workflow)
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError> {
+        sqlx::query("delete from anothergtw.tb_application_workflow where id = $1")
+            .bind(id)
+            .execute(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when deleting a workflow: {}", e);
+                ApiError::new(WF_ERR_DELETE)
+            })?;
+
+        RoutingNotification::new("workflow", id, "DELETE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(())
+    }
+}
+
+fn violated_constraint(e: &sqlx::Error) -> Option<&str> {
+    e.as_database_error()
+        .and_then(|db_error| db_error.constraint())
+}
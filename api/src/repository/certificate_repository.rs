@@ -0,0 +1,198 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{
+        ApiError, CRT_ERR_DEFAULT_ALREADY_EXISTS, CRT_ERR_DELETE, CRT_ERR_FINDING_PAGINATED,
+        CRT_ERR_FIND_BY_ID, CRT_ERR_INSERTING, CRT_ERR_NAME_ALREADY_EXISTS, CRT_ERR_UPDATING,
+    },
+    model::{Certificate, CertificateDetails, CertificateReq, Pagination, PaginationResponse},
+    notification::RoutingNotification,
+};
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait CertificateRepositoryTrait: std::fmt::Debug {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<Certificate>, ApiError>;
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<Certificate>, ApiError>;
+
+    // the id of the next certificate, its private key is sealed with it before it's inserted.
+    async fn next_id(&self) -> Result<i64, ApiError>;
+
+    async fn save(
+        &self,
+        id: i64,
+        entity: CertificateReq,
+        details: CertificateDetails,
+        private_key: String,
+    ) -> Result<Certificate, ApiError>;
+
+    async fn update(&self, entity: Certificate) -> Result<Certificate, ApiError>;
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError>;
+}
+
+#[derive(Debug)]
+pub struct CertificateRepository {
+    pub pg_pool: Arc<PgPool>,
+}
+
+#[async_trait]
+impl CertificateRepositoryTrait for CertificateRepository {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<Certificate>, ApiError> {
+        let total = sqlx::query_scalar("select count(*) as count from anothergtw.tb_certificate")
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("Error when finding certificates: {}", e);
+                ApiError::new(CRT_ERR_FINDING_PAGINATED)
+            })?;
+
+        let mut response = PaginationResponse {
+            page: pagination.page.unwrap(),
+            page_size: pagination.page_size.unwrap(),
+            total,
+            elements: Vec::new(),
+        };
+
+        if total > 0 {
+            let certificates = sqlx::query_as!(
+                Certificate,
+                r#"select * from anothergtw.tb_certificate order by id limit $1 offset $2"#,
+                pagination.page_size.unwrap(),
+                pagination.offset()
+            )
+            .fetch_all(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("Error when finding certificates: {}", e);
+                ApiError::new(CRT_ERR_FINDING_PAGINATED)
+            })?;
+
+            response.elements = certificates;
+        }
+
+        Ok(response)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<Certificate>, ApiError> {
+        let certificate = sqlx::query_as!(
+            Certificate,
+            r#"select * from anothergtw.tb_certificate where id = $1"#,
+            id
+        )
+        .fetch_optional(&*self.pg_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error when finding a certificate by id: {}", e);
+            ApiError::new(CRT_ERR_FIND_BY_ID)
+        })?;
+
+        Ok(certificate)
+    }
+
+    async fn next_id(&self) -> Result<i64, ApiError> {
+        sqlx::query_scalar("select nextval('anothergtw.tb_certificate_id_seq')")
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when reserving the id of a certificate: {}", e);
+                ApiError::new(CRT_ERR_INSERTING)
+            })
+    }
+
+    async fn save(
+        &self,
+        id: i64,
+        entity: CertificateReq,
+        details: CertificateDetails,
+        private_key: String,
+    ) -> Result<Certificate, ApiError> {
+        let certificate: Certificate = sqlx::query_as("insert into anothergtw.tb_certificate(id, name, certificate, private_key, hostnames, not_after, is_default, created_at, updated_at) values ($1, $2, $3, $4, $5, $6, $7, $8, $9) returning *;")
+            .bind(id)
+            .bind(entity.name.unwrap())
+            .bind(entity.certificate.unwrap())
+            .bind(private_key)
+            .bind(details.hostnames)
+            .bind(details.not_after)
+            .bind(entity.is_default.unwrap_or(false))
+            .bind(Utc::now())
+            .bind(Utc::now())
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when inserting a certificate: {}", e);
+                match violated_constraint(&e) {
+                    Some("uq_tcrt_name") => ApiError::new(CRT_ERR_NAME_ALREADY_EXISTS),
+                    Some("uq_tcrt_default") => ApiError::new(CRT_ERR_DEFAULT_ALREADY_EXISTS),
+                    _ => ApiError::new(CRT_ERR_INSERTING),
+                }
+            })?;
+
+        RoutingNotification::new("certificate", certificate.id, "INSERT")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(certificate)
+    }
+
+    async fn update(&self, entity: Certificate) -> Result<Certificate, ApiError> {
+        let certificate: Certificate = sqlx::query_as("update anothergtw.tb_certificate set name = $1, certificate = $2, private_key = $3, hostnames = $4, not_after = $5, is_default = $6, updated_at = $7 where id = $8 returning *;")
+            .bind(entity.name)
+            .bind(entity.certificate)
+            .bind(entity.private_key)
+            .bind(entity.hostnames)
+            .bind(entity.not_after)
+            .bind(entity.is_default)
+            .bind(Utc::now())
+            .bind(entity.id)
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when updating a certificate: {}", e);
+                match violated_constraint(&e) {
+                    Some("uq_tcrt_name") => ApiError::new(CRT_ERR_NAME_ALREADY_EXISTS),
+                    Some("uq_tcrt_default") => ApiError::new(CRT_ERR_DEFAULT_ALREADY_EXISTS),
+                    _ => ApiError::new(CRT_ERR_UPDATING),
+                }
+            })?;
+
+        RoutingNotification::new("certificate", certificate.id, "UPDATE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(certificate)
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError> {
+        sqlx::query("delete from anothergtw.tb_certificate where id = $1")
+            .bind(id)
+            .execute(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when deleting a certificate: {}", e);
+                ApiError::new(CRT_ERR_DELETE)
+            })?;
+
+        RoutingNotification::new("certificate", id, "DELETE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(())
+    }
+}
+
+fn violated_constraint(e: &sqlx::Error) -> Option<&str> {
+    e.as_database_error()
+        .and_then(|db_error| db_error.constraint())
+}
@@ -0,0 +1,240 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{
+        ApiError, CAK_ERR_CONSUMER_NOT_FOUND, CAK_ERR_DELETE, CAK_ERR_FINDING_PAGINATED,
+        CAK_ERR_FIND_BY_ID, CAK_ERR_INSERTING, CAK_ERR_ROTATING, CAK_ERR_UPDATING,
+    },
+    model::{ConsumerApiKey, ConsumerApiKeyReq, Pagination, PaginationResponse},
+    notification::RoutingNotification,
+};
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait ConsumerApiKeyRepositoryTrait: std::fmt::Debug {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ConsumerApiKey>, ApiError>;
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<ConsumerApiKey>, ApiError>;
+
+    async fn find_by_consumer(&self, id_consumer: i64) -> Result<Vec<ConsumerApiKey>, ApiError>;
+
+    async fn save(
+        &self,
+        entity: ConsumerApiKeyReq,
+        key_hash: String,
+        key_prefix: String,
+    ) -> Result<ConsumerApiKey, ApiError>;
+
+    async fn update(&self, entity: ConsumerApiKey) -> Result<ConsumerApiKey, ApiError>;
+
+    // inserts the new key and sets the expiration of the current one at once.
+    async fn rotate(
+        &self,
+        current: ConsumerApiKey,
+        entity: ConsumerApiKeyReq,
+        key_hash: String,
+        key_prefix: String,
+    ) -> Result<ConsumerApiKey, ApiError>;
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError>;
+}
+
+#[derive(Debug)]
+pub struct ConsumerApiKeyRepository {
+    pub pg_pool: Arc<PgPool>,
+}
+
+#[async_trait]
+impl ConsumerApiKeyRepositoryTrait for ConsumerApiKeyRepository {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ConsumerApiKey>, ApiError> {
+        let total =
+            sqlx::query_scalar("select count(*) as count from anothergtw.tb_consumer_api_key")
+                .fetch_one(&*self.pg_pool)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Error when finding api keys: {}", e);
+                    ApiError::new(CAK_ERR_FINDING_PAGINATED)
+                })?;
+
+        let mut response = PaginationResponse {
+            page: pagination.page.unwrap(),
+            page_size: pagination.page_size.unwrap(),
+            total,
+            elements: Vec::new(),
+        };
+
+        if total > 0 {
+            let api_keys = sqlx::query_as!(
+                ConsumerApiKey,
+                r#"select * from anothergtw.tb_consumer_api_key order by id limit $1 offset $2"#,
+                pagination.page_size.unwrap(),
+                pagination.offset()
+            )
+            .fetch_all(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("Error when finding api keys: {}", e);
+                ApiError::new(CAK_ERR_FINDING_PAGINATED)
+            })?;
+
+            response.elements = api_keys;
+        }
+
+        Ok(response)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<ConsumerApiKey>, ApiError> {
+        let api_key = sqlx::query_as!(
+            ConsumerApiKey,
+            r#"select * from anothergtw.tb_consumer_api_key where id = $1"#,
+            id
+        )
+        .fetch_optional(&*self.pg_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error when finding an api key by id: {}", e);
+            ApiError::new(CAK_ERR_FIND_BY_ID)
+        })?;
+
+        Ok(api_key)
+    }
+
+    async fn find_by_consumer(&self, id_consumer: i64) -> Result<Vec<ConsumerApiKey>, ApiError> {
+        let api_keys = sqlx::query_as!(
+            ConsumerApiKey,
+            r#"select * from anothergtw.tb_consumer_api_key where id_consumer = $1 order by id"#,
+            id_consumer
+        )
+        .fetch_all(&*self.pg_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error when finding the api keys of a consumer: {}", e);
+            ApiError::new(CAK_ERR_FINDING_PAGINATED)
+        })?;
+
+        Ok(api_keys)
+    }
+
+    async fn save(
+        &self,
+        entity: ConsumerApiKeyReq,
+        key_hash: String,
+        key_prefix: String,
+    ) -> Result<ConsumerApiKey, ApiError> {
+        let api_key: ConsumerApiKey = sqlx::query_as("insert into anothergtw.tb_consumer_api_key(id_consumer, key_hash, key_prefix, expires_at, created_at, updated_at) values ($1, $2, $3, $4, $5, $6) returning *;")
+            .bind(entity.id_consumer)
+            .bind(key_hash)
+            .bind(key_prefix)
+            .bind(entity.expires_at)
+            .bind(Utc::now())
+            .bind(Utc::now())
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when inserting an api key: {}", e);
+                match violated_constraint(&e) {
+                    Some("fk_tcak_id_consumer") => ApiError::new(CAK_ERR_CONSUMER_NOT_FOUND),
+                    _ => ApiError::new(CAK_ERR_INSERTING),
+                }
+            })?;
+
+        RoutingNotification::new("consumer_api_key", api_key.id, "INSERT")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(api_key)
+    }
+
+    async fn update(&self, entity: ConsumerApiKey) -> Result<ConsumerApiKey, ApiError> {
+        let api_key: ConsumerApiKey = sqlx::query_as("update anothergtw.tb_consumer_api_key set expires_at = $1, updated_at = $2 where id = $3 returning *;")
+            .bind(entity.expires_at)
+            .bind(Utc::now())
+            .bind(entity.id)
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when updating an api key: {}", e);
+                ApiError::new(CAK_ERR_UPDATING)
+            })?;
+
+        RoutingNotification::new("consumer_api_key", api_key.id, "UPDATE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(api_key)
+    }
+
+    async fn rotate(
+        &self,
+        current: ConsumerApiKey,
+        entity: ConsumerApiKeyReq,
+        key_hash: String,
+        key_prefix: String,
+    ) -> Result<ConsumerApiKey, ApiError> {
+        let rotating_error = |e: sqlx::Error| {
+            tracing::info!("Error when rotating an api key: {}", e);
+            ApiError::new(CAK_ERR_ROTATING)
+        };
+
+        let mut transaction = self.pg_pool.begin().await.map_err(rotating_error)?;
+
+        let api_key: ConsumerApiKey = sqlx::query_as("insert into anothergtw.tb_consumer_api_key(id_consumer, key_hash, key_prefix, expires_at, created_at, updated_at) values ($1, $2, $3, $4, $5, $6) returning *;")
+            .bind(current.id_consumer)
+            .bind(key_hash)
+            .bind(key_prefix)
+            .bind(entity.expires_at)
+            .bind(Utc::now())
+            .bind(Utc::now())
+            .fetch_one(&mut transaction)
+            .await
+            .map_err(rotating_error)?;
+
+        sqlx::query("update anothergtw.tb_consumer_api_key set expires_at = $1, updated_at = $2 where id = $3")
+            .bind(current.expires_at)
+            .bind(Utc::now())
+            .bind(current.id)
+            .execute(&mut transaction)
+            .await
+            .map_err(rotating_error)?;
+
+        transaction.commit().await.map_err(rotating_error)?;
+
+        RoutingNotification::new("consumer_api_key", api_key.id, "INSERT")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(api_key)
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError> {
+        sqlx::query("delete from anothergtw.tb_consumer_api_key where id = $1")
+            .bind(id)
+            .execute(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when deleting an api key: {}", e);
+                ApiError::new(CAK_ERR_DELETE)
+            })?;
+
+        RoutingNotification::new("consumer_api_key", id, "DELETE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(())
+    }
+}
+
+fn violated_constraint(e: &sqlx::Error) -> Option<&str> {
+    e.as_database_error()
+        .and_then(|db_error| db_error.constraint())
+}
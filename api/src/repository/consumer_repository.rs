@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{
+        ApiError, CSM_ERR_CERTIFICATE_SUBJECT_ALREADY_EXISTS, CSM_ERR_DELETE,
+        CSM_ERR_FINDING_PAGINATED, CSM_ERR_FIND_BY_ID, CSM_ERR_INSERTING,
+        CSM_ERR_NAME_ALREADY_EXISTS, CSM_ERR_UPDATING,
+    },
+    model::{Consumer, ConsumerReq, Pagination, PaginationResponse},
+    notification::RoutingNotification,
+};
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait ConsumerRepositoryTrait: std::fmt::Debug {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<Consumer>, ApiError>;
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<Consumer>, ApiError>;
+
+    async fn save(&self, entity: ConsumerReq) -> Result<Consumer, ApiError>;
+
+    async fn update(&self, entity: Consumer) -> Result<Consumer, ApiError>;
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError>;
+}
+
+#[derive(Debug)]
+pub struct ConsumerRepository {
+    pub pg_pool: Arc<PgPool>,
+}
+
+#[async_trait]
+impl ConsumerRepositoryTrait for ConsumerRepository {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<Consumer>, ApiError> {
+        let total = sqlx::query_scalar("select count(*) as count from anothergtw.tb_consumer")
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("Error when finding consumers: {}", e);
+                ApiError::new(CSM_ERR_FINDING_PAGINATED)
+            })?;
+
+        let mut response = PaginationResponse {
+            page: pagination.page.unwrap(),
+            page_size: pagination.page_size.unwrap(),
+            total,
+            elements: Vec::new(),
+        };
+
+        if total > 0 {
+            let consumers = sqlx::query_as!(
+                Consumer,
+                r#"select * from anothergtw.tb_consumer order by id limit $1 offset $2"#,
+                pagination.page_size.unwrap(),
+                pagination.offset()
+            )
+            .fetch_all(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("Error when finding consumers: {}", e);
+                ApiError::new(CSM_ERR_FINDING_PAGINATED)
+            })?;
+
+            response.elements = consumers;
+        }
+
+        Ok(response)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<Consumer>, ApiError> {
+        let consumer = sqlx::query_as!(
+            Consumer,
+            r#"select * from anothergtw.tb_consumer where id = $1"#,
+            id
+        )
+        .fetch_optional(&*self.pg_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error when finding a consumer by id: {}", e);
+            ApiError::new(CSM_ERR_FIND_BY_ID)
+        })?;
+
+        Ok(consumer)
+    }
+
+    async fn save(&self, entity: ConsumerReq) -> Result<Consumer, ApiError> {
+        let consumer: Consumer = sqlx::query_as("insert into anothergtw.tb_consumer(name, rate_limit, certificate_subject, created_at, updated_at) values ($1, $2, $3, $4, $5) returning *;")
+            .bind(entity.name.unwrap())
+            .bind(entity.rate_limit)
+            .bind(entity.certificate_subject.filter(|subject| !subject.is_empty()))
+            .bind(Utc::now())
+            .bind(Utc::now())
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when inserting a consumer: {}", e);
+                match violated_constraint(&e) {
+                    Some("uq_tc_name") => ApiError::new(CSM_ERR_NAME_ALREADY_EXISTS),
+                    Some("uq_tc_certificate_subject") => {
+                        ApiError::new(CSM_ERR_CERTIFICATE_SUBJECT_ALREADY_EXISTS)
+                    }
+                    _ => ApiError::new(CSM_ERR_INSERTING),
+                }
+            })?;
+
+        RoutingNotification::new("consumer", consumer.id, "INSERT")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(consumer)
+    }
+
+    async fn update(&self, entity: Consumer) -> Result<Consumer, ApiError> {
+        let consumer: Consumer = sqlx::query_as("update anothergtw.tb_consumer set name = $1, rate_limit = $2, certificate_subject = $3, updated_at = $4 where id = $5 returning *;")
+            .bind(entity.name)
+            .bind(entity.rate_limit)
+            .bind(entity.certificate_subject)
+            .bind(Utc::now())
+            .bind(entity.id)
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when updating a consumer: {}", e);
+                match violated_constraint(&e) {
+                    Some("uq_tc_name") => ApiError::new(CSM_ERR_NAME_ALREADY_EXISTS),
+                    Some("uq_tc_certificate_subject") => {
+                        ApiError::new(CSM_ERR_CERTIFICATE_SUBJECT_ALREADY_EXISTS)
+                    }
+                    _ => ApiError::new(CSM_ERR_UPDATING),
+                }
+            })?;
+
+        RoutingNotification::new("consumer", consumer.id, "UPDATE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(consumer)
+    }
+
+    // the api keys of the consumer are deleted with it.
+    async fn delete(&self, id: i64) -> Result<(), ApiError> {
+        sqlx::query("delete from anothergtw.tb_consumer where id = $1")
+            .bind(id)
+            .execute(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when deleting a consumer: {}", e);
+                ApiError::new(CSM_ERR_DELETE)
+            })?;
+
+        RoutingNotification::new("consumer", id, "DELETE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(())
+    }
+}
+
+fn violated_constraint(e: &sqlx::Error) -> Option<&str> {
+    e.as_database_error()
+        .and_then(|db_error| db_error.constraint())
+}
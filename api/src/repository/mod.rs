@@ -1,3 +1,21 @@
 mod application_repository;
+mod application_orchestration_repository;
+mod application_orchestration_route_repository;
+mod application_route_repository;
+mod application_workflow_repository;
+mod certificate_repository;
+mod consumer_api_key_repository;
+mod consumer_repository;
+mod upstream_repository;
+mod upstream_target_repository;
 
-pub use application_repository::*;
\ No newline at end of file
+pub use application_repository::*;
+pub use application_orchestration_repository::*;
+pub use application_orchestration_route_repository::*;
+pub use application_route_repository::*;
+pub use application_workflow_repository::*;
+pub use certificate_repository::*;
+pub use consumer_api_key_repository::*;
+pub use consumer_repository::*;
+pub use upstream_repository::*;
+pub use upstream_target_repository::*;
\ No newline at end of file
@@ -0,0 +1,211 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{
+        ApiError, UPS_ERR_DELETE, UPS_ERR_FINDING_PAGINATED, UPS_ERR_FIND_BY_ID, UPS_ERR_INSERTING,
+        UPS_ERR_IN_USE, UPS_ERR_NAME_ALREADY_EXISTS, UPS_ERR_UPDATING,
+    },
+    model::{Pagination, PaginationResponse, Upstream, UpstreamReq, ALGORITHM_ROUND_ROBIN},
+    notification::RoutingNotification,
+};
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait UpstreamRepositoryTrait: std::fmt::Debug {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<Upstream>, ApiError>;
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<Upstream>, ApiError>;
+
+    // the id of the next upstream, its credentials are sealed with it before it's inserted.
+    async fn next_id(&self) -> Result<i64, ApiError>;
+
+    // the credentials come sealed with their type.
+    async fn save(
+        &self,
+        id: i64,
+        entity: UpstreamReq,
+        credentials_type: Option<String>,
+        credentials: Option<String>,
+    ) -> Result<Upstream, ApiError>;
+
+    async fn update(&self, entity: Upstream) -> Result<Upstream, ApiError>;
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError>;
+}
+
+#[derive(Debug)]
+pub struct UpstreamRepository {
+    pub pg_pool: Arc<PgPool>,
+}
+
+#[async_trait]
+impl UpstreamRepositoryTrait for UpstreamRepository {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<Upstream>, ApiError> {
+        let total = sqlx::query_scalar("select count(*) as count from anothergtw.tb_upstream")
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("Error when finding upstreams: {}", e);
+                ApiError::new(UPS_ERR_FINDING_PAGINATED)
+            })?;
+
+        let mut response = PaginationResponse {
+            page: pagination.page.unwrap(),
+            page_size: pagination.page_size.unwrap(),
+            total,
+            elements: Vec::new(),
+        };
+
+        if total > 0 {
+            let upstreams = sqlx::query_as!(
+                Upstream,
+                r#"select * from anothergtw.tb_upstream order by id limit $1 offset $2"#,
+                pagination.page_size.unwrap(),
+                pagination.offset()
+            )
+            .fetch_all(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("Error when finding upstreams: {}", e);
+                ApiError::new(UPS_ERR_FINDING_PAGINATED)
+            })?;
+
+            response.elements = upstreams;
+        }
+
+        Ok(response)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<Upstream>, ApiError> {
+        let upstream = sqlx::query_as!(
+            Upstream,
+            r#"select * from anothergtw.tb_upstream where id = $1"#,
+            id
+        )
+        .fetch_optional(&*self.pg_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error when finding an upstream by id: {}", e);
+            ApiError::new(UPS_ERR_FIND_BY_ID)
+        })?;
+
+        Ok(upstream)
+    }
+
+    async fn next_id(&self) -> Result<i64, ApiError> {
+        sqlx::query_scalar("select nextval('anothergtw.tb_upstream_id_seq')")
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when reserving the id of an upstream: {}", e);
+                ApiError::new(UPS_ERR_INSERTING)
+            })
+    }
+
+    async fn save(
+        &self,
+        id: i64,
+        entity: UpstreamReq,
+        credentials_type: Option<String>,
+        credentials: Option<String>,
+    ) -> Result<Upstream, ApiError> {
+        let upstream: Upstream = sqlx::query_as("insert into anothergtw.tb_upstream(id, name, algorithm, hash_on, hash_key, health_check, credentials_type, credentials, tls, created_at, updated_at) values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) returning *;")
+            .bind(id)
+            .bind(entity.name.unwrap())
+            .bind(
+                entity
+                    .algorithm
+                    .unwrap_or_else(|| ALGORITHM_ROUND_ROBIN.to_owned()),
+            )
+            .bind(entity.hash_on)
+            .bind(entity.hash_key)
+            .bind(entity.health_check)
+            .bind(credentials_type)
+            .bind(credentials)
+            .bind(entity.tls)
+            .bind(Utc::now())
+            .bind(Utc::now())
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when inserting an upstream: {}", e);
+                match violated_constraint(&e) {
+                    Some("uq_tu_name") => ApiError::new(UPS_ERR_NAME_ALREADY_EXISTS),
+                    _ => ApiError::new(UPS_ERR_INSERTING),
+                }
+            })?;
+
+        RoutingNotification::new("upstream", upstream.id, "INSERT")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(upstream)
+    }
+
+    async fn update(&self, entity: Upstream) -> Result<Upstream, ApiError> {
+        let upstream: Upstream = sqlx::query_as("update anothergtw.tb_upstream set name = $1, algorithm = $2, hash_on = $3, hash_key = $4, health_check = $5, credentials_type = $6, credentials = $7, tls = $8, updated_at = $9 where id = $10 returning *;")
+            .bind(entity.name)
+            .bind(entity.algorithm)
+            .bind(entity.hash_on)
+            .bind(entity.hash_key)
+            .bind(entity.health_check)
+            .bind(entity.credentials_type)
+            .bind(entity.credentials)
+            .bind(entity.tls)
+            .bind(Utc::now())
+            .bind(entity.id)
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when updating an upstream: {}", e);
+                match violated_constraint(&e) {
+                    Some("uq_tu_name") => ApiError::new(UPS_ERR_NAME_ALREADY_EXISTS),
+                    _ => ApiError::new(UPS_ERR_UPDATING),
+                }
+            })?;
+
+        RoutingNotification::new("upstream", upstream.id, "UPDATE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(upstream)
+    }
+
+    // the targets go with the upstream, applications and routes still using it prevent it.
+    async fn delete(&self, id: i64) -> Result<(), ApiError> {
+        sqlx::query("delete from anothergtw.tb_upstream where id = $1")
+            .bind(id)
+            .execute(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when deleting an upstream: {}", e);
+                match violated_constraint(&e) {
+                    Some("fk_ta_id_upstream") | Some("fk_tar_id_upstream") => {
+                        ApiError::new(UPS_ERR_IN_USE)
+                    }
+                    _ => ApiError::new(UPS_ERR_DELETE),
+                }
+            })?;
+
+        RoutingNotification::new("upstream", id, "DELETE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(())
+    }
+}
+
+fn violated_constraint(e: &sqlx::Error) -> Option<&str> {
+    e.as_database_error()
+        .and_then(|db_error| db_error.constraint())
+}
@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{
+        ApiError, UPT_ERR_DELETE, UPT_ERR_FINDING_PAGINATED, UPT_ERR_FIND_BY_ID, UPT_ERR_INSERTING,
+        UPT_ERR_UPDATING, UPT_ERR_UPSTREAM_NOT_FOUND, UPT_ERR_URL_ALREADY_EXISTS,
+    },
+    model::{
+        Pagination, PaginationResponse, UpstreamTarget, UpstreamTargetReq, DEFAULT_TARGET_WEIGHT,
+    },
+    notification::RoutingNotification,
+};
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait UpstreamTargetRepositoryTrait: std::fmt::Debug {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<UpstreamTarget>, ApiError>;
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<UpstreamTarget>, ApiError>;
+
+    async fn save(&self, entity: UpstreamTargetReq) -> Result<UpstreamTarget, ApiError>;
+
+    async fn update(&self, entity: UpstreamTarget) -> Result<UpstreamTarget, ApiError>;
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError>;
+}
+
+#[derive(Debug)]
+pub struct UpstreamTargetRepository {
+    pub pg_pool: Arc<PgPool>,
+}
+
+#[async_trait]
+impl UpstreamTargetRepositoryTrait for UpstreamTargetRepository {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<UpstreamTarget>, ApiError> {
+        let total =
+            sqlx::query_scalar("select count(*) as count from anothergtw.tb_upstream_target")
+                .fetch_one(&*self.pg_pool)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Error when finding upstream targets: {}", e);
+                    ApiError::new(UPT_ERR_FINDING_PAGINATED)
+                })?;
+
+        let mut response = PaginationResponse {
+            page: pagination.page.unwrap(),
+            page_size: pagination.page_size.unwrap(),
+            total,
+            elements: Vec::new(),
+        };
+
+        if total > 0 {
+            let targets = sqlx::query_as!(
+                UpstreamTarget,
+                r#"select * from anothergtw.tb_upstream_target order by id limit $1 offset $2"#,
+                pagination.page_size.unwrap(),
+                pagination.offset()
+            )
+            .fetch_all(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("Error when finding upstream targets: {}", e);
+                ApiError::new(UPT_ERR_FINDING_PAGINATED)
+            })?;
+
+            response.elements = targets;
+        }
+
+        Ok(response)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<UpstreamTarget>, ApiError> {
+        let target = sqlx::query_as!(
+            UpstreamTarget,
+            r#"select * from anothergtw.tb_upstream_target where id = $1"#,
+            id
+        )
+        .fetch_optional(&*self.pg_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error when finding an upstream target by id: {}", e);
+            ApiError::new(UPT_ERR_FIND_BY_ID)
+        })?;
+
+        Ok(target)
+    }
+
+    async fn save(&self, entity: UpstreamTargetReq) -> Result<UpstreamTarget, ApiError> {
+        let target: UpstreamTarget = sqlx::query_as("insert into anothergtw.tb_upstream_target(id_upstream, url, weight, created_at, updated_at) values ($1, $2, $3, $4, $5) returning *;")
+            .bind(entity.id_upstream)
+            .bind(entity.url.unwrap())
+            .bind(entity.weight.unwrap_or(DEFAULT_TARGET_WEIGHT))
+            .bind(Utc::now())
+            .bind(Utc::now())
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when inserting an upstream target: {}", e);
+                match violated_constraint(&e) {
+                    Some("uq_tut_id_upstream_url") => ApiError::new(UPT_ERR_URL_ALREADY_EXISTS),
+                    Some("fk_tut_id_upstream") => ApiError::new(UPT_ERR_UPSTREAM_NOT_FOUND),
+                    _ => ApiError::new(UPT_ERR_INSERTING),
+                }
+            })?;
+
+        RoutingNotification::new("upstream_target", target.id, "INSERT")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(target)
+    }
+
+    async fn update(&self, entity: UpstreamTarget) -> Result<UpstreamTarget, ApiError> {
+        let target: UpstreamTarget = sqlx::query_as("update anothergtw.tb_upstream_target set url = $1, weight = $2, updated_at = $3 where id = $4 returning *;")
+            .bind(entity.url)
+            .bind(entity.weight)
+            .bind(Utc::now())
+            .bind(entity.id)
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when updating an upstream target: {}", e);
+                match violated_constraint(&e) {
+                    Some("uq_tut_id_upstream_url") => ApiError::new(UPT_ERR_URL_ALREADY_EXISTS),
+                    _ => ApiError::new(UPT_ERR_UPDATING),
+                }
+            })?;
+
+        RoutingNotification::new("upstream_target", target.id, "UPDATE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(target)
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError> {
+        sqlx::query("delete from anothergtw.tb_upstream_target where id = $1")
+            .bind(id)
+            .execute(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when deleting an upstream target: {}", e);
+                ApiError::new(UPT_ERR_DELETE)
+            })?;
+
+        RoutingNotification::new("upstream_target", id, "DELETE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(())
+    }
+}
+
+fn violated_constraint(e: &sqlx::Error) -> Option<&str> {
+    e.as_database_error()
+        .and_then(|db_error| db_error.constraint())
+}
@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{self, Path, Query, State},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use hyper::StatusCode;
+use sqlx::PgPool;
+use tracing::instrument;
+
+use crate::{
+    exception::ApiError,
+    model::{ApplicationOrchestrationReq, Pagination},
+    service::{ApplicationOrchestrationService, ApplicationOrchestrationServiceTrait},
+};
+
+pub struct ApplicationOrchestrationController;
+
+impl Default for ApplicationOrchestrationController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApplicationOrchestrationController {
+    pub fn new() -> Self {
+        ApplicationOrchestrationController {}
+    }
+
+    pub fn routes(&self, pg_pool: Arc<PgPool>) -> Router {
+        let orchestration_service: Arc<dyn ApplicationOrchestrationServiceTrait + Send + Sync> =
+            Arc::new(ApplicationOrchestrationService::new(Arc::clone(&pg_pool)));
+
+        Router::new()
+            .route(
+                "/",
+                get(ApplicationOrchestrationController::find_all)
+                    .post(ApplicationOrchestrationController::save),
+            )
+            .route(
+                "/:id",
+                get(ApplicationOrchestrationController::find_by_id)
+                    .put(ApplicationOrchestrationController::update)
+                    .delete(ApplicationOrchestrationController::delete),
+            )
+            .with_state(Arc::clone(&orchestration_service))
+    }
+
+    #[instrument]
+    async fn find_all(
+        Query(pagination): Query<Pagination>,
+        State(orchestration_service): State<
+            Arc<dyn ApplicationOrchestrationServiceTrait + Send + Sync>,
+        >,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = orchestration_service.find_all(pagination).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn find_by_id(
+        Path(id): Path<i64>,
+        State(orchestration_service): State<
+            Arc<dyn ApplicationOrchestrationServiceTrait + Send + Sync>,
+        >,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = orchestration_service.find_by_id(id).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn save(
+        State(orchestration_service): State<
+            Arc<dyn ApplicationOrchestrationServiceTrait + Send + Sync>,
+        >,
+        extract::Json(entity): extract::Json<ApplicationOrchestrationReq>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = orchestration_service.save(entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn update(
+        Path(id): Path<i64>,
+        State(orchestration_service): State<
+            Arc<dyn ApplicationOrchestrationServiceTrait + Send + Sync>,
+        >,
+        extract::Json(entity): extract::Json<ApplicationOrchestrationReq>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = orchestration_service.update(id, entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn delete(
+        Path(id): Path<i64>,
+        State(orchestration_service): State<
+            Arc<dyn ApplicationOrchestrationServiceTrait + Send + Sync>,
+        >,
+    ) -> Result<impl IntoResponse, ApiError> {
+        orchestration_service.delete(id).await?;
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
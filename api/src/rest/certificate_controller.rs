@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{self, Path, Query, State},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use hyper::StatusCode;
+use sqlx::PgPool;
+use tracing::instrument;
+
+use crate::{
+    exception::ApiError,
+    model::{CertificateReq, Pagination},
+    service::{CertificateService, CertificateServiceTrait},
+};
+
+pub struct CertificateController;
+
+impl Default for CertificateController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CertificateController {
+    pub fn new() -> Self {
+        CertificateController {}
+    }
+
+    pub fn routes(&self, pg_pool: Arc<PgPool>) -> Router {
+        let certificate_service: Arc<dyn CertificateServiceTrait + Send + Sync> =
+            Arc::new(CertificateService::new(Arc::clone(&pg_pool)));
+
+        Router::new()
+            .route(
+                "/",
+                get(CertificateController::find_all).post(CertificateController::save),
+            )
+            .route(
+                "/:id",
+                get(CertificateController::find_by_id)
+                    .put(CertificateController::update)
+                    .delete(CertificateController::delete),
+            )
+            .with_state(Arc::clone(&certificate_service))
+    }
+
+    #[instrument]
+    async fn find_all(
+        Query(pagination): Query<Pagination>,
+        State(certificate_service): State<Arc<dyn CertificateServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = certificate_service.find_all(pagination).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn find_by_id(
+        Path(id): Path<i64>,
+        State(certificate_service): State<Arc<dyn CertificateServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = certificate_service.find_by_id(id).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn save(
+        State(certificate_service): State<Arc<dyn CertificateServiceTrait + Send + Sync>>,
+        extract::Json(entity): extract::Json<CertificateReq>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = certificate_service.save(entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn update(
+        Path(id): Path<i64>,
+        State(certificate_service): State<Arc<dyn CertificateServiceTrait + Send + Sync>>,
+        extract::Json(entity): extract::Json<CertificateReq>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = certificate_service.update(id, entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn delete(
+        Path(id): Path<i64>,
+        State(certificate_service): State<Arc<dyn CertificateServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        certificate_service.delete(id).await?;
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
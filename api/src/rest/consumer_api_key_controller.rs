@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{self, Path, Query, State},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use hyper::StatusCode;
+use sqlx::PgPool;
+use tracing::instrument;
+
+use crate::{
+    exception::ApiError,
+    model::{ConsumerApiKeyReq, ConsumerApiKeyRotationReq, Pagination},
+    service::{ConsumerApiKeyService, ConsumerApiKeyServiceTrait},
+};
+
+pub struct ConsumerApiKeyController;
+
+impl Default for ConsumerApiKeyController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConsumerApiKeyController {
+    pub fn new() -> Self {
+        ConsumerApiKeyController {}
+    }
+
+    pub fn routes(&self, pg_pool: Arc<PgPool>) -> Router {
+        let api_key_service: Arc<dyn ConsumerApiKeyServiceTrait + Send + Sync> =
+            Arc::new(ConsumerApiKeyService::new(Arc::clone(&pg_pool)));
+
+        Router::new()
+            .route(
+                "/",
+                get(ConsumerApiKeyController::find_all).post(ConsumerApiKeyController::save),
+            )
+            .route(
+                "/:id",
+                get(ConsumerApiKeyController::find_by_id)
+                    .put(ConsumerApiKeyController::update)
+                    .delete(ConsumerApiKeyController::delete),
+            )
+            .route("/:id/rotate", post(ConsumerApiKeyController::rotate))
+            .route(
+                "/consumer/:id_consumer",
+                get(ConsumerApiKeyController::find_by_consumer),
+            )
+            .with_state(Arc::clone(&api_key_service))
+    }
+
+    #[instrument]
+    async fn find_all(
+        Query(pagination): Query<Pagination>,
+        State(api_key_service): State<Arc<dyn ConsumerApiKeyServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = api_key_service.find_all(pagination).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn find_by_id(
+        Path(id): Path<i64>,
+        State(api_key_service): State<Arc<dyn ConsumerApiKeyServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = api_key_service.find_by_id(id).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn find_by_consumer(
+        Path(id_consumer): Path<i64>,
+        State(api_key_service): State<Arc<dyn ConsumerApiKeyServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = api_key_service.find_by_consumer(id_consumer).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    // the answer is the only place where the key shows up.
+    async fn save(
+        State(api_key_service): State<Arc<dyn ConsumerApiKeyServiceTrait + Send + Sync>>,
+        extract::Json(entity): extract::Json<ConsumerApiKeyReq>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = api_key_service.save(entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn update(
+        Path(id): Path<i64>,
+        State(api_key_service): State<Arc<dyn ConsumerApiKeyServiceTrait + Send + Sync>>,
+        extract::Json(entity): extract::Json<ConsumerApiKeyReq>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = api_key_service.update(id, entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    // without a body the current key expires right away.
+    async fn rotate(
+        Path(id): Path<i64>,
+        State(api_key_service): State<Arc<dyn ConsumerApiKeyServiceTrait + Send + Sync>>,
+        entity: Option<extract::Json<ConsumerApiKeyRotationReq>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let entity = entity
+            .map(|extract::Json(entity)| entity)
+            .unwrap_or_default();
+        let response = api_key_service.rotate(id, entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn delete(
+        Path(id): Path<i64>,
+        State(api_key_service): State<Arc<dyn ConsumerApiKeyServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        api_key_service.delete(id).await?;
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{self, Path, Query, State},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use hyper::StatusCode;
+use sqlx::PgPool;
+use tracing::instrument;
+
+use crate::{
+    exception::ApiError,
+    model::{ConsumerReq, Pagination},
+    service::{ConsumerService, ConsumerServiceTrait},
+};
+
+pub struct ConsumerController;
+
+impl Default for ConsumerController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConsumerController {
+    pub fn new() -> Self {
+        ConsumerController {}
+    }
+
+    pub fn routes(&self, pg_pool: Arc<PgPool>) -> Router {
+        let consumer_service: Arc<dyn ConsumerServiceTrait + Send + Sync> =
+            Arc::new(ConsumerService::new(Arc::clone(&pg_pool)));
+
+        Router::new()
+            .route(
+                "/",
+                get(ConsumerController::find_all).post(ConsumerController::save),
+            )
+            .route(
+                "/:id",
+                get(ConsumerController::find_by_id)
+                    .put(ConsumerController::update)
+                    .delete(ConsumerController::delete),
+            )
+            .with_state(Arc::clone(&consumer_service))
+    }
+
+    #[instrument]
+    async fn find_all(
+        Query(pagination): Query<Pagination>,
+        State(consumer_service): State<Arc<dyn ConsumerServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = consumer_service.find_all(pagination).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn find_by_id(
+        Path(id): Path<i64>,
+        State(consumer_service): State<Arc<dyn ConsumerServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = consumer_service.find_by_id(id).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn save(
+        State(consumer_service): State<Arc<dyn ConsumerServiceTrait + Send + Sync>>,
+        extract::Json(entity): extract::Json<ConsumerReq>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = consumer_service.save(entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn update(
+        Path(id): Path<i64>,
+        State(consumer_service): State<Arc<dyn ConsumerServiceTrait + Send + Sync>>,
+        extract::Json(entity): extract::Json<ConsumerReq>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = consumer_service.update(id, entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn delete(
+        Path(id): Path<i64>,
+        State(consumer_service): State<Arc<dyn ConsumerServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        consumer_service.delete(id).await?;
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
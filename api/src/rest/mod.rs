@@ -1,3 +1,21 @@
 mod application_controller;
+mod application_orchestration_controller;
+mod application_orchestration_route_controller;
+mod application_route_controller;
+mod application_workflow_controller;
+mod certificate_controller;
+mod consumer_api_key_controller;
+mod consumer_controller;
+mod upstream_controller;
+mod upstream_target_controller;
 
-pub use application_controller::*;
\ No newline at end of file
+pub use application_controller::*;
+pub use application_orchestration_controller::*;
+pub use application_orchestration_route_controller::*;
+pub use application_route_controller::*;
+pub use application_workflow_controller::*;
+pub use certificate_controller::*;
+pub use consumer_api_key_controller::*;
+pub use consumer_controller::*;
+pub use upstream_controller::*;
+pub use upstream_target_controller::*;
\ No newline at end of file
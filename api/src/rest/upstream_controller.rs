@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{self, Path, Query, State},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use hyper::StatusCode;
+use sqlx::PgPool;
+use tracing::instrument;
+
+use crate::{
+    exception::ApiError,
+    model::{Pagination, UpstreamReq},
+    service::{UpstreamService, UpstreamServiceTrait},
+};
+
+pub struct UpstreamController;
+
+impl Default for UpstreamController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UpstreamController {
+    pub fn new() -> Self {
+        UpstreamController {}
+    }
+
+    pub fn routes(&self, pg_pool: Arc<PgPool>) -> Router {
+        let upstream_service: Arc<dyn UpstreamServiceTrait + Send + Sync> =
+            Arc::new(UpstreamService::new(Arc::clone(&pg_pool)));
+
+        Router::new()
+            .route(
+                "/",
+                get(UpstreamController::find_all).post(UpstreamController::save),
+            )
+            .route(
+                "/:id",
+                get(UpstreamController::find_by_id)
+                    .put(UpstreamController::update)
+                    .delete(UpstreamController::delete),
+            )
+            .with_state(Arc::clone(&upstream_service))
+    }
+
+    #[instrument]
+    async fn find_all(
+        Query(pagination): Query<Pagination>,
+        State(upstream_service): State<Arc<dyn UpstreamServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = upstream_service.find_all(pagination).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn find_by_id(
+        Path(id): Path<i64>,
+        State(upstream_service): State<Arc<dyn UpstreamServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = upstream_service.find_by_id(id).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn save(
+        State(upstream_service): State<Arc<dyn UpstreamServiceTrait + Send + Sync>>,
+        extract::Json(entity): extract::Json<UpstreamReq>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = upstream_service.save(entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn update(
+        Path(id): Path<i64>,
+        State(upstream_service): State<Arc<dyn UpstreamServiceTrait + Send + Sync>>,
+        extract::Json(entity): extract::Json<UpstreamReq>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = upstream_service.update(id, entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn delete(
+        Path(id): Path<i64>,
+        State(upstream_service): State<Arc<dyn UpstreamServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        upstream_service.delete(id).await?;
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
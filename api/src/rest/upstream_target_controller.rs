@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{self, Path, Query, State},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use hyper::StatusCode;
+use sqlx::PgPool;
+use tracing::instrument;
+
+use crate::{
+    exception::ApiError,
+    model::{Pagination, UpstreamTargetReq},
+    service::{UpstreamTargetService, UpstreamTargetServiceTrait},
+};
+
+pub struct UpstreamTargetController;
+
+impl Default for UpstreamTargetController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UpstreamTargetController {
+    pub fn new() -> Self {
+        UpstreamTargetController {}
+    }
+
+    pub fn routes(&self, pg_pool: Arc<PgPool>) -> Router {
+        let target_service: Arc<dyn UpstreamTargetServiceTrait + Send + Sync> =
+            Arc::new(UpstreamTargetService::new(Arc::clone(&pg_pool)));
+
+        Router::new()
+            .route(
+                "/",
+                get(UpstreamTargetController::find_all).post(UpstreamTargetController::save),
+            )
+            .route(
+                "/:id",
+                get(UpstreamTargetController::find_by_id)
+                    .put(UpstreamTargetController::update)
+                    .delete(UpstreamTargetController::delete),
+            )
+            .with_state(Arc::clone(&target_service))
+    }
+
+    #[instrument]
+    async fn find_all(
+        Query(pagination): Query<Pagination>,
+        State(target_service): State<Arc<dyn UpstreamTargetServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = target_service.find_all(pagination).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn find_by_id(
+        Path(id): Path<i64>,
+        State(target_service): State<Arc<dyn UpstreamTargetServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = target_service.find_by_id(id).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn save(
+        State(target_service): State<Arc<dyn UpstreamTargetServiceTrait + Send + Sync>>,
+        extract::Json(entity): extract::Json<UpstreamTargetReq>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = target_service.save(entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn update(
+        Path(id): Path<i64>,
+        State(target_service): State<Arc<dyn UpstreamTargetServiceTrait + Send + Sync>>,
+        extract::Json(entity): extract::Json<UpstreamTargetReq>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = target_service.update(id, entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn delete(
+        Path(id): Path<i64>,
+        State(target_service): State<Arc<dyn UpstreamTargetServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        target_service.delete(id).await?;
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
@@ -0,0 +1,273 @@
+use crate::{
+    exception::{
+        ERR_INVALID_TEMPLATE, ERR_INVALID_VALUE, ERR_REQUIRED_FIELD,
+        ORR_ERR_RESPONSE_KEY_ALREADY_EXISTS,
+    },
+    model::{FAILURE_POLICY_FAIL, FAILURE_POLICY_FALLBACK},
+    repository::MockApplicationOrchestrationRouteRepositoryTrait,
+};
+
+use super::*;
+
+fn orchestration_route() -> ApplicationOrchestrationRoute {
+    ApplicationOrchestrationRoute {
+        id: 1,
+        id_application_orchestration: 1,
+        id_application_route: 1,
+        response_key: String::from("orders"),
+        request_headers: None,
+        request_body: None,
+        failure_policy: String::from(FAILURE_POLICY_FAIL),
+        fallback: None,
+        timeout_ms: None,
+    }
+}
+
+#[tokio::test]
+async fn find_by_id() {
+    let mut mock_repo = MockApplicationOrchestrationRouteRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(orchestration_route())));
+
+    let service = ApplicationOrchestrationRouteService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.find_by_id(1).await;
+    assert!(response.is_ok());
+    assert_eq!("orders", response.unwrap().response_key);
+}
+
+#[tokio::test]
+async fn find_by_id_not_found() {
+    let mut mock_repo = MockApplicationOrchestrationRouteRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| Ok(None));
+
+    let service = ApplicationOrchestrationRouteService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.find_by_id(1).await;
+    assert!(response.is_err());
+    assert_eq!(ORR_ERR_NOT_FOUND.0, response.unwrap_err().code);
+}
+
+#[tokio::test]
+async fn save() {
+    let mut mock_repo = MockApplicationOrchestrationRouteRepositoryTrait::new();
+    mock_repo
+        .expect_save()
+        .returning(|_| Ok(orchestration_route()));
+
+    let request = ApplicationOrchestrationRouteReq {
+        id_application_orchestration: Some(1),
+        id_application_route: Some(1),
+        response_key: Some("orders".to_string()),
+        request_headers: None,
+        request_body: None,
+        failure_policy: None,
+        fallback: None,
+        timeout_ms: None,
+    };
+
+    let service = ApplicationOrchestrationRouteService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.save(request).await;
+    assert!(response.is_ok());
+    assert_eq!(1, response.unwrap().id);
+}
+
+#[tokio::test]
+async fn save_with_invalid_fields() {
+    let request = ApplicationOrchestrationRouteReq {
+        id_application_orchestration: None,
+        id_application_route: None,
+        response_key: Some(" ".to_string()),
+        request_headers: None,
+        request_body: None,
+        failure_policy: None,
+        fallback: None,
+        timeout_ms: None,
+    };
+
+    let service = ApplicationOrchestrationRouteService::new_with_repo(Arc::new(
+        MockApplicationOrchestrationRouteRepositoryTrait::new(),
+    ));
+
+    let response = service.save(request).await;
+    assert!(response.is_err());
+
+    let field_errors = response.unwrap_err().field_errors.unwrap();
+    assert_eq!(3, field_errors.len());
+    assert_eq!(ERR_REQUIRED_FIELD.0, field_errors[0].code);
+    assert_eq!(ERR_REQUIRED_FIELD.0, field_errors[1].code);
+    assert_eq!(ERR_REQUIRED_FIELD.0, field_errors[2].code);
+}
+
+#[tokio::test]
+async fn save_with_invalid_templates() {
+    let request = ApplicationOrchestrationRouteReq {
+        id_application_orchestration: Some(1),
+        id_application_route: Some(1),
+        response_key: Some("request".to_string()),
+        request_headers: Some(serde_json::json!({
+            "x-customer": "{{user.id",
+            "x-page": 1,
+        })),
+        request_body: Some("{{}}".to_string()),
+        failure_policy: None,
+        fallback: None,
+        timeout_ms: None,
+    };
+
+    let service = ApplicationOrchestrationRouteService::new_with_repo(Arc::new(
+        MockApplicationOrchestrationRouteRepositoryTrait::new(),
+    ));
+
+    let response = service.save(request).await;
+    assert!(response.is_err());
+
+    let field_errors = response.unwrap_err().field_errors.unwrap();
+    assert_eq!(4, field_errors.len());
+    assert_eq!(ERR_INVALID_VALUE.0, field_errors[0].code);
+    assert_eq!(ERR_INVALID_TEMPLATE.0, field_errors[1].code);
+    assert_eq!(
+        "orchestrationRoute.requestHeaders.x-customer",
+        field_errors[1].field
+    );
+    assert_eq!(ERR_INVALID_VALUE.0, field_errors[2].code);
+    assert_eq!(ERR_INVALID_TEMPLATE.0, field_errors[3].code);
+}
+
+#[tokio::test]
+async fn save_with_invalid_failure_handling() {
+    let request = ApplicationOrchestrationRouteReq {
+        id_application_orchestration: Some(1),
+        id_application_route: Some(1),
+        response_key: Some("orders".to_string()),
+        request_headers: None,
+        request_body: None,
+        failure_policy: Some(FAILURE_POLICY_FALLBACK.to_string()),
+        fallback: None,
+        timeout_ms: Some(0),
+    };
+
+    let service = ApplicationOrchestrationRouteService::new_with_repo(Arc::new(
+        MockApplicationOrchestrationRouteRepositoryTrait::new(),
+    ));
+
+    let response = service.save(request).await;
+    assert!(response.is_err());
+
+    let field_errors = response.unwrap_err().field_errors.unwrap();
+    assert_eq!(2, field_errors.len());
+    assert_eq!(ERR_REQUIRED_FIELD.0, field_errors[0].code);
+    assert_eq!("orchestrationRoute.fallback", field_errors[0].field);
+    assert_eq!(ERR_INVALID_VALUE.0, field_errors[1].code);
+    assert_eq!("orchestrationRoute.timeoutMs", field_errors[1].field);
+}
+
+#[tokio::test]
+async fn save_with_response_key_already_exists() {
+    let mut mock_repo = MockApplicationOrchestrationRouteRepositoryTrait::new();
+    mock_repo
+        .expect_save()
+        .returning(|_| Err(ApiError::new(ORR_ERR_RESPONSE_KEY_ALREADY_EXISTS)));
+
+    let request = ApplicationOrchestrationRouteReq {
+        id_application_orchestration: Some(1),
+        id_application_route: Some(2),
+        response_key: Some("orders".to_string()),
+        request_headers: None,
+        request_body: None,
+        failure_policy: None,
+        fallback: None,
+        timeout_ms: None,
+    };
+
+    let service = ApplicationOrchestrationRouteService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.save(request).await;
+    assert!(response.is_err());
+    assert_eq!(
+        ORR_ERR_RESPONSE_KEY_ALREADY_EXISTS.0,
+        response.unwrap_err().code
+    );
+}
+
+#[tokio::test]
+async fn update() {
+    let mut mock_repo = MockApplicationOrchestrationRouteRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(orchestration_route())));
+    mock_repo
+        .expect_update()
+        .withf(|orchestration_route| {
+            orchestration_route.id_application_route == 1
+                && orchestration_route.response_key == "customer"
+                && orchestration_route.request_body.as_deref()
+                    == Some("{\"customer\": {{user.id}}}")
+        })
+        .returning(Ok);
+
+    let request = ApplicationOrchestrationRouteReq {
+        id_application_orchestration: None,
+        id_application_route: None,
+        response_key: Some("customer".to_string()),
+        request_headers: None,
+        request_body: Some("{\"customer\": {{user.id}}}".to_string()),
+        failure_policy: None,
+        fallback: None,
+        timeout_ms: None,
+    };
+
+    let service = ApplicationOrchestrationRouteService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.update(1, request).await;
+    assert!(response.is_ok());
+}
+
+#[tokio::test]
+async fn update_failure_policy() {
+    let mut mock_repo = MockApplicationOrchestrationRouteRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| {
+        let mut orchestration_route = orchestration_route();
+        orchestration_route.fallback = Some(serde_json::json!([]));
+        Ok(Some(orchestration_route))
+    });
+    mock_repo
+        .expect_update()
+        .withf(|orchestration_route| {
+            orchestration_route.failure_policy == FAILURE_POLICY_FALLBACK
+                && orchestration_route.timeout_ms == Some(500)
+        })
+        .returning(Ok);
+
+    // the fallback is already there.
+    let request = ApplicationOrchestrationRouteReq {
+        id_application_orchestration: None,
+        id_application_route: None,
+        response_key: None,
+        request_headers: None,
+        request_body: None,
+        failure_policy: Some(FAILURE_POLICY_FALLBACK.to_string()),
+        fallback: None,
+        timeout_ms: Some(500),
+    };
+
+    let service = ApplicationOrchestrationRouteService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.update(1, request).await;
+    assert!(response.is_ok());
+}
+
+#[tokio::test]
+async fn delete() {
+    let mut mock_repo = MockApplicationOrchestrationRouteRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(orchestration_route())));
+    mock_repo.expect_delete().returning(|_| Ok(()));
+
+    let service = ApplicationOrchestrationRouteService::new_with_repo(Arc::new(mock_repo));
+    let response = service.delete(1).await;
+    assert!(response.is_ok());
+}
@@ -0,0 +1,147 @@
+use chrono::Utc;
+
+use crate::{
+    exception::{
+        ERR_INVALID_SUB_PATH, ERR_INVALID_VALUE, ERR_REQUIRED_FIELD, ORC_ERR_PATH_ALREADY_EXISTS,
+    },
+    model::{ORCHESTRATION_TYPE_PARALLEL, ORCHESTRATION_TYPE_SEQUENTIAL},
+    repository::MockApplicationOrchestrationRepositoryTrait,
+};
+
+use super::*;
+
+fn orchestration() -> ApplicationOrchestration {
+    ApplicationOrchestration {
+        id: 1,
+        id_application_workflow: 1,
+        path: String::from("/home"),
+        orchestration_type: String::from(ORCHESTRATION_TYPE_PARALLEL),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn find_by_id() {
+    let mut mock_repo = MockApplicationOrchestrationRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(orchestration())));
+
+    let service = ApplicationOrchestrationService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.find_by_id(1).await;
+    assert!(response.is_ok());
+    assert!(response.unwrap().is_parallel());
+}
+
+#[tokio::test]
+async fn find_by_id_not_found() {
+    let mut mock_repo = MockApplicationOrchestrationRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| Ok(None));
+
+    let service = ApplicationOrchestrationService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.find_by_id(1).await;
+    assert!(response.is_err());
+    assert_eq!(ORC_ERR_NOT_FOUND.0, response.unwrap_err().code);
+}
+
+#[tokio::test]
+async fn save() {
+    let mut mock_repo = MockApplicationOrchestrationRepositoryTrait::new();
+    mock_repo.expect_save().returning(|_| Ok(orchestration()));
+
+    let request = ApplicationOrchestrationReq {
+        id_application_workflow: Some(1),
+        path: Some("/home".to_string()),
+        orchestration_type: Some(ORCHESTRATION_TYPE_PARALLEL.to_string()),
+    };
+
+    let service = ApplicationOrchestrationService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.save(request).await;
+    assert!(response.is_ok());
+    assert_eq!(1, response.unwrap().id);
+}
+
+#[tokio::test]
+async fn save_with_invalid_fields() {
+    let request = ApplicationOrchestrationReq {
+        id_application_workflow: None,
+        path: Some("home".to_string()),
+        orchestration_type: Some("X".to_string()),
+    };
+
+    let service = ApplicationOrchestrationService::new_with_repo(Arc::new(
+        MockApplicationOrchestrationRepositoryTrait::new(),
+    ));
+
+    let response = service.save(request).await;
+    assert!(response.is_err());
+
+    let field_errors = response.unwrap_err().field_errors.unwrap();
+    assert_eq!(3, field_errors.len());
+    assert_eq!(ERR_REQUIRED_FIELD.0, field_errors[0].code);
+    assert_eq!(ERR_INVALID_SUB_PATH.0, field_errors[1].code);
+    assert_eq!(ERR_INVALID_VALUE.0, field_errors[2].code);
+}
+
+#[tokio::test]
+async fn save_with_path_already_exists() {
+    let mut mock_repo = MockApplicationOrchestrationRepositoryTrait::new();
+    mock_repo
+        .expect_save()
+        .returning(|_| Err(ApiError::new(ORC_ERR_PATH_ALREADY_EXISTS)));
+
+    let request = ApplicationOrchestrationReq {
+        id_application_workflow: Some(1),
+        path: Some("/home".to_string()),
+        orchestration_type: Some(ORCHESTRATION_TYPE_SEQUENTIAL.to_string()),
+    };
+
+    let service = ApplicationOrchestrationService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.save(request).await;
+    assert!(response.is_err());
+    assert_eq!(ORC_ERR_PATH_ALREADY_EXISTS.0, response.unwrap_err().code);
+}
+
+#[tokio::test]
+async fn update() {
+    let mut mock_repo = MockApplicationOrchestrationRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(orchestration())));
+    mock_repo
+        .expect_update()
+        .withf(|orchestration| {
+            orchestration.path == "/home"
+                && orchestration.orchestration_type == ORCHESTRATION_TYPE_SEQUENTIAL
+        })
+        .returning(Ok);
+
+    let request = ApplicationOrchestrationReq {
+        id_application_workflow: None,
+        path: None,
+        orchestration_type: Some(ORCHESTRATION_TYPE_SEQUENTIAL.to_string()),
+    };
+
+    let service = ApplicationOrchestrationService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.update(1, request).await;
+    assert!(response.is_ok());
+}
+
+#[tokio::test]
+async fn delete() {
+    let mut mock_repo = MockApplicationOrchestrationRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(orchestration())));
+    mock_repo.expect_delete().returning(|_| Ok(()));
+
+    let service = ApplicationOrchestrationService::new_with_repo(Arc::new(mock_repo));
+    let response = service.delete(1).await;
+    assert!(response.is_ok());
+}
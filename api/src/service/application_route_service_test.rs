@@ -0,0 +1,384 @@
+use chrono::Utc;
+use serde_json::json;
+
+use crate::{
+    exception::{
+        ERR_INVALID_SUB_PATH, ERR_INVALID_URL, ERR_REQUIRED_FIELD, ROU_ERR_INVALID_DESCRIPTOR,
+        ROU_ERR_WORKFLOW_NOT_FOUND,
+    },
+    model::JWT_ALGORITHM_HS256,
+    repository::MockApplicationRouteRepositoryTrait,
+};
+
+use super::*;
+
+const GREETER: &[u8] = include_bytes!("../../../common/fixtures/greeter.pb");
+
+fn route() -> ApplicationRoute {
+    ApplicationRoute {
+        id: 1,
+        id_application_workflow: Some(1),
+        path: String::from("/items"),
+        forward_to: None,
+        priority: 0,
+        streaming: false,
+        grpc_descriptor: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn find_by_id() {
+    let mut mock_repo = MockApplicationRouteRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(route())));
+
+    let service = ApplicationRouteService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.find_by_id(1).await;
+    assert!(response.is_ok());
+    assert_eq!("/items", response.unwrap().path);
+}
+
+#[tokio::test]
+async fn find_by_id_not_found() {
+    let mut mock_repo = MockApplicationRouteRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| Ok(None));
+
+    let service = ApplicationRouteService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.find_by_id(1).await;
+    assert!(response.is_err());
+    assert_eq!(ROU_ERR_NOT_FOUND.0, response.unwrap_err().code);
+}
+
+#[tokio::test]
+async fn save() {
+    let mut mock_repo = MockApplicationRouteRepositoryTrait::new();
+    mock_repo.expect_next_id().returning(|| Ok(1));
+    mock_repo.expect_save().returning(|_, _| Ok(route()));
+
+    let request = ApplicationRouteReq {
+        id_application_workflow: Some(1),
+        path: Some("/items".to_string()),
+        forward_to: None,
+        priority: None,
+        streaming: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+    };
+
+    let service = ApplicationRouteService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.save(request).await;
+    assert!(response.is_ok());
+    assert_eq!(1, response.unwrap().id);
+}
+
+#[tokio::test]
+async fn save_with_invalid_fields() {
+    let request = ApplicationRouteReq {
+        id_application_workflow: None,
+        path: Some("items".to_string()),
+        forward_to: Some("anothergtw".to_string()),
+        priority: None,
+        streaming: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+    };
+
+    let service = ApplicationRouteService::new_with_repo(Arc::new(
+        MockApplicationRouteRepositoryTrait::new(),
+    ));
+
+    let response = service.save(request).await;
+    assert!(response.is_err());
+
+    let field_errors = response.unwrap_err().field_errors.unwrap();
+    assert_eq!(3, field_errors.len());
+    assert_eq!(ERR_REQUIRED_FIELD.0, field_errors[0].code);
+    assert_eq!(ERR_INVALID_SUB_PATH.0, field_errors[1].code);
+    assert_eq!(ERR_INVALID_URL.0, field_errors[2].code);
+}
+
+#[tokio::test]
+async fn save_with_workflow_not_found() {
+    let mut mock_repo = MockApplicationRouteRepositoryTrait::new();
+    mock_repo.expect_next_id().returning(|| Ok(1));
+    mock_repo
+        .expect_save()
+        .returning(|_, _| Err(ApiError::new(ROU_ERR_WORKFLOW_NOT_FOUND)));
+
+    let request = ApplicationRouteReq {
+        id_application_workflow: Some(99),
+        path: Some("/items".to_string()),
+        forward_to: None,
+        priority: None,
+        streaming: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+    };
+
+    let service = ApplicationRouteService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.save(request).await;
+    assert!(response.is_err());
+    assert_eq!(ROU_ERR_WORKFLOW_NOT_FOUND.0, response.unwrap_err().code);
+}
+
+#[tokio::test]
+async fn update() {
+    let mut mock_repo = MockApplicationRouteRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(route())));
+    mock_repo
+        .expect_update()
+        .withf(|route| {
+            route.path == "/items"
+                && route.forward_to.as_deref() == Some("http://legacy.anothergtw.com")
+        })
+        .returning(Ok);
+
+    let request = ApplicationRouteReq {
+        id_application_workflow: None,
+        path: None,
+        forward_to: Some("http://legacy.anothergtw.com".to_string()),
+        priority: None,
+        streaming: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+    };
+
+    let service = ApplicationRouteService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.update(1, request).await;
+    assert!(response.is_ok());
+}
+
+#[tokio::test]
+async fn update_streaming() {
+    let mut mock_repo = MockApplicationRouteRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(route())));
+    mock_repo
+        .expect_update()
+        .withf(|route| route.path == "/items" && route.streaming)
+        .returning(Ok);
+
+    let request = ApplicationRouteReq {
+        id_application_workflow: None,
+        path: None,
+        forward_to: None,
+        priority: None,
+        streaming: Some(true),
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+    };
+
+    let service = ApplicationRouteService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.update(1, request).await;
+    assert!(response.is_ok());
+    assert!(response.unwrap().streaming);
+}
+
+#[tokio::test]
+async fn save_descriptor() {
+    let mut mock_repo = MockApplicationRouteRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(route())));
+    mock_repo
+        .expect_update_descriptor()
+        .withf(|id, grpc_descriptor| *id == 1 && grpc_descriptor.is_some())
+        .returning(|_, grpc_descriptor| {
+            let mut route = route();
+            route.grpc_descriptor = grpc_descriptor;
+            Ok(route)
+        });
+
+    let service = ApplicationRouteService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.save_descriptor(1, GREETER.to_vec()).await;
+    assert!(response.is_ok());
+    assert!(response.unwrap().grpc_descriptor.is_some());
+}
+
+#[tokio::test]
+async fn save_invalid_descriptor() {
+    let mut mock_repo = MockApplicationRouteRepositoryTrait::new();
+    mock_repo.expect_update_descriptor().never();
+
+    let service = ApplicationRouteService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.save_descriptor(1, b"greeter".to_vec()).await;
+    assert!(response.is_err());
+    assert_eq!(ROU_ERR_INVALID_DESCRIPTOR.0, response.unwrap_err().code);
+}
+
+#[tokio::test]
+async fn delete_descriptor() {
+    let mut mock_repo = MockApplicationRouteRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(route())));
+    mock_repo
+        .expect_update_descriptor()
+        .withf(|id, grpc_descriptor| *id == 1 && grpc_descriptor.is_none())
+        .returning(|_, _| Ok(route()));
+
+    let service = ApplicationRouteService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.delete_descriptor(1).await;
+    assert!(response.is_ok());
+}
+
+#[tokio::test]
+async fn delete() {
+    let mut mock_repo = MockApplicationRouteRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(route())));
+    mock_repo.expect_delete().returning(|_| Ok(()));
+
+    let service = ApplicationRouteService::new_with_repo(Arc::new(mock_repo));
+    let response = service.delete(1).await;
+    assert!(response.is_ok());
+}
+
+fn cipher() -> SecretCipher {
+    SecretCipher::from_base64("MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=").unwrap()
+}
+
+fn jwt_auth() -> Value {
+    json!({"jwt": {"algorithms": [JWT_ALGORITHM_HS256], "secret": "s3cr3t"}})
+}
+
+#[tokio::test]
+async fn save_with_jwt_secret() {
+    let mut mock_repo = MockApplicationRouteRepositoryTrait::new();
+    mock_repo.expect_next_id().returning(|| Ok(7));
+    mock_repo
+        .expect_save()
+        .withf(|id, entity| {
+            let sealed = entity.auth.as_ref().unwrap()["jwt"]["secret"]
+                .as_str()
+                .unwrap();
+            let context = format!("{}.jwt.secret", ApplicationRoute::auth_sealing_context(*id));
+            cipher().open(sealed, &context).unwrap() == "s3cr3t"
+        })
+        .returning(|id, entity| {
+            Ok(ApplicationRoute {
+                id,
+                auth: entity.auth,
+                ..route()
+            })
+        });
+
+    let service = ApplicationRouteService::new_with_cipher(Arc::new(mock_repo), Some(cipher()));
+
+    let request = ApplicationRouteReq {
+        id_application_workflow: Some(1),
+        path: Some("/items".to_string()),
+        forward_to: None,
+        priority: None,
+        streaming: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: Some(jwt_auth()),
+    };
+    let route = serde_json::to_value(service.save(request).await.unwrap()).unwrap();
+    assert_eq!(
+        json!({"jwt": {"algorithms": [JWT_ALGORITHM_HS256]}}),
+        route["auth"]
+    );
+}
+
+#[tokio::test]
+async fn find_by_id_without_auth_secrets() {
+    let mut mock_repo = MockApplicationRouteRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|id| {
+        let mut auth = jwt_auth();
+        let context = ApplicationRoute::auth_sealing_context(id);
+        AuthPolicy::seal_secrets(&mut auth, &context, &cipher()).unwrap();
+        Ok(Some(ApplicationRoute {
+            id,
+            auth: Some(auth),
+            ..route()
+        }))
+    });
+
+    let service = ApplicationRouteService::new_with_cipher(Arc::new(mock_repo), Some(cipher()));
+
+    // neither the secret nor its sealed value are shown.
+    let route = serde_json::to_value(service.find_by_id(1).await.unwrap()).unwrap();
+    assert_eq!(
+        json!({"jwt": {"algorithms": [JWT_ALGORITHM_HS256]}}),
+        route["auth"]
+    );
+}
+
+#[tokio::test]
+async fn save_with_introspection_secret() {
+    let mut mock_repo = MockApplicationRouteRepositoryTrait::new();
+    mock_repo.expect_next_id().returning(|| Ok(7));
+    mock_repo
+        .expect_save()
+        .withf(|id, entity| {
+            let sealed = entity.auth.as_ref().unwrap()["introspection"]["clientSecret"]
+                .as_str()
+                .unwrap();
+            let context = format!(
+                "{}.introspection.clientSecret",
+                ApplicationRoute::auth_sealing_context(*id)
+            );
+            cipher().open(sealed, &context).unwrap() == "s3cr3t"
+        })
+        .returning(|id, entity| {
+            Ok(ApplicationRoute {
+                id,
+                auth: entity.auth,
+                ..route()
+            })
+        });
+
+    let service = ApplicationRouteService::new_with_cipher(Arc::new(mock_repo), Some(cipher()));
+
+    let request = ApplicationRouteReq {
+        id_application_workflow: Some(1),
+        path: Some("/items".to_string()),
+        forward_to: None,
+        priority: None,
+        streaming: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: Some(json!({"introspection": {
+            "url": "https://auth.anothergw.com/introspect",
+            "clientId": "gateway",
+            "clientSecret": "s3cr3t"
+        }})),
+    };
+    let route = serde_json::to_value(service.save(request).await.unwrap()).unwrap();
+    assert!(route["auth"]["introspection"].get("clientSecret").is_none());
+    assert_eq!("gateway", route["auth"]["introspection"]["clientId"]);
+}
@@ -1,10 +1,13 @@
-use std::str::FromStr;
-
 use chrono::Utc;
+use serde_json::json;
 
 use crate::{
-    exception::{APP_ERR_INSERTING, PG_ERR_PAGE_REQUIRED, PG_ERR_PAGE_SIZE_REQUIRED, ERR_INVALID_REQUEST, APP_ERR_UPDATING, APP_ERR_DELETE},
-    model::StringMinSize3,
+    exception::{
+        APP_ERR_DELETE, APP_ERR_INSERTING, APP_ERR_PATH_ALREADY_EXISTS, APP_ERR_UPDATING,
+        ERR_INVALID_PATH, ERR_INVALID_REQUEST, ERR_INVALID_URL, ERR_INVALID_VALUE,
+        PG_ERR_PAGE_REQUIRED, PG_ERR_PAGE_SIZE_REQUIRED,
+    },
+    model::JWT_ALGORITHM_HS256,
     repository::MockApplicationRepositoryTrait,
 };
 
@@ -73,8 +76,14 @@ async fn find_by_id() {
             name: String::from("Teste"),
             path: String::from("/teste"),
             url_destination: String::from("http://anothergtw.com"),
-            created_dttm: Utc::now(),
-            update_dttm: Utc::now(),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
         }))
     });
 
@@ -97,24 +106,76 @@ async fn find_by_id_not_found() {
     assert_eq!(APP_ERR_NOT_FOUND.0, response.unwrap_err().code);
 }
 
+#[tokio::test]
+async fn find_by_path() {
+    let mut mock_repo = MockApplicationRepositoryTrait::new();
+    mock_repo.expect_find_by_path().returning(|_| {
+        Ok(Some(Application {
+            id: 1,
+            name: String::from("Teste"),
+            path: String::from("/teste"),
+            url_destination: String::from("http://anothergtw.com"),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }))
+    });
+
+    let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.find_by_path("/teste").await;
+    assert!(response.is_ok());
+    assert_eq!("/teste", response.ok().unwrap().path);
+}
+
+#[tokio::test]
+async fn find_by_path_not_found() {
+    let mut mock_repo = MockApplicationRepositoryTrait::new();
+    mock_repo.expect_find_by_path().returning(|_| Ok(None));
+
+    let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.find_by_path("/teste").await;
+    assert!(response.is_err());
+    assert_eq!(APP_ERR_NOT_FOUND.0, response.unwrap_err().code);
+}
+
 #[tokio::test]
 async fn save() {
     let mut mock_repo = MockApplicationRepositoryTrait::new();
-    mock_repo.expect_save().returning(|_| {
+    mock_repo.expect_next_id().returning(|| Ok(1));
+    mock_repo.expect_save().returning(|_, _| {
         Ok(Application {
             id: 1,
             name: String::from("Teste"),
             path: String::from("/teste"),
             url_destination: String::from("http://anothergtw.com"),
-            created_dttm: Utc::now(),
-            update_dttm: Utc::now(),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
         })
     });
 
     let request = ApplicationReq {
-        name: Some(StringMinSize3::from_str("teste").unwrap()),
-        path: Some(StringMinSize3::from_str("/teste").unwrap()),
-        url_destination: Some(StringMinSize3::from_str("http://anothergw.com").unwrap()),
+        name: Some("teste".to_string()),
+        path: Some("/teste".to_string()),
+        url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
     };
 
     let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
@@ -132,6 +193,12 @@ async fn save_without_fields() {
         name: None,
         path: None,
         url_destination: None,
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
     };
 
     let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
@@ -148,9 +215,15 @@ async fn save_without_min_size() {
     let mock_repo = MockApplicationRepositoryTrait::new();
 
     let request = ApplicationReq {
-        name: Some(StringMinSize3::from_str("te").unwrap()),
-        path: Some(StringMinSize3::from_str("/t").unwrap()),
-        url_destination: Some(StringMinSize3::from_str("ht").unwrap()),
+        name: Some("te".to_string()),
+        path: Some("/t".to_string()),
+        url_destination: Some("ht".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
     };
 
     let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
@@ -163,17 +236,146 @@ async fn save_without_min_size() {
     assert_eq!(3, api_error.field_errors.unwrap().len());
 }
 
+#[tokio::test]
+async fn save_with_invalid_format() {
+    let mock_repo = MockApplicationRepositoryTrait::new();
+
+    let request = ApplicationReq {
+        name: Some("teste".to_string()),
+        path: Some("/teste/orders".to_string()),
+        url_destination: Some("anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: Some(0),
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+    };
+
+    let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.save(request).await;
+    assert!(response.is_err());
+
+    let field_errors = response.unwrap_err().field_errors.unwrap();
+    assert_eq!(3, field_errors.len());
+    assert_eq!(ERR_INVALID_PATH.0, field_errors[0].code);
+    assert_eq!(ERR_INVALID_URL.0, field_errors[1].code);
+    assert_eq!(ERR_INVALID_VALUE.0, field_errors[2].code);
+}
+
+#[tokio::test]
+async fn save_with_invalid_upstream_policy() {
+    let mock_repo = MockApplicationRepositoryTrait::new();
+
+    let request = ApplicationReq {
+        name: Some("teste".to_string()),
+        path: Some("/teste".to_string()),
+        url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: Some(serde_json::json!({
+            "connectTimeoutMs": 0,
+            "retry": {"maxAttempts": 20, "statusCodes": [503, 700], "methods": ["GET"]},
+            "circuitBreaker": {"failureRate": 120, "openMs": 0, "fallback": {"status": 42}}
+        })),
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+    };
+
+    let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
+
+    let field_errors = service
+        .save(request)
+        .await
+        .unwrap_err()
+        .field_errors
+        .unwrap();
+    let fields: Vec<&str> = field_errors
+        .iter()
+        .map(|field_error| field_error.field.as_str())
+        .collect();
+    assert_eq!(
+        vec![
+            "application.upstreamPolicy.connectTimeoutMs",
+            "application.upstreamPolicy.retry.maxAttempts",
+            "application.upstreamPolicy.retry.statusCodes",
+            "application.upstreamPolicy.circuitBreaker.failureRate",
+            "application.upstreamPolicy.circuitBreaker.openMs",
+            "application.upstreamPolicy.circuitBreaker.fallback.status",
+        ],
+        fields
+    );
+
+    // unknown fields aren't silently dropped.
+    let request = ApplicationReq {
+        name: Some("teste".to_string()),
+        path: Some("/teste".to_string()),
+        url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: Some(serde_json::json!({"timeoutMs": 100})),
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+    };
+
+    let field_errors = service
+        .save(request)
+        .await
+        .unwrap_err()
+        .field_errors
+        .unwrap();
+    assert_eq!(1, field_errors.len());
+    assert_eq!("application.upstreamPolicy", field_errors[0].field);
+}
+
+#[tokio::test]
+async fn save_with_path_already_exists() {
+    let mut mock_repo = MockApplicationRepositoryTrait::new();
+    mock_repo.expect_next_id().returning(|| Ok(1));
+    mock_repo
+        .expect_save()
+        .returning(|_, _| Err(ApiError::new(APP_ERR_PATH_ALREADY_EXISTS)));
+
+    let request = ApplicationReq {
+        name: Some("teste".to_string()),
+        path: Some("/teste".to_string()),
+        url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+    };
+
+    let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.save(request).await;
+    assert!(response.is_err());
+    assert_eq!(APP_ERR_PATH_ALREADY_EXISTS.0, response.unwrap_err().code);
+}
+
 #[tokio::test]
 async fn save_with_repository_error() {
     let mut mock_repo = MockApplicationRepositoryTrait::new();
+    mock_repo.expect_next_id().returning(|| Ok(1));
     mock_repo
         .expect_save()
-        .returning(|_| Err(ApiError::new(APP_ERR_INSERTING)));
+        .returning(|_, _| Err(ApiError::new(APP_ERR_INSERTING)));
 
     let request = ApplicationReq {
-        name: Some(StringMinSize3::from_str("teste").unwrap()),
-        path: Some(StringMinSize3::from_str("/teste").unwrap()),
-        url_destination: Some(StringMinSize3::from_str("http://anothergw.com").unwrap()),
+        name: Some("teste".to_string()),
+        path: Some("/teste".to_string()),
+        url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
     };
 
     let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
@@ -192,8 +394,14 @@ async fn update() {
             name: String::from("Teste"),
             path: String::from("/teste"),
             url_destination: String::from("http://anothergtw.com"),
-            created_dttm: Utc::now(),
-            update_dttm: Utc::now(),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
         }))
     });
 
@@ -203,15 +411,27 @@ async fn update() {
             name: String::from("Teste"),
             path: String::from("/teste"),
             url_destination: String::from("http://anothergtw.com"),
-            created_dttm: Utc::now(),
-            update_dttm: Utc::now(),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
         })
     });
 
     let request = ApplicationReq {
-        name: Some(StringMinSize3::from_str("teste").unwrap()),
-        path: Some(StringMinSize3::from_str("/teste").unwrap()),
-        url_destination: Some(StringMinSize3::from_str("http://anothergw.com").unwrap()),
+        name: Some("teste".to_string()),
+        path: Some("/teste".to_string()),
+        url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
     };
 
     let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
@@ -222,16 +442,112 @@ async fn update() {
 }
 
 #[tokio::test]
-async fn update_application_not_found() {
+async fn update_path_and_url_destination() {
+    let mut mock_repo = MockApplicationRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| {
+        Ok(Some(Application {
+            id: 1,
+            name: String::from("Teste"),
+            path: String::from("/teste"),
+            url_destination: String::from("http://anothergtw.com"),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }))
+    });
+
+    mock_repo
+        .expect_update()
+        .withf(|application| {
+            application.name == "Teste"
+                && application.path == "/orders"
+                && application.url_destination == "http://orders.anothergtw.com"
+        })
+        .returning(Ok);
+
+    let request = ApplicationReq {
+        name: None,
+        path: Some("/orders".to_string()),
+        url_destination: Some("http://orders.anothergtw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+    };
+
+    let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.update(1, request).await;
+    assert!(response.is_ok());
+    assert_eq!("/orders", response.unwrap().path);
+}
+
+#[tokio::test]
+async fn update_preserve_host() {
     let mut mock_repo = MockApplicationRepositoryTrait::new();
     mock_repo.expect_find_by_id().returning(|_| {
-        Ok(None)
+        Ok(Some(Application {
+            id: 1,
+            name: String::from("Teste"),
+            path: String::from("/teste"),
+            url_destination: String::from("http://anothergtw.com"),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }))
     });
 
+    mock_repo
+        .expect_update()
+        .withf(|application| application.path == "/teste" && application.preserve_host)
+        .returning(Ok);
+
     let request = ApplicationReq {
-        name: Some(StringMinSize3::from_str("teste").unwrap()),
-        path: Some(StringMinSize3::from_str("/teste").unwrap()),
-        url_destination: Some(StringMinSize3::from_str("http://anothergw.com").unwrap()),
+        name: None,
+        path: None,
+        url_destination: None,
+        preserve_host: Some(true),
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+    };
+
+    let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.update(1, request).await;
+    assert!(response.is_ok());
+    assert!(response.unwrap().preserve_host);
+}
+
+#[tokio::test]
+async fn update_application_not_found() {
+    let mut mock_repo = MockApplicationRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| Ok(None));
+
+    let request = ApplicationReq {
+        name: Some("teste".to_string()),
+        path: Some("/teste".to_string()),
+        url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
     };
 
     let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
@@ -244,12 +560,19 @@ async fn update_application_not_found() {
 #[tokio::test]
 async fn update_with_field_errors() {
     let request = ApplicationReq {
-        name: Some(StringMinSize3::from_str("te").unwrap()),
-        path: Some(StringMinSize3::from_str("/teste").unwrap()),
-        url_destination: Some(StringMinSize3::from_str("http://anothergw.com").unwrap()),
+        name: Some("te".to_string()),
+        path: Some("/teste".to_string()),
+        url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
     };
 
-    let service = ApplicationService::new_with_repo(Arc::new(MockApplicationRepositoryTrait::new()));
+    let service =
+        ApplicationService::new_with_repo(Arc::new(MockApplicationRepositoryTrait::new()));
 
     let response = service.update(1, request).await;
     assert_eq!(true, response.is_err());
@@ -269,8 +592,14 @@ async fn update_repository_error() {
             name: String::from("Teste"),
             path: String::from("/teste"),
             url_destination: String::from("http://anothergtw.com"),
-            created_dttm: Utc::now(),
-            update_dttm: Utc::now(),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
         }))
     });
 
@@ -279,9 +608,15 @@ async fn update_repository_error() {
         .returning(|_| Err(ApiError::new(APP_ERR_UPDATING)));
 
     let request = ApplicationReq {
-        name: Some(StringMinSize3::from_str("teste").unwrap()),
-        path: Some(StringMinSize3::from_str("/teste").unwrap()),
-        url_destination: Some(StringMinSize3::from_str("http://anothergw.com").unwrap()),
+        name: Some("teste".to_string()),
+        path: Some("/teste".to_string()),
+        url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
     };
 
     let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
@@ -300,8 +635,14 @@ async fn delete() {
             name: String::from("Teste"),
             path: String::from("/teste"),
             url_destination: String::from("http://anothergtw.com"),
-            created_dttm: Utc::now(),
-            update_dttm: Utc::now(),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
         }))
     });
 
@@ -315,9 +656,7 @@ async fn delete() {
 #[tokio::test]
 async fn delete_application_not_found() {
     let mut mock_repo = MockApplicationRepositoryTrait::new();
-    mock_repo.expect_find_by_id().returning(|_| {
-        Ok(None)
-    });
+    mock_repo.expect_find_by_id().returning(|_| Ok(None));
 
     let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
     let response = service.delete(1).await;
@@ -334,8 +673,14 @@ async fn delete_with_repository_error() {
             name: String::from("Teste"),
             path: String::from("/teste"),
             url_destination: String::from("http://anothergtw.com"),
-            created_dttm: Utc::now(),
-            update_dttm: Utc::now(),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
         }))
     });
 
@@ -347,4 +692,292 @@ async fn delete_with_repository_error() {
     let response = service.delete(1).await;
     assert_eq!(true, response.is_err());
     assert_eq!(APP_ERR_DELETE.0, response.unwrap_err().code);
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn save_with_invalid_rate_limit() {
+    let mock_repo = MockApplicationRepositoryTrait::new();
+
+    let request = ApplicationReq {
+        name: Some("teste".to_string()),
+        path: Some("/teste".to_string()),
+        url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: Some(serde_json::json!({
+            "limits": [
+                {"requests": 100, "period": "minute", "by": "client"},
+                {"requests": 0, "period": "day", "algorithm": "leaky_bucket", "burst": 0, "by": "user"}
+            ]
+        })),
+        auth: None,
+    };
+
+    let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
+
+    let field_errors = service
+        .save(request)
+        .await
+        .unwrap_err()
+        .field_errors
+        .unwrap();
+    let fields: Vec<&str> = field_errors
+        .iter()
+        .map(|field_error| field_error.field.as_str())
+        .collect();
+    assert_eq!(
+        vec![
+            "application.rateLimit.limits[1].requests",
+            "application.rateLimit.limits[1].period",
+            "application.rateLimit.limits[1].algorithm",
+            "application.rateLimit.limits[1].burst",
+            "application.rateLimit.limits[1].by",
+        ],
+        fields
+    );
+}
+
+#[tokio::test]
+async fn save_with_invalid_auth() {
+    let mock_repo = MockApplicationRepositoryTrait::new();
+
+    let request = ApplicationReq {
+        name: Some("teste".to_string()),
+        path: Some("/teste".to_string()),
+        url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: Some(serde_json::json!({"apiKey": {"header": "x api key", "query": " "}})),
+    };
+
+    let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
+
+    let field_errors = service
+        .save(request)
+        .await
+        .unwrap_err()
+        .field_errors
+        .unwrap();
+    let fields: Vec<&str> = field_errors
+        .iter()
+        .map(|field_error| field_error.field.as_str())
+        .collect();
+    assert_eq!(
+        vec![
+            "application.auth.apiKey.header",
+            "application.auth.apiKey.query"
+        ],
+        fields
+    );
+}
+
+#[tokio::test]
+async fn save_with_invalid_jwt_auth() {
+    let mock_repo = MockApplicationRepositoryTrait::new();
+
+    let request = ApplicationReq {
+        name: Some("teste".to_string()),
+        path: Some("/teste".to_string()),
+        url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: Some(serde_json::json!({"jwt": {
+            "algorithms": ["RS256", "none"],
+            "jwksUrl": "ftp://keys.anothergw.com",
+            "secret": "secret",
+            "forwardClaims": {"sub": "x user"}
+        }})),
+    };
+
+    let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
+
+    let field_errors = service
+        .save(request)
+        .await
+        .unwrap_err()
+        .field_errors
+        .unwrap();
+    let fields: Vec<&str> = field_errors
+        .iter()
+        .map(|field_error| field_error.field.as_str())
+        .collect();
+    assert_eq!(
+        vec![
+            "application.auth.jwt.secret",
+            "application.auth.jwt.jwksUrl",
+            "application.auth.jwt.algorithms",
+            "application.auth.jwt.forwardClaims"
+        ],
+        fields
+    );
+}
+
+fn cipher() -> SecretCipher {
+    SecretCipher::from_base64("MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=").unwrap()
+}
+
+fn application_with_auth(id: i64, auth: Option<Value>) -> Application {
+    Application {
+        id,
+        name: String::from("Teste"),
+        path: String::from("/teste"),
+        url_destination: String::from("http://anothergtw.com"),
+        preserve_host: false,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+fn jwt_auth() -> Value {
+    json!({"jwt": {"algorithms": [JWT_ALGORITHM_HS256], "secret": "s3cr3t"}})
+}
+
+#[tokio::test]
+async fn save_with_jwt_secret() {
+    let mut mock_repo = MockApplicationRepositoryTrait::new();
+    mock_repo.expect_next_id().returning(|| Ok(7));
+    mock_repo
+        .expect_save()
+        .withf(|id, entity| {
+            let sealed = entity.auth.as_ref().unwrap()["jwt"]["secret"]
+                .as_str()
+                .unwrap();
+            let context = format!("{}.jwt.secret", Application::auth_sealing_context(*id));
+            let other_row = format!("{}.jwt.secret", Application::auth_sealing_context(1));
+            cipher().open(sealed, &context).unwrap() == "s3cr3t"
+                && cipher().open(sealed, &other_row).is_err()
+        })
+        .returning(|id, entity| Ok(application_with_auth(id, entity.auth)));
+
+    let service = ApplicationService::new_with_cipher(Arc::new(mock_repo), Some(cipher()));
+
+    let request = ApplicationReq {
+        name: Some("teste".to_string()),
+        path: Some("/teste".to_string()),
+        url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: Some(jwt_auth()),
+    };
+    let application = serde_json::to_value(service.save(request).await.unwrap()).unwrap();
+    assert_eq!(
+        json!({"jwt": {"algorithms": [JWT_ALGORITHM_HS256]}}),
+        application["auth"]
+    );
+}
+
+#[tokio::test]
+async fn save_with_jwt_secret_without_key() {
+    let mut mock_repo = MockApplicationRepositoryTrait::new();
+    mock_repo.expect_next_id().returning(|| Ok(1));
+    let service = ApplicationService::new_with_cipher(Arc::new(mock_repo), None);
+
+    let request = ApplicationReq {
+        name: Some("teste".to_string()),
+        path: Some("/teste".to_string()),
+        url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: Some(jwt_auth()),
+    };
+    let api_error = service.save(request).await.unwrap_err();
+    assert_eq!(500, api_error.status_code);
+    assert_eq!(APP_ERR_ENCRYPTING_AUTH.0, api_error.code);
+}
+
+#[tokio::test]
+async fn update_with_jwt_secret() {
+    let mut mock_repo = MockApplicationRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|id| Ok(Some(application_with_auth(id, None))));
+    mock_repo
+        .expect_update()
+        .withf(|application| {
+            let sealed = application.auth.as_ref().unwrap()["jwt"]["secret"]
+                .as_str()
+                .unwrap();
+            let context = format!("{}.jwt.secret", Application::auth_sealing_context(3));
+            cipher().open(sealed, &context).unwrap() == "s3cr3t"
+        })
+        .returning(Ok);
+
+    let service = ApplicationService::new_with_cipher(Arc::new(mock_repo), Some(cipher()));
+
+    let request = ApplicationReq {
+        name: None,
+        path: None,
+        url_destination: None,
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: Some(jwt_auth()),
+    };
+    assert!(service.update(3, request).await.is_ok());
+}
+
+#[tokio::test]
+async fn find_by_id_without_auth_secrets() {
+    let mut mock_repo = MockApplicationRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|id| {
+        let mut auth = jwt_auth();
+        AuthPolicy::seal_secrets(&mut auth, &Application::auth_sealing_context(id), &cipher())
+            .unwrap();
+        Ok(Some(application_with_auth(id, Some(auth))))
+    });
+
+    let service = ApplicationService::new_with_cipher(Arc::new(mock_repo), Some(cipher()));
+
+    // neither the secret nor its sealed value are shown.
+    let application = serde_json::to_value(service.find_by_id(1).await.unwrap()).unwrap();
+    assert_eq!(
+        json!({"jwt": {"algorithms": [JWT_ALGORITHM_HS256]}}),
+        application["auth"]
+    );
+}
+
+#[tokio::test]
+async fn find_by_id_without_introspection_secret() {
+    let mut mock_repo = MockApplicationRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|id| {
+        let mut auth = json!({"introspection": {
+            "url": "https://auth.anothergw.com/introspect",
+            "clientId": "gateway",
+            "clientSecret": "s3cr3t"
+        }});
+        AuthPolicy::seal_secrets(&mut auth, &Application::auth_sealing_context(id), &cipher())
+            .unwrap();
+        Ok(Some(application_with_auth(id, Some(auth))))
+    });
+
+    let service = ApplicationService::new_with_cipher(Arc::new(mock_repo), Some(cipher()));
+
+    let application = serde_json::to_value(service.find_by_id(1).await.unwrap()).unwrap();
+    assert_eq!(
+        json!({"introspection": {
+            "url": "https://auth.anothergw.com/introspect",
+            "clientId": "gateway"
+        }}),
+        application["auth"]
+    );
+}
@@ -0,0 +1,159 @@
+#[cfg(test)]
+#[path = "certificate_service_test.rs"]
+mod certificate_service_test;
+
+use std::sync::Arc;
+
+use axum::async_trait;
+use hyper::StatusCode;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{ApiError, CRT_ERR_ENCRYPTING_PRIVATE_KEY, CRT_ERR_NOT_FOUND},
+    model::{Certificate, CertificateDetails, CertificateReq, Pagination, PaginationResponse},
+    repository::{CertificateRepository, CertificateRepositoryTrait},
+    secret::SecretCipher,
+};
+
+#[async_trait]
+pub trait CertificateServiceTrait: std::fmt::Debug {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<Certificate>, ApiError>;
+
+    async fn find_by_id(&self, id: i64) -> Result<Certificate, ApiError>;
+
+    async fn save(&self, entity: CertificateReq) -> Result<Certificate, ApiError>;
+
+    async fn update(&self, id: i64, entity: CertificateReq) -> Result<Certificate, ApiError>;
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError>;
+}
+
+#[derive(Debug)]
+pub struct CertificateService {
+    certificate_repository: Arc<dyn CertificateRepositoryTrait + Send + Sync>,
+    cipher: Option<SecretCipher>,
+}
+
+#[async_trait]
+impl CertificateServiceTrait for CertificateService {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<Certificate>, ApiError> {
+        pagination.validate()?;
+
+        let response = self.certificate_repository.find_all(pagination).await?;
+        Ok(response)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Certificate, ApiError> {
+        let response = self.certificate_repository.find_by_id(id).await?;
+
+        if response.is_none() {
+            return Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                CRT_ERR_NOT_FOUND,
+            ));
+        }
+        Ok(response.unwrap())
+    }
+
+    async fn save(&self, entity: CertificateReq) -> Result<Certificate, ApiError> {
+        entity.validate()?;
+
+        let details = CertificateDetails::parse(entity.certificate.as_deref().unwrap()).unwrap();
+        let id = self.certificate_repository.next_id().await?;
+        let private_key = self.seal_private_key(id, entity.private_key.as_deref().unwrap())?;
+
+        let certificate = self
+            .certificate_repository
+            .save(id, entity, details, private_key)
+            .await?;
+        Ok(certificate)
+    }
+
+    async fn update(&self, id: i64, entity: CertificateReq) -> Result<Certificate, ApiError> {
+        if let Some(mut certificate) = self.certificate_repository.find_by_id(id).await? {
+            entity.validate_updating()?;
+
+            if let Some(name) = entity.name {
+                certificate.name = name;
+            }
+
+            if let Some(is_default) = entity.is_default {
+                certificate.is_default = is_default;
+            }
+
+            // a renewal replaces the chain and the key together, the details come from the new one.
+            if let (Some(chain), Some(private_key)) = (entity.certificate, entity.private_key) {
+                let details = CertificateDetails::parse(&chain).unwrap();
+                certificate.private_key = self.seal_private_key(certificate.id, &private_key)?;
+                certificate.certificate = chain;
+                certificate.hostnames = details.hostnames;
+                certificate.not_after = details.not_after;
+            }
+
+            certificate = self.certificate_repository.update(certificate).await?;
+            Ok(certificate)
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                CRT_ERR_NOT_FOUND,
+            ))
+        }
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError> {
+        if (self.certificate_repository.find_by_id(id).await?).is_some() {
+            self.certificate_repository.delete(id).await?;
+            Ok(())
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                CRT_ERR_NOT_FOUND,
+            ))
+        }
+    }
+}
+
+impl CertificateService {
+    pub fn new(pg_pool: Arc<PgPool>) -> Self {
+        CertificateService {
+            certificate_repository: Arc::new(CertificateRepository { pg_pool }),
+            cipher: SecretCipher::config(),
+        }
+    }
+
+    pub fn new_with_repo(repository: Arc<dyn CertificateRepositoryTrait + Send + Sync>) -> Self {
+        CertificateService::new_with_cipher(repository, SecretCipher::config())
+    }
+
+    pub fn new_with_cipher(
+        repository: Arc<dyn CertificateRepositoryTrait + Send + Sync>,
+        cipher: Option<SecretCipher>,
+    ) -> Self {
+        CertificateService {
+            certificate_repository: repository,
+            cipher,
+        }
+    }
+
+    fn seal_private_key(&self, id: i64, private_key: &str) -> Result<String, ApiError> {
+        let encrypting_error = || {
+            ApiError::new_with_status(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                CRT_ERR_ENCRYPTING_PRIVATE_KEY,
+            )
+        };
+        let cipher = self.cipher.as_ref().ok_or_else(|| {
+            tracing::error!("CREDENTIALS_ENCRYPTION_KEY is required to store private keys");
+            encrypting_error()
+        })?;
+        cipher
+            .seal(private_key, &Certificate::sealing_context(id))
+            .map_err(|_| encrypting_error())
+    }
+}
@@ -0,0 +1,187 @@
+#[cfg(test)]
+#[path = "consumer_api_key_service_test.rs"]
+mod consumer_api_key_service_test;
+
+use std::sync::Arc;
+
+use axum::async_trait;
+use chrono::{Duration, Utc};
+use hyper::StatusCode;
+use rand::{distributions::Alphanumeric, Rng};
+use sqlx::PgPool;
+
+use crate::{
+    exception::{ApiError, CAK_ERR_NOT_FOUND},
+    model::{
+        api_key_prefix, hash_api_key, ConsumerApiKey, ConsumerApiKeyCreated, ConsumerApiKeyReq,
+        ConsumerApiKeyRotationReq, Pagination, PaginationResponse,
+    },
+    repository::{ConsumerApiKeyRepository, ConsumerApiKeyRepositoryTrait},
+};
+
+// "agk_" and 40 alphanumeric characters, about 238 random bits.
+const API_KEY_PREFIX: &str = "agk_";
+const API_KEY_RANDOM_LENGTH: usize = 40;
+
+#[async_trait]
+pub trait ConsumerApiKeyServiceTrait: std::fmt::Debug {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ConsumerApiKey>, ApiError>;
+
+    async fn find_by_id(&self, id: i64) -> Result<ConsumerApiKey, ApiError>;
+
+    async fn find_by_consumer(&self, id_consumer: i64) -> Result<Vec<ConsumerApiKey>, ApiError>;
+
+    async fn save(&self, entity: ConsumerApiKeyReq) -> Result<ConsumerApiKeyCreated, ApiError>;
+
+    async fn update(&self, id: i64, entity: ConsumerApiKeyReq) -> Result<ConsumerApiKey, ApiError>;
+
+    async fn rotate(
+        &self,
+        id: i64,
+        entity: ConsumerApiKeyRotationReq,
+    ) -> Result<ConsumerApiKeyCreated, ApiError>;
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError>;
+}
+
+#[derive(Debug)]
+pub struct ConsumerApiKeyService {
+    api_key_repository: Arc<dyn ConsumerApiKeyRepositoryTrait + Send + Sync>,
+}
+
+#[async_trait]
+impl ConsumerApiKeyServiceTrait for ConsumerApiKeyService {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ConsumerApiKey>, ApiError> {
+        pagination.validate()?;
+
+        let response = self.api_key_repository.find_all(pagination).await?;
+        Ok(response)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<ConsumerApiKey, ApiError> {
+        let response = self.api_key_repository.find_by_id(id).await?;
+
+        if response.is_none() {
+            return Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                CAK_ERR_NOT_FOUND,
+            ));
+        }
+        Ok(response.unwrap())
+    }
+
+    async fn find_by_consumer(&self, id_consumer: i64) -> Result<Vec<ConsumerApiKey>, ApiError> {
+        let response = self
+            .api_key_repository
+            .find_by_consumer(id_consumer)
+            .await?;
+        Ok(response)
+    }
+
+    async fn save(&self, entity: ConsumerApiKeyReq) -> Result<ConsumerApiKeyCreated, ApiError> {
+        entity.validate()?;
+
+        let key = generate_api_key();
+        let api_key = self
+            .api_key_repository
+            .save(entity, hash_api_key(&key), api_key_prefix(&key))
+            .await?;
+        Ok(ConsumerApiKeyCreated { api_key, key })
+    }
+
+    // only the expiration of a key can change, id_consumer is ignored.
+    async fn update(&self, id: i64, entity: ConsumerApiKeyReq) -> Result<ConsumerApiKey, ApiError> {
+        entity.validate_updating()?;
+
+        if let Some(mut api_key) = self.api_key_repository.find_by_id(id).await? {
+            if entity.expires_at.is_some() {
+                api_key.expires_at = entity.expires_at;
+            }
+
+            api_key = self.api_key_repository.update(api_key).await?;
+            Ok(api_key)
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                CAK_ERR_NOT_FOUND,
+            ))
+        }
+    }
+
+    // the current key expires at the end of the grace period, right away without one. a key
+    // that would expire earlier keeps its expiration.
+    async fn rotate(
+        &self,
+        id: i64,
+        entity: ConsumerApiKeyRotationReq,
+    ) -> Result<ConsumerApiKeyCreated, ApiError> {
+        entity.validate()?;
+
+        if let Some(mut current) = self.api_key_repository.find_by_id(id).await? {
+            let grace_end =
+                Utc::now() + Duration::seconds(entity.grace_period_seconds.unwrap_or_default());
+            current.expires_at = Some(
+                current
+                    .expires_at
+                    .map_or(grace_end, |expires_at| expires_at.min(grace_end)),
+            );
+
+            let key = generate_api_key();
+            let request = ConsumerApiKeyReq {
+                id_consumer: Some(current.id_consumer),
+                expires_at: entity.expires_at,
+            };
+            let api_key = self
+                .api_key_repository
+                .rotate(current, request, hash_api_key(&key), api_key_prefix(&key))
+                .await?;
+            Ok(ConsumerApiKeyCreated { api_key, key })
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                CAK_ERR_NOT_FOUND,
+            ))
+        }
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError> {
+        if (self.api_key_repository.find_by_id(id).await?).is_some() {
+            self.api_key_repository.delete(id).await?;
+            Ok(())
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                CAK_ERR_NOT_FOUND,
+            ))
+        }
+    }
+}
+
+impl ConsumerApiKeyService {
+    pub fn new(pg_pool: Arc<PgPool>) -> Self {
+        ConsumerApiKeyService {
+            api_key_repository: Arc::new(ConsumerApiKeyRepository { pg_pool }),
+        }
+    }
+
+    pub fn new_with_repo(repository: Arc<dyn ConsumerApiKeyRepositoryTrait + Send + Sync>) -> Self {
+        ConsumerApiKeyService {
+            api_key_repository: repository,
+        }
+    }
+}
+
+fn generate_api_key() -> String {
+    let random: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(API_KEY_RANDOM_LENGTH)
+        .map(char::from)
+        .collect();
+    format!("{}{}", API_KEY_PREFIX, random)
+}
@@ -0,0 +1,184 @@
+use chrono::{DateTime, Utc};
+
+use crate::{
+    exception::{ERR_INVALID_VALUE, ERR_REQUIRED_FIELD},
+    repository::MockConsumerApiKeyRepositoryTrait,
+};
+
+use super::*;
+
+fn api_key(expires_at: Option<DateTime<Utc>>) -> ConsumerApiKey {
+    ConsumerApiKey {
+        id: 1,
+        id_consumer: 1,
+        key_hash: hash_api_key("agk_current"),
+        key_prefix: String::from("agk_curr"),
+        expires_at,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn save() {
+    let mut mock_repo = MockConsumerApiKeyRepositoryTrait::new();
+    mock_repo
+        .expect_save()
+        .returning(|entity, key_hash, key_prefix| {
+            Ok(ConsumerApiKey {
+                id: 2,
+                id_consumer: entity.id_consumer.unwrap(),
+                key_hash,
+                key_prefix,
+                expires_at: entity.expires_at,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            })
+        });
+
+    let request = ConsumerApiKeyReq {
+        id_consumer: Some(1),
+        expires_at: None,
+    };
+
+    let service = ConsumerApiKeyService::new_with_repo(Arc::new(mock_repo));
+
+    // only the hash is stored, the key is in the answer.
+    let created = service.save(request).await.unwrap();
+    assert!(created.key.starts_with(API_KEY_PREFIX));
+    assert_eq!(
+        API_KEY_PREFIX.len() + API_KEY_RANDOM_LENGTH,
+        created.key.len()
+    );
+    assert_eq!(hash_api_key(&created.key), created.api_key.key_hash);
+    assert_eq!(&created.key[..8], created.api_key.key_prefix);
+
+    let json = serde_json::to_value(&created).unwrap();
+    assert_eq!(created.key, json["key"]);
+    assert_eq!("agk_", &json["keyPrefix"].as_str().unwrap()[..4]);
+    assert!(json.get("keyHash").is_none());
+}
+
+#[tokio::test]
+async fn save_with_invalid_fields() {
+    let request = ConsumerApiKeyReq {
+        id_consumer: None,
+        expires_at: Some(Utc::now() - Duration::minutes(1)),
+    };
+
+    let service =
+        ConsumerApiKeyService::new_with_repo(Arc::new(MockConsumerApiKeyRepositoryTrait::new()));
+
+    let field_errors = service
+        .save(request)
+        .await
+        .unwrap_err()
+        .field_errors
+        .unwrap();
+    assert_eq!(2, field_errors.len());
+    assert_eq!(ERR_REQUIRED_FIELD.0, field_errors[0].code);
+    assert_eq!(ERR_INVALID_VALUE.0, field_errors[1].code);
+    assert_eq!("apiKey.expiresAt", field_errors[1].field);
+}
+
+#[tokio::test]
+async fn rotate() {
+    let mut mock_repo = MockConsumerApiKeyRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(api_key(None))));
+    mock_repo
+        .expect_rotate()
+        .withf(|current, entity, key_hash, _| {
+            let grace_end = Utc::now() + Duration::hours(1);
+            current.id == 1
+                && current
+                    .expires_at
+                    .is_some_and(|expires_at| (grace_end - expires_at).num_seconds().abs() < 5)
+                && entity.id_consumer == Some(1)
+                && *key_hash != hash_api_key("agk_current")
+        })
+        .returning(|_, entity, key_hash, key_prefix| {
+            Ok(ConsumerApiKey {
+                id: 2,
+                id_consumer: entity.id_consumer.unwrap(),
+                key_hash,
+                key_prefix,
+                expires_at: entity.expires_at,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            })
+        });
+
+    let request = ConsumerApiKeyRotationReq {
+        expires_at: None,
+        grace_period_seconds: Some(3600),
+    };
+
+    let service = ConsumerApiKeyService::new_with_repo(Arc::new(mock_repo));
+
+    let created = service.rotate(1, request).await.unwrap();
+    assert_eq!(2, created.api_key.id);
+    assert_eq!(hash_api_key(&created.key), created.api_key.key_hash);
+}
+
+// a key about to expire isn't extended by the grace period.
+#[tokio::test]
+async fn rotate_keeps_an_earlier_expiration() {
+    let expires_at = Utc::now() + Duration::minutes(5);
+
+    let mut mock_repo = MockConsumerApiKeyRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(move |_| Ok(Some(api_key(Some(expires_at)))));
+    mock_repo
+        .expect_rotate()
+        .withf(move |current, _, _, _| current.expires_at == Some(expires_at))
+        .returning(|current, _, _, _| Ok(current));
+
+    let request = ConsumerApiKeyRotationReq {
+        expires_at: None,
+        grace_period_seconds: Some(3600),
+    };
+
+    let service = ConsumerApiKeyService::new_with_repo(Arc::new(mock_repo));
+    assert!(service.rotate(1, request).await.is_ok());
+}
+
+#[tokio::test]
+async fn rotate_not_found() {
+    let mut mock_repo = MockConsumerApiKeyRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| Ok(None));
+
+    let service = ConsumerApiKeyService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service
+        .rotate(1, ConsumerApiKeyRotationReq::default())
+        .await;
+    assert!(response.is_err());
+    assert_eq!(CAK_ERR_NOT_FOUND.0, response.unwrap_err().code);
+}
+
+#[tokio::test]
+async fn update() {
+    let expires_at = Utc::now() + Duration::days(30);
+
+    let mut mock_repo = MockConsumerApiKeyRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(api_key(None))));
+    mock_repo
+        .expect_update()
+        .withf(move |api_key| api_key.id_consumer == 1 && api_key.expires_at == Some(expires_at))
+        .returning(Ok);
+
+    let request = ConsumerApiKeyReq {
+        id_consumer: Some(2),
+        expires_at: Some(expires_at),
+    };
+
+    let service = ConsumerApiKeyService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.update(1, request).await;
+    assert!(response.is_ok());
+}
@@ -0,0 +1,121 @@
+#[cfg(test)]
+#[path = "consumer_service_test.rs"]
+mod consumer_service_test;
+
+use std::sync::Arc;
+
+use axum::async_trait;
+use hyper::StatusCode;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{ApiError, CSM_ERR_NOT_FOUND},
+    model::{Consumer, ConsumerReq, Pagination, PaginationResponse},
+    repository::{ConsumerRepository, ConsumerRepositoryTrait},
+};
+
+#[async_trait]
+pub trait ConsumerServiceTrait: std::fmt::Debug {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<Consumer>, ApiError>;
+
+    async fn find_by_id(&self, id: i64) -> Result<Consumer, ApiError>;
+
+    async fn save(&self, entity: ConsumerReq) -> Result<Consumer, ApiError>;
+
+    async fn update(&self, id: i64, entity: ConsumerReq) -> Result<Consumer, ApiError>;
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError>;
+}
+
+#[derive(Debug)]
+pub struct ConsumerService {
+    consumer_repository: Arc<dyn ConsumerRepositoryTrait + Send + Sync>,
+}
+
+#[async_trait]
+impl ConsumerServiceTrait for ConsumerService {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<Consumer>, ApiError> {
+        pagination.validate()?;
+
+        let response = self.consumer_repository.find_all(pagination).await?;
+        Ok(response)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Consumer, ApiError> {
+        let response = self.consumer_repository.find_by_id(id).await?;
+
+        if response.is_none() {
+            return Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                CSM_ERR_NOT_FOUND,
+            ));
+        }
+        Ok(response.unwrap())
+    }
+
+    async fn save(&self, entity: ConsumerReq) -> Result<Consumer, ApiError> {
+        entity.validate()?;
+
+        let consumer = self.consumer_repository.save(entity).await?;
+        Ok(consumer)
+    }
+
+    async fn update(&self, id: i64, entity: ConsumerReq) -> Result<Consumer, ApiError> {
+        entity.validate_updating()?;
+
+        if let Some(mut consumer) = self.consumer_repository.find_by_id(id).await? {
+            if let Some(name) = entity.name {
+                consumer.name = name;
+            }
+
+            if entity.rate_limit.is_some() {
+                consumer.rate_limit = entity.rate_limit;
+            }
+
+            if let Some(certificate_subject) = entity.certificate_subject {
+                consumer.certificate_subject =
+                    Some(certificate_subject).filter(|subject| !subject.is_empty());
+            }
+
+            consumer = self.consumer_repository.update(consumer).await?;
+            Ok(consumer)
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                CSM_ERR_NOT_FOUND,
+            ))
+        }
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError> {
+        if (self.consumer_repository.find_by_id(id).await?).is_some() {
+            self.consumer_repository.delete(id).await?;
+            Ok(())
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                CSM_ERR_NOT_FOUND,
+            ))
+        }
+    }
+}
+
+impl ConsumerService {
+    pub fn new(pg_pool: Arc<PgPool>) -> Self {
+        ConsumerService {
+            consumer_repository: Arc::new(ConsumerRepository { pg_pool }),
+        }
+    }
+
+    pub fn new_with_repo(repository: Arc<dyn ConsumerRepositoryTrait + Send + Sync>) -> Self {
+        ConsumerService {
+            consumer_repository: repository,
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use chrono::Utc;
+
+use crate::{
+    exception::{ERR_INVALID_VALUE, ERR_MIN_SIZE, ERR_REQUIRED_FIELD},
+    repository::MockConsumerRepositoryTrait,
+};
+
+use super::*;
+
+fn consumer() -> Consumer {
+    Consumer {
+        id: 1,
+        name: String::from("mobile-app"),
+        rate_limit: None,
+        certificate_subject: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn find_by_id() {
+    let mut mock_repo = MockConsumerRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(consumer())));
+
+    let service = ConsumerService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.find_by_id(1).await;
+    assert!(response.is_ok());
+    assert_eq!("mobile-app", response.unwrap().name);
+}
+
+#[tokio::test]
+async fn find_by_id_not_found() {
+    let mut mock_repo = MockConsumerRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| Ok(None));
+
+    let service = ConsumerService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.find_by_id(1).await;
+    assert!(response.is_err());
+    assert_eq!(CSM_ERR_NOT_FOUND.0, response.unwrap_err().code);
+}
+
+#[tokio::test]
+async fn save() {
+    let mut mock_repo = MockConsumerRepositoryTrait::new();
+    mock_repo.expect_save().returning(|_| Ok(consumer()));
+
+    let request = ConsumerReq {
+        name: Some("mobile-app".to_string()),
+        rate_limit: Some(serde_json::json!({
+            "limits": [{"requests": 100, "period": "minute", "by": "consumer"}]
+        })),
+        certificate_subject: None,
+    };
+
+    let service = ConsumerService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.save(request).await;
+    assert!(response.is_ok());
+}
+
+#[tokio::test]
+async fn save_with_invalid_fields() {
+    let service = ConsumerService::new_with_repo(Arc::new(MockConsumerRepositoryTrait::new()));
+
+    let request = ConsumerReq {
+        name: None,
+        rate_limit: None,
+        certificate_subject: None,
+    };
+    let response = service.save(request).await;
+    let field_errors = response.unwrap_err().field_errors.unwrap();
+    assert_eq!(1, field_errors.len());
+    assert_eq!(ERR_REQUIRED_FIELD.0, field_errors[0].code);
+
+    let request = ConsumerReq {
+        name: Some("ab".to_string()),
+        rate_limit: Some(serde_json::json!({
+            "limits": [{"requests": 100, "period": "minute", "by": "tenant"}]
+        })),
+        certificate_subject: None,
+    };
+    let response = service.save(request).await;
+    let field_errors = response.unwrap_err().field_errors.unwrap();
+    assert_eq!(2, field_errors.len());
+    assert_eq!(ERR_MIN_SIZE.0, field_errors[0].code);
+    assert_eq!(ERR_INVALID_VALUE.0, field_errors[1].code);
+    assert_eq!("consumer.rateLimit.limits[0].by", field_errors[1].field);
+}
+
+#[tokio::test]
+async fn update() {
+    let mut mock_repo = MockConsumerRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(consumer())));
+    mock_repo
+        .expect_update()
+        .withf(|consumer| consumer.name == "partner-api" && consumer.rate_limit.is_none())
+        .returning(Ok);
+
+    let request = ConsumerReq {
+        name: Some("partner-api".to_string()),
+        rate_limit: None,
+        certificate_subject: None,
+    };
+
+    let service = ConsumerService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.update(1, request).await;
+    assert!(response.is_ok());
+}
+
+#[tokio::test]
+async fn update_certificate_subject() {
+    let mut mock_repo = MockConsumerRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| {
+        Ok(Some(Consumer {
+            certificate_subject: Some(String::from("orders-service")),
+            ..consumer()
+        }))
+    });
+    mock_repo
+        .expect_update()
+        .withf(|consumer| consumer.certificate_subject.is_none())
+        .times(1)
+        .returning(Ok);
+    mock_repo
+        .expect_update()
+        .withf(|consumer| consumer.certificate_subject.as_deref() == Some("orders-service"))
+        .times(1)
+        .returning(Ok);
+
+    let service = ConsumerService::new_with_repo(Arc::new(mock_repo));
+
+    // an empty one removes it, without one it's kept.
+    let request = ConsumerReq {
+        name: None,
+        rate_limit: None,
+        certificate_subject: Some(String::new()),
+    };
+    assert!(service.update(1, request).await.is_ok());
+    let request = ConsumerReq {
+        name: None,
+        rate_limit: None,
+        certificate_subject: None,
+    };
+    assert!(service.update(1, request).await.is_ok());
+
+    let request = ConsumerReq {
+        name: None,
+        rate_limit: None,
+        certificate_subject: Some(String::from(" orders-service")),
+    };
+    let field_errors = service
+        .update(1, request)
+        .await
+        .unwrap_err()
+        .field_errors
+        .unwrap();
+    assert_eq!(ERR_INVALID_VALUE.0, field_errors[0].code);
+    assert_eq!("consumer.certificateSubject", field_errors[0].field);
+}
+
+#[tokio::test]
+async fn delete_not_found() {
+    let mut mock_repo = MockConsumerRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| Ok(None));
+
+    let service = ConsumerService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.delete(1).await;
+    assert!(response.is_err());
+    assert_eq!(CSM_ERR_NOT_FOUND.0, response.unwrap_err().code);
+}
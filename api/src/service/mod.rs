@@ -1,3 +1,21 @@
+mod application_orchestration_service;
+mod application_orchestration_route_service;
+mod application_route_service;
 mod application_service;
+mod application_workflow_service;
+mod certificate_service;
+mod consumer_api_key_service;
+mod consumer_service;
+mod upstream_service;
+mod upstream_target_service;
 
-pub use application_service::*;
\ No newline at end of file
+pub use application_orchestration_service::*;
+pub use application_orchestration_route_service::*;
+pub use application_route_service::*;
+pub use application_service::*;
+pub use application_workflow_service::*;
+pub use certificate_service::*;
+pub use consumer_api_key_service::*;
+pub use consumer_service::*;
+pub use upstream_service::*;
+pub use upstream_target_service::*;
\ No newline at end of file
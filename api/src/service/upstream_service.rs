@@ -0,0 +1,196 @@
+#[cfg(test)]
+#[path = "upstream_service_test.rs"]
+mod upstream_service_test;
+
+use std::sync::Arc;
+
+use axum::async_trait;
+use hyper::StatusCode;
+use serde_json::Value;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{ApiError, UPS_ERR_ENCRYPTING_CREDENTIALS, UPS_ERR_NOT_FOUND},
+    model::{
+        Pagination, PaginationResponse, Upstream, UpstreamCredentials, UpstreamReq, UpstreamTls,
+    },
+    repository::{UpstreamRepository, UpstreamRepositoryTrait},
+    secret::SecretCipher,
+};
+
+#[async_trait]
+pub trait UpstreamServiceTrait: std::fmt::Debug {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<Upstream>, ApiError>;
+
+    async fn find_by_id(&self, id: i64) -> Result<Upstream, ApiError>;
+
+    async fn save(&self, entity: UpstreamReq) -> Result<Upstream, ApiError>;
+
+    async fn update(&self, id: i64, entity: UpstreamReq) -> Result<Upstream, ApiError>;
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError>;
+}
+
+#[derive(Debug)]
+pub struct UpstreamService {
+    upstream_repository: Arc<dyn UpstreamRepositoryTrait + Send + Sync>,
+    cipher: Option<SecretCipher>,
+}
+
+#[async_trait]
+impl UpstreamServiceTrait for UpstreamService {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<Upstream>, ApiError> {
+        pagination.validate()?;
+
+        let response = self.upstream_repository.find_all(pagination).await?;
+        Ok(response)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Upstream, ApiError> {
+        let response = self.upstream_repository.find_by_id(id).await?;
+
+        if response.is_none() {
+            return Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                UPS_ERR_NOT_FOUND,
+            ));
+        }
+        Ok(response.unwrap())
+    }
+
+    async fn save(&self, entity: UpstreamReq) -> Result<Upstream, ApiError> {
+        entity.validate()?;
+
+        let id = self.upstream_repository.next_id().await?;
+        let (credentials_type, credentials) = match &entity.credentials {
+            Some(credentials) => self.seal_credentials(id, credentials)?,
+            None => (None, None),
+        };
+
+        let upstream = self
+            .upstream_repository
+            .save(id, entity, credentials_type, credentials)
+            .await?;
+        Ok(upstream)
+    }
+
+    async fn update(&self, id: i64, entity: UpstreamReq) -> Result<Upstream, ApiError> {
+        if let Some(mut upstream) = self.upstream_repository.find_by_id(id).await? {
+            entity.validate_updating(&upstream)?;
+
+            if let Some(name) = entity.name {
+                upstream.name = name;
+            }
+
+            if let Some(algorithm) = entity.algorithm {
+                upstream.algorithm = algorithm;
+            }
+
+            if entity.hash_on.is_some() {
+                upstream.hash_on = entity.hash_on;
+            }
+
+            if entity.hash_key.is_some() {
+                upstream.hash_key = entity.hash_key;
+            }
+
+            if entity.health_check.is_some() {
+                upstream.health_check = entity.health_check;
+            }
+
+            // an empty object removes it.
+            if let Some(tls) = entity.tls {
+                upstream.tls = Some(tls).filter(|tls| !UpstreamTls::is_removal(tls));
+            }
+
+            // the credentials are replaced as a whole, the secrets saved before can't be read back.
+            if let Some(credentials) = &entity.credentials {
+                (upstream.credentials_type, upstream.credentials) =
+                    self.seal_credentials(upstream.id, credentials)?;
+            }
+
+            upstream = self.upstream_repository.update(upstream).await?;
+            Ok(upstream)
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                UPS_ERR_NOT_FOUND,
+            ))
+        }
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError> {
+        if (self.upstream_repository.find_by_id(id).await?).is_some() {
+            self.upstream_repository.delete(id).await?;
+            Ok(())
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                UPS_ERR_NOT_FOUND,
+            ))
+        }
+    }
+}
+
+impl UpstreamService {
+    pub fn new(pg_pool: Arc<PgPool>) -> Self {
+        UpstreamService {
+            upstream_repository: Arc::new(UpstreamRepository { pg_pool }),
+            cipher: SecretCipher::config(),
+        }
+    }
+
+    pub fn new_with_repo(repository: Arc<dyn UpstreamRepositoryTrait + Send + Sync>) -> Self {
+        UpstreamService::new_with_cipher(repository, SecretCipher::config())
+    }
+
+    pub fn new_with_cipher(
+        repository: Arc<dyn UpstreamRepositoryTrait + Send + Sync>,
+        cipher: Option<SecretCipher>,
+    ) -> Self {
+        UpstreamService {
+            upstream_repository: repository,
+            cipher,
+        }
+    }
+
+    // the type and the sealed credentials, none of them for an empty object.
+    fn seal_credentials(
+        &self,
+        id: i64,
+        credentials: &Value,
+    ) -> Result<(Option<String>, Option<String>), ApiError> {
+        if UpstreamCredentials::is_removal(credentials) {
+            return Ok((None, None));
+        }
+
+        let encrypting_error = || {
+            ApiError::new_with_status(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                UPS_ERR_ENCRYPTING_CREDENTIALS,
+            )
+        };
+        let credentials =
+            UpstreamCredentials::parse(credentials).map_err(|_| encrypting_error())?;
+        let cipher = self.cipher.as_ref().ok_or_else(|| {
+            tracing::error!("CREDENTIALS_ENCRYPTION_KEY is required to store upstream credentials");
+            encrypting_error()
+        })?;
+        let sealed = serde_json::to_string(&credentials)
+            .ok()
+            .and_then(|plain| {
+                cipher
+                    .seal(&plain, &UpstreamCredentials::sealing_context(id))
+                    .ok()
+            })
+            .ok_or_else(encrypting_error)?;
+
+        Ok((Some(credentials.credentials_type), Some(sealed)))
+    }
+}
@@ -0,0 +1,451 @@
+use chrono::Utc;
+use serde_json::json;
+
+use crate::{
+    exception::{
+        ERR_INVALID_URL, ERR_INVALID_VALUE, ERR_MIN_SIZE, ERR_REQUIRED_FIELD,
+        UPS_ERR_ENCRYPTING_CREDENTIALS, UPS_ERR_IN_USE,
+    },
+    model::{
+        ALGORITHM_CONSISTENT_HASH, ALGORITHM_LEAST_CONNECTIONS, ALGORITHM_ROUND_ROBIN,
+        CREDENTIALS_OAUTH2_CLIENT_CREDENTIALS, HASH_ON_COOKIE, HASH_ON_HEADER,
+    },
+    repository::MockUpstreamRepositoryTrait,
+};
+
+use super::*;
+
+fn upstream() -> Upstream {
+    Upstream {
+        id: 1,
+        name: String::from("orders"),
+        algorithm: String::from(ALGORITHM_ROUND_ROBIN),
+        hash_on: None,
+        hash_key: None,
+        health_check: None,
+        credentials_type: None,
+        credentials: None,
+        tls: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+fn upstream_req(name: Option<&str>, algorithm: Option<&str>) -> UpstreamReq {
+    UpstreamReq {
+        name: name.map(String::from),
+        algorithm: algorithm.map(String::from),
+        hash_on: None,
+        hash_key: None,
+        health_check: None,
+        credentials: None,
+        tls: None,
+    }
+}
+
+#[tokio::test]
+async fn find_by_id() {
+    let mut mock_repo = MockUpstreamRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(upstream())));
+
+    let service = UpstreamService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.find_by_id(1).await;
+    assert!(response.is_ok());
+    assert_eq!("orders", response.unwrap().name);
+}
+
+#[tokio::test]
+async fn find_by_id_not_found() {
+    let mut mock_repo = MockUpstreamRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| Ok(None));
+
+    let service = UpstreamService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.find_by_id(1).await;
+    assert!(response.is_err());
+    assert_eq!(UPS_ERR_NOT_FOUND.0, response.unwrap_err().code);
+}
+
+#[tokio::test]
+async fn save() {
+    let mut mock_repo = MockUpstreamRepositoryTrait::new();
+    mock_repo.expect_next_id().returning(|| Ok(1));
+    mock_repo
+        .expect_save()
+        .returning(|_, _, _, _| Ok(upstream()));
+
+    let service = UpstreamService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service
+        .save(upstream_req(
+            Some("orders"),
+            Some(ALGORITHM_LEAST_CONNECTIONS),
+        ))
+        .await;
+    assert!(response.is_ok());
+    assert_eq!(1, response.unwrap().id);
+}
+
+#[tokio::test]
+async fn save_with_invalid_fields() {
+    let service = UpstreamService::new_with_repo(Arc::new(MockUpstreamRepositoryTrait::new()));
+
+    let response = service
+        .save(upstream_req(Some("or"), Some("FASTEST")))
+        .await;
+    assert!(response.is_err());
+
+    let field_errors = response.unwrap_err().field_errors.unwrap();
+    assert_eq!(2, field_errors.len());
+    assert_eq!(ERR_MIN_SIZE.0, field_errors[0].code);
+    assert_eq!(ERR_INVALID_VALUE.0, field_errors[1].code);
+    assert_eq!("upstream.algorithm", field_errors[1].field);
+}
+
+#[tokio::test]
+async fn save_with_invalid_hashing() {
+    let service = UpstreamService::new_with_repo(Arc::new(MockUpstreamRepositoryTrait::new()));
+
+    // consistent hashing needs to know what to hash.
+    let response = service
+        .save(upstream_req(
+            Some("orders"),
+            Some(ALGORITHM_CONSISTENT_HASH),
+        ))
+        .await;
+    let field_errors = response.unwrap_err().field_errors.unwrap();
+    assert_eq!(1, field_errors.len());
+    assert_eq!(ERR_REQUIRED_FIELD.0, field_errors[0].code);
+    assert_eq!("upstream.hashOn", field_errors[0].field);
+
+    let request = UpstreamReq {
+        hash_on: Some(String::from(HASH_ON_COOKIE)),
+        ..upstream_req(Some("orders"), Some(ALGORITHM_CONSISTENT_HASH))
+    };
+    let field_errors = service
+        .save(request)
+        .await
+        .unwrap_err()
+        .field_errors
+        .unwrap();
+    assert_eq!(ERR_REQUIRED_FIELD.0, field_errors[0].code);
+    assert_eq!("upstream.hashKey", field_errors[0].field);
+
+    let request = UpstreamReq {
+        hash_on: Some(String::from(HASH_ON_HEADER)),
+        hash_key: Some(String::from("x user")),
+        ..upstream_req(Some("orders"), Some(ALGORITHM_CONSISTENT_HASH))
+    };
+    let field_errors = service
+        .save(request)
+        .await
+        .unwrap_err()
+        .field_errors
+        .unwrap();
+    assert_eq!(ERR_INVALID_VALUE.0, field_errors[0].code);
+    assert_eq!("upstream.hashKey", field_errors[0].field);
+}
+
+#[tokio::test]
+async fn save_with_invalid_health_check() {
+    let service = UpstreamService::new_with_repo(Arc::new(MockUpstreamRepositoryTrait::new()));
+
+    let request = UpstreamReq {
+        health_check: Some(json!({
+            "active": {"path": "/health", "timeoutMs": 0},
+            "passive": {"unhealthyThreshold": 0}
+        })),
+        ..upstream_req(Some("orders"), Some(ALGORITHM_ROUND_ROBIN))
+    };
+    let field_errors = service
+        .save(request)
+        .await
+        .unwrap_err()
+        .field_errors
+        .unwrap();
+    assert_eq!(2, field_errors.len());
+    assert_eq!(ERR_INVALID_VALUE.0, field_errors[0].code);
+    assert_eq!(
+        "upstream.healthCheck.active.timeoutMs",
+        field_errors[0].field
+    );
+    assert_eq!(
+        "upstream.healthCheck.passive.unhealthyThreshold",
+        field_errors[1].field
+    );
+
+    let request = UpstreamReq {
+        health_check: Some(json!({"active": {"interval": 10}})),
+        ..upstream_req(Some("orders"), Some(ALGORITHM_ROUND_ROBIN))
+    };
+    let field_errors = service
+        .save(request)
+        .await
+        .unwrap_err()
+        .field_errors
+        .unwrap();
+    assert_eq!("upstream.healthCheck", field_errors[0].field);
+}
+
+fn cipher() -> SecretCipher {
+    SecretCipher::from_base64("MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=").unwrap()
+}
+
+fn oauth2_credentials() -> Value {
+    json!({
+        "type": "OAUTH2_CLIENT_CREDENTIALS",
+        "tokenUrl": "https://auth.example.com/token",
+        "clientId": "gateway",
+        "clientSecret": "s3cr3t",
+    })
+}
+
+#[tokio::test]
+async fn save_with_credentials() {
+    let mut mock_repo = MockUpstreamRepositoryTrait::new();
+    mock_repo.expect_next_id().returning(|| Ok(7));
+    mock_repo
+        .expect_save()
+        .withf(|id, _, credentials_type, credentials| {
+            let context = UpstreamCredentials::sealing_context(*id);
+            credentials_type.as_deref() == Some(CREDENTIALS_OAUTH2_CLIENT_CREDENTIALS)
+                && credentials.as_deref().is_some_and(|sealed| {
+                    !sealed.contains("s3cr3t")
+                        && cipher().open(sealed, &context).unwrap().contains("s3cr3t")
+                        && cipher()
+                            .open(sealed, &UpstreamCredentials::sealing_context(1))
+                            .is_err()
+                })
+        })
+        .returning(|_, _, credentials_type, credentials| {
+            Ok(Upstream {
+                credentials_type,
+                credentials,
+                ..upstream()
+            })
+        });
+
+    let service = UpstreamService::new_with_cipher(Arc::new(mock_repo), Some(cipher()));
+
+    let request = UpstreamReq {
+        credentials: Some(oauth2_credentials()),
+        ..upstream_req(Some("orders"), None)
+    };
+    let upstream = service.save(request).await.unwrap();
+
+    // only the type is shown.
+    let upstream = serde_json::to_value(upstream).unwrap();
+    assert_eq!(
+        CREDENTIALS_OAUTH2_CLIENT_CREDENTIALS,
+        upstream["credentialsType"]
+    );
+    assert!(upstream.get("credentials").is_none());
+}
+
+#[tokio::test]
+async fn save_with_invalid_credentials() {
+    let service = UpstreamService::new_with_cipher(
+        Arc::new(MockUpstreamRepositoryTrait::new()),
+        Some(cipher()),
+    );
+
+    let request = UpstreamReq {
+        credentials: Some(json!({"type": "OAUTH2_CLIENT_CREDENTIALS", "tokenUrl": "token"})),
+        ..upstream_req(Some("orders"), None)
+    };
+    let field_errors = service
+        .save(request)
+        .await
+        .unwrap_err()
+        .field_errors
+        .unwrap();
+    assert_eq!(3, field_errors.len());
+    assert_eq!(ERR_INVALID_URL.0, field_errors[0].code);
+    assert_eq!("upstream.credentials.tokenUrl", field_errors[0].field);
+    assert_eq!(ERR_REQUIRED_FIELD.0, field_errors[1].code);
+    assert_eq!("upstream.credentials.clientId", field_errors[1].field);
+    assert_eq!("upstream.credentials.clientSecret", field_errors[2].field);
+
+    let request = UpstreamReq {
+        credentials: Some(json!({"type": "HEADERS", "headers": {"x api key": "k3y"}})),
+        ..upstream_req(Some("orders"), None)
+    };
+    let field_errors = service
+        .save(request)
+        .await
+        .unwrap_err()
+        .field_errors
+        .unwrap();
+    assert_eq!(ERR_INVALID_VALUE.0, field_errors[0].code);
+    assert_eq!("upstream.credentials.headers", field_errors[0].field);
+
+    let request = UpstreamReq {
+        credentials: Some(json!({"type": "DIGEST"})),
+        ..upstream_req(Some("orders"), None)
+    };
+    let field_errors = service
+        .save(request)
+        .await
+        .unwrap_err()
+        .field_errors
+        .unwrap();
+    assert_eq!("upstream.credentials.type", field_errors[0].field);
+}
+
+#[tokio::test]
+async fn save_with_credentials_without_key() {
+    let mut mock_repo = MockUpstreamRepositoryTrait::new();
+    mock_repo.expect_next_id().returning(|| Ok(1));
+    let service = UpstreamService::new_with_cipher(Arc::new(mock_repo), None);
+
+    let request = UpstreamReq {
+        credentials: Some(oauth2_credentials()),
+        ..upstream_req(Some("orders"), None)
+    };
+    let api_error = service.save(request).await.unwrap_err();
+    assert_eq!(500, api_error.status_code);
+    assert_eq!(UPS_ERR_ENCRYPTING_CREDENTIALS.0, api_error.code);
+}
+
+#[tokio::test]
+async fn update_credentials() {
+    let mut mock_repo = MockUpstreamRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| {
+        Ok(Some(Upstream {
+            credentials_type: Some(String::from(CREDENTIALS_OAUTH2_CLIENT_CREDENTIALS)),
+            credentials: Some(String::from("v1:sealed")),
+            ..upstream()
+        }))
+    });
+    mock_repo
+        .expect_update()
+        .withf(|upstream| upstream.credentials.is_none() && upstream.credentials_type.is_none())
+        .times(1)
+        .returning(Ok);
+    mock_repo
+        .expect_update()
+        .withf(|upstream| upstream.credentials.as_deref() == Some("v1:sealed"))
+        .times(1)
+        .returning(Ok);
+
+    let service = UpstreamService::new_with_cipher(Arc::new(mock_repo), Some(cipher()));
+
+    // an empty object removes them, without credentials they're kept.
+    let request = UpstreamReq {
+        credentials: Some(json!({})),
+        ..upstream_req(None, None)
+    };
+    assert!(service.update(1, request).await.is_ok());
+    assert!(service.update(1, upstream_req(None, None)).await.is_ok());
+}
+
+#[tokio::test]
+async fn save_with_invalid_tls() {
+    let service = UpstreamService::new_with_repo(Arc::new(MockUpstreamRepositoryTrait::new()));
+
+    let request = UpstreamReq {
+        tls: Some(json!({"caFile": " ", "certFile": "gateway.pem"})),
+        ..upstream_req(Some("orders"), None)
+    };
+    let field_errors = service
+        .save(request)
+        .await
+        .unwrap_err()
+        .field_errors
+        .unwrap();
+    assert_eq!(2, field_errors.len());
+    assert_eq!(ERR_INVALID_VALUE.0, field_errors[0].code);
+    assert_eq!("upstream.tls.caFile", field_errors[0].field);
+    assert_eq!(ERR_REQUIRED_FIELD.0, field_errors[1].code);
+    assert_eq!("upstream.tls.keyFile", field_errors[1].field);
+
+    let request = UpstreamReq {
+        tls: Some(json!({"ca": "ca.pem"})),
+        ..upstream_req(Some("orders"), None)
+    };
+    let field_errors = service
+        .save(request)
+        .await
+        .unwrap_err()
+        .field_errors
+        .unwrap();
+    assert_eq!(ERR_INVALID_VALUE.0, field_errors[0].code);
+    assert_eq!("upstream.tls", field_errors[0].field);
+}
+
+#[tokio::test]
+async fn update_tls() {
+    let mut mock_repo = MockUpstreamRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| {
+        Ok(Some(Upstream {
+            tls: Some(json!({"caFile": "ca.pem"})),
+            ..upstream()
+        }))
+    });
+    mock_repo
+        .expect_update()
+        .withf(|upstream| upstream.tls.is_none())
+        .times(1)
+        .returning(Ok);
+    mock_repo
+        .expect_update()
+        .withf(|upstream| upstream.tls == Some(json!({"caFile": "ca.pem"})))
+        .times(1)
+        .returning(Ok);
+
+    let service = UpstreamService::new_with_repo(Arc::new(mock_repo));
+
+    // an empty object removes it, without tls it's kept.
+    let request = UpstreamReq {
+        tls: Some(json!({})),
+        ..upstream_req(None, None)
+    };
+    assert!(service.update(1, request).await.is_ok());
+    assert!(service.update(1, upstream_req(None, None)).await.is_ok());
+}
+
+#[tokio::test]
+async fn update() {
+    let mut mock_repo = MockUpstreamRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| {
+        let mut upstream = upstream();
+        upstream.hash_on = Some(String::from(HASH_ON_HEADER));
+        upstream.hash_key = Some(String::from("x-user"));
+        Ok(Some(upstream))
+    });
+    mock_repo
+        .expect_update()
+        .withf(|upstream| {
+            upstream.algorithm == ALGORITHM_CONSISTENT_HASH
+                && upstream.hash_key.as_deref() == Some("x-user")
+        })
+        .returning(Ok);
+
+    // the hashing saved before completes the request.
+    let service = UpstreamService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service
+        .update(1, upstream_req(None, Some(ALGORITHM_CONSISTENT_HASH)))
+        .await;
+    assert!(response.is_ok());
+}
+
+#[tokio::test]
+async fn delete_in_use() {
+    let mut mock_repo = MockUpstreamRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(upstream())));
+    mock_repo
+        .expect_delete()
+        .returning(|_| Err(ApiError::new(UPS_ERR_IN_USE)));
+
+    let service = UpstreamService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.delete(1).await;
+    assert!(response.is_err());
+    assert_eq!(UPS_ERR_IN_USE.0, response.unwrap_err().code);
+}
@@ -0,0 +1,117 @@
+#[cfg(test)]
+#[path = "upstream_target_service_test.rs"]
+mod upstream_target_service_test;
+
+use std::sync::Arc;
+
+use axum::async_trait;
+use hyper::StatusCode;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{ApiError, UPT_ERR_NOT_FOUND},
+    model::{Pagination, PaginationResponse, UpstreamTarget, UpstreamTargetReq},
+    repository::{UpstreamTargetRepository, UpstreamTargetRepositoryTrait},
+};
+
+#[async_trait]
+pub trait UpstreamTargetServiceTrait: std::fmt::Debug {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<UpstreamTarget>, ApiError>;
+
+    async fn find_by_id(&self, id: i64) -> Result<UpstreamTarget, ApiError>;
+
+    async fn save(&self, entity: UpstreamTargetReq) -> Result<UpstreamTarget, ApiError>;
+
+    async fn update(&self, id: i64, entity: UpstreamTargetReq) -> Result<UpstreamTarget, ApiError>;
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError>;
+}
+
+#[derive(Debug)]
+pub struct UpstreamTargetService {
+    target_repository: Arc<dyn UpstreamTargetRepositoryTrait + Send + Sync>,
+}
+
+#[async_trait]
+impl UpstreamTargetServiceTrait for UpstreamTargetService {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<UpstreamTarget>, ApiError> {
+        pagination.validate()?;
+
+        let response = self.target_repository.find_all(pagination).await?;
+        Ok(response)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<UpstreamTarget, ApiError> {
+        let response = self.target_repository.find_by_id(id).await?;
+
+        if response.is_none() {
+            return Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                UPT_ERR_NOT_FOUND,
+            ));
+        }
+        Ok(response.unwrap())
+    }
+
+    async fn save(&self, entity: UpstreamTargetReq) -> Result<UpstreamTarget, ApiError> {
+        entity.validate()?;
+
+        let target = self.target_repository.save(entity).await?;
+        Ok(target)
+    }
+
+    // a target stays in its upstream, id_upstream is ignored.
+    async fn update(&self, id: i64, entity: UpstreamTargetReq) -> Result<UpstreamTarget, ApiError> {
+        entity.validate_updating()?;
+
+        if let Some(mut target) = self.target_repository.find_by_id(id).await? {
+            if let Some(url) = entity.url {
+                target.url = url;
+            }
+
+            if let Some(weight) = entity.weight {
+                target.weight = weight;
+            }
+
+            target = self.target_repository.update(target).await?;
+            Ok(target)
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                UPT_ERR_NOT_FOUND,
+            ))
+        }
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError> {
+        if (self.target_repository.find_by_id(id).await?).is_some() {
+            self.target_repository.delete(id).await?;
+            Ok(())
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                UPT_ERR_NOT_FOUND,
+            ))
+        }
+    }
+}
+
+impl UpstreamTargetService {
+    pub fn new(pg_pool: Arc<PgPool>) -> Self {
+        UpstreamTargetService {
+            target_repository: Arc::new(UpstreamTargetRepository { pg_pool }),
+        }
+    }
+
+    pub fn new_with_repo(repository: Arc<dyn UpstreamTargetRepositoryTrait + Send + Sync>) -> Self {
+        UpstreamTargetService {
+            target_repository: repository,
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use chrono::Utc;
+
+use crate::{
+    exception::{ERR_INVALID_URL, ERR_INVALID_VALUE, ERR_REQUIRED_FIELD},
+    repository::MockUpstreamTargetRepositoryTrait,
+};
+
+use super::*;
+
+fn target() -> UpstreamTarget {
+    UpstreamTarget {
+        id: 1,
+        id_upstream: 1,
+        url: String::from("http://10.0.0.12:8080"),
+        weight: 100,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn find_by_id() {
+    let mut mock_repo = MockUpstreamTargetRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(target())));
+
+    let service = UpstreamTargetService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.find_by_id(1).await;
+    assert!(response.is_ok());
+    assert_eq!("http://10.0.0.12:8080", response.unwrap().url);
+}
+
+#[tokio::test]
+async fn find_by_id_not_found() {
+    let mut mock_repo = MockUpstreamTargetRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| Ok(None));
+
+    let service = UpstreamTargetService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.find_by_id(1).await;
+    assert!(response.is_err());
+    assert_eq!(UPT_ERR_NOT_FOUND.0, response.unwrap_err().code);
+}
+
+#[tokio::test]
+async fn save() {
+    let mut mock_repo = MockUpstreamTargetRepositoryTrait::new();
+    mock_repo.expect_save().returning(|_| Ok(target()));
+
+    let request = UpstreamTargetReq {
+        id_upstream: Some(1),
+        url: Some("https://orders-2.internal:8443/".to_string()),
+        weight: Some(0),
+    };
+
+    let service = UpstreamTargetService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.save(request).await;
+    assert!(response.is_ok());
+}
+
+#[tokio::test]
+async fn save_with_invalid_fields() {
+    let request = UpstreamTargetReq {
+        id_upstream: None,
+        url: Some("http://10.0.0.12:8080/orders".to_string()),
+        weight: Some(1001),
+    };
+
+    let service =
+        UpstreamTargetService::new_with_repo(Arc::new(MockUpstreamTargetRepositoryTrait::new()));
+
+    let response = service.save(request).await;
+    assert!(response.is_err());
+
+    // the path comes from the destination of the application or route.
+    let field_errors = response.unwrap_err().field_errors.unwrap();
+    assert_eq!(3, field_errors.len());
+    assert_eq!(ERR_REQUIRED_FIELD.0, field_errors[0].code);
+    assert_eq!(ERR_INVALID_URL.0, field_errors[1].code);
+    assert_eq!(ERR_INVALID_VALUE.0, field_errors[2].code);
+}
+
+#[tokio::test]
+async fn update() {
+    let mut mock_repo = MockUpstreamTargetRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(target())));
+    mock_repo
+        .expect_update()
+        .withf(|target| target.id_upstream == 1 && target.weight == 0)
+        .returning(Ok);
+
+    let request = UpstreamTargetReq {
+        id_upstream: Some(2),
+        url: None,
+        weight: Some(0),
+    };
+
+    let service = UpstreamTargetService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.update(1, request).await;
+    assert!(response.is_ok());
+}
+
+#[tokio::test]
+async fn delete() {
+    let mut mock_repo = MockUpstreamTargetRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(target())));
+    mock_repo.expect_delete().returning(|_| Ok(()));
+
+    let service = UpstreamTargetService::new_with_repo(Arc::new(mock_repo));
+    let response = service.delete(1).await;
+    assert!(response.is_ok());
+}
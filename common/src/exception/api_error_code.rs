@@ -12,6 +12,12 @@ pub const PG_ERR_PAGE_SIZE_REQUIRED: ApiErrorCode = ApiErrorCode("PG0002", "Para
 // Field errors.
 pub const ERR_REQUIRED_FIELD: ApiErrorCode = ApiErrorCode("F0001", "This field is required.");
 pub const ERR_MIN_SIZE: ApiErrorCode = ApiErrorCode("F0002", "This field must has a minimum amount of characters.");
+pub const ERR_INVALID_PATH: ApiErrorCode = ApiErrorCode("F0003", "This field must be a path starting with '/' and without other '/'.");
+pub const ERR_INVALID_URL: ApiErrorCode = ApiErrorCode("F0004", "This field must be an absolute http or https url.");
+pub const ERR_INVALID_SUB_PATH: ApiErrorCode = ApiErrorCode("F0005", "This field must be a path starting with '/'.");
+pub const ERR_INVALID_VALUE: ApiErrorCode = ApiErrorCode("F0006", "This field has an invalid value.");
+pub const ERR_INVALID_TEMPLATE: ApiErrorCode = ApiErrorCode("F0007", "This field has an invalid template expression.");
+pub const ERR_INVALID_PATH_PATTERN: ApiErrorCode = ApiErrorCode("F0008", "This field has an invalid path pattern.");
 
 // Application errors.
 pub const APP_ERR_INSERTING: ApiErrorCode = ApiErrorCode("APP0001", "Error when insert a new application.");
@@ -22,7 +28,140 @@ pub const APP_ERR_UPDATING: ApiErrorCode = ApiErrorCode("APP0005", "Error when u
 pub const APP_ERR_DELETE: ApiErrorCode = ApiErrorCode("APP0006", "Error when delete an application.");
 pub const APP_ERR_ID_IS_REQUIRED: ApiErrorCode = ApiErrorCode("APP0007", "The Id of Application is required.");
 pub const APP_ERR_FIND_BY_PATH: ApiErrorCode = ApiErrorCode("APP0008", "Error when search an application by his path.");
+pub const APP_ERR_PATH_ALREADY_EXISTS: ApiErrorCode = ApiErrorCode("APP0009", "There is already an application with this path.");
+pub const APP_ERR_UPSTREAM_NOT_FOUND: ApiErrorCode = ApiErrorCode("APP0010", "Upstream of the application wasn't find.");
+pub const APP_ERR_ENCRYPTING_AUTH: ApiErrorCode = ApiErrorCode("APP0011", "The secrets of the application auth could not be encrypted.");
+
+// Workflow errors.
+pub const WF_ERR_INSERTING: ApiErrorCode = ApiErrorCode("WF0001", "Error when insert a new workflow.");
+pub const WF_ERR_FINDING_PAGINATED: ApiErrorCode = ApiErrorCode("WF0002", "Error when search workflows with pagination.");
+pub const WF_ERR_FIND_BY_ID: ApiErrorCode = ApiErrorCode("WF0003", "Error when search a workflow by id.");
+pub const WF_ERR_NOT_FOUND: ApiErrorCode = ApiErrorCode("WF0004", "Workflow wasn't find.");
+pub const WF_ERR_UPDATING: ApiErrorCode = ApiErrorCode("WF0005", "Error when update a workflow.");
+pub const WF_ERR_DELETE: ApiErrorCode = ApiErrorCode("WF0006", "Error when delete a workflow.");
+pub const WF_ERR_PATH_ALREADY_EXISTS: ApiErrorCode = ApiErrorCode("WF0007", "There is already a workflow with this path in the application.");
+pub const WF_ERR_APPLICATION_NOT_FOUND: ApiErrorCode = ApiErrorCode("WF0008", "Application of the workflow wasn't find.");
+
+// Route errors.
+pub const ROU_ERR_INSERTING: ApiErrorCode = ApiErrorCode("ROU0001", "Error when insert a new route.");
+pub const ROU_ERR_FINDING_PAGINATED: ApiErrorCode = ApiErrorCode("ROU0002", "Error when search routes with pagination.");
+pub const ROU_ERR_FIND_BY_ID: ApiErrorCode = ApiErrorCode("ROU0003", "Error when search a route by id.");
+pub const ROU_ERR_NOT_FOUND: ApiErrorCode = ApiErrorCode("ROU0004", "Route wasn't find.");
+pub const ROU_ERR_UPDATING: ApiErrorCode = ApiErrorCode("ROU0005", "Error when update a route.");
+pub const ROU_ERR_DELETE: ApiErrorCode = ApiErrorCode("ROU0006", "Error when delete a route.");
+pub const ROU_ERR_WORKFLOW_NOT_FOUND: ApiErrorCode = ApiErrorCode("ROU0007", "Workflow of the route wasn't find.");
+pub const ROU_ERR_INVALID_DESCRIPTOR: ApiErrorCode = ApiErrorCode("ROU0008", "The descriptor set is invalid or has no http rule.");
+pub const ROU_ERR_UPSTREAM_NOT_FOUND: ApiErrorCode = ApiErrorCode("ROU0009", "Upstream of the route wasn't find.");
+pub const ROU_ERR_ENCRYPTING_AUTH: ApiErrorCode = ApiErrorCode("ROU0010", "The secrets of the route auth could not be encrypted.");
+
+// Orchestration errors.
+pub const ORC_ERR_INSERTING: ApiErrorCode = ApiErrorCode("ORC0001", "Error when insert a new orchestration.");
+pub const ORC_ERR_FINDING_PAGINATED: ApiErrorCode = ApiErrorCode("ORC0002", "Error when search orchestrations with pagination.");
+pub const ORC_ERR_FIND_BY_ID: ApiErrorCode = ApiErrorCode("ORC0003", "Error when search an orchestration by id.");
+pub const ORC_ERR_NOT_FOUND: ApiErrorCode = ApiErrorCode("ORC0004", "Orchestration wasn't find.");
+pub const ORC_ERR_UPDATING: ApiErrorCode = ApiErrorCode("ORC0005", "Error when update an orchestration.");
+pub const ORC_ERR_DELETE: ApiErrorCode = ApiErrorCode("ORC0006", "Error when delete an orchestration.");
+pub const ORC_ERR_PATH_ALREADY_EXISTS: ApiErrorCode = ApiErrorCode("ORC0007", "There is already an orchestration with this path in the workflow.");
+pub const ORC_ERR_WORKFLOW_NOT_FOUND: ApiErrorCode = ApiErrorCode("ORC0008", "Workflow of the orchestration wasn't find.");
+
+// Orchestration route errors.
+pub const ORR_ERR_INSERTING: ApiErrorCode = ApiErrorCode("ORR0001", "Error when insert a new orchestration route.");
+pub const ORR_ERR_FINDING_PAGINATED: ApiErrorCode = ApiErrorCode("ORR0002", "Error when search orchestration routes with pagination.");
+pub const ORR_ERR_FIND_BY_ID: ApiErrorCode = ApiErrorCode("ORR0003", "Error when search an orchestration route by id.");
+pub const ORR_ERR_NOT_FOUND: ApiErrorCode = ApiErrorCode("ORR0004", "Orchestration route wasn't find.");
+pub const ORR_ERR_UPDATING: ApiErrorCode = ApiErrorCode("ORR0005", "Error when update an orchestration route.");
+pub const ORR_ERR_DELETE: ApiErrorCode = ApiErrorCode("ORR0006", "Error when delete an orchestration route.");
+pub const ORR_ERR_RESPONSE_KEY_ALREADY_EXISTS: ApiErrorCode = ApiErrorCode("ORR0007", "There is already an orchestration route with this response key.");
+pub const ORR_ERR_ORCHESTRATION_NOT_FOUND: ApiErrorCode = ApiErrorCode("ORR0008", "Orchestration of the orchestration route wasn't find.");
+pub const ORR_ERR_ROUTE_NOT_FOUND: ApiErrorCode = ApiErrorCode("ORR0009", "Route of the orchestration route wasn't find.");
+
+// Upstream errors.
+pub const UPS_ERR_INSERTING: ApiErrorCode = ApiErrorCode("UPS0001", "Error when insert a new upstream.");
+pub const UPS_ERR_FINDING_PAGINATED: ApiErrorCode = ApiErrorCode("UPS0002", "Error when search upstreams with pagination.");
+pub const UPS_ERR_FIND_BY_ID: ApiErrorCode = ApiErrorCode("UPS0003", "Error when search an upstream by id.");
+pub const UPS_ERR_NOT_FOUND: ApiErrorCode = ApiErrorCode("UPS0004", "Upstream wasn't find.");
+pub const UPS_ERR_UPDATING: ApiErrorCode = ApiErrorCode("UPS0005", "Error when update an upstream.");
+pub const UPS_ERR_DELETE: ApiErrorCode = ApiErrorCode("UPS0006", "Error when delete an upstream.");
+pub const UPS_ERR_NAME_ALREADY_EXISTS: ApiErrorCode = ApiErrorCode("UPS0007", "There is already an upstream with this name.");
+pub const UPS_ERR_IN_USE: ApiErrorCode = ApiErrorCode("UPS0008", "The upstream is used by an application or a route.");
+pub const UPS_ERR_ENCRYPTING_CREDENTIALS: ApiErrorCode = ApiErrorCode("UPS0009", "The credentials of the upstream could not be encrypted.");
+
+// Upstream target errors.
+pub const UPT_ERR_INSERTING: ApiErrorCode = ApiErrorCode("UPT0001", "Error when insert a new upstream target.");
+pub const UPT_ERR_FINDING_PAGINATED: ApiErrorCode = ApiErrorCode("UPT0002", "Error when search upstream targets with pagination.");
+pub const UPT_ERR_FIND_BY_ID: ApiErrorCode = ApiErrorCode("UPT0003", "Error when search an upstream target by id.");
+pub const UPT_ERR_NOT_FOUND: ApiErrorCode = ApiErrorCode("UPT0004", "Upstream target wasn't find.");
+pub const UPT_ERR_UPDATING: ApiErrorCode = ApiErrorCode("UPT0005", "Error when update an upstream target.");
+pub const UPT_ERR_DELETE: ApiErrorCode = ApiErrorCode("UPT0006", "Error when delete an upstream target.");
+pub const UPT_ERR_URL_ALREADY_EXISTS: ApiErrorCode = ApiErrorCode("UPT0007", "There is already a target with this url in the upstream.");
+pub const UPT_ERR_UPSTREAM_NOT_FOUND: ApiErrorCode = ApiErrorCode("UPT0008", "Upstream of the target wasn't find.");
+
+// Consumer errors.
+pub const CSM_ERR_INSERTING: ApiErrorCode = ApiErrorCode("CSM0001", "Error when insert a new consumer.");
+pub const CSM_ERR_FINDING_PAGINATED: ApiErrorCode = ApiErrorCode("CSM0002", "Error when search consumers with pagination.");
+pub const CSM_ERR_FIND_BY_ID: ApiErrorCode = ApiErrorCode("CSM0003", "Error when search a consumer by id.");
+pub const CSM_ERR_NOT_FOUND: ApiErrorCode = ApiErrorCode("CSM0004", "Consumer wasn't find.");
+pub const CSM_ERR_UPDATING: ApiErrorCode = ApiErrorCode("CSM0005", "Error when update a consumer.");
+pub const CSM_ERR_DELETE: ApiErrorCode = ApiErrorCode("CSM0006", "Error when delete a consumer.");
+pub const CSM_ERR_NAME_ALREADY_EXISTS: ApiErrorCode = ApiErrorCode("CSM0007", "There is already a consumer with this name.");
+pub const CSM_ERR_CERTIFICATE_SUBJECT_ALREADY_EXISTS: ApiErrorCode = ApiErrorCode("CSM0008", "There is already a consumer with this certificate subject.");
+
+// Consumer api key errors.
+pub const CAK_ERR_INSERTING: ApiErrorCode = ApiErrorCode("CAK0001", "Error when insert a new api key.");
+pub const CAK_ERR_FINDING_PAGINATED: ApiErrorCode = ApiErrorCode("CAK0002", "Error when search api keys with pagination.");
+pub const CAK_ERR_FIND_BY_ID: ApiErrorCode = ApiErrorCode("CAK0003", "Error when search an api key by id.");
+pub const CAK_ERR_NOT_FOUND: ApiErrorCode = ApiErrorCode("CAK0004", "Api key wasn't find.");
+pub const CAK_ERR_UPDATING: ApiErrorCode = ApiErrorCode("CAK0005", "Error when update an api key.");
+pub const CAK_ERR_DELETE: ApiErrorCode = ApiErrorCode("CAK0006", "Error when delete an api key.");
+pub const CAK_ERR_CONSUMER_NOT_FOUND: ApiErrorCode = ApiErrorCode("CAK0007", "Consumer of the api key wasn't find.");
+pub const CAK_ERR_ROTATING: ApiErrorCode = ApiErrorCode("CAK0008", "Error when rotate an api key.");
+
+// Certificate errors.
+pub const CRT_ERR_INSERTING: ApiErrorCode = ApiErrorCode("CRT0001", "Error when insert a new certificate.");
+pub const CRT_ERR_FINDING_PAGINATED: ApiErrorCode = ApiErrorCode("CRT0002", "Error when search certificates with pagination.");
+pub const CRT_ERR_FIND_BY_ID: ApiErrorCode = ApiErrorCode("CRT0003", "Error when search a certificate by id.");
+pub const CRT_ERR_NOT_FOUND: ApiErrorCode = ApiErrorCode("CRT0004", "Certificate wasn't find.");
+pub const CRT_ERR_UPDATING: ApiErrorCode = ApiErrorCode("CRT0005", "Error when update a certificate.");
+pub const CRT_ERR_DELETE: ApiErrorCode = ApiErrorCode("CRT0006", "Error when delete a certificate.");
+pub const CRT_ERR_NAME_ALREADY_EXISTS: ApiErrorCode = ApiErrorCode("CRT0007", "There is already a certificate with this name.");
+pub const CRT_ERR_DEFAULT_ALREADY_EXISTS: ApiErrorCode = ApiErrorCode("CRT0008", "There is already a default certificate.");
+pub const CRT_ERR_ENCRYPTING_PRIVATE_KEY: ApiErrorCode = ApiErrorCode("CRT0009", "The private key of the certificate could not be encrypted.");
+pub const CRT_ERR_LOADING: ApiErrorCode = ApiErrorCode("CRT0010", "Error when loading the certificates of the gateway.");
+
+// Template errors.
+pub const TEMPLATE_ERR_INVALID: ApiErrorCode = ApiErrorCode("TPL0001", "Invalid template expression.");
+pub const TEMPLATE_ERR_REQUEST_REFERENCE: ApiErrorCode = ApiErrorCode("TPL0002", "A template reference to the request couldn't be resolved.");
+pub const TEMPLATE_ERR_RESPONSE_REFERENCE: ApiErrorCode = ApiErrorCode("TPL0003", "A template reference to a previous response couldn't be resolved.");
+pub const TEMPLATE_ERR_INVALID_HEADER: ApiErrorCode = ApiErrorCode("TPL0004", "A rendered template isn't a valid header.");
+
+// Routing errors.
+pub const ROUTING_ERR_LOADING: ApiErrorCode = ApiErrorCode("RT0001", "Error when loading the routing table.");
 
 // Forward errors.
 pub const FORWARD_ERR_PATH_IS_REQUIRED: ApiErrorCode = ApiErrorCode("FWD0001", "At least one path is required.");
-pub const FORWARD_ERR_PATH_NOT_FOUND: ApiErrorCode = ApiErrorCode("FWD0002", "Main path could not be found.");
\ No newline at end of file
+pub const FORWARD_ERR_PATH_NOT_FOUND: ApiErrorCode = ApiErrorCode("FWD0002", "Main path could not be found.");
+pub const FORWARD_ERR_INVALID_DESTINATION: ApiErrorCode = ApiErrorCode("FWD0003", "Destination url of the application is invalid.");
+pub const FORWARD_ERR_WORKFLOW_INACTIVE: ApiErrorCode = ApiErrorCode("FWD0004", "The workflow of this path isn't active.");
+pub const FORWARD_ERR_READING_BODY: ApiErrorCode = ApiErrorCode("FWD0005", "Error when reading the request body.");
+pub const FORWARD_ERR_ORCHESTRATION_FAILED: ApiErrorCode = ApiErrorCode("FWD0006", "An upstream of the orchestration has failed.");
+pub const FORWARD_ERR_ORCHESTRATION_TIMEOUT: ApiErrorCode = ApiErrorCode("FWD0007", "An upstream of the orchestration has timed out.");
+pub const FORWARD_ERR_BODY_TOO_LARGE: ApiErrorCode = ApiErrorCode("FWD0008", "The body is too large to be buffered.");
+pub const FORWARD_ERR_WEBSOCKET_LIMIT: ApiErrorCode = ApiErrorCode("FWD0009", "The application has reached its limit of websocket connections.");
+pub const FORWARD_ERR_UPSTREAM_TIMEOUT: ApiErrorCode = ApiErrorCode("FWD0010", "The upstream has not answered in time.");
+pub const FORWARD_ERR_CIRCUIT_OPEN: ApiErrorCode = ApiErrorCode("FWD0011", "The upstream is failing, its circuit is open.");
+pub const FORWARD_ERR_NO_UPSTREAM_TARGET: ApiErrorCode = ApiErrorCode("FWD0012", "The upstream has no target to receive the request.");
+pub const FORWARD_ERR_RATE_LIMITED: ApiErrorCode = ApiErrorCode("FWD0013", "Too many requests, the rate limit has been exceeded.");
+pub const FORWARD_ERR_UNAUTHORIZED: ApiErrorCode = ApiErrorCode("FWD0014", "A valid api key is required.");
+pub const FORWARD_ERR_INVALID_TOKEN: ApiErrorCode = ApiErrorCode("FWD0015", "A valid bearer token is required.");
+pub const FORWARD_ERR_FORBIDDEN: ApiErrorCode = ApiErrorCode("FWD0016", "The token does not grant access to this path.");
+pub const FORWARD_ERR_INTROSPECTION_FAILED: ApiErrorCode = ApiErrorCode("FWD0017", "The token could not be introspected.");
+pub const FORWARD_ERR_UPSTREAM_CREDENTIALS: ApiErrorCode = ApiErrorCode("FWD0018", "The credentials of the upstream could not be obtained.");
+pub const FORWARD_ERR_UPSTREAM_TLS: ApiErrorCode = ApiErrorCode("FWD0019", "The tls configuration of the upstream could not be loaded.");
+pub const FORWARD_ERR_CLIENT_CERTIFICATE_REQUIRED: ApiErrorCode = ApiErrorCode("FWD0020", "A verified client certificate is required.");
+pub const FORWARD_ERR_CLIENT_CERTIFICATE_FORBIDDEN: ApiErrorCode = ApiErrorCode("FWD0021", "The client certificate does not grant access to this path.");
+
+// Transcoding errors.
+pub const TRANSCODING_ERR_METHOD_NOT_FOUND: ApiErrorCode = ApiErrorCode("TRC0001", "No grpc method is bound to this http method and path.");
+pub const TRANSCODING_ERR_INVALID_REQUEST: ApiErrorCode = ApiErrorCode("TRC0002", "The request couldn't be converted to the grpc message.");
+pub const TRANSCODING_ERR_INVALID_RESPONSE: ApiErrorCode = ApiErrorCode("TRC0003", "The grpc response couldn't be converted to json.");
+pub const TRANSCODING_ERR_GRPC_FAILED: ApiErrorCode = ApiErrorCode("TRC0004", "The grpc upstream has failed.");
\ No newline at end of file
@@ -2,4 +2,8 @@ extern crate derive_more;
 extern crate serde;
 
 pub mod exception;
-pub mod model;
\ No newline at end of file
+pub mod model;
+pub mod notification;
+pub mod secret;
+pub mod template;
+pub mod transcoding;
\ No newline at end of file
@@ -1,24 +1,60 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use sqlx::FromRow;
 
-use crate::exception::{ApiError, ApiFieldError, ERR_INVALID_REQUEST, ERR_REQUIRED_FIELD, ERR_MIN_SIZE};
+use crate::{
+    exception::{
+        ApiError, ApiFieldError, ERR_INVALID_PATH, ERR_INVALID_REQUEST, ERR_INVALID_URL,
+        ERR_INVALID_VALUE, ERR_MIN_SIZE, ERR_REQUIRED_FIELD,
+    },
+    model::{is_absolute_http_url, serialize_auth, AuthPolicy, RateLimitPolicy, UpstreamPolicy},
+};
 
 #[derive(Serialize, Deserialize, Debug, FromRow)]
 #[serde(rename_all = "camelCase")]
 pub struct Application {
     pub id: i64,
     pub name: String,
+    pub path: String,
+    pub url_destination: String,
+    // the upstream receives the client's host instead of the one of url_destination.
+    pub preserve_host: bool,
+    // upgraded connections open at the same time, without a limit when empty.
+    pub websocket_max_connections: Option<i32>,
+    // timeouts and retries, see UpstreamPolicy.
+    pub upstream_policy: Option<Value>,
+    // the targets of the upstream replace the host of url_destination.
+    pub id_upstream: Option<i64>,
+    // requests allowed per period, see RateLimitPolicy.
+    pub rate_limit: Option<Value>,
+    // credentials the clients must present, see AuthPolicy. its secrets are sealed and never
+    // shown.
+    #[serde(serialize_with = "serialize_auth")]
+    pub auth: Option<Value>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl Application {
+    // what the secrets of its auth are sealed with, they only open on its row.
+    pub fn auth_sealing_context(id: i64) -> String {
+        format!("tb_application:{}:auth", id)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ApplicationReq {
     pub name: Option<String>,
     pub path: Option<String>,
     pub url_destination: Option<String>,
+    pub preserve_host: Option<bool>,
+    pub websocket_max_connections: Option<i32>,
+    pub upstream_policy: Option<Value>,
+    pub id_upstream: Option<i64>,
+    pub rate_limit: Option<Value>,
+    pub auth: Option<Value>,
 }
 
 impl ApplicationReq {
@@ -37,6 +73,14 @@ impl ApplicationReq {
             field_errors.push(error);
         }
 
+        if let Err(error) = self.validate_websocket_max_connections() {
+            field_errors.push(error);
+        }
+
+        field_errors.append(&mut self.validate_upstream_policy());
+        field_errors.append(&mut self.validate_rate_limit());
+        field_errors.append(&mut self.validate_auth());
+
         if !field_errors.is_empty() {
             return Err(ApiError::new_with_field_errors(
                 ERR_INVALID_REQUEST,
@@ -62,6 +106,14 @@ impl ApplicationReq {
             field_errors.push(error);
         }
 
+        if let Err(error) = self.validate_websocket_max_connections() {
+            field_errors.push(error);
+        }
+
+        field_errors.append(&mut self.validate_upstream_policy());
+        field_errors.append(&mut self.validate_rate_limit());
+        field_errors.append(&mut self.validate_auth());
+
         if !field_errors.is_empty() {
             return Err(ApiError::new_with_field_errors(
                 ERR_INVALID_REQUEST,
@@ -108,6 +160,11 @@ impl ApplicationReq {
                         "application.path".to_owned(),
                         3,
                     ))
+                } else if !path.starts_with('/') || path[1..].contains('/') {
+                    Err(ApiFieldError::new(
+                        ERR_INVALID_PATH,
+                        "application.path".to_owned(),
+                    ))
                 } else {
                     Ok(())
                 }
@@ -134,6 +191,11 @@ impl ApplicationReq {
                         "application.urlDestination".to_owned(),
                         3
                     ))
+                } else if !is_absolute_http_url(url_destination) {
+                    Err(ApiFieldError::new(
+                        ERR_INVALID_URL,
+                        "application.urlDestination".to_owned(),
+                    ))
                 } else {
                     Ok(())
                 }
@@ -150,4 +212,37 @@ impl ApplicationReq {
             }
         }
     }
+
+    fn validate_websocket_max_connections(&self) -> Result<(), ApiFieldError> {
+        match self.websocket_max_connections {
+            Some(max_connections) if max_connections < 1 => Err(ApiFieldError::new(
+                ERR_INVALID_VALUE,
+                "application.websocketMaxConnections".to_owned(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_upstream_policy(&self) -> Vec<ApiFieldError> {
+        match &self.upstream_policy {
+            Some(upstream_policy) => {
+                UpstreamPolicy::validate(upstream_policy, "application.upstreamPolicy")
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn validate_rate_limit(&self) -> Vec<ApiFieldError> {
+        match &self.rate_limit {
+            Some(rate_limit) => RateLimitPolicy::validate(rate_limit, "application.rateLimit"),
+            None => Vec::new(),
+        }
+    }
+
+    fn validate_auth(&self) -> Vec<ApiFieldError> {
+        match &self.auth {
+            Some(auth) => AuthPolicy::validate(auth, "application.auth"),
+            None => Vec::new(),
+        }
+    }
 }
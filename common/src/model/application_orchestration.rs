@@ -0,0 +1,150 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::{
+    exception::{
+        ApiError, ApiFieldError, ERR_INVALID_PATH_PATTERN, ERR_INVALID_REQUEST,
+        ERR_INVALID_SUB_PATH, ERR_INVALID_VALUE, ERR_REQUIRED_FIELD,
+    },
+    model::PathPattern,
+};
+
+pub const ORCHESTRATION_TYPE_PARALLEL: &str = "P";
+pub const ORCHESTRATION_TYPE_SEQUENTIAL: &str = "S";
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplicationOrchestration {
+    pub id: i64,
+    pub id_application_workflow: i64,
+    pub path: String,
+    #[serde(rename = "type")]
+    #[sqlx(rename = "type")]
+    pub orchestration_type: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ApplicationOrchestration {
+    pub fn is_parallel(&self) -> bool {
+        self.orchestration_type == ORCHESTRATION_TYPE_PARALLEL
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplicationOrchestrationReq {
+    pub id_application_workflow: Option<i64>,
+    pub path: Option<String>,
+    #[serde(rename = "type")]
+    pub orchestration_type: Option<String>,
+}
+
+impl ApplicationOrchestrationReq {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if self.id_application_workflow.is_none() {
+            field_errors.push(ApiFieldError::new(
+                ERR_REQUIRED_FIELD,
+                "orchestration.idApplicationWorkflow".to_owned(),
+            ));
+        }
+
+        if let Err(error) = self.validate_path(true) {
+            field_errors.push(error);
+        }
+
+        if let Err(error) = self.validate_type(true) {
+            field_errors.push(error);
+        }
+
+        if !field_errors.is_empty() {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                field_errors,
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_updating(&self) -> Result<(), ApiError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if let Err(error) = self.validate_path(false) {
+            field_errors.push(error);
+        }
+
+        if let Err(error) = self.validate_type(false) {
+            field_errors.push(error);
+        }
+
+        if !field_errors.is_empty() {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                field_errors,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn validate_path(&self, is_required: bool) -> Result<(), ApiFieldError> {
+        match &self.path {
+            Some(path) => {
+                if path.len() < 2 || !path.starts_with('/') {
+                    Err(ApiFieldError::new(
+                        ERR_INVALID_SUB_PATH,
+                        "orchestration.path".to_owned(),
+                    ))
+                } else if PathPattern::parse(path).is_err() {
+                    Err(ApiFieldError::new(
+                        ERR_INVALID_PATH_PATTERN,
+                        "orchestration.path".to_owned(),
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+            None => {
+                if is_required {
+                    Err(ApiFieldError::new(
+                        ERR_REQUIRED_FIELD,
+                        "orchestration.path".to_owned(),
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    fn validate_type(&self, is_required: bool) -> Result<(), ApiFieldError> {
+        match &self.orchestration_type {
+            Some(orchestration_type) => {
+                if orchestration_type != ORCHESTRATION_TYPE_PARALLEL
+                    && orchestration_type != ORCHESTRATION_TYPE_SEQUENTIAL
+                {
+                    Err(ApiFieldError::new(
+                        ERR_INVALID_VALUE,
+                        "orchestration.type".to_owned(),
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+            None => {
+                if is_required {
+                    Err(ApiFieldError::new(
+                        ERR_REQUIRED_FIELD,
+                        "orchestration.type".to_owned(),
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use hyper::header::HeaderName;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+
+use crate::{
+    exception::{
+        ApiError, ApiFieldError, ERR_INVALID_REQUEST, ERR_INVALID_TEMPLATE, ERR_INVALID_VALUE,
+        ERR_REQUIRED_FIELD,
+    },
+    template::{Template, TEMPLATE_REQUEST},
+};
+
+// what an orchestration does when this route fails or times out.
+pub const FAILURE_POLICY_FAIL: &str = "FAIL";
+pub const FAILURE_POLICY_OMIT: &str = "OMIT";
+pub const FAILURE_POLICY_FALLBACK: &str = "FALLBACK";
+pub const FAILURE_POLICY_ERROR: &str = "ERROR";
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplicationOrchestrationRoute {
+    pub id: i64,
+    pub id_application_orchestration: i64,
+    pub id_application_route: i64,
+    pub response_key: String,
+    // header name -> template, applied over the incoming headers.
+    pub request_headers: Option<Value>,
+    // template that replaces the incoming body.
+    pub request_body: Option<String>,
+    pub failure_policy: String,
+    // returned under the response key by the FALLBACK policy.
+    pub fallback: Option<Value>,
+    pub timeout_ms: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplicationOrchestrationRouteReq {
+    pub id_application_orchestration: Option<i64>,
+    pub id_application_route: Option<i64>,
+    pub response_key: Option<String>,
+    pub request_headers: Option<Value>,
+    pub request_body: Option<String>,
+    pub failure_policy: Option<String>,
+    pub fallback: Option<Value>,
+    pub timeout_ms: Option<i64>,
+}
+
+impl ApplicationOrchestrationRouteReq {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if self.id_application_orchestration.is_none() {
+            field_errors.push(ApiFieldError::new(
+                ERR_REQUIRED_FIELD,
+                "orchestrationRoute.idApplicationOrchestration".to_owned(),
+            ));
+        }
+
+        if self.id_application_route.is_none() {
+            field_errors.push(ApiFieldError::new(
+                ERR_REQUIRED_FIELD,
+                "orchestrationRoute.idApplicationRoute".to_owned(),
+            ));
+        }
+
+        if let Err(error) = self.validate_response_key(true) {
+            field_errors.push(error);
+        }
+
+        field_errors.append(&mut self.validate_templates());
+        field_errors.append(&mut self.validate_failure_handling(None));
+
+        if !field_errors.is_empty() {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                field_errors,
+            ));
+        }
+
+        Ok(())
+    }
+
+    // the current route is needed to know if a fallback is already there.
+    pub fn validate_updating(
+        &self,
+        current: &ApplicationOrchestrationRoute,
+    ) -> Result<(), ApiError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if let Err(error) = self.validate_response_key(false) {
+            field_errors.push(error);
+        }
+
+        field_errors.append(&mut self.validate_templates());
+        field_errors.append(&mut self.validate_failure_handling(Some(current)));
+
+        if !field_errors.is_empty() {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                field_errors,
+            ));
+        }
+
+        Ok(())
+    }
+
+    // "request" is reserved, templates use it to reference the incoming request.
+    fn validate_response_key(&self, is_required: bool) -> Result<(), ApiFieldError> {
+        match &self.response_key {
+            Some(response_key) if response_key.trim().is_empty() => Err(ApiFieldError::new(
+                ERR_REQUIRED_FIELD,
+                "orchestrationRoute.responseKey".to_owned(),
+            )),
+            Some(response_key) if response_key == TEMPLATE_REQUEST => Err(ApiFieldError::new(
+                ERR_INVALID_VALUE,
+                "orchestrationRoute.responseKey".to_owned(),
+            )),
+            None if is_required => Err(ApiFieldError::new(
+                ERR_REQUIRED_FIELD,
+                "orchestrationRoute.responseKey".to_owned(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_templates(&self) -> Vec<ApiFieldError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        match &self.request_headers {
+            Some(Value::Object(headers)) => {
+                for (name, value) in headers {
+                    let field = format!("orchestrationRoute.requestHeaders.{}", name);
+                    match value {
+                        Value::String(value) if HeaderName::try_from(name.as_str()).is_ok() => {
+                            if Template::parse(value).is_err() {
+                                field_errors.push(ApiFieldError::new(ERR_INVALID_TEMPLATE, field));
+                            }
+                        }
+                        _ => field_errors.push(ApiFieldError::new(ERR_INVALID_VALUE, field)),
+                    }
+                }
+            }
+            Some(Value::Null) | None => {}
+            Some(_) => field_errors.push(ApiFieldError::new(
+                ERR_INVALID_VALUE,
+                "orchestrationRoute.requestHeaders".to_owned(),
+            )),
+        }
+
+        if let Some(request_body) = &self.request_body {
+            if Template::parse(request_body).is_err() {
+                field_errors.push(ApiFieldError::new(
+                    ERR_INVALID_TEMPLATE,
+                    "orchestrationRoute.requestBody".to_owned(),
+                ));
+            }
+        }
+
+        field_errors
+    }
+
+    fn validate_failure_handling(
+        &self,
+        current: Option<&ApplicationOrchestrationRoute>,
+    ) -> Vec<ApiFieldError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if let Some(failure_policy) = &self.failure_policy {
+            if ![
+                FAILURE_POLICY_FAIL,
+                FAILURE_POLICY_OMIT,
+                FAILURE_POLICY_FALLBACK,
+                FAILURE_POLICY_ERROR,
+            ]
+            .contains(&failure_policy.as_str())
+            {
+                field_errors.push(ApiFieldError::new(
+                    ERR_INVALID_VALUE,
+                    "orchestrationRoute.failurePolicy".to_owned(),
+                ));
+            }
+        }
+
+        let failure_policy = self
+            .failure_policy
+            .as_deref()
+            .or_else(|| current.map(|current| current.failure_policy.as_str()));
+        let has_fallback = matches!(&self.fallback, Some(fallback) if !fallback.is_null())
+            || current.is_some_and(|current| current.fallback.is_some());
+
+        if failure_policy == Some(FAILURE_POLICY_FALLBACK) && !has_fallback {
+            field_errors.push(ApiFieldError::new(
+                ERR_REQUIRED_FIELD,
+                "orchestrationRoute.fallback".to_owned(),
+            ));
+        }
+
+        if matches!(self.timeout_ms, Some(timeout_ms) if timeout_ms <= 0) {
+            field_errors.push(ApiFieldError::new(
+                ERR_INVALID_VALUE,
+                "orchestrationRoute.timeoutMs".to_owned(),
+            ));
+        }
+
+        field_errors
+    }
+}
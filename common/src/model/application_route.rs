@@ -0,0 +1,196 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+
+use crate::{
+    exception::{
+        ApiError, ApiFieldError, ERR_INVALID_PATH_PATTERN, ERR_INVALID_REQUEST,
+        ERR_INVALID_SUB_PATH, ERR_INVALID_TEMPLATE, ERR_INVALID_URL, ERR_REQUIRED_FIELD,
+    },
+    model::{
+        is_absolute_http_url, serialize_auth, AuthPolicy, PathPattern, RateLimitPolicy,
+        UpstreamPolicy,
+    },
+    template::Template,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplicationRoute {
+    pub id: i64,
+    pub id_application_workflow: Option<i64>,
+    pub path: String,
+    pub forward_to: Option<String>,
+    // breaks ties between routes that are equally specific, the highest wins.
+    pub priority: i32,
+    // text/event-stream and long-poll routes, responses are flushed as they arrive and use the
+    // streaming idle timeout.
+    pub streaming: bool,
+    // protobuf descriptor set with google.api.http rules, json requests are transcoded to the
+    // grpc methods it binds. uploaded apart, it is never part of the json.
+    #[serde(skip)]
+    pub grpc_descriptor: Option<Vec<u8>>,
+    // timeouts and retries, see UpstreamPolicy.
+    pub upstream_policy: Option<Value>,
+    // the targets of the upstream replace the host of the destination.
+    pub id_upstream: Option<i64>,
+    // requests allowed per period, see RateLimitPolicy.
+    pub rate_limit: Option<Value>,
+    // credentials the clients must present, see AuthPolicy. its secrets are sealed and never
+    // shown.
+    #[serde(serialize_with = "serialize_auth")]
+    pub auth: Option<Value>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ApplicationRoute {
+    // what the secrets of its auth are sealed with, they only open on its row.
+    pub fn auth_sealing_context(id: i64) -> String {
+        format!("tb_application_route:{}:auth", id)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplicationRouteReq {
+    pub id_application_workflow: Option<i64>,
+    pub path: Option<String>,
+    pub forward_to: Option<String>,
+    pub priority: Option<i32>,
+    pub streaming: Option<bool>,
+    pub upstream_policy: Option<Value>,
+    pub id_upstream: Option<i64>,
+    pub rate_limit: Option<Value>,
+    pub auth: Option<Value>,
+}
+
+impl ApplicationRouteReq {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if self.id_application_workflow.is_none() {
+            field_errors.push(ApiFieldError::new(
+                ERR_REQUIRED_FIELD,
+                "route.idApplicationWorkflow".to_owned(),
+            ));
+        }
+
+        if let Err(error) = self.validate_path(true) {
+            field_errors.push(error);
+        }
+
+        if let Err(error) = self.validate_forward_to() {
+            field_errors.push(error);
+        }
+
+        field_errors.append(&mut self.validate_upstream_policy());
+        field_errors.append(&mut self.validate_rate_limit());
+        field_errors.append(&mut self.validate_auth());
+
+        if !field_errors.is_empty() {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                field_errors,
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_updating(&self) -> Result<(), ApiError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if let Err(error) = self.validate_path(false) {
+            field_errors.push(error);
+        }
+
+        if let Err(error) = self.validate_forward_to() {
+            field_errors.push(error);
+        }
+
+        field_errors.append(&mut self.validate_upstream_policy());
+        field_errors.append(&mut self.validate_rate_limit());
+        field_errors.append(&mut self.validate_auth());
+
+        if !field_errors.is_empty() {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                field_errors,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn validate_path(&self, is_required: bool) -> Result<(), ApiFieldError> {
+        match &self.path {
+            Some(path) => {
+                if path.len() < 2 || !path.starts_with('/') {
+                    Err(ApiFieldError::new(
+                        ERR_INVALID_SUB_PATH,
+                        "route.path".to_owned(),
+                    ))
+                } else if PathPattern::parse(path).is_err() {
+                    Err(ApiFieldError::new(
+                        ERR_INVALID_PATH_PATTERN,
+                        "route.path".to_owned(),
+                    ))
+                } else if Template::parse(path).is_err() {
+                    // orchestrations render the path, e.g. "/users/{{user.id}}/orders".
+                    Err(ApiFieldError::new(
+                        ERR_INVALID_TEMPLATE,
+                        "route.path".to_owned(),
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+            None => {
+                if is_required {
+                    Err(ApiFieldError::new(
+                        ERR_REQUIRED_FIELD,
+                        "route.path".to_owned(),
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    // forward_to is optional, the route inherits the workflow's one when it's empty.
+    fn validate_forward_to(&self) -> Result<(), ApiFieldError> {
+        match &self.forward_to {
+            Some(forward_to) if !is_absolute_http_url(forward_to) => Err(ApiFieldError::new(
+                ERR_INVALID_URL,
+                "route.forwardTo".to_owned(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_upstream_policy(&self) -> Vec<ApiFieldError> {
+        match &self.upstream_policy {
+            Some(upstream_policy) => {
+                UpstreamPolicy::validate(upstream_policy, "route.upstreamPolicy")
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn validate_rate_limit(&self) -> Vec<ApiFieldError> {
+        match &self.rate_limit {
+            Some(rate_limit) => RateLimitPolicy::validate(rate_limit, "route.rateLimit"),
+            None => Vec::new(),
+        }
+    }
+
+    fn validate_auth(&self) -> Vec<ApiFieldError> {
+        match &self.auth {
+            Some(auth) => AuthPolicy::validate(auth, "route.auth"),
+            None => Vec::new(),
+        }
+    }
+}
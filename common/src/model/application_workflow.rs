@@ -0,0 +1,200 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+
+use crate::{
+    exception::{
+        ApiError, ApiFieldError, ERR_INVALID_PATH_PATTERN, ERR_INVALID_REQUEST,
+        ERR_INVALID_SUB_PATH, ERR_INVALID_URL, ERR_INVALID_VALUE, ERR_REQUIRED_FIELD,
+    },
+    model::{is_absolute_http_url, PathPattern, UpstreamPolicy},
+};
+
+pub const WORKFLOW_STATUS_ACTIVE: &str = "ACTIVE";
+pub const WORKFLOW_STATUS_INACTIVE: &str = "INACTIVE";
+
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplicationWorkflow {
+    pub id: i64,
+    pub id_application: i64,
+    pub path: String,
+    pub forward_to: String,
+    pub status: String,
+    // breaks ties between workflows that are equally specific, the highest wins.
+    pub priority: i32,
+    // timeouts and retries, see UpstreamPolicy.
+    pub upstream_policy: Option<Value>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ApplicationWorkflow {
+    pub fn is_active(&self) -> bool {
+        self.status == WORKFLOW_STATUS_ACTIVE
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplicationWorkflowReq {
+    pub id_application: Option<i64>,
+    pub path: Option<String>,
+    pub forward_to: Option<String>,
+    pub status: Option<String>,
+    pub priority: Option<i32>,
+    pub upstream_policy: Option<Value>,
+}
+
+impl ApplicationWorkflowReq {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if self.id_application.is_none() {
+            field_errors.push(ApiFieldError::new(
+                ERR_REQUIRED_FIELD,
+                "workflow.idApplication".to_owned(),
+            ));
+        }
+
+        if let Err(error) = self.validate_path(true) {
+            field_errors.push(error);
+        }
+
+        if let Err(error) = self.validate_forward_to(true) {
+            field_errors.push(error);
+        }
+
+        if let Err(error) = self.validate_status(true) {
+            field_errors.push(error);
+        }
+
+        field_errors.append(&mut self.validate_upstream_policy());
+
+        if !field_errors.is_empty() {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                field_errors,
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_updating(&self) -> Result<(), ApiError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if let Err(error) = self.validate_path(false) {
+            field_errors.push(error);
+        }
+
+        if let Err(error) = self.validate_forward_to(false) {
+            field_errors.push(error);
+        }
+
+        if let Err(error) = self.validate_status(false) {
+            field_errors.push(error);
+        }
+
+        field_errors.append(&mut self.validate_upstream_policy());
+
+        if !field_errors.is_empty() {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                field_errors,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn validate_path(&self, is_required: bool) -> Result<(), ApiFieldError> {
+        match &self.path {
+            Some(path) => {
+                if path.len() < 2 || !path.starts_with('/') {
+                    Err(ApiFieldError::new(
+                        ERR_INVALID_SUB_PATH,
+                        "workflow.path".to_owned(),
+                    ))
+                } else if PathPattern::parse(path).is_err() {
+                    Err(ApiFieldError::new(
+                        ERR_INVALID_PATH_PATTERN,
+                        "workflow.path".to_owned(),
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+            None => {
+                if is_required {
+                    Err(ApiFieldError::new(
+                        ERR_REQUIRED_FIELD,
+                        "workflow.path".to_owned(),
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    fn validate_forward_to(&self, is_required: bool) -> Result<(), ApiFieldError> {
+        match &self.forward_to {
+            Some(forward_to) => {
+                if !is_absolute_http_url(forward_to) {
+                    Err(ApiFieldError::new(
+                        ERR_INVALID_URL,
+                        "workflow.forwardTo".to_owned(),
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+            None => {
+                if is_required {
+                    Err(ApiFieldError::new(
+                        ERR_REQUIRED_FIELD,
+                        "workflow.forwardTo".to_owned(),
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    fn validate_status(&self, is_required: bool) -> Result<(), ApiFieldError> {
+        match &self.status {
+            Some(status) => {
+                if status != WORKFLOW_STATUS_ACTIVE && status != WORKFLOW_STATUS_INACTIVE {
+                    Err(ApiFieldError::new(
+                        ERR_INVALID_VALUE,
+                        "workflow.status".to_owned(),
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+            None => {
+                if is_required {
+                    Err(ApiFieldError::new(
+                        ERR_REQUIRED_FIELD,
+                        "workflow.status".to_owned(),
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    fn validate_upstream_policy(&self) -> Vec<ApiFieldError> {
+        match &self.upstream_policy {
+            Some(upstream_policy) => {
+                UpstreamPolicy::validate(upstream_policy, "workflow.upstreamPolicy")
+            }
+            None => Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,389 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use hyper::header::HeaderName;
+use ring::error::Unspecified;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::{
+    exception::{ApiFieldError, ERR_INVALID_URL, ERR_INVALID_VALUE, ERR_REQUIRED_FIELD},
+    secret::SecretCipher,
+};
+
+use super::valid_url;
+
+pub const DEFAULT_API_KEY_HEADER: &str = "x-api-key";
+pub const JWT_ALGORITHM_RS256: &str = "RS256";
+pub const JWT_ALGORITHM_ES256: &str = "ES256";
+pub const JWT_ALGORITHM_HS256: &str = "HS256";
+
+const JWT_ALGORITHMS: [&str; 3] = [
+    JWT_ALGORITHM_RS256,
+    JWT_ALGORITHM_ES256,
+    JWT_ALGORITHM_HS256,
+];
+const DEFAULT_JWT_CLOCK_SKEW: u64 = 60;
+const DEFAULT_JWKS_REFRESH: u64 = 300;
+const DEFAULT_INTROSPECTION_CACHE: u64 = 60;
+const DEFAULT_INTROSPECTION_NEGATIVE_CACHE: u64 = 10;
+
+// the secrets of an auth by policy and field, they're stored sealed and never shown by the
+// admin api.
+const AUTH_SECRETS: [(&str, &str); 2] = [("jwt", "secret"), ("introspection", "clientSecret")];
+
+// how the clients of an application or route are authenticated, stored as json. the auth of a
+// route replaces the one of its application, an empty one makes the route public. with an api
+// key too, only the requests carrying a bearer token are checked with the jwt or the
+// introspection. with both of them, the tokens that aren't jwts are introspected. a client
+// certificate is required on top of any of them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AuthPolicy {
+    pub api_key: Option<ApiKeyAuth>,
+    pub jwt: Option<JwtAuth>,
+    pub introspection: Option<IntrospectionAuth>,
+    pub client_certificate: Option<ClientCertificateAuth>,
+}
+
+// the key of a consumer, read from the header and then from the query param. x-api-key when
+// both are empty.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ApiKeyAuth {
+    pub header: Option<String>,
+    pub query: Option<String>,
+}
+
+// a certificate of the client verified by the tls listener. without other auth, the consumer
+// with its subject is the caller.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ClientCertificateAuth {
+    // accepted common or alternative names, any verified certificate when empty.
+    #[serde(default)]
+    pub subjects: Vec<String>,
+}
+
+// a bearer token signed by one of the keys, which come from exactly one of the jwks file, the
+// jwks url, the pem public key or the secret.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct JwtAuth {
+    // accepted iss claims, any when empty.
+    #[serde(default)]
+    pub issuers: Vec<String>,
+    // accepted aud claims, any when empty.
+    #[serde(default)]
+    pub audiences: Vec<String>,
+    // RS256 when empty.
+    #[serde(default)]
+    pub algorithms: Vec<String>,
+    // tolerance of the exp and nbf claims, 60 seconds when empty.
+    pub clock_skew_seconds: Option<u64>,
+    // claims the token must carry, with this value unless it's null. an array claim must
+    // contain the value and a string one may hold it as one of its words, like scope.
+    #[serde(default)]
+    pub required_claims: Map<String, Value>,
+    // headers sent to the upstream with the value of a claim, by claim.
+    #[serde(default)]
+    pub forward_claims: BTreeMap<String, String>,
+    pub jwks_file: Option<String>,
+    pub jwks_url: Option<String>,
+    // how often the keys of the jwks file or url are loaded again, 300 seconds when empty.
+    pub jwks_refresh_seconds: Option<u64>,
+    // RS256 or ES256 key.
+    pub public_key: Option<String>,
+    // HS256 key, sealed in the database.
+    pub secret: Option<String>,
+}
+
+// an opaque token checked by an rfc 7662 introspection endpoint, which the gateway calls with
+// its client credentials.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct IntrospectionAuth {
+    pub url: String,
+    pub client_id: String,
+    // sealed in the database.
+    pub client_secret: String,
+    // scopes the token must have, all of them.
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    // accepted aud values, any when empty.
+    #[serde(default)]
+    pub audiences: Vec<String>,
+    // how long an active token is trusted without asking again, 60 seconds when empty. never
+    // beyond its exp.
+    pub cache_seconds: Option<u64>,
+    // how long an inactive token is rejected without asking again, 10 seconds when empty.
+    pub negative_cache_seconds: Option<u64>,
+    // headers sent to the upstream with the value of a field of the introspection, by field.
+    #[serde(default)]
+    pub forward_claims: BTreeMap<String, String>,
+}
+
+impl AuthPolicy {
+    pub fn parse(value: &Value) -> Result<AuthPolicy, serde_json::Error> {
+        AuthPolicy::deserialize(value)
+    }
+
+    pub fn has_secrets(value: &Value) -> bool {
+        AUTH_SECRETS.iter().any(|(policy, field)| {
+            value
+                .get(policy)
+                .and_then(|policy| policy.get(field))
+                .is_some()
+        })
+    }
+
+    // each secret is sealed with the context of the auth and its path, "tb_application:1:auth"
+    // and ".jwt.secret".
+    pub fn seal_secrets(
+        value: &mut Value,
+        context: &str,
+        cipher: &SecretCipher,
+    ) -> Result<(), Unspecified> {
+        AuthPolicy::replace_secrets(value, |secret, path| {
+            cipher.seal(secret, &format!("{}.{}", context, path))
+        })
+    }
+
+    pub fn open_secrets(
+        value: &mut Value,
+        context: &str,
+        cipher: &SecretCipher,
+    ) -> Result<(), Unspecified> {
+        AuthPolicy::replace_secrets(value, |secret, path| {
+            cipher.open(secret, &format!("{}.{}", context, path))
+        })
+    }
+
+    // the json without its secrets, the way the admin api shows it.
+    pub fn redact(value: &Value) -> Value {
+        let mut value = value.clone();
+        for (policy, field) in AUTH_SECRETS {
+            if let Some(Value::Object(fields)) = value.get_mut(policy) {
+                fields.remove(field);
+            }
+        }
+        value
+    }
+
+    fn replace_secrets(
+        value: &mut Value,
+        mut replace: impl FnMut(&str, &str) -> Result<String, Unspecified>,
+    ) -> Result<(), Unspecified> {
+        for (policy, field) in AUTH_SECRETS {
+            if let Some(Value::String(secret)) = value
+                .get_mut(policy)
+                .and_then(|policy| policy.get_mut(field))
+            {
+                *secret = replace(secret, &format!("{}.{}", policy, field))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate(value: &Value, field: &str) -> Vec<ApiFieldError> {
+        let policy = match AuthPolicy::parse(value) {
+            Ok(policy) => policy,
+            Err(_) => return vec![ApiFieldError::new(ERR_INVALID_VALUE, field.to_owned())],
+        };
+
+        let mut field_errors = Vec::<ApiFieldError>::new();
+        if let Some(api_key) = &policy.api_key {
+            let invalid = |name: &str| {
+                ApiFieldError::new(ERR_INVALID_VALUE, format!("{}.apiKey.{}", field, name))
+            };
+
+            if matches!(&api_key.header, Some(header) if HeaderName::try_from(header.as_str()).is_err())
+            {
+                field_errors.push(invalid("header"));
+            }
+
+            if matches!(&api_key.query, Some(query) if query.trim().is_empty()) {
+                field_errors.push(invalid("query"));
+            }
+        }
+
+        if let Some(jwt) = &policy.jwt {
+            field_errors.append(&mut jwt.validate(&format!("{}.jwt", field)));
+        }
+
+        if let Some(introspection) = &policy.introspection {
+            field_errors.append(&mut introspection.validate(&format!("{}.introspection", field)));
+        }
+
+        if let Some(client_certificate) = &policy.client_certificate {
+            if client_certificate
+                .subjects
+                .iter()
+                .any(|subject| subject.trim().is_empty())
+            {
+                field_errors.push(ApiFieldError::new(
+                    ERR_INVALID_VALUE,
+                    format!("{}.clientCertificate.subjects", field),
+                ));
+            }
+        }
+
+        field_errors
+    }
+}
+
+impl ApiKeyAuth {
+    pub fn header(&self) -> Option<&str> {
+        match (&self.header, &self.query) {
+            (Some(header), _) => Some(header),
+            (None, None) => Some(DEFAULT_API_KEY_HEADER),
+            (None, Some(_)) => None,
+        }
+    }
+
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+}
+
+impl JwtAuth {
+    pub fn algorithms(&self) -> Vec<&str> {
+        if self.algorithms.is_empty() {
+            vec![JWT_ALGORITHM_RS256]
+        } else {
+            self.algorithms.iter().map(String::as_str).collect()
+        }
+    }
+
+    pub fn clock_skew(&self) -> Duration {
+        Duration::from_secs(self.clock_skew_seconds.unwrap_or(DEFAULT_JWT_CLOCK_SKEW))
+    }
+
+    pub fn jwks_refresh(&self) -> Duration {
+        Duration::from_secs(self.jwks_refresh_seconds.unwrap_or(DEFAULT_JWKS_REFRESH))
+    }
+
+    fn validate(&self, field: &str) -> Vec<ApiFieldError> {
+        let error = |code, name: &str| ApiFieldError::new(code, format!("{}.{}", field, name));
+
+        let mut field_errors = Vec::<ApiFieldError>::new();
+        let key_sources = [
+            ("jwksFile", &self.jwks_file),
+            ("jwksUrl", &self.jwks_url),
+            ("publicKey", &self.public_key),
+            ("secret", &self.secret),
+        ];
+        let configured: Vec<&str> = key_sources
+            .iter()
+            .filter(|(_, source)| source.is_some())
+            .map(|(name, _)| *name)
+            .collect();
+        match configured.as_slice() {
+            [] => field_errors.push(error(ERR_REQUIRED_FIELD, "jwksUrl")),
+            [_] => {}
+            [_, others @ ..] => {
+                for name in others {
+                    field_errors.push(error(ERR_INVALID_VALUE, name));
+                }
+            }
+        }
+
+        for (name, source) in key_sources {
+            if matches!(source, Some(source) if source.trim().is_empty()) {
+                field_errors.push(error(ERR_INVALID_VALUE, name));
+            }
+        }
+
+        if let Some(jwks_url) = &self.jwks_url {
+            if !valid_url(jwks_url) {
+                field_errors.push(error(ERR_INVALID_URL, "jwksUrl"));
+            }
+        }
+
+        // hmac keys only come from the secret and the secret only signs hmac tokens.
+        let algorithms = self.algorithms();
+        let hmac = self.secret.is_some();
+        if algorithms.iter().any(|algorithm| {
+            !JWT_ALGORITHMS.contains(algorithm) || (*algorithm == JWT_ALGORITHM_HS256) != hmac
+        }) {
+            field_errors.push(error(ERR_INVALID_VALUE, "algorithms"));
+        }
+
+        if self.jwks_refresh_seconds == Some(0) {
+            field_errors.push(error(ERR_INVALID_VALUE, "jwksRefreshSeconds"));
+        }
+
+        if self
+            .required_claims
+            .keys()
+            .any(|claim| claim.trim().is_empty())
+        {
+            field_errors.push(error(ERR_INVALID_VALUE, "requiredClaims"));
+        }
+
+        if !valid_forward_claims(&self.forward_claims) {
+            field_errors.push(error(ERR_INVALID_VALUE, "forwardClaims"));
+        }
+
+        field_errors
+    }
+}
+
+impl IntrospectionAuth {
+    pub fn cache(&self) -> Duration {
+        Duration::from_secs(self.cache_seconds.unwrap_or(DEFAULT_INTROSPECTION_CACHE))
+    }
+
+    pub fn negative_cache(&self) -> Duration {
+        Duration::from_secs(
+            self.negative_cache_seconds
+                .unwrap_or(DEFAULT_INTROSPECTION_NEGATIVE_CACHE),
+        )
+    }
+
+    fn validate(&self, field: &str) -> Vec<ApiFieldError> {
+        let error = |code, name: &str| ApiFieldError::new(code, format!("{}.{}", field, name));
+
+        let mut field_errors = Vec::<ApiFieldError>::new();
+        if self.url.trim().is_empty() {
+            field_errors.push(error(ERR_REQUIRED_FIELD, "url"));
+        } else if !valid_url(&self.url) {
+            field_errors.push(error(ERR_INVALID_URL, "url"));
+        }
+
+        if self.client_id.trim().is_empty() {
+            field_errors.push(error(ERR_REQUIRED_FIELD, "clientId"));
+        }
+
+        if self.client_secret.is_empty() {
+            field_errors.push(error(ERR_REQUIRED_FIELD, "clientSecret"));
+        }
+
+        if self
+            .scopes
+            .iter()
+            .any(|scope| scope.is_empty() || scope.contains(char::is_whitespace))
+        {
+            field_errors.push(error(ERR_INVALID_VALUE, "scopes"));
+        }
+
+        if !valid_forward_claims(&self.forward_claims) {
+            field_errors.push(error(ERR_INVALID_VALUE, "forwardClaims"));
+        }
+
+        field_errors
+    }
+}
+
+fn valid_forward_claims(forward_claims: &BTreeMap<String, String>) -> bool {
+    forward_claims.iter().all(|(claim, header)| {
+        !claim.trim().is_empty() && HeaderName::try_from(header.as_str()).is_ok()
+    })
+}
+
+// the auth of an application or route as the admin api shows it, without its secrets.
+pub fn serialize_auth<S: Serializer>(
+    auth: &Option<Value>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    auth.as_ref().map(AuthPolicy::redact).serialize(serializer)
+}
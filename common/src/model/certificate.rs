@@ -0,0 +1,194 @@
+use chrono::{DateTime, TimeZone, Utc};
+use rustls_pemfile::Item;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use x509_parser::{extensions::GeneralName, parse_x509_certificate};
+
+use crate::exception::{
+    ApiError, ApiFieldError, ERR_INVALID_REQUEST, ERR_INVALID_VALUE, ERR_MIN_SIZE,
+    ERR_REQUIRED_FIELD,
+};
+
+// certificate the gateway presents to the clients asking for one of its hostnames by sni.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Certificate {
+    pub id: i64,
+    pub name: String,
+    // pem chain, the certificate of the hostnames first.
+    pub certificate: String,
+    // sealed pem key, only the gateway opens it.
+    #[serde(skip)]
+    pub private_key: String,
+    // dns names of the certificate, taken from it when it's saved.
+    pub hostnames: Vec<String>,
+    pub not_after: DateTime<Utc>,
+    // presented when no certificate matches the hostname.
+    pub is_default: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateReq {
+    pub name: Option<String>,
+    pub certificate: Option<String>,
+    pub private_key: Option<String>,
+    pub is_default: Option<bool>,
+}
+
+// what the gateway needs to know of a certificate besides the chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateDetails {
+    pub hostnames: Vec<String>,
+    pub not_after: DateTime<Utc>,
+}
+
+impl Certificate {
+    // what the private key of a certificate is sealed with, it only opens on its row.
+    pub fn sealing_context(id_certificate: i64) -> String {
+        format!("tb_certificate:{}:private_key", id_certificate)
+    }
+}
+
+impl CertificateDetails {
+    // the hostnames are the dns alternative names, or the common name without them.
+    pub fn parse(pem: &str) -> Option<CertificateDetails> {
+        let chain = rustls_pemfile::certs(&mut pem.as_bytes()).ok()?;
+        let (_, certificate) = parse_x509_certificate(chain.first()?).ok()?;
+
+        let mut hostnames: Vec<String> = match certificate.subject_alternative_name() {
+            Ok(Some(alt_names)) => alt_names
+                .value
+                .general_names
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::DNSName(name) => Some(name.to_ascii_lowercase()),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        if hostnames.is_empty() {
+            hostnames.extend(
+                certificate
+                    .subject()
+                    .iter_common_name()
+                    .filter_map(|common_name| common_name.as_str().ok())
+                    .map(str::to_ascii_lowercase),
+            );
+        }
+
+        let not_after = Utc
+            .timestamp_opt(certificate.validity().not_after.timestamp(), 0)
+            .single()?;
+
+        Some(CertificateDetails {
+            hostnames,
+            not_after,
+        })
+    }
+}
+
+// der of the first pkcs#8, rsa or ec key of a pem.
+pub fn parse_private_key(pem: &str) -> Option<Vec<u8>> {
+    let mut reader = pem.as_bytes();
+    while let Ok(Some(item)) = rustls_pemfile::read_one(&mut reader) {
+        if let Item::PKCS8Key(key) | Item::RSAKey(key) | Item::ECKey(key) = item {
+            return Some(key);
+        }
+    }
+    None
+}
+
+impl CertificateReq {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if let Err(error) = self.validate_name(true) {
+            field_errors.push(error);
+        }
+
+        field_errors.append(&mut self.validate_pems(true));
+
+        if !field_errors.is_empty() {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                field_errors,
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_updating(&self) -> Result<(), ApiError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if let Err(error) = self.validate_name(false) {
+            field_errors.push(error);
+        }
+
+        field_errors.append(&mut self.validate_pems(false));
+
+        if !field_errors.is_empty() {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                field_errors,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn validate_name(&self, is_required: bool) -> Result<(), ApiFieldError> {
+        match &self.name {
+            Some(name) if name.len() < 3 => Err(ApiFieldError::new_with_min_size(
+                ERR_MIN_SIZE,
+                "certificate.name".to_owned(),
+                3,
+            )),
+            Some(_) => Ok(()),
+            None if is_required => Err(ApiFieldError::new(
+                ERR_REQUIRED_FIELD,
+                "certificate.name".to_owned(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    // the certificate is replaced with its key, a renewal can't keep the old one.
+    fn validate_pems(&self, is_required: bool) -> Vec<ApiFieldError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+        let is_required = is_required || self.certificate.is_some() || self.private_key.is_some();
+
+        match &self.certificate {
+            Some(certificate) => match CertificateDetails::parse(certificate) {
+                Some(details) if !details.hostnames.is_empty() => {}
+                _ => field_errors.push(ApiFieldError::new(
+                    ERR_INVALID_VALUE,
+                    "certificate.certificate".to_owned(),
+                )),
+            },
+            None if is_required => field_errors.push(ApiFieldError::new(
+                ERR_REQUIRED_FIELD,
+                "certificate.certificate".to_owned(),
+            )),
+            None => {}
+        }
+
+        match &self.private_key {
+            Some(private_key) if parse_private_key(private_key).is_none() => field_errors.push(
+                ApiFieldError::new(ERR_INVALID_VALUE, "certificate.privateKey".to_owned()),
+            ),
+            Some(_) => {}
+            None if is_required => field_errors.push(ApiFieldError::new(
+                ERR_REQUIRED_FIELD,
+                "certificate.privateKey".to_owned(),
+            )),
+            None => {}
+        }
+
+        field_errors
+    }
+}
@@ -0,0 +1,245 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::FromRow;
+
+use crate::{
+    exception::{
+        ApiError, ApiFieldError, ERR_INVALID_REQUEST, ERR_INVALID_VALUE, ERR_MIN_SIZE,
+        ERR_REQUIRED_FIELD,
+    },
+    model::RateLimitPolicy,
+};
+
+// characters of a key kept in clear to tell the keys of a consumer apart.
+pub const API_KEY_PREFIX_LENGTH: usize = 8;
+
+// who is calling the gateway, it authenticates with one of its api keys or with a client
+// certificate identified by the subject.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Consumer {
+    pub id: i64,
+    pub name: String,
+    // requests allowed per period to the consumer on every route, see RateLimitPolicy.
+    pub rate_limit: Option<Value>,
+    // common name or alternative name of its client certificate.
+    pub certificate_subject: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsumerReq {
+    pub name: Option<String>,
+    pub rate_limit: Option<Value>,
+    // an empty one removes it.
+    pub certificate_subject: Option<String>,
+}
+
+// only the sha-256 of the key is stored, the key itself is shown once when it's created.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsumerApiKey {
+    pub id: i64,
+    pub id_consumer: i64,
+    #[serde(skip)]
+    pub key_hash: String,
+    pub key_prefix: String,
+    // the key is valid forever when empty.
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsumerApiKeyReq {
+    pub id_consumer: Option<i64>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+// a new key replaces the current one, which keeps working during the grace period.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsumerApiKeyRotationReq {
+    pub expires_at: Option<DateTime<Utc>>,
+    pub grace_period_seconds: Option<i64>,
+}
+
+// answer of the creation and rotation, the only one with the key.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsumerApiKeyCreated {
+    #[serde(flatten)]
+    pub api_key: ConsumerApiKey,
+    pub key: String,
+}
+
+impl ConsumerReq {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if let Err(error) = self.validate_name(true) {
+            field_errors.push(error);
+        }
+
+        field_errors.append(&mut self.validate_rate_limit());
+        field_errors.append(&mut self.validate_certificate_subject());
+
+        if !field_errors.is_empty() {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                field_errors,
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_updating(&self) -> Result<(), ApiError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if let Err(error) = self.validate_name(false) {
+            field_errors.push(error);
+        }
+
+        field_errors.append(&mut self.validate_rate_limit());
+        field_errors.append(&mut self.validate_certificate_subject());
+
+        if !field_errors.is_empty() {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                field_errors,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn validate_name(&self, is_required: bool) -> Result<(), ApiFieldError> {
+        match &self.name {
+            Some(name) if name.len() < 3 => Err(ApiFieldError::new_with_min_size(
+                ERR_MIN_SIZE,
+                "consumer.name".to_owned(),
+                3,
+            )),
+            Some(_) => Ok(()),
+            None if is_required => Err(ApiFieldError::new(
+                ERR_REQUIRED_FIELD,
+                "consumer.name".to_owned(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn validate_rate_limit(&self) -> Vec<ApiFieldError> {
+        match &self.rate_limit {
+            Some(rate_limit) => RateLimitPolicy::validate(rate_limit, "consumer.rateLimit"),
+            None => Vec::new(),
+        }
+    }
+
+    fn validate_certificate_subject(&self) -> Vec<ApiFieldError> {
+        match &self.certificate_subject {
+            Some(subject) if !subject.is_empty() && subject.trim() != subject => {
+                vec![ApiFieldError::new(
+                    ERR_INVALID_VALUE,
+                    "consumer.certificateSubject".to_owned(),
+                )]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl ConsumerApiKeyReq {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if self.id_consumer.is_none() {
+            field_errors.push(ApiFieldError::new(
+                ERR_REQUIRED_FIELD,
+                "apiKey.idConsumer".to_owned(),
+            ));
+        }
+
+        if let Err(error) = validate_expires_at(self.expires_at) {
+            field_errors.push(error);
+        }
+
+        if !field_errors.is_empty() {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                field_errors,
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_updating(&self) -> Result<(), ApiError> {
+        if let Err(error) = validate_expires_at(self.expires_at) {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                vec![error],
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl ConsumerApiKeyRotationReq {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if let Err(error) = validate_expires_at(self.expires_at) {
+            field_errors.push(error);
+        }
+
+        if matches!(self.grace_period_seconds, Some(grace_period) if grace_period < 0) {
+            field_errors.push(ApiFieldError::new(
+                ERR_INVALID_VALUE,
+                "apiKey.gracePeriodSeconds".to_owned(),
+            ));
+        }
+
+        if !field_errors.is_empty() {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                field_errors,
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl ConsumerApiKey {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+// a key that is already expired would never be accepted.
+fn validate_expires_at(expires_at: Option<DateTime<Utc>>) -> Result<(), ApiFieldError> {
+    match expires_at {
+        Some(expires_at) if expires_at <= Utc::now() => Err(ApiFieldError::new(
+            ERR_INVALID_VALUE,
+            "apiKey.expiresAt".to_owned(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+// keys are random enough for a fast hash, the admin api stores it and the gateway looks it up.
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+pub fn api_key_prefix(key: &str) -> String {
+    key.chars().take(API_KEY_PREFIX_LENGTH).collect()
+}
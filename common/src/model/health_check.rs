@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::exception::{ApiFieldError, ERR_INVALID_VALUE};
+
+pub const DEFAULT_HEALTH_CHECK_INTERVAL_MS: i64 = 10_000;
+pub const DEFAULT_HEALTH_CHECK_TIMEOUT_MS: i64 = 2_000;
+pub const DEFAULT_HEALTHY_THRESHOLD: i32 = 2;
+pub const DEFAULT_UNHEALTHY_THRESHOLD: i32 = 3;
+pub const DEFAULT_PASSIVE_UNHEALTHY_THRESHOLD: i32 = 5;
+pub const DEFAULT_PASSIVE_EJECTION_MS: i64 = 30_000;
+// probes are scheduled once a second, shorter intervals can't be honored.
+pub const MIN_HEALTH_CHECK_INTERVAL_MS: i64 = 1_000;
+
+// health checking of the targets of an upstream, stored as json. without it every target
+// receives requests.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct HealthCheck {
+    pub active: Option<ActiveHealthCheck>,
+    pub passive: Option<PassiveHealthCheck>,
+}
+
+// periodic probe of every target, consecutive results move it between healthy and unhealthy.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ActiveHealthCheck {
+    pub path: String,
+    pub interval_ms: Option<i64>,
+    pub timeout_ms: Option<i64>,
+    // any 2xx or 3xx when empty.
+    pub expected_statuses: Option<Vec<u16>>,
+    pub healthy_threshold: Option<i32>,
+    pub unhealthy_threshold: Option<i32>,
+}
+
+// live traffic ejects a target after consecutive 5xx or connection errors. it comes back after
+// the ejection time, or through the active probes when there are any.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PassiveHealthCheck {
+    pub unhealthy_threshold: Option<i32>,
+    pub ejection_ms: Option<i64>,
+}
+
+impl HealthCheck {
+    pub fn parse(value: &Value) -> Result<HealthCheck, serde_json::Error> {
+        HealthCheck::deserialize(value)
+    }
+
+    pub fn validate(value: &Value, field: &str) -> Vec<ApiFieldError> {
+        let invalid =
+            |name: &str| ApiFieldError::new(ERR_INVALID_VALUE, format!("{}.{}", field, name));
+
+        let health_check = match HealthCheck::parse(value) {
+            Ok(health_check) => health_check,
+            Err(_) => return vec![ApiFieldError::new(ERR_INVALID_VALUE, field.to_owned())],
+        };
+
+        let mut field_errors = Vec::<ApiFieldError>::new();
+        if let Some(active) = &health_check.active {
+            if !active.path.starts_with('/') {
+                field_errors.push(invalid("active.path"));
+            }
+
+            if matches!(active.interval_ms, Some(interval) if interval < MIN_HEALTH_CHECK_INTERVAL_MS)
+            {
+                field_errors.push(invalid("active.intervalMs"));
+            }
+
+            if matches!(active.timeout_ms, Some(timeout) if timeout <= 0) {
+                field_errors.push(invalid("active.timeoutMs"));
+            }
+
+            if active
+                .expected_statuses
+                .iter()
+                .flatten()
+                .any(|status| !(100..=599).contains(status))
+            {
+                field_errors.push(invalid("active.expectedStatuses"));
+            }
+
+            for (name, value) in [
+                ("healthyThreshold", active.healthy_threshold),
+                ("unhealthyThreshold", active.unhealthy_threshold),
+            ] {
+                if matches!(value, Some(value) if value < 1) {
+                    field_errors.push(invalid(&format!("active.{}", name)));
+                }
+            }
+        }
+
+        if let Some(passive) = &health_check.passive {
+            if matches!(passive.unhealthy_threshold, Some(threshold) if threshold < 1) {
+                field_errors.push(invalid("passive.unhealthyThreshold"));
+            }
+
+            if matches!(passive.ejection_ms, Some(ejection) if ejection <= 0) {
+                field_errors.push(invalid("passive.ejectionMs"));
+            }
+        }
+
+        field_errors
+    }
+}
+
+impl ActiveHealthCheck {
+    pub fn interval(&self) -> Duration {
+        duration(
+            self.interval_ms
+                .unwrap_or(DEFAULT_HEALTH_CHECK_INTERVAL_MS),
+        )
+    }
+
+    pub fn timeout(&self) -> Duration {
+        duration(self.timeout_ms.unwrap_or(DEFAULT_HEALTH_CHECK_TIMEOUT_MS))
+    }
+
+    pub fn expects(&self, status: u16) -> bool {
+        match &self.expected_statuses {
+            Some(expected_statuses) => expected_statuses.contains(&status),
+            None => (200..400).contains(&status),
+        }
+    }
+
+    pub fn healthy_threshold(&self) -> i32 {
+        self.healthy_threshold.unwrap_or(DEFAULT_HEALTHY_THRESHOLD)
+    }
+
+    pub fn unhealthy_threshold(&self) -> i32 {
+        self.unhealthy_threshold
+            .unwrap_or(DEFAULT_UNHEALTHY_THRESHOLD)
+    }
+}
+
+impl PassiveHealthCheck {
+    pub fn unhealthy_threshold(&self) -> i32 {
+        self.unhealthy_threshold
+            .unwrap_or(DEFAULT_PASSIVE_UNHEALTHY_THRESHOLD)
+    }
+
+    pub fn ejection(&self) -> Duration {
+        duration(self.ejection_ms.unwrap_or(DEFAULT_PASSIVE_EJECTION_MS))
+    }
+}
+
+fn duration(millis: i64) -> Duration {
+    Duration::from_millis(millis.max(0) as u64)
+}
@@ -1,7 +1,39 @@
 mod application;
+mod application_orchestration;
+mod application_orchestration_route;
+mod application_route;
+mod application_workflow;
+mod auth_policy;
+mod certificate;
+mod consumer;
+mod health_check;
 mod pagination;
+mod path_pattern;
+mod rate_limit;
+mod upstream;
+mod upstream_credentials;
+mod upstream_policy;
+mod upstream_target;
+mod upstream_tls;
 mod custom_type;
+mod validation;
 
 pub use application::*;
+pub use application_orchestration::*;
+pub use application_orchestration_route::*;
+pub use application_route::*;
+pub use application_workflow::*;
+pub use auth_policy::*;
+pub use certificate::*;
+pub use consumer::*;
+pub use health_check::*;
 pub use pagination::*;
-pub use custom_type::*;
\ No newline at end of file
+pub use path_pattern::*;
+pub use rate_limit::*;
+pub use upstream::*;
+pub use upstream_credentials::*;
+pub use upstream_policy::*;
+pub use upstream_target::*;
+pub use upstream_tls::*;
+pub use custom_type::*;
+pub(crate) use validation::*;
\ No newline at end of file
@@ -0,0 +1,120 @@
+use regex::Regex;
+
+use crate::exception::{ApiError, ERR_INVALID_PATH_PATTERN};
+
+#[derive(Debug, Clone)]
+pub enum PathSegment {
+    Literal(String),
+    // ":id" or ":id(\d+)", the regex has to match the whole segment.
+    Param { name: String, regex: Option<Regex> },
+    // "*" matches any single segment.
+    Wildcard,
+    // "**" or "**name" matches one or more segments, it can only be the last one.
+    MultiWildcard(Option<String>),
+}
+
+impl PathSegment {
+    // how specific a segment is when two patterns match the same path.
+    pub fn rank(&self) -> u8 {
+        match self {
+            PathSegment::Literal(_) => 4,
+            PathSegment::Param { regex: Some(_), .. } => 3,
+            PathSegment::Param { regex: None, .. } => 2,
+            PathSegment::Wildcard => 1,
+            PathSegment::MultiWildcard(_) => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PathPattern {
+    pub segments: Vec<PathSegment>,
+}
+
+impl PathPattern {
+    pub fn parse(path: &str) -> Result<PathPattern, ApiError> {
+        let mut segments = Vec::new();
+
+        for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+            if let Some(PathSegment::MultiWildcard(_)) = segments.last() {
+                return Err(ApiError::new(ERR_INVALID_PATH_PATTERN));
+            }
+
+            segments.push(PathPattern::parse_segment(segment)?);
+        }
+
+        Ok(PathPattern { segments })
+    }
+
+    fn parse_segment(segment: &str) -> Result<PathSegment, ApiError> {
+        if segment == "*" {
+            return Ok(PathSegment::Wildcard);
+        }
+
+        if let Some(name) = segment.strip_prefix("**") {
+            return if name.is_empty() {
+                Ok(PathSegment::MultiWildcard(None))
+            } else if is_param_name(name) {
+                Ok(PathSegment::MultiWildcard(Some(name.to_owned())))
+            } else {
+                Err(ApiError::new(ERR_INVALID_PATH_PATTERN))
+            };
+        }
+
+        if let Some(param) = segment.strip_prefix(':') {
+            let (name, regex) = match param.find('(') {
+                Some(start) if param.ends_with(')') => {
+                    let regex =
+                        Regex::new(&format!("^(?:{})$", &param[start + 1..param.len() - 1]))
+                            .map_err(|_| ApiError::new(ERR_INVALID_PATH_PATTERN))?;
+                    (&param[..start], Some(regex))
+                }
+                Some(_) => return Err(ApiError::new(ERR_INVALID_PATH_PATTERN)),
+                None => (param, None),
+            };
+
+            if !is_param_name(name) {
+                return Err(ApiError::new(ERR_INVALID_PATH_PATTERN));
+            }
+
+            return Ok(PathSegment::Param {
+                name: name.to_owned(),
+                regex,
+            });
+        }
+
+        if segment.contains('*') {
+            return Err(ApiError::new(ERR_INVALID_PATH_PATTERN));
+        }
+
+        Ok(PathSegment::Literal(segment.to_owned()))
+    }
+
+    // "/orders" + "/:id" = "/orders/:id".
+    pub fn join(&self, other: &PathPattern) -> Result<PathPattern, ApiError> {
+        if let Some(PathSegment::MultiWildcard(_)) = self.segments.last() {
+            if !other.segments.is_empty() {
+                return Err(ApiError::new(ERR_INVALID_PATH_PATTERN));
+            }
+        }
+
+        let mut segments = self.segments.clone();
+        segments.extend(other.segments.iter().cloned());
+        Ok(PathPattern { segments })
+    }
+
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+}
+
+fn is_param_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
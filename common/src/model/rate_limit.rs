@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::exception::{ApiFieldError, ERR_INVALID_VALUE};
+
+pub const RATE_LIMIT_PERIOD_SECOND: &str = "second";
+pub const RATE_LIMIT_PERIOD_MINUTE: &str = "minute";
+pub const RATE_LIMIT_PERIOD_HOUR: &str = "hour";
+pub const RATE_LIMIT_ALGORITHM_SLIDING_WINDOW: &str = "sliding_window";
+pub const RATE_LIMIT_ALGORITHM_TOKEN_BUCKET: &str = "token_bucket";
+// the requests of every client count against the same limit.
+pub const RATE_LIMIT_BY_ALL: &str = "all";
+pub const RATE_LIMIT_BY_CLIENT: &str = "client";
+// the consumer of the api key or the subject of the token, the client for anonymous requests.
+pub const RATE_LIMIT_BY_CONSUMER: &str = "consumer";
+
+const RATE_LIMIT_PERIODS: [&str; 3] = [
+    RATE_LIMIT_PERIOD_SECOND,
+    RATE_LIMIT_PERIOD_MINUTE,
+    RATE_LIMIT_PERIOD_HOUR,
+];
+const RATE_LIMIT_ALGORITHMS: [&str; 2] = [
+    RATE_LIMIT_ALGORITHM_SLIDING_WINDOW,
+    RATE_LIMIT_ALGORITHM_TOKEN_BUCKET,
+];
+const RATE_LIMIT_BYS: [&str; 3] = [
+    RATE_LIMIT_BY_ALL,
+    RATE_LIMIT_BY_CLIENT,
+    RATE_LIMIT_BY_CONSUMER,
+];
+
+// limits of an application, route or consumer, stored as json. a request goes through when it
+// fits in every limit of its application, of its route and of its consumer.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RateLimitPolicy {
+    pub limits: Vec<RateLimit>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RateLimit {
+    // requests allowed in each period.
+    pub requests: i64,
+    pub period: String,
+    // sliding_window when empty.
+    pub algorithm: Option<String>,
+    // requests a token bucket can take at once, the requests of a period when empty.
+    pub burst: Option<i64>,
+    // all when empty.
+    pub by: Option<String>,
+}
+
+impl RateLimitPolicy {
+    pub fn parse(value: &Value) -> Result<RateLimitPolicy, serde_json::Error> {
+        RateLimitPolicy::deserialize(value)
+    }
+
+    pub fn validate(value: &Value, field: &str) -> Vec<ApiFieldError> {
+        let policy = match RateLimitPolicy::parse(value) {
+            Ok(policy) => policy,
+            Err(_) => return vec![ApiFieldError::new(ERR_INVALID_VALUE, field.to_owned())],
+        };
+
+        let mut field_errors = Vec::<ApiFieldError>::new();
+        for (index, limit) in policy.limits.iter().enumerate() {
+            let invalid = |name: &str| {
+                ApiFieldError::new(
+                    ERR_INVALID_VALUE,
+                    format!("{}.limits[{}].{}", field, index, name),
+                )
+            };
+
+            if limit.requests < 1 {
+                field_errors.push(invalid("requests"));
+            }
+
+            if !RATE_LIMIT_PERIODS.contains(&limit.period.as_str()) {
+                field_errors.push(invalid("period"));
+            }
+
+            if matches!(&limit.algorithm, Some(algorithm) if !RATE_LIMIT_ALGORITHMS.contains(&algorithm.as_str()))
+            {
+                field_errors.push(invalid("algorithm"));
+            }
+
+            if matches!(limit.burst, Some(burst) if burst < 1) {
+                field_errors.push(invalid("burst"));
+            }
+
+            if matches!(&limit.by, Some(by) if !RATE_LIMIT_BYS.contains(&by.as_str())) {
+                field_errors.push(invalid("by"));
+            }
+        }
+
+        field_errors
+    }
+}
+
+impl RateLimit {
+    pub fn period(&self) -> Duration {
+        match self.period.as_str() {
+            RATE_LIMIT_PERIOD_HOUR => Duration::from_secs(3600),
+            RATE_LIMIT_PERIOD_MINUTE => Duration::from_secs(60),
+            _ => Duration::from_secs(1),
+        }
+    }
+
+    pub fn algorithm(&self) -> &str {
+        self.algorithm
+            .as_deref()
+            .unwrap_or(RATE_LIMIT_ALGORITHM_SLIDING_WINDOW)
+    }
+
+    pub fn burst(&self) -> i64 {
+        self.burst.unwrap_or(self.requests)
+    }
+
+    pub fn by(&self) -> &str {
+        self.by.as_deref().unwrap_or(RATE_LIMIT_BY_ALL)
+    }
+}
@@ -0,0 +1,201 @@
+use chrono::{DateTime, Utc};
+use hyper::header::HeaderName;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+
+use crate::exception::{
+    ApiError, ApiFieldError, ERR_INVALID_REQUEST, ERR_INVALID_VALUE, ERR_MIN_SIZE,
+    ERR_REQUIRED_FIELD,
+};
+
+use super::{HealthCheck, UpstreamCredentials, UpstreamTls};
+
+// how the gateway picks the target of each request.
+pub const ALGORITHM_ROUND_ROBIN: &str = "ROUND_ROBIN";
+pub const ALGORITHM_WEIGHTED_ROUND_ROBIN: &str = "WEIGHTED_ROUND_ROBIN";
+pub const ALGORITHM_LEAST_CONNECTIONS: &str = "LEAST_CONNECTIONS";
+pub const ALGORITHM_RANDOM_TWO_CHOICES: &str = "RANDOM_TWO_CHOICES";
+pub const ALGORITHM_CONSISTENT_HASH: &str = "CONSISTENT_HASH";
+
+// what the consistent hash is computed on, hash_key names the header or the cookie.
+pub const HASH_ON_HEADER: &str = "HEADER";
+pub const HASH_ON_COOKIE: &str = "COOKIE";
+pub const HASH_ON_IP: &str = "IP";
+
+const ALGORITHMS: [&str; 5] = [
+    ALGORITHM_ROUND_ROBIN,
+    ALGORITHM_WEIGHTED_ROUND_ROBIN,
+    ALGORITHM_LEAST_CONNECTIONS,
+    ALGORITHM_RANDOM_TWO_CHOICES,
+    ALGORITHM_CONSISTENT_HASH,
+];
+
+// group of targets running the same service, applications and routes point at it instead of
+// a single host.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Upstream {
+    pub id: i64,
+    pub name: String,
+    pub algorithm: String,
+    pub hash_on: Option<String>,
+    pub hash_key: Option<String>,
+    pub health_check: Option<Value>,
+    pub credentials_type: Option<String>,
+    // sealed, it never leaves the database through the admin api.
+    #[serde(skip_serializing)]
+    pub credentials: Option<String>,
+    pub tls: Option<Value>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpstreamReq {
+    pub name: Option<String>,
+    pub algorithm: Option<String>,
+    pub hash_on: Option<String>,
+    pub hash_key: Option<String>,
+    pub health_check: Option<Value>,
+    pub credentials: Option<Value>,
+    pub tls: Option<Value>,
+}
+
+impl UpstreamReq {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if let Err(error) = self.validate_name(true) {
+            field_errors.push(error);
+        }
+
+        field_errors.append(&mut self.validate_balancing(None));
+        field_errors.append(&mut self.validate_health_check());
+        field_errors.append(&mut self.validate_credentials());
+        field_errors.append(&mut self.validate_tls());
+
+        if !field_errors.is_empty() {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                field_errors,
+            ));
+        }
+
+        Ok(())
+    }
+
+    // the balancing fields are checked together with the ones already saved.
+    pub fn validate_updating(&self, current: &Upstream) -> Result<(), ApiError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if let Err(error) = self.validate_name(false) {
+            field_errors.push(error);
+        }
+
+        field_errors.append(&mut self.validate_balancing(Some(current)));
+        field_errors.append(&mut self.validate_health_check());
+        field_errors.append(&mut self.validate_credentials());
+        field_errors.append(&mut self.validate_tls());
+
+        if !field_errors.is_empty() {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                field_errors,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn validate_name(&self, is_required: bool) -> Result<(), ApiFieldError> {
+        match &self.name {
+            Some(name) if name.len() < 3 => Err(ApiFieldError::new_with_min_size(
+                ERR_MIN_SIZE,
+                "upstream.name".to_owned(),
+                3,
+            )),
+            Some(_) => Ok(()),
+            None if is_required => Err(ApiFieldError::new(
+                ERR_REQUIRED_FIELD,
+                "upstream.name".to_owned(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn validate_balancing(&self, current: Option<&Upstream>) -> Vec<ApiFieldError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        let algorithm = self
+            .algorithm
+            .as_deref()
+            .or(current.map(|current| current.algorithm.as_str()))
+            .unwrap_or(ALGORITHM_ROUND_ROBIN);
+        if !ALGORITHMS.contains(&algorithm) {
+            field_errors.push(ApiFieldError::new(
+                ERR_INVALID_VALUE,
+                "upstream.algorithm".to_owned(),
+            ));
+        }
+
+        let hash_on = self
+            .hash_on
+            .as_deref()
+            .or(current.and_then(|current| current.hash_on.as_deref()));
+        let hash_key = self
+            .hash_key
+            .as_deref()
+            .or(current.and_then(|current| current.hash_key.as_deref()));
+
+        match hash_on {
+            None if algorithm == ALGORITHM_CONSISTENT_HASH => field_errors.push(
+                ApiFieldError::new(ERR_REQUIRED_FIELD, "upstream.hashOn".to_owned()),
+            ),
+            None | Some(HASH_ON_IP) => {}
+            Some(HASH_ON_HEADER) | Some(HASH_ON_COOKIE) => match hash_key {
+                None => field_errors.push(ApiFieldError::new(
+                    ERR_REQUIRED_FIELD,
+                    "upstream.hashKey".to_owned(),
+                )),
+                Some(hash_key)
+                    if hash_on == Some(HASH_ON_HEADER)
+                        && HeaderName::try_from(hash_key).is_err() =>
+                {
+                    field_errors.push(ApiFieldError::new(
+                        ERR_INVALID_VALUE,
+                        "upstream.hashKey".to_owned(),
+                    ))
+                }
+                Some(_) => {}
+            },
+            Some(_) => field_errors.push(ApiFieldError::new(
+                ERR_INVALID_VALUE,
+                "upstream.hashOn".to_owned(),
+            )),
+        }
+
+        field_errors
+    }
+    fn validate_health_check(&self) -> Vec<ApiFieldError> {
+        match &self.health_check {
+            Some(health_check) => HealthCheck::validate(health_check, "upstream.healthCheck"),
+            None => Vec::new(),
+        }
+    }
+
+    fn validate_credentials(&self) -> Vec<ApiFieldError> {
+        match &self.credentials {
+            Some(credentials) => UpstreamCredentials::validate(credentials, "upstream.credentials"),
+            None => Vec::new(),
+        }
+    }
+
+    fn validate_tls(&self) -> Vec<ApiFieldError> {
+        match &self.tls {
+            Some(tls) => UpstreamTls::validate(tls, "upstream.tls"),
+            None => Vec::new(),
+        }
+    }
+}
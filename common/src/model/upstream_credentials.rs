@@ -0,0 +1,132 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use hyper::header::{HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::exception::{ApiFieldError, ERR_INVALID_URL, ERR_INVALID_VALUE, ERR_REQUIRED_FIELD};
+
+use super::valid_url;
+
+// how the gateway authenticates itself to the targets of an upstream.
+pub const CREDENTIALS_HEADERS: &str = "HEADERS";
+pub const CREDENTIALS_BASIC: &str = "BASIC";
+pub const CREDENTIALS_OAUTH2_CLIENT_CREDENTIALS: &str = "OAUTH2_CLIENT_CREDENTIALS";
+
+const DEFAULT_REFRESH_BEFORE: u64 = 30;
+
+// credentials injected into every request sent to an upstream. they're stored sealed, only the
+// type is ever shown by the admin api.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct UpstreamCredentials {
+    #[serde(rename = "type")]
+    pub credentials_type: String,
+    // static headers, by name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+    // the token is fetched again this long before it expires.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_before_seconds: Option<u64>,
+}
+
+impl UpstreamCredentials {
+    // what the credentials of an upstream are sealed with, they only open on its row.
+    pub fn sealing_context(id_upstream: i64) -> String {
+        format!("tb_upstream:{}:credentials", id_upstream)
+    }
+
+    pub fn parse(value: &Value) -> Result<UpstreamCredentials, serde_json::Error> {
+        UpstreamCredentials::deserialize(value)
+    }
+
+    // an empty object removes the credentials of the upstream.
+    pub fn is_removal(value: &Value) -> bool {
+        value.as_object().is_some_and(|fields| fields.is_empty())
+    }
+
+    pub fn refresh_before(&self) -> Duration {
+        Duration::from_secs(
+            self.refresh_before_seconds
+                .unwrap_or(DEFAULT_REFRESH_BEFORE),
+        )
+    }
+
+    pub fn validate(value: &Value, field: &str) -> Vec<ApiFieldError> {
+        if UpstreamCredentials::is_removal(value) {
+            return Vec::new();
+        }
+
+        let credentials = match UpstreamCredentials::parse(value) {
+            Ok(credentials) => credentials,
+            Err(_) => return vec![ApiFieldError::new(ERR_INVALID_VALUE, field.to_owned())],
+        };
+
+        let required =
+            |name: &str| ApiFieldError::new(ERR_REQUIRED_FIELD, format!("{}.{}", field, name));
+        let mut field_errors = Vec::<ApiFieldError>::new();
+        match credentials.credentials_type.as_str() {
+            CREDENTIALS_HEADERS => {
+                if credentials.headers.is_empty() {
+                    field_errors.push(required("headers"));
+                }
+
+                if !credentials.headers.iter().all(|(name, value)| {
+                    HeaderName::try_from(name.as_str()).is_ok()
+                        && HeaderValue::try_from(value.as_str()).is_ok()
+                }) {
+                    field_errors.push(ApiFieldError::new(
+                        ERR_INVALID_VALUE,
+                        format!("{}.headers", field),
+                    ));
+                }
+            }
+            CREDENTIALS_BASIC => {
+                if credentials.username.is_none() {
+                    field_errors.push(required("username"));
+                }
+
+                if credentials.password.is_none() {
+                    field_errors.push(required("password"));
+                }
+            }
+            CREDENTIALS_OAUTH2_CLIENT_CREDENTIALS => {
+                match &credentials.token_url {
+                    Some(token_url) if !valid_url(token_url) => field_errors.push(
+                        ApiFieldError::new(ERR_INVALID_URL, format!("{}.tokenUrl", field)),
+                    ),
+                    Some(_) => {}
+                    None => field_errors.push(required("tokenUrl")),
+                }
+
+                if credentials.client_id.is_none() {
+                    field_errors.push(required("clientId"));
+                }
+
+                if credentials.client_secret.is_none() {
+                    field_errors.push(required("clientSecret"));
+                }
+            }
+            _ => field_errors.push(ApiFieldError::new(
+                ERR_INVALID_VALUE,
+                format!("{}.type", field),
+            )),
+        }
+
+        field_errors
+    }
+}
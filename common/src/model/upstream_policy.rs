@@ -0,0 +1,259 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use hyper::{
+    header::{HeaderName, HeaderValue},
+    Method,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::exception::{ApiFieldError, ERR_INVALID_VALUE};
+
+pub const RETRY_MAX_ATTEMPTS: i32 = 10;
+pub const DEFAULT_RETRY_BASE_BACKOFF_MS: i64 = 50;
+pub const DEFAULT_RETRY_MAX_BACKOFF_MS: i64 = 1000;
+pub const DEFAULT_RETRY_STATUS_CODES: [u16; 3] = [502, 503, 504];
+pub const DEFAULT_RETRY_METHODS: [&str; 6] = ["GET", "HEAD", "OPTIONS", "PUT", "DELETE", "TRACE"];
+pub const DEFAULT_CIRCUIT_FAILURE_RATE: i32 = 50;
+pub const DEFAULT_CIRCUIT_CONSECUTIVE_FAILURES: i32 = 5;
+pub const DEFAULT_CIRCUIT_MINIMUM_REQUESTS: i32 = 20;
+pub const DEFAULT_CIRCUIT_WINDOW_MS: i64 = 10_000;
+pub const DEFAULT_CIRCUIT_OPEN_MS: i64 = 30_000;
+pub const DEFAULT_CIRCUIT_HALF_OPEN_REQUESTS: i32 = 1;
+
+// timeouts and retries towards the upstream, stored as json in applications, workflows and
+// routes. what a route leaves empty comes from its workflow, then from its application.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct UpstreamPolicy {
+    pub connect_timeout_ms: Option<i64>,
+    // wait for the response headers of each attempt.
+    pub response_timeout_ms: Option<i64>,
+    // every attempt and backoff included, up to the end of the response body.
+    pub total_timeout_ms: Option<i64>,
+    pub retry: Option<RetryPolicy>,
+    pub circuit_breaker: Option<CircuitBreakerPolicy>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RetryPolicy {
+    // the first attempt included.
+    pub max_attempts: i32,
+    pub base_backoff_ms: Option<i64>,
+    pub max_backoff_ms: Option<i64>,
+    pub status_codes: Option<Vec<u16>>,
+    // other methods are retried only with an idempotency-key header.
+    pub methods: Option<Vec<String>>,
+}
+
+// the breaker of an upstream opens when the failure rate over the window or the consecutive
+// failures reach their threshold, it lets trial requests through after a while.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CircuitBreakerPolicy {
+    // percentage of failed requests.
+    pub failure_rate: Option<i32>,
+    pub consecutive_failures: Option<i32>,
+    // requests in the window before the failure rate is taken into account.
+    pub minimum_requests: Option<i32>,
+    pub window_ms: Option<i64>,
+    pub open_ms: Option<i64>,
+    pub half_open_requests: Option<i32>,
+    // answered while the circuit is open, instead of an error.
+    pub fallback: Option<FallbackResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FallbackResponse {
+    pub status: Option<u16>,
+    pub headers: Option<BTreeMap<String, String>>,
+    // sent as json.
+    pub body: Option<Value>,
+}
+
+impl UpstreamPolicy {
+    pub fn parse(value: &Value) -> Result<UpstreamPolicy, serde_json::Error> {
+        UpstreamPolicy::deserialize(value)
+    }
+
+    // the fields of self win, the missing ones come from the parent.
+    pub fn merge(&self, parent: &UpstreamPolicy) -> UpstreamPolicy {
+        UpstreamPolicy {
+            connect_timeout_ms: self.connect_timeout_ms.or(parent.connect_timeout_ms),
+            response_timeout_ms: self.response_timeout_ms.or(parent.response_timeout_ms),
+            total_timeout_ms: self.total_timeout_ms.or(parent.total_timeout_ms),
+            retry: self.retry.clone().or_else(|| parent.retry.clone()),
+            circuit_breaker: self
+                .circuit_breaker
+                .clone()
+                .or_else(|| parent.circuit_breaker.clone()),
+        }
+    }
+
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout_ms.map(duration)
+    }
+
+    pub fn response_timeout(&self) -> Option<Duration> {
+        self.response_timeout_ms.map(duration)
+    }
+
+    pub fn total_timeout(&self) -> Option<Duration> {
+        self.total_timeout_ms.map(duration)
+    }
+
+    pub fn validate(value: &Value, field: &str) -> Vec<ApiFieldError> {
+        let invalid =
+            |name: &str| ApiFieldError::new(ERR_INVALID_VALUE, format!("{}.{}", field, name));
+
+        let policy = match UpstreamPolicy::parse(value) {
+            Ok(policy) => policy,
+            Err(_) => return vec![ApiFieldError::new(ERR_INVALID_VALUE, field.to_owned())],
+        };
+
+        let mut field_errors = Vec::<ApiFieldError>::new();
+        for (name, timeout) in [
+            ("connectTimeoutMs", policy.connect_timeout_ms),
+            ("responseTimeoutMs", policy.response_timeout_ms),
+            ("totalTimeoutMs", policy.total_timeout_ms),
+        ] {
+            if matches!(timeout, Some(timeout) if timeout <= 0) {
+                field_errors.push(invalid(name));
+            }
+        }
+
+        if let Some(retry) = &policy.retry {
+            if !(1..=RETRY_MAX_ATTEMPTS).contains(&retry.max_attempts) {
+                field_errors.push(invalid("retry.maxAttempts"));
+            }
+
+            if matches!(retry.base_backoff_ms, Some(backoff) if backoff < 0) {
+                field_errors.push(invalid("retry.baseBackoffMs"));
+            }
+
+            if matches!(retry.max_backoff_ms, Some(backoff) if backoff < retry.base_backoff()) {
+                field_errors.push(invalid("retry.maxBackoffMs"));
+            }
+
+            if retry
+                .status_codes
+                .iter()
+                .flatten()
+                .any(|status| !(100..=599).contains(status))
+            {
+                field_errors.push(invalid("retry.statusCodes"));
+            }
+
+            if retry
+                .methods
+                .iter()
+                .flatten()
+                .any(|method| Method::from_bytes(method.as_bytes()).is_err())
+            {
+                field_errors.push(invalid("retry.methods"));
+            }
+        }
+
+        if let Some(circuit_breaker) = &policy.circuit_breaker {
+            if matches!(circuit_breaker.failure_rate, Some(rate) if !(1..=100).contains(&rate)) {
+                field_errors.push(invalid("circuitBreaker.failureRate"));
+            }
+
+            for (name, value) in [
+                ("consecutiveFailures", circuit_breaker.consecutive_failures),
+                ("minimumRequests", circuit_breaker.minimum_requests),
+                ("halfOpenRequests", circuit_breaker.half_open_requests),
+            ] {
+                if matches!(value, Some(value) if value < 1) {
+                    field_errors.push(invalid(&format!("circuitBreaker.{}", name)));
+                }
+            }
+
+            for (name, value) in [
+                ("windowMs", circuit_breaker.window_ms),
+                ("openMs", circuit_breaker.open_ms),
+            ] {
+                if matches!(value, Some(value) if value <= 0) {
+                    field_errors.push(invalid(&format!("circuitBreaker.{}", name)));
+                }
+            }
+
+            if let Some(fallback) = &circuit_breaker.fallback {
+                if matches!(fallback.status, Some(status) if !(100..=599).contains(&status)) {
+                    field_errors.push(invalid("circuitBreaker.fallback.status"));
+                }
+
+                if fallback.headers.iter().flatten().any(|(name, value)| {
+                    HeaderName::try_from(name.as_str()).is_err()
+                        || HeaderValue::try_from(value.as_str()).is_err()
+                }) {
+                    field_errors.push(invalid("circuitBreaker.fallback.headers"));
+                }
+            }
+        }
+
+        field_errors
+    }
+}
+
+impl RetryPolicy {
+    pub fn base_backoff(&self) -> i64 {
+        self.base_backoff_ms
+            .unwrap_or(DEFAULT_RETRY_BASE_BACKOFF_MS)
+    }
+
+    pub fn max_backoff(&self) -> i64 {
+        self.max_backoff_ms
+            .unwrap_or(DEFAULT_RETRY_MAX_BACKOFF_MS)
+            .max(self.base_backoff())
+    }
+
+    pub fn retries_status(&self, status: u16) -> bool {
+        match &self.status_codes {
+            Some(status_codes) => status_codes.contains(&status),
+            None => DEFAULT_RETRY_STATUS_CODES.contains(&status),
+        }
+    }
+
+    pub fn retries_method(&self, method: &Method) -> bool {
+        match &self.methods {
+            Some(methods) => methods.iter().any(|name| name == method.as_str()),
+            None => DEFAULT_RETRY_METHODS.contains(&method.as_str()),
+        }
+    }
+}
+
+impl CircuitBreakerPolicy {
+    pub fn failure_rate(&self) -> i32 {
+        self.failure_rate.unwrap_or(DEFAULT_CIRCUIT_FAILURE_RATE)
+    }
+
+    pub fn consecutive_failures(&self) -> i32 {
+        self.consecutive_failures
+            .unwrap_or(DEFAULT_CIRCUIT_CONSECUTIVE_FAILURES)
+    }
+
+    pub fn minimum_requests(&self) -> i32 {
+        self.minimum_requests
+            .unwrap_or(DEFAULT_CIRCUIT_MINIMUM_REQUESTS)
+    }
+
+    pub fn window(&self) -> Duration {
+        duration(self.window_ms.unwrap_or(DEFAULT_CIRCUIT_WINDOW_MS))
+    }
+
+    pub fn open_duration(&self) -> Duration {
+        duration(self.open_ms.unwrap_or(DEFAULT_CIRCUIT_OPEN_MS))
+    }
+
+    pub fn half_open_requests(&self) -> i32 {
+        self.half_open_requests
+            .unwrap_or(DEFAULT_CIRCUIT_HALF_OPEN_REQUESTS)
+    }
+}
+
+fn duration(millis: i64) -> Duration {
+    Duration::from_millis(millis.max(0) as u64)
+}
@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use hyper::Uri;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::exception::{
+    ApiError, ApiFieldError, ERR_INVALID_REQUEST, ERR_INVALID_URL, ERR_INVALID_VALUE,
+    ERR_REQUIRED_FIELD,
+};
+
+pub const DEFAULT_TARGET_WEIGHT: i32 = 100;
+pub const MAX_TARGET_WEIGHT: i32 = 1000;
+
+// a replica of the upstream. its scheme and authority replace the ones of the destination of
+// the application or route, the path of the destination is kept.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct UpstreamTarget {
+    pub id: i64,
+    pub id_upstream: i64,
+    // "http://10.0.0.12:8080", without a path.
+    pub url: String,
+    // share of the requests compared to the other targets, 0 drains the target.
+    pub weight: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpstreamTargetReq {
+    pub id_upstream: Option<i64>,
+    pub url: Option<String>,
+    pub weight: Option<i32>,
+}
+
+impl UpstreamTargetReq {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if self.id_upstream.is_none() {
+            field_errors.push(ApiFieldError::new(
+                ERR_REQUIRED_FIELD,
+                "target.idUpstream".to_owned(),
+            ));
+        }
+
+        if let Err(error) = self.validate_url(true) {
+            field_errors.push(error);
+        }
+
+        if let Err(error) = self.validate_weight() {
+            field_errors.push(error);
+        }
+
+        if !field_errors.is_empty() {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                field_errors,
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_updating(&self) -> Result<(), ApiError> {
+        let mut field_errors = Vec::<ApiFieldError>::new();
+
+        if let Err(error) = self.validate_url(false) {
+            field_errors.push(error);
+        }
+
+        if let Err(error) = self.validate_weight() {
+            field_errors.push(error);
+        }
+
+        if !field_errors.is_empty() {
+            return Err(ApiError::new_with_field_errors(
+                ERR_INVALID_REQUEST,
+                field_errors,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn validate_url(&self, is_required: bool) -> Result<(), ApiFieldError> {
+        match &self.url {
+            Some(url) if !is_target_url(url) => Err(ApiFieldError::new(
+                ERR_INVALID_URL,
+                "target.url".to_owned(),
+            )),
+            Some(_) => Ok(()),
+            None if is_required => Err(ApiFieldError::new(
+                ERR_REQUIRED_FIELD,
+                "target.url".to_owned(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn validate_weight(&self) -> Result<(), ApiFieldError> {
+        match self.weight {
+            Some(weight) if !(0..=MAX_TARGET_WEIGHT).contains(&weight) => Err(
+                ApiFieldError::new(ERR_INVALID_VALUE, "target.weight".to_owned()),
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn is_target_url(url: &str) -> bool {
+    match url.parse::<Uri>() {
+        Ok(uri) => {
+            matches!(uri.scheme_str(), Some("http") | Some("https"))
+                && uri.authority().is_some()
+                && matches!(uri.path(), "" | "/")
+                && uri.query().is_none()
+        }
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::exception::{ApiFieldError, ERR_INVALID_VALUE, ERR_REQUIRED_FIELD};
+
+// tls of the connections to the targets of an upstream, stored as json. the files are read by
+// the gateway.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct UpstreamTls {
+    // pem bundle of the cas trusted instead of the system ones.
+    pub ca_file: Option<String>,
+    // pem certificate and pkcs#8 key the gateway presents to the targets.
+    pub cert_file: Option<String>,
+    pub key_file: Option<String>,
+}
+
+impl UpstreamTls {
+    pub fn parse(value: &Value) -> Result<UpstreamTls, serde_json::Error> {
+        UpstreamTls::deserialize(value)
+    }
+
+    // an empty object removes the tls of the upstream.
+    pub fn is_removal(value: &Value) -> bool {
+        value.as_object().is_some_and(|fields| fields.is_empty())
+    }
+
+    pub fn validate(value: &Value, field: &str) -> Vec<ApiFieldError> {
+        let tls = match UpstreamTls::parse(value) {
+            Ok(tls) => tls,
+            Err(_) => return vec![ApiFieldError::new(ERR_INVALID_VALUE, field.to_owned())],
+        };
+
+        let mut field_errors = Vec::<ApiFieldError>::new();
+        for (name, file) in [
+            ("caFile", &tls.ca_file),
+            ("certFile", &tls.cert_file),
+            ("keyFile", &tls.key_file),
+        ] {
+            if matches!(file, Some(file) if file.trim().is_empty()) {
+                field_errors.push(ApiFieldError::new(
+                    ERR_INVALID_VALUE,
+                    format!("{}.{}", field, name),
+                ));
+            }
+        }
+
+        // the certificate goes with its key.
+        match (&tls.cert_file, &tls.key_file) {
+            (Some(_), None) => field_errors.push(ApiFieldError::new(
+                ERR_REQUIRED_FIELD,
+                format!("{}.keyFile", field),
+            )),
+            (None, Some(_)) => field_errors.push(ApiFieldError::new(
+                ERR_REQUIRED_FIELD,
+                format!("{}.certFile", field),
+            )),
+            _ => {}
+        }
+
+        field_errors
+    }
+}
@@ -0,0 +1,26 @@
+use hyper::Uri;
+
+use crate::template::Template;
+
+// urls may have templates like "{{request.path.id}}", they're checked with a sample value.
+pub(crate) fn is_absolute_http_url(url: &str) -> bool {
+    let url =
+        match Template::parse(url).and_then(|template| template.render(|_| Ok("x".to_owned()))) {
+            Ok(url) => url,
+            Err(_) => return false,
+        };
+
+    match url.parse::<Uri>() {
+        Ok(uri) => {
+            matches!(uri.scheme_str(), Some("http") | Some("https")) && uri.authority().is_some()
+        }
+        Err(_) => false,
+    }
+}
+
+// an url without templates, like the endpoints the gateway calls by itself.
+pub(crate) fn valid_url(url: &str) -> bool {
+    Uri::try_from(url).is_ok_and(|uri| {
+        matches!(uri.scheme_str(), Some("http" | "https")) && uri.authority().is_some()
+    })
+}
@@ -0,0 +1,3 @@
+mod routing_notification;
+
+pub use routing_notification::*;
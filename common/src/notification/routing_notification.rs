@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+// channel used by the admin api to tell the gateways that the routing tables have changed.
+pub const ROUTING_CHANNEL: &str = "anothergtw_routing";
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutingNotification {
+    pub entity: String,
+    pub id: i64,
+    pub operation: String,
+}
+
+impl RoutingNotification {
+    pub fn new(entity: &str, id: i64, operation: &str) -> RoutingNotification {
+        RoutingNotification {
+            entity: entity.to_owned(),
+            id,
+            operation: operation.to_owned(),
+        }
+    }
+
+    // a failed notification must not fail the request that changed the row, the
+    // gateways also reload their routing tables periodically.
+    pub async fn publish(&self, pg_pool: &PgPool) {
+        let payload = match serde_json::to_string(self) {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::error!("Error when serializing a routing notification: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = sqlx::query("select pg_notify($1, $2)")
+            .bind(ROUTING_CHANNEL)
+            .bind(payload)
+            .execute(pg_pool)
+            .await
+        {
+            tracing::error!("Error when publishing a routing notification: {}", e);
+        }
+    }
+}
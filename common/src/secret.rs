@@ -0,0 +1,83 @@
+#[cfg(test)]
+#[path = "secret_test.rs"]
+mod secret_test;
+
+use std::sync::Arc;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    error::Unspecified,
+    rand::{SecureRandom, SystemRandom},
+};
+
+const SEALED_PREFIX: &str = "v1:";
+
+// secrets kept in the database are sealed with aes-256-gcm. the admin api and the gateway share
+// the key, a random nonce goes in front of every sealed value. the context names the row and the
+// column of the secret, a sealed value copied to another row can't be opened.
+#[derive(Debug, Clone)]
+pub struct SecretCipher {
+    key: Arc<LessSafeKey>,
+}
+
+impl SecretCipher {
+    // CREDENTIALS_ENCRYPTION_KEY has the 32 bytes of the key in base64. there is no default, the
+    // operator supplies it and without it no secret can be stored or read.
+    pub fn config() -> Option<SecretCipher> {
+        let key = match std::env::var("CREDENTIALS_ENCRYPTION_KEY") {
+            Ok(key) if !key.trim().is_empty() => key,
+            _ => {
+                tracing::warn!(
+                    "CREDENTIALS_ENCRYPTION_KEY is not set, secrets can't be stored or read"
+                );
+                return None;
+            }
+        };
+        let cipher = SecretCipher::from_base64(&key);
+        if cipher.is_none() {
+            tracing::error!("CREDENTIALS_ENCRYPTION_KEY must be 32 bytes in base64");
+        }
+        cipher
+    }
+
+    pub fn from_base64(key: &str) -> Option<SecretCipher> {
+        let key = STANDARD.decode(key.trim()).ok()?;
+        let key = UnboundKey::new(&AES_256_GCM, &key).ok()?;
+        Some(SecretCipher {
+            key: Arc::new(LessSafeKey::new(key)),
+        })
+    }
+
+    // "v1:" and the nonce with the ciphertext in base64.
+    pub fn seal(&self, secret: &str, context: &str) -> Result<String, Unspecified> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new().fill(&mut nonce)?;
+
+        let mut sealed = secret.as_bytes().to_vec();
+        self.key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(context.as_bytes()),
+            &mut sealed,
+        )?;
+
+        let mut value = nonce.to_vec();
+        value.append(&mut sealed);
+        Ok(format!("{}{}", SEALED_PREFIX, STANDARD.encode(value)))
+    }
+
+    pub fn open(&self, sealed: &str, context: &str) -> Result<String, Unspecified> {
+        let value = sealed.strip_prefix(SEALED_PREFIX).ok_or(Unspecified)?;
+        let mut value = STANDARD.decode(value).map_err(|_| Unspecified)?;
+        if value.len() < NONCE_LEN {
+            return Err(Unspecified);
+        }
+
+        let mut sealed = value.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&value)?;
+        let secret = self
+            .key
+            .open_in_place(nonce, Aad::from(context.as_bytes()), &mut sealed)?;
+        String::from_utf8(secret.to_vec()).map_err(|_| Unspecified)
+    }
+}
@@ -0,0 +1,49 @@
+use super::SecretCipher;
+
+const KEY: &str = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=";
+const OTHER_KEY: &str = "ZmVkY2JhOTg3NjU0MzIxMGZlZGNiYTk4NzY1NDMyMTA=";
+const CONTEXT: &str = "tb_upstream:1:credentials";
+
+#[test]
+fn seal_and_open() {
+    let cipher = SecretCipher::from_base64(KEY).unwrap();
+
+    let sealed = cipher.seal("s3cr3t", CONTEXT).unwrap();
+    assert!(sealed.starts_with("v1:"));
+    assert!(!sealed.contains("s3cr3t"));
+    assert_ne!(sealed, cipher.seal("s3cr3t", CONTEXT).unwrap());
+    assert_eq!(cipher.open(&sealed, CONTEXT).unwrap(), "s3cr3t");
+}
+
+#[test]
+fn open_tampered_or_foreign() {
+    let cipher = SecretCipher::from_base64(KEY).unwrap();
+    let sealed = cipher.seal("s3cr3t", CONTEXT).unwrap();
+
+    let other = SecretCipher::from_base64(OTHER_KEY).unwrap();
+    assert!(other.open(&sealed, CONTEXT).is_err());
+
+    let mut tampered = sealed.clone().into_bytes();
+    tampered[20] = if tampered[20] == b'A' { b'B' } else { b'A' };
+    assert!(cipher
+        .open(&String::from_utf8(tampered).unwrap(), CONTEXT)
+        .is_err());
+
+    assert!(cipher.open("s3cr3t", CONTEXT).is_err());
+    assert!(cipher.open("v1:AAAA", CONTEXT).is_err());
+}
+
+#[test]
+fn open_with_another_context() {
+    let cipher = SecretCipher::from_base64(KEY).unwrap();
+    let sealed = cipher.seal("s3cr3t", CONTEXT).unwrap();
+
+    assert!(cipher.open(&sealed, "tb_upstream:2:credentials").is_err());
+    assert!(cipher.open(&sealed, "").is_err());
+}
+
+#[test]
+fn from_base64_invalid() {
+    assert!(SecretCipher::from_base64("short").is_none());
+    assert!(SecretCipher::from_base64("c2hvcnQ=").is_none());
+}
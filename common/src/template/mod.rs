@@ -0,0 +1,3 @@
+mod template_parser;
+
+pub use template_parser::*;
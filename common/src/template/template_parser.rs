@@ -0,0 +1,95 @@
+#[cfg(test)]
+#[path = "template_parser_test.rs"]
+mod template_parser_test;
+
+use crate::exception::{ApiError, TEMPLATE_ERR_INVALID};
+
+pub const TEMPLATE_REQUEST: &str = "request";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplatePart {
+    Literal(String),
+    Reference(Vec<String>),
+}
+
+// "/orders?customer={{user.id}}" references the field id of the response kept under "user".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<TemplatePart>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Template, ApiError> {
+        let mut parts = Vec::new();
+        let mut remaining = source;
+
+        while let Some(start) = remaining.find("{{") {
+            if start > 0 {
+                parts.push(TemplatePart::Literal(remaining[..start].to_owned()));
+            }
+
+            let expression = &remaining[start + 2..];
+            let end = expression
+                .find("}}")
+                .ok_or_else(|| ApiError::new(TEMPLATE_ERR_INVALID))?;
+
+            parts.push(TemplatePart::Reference(Template::parse_reference(
+                &expression[..end],
+            )?));
+            remaining = &expression[end + 2..];
+        }
+
+        if !remaining.is_empty() {
+            parts.push(TemplatePart::Literal(remaining.to_owned()));
+        }
+
+        Ok(Template { parts })
+    }
+
+    fn parse_reference(expression: &str) -> Result<Vec<String>, ApiError> {
+        let reference: Vec<String> = expression
+            .trim()
+            .split('.')
+            .map(|segment| segment.to_owned())
+            .collect();
+
+        let is_valid = reference.iter().all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        });
+
+        if is_valid {
+            Ok(reference)
+        } else {
+            Err(ApiError::new(TEMPLATE_ERR_INVALID))
+        }
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.references().next().is_none()
+    }
+
+    pub fn references(&self) -> impl Iterator<Item = &[String]> {
+        self.parts.iter().filter_map(|part| match part {
+            TemplatePart::Reference(reference) => Some(reference.as_slice()),
+            TemplatePart::Literal(_) => None,
+        })
+    }
+
+    pub fn render<F>(&self, mut resolve: F) -> Result<String, ApiError>
+    where
+        F: FnMut(&[String]) -> Result<String, ApiError>,
+    {
+        let mut rendered = String::new();
+        for part in &self.parts {
+            match part {
+                TemplatePart::Literal(literal) => rendered.push_str(literal),
+                TemplatePart::Reference(reference) => rendered.push_str(&resolve(reference)?),
+            }
+        }
+
+        Ok(rendered)
+    }
+}
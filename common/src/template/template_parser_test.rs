@@ -0,0 +1,58 @@
+use crate::exception::TEMPLATE_ERR_REQUEST_REFERENCE;
+
+use super::*;
+
+#[test]
+fn parse() {
+    let template =
+        Template::parse("/orders?customer={{ user.id }}&page={{request.query.page}}").unwrap();
+
+    assert!(!template.is_static());
+
+    let references: Vec<&[String]> = template.references().collect();
+    assert_eq!(2, references.len());
+    assert_eq!(["user", "id"], references[0]);
+    assert_eq!(["request", "query", "page"], references[1]);
+}
+
+#[test]
+fn parse_static() {
+    let template = Template::parse("/orders/}}").unwrap();
+
+    assert!(template.is_static());
+    assert_eq!(
+        "/orders/}}",
+        template.render(|_| Ok(String::new())).unwrap()
+    );
+}
+
+#[test]
+fn parse_invalid() {
+    assert_eq!(
+        TEMPLATE_ERR_INVALID.0,
+        Template::parse("/orders/{{user.id").unwrap_err().code
+    );
+    assert_eq!(
+        TEMPLATE_ERR_INVALID.0,
+        Template::parse("/orders/{{}}").unwrap_err().code
+    );
+    assert_eq!(
+        TEMPLATE_ERR_INVALID.0,
+        Template::parse("/orders/{{user..id}}").unwrap_err().code
+    );
+    assert_eq!(
+        TEMPLATE_ERR_INVALID.0,
+        Template::parse("/orders/{{user id}}").unwrap_err().code
+    );
+}
+
+#[test]
+fn render() {
+    let template = Template::parse("{{user.id}}-{{user.name}}").unwrap();
+
+    let rendered = template.render(|reference| Ok(reference.join(":")));
+    assert_eq!("user:id-user:name", rendered.unwrap());
+
+    let rendered = template.render(|_| Err(ApiError::new(TEMPLATE_ERR_REQUEST_REFERENCE)));
+    assert_eq!(TEMPLATE_ERR_REQUEST_REFERENCE.0, rendered.unwrap_err().code);
+}
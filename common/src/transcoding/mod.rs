@@ -0,0 +1,3 @@
+mod transcoding_descriptor;
+
+pub use transcoding_descriptor::*;
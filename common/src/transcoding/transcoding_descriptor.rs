@@ -0,0 +1,172 @@
+#[cfg(test)]
+#[path = "transcoding_descriptor_test.rs"]
+mod transcoding_descriptor_test;
+
+use hyper::Method;
+use prost_reflect::{DescriptorPool, DynamicMessage, MethodDescriptor, Value};
+
+use crate::{
+    exception::{ApiError, ROU_ERR_INVALID_DESCRIPTOR},
+    model::{PathPattern, PathSegment},
+};
+
+const HTTP_RULE_EXTENSION: &str = "google.api.http";
+const HTTP_RULE_METHODS: [(&str, Method); 5] = [
+    ("get", Method::GET),
+    ("put", Method::PUT),
+    ("post", Method::POST),
+    ("delete", Method::DELETE),
+    ("patch", Method::PATCH),
+];
+
+// one binding of a google.api.http rule, "body" and "response_body" name a field of the
+// request and of the response, "*" or nothing for the whole message.
+#[derive(Debug, Clone)]
+pub struct TranscodingRule {
+    pub http_method: Method,
+    pub path: PathPattern,
+    pub body: Option<String>,
+    pub response_body: Option<String>,
+    pub method: MethodDescriptor,
+}
+
+impl TranscodingRule {
+    // "/greeter.v1.Greeter/SayHello".
+    pub fn grpc_path(&self) -> String {
+        format!(
+            "/{}/{}",
+            self.method.parent_service().full_name(),
+            self.method.name()
+        )
+    }
+}
+
+// a protobuf descriptor set, compiled with --include_imports so the http rules can be read.
+#[derive(Debug, Clone)]
+pub struct TranscodingDescriptor {
+    pub rules: Vec<TranscodingRule>,
+}
+
+impl TranscodingDescriptor {
+    pub fn decode(bytes: &[u8]) -> Result<TranscodingDescriptor, ApiError> {
+        let pool = DescriptorPool::decode(bytes).map_err(|e| {
+            tracing::info!("Error when decoding a descriptor set: {}", e);
+            ApiError::new(ROU_ERR_INVALID_DESCRIPTOR)
+        })?;
+        let extension = pool
+            .get_extension_by_name(HTTP_RULE_EXTENSION)
+            .ok_or_else(|| ApiError::new(ROU_ERR_INVALID_DESCRIPTOR))?;
+
+        let mut rules = Vec::new();
+        for service in pool.services() {
+            for method in service.methods() {
+                let options = method.options();
+                if !options.has_extension(&extension) {
+                    continue;
+                }
+
+                if let Value::Message(rule) = options.get_extension(&extension).as_ref() {
+                    add_rules(rule, &method, &mut rules);
+                    for binding in list_field(rule, "additional_bindings") {
+                        if let Value::Message(binding) = binding {
+                            add_rules(&binding, &method, &mut rules);
+                        }
+                    }
+                }
+            }
+        }
+
+        if rules.is_empty() {
+            return Err(ApiError::new(ROU_ERR_INVALID_DESCRIPTOR));
+        }
+
+        Ok(TranscodingDescriptor { rules })
+    }
+}
+
+fn string_field(message: &DynamicMessage, name: &str) -> Option<String> {
+    message
+        .get_field_by_name(name)
+        .and_then(|value| value.as_str().map(str::to_owned))
+        .filter(|value| !value.is_empty())
+}
+
+fn list_field(message: &DynamicMessage, name: &str) -> Vec<Value> {
+    message
+        .get_field_by_name(name)
+        .and_then(|value| value.as_list().map(<[Value]>::to_vec))
+        .unwrap_or_default()
+}
+
+fn add_rules(rule: &DynamicMessage, method: &MethodDescriptor, rules: &mut Vec<TranscodingRule>) {
+    let mut patterns: Vec<(Method, String)> = HTTP_RULE_METHODS
+        .iter()
+        .filter(|(name, _)| rule.has_field_by_name(name))
+        .filter_map(|(name, http_method)| {
+            string_field(rule, name).map(|path| (http_method.clone(), path))
+        })
+        .collect();
+
+    if rule.has_field_by_name("custom") {
+        let custom = rule.get_field_by_name("custom");
+        if let Some(Value::Message(custom)) = custom.as_deref() {
+            let kind = string_field(custom, "kind")
+                .and_then(|kind| Method::from_bytes(kind.as_bytes()).ok());
+            if let Some((kind, path)) = kind.zip(string_field(custom, "path")) {
+                patterns.push((kind, path));
+            }
+        }
+    }
+
+    for (http_method, path) in patterns {
+        match parse_path_template(&path) {
+            Some(pattern) => rules.push(TranscodingRule {
+                http_method,
+                path: pattern,
+                body: string_field(rule, "body"),
+                response_body: string_field(rule, "response_body"),
+                method: method.clone(),
+            }),
+            None => tracing::warn!(
+                "Ignoring the unsupported http rule {} of {}",
+                path,
+                method.full_name()
+            ),
+        }
+    }
+}
+
+// "/v1/{name=shelves/*}" style templates, only whole segment variables are supported:
+// "{field}", "{field=*}" and a trailing "{field=**}".
+pub fn parse_path_template(template: &str) -> Option<PathPattern> {
+    let path = template.strip_prefix('/')?;
+    let mut segments = Vec::new();
+
+    for segment in path.split('/') {
+        let segment = match segment {
+            "" => return None,
+            "*" => PathSegment::Wildcard,
+            "**" => PathSegment::MultiWildcard(None),
+            _ if segment.starts_with('{') && segment.ends_with('}') => {
+                let variable = &segment[1..segment.len() - 1];
+                match variable.split_once('=') {
+                    None | Some((_, "*")) => PathSegment::Param {
+                        name: variable.split('=').next()?.to_owned(),
+                        regex: None,
+                    },
+                    Some((name, "**")) => PathSegment::MultiWildcard(Some(name.to_owned())),
+                    Some(_) => return None,
+                }
+            }
+            _ if segment.contains(['{', '}', '*']) => return None,
+            _ => PathSegment::Literal(segment.to_owned()),
+        };
+
+        if let Some(PathSegment::MultiWildcard(_)) = segments.last() {
+            return None;
+        }
+        segments.push(segment);
+    }
+
+    Some(PathPattern { segments })
+}
@@ -0,0 +1,75 @@
+use super::*;
+
+const GREETER: &[u8] = include_bytes!("../../fixtures/greeter.pb");
+
+fn rule<'a>(
+    descriptor: &'a TranscodingDescriptor,
+    http_method: Method,
+    grpc_path: &str,
+) -> &'a TranscodingRule {
+    descriptor
+        .rules
+        .iter()
+        .find(|rule| rule.http_method == http_method && rule.grpc_path() == grpc_path)
+        .unwrap()
+}
+
+#[test]
+fn decode() {
+    let descriptor = TranscodingDescriptor::decode(GREETER).unwrap();
+    // the additional binding is a rule of its own, the method without http rule has none.
+    assert_eq!(4, descriptor.rules.len());
+
+    let say_hello = rule(&descriptor, Method::GET, "/greeter.v1.Greeter/SayHello");
+    assert_eq!(2, say_hello.path.len());
+    assert!(say_hello.body.is_none());
+
+    let create = rule(
+        &descriptor,
+        Method::POST,
+        "/greeter.v1.Greeter/CreateGreeting",
+    );
+    assert_eq!(Some("greeting"), create.body.as_deref());
+
+    let update = rule(
+        &descriptor,
+        Method::PUT,
+        "/greeter.v1.Greeter/CreateGreeting",
+    );
+    assert!(matches!(
+        &update.path.segments[1],
+        PathSegment::Param { name, .. } if name == "greeting.id"
+    ));
+
+    let get = rule(&descriptor, Method::GET, "/greeter.v1.Greeter/GetGreeting");
+    assert_eq!(Some("text"), get.response_body.as_deref());
+}
+
+#[test]
+fn decode_invalid() {
+    let api_error = TranscodingDescriptor::decode(b"not a descriptor set").unwrap_err();
+    assert_eq!(ROU_ERR_INVALID_DESCRIPTOR.0, api_error.code);
+
+    // a valid set without any http rule.
+    let api_error = TranscodingDescriptor::decode(&[]).unwrap_err();
+    assert_eq!(ROU_ERR_INVALID_DESCRIPTOR.0, api_error.code);
+}
+
+#[test]
+fn parse_template() {
+    let pattern = parse_path_template("/v1/{name}/items/*/{path=**}").unwrap();
+    assert_eq!(5, pattern.len());
+    assert!(matches!(&pattern.segments[0], PathSegment::Literal(literal) if literal == "v1"));
+    assert!(matches!(&pattern.segments[1], PathSegment::Param { name, .. } if name == "name"));
+    assert!(matches!(&pattern.segments[3], PathSegment::Wildcard));
+    assert!(matches!(
+        &pattern.segments[4],
+        PathSegment::MultiWildcard(Some(name)) if name == "path"
+    ));
+
+    assert!(parse_path_template("/v1/{name=*}").is_some());
+    assert!(parse_path_template("v1/items").is_none());
+    assert!(parse_path_template("/v1/{name=shelves/*}").is_none());
+    assert!(parse_path_template("/v1/**/items").is_none());
+    assert!(parse_path_template("/v1/item{id}").is_none());
+}
@@ -0,0 +1,8 @@
+--liquibase formatted sql
+
+--changeset johny:1
+alter table anothergtw.tb_application add column path varchar(255);
+alter table anothergtw.tb_application add column url_destination varchar(255) not null default '';
+update anothergtw.tb_application set path = '/application-' || id where path is null;
+alter table anothergtw.tb_application alter column path set not null;
+alter table anothergtw.tb_application alter column url_destination drop default;
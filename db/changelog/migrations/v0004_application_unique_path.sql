@@ -0,0 +1,4 @@
+--liquibase formatted sql
+
+--changeset johny:1
+alter table anothergtw.tb_application add constraint uq_ta_path unique (path);
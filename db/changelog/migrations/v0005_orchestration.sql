@@ -0,0 +1,4 @@
+--liquibase formatted sql
+
+--changeset johny:1
+alter table anothergtw.tb_application_orchestration_route add constraint uq_taor_id_application_orchestration_response_key unique (id_application_orchestration, response_key);
@@ -0,0 +1,5 @@
+--liquibase formatted sql
+
+--changeset johny:1
+alter table anothergtw.tb_application_orchestration_route add column request_headers jsonb null;
+alter table anothergtw.tb_application_orchestration_route add column request_body text null;
@@ -0,0 +1,6 @@
+--liquibase formatted sql
+
+--changeset johny:1
+alter table anothergtw.tb_application_orchestration_route add column failure_policy varchar(10) not null default 'FAIL' constraint failure_policy_check check(failure_policy in ('FAIL', 'OMIT', 'FALLBACK', 'ERROR'));
+alter table anothergtw.tb_application_orchestration_route add column fallback jsonb null;
+alter table anothergtw.tb_application_orchestration_route add column timeout_ms bigint null;
@@ -0,0 +1,5 @@
+--liquibase formatted sql
+
+--changeset johny:1
+alter table anothergtw.tb_application_workflow add column priority integer not null default 0;
+alter table anothergtw.tb_application_route add column priority integer not null default 0;
@@ -0,0 +1,5 @@
+--liquibase formatted sql
+
+--changeset johny:1
+alter table anothergtw.tb_application add column websocket_max_connections integer;
+alter table anothergtw.tb_application add constraint ck_ta_websocket_max_connections check (websocket_max_connections > 0);
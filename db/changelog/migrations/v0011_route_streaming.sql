@@ -0,0 +1,4 @@
+--liquibase formatted sql
+
+--changeset johny:1
+alter table anothergtw.tb_application_route add column streaming boolean not null default false;
@@ -0,0 +1,6 @@
+--liquibase formatted sql
+
+--changeset johny:1
+alter table anothergtw.tb_application add column upstream_policy jsonb null;
+alter table anothergtw.tb_application_workflow add column upstream_policy jsonb null;
+alter table anothergtw.tb_application_route add column upstream_policy jsonb null;
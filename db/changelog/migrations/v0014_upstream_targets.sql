@@ -0,0 +1,29 @@
+--liquibase formatted sql
+
+--changeset johny:1
+create table anothergtw.tb_upstream (
+    id bigserial primary key,
+    name varchar(100) not null,
+    algorithm varchar(30) not null default 'ROUND_ROBIN' constraint algorithm_check check(algorithm in ('ROUND_ROBIN', 'WEIGHTED_ROUND_ROBIN', 'LEAST_CONNECTIONS', 'RANDOM_TWO_CHOICES', 'CONSISTENT_HASH')),
+    hash_on varchar(10) null constraint hash_on_check check(hash_on in ('HEADER', 'COOKIE', 'IP')),
+    hash_key varchar(255) null,
+    created_at timestamptz not null,
+    updated_at timestamptz not null,
+    constraint uq_tu_name unique (name)
+);
+
+--changeset johny:2
+create table anothergtw.tb_upstream_target (
+    id bigserial primary key,
+    id_upstream bigint not null,
+    url varchar(255) not null,
+    weight integer not null default 100,
+    created_at timestamptz not null,
+    updated_at timestamptz not null,
+    constraint uq_tut_id_upstream_url unique (id_upstream, url),
+    constraint fk_tut_id_upstream foreign key(id_upstream) references anothergtw.tb_upstream(id) on delete cascade
+);
+
+--changeset johny:3
+alter table anothergtw.tb_application add column id_upstream bigint null constraint fk_ta_id_upstream references anothergtw.tb_upstream(id);
+alter table anothergtw.tb_application_route add column id_upstream bigint null constraint fk_tar_id_upstream references anothergtw.tb_upstream(id);
@@ -0,0 +1,5 @@
+--liquibase formatted sql
+
+--changeset johny:1
+alter table anothergtw.tb_application add column rate_limit jsonb null;
+alter table anothergtw.tb_application_route add column rate_limit jsonb null;
@@ -0,0 +1,28 @@
+--liquibase formatted sql
+
+--changeset johny:1
+create table anothergtw.tb_consumer (
+    id bigserial primary key,
+    name varchar(100) not null,
+    rate_limit jsonb null,
+    created_at timestamptz not null,
+    updated_at timestamptz not null,
+    constraint uq_tc_name unique (name)
+);
+
+--changeset johny:2
+create table anothergtw.tb_consumer_api_key (
+    id bigserial primary key,
+    id_consumer bigint not null,
+    key_hash varchar(64) not null,
+    key_prefix varchar(12) not null,
+    expires_at timestamptz null,
+    created_at timestamptz not null,
+    updated_at timestamptz not null,
+    constraint uq_tcak_key_hash unique (key_hash),
+    constraint fk_tcak_id_consumer foreign key(id_consumer) references anothergtw.tb_consumer(id) on delete cascade
+);
+
+--changeset johny:3
+alter table anothergtw.tb_application add column auth jsonb null;
+alter table anothergtw.tb_application_route add column auth jsonb null;
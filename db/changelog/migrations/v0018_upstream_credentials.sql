@@ -0,0 +1,5 @@
+--liquibase formatted sql
+
+--changeset johny:1
+alter table anothergtw.tb_upstream add column credentials_type varchar(40) null;
+alter table anothergtw.tb_upstream add column credentials text null;
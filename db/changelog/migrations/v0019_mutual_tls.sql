@@ -0,0 +1,8 @@
+--liquibase formatted sql
+
+--changeset johny:1
+alter table anothergtw.tb_consumer add column certificate_subject varchar(255) null;
+alter table anothergtw.tb_consumer add constraint uq_tc_certificate_subject unique (certificate_subject);
+
+--changeset johny:2
+alter table anothergtw.tb_upstream add column tls jsonb null;
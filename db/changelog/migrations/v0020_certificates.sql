@@ -0,0 +1,16 @@
+--liquibase formatted sql
+
+--changeset johny:1
+create table anothergtw.tb_certificate (
+    id bigserial primary key,
+    name varchar(100) not null,
+    certificate text not null,
+    private_key text not null,
+    hostnames text[] not null,
+    not_after timestamptz not null,
+    is_default boolean not null default false,
+    created_at timestamptz not null,
+    updated_at timestamptz not null,
+    constraint uq_tcrt_name unique (name)
+);
+create unique index uq_tcrt_default on anothergtw.tb_certificate (is_default) where is_default;
@@ -1,9 +1,220 @@
-use hyper::{Client, client::HttpConnector};
+use std::{
+    collections::HashMap,
+    error::Error,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+
+use hyper::{client::HttpConnector, Client, StatusCode};
+use hyper_tls::HttpsConnector;
+
+use crate::{
+    exception::{ApiError, FORWARD_ERR_UPSTREAM_TLS},
+    model::UpstreamTls,
+    service::CertificateStore,
+};
+
+use super::Rustls;
+
+pub type HttpsClient = Client<HttpsConnector<HttpConnector>>;
+
+const DEFAULT_UPSTREAM_CONNECT_TIMEOUT: u64 = 5;
 
 pub struct HttpClient;
 
 impl HttpClient {
-    pub fn config() -> Client<HttpConnector> {
-        Client::new()
+    pub fn config() -> HttpsClient {
+        HttpClient::config_with_connect_timeout(upstream_connect_timeout(), false)
+    }
+
+    // grpc upstreams speak only http/2, with prior knowledge over plain connections and
+    // negotiated by alpn over tls.
+    pub fn config_http2() -> HttpsClient {
+        HttpClient::config_with_connect_timeout(upstream_connect_timeout(), true)
     }
-}
\ No newline at end of file
+
+    pub fn config_with_connect_timeout(connect_timeout: Duration, http2: bool) -> HttpsClient {
+        HttpClient::config_with_tls(connect_timeout, http2, None)
+            .expect("can build the tls connector")
+    }
+
+    // the cas of the upstream replace the system ones, and its certificate is presented to the
+    // targets that ask for one.
+    pub fn config_with_tls(
+        connect_timeout: Duration,
+        http2: bool,
+        upstream_tls: Option<&UpstreamTls>,
+    ) -> Result<HttpsClient, Box<dyn Error>> {
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        http.set_connect_timeout(Some(connect_timeout));
+
+        let mut tls = native_tls::TlsConnector::builder();
+        if http2 {
+            tls.request_alpns(&["h2"]);
+        }
+        if let Some(upstream_tls) = upstream_tls {
+            if let Some(ca_file) = &upstream_tls.ca_file {
+                tls.disable_built_in_roots(true);
+                for ca in Rustls::read_certs(ca_file)? {
+                    tls.add_root_certificate(native_tls::Certificate::from_der(&ca)?);
+                }
+            }
+            if let (Some(cert_file), Some(key_file)) =
+                (&upstream_tls.cert_file, &upstream_tls.key_file)
+            {
+                tls.identity(native_tls::Identity::from_pkcs8(
+                    &std::fs::read(Rustls::path(cert_file))?,
+                    &std::fs::read(Rustls::path(key_file))?,
+                )?);
+            }
+        }
+        let tls = tls.build()?;
+
+        Ok(Client::builder()
+            .http2_only(http2)
+            .build(HttpsConnector::from((http, tls.into()))))
+    }
+}
+
+// longest wait for a connection to an upstream, routes may ask for another one.
+pub fn upstream_connect_timeout() -> Duration {
+    Duration::from_secs(
+        std::env::var("UPSTREAM_CONNECT_TIMEOUT")
+            .ok()
+            .and_then(|timeout| timeout.parse().ok())
+            .unwrap_or(DEFAULT_UPSTREAM_CONNECT_TIMEOUT),
+    )
+}
+
+// the modification time and the length of each file of a tls, none for a file that can't be
+// read.
+type TlsFingerprint = Vec<Option<(SystemTime, u64)>>;
+
+// the client of a tls, with the fingerprint of its files when they were last checked.
+struct TlsClient {
+    fingerprint: TlsFingerprint,
+    checked_at: Instant,
+    client: Arc<HttpsClient>,
+}
+
+type TlsClients = HashMap<(Duration, bool, UpstreamTls), TlsClient>;
+
+// the connect timeout belongs to the connector, so there's a client for each one in use. they
+// don't share the pooled connections, but routes rarely use more than a few timeouts.
+pub struct HttpClients {
+    connect_timeout: Duration,
+    client: Arc<HttpsClient>,
+    http2_client: Arc<HttpsClient>,
+    clients: Mutex<HashMap<(Duration, bool), Arc<HttpsClient>>>,
+    // upstreams with a tls of their own, built again when one of their files changes.
+    tls_clients: Mutex<TlsClients>,
+    tls_check_interval: Duration,
+}
+
+impl HttpClients {
+    pub fn config() -> HttpClients {
+        HttpClients::config_with_tls_check_interval(CertificateStore::reload_interval())
+    }
+
+    pub fn config_with_tls_check_interval(tls_check_interval: Duration) -> HttpClients {
+        let connect_timeout = upstream_connect_timeout();
+        HttpClients {
+            connect_timeout,
+            client: Arc::new(HttpClient::config_with_connect_timeout(
+                connect_timeout,
+                false,
+            )),
+            http2_client: Arc::new(HttpClient::config_with_connect_timeout(
+                connect_timeout,
+                true,
+            )),
+            clients: Mutex::new(HashMap::new()),
+            tls_clients: Mutex::new(HashMap::new()),
+            tls_check_interval,
+        }
+    }
+
+    pub fn get(&self, connect_timeout: Option<Duration>, http2: bool) -> Arc<HttpsClient> {
+        match connect_timeout {
+            Some(connect_timeout) if connect_timeout != self.connect_timeout => Arc::clone(
+                self.clients
+                    .lock()
+                    .unwrap()
+                    .entry((connect_timeout, http2))
+                    .or_insert_with(|| {
+                        Arc::new(HttpClient::config_with_connect_timeout(
+                            connect_timeout,
+                            http2,
+                        ))
+                    }),
+            ),
+            _ if http2 => Arc::clone(&self.http2_client),
+            _ => Arc::clone(&self.client),
+        }
+    }
+
+    // a tls that can't be loaded fails the request, it's tried again by the next one. the files
+    // are checked for changes once per interval, off the lock, and read again when one of them
+    // has.
+    pub fn get_with_tls(
+        &self,
+        connect_timeout: Option<Duration>,
+        http2: bool,
+        tls: Option<&UpstreamTls>,
+    ) -> Result<Arc<HttpsClient>, ApiError> {
+        let tls = match tls {
+            Some(tls) => tls,
+            None => return Ok(self.get(connect_timeout, http2)),
+        };
+
+        let key = (
+            connect_timeout.unwrap_or(self.connect_timeout),
+            http2,
+            tls.clone(),
+        );
+        let loaded = match self.tls_clients.lock().unwrap().get_mut(&key) {
+            Some(loaded) if loaded.checked_at.elapsed() < self.tls_check_interval => {
+                return Ok(Arc::clone(&loaded.client));
+            }
+            // the next requests keep the client while this one checks the files.
+            Some(loaded) => {
+                loaded.checked_at = Instant::now();
+                Some((loaded.fingerprint.clone(), Arc::clone(&loaded.client)))
+            }
+            None => None,
+        };
+        let fingerprint = tls_fingerprint(tls);
+        if let Some((loaded_fingerprint, client)) = loaded {
+            if loaded_fingerprint == fingerprint {
+                return Ok(client);
+            }
+        }
+
+        let client = HttpClient::config_with_tls(key.0, http2, Some(tls)).map_err(|e| {
+            tracing::error!("Error when loading the tls of an upstream: {}", e);
+            ApiError::new_with_status(StatusCode::BAD_GATEWAY, FORWARD_ERR_UPSTREAM_TLS)
+        })?;
+        let client = Arc::new(client);
+        self.tls_clients.lock().unwrap().insert(
+            key,
+            TlsClient {
+                fingerprint,
+                checked_at: Instant::now(),
+                client: Arc::clone(&client),
+            },
+        );
+        Ok(client)
+    }
+}
+
+fn tls_fingerprint(tls: &UpstreamTls) -> TlsFingerprint {
+    [&tls.ca_file, &tls.cert_file, &tls.key_file]
+        .into_iter()
+        .flatten()
+        .map(|file| {
+            let metadata = std::fs::metadata(Rustls::path(file)).ok()?;
+            Some((metadata.modified().ok()?, metadata.len()))
+        })
+        .collect()
+}
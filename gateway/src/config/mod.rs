@@ -1,7 +1,9 @@
 mod db;
 mod rustls;
 mod http_client;
+mod trusted_proxies;
 
 pub use db::*;
 pub use rustls::*;
-pub use http_client::*;
\ No newline at end of file
+pub use http_client::*;
+pub use trusted_proxies::*;
\ No newline at end of file
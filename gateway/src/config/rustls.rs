@@ -1,20 +1,288 @@
-use std::path::PathBuf;
+#[cfg(test)]
+#[path = "rustls_test.rs"]
+mod rustls_test;
 
-use axum_server::tls_rustls::RustlsConfig;
+use std::{
+    collections::hash_map::DefaultHasher,
+    error::Error,
+    fs::File,
+    hash::{Hash, Hasher},
+    io,
+    io::BufReader,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{middleware::AddExtension, Extension};
+use axum_server::{
+    accept::Accept,
+    tls_rustls::{RustlsAcceptor, RustlsConfig},
+};
+use futures::future::BoxFuture;
+use rustls::{
+    server::{
+        AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient, NoClientAuth,
+        ResolvesServerCert,
+    },
+    Certificate, RootCertStore, ServerConfig,
+};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tower_layer::Layer;
+use x509_parser::{extensions::GeneralName, parse_x509_certificate};
+
+use crate::service::CertificateStore;
+
+const CLIENT_AUTH_OPTIONAL: &str = "optional";
 
 pub struct Rustls;
 
 impl Rustls {
-    pub async fn config() -> RustlsConfig {
-        RustlsConfig::from_pem_file(
-            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-                .join(std::env::var("CERTS_PATH").unwrap())
-                .join(std::env::var("CERT_FILE").unwrap()),
-            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-                .join(std::env::var("CERTS_PATH").unwrap())
-                .join(std::env::var("KEY_FILE").unwrap()),
+    // https when certificates are expected, from CERT_FILE, CERTS_DIR or with TLS_LISTENER=true
+    // for the admin api, or when the store already has some. the certificates added later are
+    // served without a restart. with a client ca the clients must present a certificate signed
+    // by it, unless CLIENT_AUTH is optional, the file is read again when it changes.
+    pub fn config(certificates: Arc<CertificateStore>) -> Option<RustlsConfig> {
+        let tls_listener = std::env::var("TLS_LISTENER")
+            .is_ok_and(|tls_listener| tls_listener.eq_ignore_ascii_case("true"));
+        if !Rustls::is_listener_tls(&certificates, tls_listener) {
+            return None;
+        }
+
+        let client_ca_file = std::env::var("CLIENT_CA_FILE").ok();
+        let client_auth_optional = std::env::var("CLIENT_AUTH")
+            .is_ok_and(|client_auth| client_auth.eq_ignore_ascii_case(CLIENT_AUTH_OPTIONAL));
+
+        let config = Rustls::server_config(
+            certificates.clone(),
+            client_ca_file.as_deref(),
+            client_auth_optional,
         )
-        .await
-        .unwrap()
+        .expect("can load the client ca of the gateway");
+        let config = RustlsConfig::from_config(Arc::new(config));
+
+        if let Some(client_ca_file) = client_ca_file {
+            let reloader = ClientCaReloader::new(
+                config.clone(),
+                certificates,
+                client_ca_file,
+                client_auth_optional,
+            );
+            ClientCaReloader::listen(Arc::new(reloader), CertificateStore::reload_interval());
+        }
+        Some(config)
+    }
+
+    pub fn is_listener_tls(certificates: &CertificateStore, tls_listener: bool) -> bool {
+        tls_listener || certificates.is_configured() || !certificates.is_empty()
+    }
+
+    // the certificate of each handshake is picked by the resolver, by sni.
+    pub fn server_config(
+        certificates: Arc<dyn ResolvesServerCert>,
+        client_ca_file: Option<&str>,
+        client_auth_optional: bool,
+    ) -> Result<ServerConfig, Box<dyn Error>> {
+        let client_verifier = match client_ca_file {
+            Some(client_ca_file) => {
+                let mut roots = RootCertStore::empty();
+                for ca in Rustls::read_certs(client_ca_file)? {
+                    roots.add(&Certificate(ca))?;
+                }
+                if client_auth_optional {
+                    AllowAnyAnonymousOrAuthenticatedClient::new(roots)
+                } else {
+                    AllowAnyAuthenticatedClient::new(roots)
+                }
+            }
+            None => NoClientAuth::new(),
+        };
+
+        let mut config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_client_cert_verifier(client_verifier)
+            .with_cert_resolver(certificates);
+        // grpc clients negotiate h2.
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Ok(config)
+    }
+
+    // relative files are looked up in CERTS_PATH.
+    pub fn path(file: &str) -> PathBuf {
+        PathBuf::from(std::env::var("CERTS_PATH").unwrap_or_else(|_| String::from("."))).join(file)
+    }
+
+    // the der of every certificate of a pem bundle.
+    pub fn read_certs(file: &str) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+        let certs = rustls_pemfile::certs(&mut BufReader::new(File::open(Rustls::path(file))?))?;
+        if certs.is_empty() {
+            return Err(format!("no certificate in {}", file).into());
+        }
+        Ok(certs)
+    }
+}
+
+// builds the server config again when the client ca file changes, the connections already
+// established keep the ca they were verified with.
+pub struct ClientCaReloader {
+    config: RustlsConfig,
+    certificates: Arc<dyn ResolvesServerCert>,
+    client_ca_file: String,
+    client_auth_optional: bool,
+    fingerprint: Mutex<Option<u64>>,
+}
+
+impl ClientCaReloader {
+    pub fn new(
+        config: RustlsConfig,
+        certificates: Arc<dyn ResolvesServerCert>,
+        client_ca_file: String,
+        client_auth_optional: bool,
+    ) -> Self {
+        let fingerprint = ClientCaReloader::fingerprint(&client_ca_file).ok();
+        ClientCaReloader {
+            config,
+            certificates,
+            client_ca_file,
+            client_auth_optional,
+            fingerprint: Mutex::new(fingerprint),
+        }
+    }
+
+    // whether the file had changed. a ca that can't be loaded leaves the current one in use.
+    pub fn reload(&self) -> Result<bool, Box<dyn Error>> {
+        let fingerprint = ClientCaReloader::fingerprint(&self.client_ca_file)?;
+        if *self.fingerprint.lock().unwrap() == Some(fingerprint) {
+            return Ok(false);
+        }
+
+        let config = Rustls::server_config(
+            Arc::clone(&self.certificates),
+            Some(&self.client_ca_file),
+            self.client_auth_optional,
+        )?;
+        self.config.reload_from_config(Arc::new(config));
+        *self.fingerprint.lock().unwrap() = Some(fingerprint);
+        Ok(true)
+    }
+
+    pub fn listen(reloader: Arc<ClientCaReloader>, reload_interval: Duration) {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(reload_interval).await;
+                match reloader.reload() {
+                    Ok(true) => tracing::info!("client ca {} reloaded", reloader.client_ca_file),
+                    Ok(false) => {}
+                    Err(e) => tracing::error!(
+                        "Error when reloading the client ca {}: {}",
+                        reloader.client_ca_file,
+                        e
+                    ),
+                }
+            }
+        });
+    }
+
+    fn fingerprint(client_ca_file: &str) -> Result<u64, Box<dyn Error>> {
+        let mut hasher = DefaultHasher::new();
+        std::fs::read(Rustls::path(client_ca_file))?.hash(&mut hasher);
+        Ok(hasher.finish())
+    }
+}
+
+// identities of a verified client certificate, the alternative names come before the common
+// name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientCertificate {
+    pub common_name: Option<String>,
+    pub alt_names: Vec<String>,
+}
+
+impl ClientCertificate {
+    pub fn parse(der: &[u8]) -> Option<ClientCertificate> {
+        let (_, certificate) = parse_x509_certificate(der).ok()?;
+        let common_name = certificate
+            .subject()
+            .iter_common_name()
+            .next()
+            .and_then(|common_name| common_name.as_str().ok())
+            .map(str::to_owned);
+        let alt_names = match certificate.subject_alternative_name() {
+            Ok(Some(alt_names)) => alt_names
+                .value
+                .general_names
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::DNSName(name)
+                    | GeneralName::URI(name)
+                    | GeneralName::RFC822Name(name) => Some(name.to_string()),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        Some(ClientCertificate {
+            common_name,
+            alt_names,
+        })
+    }
+
+    pub fn identities(&self) -> impl Iterator<Item = &str> {
+        self.alt_names
+            .iter()
+            .map(String::as_str)
+            .chain(self.common_name.as_deref())
+    }
+
+    pub fn subject(&self) -> Option<&str> {
+        self.identities().next()
+    }
+}
+
+// extension of the requests received over tls.
+#[derive(Debug, Clone, Default)]
+pub struct TlsConnection {
+    pub client_certificate: Option<Arc<ClientCertificate>>,
+}
+
+// hands the certificate the client presented in the handshake to the requests of the connection.
+#[derive(Debug, Clone)]
+pub struct ClientCertificateAcceptor {
+    inner: RustlsAcceptor,
+}
+
+impl ClientCertificateAcceptor {
+    pub fn new(inner: RustlsAcceptor) -> Self {
+        ClientCertificateAcceptor { inner }
+    }
+}
+
+impl<I, S> Accept<I, S> for ClientCertificateAcceptor
+where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    S: Send + 'static,
+{
+    type Stream = <RustlsAcceptor as Accept<I, S>>::Stream;
+    type Service = AddExtension<S, TlsConnection>;
+    type Future = BoxFuture<'static, io::Result<(Self::Stream, Self::Service)>>;
+
+    fn accept(&self, stream: I, service: S) -> Self::Future {
+        let inner = self.inner.clone();
+        Box::pin(async move {
+            let (stream, service) = inner.accept(stream, service).await?;
+            let client_certificate = stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(|certificates| certificates.first())
+                .and_then(|certificate| ClientCertificate::parse(&certificate.0))
+                .map(Arc::new);
+
+            Ok((
+                stream,
+                Extension(TlsConnection { client_certificate }).layer(service),
+            ))
+        })
     }
 }
@@ -0,0 +1,39 @@
+use std::net::IpAddr;
+
+use ipnet::IpNet;
+
+// forwarded headers sent by clients are only kept when the peer is one of these networks.
+#[derive(Debug, Default, Clone)]
+pub struct TrustedProxies {
+    networks: Vec<IpNet>,
+}
+
+impl TrustedProxies {
+    pub fn config() -> TrustedProxies {
+        TrustedProxies::parse(&std::env::var("TRUSTED_PROXIES").unwrap_or_default())
+    }
+
+    // "10.0.0.0/8, 192.168.0.1", an address without prefix is a single host.
+    pub fn parse(cidrs: &str) -> TrustedProxies {
+        let networks = cidrs
+            .split(',')
+            .map(|cidr| cidr.trim())
+            .filter(|cidr| !cidr.is_empty())
+            .filter_map(|cidr| {
+                let network = cidr
+                    .parse::<IpNet>()
+                    .or_else(|_| cidr.parse::<IpAddr>().map(IpNet::from));
+                if network.is_err() {
+                    tracing::error!("Ignoring the invalid trusted proxy {}", cidr);
+                }
+                network.ok()
+            })
+            .collect();
+
+        TrustedProxies { networks }
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        self.networks.iter().any(|network| network.contains(ip))
+    }
+}
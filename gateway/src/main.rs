@@ -1,8 +1,16 @@
 extern crate derive_more;
 extern crate serde;
 
-use crate::config::Db;
-use crate::rest::{ApplicationController, ForwardController};
+use crate::config::{ClientCertificateAcceptor, Db, Rustls};
+use crate::rest::{
+    ApplicationController, ApplicationOrchestrationController,
+    ApplicationOrchestrationRouteController, ApplicationRouteController,
+    ApplicationWorkflowController, CircuitBreakerController, ForwardController,
+    MetricsController, UpstreamHealthController,
+};
+use crate::service::{
+    CertificateStore, CircuitBreakers, HealthChecker, RoutingService, RoutingServiceTrait,
+};
 
 use axum::routing::any;
 use axum::{Json, Router};
@@ -13,7 +21,7 @@ use std::sync::Arc;
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use common::{exception, model};
+use common::{exception, model, notification, secret, template, transcoding};
 
 pub mod config;
 pub mod repository;
@@ -42,13 +50,46 @@ async fn main() {
         .init();
 
     let pg_pool = Arc::new(Db::config().await);
-    let forward_controller = ForwardController::new(Arc::clone(&pg_pool));
+
+    // loading routing table before accepting requests.
+    let routing_service = Arc::new(RoutingService::new(Arc::clone(&pg_pool)));
+    routing_service
+        .reload()
+        .await
+        .expect("can load the routing table");
+    RoutingService::listen(Arc::clone(&routing_service), Arc::clone(&pg_pool));
+
+    // certificates presented by sni, the listener is https when they're expected.
+    let certificate_store = Arc::new(CertificateStore::new(Arc::clone(&pg_pool)));
+    certificate_store
+        .reload()
+        .await
+        .expect("can load the certificates");
+    CertificateStore::listen(Arc::clone(&certificate_store), Arc::clone(&pg_pool));
+
+    // probes run against the routing table in use, reloads included.
+    HealthChecker::start(Arc::new(HealthChecker::new(routing_service.clone())));
+    let upstream_health_controller =
+        UpstreamHealthController::new().routes(routing_service.clone());
+
+    let circuit_breakers = Arc::new(CircuitBreakers::default());
+    let forward_controller =
+        ForwardController::new(routing_service, Arc::clone(&circuit_breakers));
 
     let app = Router::new()
         .nest(
             "/api",
             ApplicationController::new()
                 .routes(Arc::clone(&pg_pool))
+                .merge(ApplicationWorkflowController::new().routes(Arc::clone(&pg_pool)))
+                .merge(ApplicationRouteController::new().routes(Arc::clone(&pg_pool)))
+                .merge(ApplicationOrchestrationController::new().routes(Arc::clone(&pg_pool)))
+                .merge(
+                    ApplicationOrchestrationRouteController::new().routes(Arc::clone(&pg_pool)),
+                )
+                .merge(MetricsController::new().routes())
+                .merge(CircuitBreakerController::new().routes(circuit_breakers))
+                .merge(upstream_health_controller)
                 .fallback(api_fallback),
         )
         .route(
@@ -62,9 +103,18 @@ async fn main() {
 
     tracing::debug!("listening on {}", addr);
 
-    axum::Server::bind(&addr)
-        .serve(app.into_make_service())
-        .await
-        .unwrap();
-    // axum_server::bind_rustls(addr, Rustls::config().await)
+    // http/1.1 and http/2 with prior knowledge (h2c) share the listener, grpc clients need the
+    // latter. with certificates the listener is https and negotiates h2 by alpn, the client
+    // certificate reaches the requests of its connection.
+    match Rustls::config(certificate_store) {
+        Some(config) => axum_server::bind_rustls(addr, config)
+            .map(ClientCertificateAcceptor::new)
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+            .await
+            .unwrap(),
+        None => axum::Server::bind(&addr)
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+            .await
+            .unwrap(),
+    }
 }
@@ -0,0 +1,179 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{
+        ApiError, ORC_ERR_DELETE, ORC_ERR_FINDING_PAGINATED, ORC_ERR_FIND_BY_ID, ORC_ERR_INSERTING,
+        ORC_ERR_PATH_ALREADY_EXISTS, ORC_ERR_UPDATING, ORC_ERR_WORKFLOW_NOT_FOUND,
+    },
+    model::{
+        ApplicationOrchestration, ApplicationOrchestrationReq, Pagination, PaginationResponse,
+    },
+    notification::RoutingNotification,
+};
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait ApplicationOrchestrationRepositoryTrait {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ApplicationOrchestration>, ApiError>;
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<ApplicationOrchestration>, ApiError>;
+
+    async fn save(
+        &self,
+        entity: ApplicationOrchestrationReq,
+    ) -> Result<ApplicationOrchestration, ApiError>;
+
+    async fn update(
+        &self,
+        entity: ApplicationOrchestration,
+    ) -> Result<ApplicationOrchestration, ApiError>;
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError>;
+}
+
+pub struct ApplicationOrchestrationRepository {
+    pub pg_pool: Arc<PgPool>,
+}
+
+#[async_trait]
+impl ApplicationOrchestrationRepositoryTrait for ApplicationOrchestrationRepository {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ApplicationOrchestration>, ApiError> {
+        let total = sqlx::query_scalar(
+            "select count(*) as count from anothergtw.tb_application_orchestration",
+        )
+        .fetch_one(&*self.pg_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error when finding orchestrations: {}", e);
+            ApiError::new(ORC_ERR_FINDING_PAGINATED)
+        })?;
+
+        let mut response = PaginationResponse {
+            page: pagination.page.unwrap(),
+            page_size: pagination.page_size.unwrap(),
+            total,
+            elements: Vec::new(),
+        };
+
+        if total > 0 {
+            let orchestrations = sqlx::query_as!(
+                ApplicationOrchestration,
+                r#"select id, id_application_workflow, path, type as orchestration_type, created_at, updated_at from anothergtw.tb_application_orchestration order by id limit $1 offset $2"#,
+                pagination.page_size.unwrap(),
+                pagination.offset()
+            )
+            .fetch_all(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("Error when finding orchestrations: {}", e);
+                ApiError::new(ORC_ERR_FINDING_PAGINATED)
+            })?;
+
+            response.elements = orchestrations;
+        }
+
+        Ok(response)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<ApplicationOrchestration>, ApiError> {
+        let orchestration = sqlx::query_as!(
+            ApplicationOrchestration,
+            r#"select id, id_application_workflow, path, type as orchestration_type, created_at, updated_at from anothergtw.tb_application_orchestration where id = $1"#,
+            id
+        )
+        .fetch_optional(&*self.pg_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error when finding an orchestration by id: {}", e);
+            ApiError::new(ORC_ERR_FIND_BY_ID)
+        })?;
+
+        Ok(orchestration)
+    }
+
+    async fn save(
+        &self,
+        entity: ApplicationOrchestrationReq,
+    ) -> Result<ApplicationOrchestration, ApiError> {
+        let orchestration: ApplicationOrchestration = sqlx::query_as("insert into anothergtw.tb_application_orchestration(id_application_workflow, path, type, created_at, updated_at) values ($1, $2, $3, $4, $5) returning *;")
+            .bind(entity.id_application_workflow)
+            .bind(entity.path.unwrap())
+            .bind(entity.orchestration_type.unwrap())
+            .bind(Utc::now())
+            .bind(Utc::now())
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when inserting an orchestration: {}", e);
+                match violated_constraint(&e) {
+                    Some("uq_tao_id_application_workflow_path") => ApiError::new(ORC_ERR_PATH_ALREADY_EXISTS),
+                    Some("fk_tao_id_application_workflow") => ApiError::new(ORC_ERR_WORKFLOW_NOT_FOUND),
+                    _ => ApiError::new(ORC_ERR_INSERTING),
+                }
+            })?;
+
+        RoutingNotification::new("orchestration", orchestration.id, "INSERT")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(orchestration)
+    }
+
+    async fn update(
+        &self,
+        entity: ApplicationOrchestration,
+    ) -> Result<ApplicationOrchestration, ApiError> {
+        let orchestration: ApplicationOrchestration = sqlx::query_as("update anothergtw.tb_application_orchestration set path = $1, type = $2, updated_at = $3 where id = $4 returning *;")
+            .bind(entity.path)
+            .bind(entity.orchestration_type)
+            .bind(Utc::now())
+            .bind(entity.id)
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when updating an orchestration: {}", e);
+                match violated_constraint(&e) {
+                    Some("uq_tao_id_application_workflow_path") => ApiError::new(ORC_ERR_PATH_ALREADY_EXISTS),
+                    _ => ApiError::new(ORC_ERR_UPDATING),
+                }
+            })?;
+
+        RoutingNotification::new("orchestration", orchestration.id, "UPDATE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(orchestration)
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError> {
+        sqlx::query("delete from anothergtw.tb_application_orchestration where id = $1")
+            .bind(id)
+            .execute(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when deleting an orchestration: {}", e);
+                ApiError::new(ORC_ERR_DELETE)
+            })?;
+
+        RoutingNotification::new("orchestration", id, "DELETE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(())
+    }
+}
+
+fn violated_constraint(e: &sqlx::Error) -> Option<&str> {
+    e.as_database_error()
+        .and_then(|db_error| db_error.constraint())
+}
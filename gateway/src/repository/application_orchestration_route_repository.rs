@@ -0,0 +1,193 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{
+        ApiError, ORR_ERR_DELETE, ORR_ERR_FINDING_PAGINATED, ORR_ERR_FIND_BY_ID, ORR_ERR_INSERTING,
+        ORR_ERR_ORCHESTRATION_NOT_FOUND, ORR_ERR_RESPONSE_KEY_ALREADY_EXISTS,
+        ORR_ERR_ROUTE_NOT_FOUND, ORR_ERR_UPDATING,
+    },
+    model::{
+        ApplicationOrchestrationRoute, ApplicationOrchestrationRouteReq, Pagination,
+        PaginationResponse, FAILURE_POLICY_FAIL,
+    },
+    notification::RoutingNotification,
+};
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait ApplicationOrchestrationRouteRepositoryTrait {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ApplicationOrchestrationRoute>, ApiError>;
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<ApplicationOrchestrationRoute>, ApiError>;
+
+    async fn save(
+        &self,
+        entity: ApplicationOrchestrationRouteReq,
+    ) -> Result<ApplicationOrchestrationRoute, ApiError>;
+
+    async fn update(
+        &self,
+        entity: ApplicationOrchestrationRoute,
+    ) -> Result<ApplicationOrchestrationRoute, ApiError>;
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError>;
+}
+
+pub struct ApplicationOrchestrationRouteRepository {
+    pub pg_pool: Arc<PgPool>,
+}
+
+#[async_trait]
+impl ApplicationOrchestrationRouteRepositoryTrait for ApplicationOrchestrationRouteRepository {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ApplicationOrchestrationRoute>, ApiError> {
+        let total = sqlx::query_scalar(
+            "select count(*) as count from anothergtw.tb_application_orchestration_route",
+        )
+        .fetch_one(&*self.pg_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error when finding orchestration routes: {}", e);
+            ApiError::new(ORR_ERR_FINDING_PAGINATED)
+        })?;
+
+        let mut response = PaginationResponse {
+            page: pagination.page.unwrap(),
+            page_size: pagination.page_size.unwrap(),
+            total,
+            elements: Vec::new(),
+        };
+
+        if total > 0 {
+            let orchestration_routes = sqlx::query_as!(
+                ApplicationOrchestrationRoute,
+                r#"select * from anothergtw.tb_application_orchestration_route order by id limit $1 offset $2"#,
+                pagination.page_size.unwrap(),
+                pagination.offset()
+            )
+            .fetch_all(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("Error when finding orchestration routes: {}", e);
+                ApiError::new(ORR_ERR_FINDING_PAGINATED)
+            })?;
+
+            response.elements = orchestration_routes;
+        }
+
+        Ok(response)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<ApplicationOrchestrationRoute>, ApiError> {
+        let orchestration_route = sqlx::query_as!(
+            ApplicationOrchestrationRoute,
+            r#"select * from anothergtw.tb_application_orchestration_route where id = $1"#,
+            id
+        )
+        .fetch_optional(&*self.pg_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error when finding an orchestration route by id: {}", e);
+            ApiError::new(ORR_ERR_FIND_BY_ID)
+        })?;
+
+        Ok(orchestration_route)
+    }
+
+    async fn save(
+        &self,
+        entity: ApplicationOrchestrationRouteReq,
+    ) -> Result<ApplicationOrchestrationRoute, ApiError> {
+        let orchestration_route: ApplicationOrchestrationRoute = sqlx::query_as("insert into anothergtw.tb_application_orchestration_route(id_application_orchestration, id_application_route, response_key, request_headers, request_body, failure_policy, fallback, timeout_ms) values ($1, $2, $3, $4, $5, $6, $7, $8) returning *;")
+            .bind(entity.id_application_orchestration)
+            .bind(entity.id_application_route)
+            .bind(entity.response_key.unwrap())
+            .bind(entity.request_headers)
+            .bind(entity.request_body)
+            .bind(
+                entity
+                    .failure_policy
+                    .unwrap_or_else(|| FAILURE_POLICY_FAIL.to_owned()),
+            )
+            .bind(entity.fallback)
+            .bind(entity.timeout_ms)
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when inserting an orchestration route: {}", e);
+                match violated_constraint(&e) {
+                    Some("uq_taor_id_application_orchestration_response_key") => ApiError::new(ORR_ERR_RESPONSE_KEY_ALREADY_EXISTS),
+                    Some("fk_taor_id_application_orchestration") => ApiError::new(ORR_ERR_ORCHESTRATION_NOT_FOUND),
+                    Some("fk_taor_id_application_route") => ApiError::new(ORR_ERR_ROUTE_NOT_FOUND),
+                    _ => ApiError::new(ORR_ERR_INSERTING),
+                }
+            })?;
+
+        RoutingNotification::new("orchestration_route", orchestration_route.id, "INSERT")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(orchestration_route)
+    }
+
+    async fn update(
+        &self,
+        entity: ApplicationOrchestrationRoute,
+    ) -> Result<ApplicationOrchestrationRoute, ApiError> {
+        let orchestration_route: ApplicationOrchestrationRoute = sqlx::query_as("update anothergtw.tb_application_orchestration_route set id_application_route = $1, response_key = $2, request_headers = $3, request_body = $4, failure_policy = $5, fallback = $6, timeout_ms = $7 where id = $8 returning *;")
+            .bind(entity.id_application_route)
+            .bind(entity.response_key)
+            .bind(entity.request_headers)
+            .bind(entity.request_body)
+            .bind(entity.failure_policy)
+            .bind(entity.fallback)
+            .bind(entity.timeout_ms)
+            .bind(entity.id)
+            .fetch_one(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when updating an orchestration route: {}", e);
+                match violated_constraint(&e) {
+                    Some("uq_taor_id_application_orchestration_response_key") => ApiError::new(ORR_ERR_RESPONSE_KEY_ALREADY_EXISTS),
+                    Some("fk_taor_id_application_route") => ApiError::new(ORR_ERR_ROUTE_NOT_FOUND),
+                    _ => ApiError::new(ORR_ERR_UPDATING),
+                }
+            })?;
+
+        RoutingNotification::new("orchestration_route", orchestration_route.id, "UPDATE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(orchestration_route)
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError> {
+        sqlx::query("delete from anothergtw.tb_application_orchestration_route where id = $1")
+            .bind(id)
+            .execute(&*self.pg_pool)
+            .await
+            .map_err(|e| {
+                tracing::info!("Error when deleting an orchestration route: {}", e);
+                ApiError::new(ORR_ERR_DELETE)
+            })?;
+
+        RoutingNotification::new("orchestration_route", id, "DELETE")
+            .publish(&self.pg_pool)
+            .await;
+
+        Ok(())
+    }
+}
+
+fn violated_constraint(e: &sqlx::Error) -> Option<&str> {
+    e.as_database_error()
+        .and_then(|db_error| db_error.constraint())
+}
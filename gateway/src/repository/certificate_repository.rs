@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{ApiError, CRT_ERR_LOADING},
+    model::Certificate,
+};
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait CertificateRepositoryTrait {
+    async fn find_certificates(&self) -> Result<Vec<Certificate>, ApiError>;
+}
+
+pub struct CertificateRepository {
+    pub pg_pool: Arc<PgPool>,
+}
+
+#[async_trait]
+impl CertificateRepositoryTrait for CertificateRepository {
+    async fn find_certificates(&self) -> Result<Vec<Certificate>, ApiError> {
+        let certificates = sqlx::query_as!(
+            Certificate,
+            r#"select * from anothergtw.tb_certificate order by id"#
+        )
+        .fetch_all(&*self.pg_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error when loading certificates: {}", e);
+            ApiError::new(CRT_ERR_LOADING)
+        })?;
+
+        Ok(certificates)
+    }
+}
@@ -1,3 +1,15 @@
 mod application_repository;
+mod application_orchestration_repository;
+mod application_orchestration_route_repository;
+mod application_route_repository;
+mod application_workflow_repository;
+mod certificate_repository;
+mod routing_repository;
 
-pub use application_repository::*;
\ No newline at end of file
+pub use application_repository::*;
+pub use application_orchestration_repository::*;
+pub use application_orchestration_route_repository::*;
+pub use application_route_repository::*;
+pub use application_workflow_repository::*;
+pub use certificate_repository::*;
+pub use routing_repository::*;
\ No newline at end of file
@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{ApiError, ROUTING_ERR_LOADING},
+    model::{
+        Application, ApplicationOrchestration, ApplicationOrchestrationRoute, ApplicationRoute,
+        ApplicationWorkflow, Consumer, ConsumerApiKey, Upstream, UpstreamTarget,
+    },
+};
+
+// every table the routing snapshot is built from, read at the same point in time.
+#[derive(Default)]
+pub struct RoutingRows {
+    pub applications: Vec<Application>,
+    pub workflows: Vec<ApplicationWorkflow>,
+    pub routes: Vec<ApplicationRoute>,
+    pub orchestrations: Vec<ApplicationOrchestration>,
+    pub orchestration_routes: Vec<ApplicationOrchestrationRoute>,
+    pub upstreams: Vec<Upstream>,
+    pub upstream_targets: Vec<UpstreamTarget>,
+    pub consumers: Vec<Consumer>,
+    // expired keys are left out.
+    pub consumer_api_keys: Vec<ConsumerApiKey>,
+}
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait RoutingRepositoryTrait {
+    async fn find_routing_rows(&self) -> Result<RoutingRows, ApiError>;
+}
+
+pub struct RoutingRepository {
+    pub pg_pool: Arc<PgPool>,
+}
+
+#[async_trait]
+impl RoutingRepositoryTrait for RoutingRepository {
+    // the reads share one repeatable read transaction, a change committed while they run can't
+    // leave a route pointing to a workflow or an upstream that's only half loaded.
+    async fn find_routing_rows(&self) -> Result<RoutingRows, ApiError> {
+        let loading_error = |rows: &'static str| {
+            move |e: sqlx::Error| {
+                tracing::error!("Error when loading {} for routing: {}", rows, e);
+                ApiError::new(ROUTING_ERR_LOADING)
+            }
+        };
+
+        let mut transaction = self
+            .pg_pool
+            .begin()
+            .await
+            .map_err(loading_error("the routing table"))?;
+        sqlx::query("set transaction isolation level repeatable read, read only")
+            .execute(&mut transaction)
+            .await
+            .map_err(loading_error("the routing table"))?;
+
+        let applications = sqlx::query_as!(
+            Application,
+            r#"select * from anothergtw.tb_application order by id"#
+        )
+        .fetch_all(&mut transaction)
+        .await
+        .map_err(loading_error("applications"))?;
+
+        let workflows = sqlx::query_as!(
+            ApplicationWorkflow,
+            r#"select * from anothergtw.tb_application_workflow order by id"#
+        )
+        .fetch_all(&mut transaction)
+        .await
+        .map_err(loading_error("workflows"))?;
+
+        let routes = sqlx::query_as!(
+            ApplicationRoute,
+            r#"select * from anothergtw.tb_application_route order by id"#
+        )
+        .fetch_all(&mut transaction)
+        .await
+        .map_err(loading_error("routes"))?;
+
+        let orchestrations = sqlx::query_as!(
+            ApplicationOrchestration,
+            r#"select id, id_application_workflow, path, type as orchestration_type, created_at, updated_at from anothergtw.tb_application_orchestration order by id"#
+        )
+        .fetch_all(&mut transaction)
+        .await
+        .map_err(loading_error("orchestrations"))?;
+
+        let orchestration_routes = sqlx::query_as!(
+            ApplicationOrchestrationRoute,
+            r#"select * from anothergtw.tb_application_orchestration_route order by id"#
+        )
+        .fetch_all(&mut transaction)
+        .await
+        .map_err(loading_error("orchestration routes"))?;
+
+        let upstreams = sqlx::query_as!(
+            Upstream,
+            r#"select * from anothergtw.tb_upstream order by id"#
+        )
+        .fetch_all(&mut transaction)
+        .await
+        .map_err(loading_error("upstreams"))?;
+
+        let upstream_targets = sqlx::query_as!(
+            UpstreamTarget,
+            r#"select * from anothergtw.tb_upstream_target order by id"#
+        )
+        .fetch_all(&mut transaction)
+        .await
+        .map_err(loading_error("upstream targets"))?;
+
+        let consumers = sqlx::query_as!(
+            Consumer,
+            r#"select * from anothergtw.tb_consumer order by id"#
+        )
+        .fetch_all(&mut transaction)
+        .await
+        .map_err(loading_error("consumers"))?;
+
+        let consumer_api_keys = sqlx::query_as!(
+            ConsumerApiKey,
+            r#"select * from anothergtw.tb_consumer_api_key where expires_at is null or expires_at > now() order by id"#
+        )
+        .fetch_all(&mut transaction)
+        .await
+        .map_err(loading_error("consumer api keys"))?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(loading_error("the routing table"))?;
+
+        Ok(RoutingRows {
+            applications,
+            workflows,
+            routes,
+            orchestrations,
+            orchestration_routes,
+            upstreams,
+            upstream_targets,
+            consumers,
+            consumer_api_keys,
+        })
+    }
+}
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{self, Path, Query, State},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use hyper::StatusCode;
+use sqlx::PgPool;
+
+use crate::{
+    exception::ApiError,
+    model::{ApplicationOrchestrationRouteReq, Pagination},
+    service::{ApplicationOrchestrationRouteService, ApplicationOrchestrationRouteServiceTrait},
+};
+
+pub struct ApplicationOrchestrationRouteController;
+
+impl Default for ApplicationOrchestrationRouteController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApplicationOrchestrationRouteController {
+    pub fn new() -> Self {
+        ApplicationOrchestrationRouteController {}
+    }
+
+    pub fn routes(&self, pg_pool: Arc<PgPool>) -> Router {
+        let orchestration_route_service: Arc<
+            dyn ApplicationOrchestrationRouteServiceTrait + Send + Sync,
+        > = Arc::new(ApplicationOrchestrationRouteService::new(Arc::clone(
+            &pg_pool,
+        )));
+
+        Router::new()
+            .route(
+                "/orchestration-route",
+                get(ApplicationOrchestrationRouteController::find_all)
+                    .post(ApplicationOrchestrationRouteController::save),
+            )
+            .route(
+                "/orchestration-route/:id",
+                get(ApplicationOrchestrationRouteController::find_by_id)
+                    .put(ApplicationOrchestrationRouteController::update)
+                    .delete(ApplicationOrchestrationRouteController::delete),
+            )
+            .with_state(Arc::clone(&orchestration_route_service))
+    }
+
+    async fn find_all(
+        Query(pagination): Query<Pagination>,
+        State(orchestration_route_service): State<
+            Arc<dyn ApplicationOrchestrationRouteServiceTrait + Send + Sync>,
+        >,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = orchestration_route_service.find_all(pagination).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn find_by_id(
+        Path(id): Path<i64>,
+        State(orchestration_route_service): State<
+            Arc<dyn ApplicationOrchestrationRouteServiceTrait + Send + Sync>,
+        >,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = orchestration_route_service.find_by_id(id).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn save(
+        State(orchestration_route_service): State<
+            Arc<dyn ApplicationOrchestrationRouteServiceTrait + Send + Sync>,
+        >,
+        extract::Json(entity): extract::Json<ApplicationOrchestrationRouteReq>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = orchestration_route_service.save(entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn update(
+        Path(id): Path<i64>,
+        State(orchestration_route_service): State<
+            Arc<dyn ApplicationOrchestrationRouteServiceTrait + Send + Sync>,
+        >,
+        extract::Json(entity): extract::Json<ApplicationOrchestrationRouteReq>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = orchestration_route_service.update(id, entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn delete(
+        Path(id): Path<i64>,
+        State(orchestration_route_service): State<
+            Arc<dyn ApplicationOrchestrationRouteServiceTrait + Send + Sync>,
+        >,
+    ) -> Result<impl IntoResponse, ApiError> {
+        orchestration_route_service.delete(id).await?;
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Bytes,
+    extract::{self, Path, Query, State},
+    response::IntoResponse,
+    routing::{get, put},
+    Json, Router,
+};
+use hyper::StatusCode;
+use sqlx::PgPool;
+
+use crate::{
+    exception::ApiError,
+    model::{ApplicationRouteReq, Pagination},
+    service::{ApplicationRouteService, ApplicationRouteServiceTrait},
+};
+
+pub struct ApplicationRouteController;
+
+impl Default for ApplicationRouteController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApplicationRouteController {
+    pub fn new() -> Self {
+        ApplicationRouteController {}
+    }
+
+    pub fn routes(&self, pg_pool: Arc<PgPool>) -> Router {
+        let route_service: Arc<dyn ApplicationRouteServiceTrait + Send + Sync> =
+            Arc::new(ApplicationRouteService::new(Arc::clone(&pg_pool)));
+
+        Router::new()
+            .route(
+                "/route",
+                get(ApplicationRouteController::find_all).post(ApplicationRouteController::save),
+            )
+            .route(
+                "/route/:id",
+                get(ApplicationRouteController::find_by_id)
+                    .put(ApplicationRouteController::update)
+                    .delete(ApplicationRouteController::delete),
+            )
+            .route(
+                "/route/:id/descriptor",
+                put(ApplicationRouteController::save_descriptor)
+                    .delete(ApplicationRouteController::delete_descriptor),
+            )
+            .with_state(Arc::clone(&route_service))
+    }
+
+    async fn find_all(
+        Query(pagination): Query<Pagination>,
+        State(route_service): State<Arc<dyn ApplicationRouteServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = route_service.find_all(pagination).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn find_by_id(
+        Path(id): Path<i64>,
+        State(route_service): State<Arc<dyn ApplicationRouteServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = route_service.find_by_id(id).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn save(
+        State(route_service): State<Arc<dyn ApplicationRouteServiceTrait + Send + Sync>>,
+        extract::Json(entity): extract::Json<ApplicationRouteReq>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = route_service.save(entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn update(
+        Path(id): Path<i64>,
+        State(route_service): State<Arc<dyn ApplicationRouteServiceTrait + Send + Sync>>,
+        extract::Json(entity): extract::Json<ApplicationRouteReq>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = route_service.update(id, entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn delete(
+        Path(id): Path<i64>,
+        State(route_service): State<Arc<dyn ApplicationRouteServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        route_service.delete(id).await?;
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    async fn save_descriptor(
+        Path(id): Path<i64>,
+        State(route_service): State<Arc<dyn ApplicationRouteServiceTrait + Send + Sync>>,
+        grpc_descriptor: Bytes,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = route_service
+            .save_descriptor(id, grpc_descriptor.to_vec())
+            .await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn delete_descriptor(
+        Path(id): Path<i64>,
+        State(route_service): State<Arc<dyn ApplicationRouteServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = route_service.delete_descriptor(id).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+}
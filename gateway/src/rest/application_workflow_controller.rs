@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{self, Path, Query, State},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use hyper::StatusCode;
+use sqlx::PgPool;
+
+use crate::{
+    exception::ApiError,
+    model::{ApplicationWorkflowReq, Pagination},
+    service::{ApplicationWorkflowService, ApplicationWorkflowServiceTrait},
+};
+
+pub struct ApplicationWorkflowController;
+
+impl Default for ApplicationWorkflowController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApplicationWorkflowController {
+    pub fn new() -> Self {
+        ApplicationWorkflowController {}
+    }
+
+    pub fn routes(&self, pg_pool: Arc<PgPool>) -> Router {
+        let workflow_service: Arc<dyn ApplicationWorkflowServiceTrait + Send + Sync> =
+            Arc::new(ApplicationWorkflowService::new(Arc::clone(&pg_pool)));
+
+        Router::new()
+            .route(
+                "/workflow",
+                get(ApplicationWorkflowController::find_all)
+                    .post(ApplicationWorkflowController::save),
+            )
+            .route(
+                "/workflow/:id",
+                get(ApplicationWorkflowController::find_by_id)
+                    .put(ApplicationWorkflowController::update)
+                    .delete(ApplicationWorkflowController::delete),
+            )
+            .with_state(Arc::clone(&workflow_service))
+    }
+
+    async fn find_all(
+        Query(pagination): Query<Pagination>,
+        State(workflow_service): State<Arc<dyn ApplicationWorkflowServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = workflow_service.find_all(pagination).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn find_by_id(
+        Path(id): Path<i64>,
+        State(workflow_service): State<Arc<dyn ApplicationWorkflowServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = workflow_service.find_by_id(id).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn save(
+        State(workflow_service): State<Arc<dyn ApplicationWorkflowServiceTrait + Send + Sync>>,
+        extract::Json(entity): extract::Json<ApplicationWorkflowReq>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = workflow_service.save(entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn update(
+        Path(id): Path<i64>,
+        State(workflow_service): State<Arc<dyn ApplicationWorkflowServiceTrait + Send + Sync>>,
+        extract::Json(entity): extract::Json<ApplicationWorkflowReq>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let response = workflow_service.update(id, entity).await?;
+        Ok((StatusCode::OK, Json(response)))
+    }
+
+    async fn delete(
+        Path(id): Path<i64>,
+        State(workflow_service): State<Arc<dyn ApplicationWorkflowServiceTrait + Send + Sync>>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        workflow_service.delete(id).await?;
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
@@ -0,0 +1,31 @@
+use std::sync::Arc;
+
+use axum::{extract::State, routing::get, Json, Router};
+
+use crate::service::{CircuitBreakers, CircuitSnapshot};
+
+pub struct CircuitBreakerController;
+
+impl Default for CircuitBreakerController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CircuitBreakerController {
+    pub fn new() -> Self {
+        CircuitBreakerController {}
+    }
+
+    pub fn routes(&self, circuit_breakers: Arc<CircuitBreakers>) -> Router {
+        Router::new()
+            .route("/circuit-breakers", get(CircuitBreakerController::list))
+            .with_state(circuit_breakers)
+    }
+
+    async fn list(
+        State(circuit_breakers): State<Arc<CircuitBreakers>>,
+    ) -> Json<Vec<CircuitSnapshot>> {
+        Json(circuit_breakers.snapshot())
+    }
+}
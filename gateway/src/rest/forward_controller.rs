@@ -5,11 +5,10 @@ use axum::{
     http::{Request, Response},
 };
 use hyper::{Body, StatusCode};
-use sqlx::PgPool;
 
 use crate::{
     exception::ApiError,
-    service::{ForwardService, ForwardServiceTrait},
+    service::{CircuitBreakers, ForwardService, ForwardServiceTrait, RoutingServiceTrait},
 };
 
 pub struct ForwardController {
@@ -17,9 +16,13 @@ pub struct ForwardController {
 }
 
 impl ForwardController {
-    pub fn new(pg_pool: Arc<PgPool>) -> Self {
-        let forward_service: Arc<dyn ForwardServiceTrait + Send + Sync> =
-            Arc::new(ForwardService::new(Arc::clone(&pg_pool)));
+    pub fn new(
+        routing_service: Arc<dyn RoutingServiceTrait + Send + Sync>,
+        circuit_breakers: Arc<CircuitBreakers>,
+    ) -> Self {
+        let forward_service: Arc<dyn ForwardServiceTrait + Send + Sync> = Arc::new(
+            ForwardService::new_with_circuit_breakers(routing_service, circuit_breakers),
+        );
         ForwardController { forward_service }
     }
 
@@ -0,0 +1,30 @@
+use axum::{http::header::CONTENT_TYPE, response::IntoResponse, routing::get, Router};
+use hyper::StatusCode;
+
+use crate::service::Metrics;
+
+pub struct MetricsController;
+
+impl Default for MetricsController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsController {
+    pub fn new() -> Self {
+        MetricsController {}
+    }
+
+    pub fn routes(&self) -> Router {
+        Router::new().route("/metrics", get(MetricsController::render))
+    }
+
+    async fn render() -> impl IntoResponse {
+        (
+            StatusCode::OK,
+            [(CONTENT_TYPE, "text/plain; version=0.0.4")],
+            Metrics::global().render(),
+        )
+    }
+}
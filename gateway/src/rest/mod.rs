@@ -1,5 +1,19 @@
 mod application_controller;
+mod application_orchestration_controller;
+mod application_orchestration_route_controller;
+mod application_route_controller;
+mod application_workflow_controller;
+mod circuit_breaker_controller;
 mod forward_controller;
+mod metrics_controller;
+mod upstream_health_controller;
 
 pub use application_controller::*;
-pub use forward_controller::*;
\ No newline at end of file
+pub use application_orchestration_controller::*;
+pub use application_orchestration_route_controller::*;
+pub use application_route_controller::*;
+pub use application_workflow_controller::*;
+pub use circuit_breaker_controller::*;
+pub use forward_controller::*;
+pub use metrics_controller::*;
+pub use upstream_health_controller::*;
\ No newline at end of file
@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use axum::{extract::State, routing::get, Json, Router};
+
+use crate::service::{RoutingServiceTrait, TargetHealthSnapshot};
+
+pub struct UpstreamHealthController;
+
+impl Default for UpstreamHealthController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UpstreamHealthController {
+    pub fn new() -> Self {
+        UpstreamHealthController {}
+    }
+
+    pub fn routes(&self, routing_service: Arc<dyn RoutingServiceTrait + Send + Sync>) -> Router {
+        Router::new()
+            .route("/upstream-health", get(UpstreamHealthController::list))
+            .with_state(routing_service)
+    }
+
+    // targets of the upstreams in the routing table in use.
+    async fn list(
+        State(routing_service): State<Arc<dyn RoutingServiceTrait + Send + Sync>>,
+    ) -> Json<Vec<TargetHealthSnapshot>> {
+        let snapshot = routing_service.snapshot();
+        let mut balancers: Vec<_> = snapshot.balancers().collect();
+        balancers.sort_by_key(|balancer| balancer.id());
+        Json(
+            balancers
+                .into_iter()
+                .flat_map(|balancer| balancer.health_snapshot())
+                .collect(),
+        )
+    }
+}
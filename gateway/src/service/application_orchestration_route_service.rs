@@ -0,0 +1,167 @@
+#[cfg(test)]
+#[path = "application_orchestration_route_service_test.rs"]
+mod application_orchestration_route_service_test;
+
+use std::sync::Arc;
+
+use axum::async_trait;
+use hyper::StatusCode;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{ApiError, ORR_ERR_NOT_FOUND},
+    model::{
+        ApplicationOrchestrationRoute, ApplicationOrchestrationRouteReq, Pagination,
+        PaginationResponse,
+    },
+    repository::{
+        ApplicationOrchestrationRouteRepository, ApplicationOrchestrationRouteRepositoryTrait,
+    },
+};
+
+#[async_trait]
+pub trait ApplicationOrchestrationRouteServiceTrait {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ApplicationOrchestrationRoute>, ApiError>;
+
+    async fn find_by_id(&self, id: i64) -> Result<ApplicationOrchestrationRoute, ApiError>;
+
+    async fn save(
+        &self,
+        entity: ApplicationOrchestrationRouteReq,
+    ) -> Result<ApplicationOrchestrationRoute, ApiError>;
+
+    async fn update(
+        &self,
+        id: i64,
+        entity: ApplicationOrchestrationRouteReq,
+    ) -> Result<ApplicationOrchestrationRoute, ApiError>;
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError>;
+}
+
+pub struct ApplicationOrchestrationRouteService {
+    orchestration_route_repository:
+        Arc<dyn ApplicationOrchestrationRouteRepositoryTrait + Send + Sync>,
+}
+
+#[async_trait]
+impl ApplicationOrchestrationRouteServiceTrait for ApplicationOrchestrationRouteService {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ApplicationOrchestrationRoute>, ApiError> {
+        pagination.validate()?;
+
+        let response = self
+            .orchestration_route_repository
+            .find_all(pagination)
+            .await?;
+        Ok(response)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<ApplicationOrchestrationRoute, ApiError> {
+        let response = self.orchestration_route_repository.find_by_id(id).await?;
+
+        if response.is_none() {
+            return Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                ORR_ERR_NOT_FOUND,
+            ));
+        }
+        Ok(response.unwrap())
+    }
+
+    async fn save(
+        &self,
+        entity: ApplicationOrchestrationRouteReq,
+    ) -> Result<ApplicationOrchestrationRoute, ApiError> {
+        entity.validate()?;
+
+        let orchestration_route = self.orchestration_route_repository.save(entity).await?;
+        Ok(orchestration_route)
+    }
+
+    async fn update(
+        &self,
+        id: i64,
+        entity: ApplicationOrchestrationRouteReq,
+    ) -> Result<ApplicationOrchestrationRoute, ApiError> {
+        if let Some(mut orchestration_route) =
+            self.orchestration_route_repository.find_by_id(id).await?
+        {
+            entity.validate_updating(&orchestration_route)?;
+
+            if let Some(id_application_route) = entity.id_application_route {
+                orchestration_route.id_application_route = id_application_route;
+            }
+
+            if let Some(response_key) = entity.response_key {
+                orchestration_route.response_key = response_key;
+            }
+
+            if entity.request_headers.is_some() {
+                orchestration_route.request_headers = entity.request_headers;
+            }
+
+            if entity.request_body.is_some() {
+                orchestration_route.request_body = entity.request_body;
+            }
+
+            if let Some(failure_policy) = entity.failure_policy {
+                orchestration_route.failure_policy = failure_policy;
+            }
+
+            if entity.fallback.is_some() {
+                orchestration_route.fallback = entity.fallback;
+            }
+
+            if entity.timeout_ms.is_some() {
+                orchestration_route.timeout_ms = entity.timeout_ms;
+            }
+
+            orchestration_route = self
+                .orchestration_route_repository
+                .update(orchestration_route)
+                .await?;
+            Ok(orchestration_route)
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                ORR_ERR_NOT_FOUND,
+            ))
+        }
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError> {
+        if (self.orchestration_route_repository.find_by_id(id).await?).is_some() {
+            self.orchestration_route_repository.delete(id).await?;
+            Ok(())
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                ORR_ERR_NOT_FOUND,
+            ))
+        }
+    }
+}
+
+impl ApplicationOrchestrationRouteService {
+    pub fn new(pg_pool: Arc<PgPool>) -> Self {
+        ApplicationOrchestrationRouteService {
+            orchestration_route_repository: Arc::new(ApplicationOrchestrationRouteRepository {
+                pg_pool,
+            }),
+        }
+    }
+
+    pub fn new_with_repo(
+        repository: Arc<dyn ApplicationOrchestrationRouteRepositoryTrait + Send + Sync>,
+    ) -> Self {
+        ApplicationOrchestrationRouteService {
+            orchestration_route_repository: repository,
+        }
+    }
+}
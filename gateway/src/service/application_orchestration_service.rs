@@ -0,0 +1,133 @@
+#[cfg(test)]
+#[path = "application_orchestration_service_test.rs"]
+mod application_orchestration_service_test;
+
+use std::sync::Arc;
+
+use axum::async_trait;
+use hyper::StatusCode;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{ApiError, ORC_ERR_NOT_FOUND},
+    model::{
+        ApplicationOrchestration, ApplicationOrchestrationReq, Pagination, PaginationResponse,
+    },
+    repository::{ApplicationOrchestrationRepository, ApplicationOrchestrationRepositoryTrait},
+};
+
+#[async_trait]
+pub trait ApplicationOrchestrationServiceTrait {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ApplicationOrchestration>, ApiError>;
+
+    async fn find_by_id(&self, id: i64) -> Result<ApplicationOrchestration, ApiError>;
+
+    async fn save(
+        &self,
+        entity: ApplicationOrchestrationReq,
+    ) -> Result<ApplicationOrchestration, ApiError>;
+
+    async fn update(
+        &self,
+        id: i64,
+        entity: ApplicationOrchestrationReq,
+    ) -> Result<ApplicationOrchestration, ApiError>;
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError>;
+}
+
+pub struct ApplicationOrchestrationService {
+    orchestration_repository: Arc<dyn ApplicationOrchestrationRepositoryTrait + Send + Sync>,
+}
+
+#[async_trait]
+impl ApplicationOrchestrationServiceTrait for ApplicationOrchestrationService {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ApplicationOrchestration>, ApiError> {
+        pagination.validate()?;
+
+        let response = self.orchestration_repository.find_all(pagination).await?;
+        Ok(response)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<ApplicationOrchestration, ApiError> {
+        let response = self.orchestration_repository.find_by_id(id).await?;
+
+        if response.is_none() {
+            return Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                ORC_ERR_NOT_FOUND,
+            ));
+        }
+        Ok(response.unwrap())
+    }
+
+    async fn save(
+        &self,
+        entity: ApplicationOrchestrationReq,
+    ) -> Result<ApplicationOrchestration, ApiError> {
+        entity.validate()?;
+
+        let orchestration = self.orchestration_repository.save(entity).await?;
+        Ok(orchestration)
+    }
+
+    async fn update(
+        &self,
+        id: i64,
+        entity: ApplicationOrchestrationReq,
+    ) -> Result<ApplicationOrchestration, ApiError> {
+        entity.validate_updating()?;
+
+        if let Some(mut orchestration) = self.orchestration_repository.find_by_id(id).await? {
+            if let Some(path) = entity.path {
+                orchestration.path = path;
+            }
+
+            if let Some(orchestration_type) = entity.orchestration_type {
+                orchestration.orchestration_type = orchestration_type;
+            }
+
+            orchestration = self.orchestration_repository.update(orchestration).await?;
+            Ok(orchestration)
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                ORC_ERR_NOT_FOUND,
+            ))
+        }
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError> {
+        if (self.orchestration_repository.find_by_id(id).await?).is_some() {
+            self.orchestration_repository.delete(id).await?;
+            Ok(())
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                ORC_ERR_NOT_FOUND,
+            ))
+        }
+    }
+}
+
+impl ApplicationOrchestrationService {
+    pub fn new(pg_pool: Arc<PgPool>) -> Self {
+        ApplicationOrchestrationService {
+            orchestration_repository: Arc::new(ApplicationOrchestrationRepository { pg_pool }),
+        }
+    }
+
+    pub fn new_with_repo(
+        repository: Arc<dyn ApplicationOrchestrationRepositoryTrait + Send + Sync>,
+    ) -> Self {
+        ApplicationOrchestrationService {
+            orchestration_repository: repository,
+        }
+    }
+}
@@ -0,0 +1,213 @@
+#[cfg(test)]
+#[path = "application_route_service_test.rs"]
+mod application_route_service_test;
+
+use std::sync::Arc;
+
+use axum::async_trait;
+use hyper::StatusCode;
+use serde_json::Value;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{ApiError, ROU_ERR_ENCRYPTING_AUTH, ROU_ERR_NOT_FOUND},
+    model::{ApplicationRoute, ApplicationRouteReq, AuthPolicy, Pagination, PaginationResponse},
+    repository::{ApplicationRouteRepository, ApplicationRouteRepositoryTrait},
+    secret::SecretCipher,
+    transcoding::TranscodingDescriptor,
+};
+
+#[async_trait]
+pub trait ApplicationRouteServiceTrait {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ApplicationRoute>, ApiError>;
+
+    async fn find_by_id(&self, id: i64) -> Result<ApplicationRoute, ApiError>;
+
+    async fn save(&self, entity: ApplicationRouteReq) -> Result<ApplicationRoute, ApiError>;
+
+    async fn update(
+        &self,
+        id: i64,
+        entity: ApplicationRouteReq,
+    ) -> Result<ApplicationRoute, ApiError>;
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError>;
+
+    async fn save_descriptor(
+        &self,
+        id: i64,
+        grpc_descriptor: Vec<u8>,
+    ) -> Result<ApplicationRoute, ApiError>;
+
+    async fn delete_descriptor(&self, id: i64) -> Result<ApplicationRoute, ApiError>;
+}
+
+pub struct ApplicationRouteService {
+    route_repository: Arc<dyn ApplicationRouteRepositoryTrait + Send + Sync>,
+    cipher: Option<SecretCipher>,
+}
+
+#[async_trait]
+impl ApplicationRouteServiceTrait for ApplicationRouteService {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ApplicationRoute>, ApiError> {
+        pagination.validate()?;
+
+        let response = self.route_repository.find_all(pagination).await?;
+        Ok(response)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<ApplicationRoute, ApiError> {
+        let response = self.route_repository.find_by_id(id).await?;
+
+        if response.is_none() {
+            return Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                ROU_ERR_NOT_FOUND,
+            ));
+        }
+        Ok(response.unwrap())
+    }
+
+    async fn save(&self, mut entity: ApplicationRouteReq) -> Result<ApplicationRoute, ApiError> {
+        entity.validate()?;
+
+        let id = self.route_repository.next_id().await?;
+        if let Some(auth) = &mut entity.auth {
+            self.seal_auth(id, auth)?;
+        }
+
+        let route = self.route_repository.save(id, entity).await?;
+        Ok(route)
+    }
+
+    async fn update(
+        &self,
+        id: i64,
+        entity: ApplicationRouteReq,
+    ) -> Result<ApplicationRoute, ApiError> {
+        entity.validate_updating()?;
+
+        if let Some(mut route) = self.route_repository.find_by_id(id).await? {
+            if let Some(path) = entity.path {
+                route.path = path;
+            }
+
+            if entity.forward_to.is_some() {
+                route.forward_to = entity.forward_to;
+            }
+
+            if let Some(priority) = entity.priority {
+                route.priority = priority;
+            }
+            if let Some(streaming) = entity.streaming {
+                route.streaming = streaming;
+            }
+
+            if let Some(upstream_policy) = entity.upstream_policy {
+                route.upstream_policy = Some(upstream_policy);
+            }
+
+            if entity.id_upstream.is_some() {
+                route.id_upstream = entity.id_upstream;
+            }
+
+            if entity.rate_limit.is_some() {
+                route.rate_limit = entity.rate_limit;
+            }
+
+            // the auth is replaced as a whole, the secrets saved before can't be read back.
+            if let Some(mut auth) = entity.auth {
+                self.seal_auth(route.id, &mut auth)?;
+                route.auth = Some(auth);
+            }
+
+            route = self.route_repository.update(route).await?;
+            Ok(route)
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                ROU_ERR_NOT_FOUND,
+            ))
+        }
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError> {
+        if (self.route_repository.find_by_id(id).await?).is_some() {
+            self.route_repository.delete(id).await?;
+            Ok(())
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                ROU_ERR_NOT_FOUND,
+            ))
+        }
+    }
+
+    async fn save_descriptor(
+        &self,
+        id: i64,
+        grpc_descriptor: Vec<u8>,
+    ) -> Result<ApplicationRoute, ApiError> {
+        TranscodingDescriptor::decode(&grpc_descriptor)?;
+
+        self.find_by_id(id).await?;
+        let route = self
+            .route_repository
+            .update_descriptor(id, Some(grpc_descriptor))
+            .await?;
+        Ok(route)
+    }
+
+    async fn delete_descriptor(&self, id: i64) -> Result<ApplicationRoute, ApiError> {
+        self.find_by_id(id).await?;
+        let route = self.route_repository.update_descriptor(id, None).await?;
+        Ok(route)
+    }
+}
+
+impl ApplicationRouteService {
+    pub fn new(pg_pool: Arc<PgPool>) -> Self {
+        ApplicationRouteService {
+            route_repository: Arc::new(ApplicationRouteRepository { pg_pool }),
+            cipher: SecretCipher::config(),
+        }
+    }
+
+    pub fn new_with_repo(
+        repository: Arc<dyn ApplicationRouteRepositoryTrait + Send + Sync>,
+    ) -> Self {
+        ApplicationRouteService::new_with_cipher(repository, SecretCipher::config())
+    }
+
+    pub fn new_with_cipher(
+        repository: Arc<dyn ApplicationRouteRepositoryTrait + Send + Sync>,
+        cipher: Option<SecretCipher>,
+    ) -> Self {
+        ApplicationRouteService {
+            route_repository: repository,
+            cipher,
+        }
+    }
+
+    fn seal_auth(&self, id: i64, auth: &mut Value) -> Result<(), ApiError> {
+        if !AuthPolicy::has_secrets(auth) {
+            return Ok(());
+        }
+
+        let encrypting_error = || {
+            ApiError::new_with_status(StatusCode::INTERNAL_SERVER_ERROR, ROU_ERR_ENCRYPTING_AUTH)
+        };
+        let cipher = self.cipher.as_ref().ok_or_else(|| {
+            tracing::error!("CREDENTIALS_ENCRYPTION_KEY is required to store auth secrets");
+            encrypting_error()
+        })?;
+        AuthPolicy::seal_secrets(auth, &ApplicationRoute::auth_sealing_context(id), cipher)
+            .map_err(|_| encrypting_error())
+    }
+}
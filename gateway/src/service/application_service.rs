@@ -1,17 +1,20 @@
 #[cfg(test)]
 #[path = "application_service_test.rs"]
+#[allow(clippy::bool_assert_comparison)]
 mod application_service_test;
 
 use std::sync::Arc;
 
 use axum::async_trait;
 use hyper::StatusCode;
+use serde_json::Value;
 use sqlx::PgPool;
 
 use crate::{
-    exception::{ApiError, APP_ERR_NOT_FOUND},
-    model::{Application, ApplicationReq, Pagination, PaginationResponse},
+    exception::{ApiError, APP_ERR_ENCRYPTING_AUTH, APP_ERR_NOT_FOUND, FORWARD_ERR_PATH_NOT_FOUND},
+    model::{Application, ApplicationReq, AuthPolicy, Pagination, PaginationResponse},
     repository::{ApplicationRepository, ApplicationRepositoryTrait},
+    secret::SecretCipher,
 };
 
 #[async_trait]
@@ -23,6 +26,8 @@ pub trait ApplicationServiceTrait {
 
     async fn find_by_id(&self, id: i64) -> Result<Application, ApiError>;
 
+    async fn find_by_path(&self, path: &str) -> Result<Application, ApiError>;
+
     async fn save(&self, entity: ApplicationReq) -> Result<Application, ApiError>;
 
     async fn update(&self, id: i64, entity: ApplicationReq) -> Result<Application, ApiError>;
@@ -32,6 +37,7 @@ pub trait ApplicationServiceTrait {
 
 pub struct ApplicationService {
     application_repository: Arc<dyn ApplicationRepositoryTrait + Send + Sync>,
+    cipher: Option<SecretCipher>,
 }
 
 #[async_trait]
@@ -58,10 +64,27 @@ impl ApplicationServiceTrait for ApplicationService {
         Ok(response.unwrap())
     }
 
-    async fn save(&self, entity: ApplicationReq) -> Result<Application, ApiError> {
+    async fn find_by_path(&self, path: &str) -> Result<Application, ApiError> {
+        let response = self.application_repository.find_by_path(path).await?;
+
+        if response.is_none() {
+            return Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                FORWARD_ERR_PATH_NOT_FOUND,
+            ));
+        }
+        Ok(response.unwrap())
+    }
+
+    async fn save(&self, mut entity: ApplicationReq) -> Result<Application, ApiError> {
         entity.validate()?;
 
-        let application = self.application_repository.save(entity).await?;
+        let id = self.application_repository.next_id().await?;
+        if let Some(auth) = &mut entity.auth {
+            self.seal_auth(id, auth)?;
+        }
+
+        let application = self.application_repository.save(id, entity).await?;
         Ok(application)
     }
 
@@ -73,6 +96,40 @@ impl ApplicationServiceTrait for ApplicationService {
                 application.name = name;
             }
 
+            if let Some(path) = entity.path {
+                application.path = path;
+            }
+
+            if let Some(url_destination) = entity.url_destination {
+                application.url_destination = url_destination;
+            }
+
+            if let Some(preserve_host) = entity.preserve_host {
+                application.preserve_host = preserve_host;
+            }
+
+            if let Some(websocket_max_connections) = entity.websocket_max_connections {
+                application.websocket_max_connections = Some(websocket_max_connections);
+            }
+
+            if let Some(upstream_policy) = entity.upstream_policy {
+                application.upstream_policy = Some(upstream_policy);
+            }
+
+            if entity.id_upstream.is_some() {
+                application.id_upstream = entity.id_upstream;
+            }
+
+            if entity.rate_limit.is_some() {
+                application.rate_limit = entity.rate_limit;
+            }
+
+            // the auth is replaced as a whole, the secrets saved before can't be read back.
+            if let Some(mut auth) = entity.auth {
+                self.seal_auth(application.id, &mut auth)?;
+                application.auth = Some(auth);
+            }
+
             application = self.application_repository.update(application).await?;
             Ok(application)
         } else {
@@ -100,12 +157,37 @@ impl ApplicationService {
     pub fn new(pg_pool: Arc<PgPool>) -> Self {
         ApplicationService {
             application_repository: Arc::new(ApplicationRepository { pg_pool }),
+            cipher: SecretCipher::config(),
         }
     }
 
     pub fn new_with_repo(repository: Arc<dyn ApplicationRepositoryTrait + Send + Sync>) -> Self {
+        ApplicationService::new_with_cipher(repository, SecretCipher::config())
+    }
+
+    pub fn new_with_cipher(
+        repository: Arc<dyn ApplicationRepositoryTrait + Send + Sync>,
+        cipher: Option<SecretCipher>,
+    ) -> Self {
         ApplicationService {
             application_repository: repository,
+            cipher,
         }
     }
+
+    fn seal_auth(&self, id: i64, auth: &mut Value) -> Result<(), ApiError> {
+        if !AuthPolicy::has_secrets(auth) {
+            return Ok(());
+        }
+
+        let encrypting_error = || {
+            ApiError::new_with_status(StatusCode::INTERNAL_SERVER_ERROR, APP_ERR_ENCRYPTING_AUTH)
+        };
+        let cipher = self.cipher.as_ref().ok_or_else(|| {
+            tracing::error!("CREDENTIALS_ENCRYPTION_KEY is required to store auth secrets");
+            encrypting_error()
+        })?;
+        AuthPolicy::seal_secrets(auth, &Application::auth_sealing_context(id), cipher)
+            .map_err(|_| encrypting_error())
+    }
 }
@@ -1,7 +1,7 @@
 use chrono::Utc;
 
 use crate::{
-    exception::{APP_ERR_INSERTING, PG_ERR_PAGE_REQUIRED, PG_ERR_PAGE_SIZE_REQUIRED, ERR_INVALID_REQUEST, APP_ERR_UPDATING, APP_ERR_DELETE},
+    exception::{APP_ERR_INSERTING, PG_ERR_PAGE_REQUIRED, PG_ERR_PAGE_SIZE_REQUIRED, ERR_INVALID_REQUEST, APP_ERR_UPDATING, APP_ERR_DELETE, APP_ERR_PATH_ALREADY_EXISTS, ERR_INVALID_PATH, ERR_INVALID_URL, ERR_INVALID_VALUE},
     repository::MockApplicationRepositoryTrait,
 };
 
@@ -68,6 +68,14 @@ async fn find_by_id() {
         Ok(Some(Application {
             id: 1,
             name: String::from("Teste"),
+            path: String::from("/teste"),
+            url_destination: String::from("http://anothergtw.com"),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }))
@@ -92,13 +100,61 @@ async fn find_by_id_not_found() {
     assert_eq!(APP_ERR_NOT_FOUND.0, response.unwrap_err().code);
 }
 
+#[tokio::test]
+async fn find_by_path() {
+    let mut mock_repo = MockApplicationRepositoryTrait::new();
+    mock_repo.expect_find_by_path().returning(|_| {
+        Ok(Some(Application {
+            id: 1,
+            name: String::from("Teste"),
+            path: String::from("/teste"),
+            url_destination: String::from("http://anothergtw.com"),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }))
+    });
+
+    let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.find_by_path("/teste").await;
+    assert!(response.is_ok());
+    assert_eq!("http://anothergtw.com", response.ok().unwrap().url_destination);
+}
+
+#[tokio::test]
+async fn find_by_path_not_found() {
+    let mut mock_repo = MockApplicationRepositoryTrait::new();
+    mock_repo.expect_find_by_path().returning(|_| Ok(None));
+
+    let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.find_by_path("/teste").await;
+    assert!(response.is_err());
+    assert_eq!(FORWARD_ERR_PATH_NOT_FOUND.0, response.unwrap_err().code);
+}
+
 #[tokio::test]
 async fn save() {
     let mut mock_repo = MockApplicationRepositoryTrait::new();
-    mock_repo.expect_save().returning(|_| {
+    mock_repo.expect_next_id().returning(|| Ok(1));
+    mock_repo.expect_save().returning(|_, _| {
         Ok(Application {
             id: 1,
             name: String::from("Teste"),
+            path: String::from("/teste"),
+            url_destination: String::from("http://anothergtw.com"),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         })
@@ -108,6 +164,12 @@ async fn save() {
         name: Some("teste".to_string()),
         path: Some("/teste".to_string()),
         url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
     };
 
     let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
@@ -125,6 +187,12 @@ async fn save_without_fields() {
         name: None,
         path: None,
         url_destination: None,
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
     };
 
     let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
@@ -144,6 +212,12 @@ async fn save_without_min_size() {
         name: Some("te".to_string()),
         path: Some("/t".to_string()),
         url_destination: Some("ht".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
     };
 
     let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
@@ -156,17 +230,132 @@ async fn save_without_min_size() {
     assert_eq!(3, api_error.field_errors.unwrap().len());
 }
 
+#[tokio::test]
+async fn save_with_invalid_format() {
+    let mock_repo = MockApplicationRepositoryTrait::new();
+
+    let request = ApplicationReq {
+        name: Some("teste".to_string()),
+        path: Some("/teste/orders".to_string()),
+        url_destination: Some("anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: Some(0),
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+    };
+
+    let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.save(request).await;
+    assert!(response.is_err());
+
+    let field_errors = response.unwrap_err().field_errors.unwrap();
+    assert_eq!(3, field_errors.len());
+    assert_eq!(ERR_INVALID_PATH.0, field_errors[0].code);
+    assert_eq!(ERR_INVALID_URL.0, field_errors[1].code);
+    assert_eq!(ERR_INVALID_VALUE.0, field_errors[2].code);
+}
+
+#[tokio::test]
+async fn save_with_invalid_upstream_policy() {
+    let mock_repo = MockApplicationRepositoryTrait::new();
+
+    let request = ApplicationReq {
+        name: Some("teste".to_string()),
+        path: Some("/teste".to_string()),
+        url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: Some(serde_json::json!({
+            "connectTimeoutMs": 0,
+            "retry": {"maxAttempts": 20, "statusCodes": [503, 700], "methods": ["GET"]}
+        })),
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+    };
+
+    let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
+
+    let field_errors = service.save(request).await.unwrap_err().field_errors.unwrap();
+    let fields: Vec<&str> = field_errors
+        .iter()
+        .map(|field_error| field_error.field.as_str())
+        .collect();
+    assert_eq!(
+        vec![
+            "application.upstreamPolicy.connectTimeoutMs",
+            "application.upstreamPolicy.retry.maxAttempts",
+            "application.upstreamPolicy.retry.statusCodes",
+        ],
+        fields
+    );
+
+    // unknown fields aren't silently dropped.
+    let request = ApplicationReq {
+        name: Some("teste".to_string()),
+        path: Some("/teste".to_string()),
+        url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: Some(serde_json::json!({"timeoutMs": 100})),
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+    };
+
+    let field_errors = service.save(request).await.unwrap_err().field_errors.unwrap();
+    assert_eq!(1, field_errors.len());
+    assert_eq!("application.upstreamPolicy", field_errors[0].field);
+}
+
+#[tokio::test]
+async fn save_with_path_already_exists() {
+    let mut mock_repo = MockApplicationRepositoryTrait::new();
+    mock_repo.expect_next_id().returning(|| Ok(1));
+    mock_repo
+        .expect_save()
+        .returning(|_, _| Err(ApiError::new(APP_ERR_PATH_ALREADY_EXISTS)));
+
+    let request = ApplicationReq {
+        name: Some("teste".to_string()),
+        path: Some("/teste".to_string()),
+        url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+    };
+
+    let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.save(request).await;
+    assert!(response.is_err());
+    assert_eq!(APP_ERR_PATH_ALREADY_EXISTS.0, response.unwrap_err().code);
+}
+
 #[tokio::test]
 async fn save_with_repository_error() {
     let mut mock_repo = MockApplicationRepositoryTrait::new();
+    mock_repo.expect_next_id().returning(|| Ok(1));
     mock_repo
         .expect_save()
-        .returning(|_| Err(ApiError::new(APP_ERR_INSERTING)));
+        .returning(|_, _| Err(ApiError::new(APP_ERR_INSERTING)));
 
     let request = ApplicationReq {
         name: Some("teste".to_string()),
         path: Some("/teste".to_string()),
         url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
     };
 
     let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
@@ -183,6 +372,14 @@ async fn update() {
         Ok(Some(Application {
             id: 1,
             name: String::from("Teste"),
+            path: String::from("/teste"),
+            url_destination: String::from("http://anothergtw.com"),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }))
@@ -192,6 +389,14 @@ async fn update() {
         Ok(Application {
             id: 1,
             name: String::from("Teste"),
+            path: String::from("/teste"),
+            url_destination: String::from("http://anothergtw.com"),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         })
@@ -201,6 +406,12 @@ async fn update() {
         name: Some("teste".to_string()),
         path: Some("/teste".to_string()),
         url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
     };
 
     let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
@@ -210,6 +421,98 @@ async fn update() {
     assert_eq!(1, response.unwrap().id);
 }
 
+#[tokio::test]
+async fn update_path_and_url_destination() {
+    let mut mock_repo = MockApplicationRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| {
+        Ok(Some(Application {
+            id: 1,
+            name: String::from("Teste"),
+            path: String::from("/teste"),
+            url_destination: String::from("http://anothergtw.com"),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }))
+    });
+
+    mock_repo
+        .expect_update()
+        .withf(|application| {
+            application.name == "Teste"
+                && application.path == "/orders"
+                && application.url_destination == "http://orders.anothergtw.com"
+        })
+        .returning(Ok);
+
+    let request = ApplicationReq {
+        name: None,
+        path: Some("/orders".to_string()),
+        url_destination: Some("http://orders.anothergtw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+    };
+
+    let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.update(1, request).await;
+    assert!(response.is_ok());
+    assert_eq!("/orders", response.unwrap().path);
+}
+
+#[tokio::test]
+async fn update_preserve_host() {
+    let mut mock_repo = MockApplicationRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| {
+        Ok(Some(Application {
+            id: 1,
+            name: String::from("Teste"),
+            path: String::from("/teste"),
+            url_destination: String::from("http://anothergtw.com"),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }))
+    });
+
+    mock_repo
+        .expect_update()
+        .withf(|application| application.path == "/teste" && application.preserve_host)
+        .returning(Ok);
+
+    let request = ApplicationReq {
+        name: None,
+        path: None,
+        url_destination: None,
+        preserve_host: Some(true),
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+    };
+
+    let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.update(1, request).await;
+    assert!(response.is_ok());
+    assert!(response.unwrap().preserve_host);
+}
+
 #[tokio::test]
 async fn update_application_not_found() {
     let mut mock_repo = MockApplicationRepositoryTrait::new();
@@ -221,6 +524,12 @@ async fn update_application_not_found() {
         name: Some("teste".to_string()),
         path: Some("/teste".to_string()),
         url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
     };
 
     let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
@@ -236,6 +545,12 @@ async fn update_with_field_errors() {
         name: Some("te".to_string()),
         path: Some("/teste".to_string()),
         url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
     };
 
     let service = ApplicationService::new_with_repo(Arc::new(MockApplicationRepositoryTrait::new()));
@@ -256,6 +571,14 @@ async fn update_repository_error() {
         Ok(Some(Application {
             id: 1,
             name: String::from("Teste"),
+            path: String::from("/teste"),
+            url_destination: String::from("http://anothergtw.com"),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }))
@@ -269,6 +592,12 @@ async fn update_repository_error() {
         name: Some("teste".to_string()),
         path: Some("/teste".to_string()),
         url_destination: Some("http://anothergw.com".to_string()),
+        preserve_host: None,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
     };
 
     let service = ApplicationService::new_with_repo(Arc::new(mock_repo));
@@ -285,6 +614,14 @@ async fn delete() {
         Ok(Some(Application {
             id: 1,
             name: String::from("Teste"),
+            path: String::from("/teste"),
+            url_destination: String::from("http://anothergtw.com"),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }))
@@ -317,6 +654,14 @@ async fn delete_with_repository_error() {
         Ok(Some(Application {
             id: 1,
             name: String::from("Teste"),
+            path: String::from("/teste"),
+            url_destination: String::from("http://anothergtw.com"),
+            preserve_host: false,
+            websocket_max_connections: None,
+            upstream_policy: None,
+            id_upstream: None,
+            rate_limit: None,
+            auth: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }))
@@ -0,0 +1,137 @@
+#[cfg(test)]
+#[path = "application_workflow_service_test.rs"]
+mod application_workflow_service_test;
+
+use std::sync::Arc;
+
+use axum::async_trait;
+use hyper::StatusCode;
+use sqlx::PgPool;
+
+use crate::{
+    exception::{ApiError, WF_ERR_NOT_FOUND},
+    model::{ApplicationWorkflow, ApplicationWorkflowReq, Pagination, PaginationResponse},
+    repository::{ApplicationWorkflowRepository, ApplicationWorkflowRepositoryTrait},
+};
+
+#[async_trait]
+pub trait ApplicationWorkflowServiceTrait {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ApplicationWorkflow>, ApiError>;
+
+    async fn find_by_id(&self, id: i64) -> Result<ApplicationWorkflow, ApiError>;
+
+    async fn save(&self, entity: ApplicationWorkflowReq) -> Result<ApplicationWorkflow, ApiError>;
+
+    async fn update(
+        &self,
+        id: i64,
+        entity: ApplicationWorkflowReq,
+    ) -> Result<ApplicationWorkflow, ApiError>;
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError>;
+}
+
+pub struct ApplicationWorkflowService {
+    workflow_repository: Arc<dyn ApplicationWorkflowRepositoryTrait + Send + Sync>,
+}
+
+#[async_trait]
+impl ApplicationWorkflowServiceTrait for ApplicationWorkflowService {
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginationResponse<ApplicationWorkflow>, ApiError> {
+        pagination.validate()?;
+
+        let response = self.workflow_repository.find_all(pagination).await?;
+        Ok(response)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<ApplicationWorkflow, ApiError> {
+        let response = self.workflow_repository.find_by_id(id).await?;
+
+        if response.is_none() {
+            return Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                WF_ERR_NOT_FOUND,
+            ));
+        }
+        Ok(response.unwrap())
+    }
+
+    async fn save(&self, entity: ApplicationWorkflowReq) -> Result<ApplicationWorkflow, ApiError> {
+        entity.validate()?;
+
+        let workflow = self.workflow_repository.save(entity).await?;
+        Ok(workflow)
+    }
+
+    async fn update(
+        &self,
+        id: i64,
+        entity: ApplicationWorkflowReq,
+    ) -> Result<ApplicationWorkflow, ApiError> {
+        entity.validate_updating()?;
+
+        if let Some(mut workflow) = self.workflow_repository.find_by_id(id).await? {
+            if let Some(path) = entity.path {
+                workflow.path = path;
+            }
+
+            if let Some(forward_to) = entity.forward_to {
+                workflow.forward_to = forward_to;
+            }
+
+            if let Some(status) = entity.status {
+                workflow.status = status;
+            }
+
+            if let Some(priority) = entity.priority {
+                workflow.priority = priority;
+            }
+
+            if let Some(upstream_policy) = entity.upstream_policy {
+                workflow.upstream_policy = Some(upstream_policy);
+            }
+
+            workflow = self.workflow_repository.update(workflow).await?;
+            Ok(workflow)
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                WF_ERR_NOT_FOUND,
+            ))
+        }
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), ApiError> {
+        if (self.workflow_repository.find_by_id(id).await?).is_some() {
+            self.workflow_repository.delete(id).await?;
+            Ok(())
+        } else {
+            Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                WF_ERR_NOT_FOUND,
+            ))
+        }
+    }
+}
+
+impl ApplicationWorkflowService {
+    pub fn new(pg_pool: Arc<PgPool>) -> Self {
+        ApplicationWorkflowService {
+            workflow_repository: Arc::new(ApplicationWorkflowRepository { pg_pool }),
+        }
+    }
+
+    pub fn new_with_repo(
+        repository: Arc<dyn ApplicationWorkflowRepositoryTrait + Send + Sync>,
+    ) -> Self {
+        ApplicationWorkflowService {
+            workflow_repository: repository,
+        }
+    }
+}
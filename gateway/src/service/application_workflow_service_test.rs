@@ -0,0 +1,230 @@
+use chrono::Utc;
+
+use crate::{
+    exception::{
+        ERR_INVALID_PATH_PATTERN, ERR_INVALID_SUB_PATH, ERR_INVALID_URL, ERR_INVALID_VALUE,
+        PG_ERR_PAGE_REQUIRED, WF_ERR_APPLICATION_NOT_FOUND, WF_ERR_DELETE,
+    },
+    repository::MockApplicationWorkflowRepositoryTrait,
+};
+
+use super::*;
+
+fn workflow() -> ApplicationWorkflow {
+    ApplicationWorkflow {
+        id: 1,
+        id_application: 1,
+        path: String::from("/v1"),
+        forward_to: String::from("http://anothergtw.com"),
+        status: String::from("ACTIVE"),
+        priority: 0,
+        upstream_policy: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn find_all_without_page() {
+    let service = ApplicationWorkflowService::new_with_repo(Arc::new(
+        MockApplicationWorkflowRepositoryTrait::new(),
+    ));
+
+    let response = service
+        .find_all(Pagination {
+            page: None,
+            page_size: None,
+        })
+        .await;
+    assert!(response.is_err());
+    assert_eq!(PG_ERR_PAGE_REQUIRED.0, response.unwrap_err().code);
+}
+
+#[tokio::test]
+async fn find_by_id_not_found() {
+    let mut mock_repo = MockApplicationWorkflowRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| Ok(None));
+
+    let service = ApplicationWorkflowService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.find_by_id(1).await;
+    assert!(response.is_err());
+    assert_eq!(WF_ERR_NOT_FOUND.0, response.unwrap_err().code);
+}
+
+#[tokio::test]
+async fn save() {
+    let mut mock_repo = MockApplicationWorkflowRepositoryTrait::new();
+    mock_repo.expect_save().returning(|_| Ok(workflow()));
+
+    let request = ApplicationWorkflowReq {
+        id_application: Some(1),
+        path: Some("/v1".to_string()),
+        forward_to: Some("http://anothergtw.com".to_string()),
+        status: Some("ACTIVE".to_string()),
+        priority: None,
+        upstream_policy: None,
+    };
+
+    let service = ApplicationWorkflowService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.save(request).await;
+    assert!(response.is_ok());
+    assert_eq!(1, response.unwrap().id);
+}
+
+#[tokio::test]
+async fn save_without_fields() {
+    let request = ApplicationWorkflowReq {
+        id_application: None,
+        path: None,
+        forward_to: None,
+        status: None,
+        priority: None,
+        upstream_policy: None,
+    };
+
+    let service = ApplicationWorkflowService::new_with_repo(Arc::new(
+        MockApplicationWorkflowRepositoryTrait::new(),
+    ));
+
+    let response = service.save(request).await;
+    assert!(response.is_err());
+    assert_eq!(4, response.unwrap_err().field_errors.unwrap().len());
+}
+
+#[tokio::test]
+async fn save_with_invalid_fields() {
+    let request = ApplicationWorkflowReq {
+        id_application: Some(1),
+        path: Some("v1".to_string()),
+        forward_to: Some("anothergtw".to_string()),
+        status: Some("DISABLED".to_string()),
+        priority: None,
+        upstream_policy: None,
+    };
+
+    let service = ApplicationWorkflowService::new_with_repo(Arc::new(
+        MockApplicationWorkflowRepositoryTrait::new(),
+    ));
+
+    let response = service.save(request).await;
+    assert!(response.is_err());
+
+    let field_errors = response.unwrap_err().field_errors.unwrap();
+    assert_eq!(3, field_errors.len());
+    assert_eq!(ERR_INVALID_SUB_PATH.0, field_errors[0].code);
+    assert_eq!(ERR_INVALID_URL.0, field_errors[1].code);
+    assert_eq!(ERR_INVALID_VALUE.0, field_errors[2].code);
+}
+
+#[tokio::test]
+async fn save_with_path_pattern() {
+    let mut mock_repo = MockApplicationWorkflowRepositoryTrait::new();
+    mock_repo.expect_save().returning(|_| Ok(workflow()));
+
+    let request = ApplicationWorkflowReq {
+        id_application: Some(1),
+        path: Some("/:version(v\\d+)/tenants/:tenant".to_string()),
+        forward_to: Some("http://{{request.path.tenant}}.anothergtw.com".to_string()),
+        status: Some("ACTIVE".to_string()),
+        priority: Some(10),
+        upstream_policy: None,
+    };
+
+    let service = ApplicationWorkflowService::new_with_repo(Arc::new(mock_repo));
+    assert!(service.save(request).await.is_ok());
+
+    let request = ApplicationWorkflowReq {
+        id_application: Some(1),
+        path: Some("/files/**/:name".to_string()),
+        forward_to: Some("http://anothergtw.com".to_string()),
+        status: Some("ACTIVE".to_string()),
+        priority: None,
+        upstream_policy: None,
+    };
+
+    let response = service.save(request).await;
+    assert!(response.is_err());
+
+    let field_errors = response.unwrap_err().field_errors.unwrap();
+    assert_eq!(1, field_errors.len());
+    assert_eq!(ERR_INVALID_PATH_PATTERN.0, field_errors[0].code);
+}
+
+#[tokio::test]
+async fn save_with_application_not_found() {
+    let mut mock_repo = MockApplicationWorkflowRepositoryTrait::new();
+    mock_repo
+        .expect_save()
+        .returning(|_| Err(ApiError::new(WF_ERR_APPLICATION_NOT_FOUND)));
+
+    let request = ApplicationWorkflowReq {
+        id_application: Some(99),
+        path: Some("/v1".to_string()),
+        forward_to: Some("http://anothergtw.com".to_string()),
+        status: Some("ACTIVE".to_string()),
+        priority: None,
+        upstream_policy: None,
+    };
+
+    let service = ApplicationWorkflowService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.save(request).await;
+    assert!(response.is_err());
+    assert_eq!(WF_ERR_APPLICATION_NOT_FOUND.0, response.unwrap_err().code);
+}
+
+#[tokio::test]
+async fn update() {
+    let mut mock_repo = MockApplicationWorkflowRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(workflow())));
+    mock_repo
+        .expect_update()
+        .withf(|workflow| workflow.path == "/v1" && workflow.status == "INACTIVE")
+        .returning(Ok);
+
+    let request = ApplicationWorkflowReq {
+        id_application: None,
+        path: None,
+        forward_to: None,
+        status: Some("INACTIVE".to_string()),
+        priority: None,
+        upstream_policy: None,
+    };
+
+    let service = ApplicationWorkflowService::new_with_repo(Arc::new(mock_repo));
+
+    let response = service.update(1, request).await;
+    assert!(response.is_ok());
+    assert_eq!("INACTIVE", response.unwrap().status);
+}
+
+#[tokio::test]
+async fn delete_workflow_not_found() {
+    let mut mock_repo = MockApplicationWorkflowRepositoryTrait::new();
+    mock_repo.expect_find_by_id().returning(|_| Ok(None));
+
+    let service = ApplicationWorkflowService::new_with_repo(Arc::new(mock_repo));
+    let response = service.delete(1).await;
+    assert!(response.is_err());
+    assert_eq!(WF_ERR_NOT_FOUND.0, response.unwrap_err().code);
+}
+
+#[tokio::test]
+async fn delete_with_repository_error() {
+    let mut mock_repo = MockApplicationWorkflowRepositoryTrait::new();
+    mock_repo
+        .expect_find_by_id()
+        .returning(|_| Ok(Some(workflow())));
+    mock_repo
+        .expect_delete()
+        .returning(|_| Err(ApiError::new(WF_ERR_DELETE)));
+
+    let service = ApplicationWorkflowService::new_with_repo(Arc::new(mock_repo));
+    let response = service.delete(1).await;
+    assert!(response.is_err());
+    assert_eq!(WF_ERR_DELETE.0, response.unwrap_err().code);
+}
@@ -0,0 +1,50 @@
+#[cfg(test)]
+#[path = "body_buffer_test.rs"]
+mod body_buffer_test;
+
+use hyper::{
+    body::{Bytes, HttpBody},
+    StatusCode,
+};
+
+use crate::exception::{ApiError, FORWARD_ERR_BODY_TOO_LARGE, FORWARD_ERR_READING_BODY};
+
+const DEFAULT_MAX_BUFFERED_BODY_SIZE: usize = 10 * 1024 * 1024;
+
+// forwarded bodies are streamed, only the features that need the whole body (orchestrations)
+// buffer it and never more than MAX_BUFFERED_BODY_SIZE bytes.
+pub fn max_buffered_body_size() -> usize {
+    std::env::var("MAX_BUFFERED_BODY_SIZE")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(DEFAULT_MAX_BUFFERED_BODY_SIZE)
+}
+
+// also takes a &mut Body, whose trailers can still be read afterwards.
+pub async fn buffer_body<B>(mut body: B, max_size: usize) -> Result<Bytes, ApiError>
+where
+    B: HttpBody<Data = Bytes, Error = hyper::Error> + Unpin,
+{
+    let too_large =
+        || ApiError::new_with_status(StatusCode::PAYLOAD_TOO_LARGE, FORWARD_ERR_BODY_TOO_LARGE);
+
+    // a declared content-length is enough to refuse it before reading anything.
+    if body.size_hint().lower() > max_size as u64 {
+        return Err(too_large());
+    }
+
+    let mut buffer = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| {
+            tracing::error!("Error when reading a body: {:?}", e);
+            ApiError::new(FORWARD_ERR_READING_BODY)
+        })?;
+
+        if buffer.len() + chunk.len() > max_size {
+            return Err(too_large());
+        }
+        buffer.extend_from_slice(&chunk);
+    }
+
+    Ok(Bytes::from(buffer))
+}
@@ -0,0 +1,36 @@
+use hyper::Body;
+
+use super::*;
+
+#[tokio::test]
+async fn buffer() {
+    let (mut sender, body) = Body::channel();
+    tokio::spawn(async move {
+        sender.send_data(Bytes::from("first ")).await.unwrap();
+        sender.send_data(Bytes::from("second")).await.unwrap();
+    });
+
+    let response = buffer_body(body, 12).await;
+    assert!(response.is_ok());
+    assert_eq!(Bytes::from("first second"), response.unwrap());
+}
+
+#[tokio::test]
+async fn buffer_too_large() {
+    let (mut sender, body) = Body::channel();
+    tokio::spawn(async move {
+        sender.send_data(Bytes::from("first ")).await.unwrap();
+        sender.send_data(Bytes::from("second")).await.unwrap();
+    });
+
+    let response = buffer_body(body, 11).await;
+    assert!(response.is_err());
+
+    let api_error = response.unwrap_err();
+    assert_eq!(413, api_error.status_code);
+    assert_eq!(FORWARD_ERR_BODY_TOO_LARGE.0, api_error.code);
+
+    let response = buffer_body(Body::from("first second"), 11).await;
+    assert!(response.is_err());
+    assert_eq!(FORWARD_ERR_BODY_TOO_LARGE.0, response.unwrap_err().code);
+}
@@ -0,0 +1,440 @@
+#[cfg(test)]
+#[path = "certificate_store_test.rs"]
+mod certificate_store_test;
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    error::Error,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use arc_swap::ArcSwap;
+use chrono::{DateTime, Utc};
+use rustls::{
+    server::{ClientHello, ResolvesServerCert},
+    sign::{self, CertifiedKey},
+    PrivateKey,
+};
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+
+use crate::{
+    config::Rustls,
+    exception::ApiError,
+    model::{parse_private_key, Certificate, CertificateDetails},
+    repository::{CertificateRepository, CertificateRepositoryTrait},
+    secret::SecretCipher,
+};
+
+use super::{
+    Metrics, RoutingEvent, RoutingService, GATEWAY_CERTIFICATE_EXPIRING,
+    GATEWAY_CERTIFICATE_EXPIRY_SECONDS,
+};
+
+pub const CERTIFICATE_SOURCE_FILE: &str = "file";
+pub const CERTIFICATE_SOURCE_DIRECTORY: &str = "directory";
+pub const CERTIFICATE_SOURCE_DATABASE: &str = "database";
+
+// <name>.crt of CERTS_DIR goes with <name>.key, the one named default is the default certificate.
+const DIRECTORY_CERTIFICATE_EXTENSION: &str = "crt";
+const DIRECTORY_KEY_EXTENSION: &str = "key";
+const DIRECTORY_DEFAULT_NAME: &str = "default";
+
+// a certificate ready to be presented, the hostnames come from the certificate itself.
+pub struct StoredCertificate {
+    pub source: &'static str,
+    pub name: String,
+    pub hostnames: Vec<String>,
+    pub not_after: DateTime<Utc>,
+    pub is_default: bool,
+    pub key: Arc<CertifiedKey>,
+}
+
+impl StoredCertificate {
+    pub fn load(
+        source: &'static str,
+        name: &str,
+        chain: &str,
+        private_key: &str,
+        is_default: bool,
+    ) -> Result<StoredCertificate, Box<dyn Error>> {
+        let details = CertificateDetails::parse(chain).ok_or("no certificate in the chain")?;
+        let certs = rustls_pemfile::certs(&mut chain.as_bytes())?
+            .into_iter()
+            .map(rustls::Certificate)
+            .collect();
+        let private_key = parse_private_key(private_key).ok_or("no private key")?;
+        let signing_key = sign::any_supported_type(&PrivateKey(private_key))
+            .map_err(|_| "unsupported private key")?;
+
+        Ok(StoredCertificate {
+            source,
+            name: name.to_owned(),
+            hostnames: details.hostnames,
+            not_after: details.not_after,
+            is_default,
+            key: Arc::new(CertifiedKey::new(certs, signing_key)),
+        })
+    }
+
+    fn load_files(
+        source: &'static str,
+        name: &str,
+        cert_file: &Path,
+        key_file: &Path,
+        is_default: bool,
+    ) -> Result<StoredCertificate, Box<dyn Error>> {
+        let chain = std::fs::read_to_string(cert_file)?;
+        let private_key = std::fs::read_to_string(key_file)?;
+        StoredCertificate::load(source, name, &chain, &private_key, is_default)
+    }
+}
+
+// certificates by hostname, the wildcard ones under their "*." name.
+#[derive(Default)]
+pub struct CertificateTable {
+    hostnames: HashMap<String, Arc<CertifiedKey>>,
+    default: Option<Arc<CertifiedKey>>,
+    len: usize,
+    // tells a reload that changed something from the periodic ones.
+    fingerprint: u64,
+}
+
+impl CertificateTable {
+    // later certificates take the hostnames of the earlier ones, the database ones come last.
+    pub fn build(certificates: &[StoredCertificate]) -> CertificateTable {
+        let mut table = CertificateTable {
+            len: certificates.len(),
+            ..CertificateTable::default()
+        };
+        let mut hasher = DefaultHasher::new();
+
+        for certificate in certificates {
+            for hostname in &certificate.hostnames {
+                table
+                    .hostnames
+                    .insert(hostname.clone(), Arc::clone(&certificate.key));
+            }
+            if certificate.is_default {
+                table.default = Some(Arc::clone(&certificate.key));
+            }
+
+            (
+                certificate.source,
+                &certificate.name,
+                certificate.is_default,
+            )
+                .hash(&mut hasher);
+            for cert in &certificate.key.cert {
+                cert.0.hash(&mut hasher);
+            }
+        }
+
+        table.fingerprint = hasher.finish();
+        table
+    }
+
+    // the exact name, then the wildcard of its parent domain, then the default certificate.
+    pub fn resolve(&self, server_name: Option<&str>) -> Option<Arc<CertifiedKey>> {
+        server_name
+            .map(str::to_ascii_lowercase)
+            .and_then(|server_name| {
+                self.hostnames.get(&server_name).or_else(|| {
+                    let (_, parent) = server_name.split_once('.')?;
+                    self.hostnames.get(&format!("*.{}", parent))
+                })
+            })
+            .or(self.default.as_ref())
+            .cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+// certificates the gateway presents by sni, from CERT_FILE and KEY_FILE, CERTS_DIR and the admin
+// api.
+pub struct CertificateStore {
+    certificate_repository: Arc<dyn CertificateRepositoryTrait + Send + Sync>,
+    cipher: Option<SecretCipher>,
+    files: Option<(PathBuf, PathBuf)>,
+    directory: Option<PathBuf>,
+    expiry_warning: chrono::Duration,
+    table: ArcSwap<CertificateTable>,
+    // series of the expiry gauges, with whether the certificate was already expiring.
+    expiring: Mutex<HashMap<(&'static str, String), bool>>,
+}
+
+// a name without a certificate and without a default one fails the handshake, as does any name
+// while the store is empty.
+impl ResolvesServerCert for CertificateStore {
+    fn resolve(&self, client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        self.table.load().resolve(client_hello.server_name())
+    }
+}
+
+impl CertificateStore {
+    pub fn new(pg_pool: Arc<PgPool>) -> Self {
+        let files = std::env::var("CERT_FILE")
+            .ok()
+            .zip(std::env::var("KEY_FILE").ok())
+            .map(|(cert_file, key_file)| (Rustls::path(&cert_file), Rustls::path(&key_file)));
+        let directory = std::env::var("CERTS_DIR")
+            .ok()
+            .map(|directory| Rustls::path(&directory));
+        let expiry_warning = chrono::Duration::days(
+            std::env::var("CERTIFICATE_EXPIRY_WARNING_DAYS")
+                .unwrap_or(String::from("30"))
+                .parse()
+                .unwrap_or(30),
+        );
+
+        CertificateStore::new_with_repo(
+            Arc::new(CertificateRepository { pg_pool }),
+            SecretCipher::config(),
+            files,
+            directory,
+            expiry_warning,
+        )
+    }
+
+    pub fn new_with_repo(
+        repository: Arc<dyn CertificateRepositoryTrait + Send + Sync>,
+        cipher: Option<SecretCipher>,
+        files: Option<(PathBuf, PathBuf)>,
+        directory: Option<PathBuf>,
+        expiry_warning: chrono::Duration,
+    ) -> Self {
+        CertificateStore {
+            certificate_repository: repository,
+            cipher,
+            files,
+            directory,
+            expiry_warning,
+            table: ArcSwap::from_pointee(CertificateTable::default()),
+            expiring: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn table(&self) -> Arc<CertificateTable> {
+        self.table.load_full()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.load().is_empty()
+    }
+
+    // certificates are expected from the files or the directory, even when there is none yet.
+    pub fn is_configured(&self) -> bool {
+        self.files.is_some() || self.directory.is_some()
+    }
+
+    // CERTIFICATE_RELOAD_INTERVAL, in seconds, also applies to the client ca and the upstream tls
+    // files.
+    pub fn reload_interval() -> Duration {
+        Duration::from_secs(
+            std::env::var("CERTIFICATE_RELOAD_INTERVAL")
+                .unwrap_or(String::from("60"))
+                .parse()
+                .unwrap_or(60),
+        )
+    }
+
+    // a broken certificate is left out, the others are still served. the table is kept when the
+    // database can't be read.
+    pub async fn reload(&self) -> Result<(), ApiError> {
+        let rows = self.certificate_repository.find_certificates().await?;
+
+        let mut certificates = Vec::new();
+        if let Some((cert_file, key_file)) = &self.files {
+            let name = cert_file.to_string_lossy();
+            let loaded = StoredCertificate::load_files(
+                CERTIFICATE_SOURCE_FILE,
+                &name,
+                cert_file,
+                key_file,
+                true,
+            );
+            certificates.extend(loaded_or_logged(loaded, CERTIFICATE_SOURCE_FILE, &name));
+        }
+        if let Some(directory) = &self.directory {
+            certificates.append(&mut CertificateStore::load_directory(directory));
+        }
+        for row in rows {
+            let loaded = self.load_row(&row);
+            certificates.extend(loaded_or_logged(
+                loaded,
+                CERTIFICATE_SOURCE_DATABASE,
+                &row.name,
+            ));
+        }
+
+        self.record_expiry(&certificates, Utc::now());
+
+        let table = CertificateTable::build(&certificates);
+        if table.fingerprint != self.table.load().fingerprint {
+            tracing::info!(
+                "{} certificates loaded for {} hostnames",
+                table.len(),
+                table.hostnames.len()
+            );
+        }
+        self.table.store(Arc::new(table));
+        Ok(())
+    }
+
+    // the files are read again periodically, the rows when the admin api notifies a change.
+    pub fn listen(store: Arc<CertificateStore>, pg_pool: Arc<PgPool>) {
+        let reload_interval = CertificateStore::reload_interval();
+
+        let (sender, mut receiver) = mpsc::unbounded_channel::<RoutingEvent>();
+        tokio::spawn(RoutingService::receive_notifications(pg_pool, sender));
+
+        tokio::spawn(async move {
+            loop {
+                // the notifications don't tell the entity, reloading a few certificates is cheap.
+                match tokio::time::timeout(reload_interval, receiver.recv()).await {
+                    Ok(Some(_)) => {
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        while receiver.try_recv().is_ok() {}
+                    }
+                    Ok(None) => break,
+                    Err(_) => tracing::debug!("reloading certificates periodically"),
+                }
+
+                if let Err(e) = store.reload().await {
+                    tracing::error!("Error when reloading the certificates: {}", e);
+                }
+            }
+        });
+    }
+
+    fn load_directory(directory: &Path) -> Vec<StoredCertificate> {
+        let entries = match std::fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::error!(
+                    "Error when reading the certificates of {}: {}",
+                    directory.display(),
+                    e
+                );
+                return Vec::new();
+            }
+        };
+
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == DIRECTORY_CERTIFICATE_EXTENSION)
+            })
+            .filter_map(|path| path.file_stem()?.to_str().map(str::to_owned))
+            .collect();
+        // the same hostname in two files always resolves to the same one.
+        names.sort();
+
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let loaded = StoredCertificate::load_files(
+                    CERTIFICATE_SOURCE_DIRECTORY,
+                    &name,
+                    &directory.join(format!("{}.{}", name, DIRECTORY_CERTIFICATE_EXTENSION)),
+                    &directory.join(format!("{}.{}", name, DIRECTORY_KEY_EXTENSION)),
+                    name == DIRECTORY_DEFAULT_NAME,
+                );
+                loaded_or_logged(loaded, CERTIFICATE_SOURCE_DIRECTORY, &name)
+            })
+            .collect()
+    }
+
+    fn load_row(&self, certificate: &Certificate) -> Result<StoredCertificate, Box<dyn Error>> {
+        let cipher = self
+            .cipher
+            .as_ref()
+            .ok_or("CREDENTIALS_ENCRYPTION_KEY is required to open the private key")?;
+        let private_key = cipher
+            .open(
+                &certificate.private_key,
+                &Certificate::sealing_context(certificate.id),
+            )
+            .map_err(|_| "the private key could not be decrypted")?;
+
+        StoredCertificate::load(
+            CERTIFICATE_SOURCE_DATABASE,
+            &certificate.name,
+            &certificate.certificate,
+            &private_key,
+            certificate.is_default,
+        )
+    }
+
+    // warns once when a certificate enters the warning period, the gauges keep telling it.
+    fn record_expiry(&self, certificates: &[StoredCertificate], now: DateTime<Utc>) {
+        let metrics = Metrics::global();
+        let mut expiring = self.expiring.lock().unwrap();
+        let mut recorded = HashMap::new();
+
+        for certificate in certificates {
+            let labels = [
+                ("source", certificate.source),
+                ("name", certificate.name.as_str()),
+            ];
+            let remaining = certificate.not_after - now;
+            let is_expiring = remaining < self.expiry_warning;
+
+            metrics.set(
+                &GATEWAY_CERTIFICATE_EXPIRY_SECONDS,
+                &labels,
+                remaining.num_seconds(),
+            );
+            metrics.set(&GATEWAY_CERTIFICATE_EXPIRING, &labels, is_expiring as i64);
+
+            let key = (certificate.source, certificate.name.clone());
+            if is_expiring && expiring.get(&key) != Some(&true) {
+                tracing::warn!(
+                    "the certificate {} from {} expires at {}",
+                    certificate.name,
+                    certificate.source,
+                    certificate.not_after
+                );
+            }
+            recorded.insert(key, is_expiring);
+        }
+
+        for (source, name) in expiring.keys() {
+            if !recorded.contains_key(&(*source, name.clone())) {
+                let labels = [("source", *source), ("name", name.as_str())];
+                metrics.remove(&GATEWAY_CERTIFICATE_EXPIRY_SECONDS, &labels);
+                metrics.remove(&GATEWAY_CERTIFICATE_EXPIRING, &labels);
+            }
+        }
+        *expiring = recorded;
+    }
+}
+
+fn loaded_or_logged(
+    loaded: Result<StoredCertificate, Box<dyn Error>>,
+    source: &str,
+    name: &str,
+) -> Option<StoredCertificate> {
+    loaded
+        .map_err(|e| {
+            tracing::error!(
+                "Error when loading the certificate {} from {}: {}",
+                name,
+                source,
+                e
+            )
+        })
+        .ok()
+}
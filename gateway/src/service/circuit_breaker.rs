@@ -0,0 +1,270 @@
+#[cfg(test)]
+#[path = "circuit_breaker_test.rs"]
+mod circuit_breaker_test;
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use axum::http::{uri::Uri, Response};
+use hyper::{
+    header::{HeaderName, HeaderValue, CONTENT_TYPE},
+    Body, StatusCode,
+};
+use serde::Serialize;
+
+use crate::model::{CircuitBreakerPolicy, FallbackResponse};
+
+use super::{Metrics, GATEWAY_CIRCUIT_BREAKER_STATE};
+
+// the rolling window is split in buckets, the oldest one is dropped as a whole.
+const WINDOW_BUCKETS: u32 = 10;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl CircuitState {
+    fn gauge(&self) -> i64 {
+        match self {
+            CircuitState::Closed => 0,
+            CircuitState::HalfOpen => 1,
+            CircuitState::Open => 2,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitSnapshot {
+    pub upstream: String,
+    pub state: CircuitState,
+    pub requests: u64,
+    pub failures: u64,
+    pub consecutive_failures: i32,
+    // time in the current state.
+    pub since_ms: u128,
+}
+
+struct Bucket {
+    started: Instant,
+    requests: u64,
+    failures: u64,
+}
+
+struct CircuitBreaker {
+    state: CircuitState,
+    since: Instant,
+    buckets: VecDeque<Bucket>,
+    consecutive_failures: i32,
+    trials: i32,
+    successful_trials: i32,
+}
+
+// one breaker for each upstream (scheme and authority), shared by the routes sending to it.
+// the thresholds come from the policy of the route of every request.
+#[derive(Default)]
+pub struct CircuitBreakers {
+    breakers: Mutex<BTreeMap<String, CircuitBreaker>>,
+}
+
+impl CircuitBreakers {
+    // false while the circuit is open. once the open duration has passed, a few trial requests
+    // go through; trials that never report back are given up after the same duration.
+    pub fn try_acquire(&self, upstream: &str, policy: &CircuitBreakerPolicy) -> bool {
+        let mut breakers = self.breakers.lock().unwrap();
+        let breaker = breakers
+            .entry(upstream.to_owned())
+            .or_insert_with(CircuitBreaker::new);
+
+        match breaker.state {
+            CircuitState::Closed => true,
+            CircuitState::Open if breaker.since.elapsed() < policy.open_duration() => false,
+            CircuitState::Open => {
+                breaker.transition(upstream, CircuitState::HalfOpen);
+                breaker.trials = 1;
+                true
+            }
+            CircuitState::HalfOpen => {
+                if breaker.since.elapsed() >= policy.open_duration() {
+                    breaker.transition(upstream, CircuitState::HalfOpen);
+                }
+                if breaker.trials < policy.half_open_requests() {
+                    breaker.trials += 1;
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    pub fn record(&self, upstream: &str, policy: &CircuitBreakerPolicy, success: bool) {
+        let mut breakers = self.breakers.lock().unwrap();
+        let breaker = breakers
+            .entry(upstream.to_owned())
+            .or_insert_with(CircuitBreaker::new);
+
+        match breaker.state {
+            // answers of requests sent before the circuit opened.
+            CircuitState::Open => {}
+            CircuitState::HalfOpen if !success => {
+                tracing::warn!(
+                    "trial request to {} has failed, circuit open again",
+                    upstream
+                );
+                breaker.transition(upstream, CircuitState::Open);
+            }
+            CircuitState::HalfOpen => {
+                breaker.successful_trials += 1;
+                if breaker.successful_trials >= policy.half_open_requests() {
+                    tracing::info!("circuit of {} closed", upstream);
+                    breaker.transition(upstream, CircuitState::Closed);
+                }
+            }
+            CircuitState::Closed => {
+                breaker.count(policy.window(), success);
+                let (requests, failures) = breaker.totals();
+
+                let failure_rate_reached = requests >= policy.minimum_requests() as u64
+                    && failures * 100 >= requests * policy.failure_rate() as u64;
+                if failure_rate_reached
+                    || breaker.consecutive_failures >= policy.consecutive_failures()
+                {
+                    tracing::warn!(
+                        "circuit of {} open, {} of {} requests and the last {} have failed",
+                        upstream,
+                        failures,
+                        requests,
+                        breaker.consecutive_failures
+                    );
+                    breaker.transition(upstream, CircuitState::Open);
+                }
+            }
+        }
+    }
+
+    pub fn snapshot(&self) -> Vec<CircuitSnapshot> {
+        let breakers = self.breakers.lock().unwrap();
+        breakers
+            .iter()
+            .map(|(upstream, breaker)| {
+                let (requests, failures) = breaker.totals();
+                CircuitSnapshot {
+                    upstream: upstream.clone(),
+                    state: breaker.state,
+                    requests,
+                    failures,
+                    consecutive_failures: breaker.consecutive_failures,
+                    since_ms: breaker.since.elapsed().as_millis(),
+                }
+            })
+            .collect()
+    }
+}
+
+impl CircuitBreaker {
+    fn new() -> CircuitBreaker {
+        CircuitBreaker {
+            state: CircuitState::Closed,
+            since: Instant::now(),
+            buckets: VecDeque::new(),
+            consecutive_failures: 0,
+            trials: 0,
+            successful_trials: 0,
+        }
+    }
+
+    // every state starts over, a closed circuit with an empty window.
+    fn transition(&mut self, upstream: &str, state: CircuitState) {
+        *self = CircuitBreaker {
+            state,
+            ..CircuitBreaker::new()
+        };
+        Metrics::global().set(
+            &GATEWAY_CIRCUIT_BREAKER_STATE,
+            &[("upstream", upstream)],
+            state.gauge(),
+        );
+    }
+
+    fn count(&mut self, window: Duration, success: bool) {
+        let now = Instant::now();
+        let bucket_duration = window / WINDOW_BUCKETS;
+        while self
+            .buckets
+            .front()
+            .is_some_and(|bucket| now.duration_since(bucket.started) >= window)
+        {
+            self.buckets.pop_front();
+        }
+        if self
+            .buckets
+            .back()
+            .is_none_or(|bucket| now.duration_since(bucket.started) >= bucket_duration)
+        {
+            self.buckets.push_back(Bucket {
+                started: now,
+                requests: 0,
+                failures: 0,
+            });
+        }
+
+        let bucket = self.buckets.back_mut().unwrap();
+        bucket.requests += 1;
+        if success {
+            self.consecutive_failures = 0;
+        } else {
+            bucket.failures += 1;
+            self.consecutive_failures += 1;
+        }
+    }
+
+    fn totals(&self) -> (u64, u64) {
+        self.buckets
+            .iter()
+            .fold((0, 0), |(requests, failures), bucket| {
+                (requests + bucket.requests, failures + bucket.failures)
+            })
+    }
+}
+
+pub fn upstream_key(uri: &Uri) -> String {
+    format!(
+        "{}://{}",
+        uri.scheme_str().unwrap_or("http"),
+        uri.authority().map_or("", |authority| authority.as_str())
+    )
+}
+
+pub fn fallback_response(fallback: &FallbackResponse) -> Response<Body> {
+    let mut response = Response::new(match &fallback.body {
+        Some(body) => Body::from(body.to_string()),
+        None => Body::empty(),
+    });
+    *response.status_mut() = fallback
+        .status
+        .and_then(|status| StatusCode::from_u16(status).ok())
+        .unwrap_or(StatusCode::OK);
+
+    let headers = response.headers_mut();
+    if fallback.body.is_some() {
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    }
+    for (name, value) in fallback.headers.iter().flatten() {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::try_from(name.as_str()),
+            HeaderValue::try_from(value.as_str()),
+        ) {
+            headers.insert(name, value);
+        }
+    }
+
+    response
+}
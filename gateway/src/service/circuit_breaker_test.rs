@@ -0,0 +1,169 @@
+use serde_json::json;
+
+use super::*;
+
+const UPSTREAM: &str = "http://127.0.0.1:8080";
+
+fn circuit_policy() -> CircuitBreakerPolicy {
+    CircuitBreakerPolicy {
+        failure_rate: Some(50),
+        consecutive_failures: Some(3),
+        minimum_requests: Some(4),
+        window_ms: Some(60_000),
+        open_ms: Some(50),
+        half_open_requests: Some(1),
+        fallback: None,
+    }
+}
+
+fn state(breakers: &CircuitBreakers) -> CircuitState {
+    breakers.snapshot()[0].state
+}
+
+#[test]
+fn open_after_consecutive_failures() {
+    let breakers = CircuitBreakers::default();
+    let policy = circuit_policy();
+
+    for _ in 0..2 {
+        assert!(breakers.try_acquire(UPSTREAM, &policy));
+        breakers.record(UPSTREAM, &policy, false);
+    }
+    assert_eq!(CircuitState::Closed, state(&breakers));
+
+    breakers.record(UPSTREAM, &policy, false);
+    assert_eq!(CircuitState::Open, state(&breakers));
+    assert!(!breakers.try_acquire(UPSTREAM, &policy));
+
+    // every upstream has its own circuit.
+    assert!(breakers.try_acquire("http://127.0.0.1:8081", &policy));
+}
+
+#[test]
+fn open_at_failure_rate() {
+    let breakers = CircuitBreakers::default();
+    let policy = CircuitBreakerPolicy {
+        consecutive_failures: Some(10),
+        ..circuit_policy()
+    };
+
+    // not before the minimum of requests.
+    for success in [false, false, true] {
+        breakers.record(UPSTREAM, &policy, success);
+    }
+    assert_eq!(CircuitState::Closed, state(&breakers));
+
+    breakers.record(UPSTREAM, &policy, true);
+    let snapshot = &breakers.snapshot()[0];
+    assert_eq!(CircuitState::Open, snapshot.state);
+    assert_eq!(0, snapshot.requests);
+}
+
+#[test]
+fn forget_failures_out_of_window() {
+    let breakers = CircuitBreakers::default();
+    let policy = CircuitBreakerPolicy {
+        consecutive_failures: Some(10),
+        window_ms: Some(50),
+        ..circuit_policy()
+    };
+
+    for _ in 0..3 {
+        breakers.record(UPSTREAM, &policy, false);
+    }
+    std::thread::sleep(Duration::from_millis(60));
+
+    for _ in 0..3 {
+        breakers.record(UPSTREAM, &policy, true);
+    }
+    breakers.record(UPSTREAM, &policy, false);
+
+    let snapshot = &breakers.snapshot()[0];
+    assert_eq!(CircuitState::Closed, snapshot.state);
+    assert_eq!(4, snapshot.requests);
+    assert_eq!(1, snapshot.failures);
+}
+
+#[test]
+fn close_after_successful_trial() {
+    let breakers = CircuitBreakers::default();
+    let policy = circuit_policy();
+
+    for _ in 0..3 {
+        breakers.record(UPSTREAM, &policy, false);
+    }
+    std::thread::sleep(Duration::from_millis(60));
+
+    // a single trial at a time.
+    assert!(breakers.try_acquire(UPSTREAM, &policy));
+    assert_eq!(CircuitState::HalfOpen, state(&breakers));
+    assert!(!breakers.try_acquire(UPSTREAM, &policy));
+
+    breakers.record(UPSTREAM, &policy, true);
+    assert_eq!(CircuitState::Closed, state(&breakers));
+    assert!(breakers.try_acquire(UPSTREAM, &policy));
+}
+
+#[test]
+fn open_again_after_failed_trial() {
+    let breakers = CircuitBreakers::default();
+    let policy = circuit_policy();
+
+    for _ in 0..3 {
+        breakers.record(UPSTREAM, &policy, false);
+    }
+    std::thread::sleep(Duration::from_millis(60));
+
+    assert!(breakers.try_acquire(UPSTREAM, &policy));
+    breakers.record(UPSTREAM, &policy, false);
+    assert_eq!(CircuitState::Open, state(&breakers));
+    assert!(!breakers.try_acquire(UPSTREAM, &policy));
+}
+
+#[test]
+fn retry_stuck_trial() {
+    let breakers = CircuitBreakers::default();
+    let policy = circuit_policy();
+
+    for _ in 0..3 {
+        breakers.record(UPSTREAM, &policy, false);
+    }
+    std::thread::sleep(Duration::from_millis(60));
+
+    assert!(breakers.try_acquire(UPSTREAM, &policy));
+    assert!(!breakers.try_acquire(UPSTREAM, &policy));
+
+    std::thread::sleep(Duration::from_millis(60));
+    assert!(breakers.try_acquire(UPSTREAM, &policy));
+}
+
+#[test]
+fn build_fallback_response() {
+    let fallback = FallbackResponse {
+        status: Some(503),
+        headers: Some(BTreeMap::from([(
+            String::from("retry-after"),
+            String::from("30"),
+        )])),
+        body: Some(json!({"message": "try again later"})),
+    };
+
+    let response = fallback_response(&fallback);
+    assert_eq!(StatusCode::SERVICE_UNAVAILABLE, response.status());
+    assert_eq!("30", response.headers()["retry-after"]);
+    assert_eq!("application/json", response.headers()[CONTENT_TYPE]);
+
+    let response = fallback_response(&FallbackResponse {
+        status: None,
+        headers: None,
+        body: None,
+    });
+    assert_eq!(StatusCode::OK, response.status());
+    assert!(response.headers().is_empty());
+}
+
+#[test]
+fn key_by_upstream() {
+    let uri = Uri::from_static("https://api.example.com:8443/orders?id=1");
+    assert_eq!("https://api.example.com:8443", upstream_key(&uri));
+}
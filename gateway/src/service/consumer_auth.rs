@@ -0,0 +1,268 @@
+#[cfg(test)]
+#[path = "consumer_auth_test.rs"]
+mod consumer_auth_test;
+
+use std::{collections::HashMap, sync::Arc};
+
+use axum::http::{uri::PathAndQuery, Request, Uri};
+use chrono::{DateTime, Utc};
+use hyper::{header::HeaderValue, Body, HeaderMap, StatusCode};
+use percent_encoding::percent_decode_str;
+
+use crate::{
+    config::{ClientCertificate, TlsConnection},
+    exception::{
+        ApiError, FORWARD_ERR_CLIENT_CERTIFICATE_FORBIDDEN,
+        FORWARD_ERR_CLIENT_CERTIFICATE_REQUIRED, FORWARD_ERR_UNAUTHORIZED,
+    },
+    model::{
+        hash_api_key, ApiKeyAuth, AuthPolicy, ClientCertificateAuth, Consumer, ConsumerApiKey,
+    },
+};
+
+use super::{routing_service::parse_rate_limit, ScopedRateLimit};
+
+// identity of the caller sent to the upstreams, the ones sent by the client are always dropped.
+pub const X_CONSUMER_ID: &str = "x-consumer-id";
+pub const X_CONSUMER_NAME: &str = "x-consumer-name";
+pub const X_AUTH_SUBJECT: &str = "x-auth-subject";
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct AuthenticatedConsumer {
+    pub id: i64,
+    pub name: String,
+    // limits of the consumer on every route, on top of the ones of the route.
+    pub rate_limit: Option<Arc<ScopedRateLimit>>,
+}
+
+// the caller of a request, as far as the auth of its target can tell.
+#[derive(Debug, Default)]
+pub struct Principal {
+    pub consumer: Option<Arc<AuthenticatedConsumer>>,
+    // sub of the jwt or of the introspected token.
+    pub subject: Option<String>,
+}
+
+impl Principal {
+    // what the limits by consumer count against, none for anonymous requests.
+    pub fn rate_limit_key(&self) -> Option<String> {
+        match (&self.consumer, &self.subject) {
+            (Some(consumer), _) => Some(format!("consumer:{}", consumer.id)),
+            (None, Some(subject)) => Some(format!("subject:{}", subject)),
+            (None, None) => None,
+        }
+    }
+}
+
+// consumers by the hash of their api keys, and by the subject of their client certificate.
+#[derive(Debug, Default)]
+pub struct ConsumerKeys {
+    keys: HashMap<String, (Option<DateTime<Utc>>, Arc<AuthenticatedConsumer>)>,
+    certificates: HashMap<String, Arc<AuthenticatedConsumer>>,
+}
+
+impl ConsumerKeys {
+    pub fn build(consumers: Vec<Consumer>, api_keys: Vec<ConsumerApiKey>) -> ConsumerKeys {
+        let mut certificates = HashMap::new();
+        let consumers: HashMap<i64, Arc<AuthenticatedConsumer>> = consumers
+            .into_iter()
+            .map(|consumer| {
+                let rate_limit = parse_rate_limit("consumer", consumer.id, &consumer.rate_limit);
+                let authenticated = AuthenticatedConsumer {
+                    id: consumer.id,
+                    name: consumer.name,
+                    rate_limit,
+                };
+                let authenticated = Arc::new(authenticated);
+                if let Some(subject) = consumer.certificate_subject {
+                    certificates.insert(subject, Arc::clone(&authenticated));
+                }
+                (consumer.id, authenticated)
+            })
+            .collect();
+
+        let keys = api_keys
+            .into_iter()
+            .filter_map(|api_key| {
+                let consumer = consumers.get(&api_key.id_consumer)?;
+                Some((api_key.key_hash, (api_key.expires_at, Arc::clone(consumer))))
+            })
+            .collect();
+
+        ConsumerKeys { keys, certificates }
+    }
+
+    // keys may expire between two reloads of the routing table, it's checked on each request.
+    pub fn find(&self, key: &str, now: DateTime<Utc>) -> Option<Arc<AuthenticatedConsumer>> {
+        match self.keys.get(&hash_api_key(key)) {
+            Some((expires_at, consumer))
+                if expires_at.is_none_or(|expires_at| expires_at > now) =>
+            {
+                Some(Arc::clone(consumer))
+            }
+            _ => None,
+        }
+    }
+
+    // the alternative names of the certificate are looked up before its common name.
+    pub fn find_by_certificate(
+        &self,
+        certificate: &ClientCertificate,
+    ) -> Option<Arc<AuthenticatedConsumer>> {
+        certificate
+            .identities()
+            .find_map(|identity| self.certificates.get(identity))
+            .cloned()
+    }
+
+    // number of api keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+// the key is taken out of the request, the upstream never receives it. the error is the reason
+// of the failure, "missing" or "invalid".
+pub fn authenticate(
+    policy: Option<&AuthPolicy>,
+    consumers: &ConsumerKeys,
+    req: &mut Request<Body>,
+) -> Result<Option<Arc<AuthenticatedConsumer>>, &'static str> {
+    let api_key = match policy.and_then(|policy| policy.api_key.as_ref()) {
+        Some(api_key) => api_key,
+        None => return Ok(None),
+    };
+
+    let key = take_api_key(api_key, req).ok_or("missing")?;
+    consumers.find(&key, Utc::now()).map(Some).ok_or("invalid")
+}
+
+// the certificate was verified in the handshake, the route may accept only some subjects. the
+// error is the reason of the failure, "missing" or "forbidden".
+pub fn verify_client_certificate(
+    client_certificate: &ClientCertificateAuth,
+    req: &Request<Body>,
+) -> Result<Arc<ClientCertificate>, &'static str> {
+    let certificate = req
+        .extensions()
+        .get::<TlsConnection>()
+        .and_then(|connection| connection.client_certificate.clone())
+        .ok_or("missing")?;
+
+    if !client_certificate.subjects.is_empty()
+        && !certificate.identities().any(|identity| {
+            client_certificate
+                .subjects
+                .iter()
+                .any(|subject| subject == identity)
+        })
+    {
+        return Err("forbidden");
+    }
+    Ok(certificate)
+}
+
+pub fn client_certificate_error(reason: &str) -> ApiError {
+    match reason {
+        "forbidden" => ApiError::new_with_status(
+            StatusCode::FORBIDDEN,
+            FORWARD_ERR_CLIENT_CERTIFICATE_FORBIDDEN,
+        ),
+        _ => ApiError::new_with_status(
+            StatusCode::UNAUTHORIZED,
+            FORWARD_ERR_CLIENT_CERTIFICATE_REQUIRED,
+        ),
+    }
+}
+
+pub fn unauthorized_error() -> ApiError {
+    ApiError::new_with_status(StatusCode::UNAUTHORIZED, FORWARD_ERR_UNAUTHORIZED)
+}
+
+pub fn add_consumer_headers(headers: &mut HeaderMap, consumer: Option<&AuthenticatedConsumer>) {
+    headers.remove(X_CONSUMER_ID);
+    headers.remove(X_CONSUMER_NAME);
+
+    if let Some(consumer) = consumer {
+        headers.insert(X_CONSUMER_ID, HeaderValue::from(consumer.id));
+        if let Ok(name) = HeaderValue::from_str(&consumer.name) {
+            headers.insert(X_CONSUMER_NAME, name);
+        }
+    }
+}
+
+pub fn add_principal_headers(headers: &mut HeaderMap, principal: &Principal) {
+    add_consumer_headers(headers, principal.consumer.as_deref());
+
+    headers.remove(X_AUTH_SUBJECT);
+    if let Some(subject) = principal
+        .subject
+        .as_deref()
+        .and_then(|subject| HeaderValue::from_str(subject).ok())
+    {
+        headers.insert(X_AUTH_SUBJECT, subject);
+    }
+}
+
+// both places are cleared, the header wins when the key is in both.
+fn take_api_key(api_key: &ApiKeyAuth, req: &mut Request<Body>) -> Option<String> {
+    let from_header = api_key
+        .header()
+        .and_then(|header| req.headers_mut().remove(header))
+        .and_then(|value| value.to_str().ok().map(|value| value.trim().to_owned()));
+
+    let from_query = match api_key
+        .query()
+        .and_then(|query| take_query_param(req.uri(), query))
+    {
+        Some((value, uri)) => {
+            *req.uri_mut() = uri;
+            Some(value)
+        }
+        None => None,
+    };
+
+    from_header
+        .into_iter()
+        .chain(from_query)
+        .find(|key| !key.is_empty())
+}
+
+// the first value of the param and the uri without any of its occurrences.
+pub(crate) fn take_query_param(uri: &Uri, name: &str) -> Option<(String, Uri)> {
+    let query = uri.query()?;
+
+    let mut value = None;
+    let kept: Vec<&str> = query
+        .split('&')
+        .filter(|pair| {
+            let (key, pair_value) = pair.split_once('=').unwrap_or((pair, ""));
+            if decode_query_component(key) != name {
+                return true;
+            }
+            value.get_or_insert_with(|| decode_query_component(pair_value));
+            false
+        })
+        .collect();
+    let value = value?;
+
+    let path_and_query = if kept.is_empty() {
+        uri.path().to_owned()
+    } else {
+        format!("{}?{}", uri.path(), kept.join("&"))
+    };
+
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(PathAndQuery::try_from(path_and_query).ok()?);
+    Some((value, Uri::from_parts(parts).ok()?))
+}
+
+fn decode_query_component(value: &str) -> String {
+    percent_decode_str(&value.replace('+', " "))
+        .decode_utf8_lossy()
+        .into_owned()
+}
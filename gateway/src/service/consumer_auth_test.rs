@@ -0,0 +1,323 @@
+use chrono::Duration;
+
+use super::*;
+
+fn consumer(id: i64, name: &str, rate_limit: Option<serde_json::Value>) -> Consumer {
+    Consumer {
+        id,
+        name: name.to_owned(),
+        rate_limit,
+        certificate_subject: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+fn api_key(
+    id: i64,
+    id_consumer: i64,
+    key: &str,
+    expires_at: Option<DateTime<Utc>>,
+) -> ConsumerApiKey {
+    ConsumerApiKey {
+        id,
+        id_consumer,
+        key_hash: hash_api_key(key),
+        key_prefix: key[..4].to_owned(),
+        expires_at,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+fn consumer_keys() -> ConsumerKeys {
+    ConsumerKeys::build(
+        vec![
+            consumer(
+                1,
+                "mobile-app",
+                Some(serde_json::json!({"limits": [{"requests": 10, "period": "second"}]})),
+            ),
+            consumer(2, "partner", None),
+        ],
+        vec![
+            api_key(1, 1, "agk_mobile", None),
+            api_key(
+                2,
+                2,
+                "agk_partner_old",
+                Some(Utc::now() + Duration::minutes(5)),
+            ),
+            api_key(3, 2, "agk_partner", None),
+            // the consumer has been deleted in the meantime.
+            api_key(4, 3, "agk_orphan", None),
+        ],
+    )
+}
+
+fn policy(header: Option<&str>, query: Option<&str>) -> AuthPolicy {
+    AuthPolicy {
+        api_key: Some(ApiKeyAuth {
+            header: header.map(str::to_owned),
+            query: query.map(str::to_owned),
+        }),
+        jwt: None,
+        introspection: None,
+        client_certificate: None,
+    }
+}
+
+#[test]
+fn find() {
+    let consumer_keys = consumer_keys();
+    assert_eq!(3, consumer_keys.len());
+
+    let consumer = consumer_keys.find("agk_mobile", Utc::now()).unwrap();
+    assert_eq!(1, consumer.id);
+    assert_eq!("mobile-app", consumer.name);
+    assert_eq!("consumer:1", consumer.rate_limit.as_ref().unwrap().scope);
+
+    // both keys of a rotation work until the old one expires.
+    assert_eq!(
+        2,
+        consumer_keys
+            .find("agk_partner_old", Utc::now())
+            .unwrap()
+            .id
+    );
+    assert_eq!(2, consumer_keys.find("agk_partner", Utc::now()).unwrap().id);
+    assert!(consumer_keys
+        .find("agk_partner_old", Utc::now() + Duration::minutes(6))
+        .is_none());
+
+    assert!(consumer_keys.find("agk_orphan", Utc::now()).is_none());
+    assert!(consumer_keys.find("agk_unknown", Utc::now()).is_none());
+}
+
+#[test]
+fn authenticate_with_header() {
+    let consumer_keys = consumer_keys();
+
+    let mut req = Request::builder()
+        .uri("/orders")
+        .header("x-api-key", "agk_mobile")
+        .body(Body::empty())
+        .unwrap();
+    let consumer = authenticate(Some(&policy(None, None)), &consumer_keys, &mut req).unwrap();
+    assert_eq!(1, consumer.unwrap().id);
+    assert!(!req.headers().contains_key("x-api-key"));
+
+    let mut req = Request::builder()
+        .uri("/orders")
+        .header("x-api-key", "agk_unknown")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(
+        Err("invalid"),
+        authenticate(Some(&policy(None, None)), &consumer_keys, &mut req)
+    );
+
+    let mut req = Request::builder()
+        .uri("/orders")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(
+        Err("missing"),
+        authenticate(
+            Some(&policy(Some("Authorization-Key"), None)),
+            &consumer_keys,
+            &mut req
+        )
+    );
+}
+
+#[test]
+fn authenticate_with_query() {
+    let consumer_keys = consumer_keys();
+
+    let mut req = Request::builder()
+        .uri("/orders?page=1&api_key=agk_partner&size=10")
+        .body(Body::empty())
+        .unwrap();
+    let consumer = authenticate(
+        Some(&policy(None, Some("api_key"))),
+        &consumer_keys,
+        &mut req,
+    )
+    .unwrap();
+    assert_eq!(2, consumer.unwrap().id);
+    assert_eq!("/orders?page=1&size=10", req.uri().to_string());
+
+    // the query param is cleared even when the header has the key.
+    let mut req = Request::builder()
+        .uri("/orders?api_key=agk_unknown")
+        .header("x-key", "agk_mobile")
+        .body(Body::empty())
+        .unwrap();
+    let consumer = authenticate(
+        Some(&policy(Some("x-key"), Some("api_key"))),
+        &consumer_keys,
+        &mut req,
+    )
+    .unwrap();
+    assert_eq!(1, consumer.unwrap().id);
+    assert_eq!("/orders", req.uri().to_string());
+}
+
+#[test]
+fn authenticate_without_api_key() {
+    let consumer_keys = consumer_keys();
+
+    let mut req = Request::builder()
+        .uri("/orders")
+        .header("x-api-key", "agk_mobile")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(Ok(None), authenticate(None, &consumer_keys, &mut req));
+    assert_eq!(
+        Ok(None),
+        authenticate(Some(&AuthPolicy::default()), &consumer_keys, &mut req)
+    );
+    assert!(req.headers().contains_key("x-api-key"));
+}
+
+fn certificate(common_name: &str, alt_names: &[&str]) -> ClientCertificate {
+    ClientCertificate {
+        common_name: Some(common_name.to_owned()),
+        alt_names: alt_names.iter().map(|name| name.to_string()).collect(),
+    }
+}
+
+#[test]
+fn find_by_certificate() {
+    let consumer_keys = ConsumerKeys::build(
+        vec![
+            Consumer {
+                certificate_subject: Some(String::from("spiffe://example.org/orders")),
+                ..consumer(1, "orders", None)
+            },
+            Consumer {
+                certificate_subject: Some(String::from("billing-service")),
+                ..consumer(2, "billing", None)
+            },
+        ],
+        Vec::new(),
+    );
+
+    let orders = certificate("orders-service", &["spiffe://example.org/orders"]);
+    assert_eq!(1, consumer_keys.find_by_certificate(&orders).unwrap().id);
+    let billing = certificate("billing-service", &["billing.internal"]);
+    assert_eq!(2, consumer_keys.find_by_certificate(&billing).unwrap().id);
+    let unknown = certificate("unknown", &[]);
+    assert!(consumer_keys.find_by_certificate(&unknown).is_none());
+}
+
+#[test]
+fn verify_client_certificate_subjects() {
+    let auth = ClientCertificateAuth {
+        subjects: vec![String::from("orders.internal")],
+    };
+    let request = |certificate: Option<ClientCertificate>| {
+        let mut req = Request::builder()
+            .uri("/orders")
+            .body(Body::empty())
+            .unwrap();
+        req.extensions_mut().insert(TlsConnection {
+            client_certificate: certificate.map(Arc::new),
+        });
+        req
+    };
+
+    let req = request(Some(certificate(
+        "orders-service",
+        &["spiffe://example.org/orders", "orders.internal"],
+    )));
+    assert_eq!(
+        Some("spiffe://example.org/orders"),
+        verify_client_certificate(&auth, &req).unwrap().subject()
+    );
+
+    let req = request(Some(certificate("billing-service", &[])));
+    assert_eq!(
+        Err("forbidden"),
+        verify_client_certificate(&auth, &req).map(|_| ())
+    );
+    assert!(verify_client_certificate(&ClientCertificateAuth::default(), &req).is_ok());
+
+    let req = request(None);
+    assert_eq!(
+        Err("missing"),
+        verify_client_certificate(&auth, &req).map(|_| ())
+    );
+    let req = Request::builder()
+        .uri("/orders")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(
+        Err("missing"),
+        verify_client_certificate(&auth, &req).map(|_| ())
+    );
+    assert_eq!(403, client_certificate_error("forbidden").status_code);
+    assert_eq!(401, client_certificate_error("missing").status_code);
+}
+
+#[test]
+fn take_query_param_decoded() {
+    let uri: Uri = "http://anothergtw.com/orders?api%5Fkey=agk%2Babc&q=a+b&api_key=other"
+        .parse()
+        .unwrap();
+    let (value, uri) = take_query_param(&uri, "api_key").unwrap();
+    assert_eq!("agk+abc", value);
+    assert_eq!("http://anothergtw.com/orders?q=a+b", uri.to_string());
+
+    assert!(take_query_param(&uri, "api_key").is_none());
+}
+
+#[test]
+fn consumer_headers() {
+    let mut headers = HeaderMap::new();
+    headers.insert(X_CONSUMER_ID, HeaderValue::from_static("99"));
+    headers.insert(X_CONSUMER_NAME, HeaderValue::from_static("admin"));
+
+    add_consumer_headers(&mut headers, None);
+    assert!(!headers.contains_key(X_CONSUMER_ID));
+    assert!(!headers.contains_key(X_CONSUMER_NAME));
+
+    let consumer = AuthenticatedConsumer {
+        id: 1,
+        name: String::from("mobile-app"),
+        rate_limit: None,
+    };
+    add_consumer_headers(&mut headers, Some(&consumer));
+    assert_eq!("1", headers[X_CONSUMER_ID]);
+    assert_eq!("mobile-app", headers[X_CONSUMER_NAME]);
+}
+
+#[test]
+fn principal() {
+    let consumer = consumer_keys().find("agk_mobile", Utc::now());
+    let principal = Principal {
+        consumer,
+        subject: Some(String::from("user-1")),
+    };
+    assert_eq!(Some(String::from("consumer:1")), principal.rate_limit_key());
+
+    let mut headers = HeaderMap::new();
+    headers.insert(X_AUTH_SUBJECT, HeaderValue::from_static("forged"));
+    add_principal_headers(&mut headers, &principal);
+    assert_eq!("1", headers.get(X_CONSUMER_ID).unwrap());
+    assert_eq!("user-1", headers.get(X_AUTH_SUBJECT).unwrap());
+
+    let principal = Principal {
+        consumer: None,
+        subject: Some(String::from("user-1")),
+    };
+    assert_eq!(
+        Some(String::from("subject:user-1")),
+        principal.rate_limit_key()
+    );
+
+    add_principal_headers(&mut headers, &Principal::default());
+    assert!(headers.is_empty());
+    assert_eq!(None, Principal::default().rate_limit_key());
+}
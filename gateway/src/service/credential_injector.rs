@@ -0,0 +1,319 @@
+#[cfg(test)]
+#[path = "credential_injector_test.rs"]
+mod credential_injector_test;
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::http::Request;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hyper::{
+    header::{HeaderName, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE},
+    Body, HeaderMap, StatusCode,
+};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::Deserialize;
+use tokio::time::Instant;
+
+use crate::{
+    config::{HttpClient, HttpsClient},
+    exception::{ApiError, FORWARD_ERR_UPSTREAM_CREDENTIALS},
+    model::{
+        Upstream, UpstreamCredentials, CREDENTIALS_BASIC, CREDENTIALS_HEADERS,
+        CREDENTIALS_OAUTH2_CLIENT_CREDENTIALS,
+    },
+    secret::SecretCipher,
+};
+
+const TOKEN_FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+// a token endpoint that doesn't tell when the token expires.
+const DEFAULT_TOKEN_LIFETIME: Duration = Duration::from_secs(300);
+// a failed refresh is tried again after this while the current token is still valid.
+const TOKEN_RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Clone)]
+struct AccessToken {
+    authorization: HeaderValue,
+    expires_at: Instant,
+    refresh_at: Instant,
+}
+
+struct OpenedCredentials {
+    sealed: String,
+    credentials: UpstreamCredentials,
+    token: Mutex<Option<AccessToken>>,
+    // only one request at a time fetches the token, the others wait for it.
+    fetching: tokio::sync::Mutex<()>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+// credentials of the upstreams by id, opened once and kept with their tokens across the reloads
+// of the routing table. a change of the credentials replaces them, an upstream removed from the
+// table takes them with it.
+pub struct CredentialInjector {
+    client: HttpsClient,
+    cipher: Option<SecretCipher>,
+    credentials: Mutex<HashMap<i64, Arc<OpenedCredentials>>>,
+}
+
+impl Default for CredentialInjector {
+    fn default() -> Self {
+        CredentialInjector::new(SecretCipher::config())
+    }
+}
+
+impl CredentialInjector {
+    pub fn new(cipher: Option<SecretCipher>) -> Self {
+        CredentialInjector {
+            client: HttpClient::config(),
+            cipher,
+            credentials: Mutex::default(),
+        }
+    }
+
+    pub fn retain(&self, keep: impl Fn(i64) -> bool) {
+        self.credentials
+            .lock()
+            .unwrap()
+            .retain(|id_upstream, _| keep(*id_upstream));
+    }
+
+    // the credentials of the upstream replace the ones of the client. an upstream whose
+    // credentials can't be opened or whose token can't be fetched receives nothing.
+    pub async fn inject(
+        &self,
+        upstream: Option<&Upstream>,
+        headers: &mut HeaderMap,
+    ) -> Result<(), ApiError> {
+        let (upstream, sealed) = match upstream.and_then(|upstream| {
+            upstream
+                .credentials
+                .as_deref()
+                .map(|sealed| (upstream, sealed))
+        }) {
+            Some(credentials) => credentials,
+            None => return Ok(()),
+        };
+
+        let opened = self.open(upstream, sealed)?;
+        let credentials = &opened.credentials;
+        match credentials.credentials_type.as_str() {
+            CREDENTIALS_HEADERS => {
+                for (name, value) in &credentials.headers {
+                    if let (Ok(name), Ok(mut value)) = (
+                        HeaderName::try_from(name.as_str()),
+                        HeaderValue::try_from(value.as_str()),
+                    ) {
+                        value.set_sensitive(true);
+                        headers.insert(name, value);
+                    }
+                }
+            }
+            CREDENTIALS_BASIC => {
+                let basic = STANDARD.encode(format!(
+                    "{}:{}",
+                    credentials.username.as_deref().unwrap_or_default(),
+                    credentials.password.as_deref().unwrap_or_default()
+                ));
+                let mut value = HeaderValue::try_from(format!("Basic {}", basic))
+                    .map_err(|_| credentials_error())?;
+                value.set_sensitive(true);
+                headers.insert(AUTHORIZATION, value);
+            }
+            CREDENTIALS_OAUTH2_CLIENT_CREDENTIALS => {
+                let authorization = self.token(upstream, &opened).await?;
+                headers.insert(AUTHORIZATION, authorization);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn open(&self, upstream: &Upstream, sealed: &str) -> Result<Arc<OpenedCredentials>, ApiError> {
+        if let Some(opened) = self
+            .credentials
+            .lock()
+            .unwrap()
+            .get(&upstream.id)
+            .filter(|opened| opened.sealed == sealed)
+        {
+            return Ok(Arc::clone(opened));
+        }
+
+        let cipher = self.cipher.as_ref().ok_or_else(|| {
+            tracing::error!(
+                "CREDENTIALS_ENCRYPTION_KEY is required to use the credentials of the upstream {}",
+                upstream.name
+            );
+            credentials_error()
+        })?;
+        let credentials = cipher
+            .open(sealed, &UpstreamCredentials::sealing_context(upstream.id))
+            .ok()
+            .and_then(|credentials| serde_json::from_str(&credentials).ok())
+            .ok_or_else(|| {
+                tracing::error!(
+                    "The credentials of the upstream {} could not be opened",
+                    upstream.name
+                );
+                credentials_error()
+            })?;
+
+        let opened = Arc::new(OpenedCredentials {
+            sealed: sealed.to_owned(),
+            credentials,
+            token: Mutex::default(),
+            fetching: tokio::sync::Mutex::default(),
+        });
+        self.credentials
+            .lock()
+            .unwrap()
+            .insert(upstream.id, Arc::clone(&opened));
+        Ok(opened)
+    }
+
+    // a valid token is used as it is, one close to its expiry is refreshed in the background
+    // and only an expired one makes the request wait for a new token.
+    async fn token(
+        &self,
+        upstream: &Upstream,
+        opened: &Arc<OpenedCredentials>,
+    ) -> Result<HeaderValue, ApiError> {
+        let token = valid_token(opened);
+        if let Some(token) = token {
+            if token.refresh_at <= Instant::now() {
+                self.refresh(upstream, opened);
+            }
+            return Ok(token.authorization);
+        }
+
+        let _fetching = opened.fetching.lock().await;
+        // another request may have fetched it while this one waited.
+        if let Some(token) = valid_token(opened) {
+            return Ok(token.authorization);
+        }
+
+        match fetch_token(&self.client, &opened.credentials).await {
+            Ok(token) => {
+                let authorization = token.authorization.clone();
+                *opened.token.lock().unwrap() = Some(token);
+                Ok(authorization)
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Error when fetching the token of the upstream {}: {}",
+                    upstream.name,
+                    e
+                );
+                Err(credentials_error())
+            }
+        }
+    }
+
+    fn refresh(&self, upstream: &Upstream, opened: &Arc<OpenedCredentials>) {
+        let client = self.client.clone();
+        let opened = Arc::clone(opened);
+        let name = upstream.name.clone();
+        tokio::spawn(async move {
+            let Ok(_fetching) = opened.fetching.try_lock() else {
+                return;
+            };
+            if valid_token(&opened).is_some_and(|token| token.refresh_at > Instant::now()) {
+                return;
+            }
+
+            let token = fetch_token(&client, &opened.credentials).await;
+            let mut current = opened.token.lock().unwrap();
+            match token {
+                Ok(token) => *current = Some(token),
+                // the current token is kept until it expires.
+                Err(e) => {
+                    tracing::warn!(
+                        "Error when refreshing the token of the upstream {}: {}",
+                        name,
+                        e
+                    );
+                    if let Some(current) = current.as_mut() {
+                        current.refresh_at = Instant::now() + TOKEN_RETRY_INTERVAL;
+                    }
+                }
+            }
+        });
+    }
+}
+
+fn valid_token(opened: &OpenedCredentials) -> Option<AccessToken> {
+    let token = opened.token.lock().unwrap().clone();
+    token.filter(|token| token.expires_at > Instant::now())
+}
+
+// the client credentials go in the basic authorization, form encoded as rfc 6749 asks.
+async fn fetch_token(
+    client: &HttpsClient,
+    credentials: &UpstreamCredentials,
+) -> Result<AccessToken, Box<dyn Error + Send + Sync>> {
+    let token_url = credentials.token_url.as_deref().ok_or("no token url")?;
+    let client_auth = STANDARD.encode(format!(
+        "{}:{}",
+        utf8_percent_encode(
+            credentials.client_id.as_deref().unwrap_or_default(),
+            NON_ALPHANUMERIC
+        ),
+        utf8_percent_encode(
+            credentials.client_secret.as_deref().unwrap_or_default(),
+            NON_ALPHANUMERIC
+        )
+    ));
+
+    let mut form = vec![("grant_type", "client_credentials".to_owned())];
+    if !credentials.scopes.is_empty() {
+        form.push(("scope", credentials.scopes.join(" ")));
+    }
+    if let Some(audience) = &credentials.audience {
+        form.push(("audience", audience.clone()));
+    }
+
+    let request = Request::post(token_url)
+        .header(AUTHORIZATION, format!("Basic {}", client_auth))
+        .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .header(ACCEPT, "application/json")
+        .body(Body::from(serde_urlencoded::to_string(form)?))?;
+    let response = tokio::time::timeout(TOKEN_FETCH_TIMEOUT, client.request(request)).await??;
+    if !response.status().is_success() {
+        return Err(format!("{} answered {}", token_url, response.status()).into());
+    }
+
+    let body = hyper::body::to_bytes(response.into_body()).await?;
+    let response: TokenResponse = serde_json::from_slice(&body)?;
+    let mut authorization = HeaderValue::try_from(format!("Bearer {}", response.access_token))?;
+    authorization.set_sensitive(true);
+
+    // refreshed ahead of the expiry, but halfway through at the earliest, a short-lived token
+    // isn't fetched again on every request.
+    let lifetime = response
+        .expires_in
+        .map_or(DEFAULT_TOKEN_LIFETIME, Duration::from_secs);
+    let now = Instant::now();
+    Ok(AccessToken {
+        authorization,
+        expires_at: now + lifetime,
+        refresh_at: now
+            + lifetime
+                .saturating_sub(credentials.refresh_before())
+                .max(lifetime / 2),
+    })
+}
+
+fn credentials_error() -> ApiError {
+    ApiError::new_with_status(StatusCode::BAD_GATEWAY, FORWARD_ERR_UPSTREAM_CREDENTIALS)
+}
@@ -0,0 +1,291 @@
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use chrono::Utc;
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Response,
+};
+use serde_json::{json, Value};
+
+use crate::model::ALGORITHM_ROUND_ROBIN;
+
+use super::*;
+
+const KEY: &str = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=";
+
+// issues "token-<n>" to the client "gateway" with the secret "s3cr3t", for the scope and the
+// audience it's asked. "broken" fails the first time, "short" tokens expire in 2 seconds. every
+// fetch is counted.
+async fn start_token_server() -> (SocketAddr, Arc<AtomicUsize>) {
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&fetches);
+    let make_service = make_service_fn(move |_| {
+        let fetches = Arc::clone(&fetches);
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                let fetches = Arc::clone(&fetches);
+                async move {
+                    let fetch = fetches.fetch_add(1, Ordering::SeqCst) + 1;
+                    let client = format!("Basic {}", STANDARD.encode("gateway:s3cr3t"));
+                    let path = req.uri().path().to_owned();
+                    let authorized = req.headers().get(AUTHORIZATION).unwrap() == client.as_str();
+                    let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+                    let form: HashMap<String, String> =
+                        serde_urlencoded::from_bytes(&body).unwrap();
+
+                    if !authorized
+                        || form["grant_type"] != "client_credentials"
+                        || (path == "/broken" && fetch == 1)
+                    {
+                        return Ok::<_, Infallible>(
+                            Response::builder()
+                                .status(StatusCode::UNAUTHORIZED)
+                                .body(Body::empty())
+                                .unwrap(),
+                        );
+                    }
+
+                    let expires_in = if path == "/short" { 2 } else { 3600 };
+                    let token = json!({
+                        "access_token": format!(
+                            "token-{}-{}-{}",
+                            fetch,
+                            form.get("scope").map_or("", String::as_str),
+                            form.get("audience").map_or("", String::as_str)
+                        ),
+                        "token_type": "Bearer",
+                        "expires_in": expires_in,
+                    });
+                    Ok(Response::new(Body::from(token.to_string())))
+                }
+            }))
+        }
+    });
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = hyper::Server::from_tcp(listener)
+        .unwrap()
+        .serve(make_service);
+    tokio::spawn(server);
+    (addr, counter)
+}
+
+fn cipher() -> SecretCipher {
+    SecretCipher::from_base64(KEY).unwrap()
+}
+
+fn upstream(credentials: Value) -> Upstream {
+    let credentials = UpstreamCredentials::parse(&credentials).unwrap();
+    Upstream {
+        id: 1,
+        name: String::from("orders"),
+        algorithm: String::from(ALGORITHM_ROUND_ROBIN),
+        hash_on: None,
+        hash_key: None,
+        health_check: None,
+        credentials_type: Some(credentials.credentials_type.clone()),
+        credentials: Some(
+            cipher()
+                .seal(
+                    &serde_json::to_string(&credentials).unwrap(),
+                    &UpstreamCredentials::sealing_context(1),
+                )
+                .unwrap(),
+        ),
+        tls: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+fn oauth2(addr: SocketAddr, path: &str) -> Value {
+    json!({
+        "type": "OAUTH2_CLIENT_CREDENTIALS",
+        "tokenUrl": format!("http://{}{}", addr, path),
+        "clientId": "gateway",
+        "clientSecret": "s3cr3t",
+        "scopes": ["orders:read", "orders:write"],
+        "audience": "orders",
+    })
+}
+
+async fn authorization(
+    injector: &CredentialInjector,
+    upstream: &Upstream,
+) -> Result<String, ApiError> {
+    let mut headers = HeaderMap::new();
+    headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer client"));
+    injector.inject(Some(upstream), &mut headers).await?;
+    Ok(headers[AUTHORIZATION].to_str().unwrap().to_owned())
+}
+
+#[tokio::test]
+async fn inject_headers_and_basic() {
+    let injector = CredentialInjector::new(Some(cipher()));
+
+    let upstream = upstream(json!({
+        "type": "HEADERS",
+        "headers": {"x-api-key": "k3y", "authorization": "Token t0k3n"},
+    }));
+    let mut headers = HeaderMap::new();
+    headers.insert("x-api-key", HeaderValue::from_static("client"));
+    injector
+        .inject(Some(&upstream), &mut headers)
+        .await
+        .unwrap();
+    assert_eq!("k3y", headers["x-api-key"]);
+    assert_eq!("Token t0k3n", headers[AUTHORIZATION]);
+
+    let upstream = self::upstream(json!({
+        "type": "BASIC",
+        "username": "gateway",
+        "password": "s3cr3t",
+    }));
+    assert_eq!(
+        format!("Basic {}", STANDARD.encode("gateway:s3cr3t")),
+        authorization(&injector, &upstream).await.unwrap()
+    );
+}
+
+#[tokio::test]
+async fn retain_the_upstreams_of_the_routing_table() {
+    let injector = CredentialInjector::new(Some(cipher()));
+
+    let upstream = upstream(json!({"type": "BASIC", "username": "u", "password": "p"}));
+    authorization(&injector, &upstream).await.unwrap();
+    injector.retain(|id_upstream| id_upstream == 1);
+    assert_eq!(1, injector.credentials.lock().unwrap().len());
+
+    injector.retain(|id_upstream| id_upstream == 2);
+    assert!(injector.credentials.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn inject_nothing() {
+    let injector = CredentialInjector::new(Some(cipher()));
+
+    let mut headers = HeaderMap::new();
+    headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer client"));
+    injector.inject(None, &mut headers).await.unwrap();
+
+    let mut upstream = upstream(json!({"type": "BASIC", "username": "u", "password": "p"}));
+    upstream.credentials = None;
+    injector
+        .inject(Some(&upstream), &mut headers)
+        .await
+        .unwrap();
+    assert_eq!("Bearer client", headers[AUTHORIZATION]);
+}
+
+#[tokio::test]
+async fn inject_without_key() {
+    let upstream = upstream(json!({"type": "BASIC", "username": "u", "password": "p"}));
+
+    let response = authorization(&CredentialInjector::new(None), &upstream).await;
+    assert_eq!(
+        FORWARD_ERR_UPSTREAM_CREDENTIALS.0,
+        response.unwrap_err().code
+    );
+
+    let other = SecretCipher::from_base64("ZmVkY2JhOTg3NjU0MzIxMGZlZGNiYTk4NzY1NDMyMTA=");
+    let response = authorization(&CredentialInjector::new(other), &upstream).await;
+    let api_error = response.unwrap_err();
+    assert_eq!(502, api_error.status_code);
+    assert_eq!(FORWARD_ERR_UPSTREAM_CREDENTIALS.0, api_error.code);
+}
+
+#[tokio::test]
+async fn inject_copied_from_another_upstream() {
+    let injector = CredentialInjector::new(Some(cipher()));
+
+    let mut upstream = upstream(json!({"type": "BASIC", "username": "u", "password": "p"}));
+    upstream.id = 2;
+    let response = authorization(&injector, &upstream).await;
+    assert_eq!(
+        FORWARD_ERR_UPSTREAM_CREDENTIALS.0,
+        response.unwrap_err().code
+    );
+}
+
+#[tokio::test]
+async fn inject_oauth2_token() {
+    let (addr, fetches) = start_token_server().await;
+    let injector = CredentialInjector::new(Some(cipher()));
+    let upstream = upstream(oauth2(addr, "/token"));
+
+    // concurrent requests share a single fetch of the token.
+    let (first, second) = tokio::join!(
+        authorization(&injector, &upstream),
+        authorization(&injector, &upstream)
+    );
+    assert_eq!(
+        "Bearer token-1-orders:read orders:write-orders",
+        first.unwrap()
+    );
+    assert_eq!(
+        "Bearer token-1-orders:read orders:write-orders",
+        second.unwrap()
+    );
+    assert_eq!(
+        "Bearer token-1-orders:read orders:write-orders",
+        authorization(&injector, &upstream).await.unwrap()
+    );
+    assert_eq!(1, fetches.load(Ordering::SeqCst));
+
+    // changed credentials get a token of their own.
+    let mut credentials = oauth2(addr, "/token");
+    credentials["scopes"] = json!([]);
+    let upstream = self::upstream(credentials);
+    assert_eq!(
+        "Bearer token-2--orders",
+        authorization(&injector, &upstream).await.unwrap()
+    );
+}
+
+#[tokio::test]
+async fn inject_oauth2_token_refreshed() {
+    let (addr, fetches) = start_token_server().await;
+    let injector = CredentialInjector::new(Some(cipher()));
+    let upstream = upstream(oauth2(addr, "/short"));
+
+    let first = authorization(&injector, &upstream).await.unwrap();
+    assert!(first.starts_with("Bearer token-1-"));
+
+    // past half of its lifetime the token is still used while a new one is fetched.
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    assert_eq!(first, authorization(&injector, &upstream).await.unwrap());
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(authorization(&injector, &upstream)
+        .await
+        .unwrap()
+        .starts_with("Bearer token-2-"));
+    assert_eq!(2, fetches.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn inject_oauth2_token_failed() {
+    let (addr, fetches) = start_token_server().await;
+    let injector = CredentialInjector::new(Some(cipher()));
+    let upstream = upstream(oauth2(addr, "/broken"));
+
+    let api_error = authorization(&injector, &upstream).await.unwrap_err();
+    assert_eq!(502, api_error.status_code);
+    assert_eq!(FORWARD_ERR_UPSTREAM_CREDENTIALS.0, api_error.code);
+
+    // the failure isn't kept, the next request fetches the token again.
+    assert!(authorization(&injector, &upstream)
+        .await
+        .unwrap()
+        .starts_with("Bearer token-2-"));
+    assert_eq!(2, fetches.load(Ordering::SeqCst));
+
+    let mut credentials = oauth2(addr, "/token");
+    credentials["clientSecret"] = json!("wrong");
+    let upstream = self::upstream(credentials);
+    assert!(authorization(&injector, &upstream).await.is_err());
+}
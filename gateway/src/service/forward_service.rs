@@ -1,77 +1,946 @@
-use std::{sync::Arc, collections::VecDeque};
+#[cfg(test)]
+#[path = "forward_service_test.rs"]
+mod forward_service_test;
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
 
 use axum::{
     async_trait,
-    http::{Request, Response, uri::Uri},
+    extract::ConnectInfo,
+    http::{uri::Uri, Request, Response},
 };
-use hyper::{Body, Client, header::HeaderName, client::HttpConnector, StatusCode};
-use hyper_tls::HttpsConnector;
-use sqlx::PgPool;
+use hyper::{
+    body::{Bytes, HttpBody},
+    header::{HeaderName, HeaderValue, ACCEPT_ENCODING, CONNECTION, HOST, UPGRADE},
+    Body, StatusCode, Version,
+};
+use serde_json::Value;
+use tokio::time::Instant;
+use tracing::Instrument;
 
-use crate::exception::{ApiError, FORWARD_ERR_PATH_IS_REQUIRED};
+use crate::{
+    config::{HttpClients, TlsConnection, TrustedProxies},
+    exception::{
+        ApiError, ERR_HYPER_ERROR, FORWARD_ERR_CIRCUIT_OPEN, FORWARD_ERR_INVALID_DESTINATION,
+        FORWARD_ERR_UPSTREAM_TIMEOUT, FORWARD_ERR_WEBSOCKET_LIMIT,
+    },
+    model::{Application, AuthPolicy, CircuitBreakerPolicy},
+};
 
-use super::{ApplicationService, ApplicationServiceTrait};
+use super::{
+    add_claim_headers, add_forwarded_headers, add_grpc_headers, add_principal_headers,
+    add_rate_limit_headers, api_error_response, authenticate, backoff, balance, bearer_token,
+    buffer_body, client_certificate_error, fallback_response, grpc_error_response, is_event_stream,
+    is_grpc, is_jwt, is_websocket_upgrade, max_buffered_body_size, original_client_ip,
+    proxy_idle_timeout, rate_limited_error, release_after, remove_hop_by_hop_headers,
+    retries_request, splice, streaming_idle_timeout, token_error, unauthorized_error, upstream_key,
+    verify_client_certificate, websocket_idle_timeout, with_body_timeouts, ActiveRequest,
+    CircuitBreakers, ConsumerKeys, CredentialInjector, ForwardTarget, JwtKeys, Metrics,
+    OrchestrationService, OrchestrationServiceTrait, Principal, RateLimitDecision, RateLimiter,
+    RetryBudget, RoutingServiceTrait, RoutingTarget, ScopedRateLimit, TokenIntrospector,
+    TranscodingService, TranscodingServiceTrait, UpstreamBalancer, WebSocketConnections,
+    GATEWAY_AUTH_FAILURES, GATEWAY_CIRCUIT_BREAKER_REJECTED, GATEWAY_CONSUMER_REQUESTS,
+    GATEWAY_RATE_LIMITED, GATEWAY_REQUESTS, GATEWAY_RETRY_BUDGET_EXHAUSTED,
+    GATEWAY_UPSTREAM_RETRIES, GATEWAY_WEBSOCKET_ACTIVE_CONNECTIONS, GATEWAY_WEBSOCKET_BYTES,
+    GATEWAY_WEBSOCKET_CONNECTIONS, GATEWAY_WEBSOCKET_REJECTED,
+};
 
 #[async_trait]
 pub trait ForwardServiceTrait {
-    async fn handle(&self, mut req: Request<Body>) -> Result<Response<Body>, ApiError>;
+    async fn handle(&self, req: Request<Body>) -> Result<Response<Body>, ApiError>;
 }
 
 pub struct ForwardService {
-    application_service: Arc<dyn ApplicationServiceTrait + Send + Sync>,
-    client: Arc<Client<HttpsConnector<HttpConnector>>>,
+    routing_service: Arc<dyn RoutingServiceTrait + Send + Sync>,
+    orchestration_service: Arc<dyn OrchestrationServiceTrait + Send + Sync>,
+    clients: Arc<HttpClients>,
+    transcoding_service: Arc<dyn TranscodingServiceTrait + Send + Sync>,
+    trusted_proxies: TrustedProxies,
+    websocket_connections: Arc<WebSocketConnections>,
+    retry_budget: RetryBudget,
+    rate_limiter: RateLimiter,
+    circuit_breakers: Arc<CircuitBreakers>,
+    jwt_keys: JwtKeys,
+    token_introspector: TokenIntrospector,
+    credential_injector: Arc<CredentialInjector>,
+    max_body_size: usize,
+    websocket_idle_timeout: Duration,
+    proxy_idle_timeout: Duration,
+    streaming_idle_timeout: Duration,
 }
 
+// asks nginx-like proxies in front of the gateway not to buffer the response either.
+const X_ACCEL_BUFFERING: HeaderName = HeaderName::from_static("x-accel-buffering");
+
 impl ForwardService {
-    pub fn new(pg_pool: Arc<PgPool>) -> Self {
-        let client = Client::builder().build(HttpsConnector::new());
+    pub fn new(routing_service: Arc<dyn RoutingServiceTrait + Send + Sync>) -> Self {
+        ForwardService::new_with_circuit_breakers(routing_service, Arc::default())
+    }
+
+    // the breakers are shared with the admin api that shows their state.
+    pub fn new_with_circuit_breakers(
+        routing_service: Arc<dyn RoutingServiceTrait + Send + Sync>,
+        circuit_breakers: Arc<CircuitBreakers>,
+    ) -> Self {
+        ForwardService::build(
+            routing_service,
+            TrustedProxies::config(),
+            circuit_breakers,
+            RateLimiter::config(),
+        )
+    }
+
+    pub fn new_with_trusted_proxies(
+        routing_service: Arc<dyn RoutingServiceTrait + Send + Sync>,
+        trusted_proxies: TrustedProxies,
+    ) -> Self {
+        ForwardService::build(
+            routing_service,
+            trusted_proxies,
+            Arc::default(),
+            RateLimiter::local(),
+        )
+    }
+
+    pub fn new_with_rate_limiter(
+        routing_service: Arc<dyn RoutingServiceTrait + Send + Sync>,
+        rate_limiter: RateLimiter,
+    ) -> Self {
+        ForwardService::build(
+            routing_service,
+            TrustedProxies::default(),
+            Arc::default(),
+            rate_limiter,
+        )
+    }
+
+    fn build(
+        routing_service: Arc<dyn RoutingServiceTrait + Send + Sync>,
+        trusted_proxies: TrustedProxies,
+        circuit_breakers: Arc<CircuitBreakers>,
+        rate_limiter: RateLimiter,
+    ) -> Self {
+        let clients = Arc::new(HttpClients::config());
+        let credential_injector = routing_service.credential_injector();
         ForwardService {
-            application_service: Arc::new(ApplicationService::new(Arc::clone(&pg_pool))),
-            client: Arc::new(client)
+            routing_service,
+            orchestration_service: Arc::new(OrchestrationService::new(
+                Arc::clone(&clients),
+                Arc::clone(&credential_injector),
+                Arc::clone(&circuit_breakers),
+            )),
+            transcoding_service: Arc::new(TranscodingService::new(Arc::clone(&clients))),
+            clients,
+            trusted_proxies,
+            websocket_connections: Arc::new(WebSocketConnections::default()),
+            retry_budget: RetryBudget::config(),
+            rate_limiter,
+            circuit_breakers,
+            jwt_keys: JwtKeys::default(),
+            token_introspector: TokenIntrospector::default(),
+            credential_injector,
+            max_body_size: max_buffered_body_size(),
+            websocket_idle_timeout: websocket_idle_timeout(),
+            proxy_idle_timeout: proxy_idle_timeout(),
+            streaming_idle_timeout: streaming_idle_timeout(),
         }
     }
+
+    pub(crate) fn forward_uri(
+        url_destination: &str,
+        remaining_path: &str,
+        query: Option<&str>,
+    ) -> Result<Uri, ApiError> {
+        let mut new_uri = format!(
+            "{}{}",
+            url_destination.trim_end_matches('/'),
+            remaining_path
+        );
+        if let Some(query) = query {
+            let separator = if new_uri.contains('?') { '&' } else { '?' };
+            new_uri = format!("{}{}{}", new_uri, separator, query);
+        }
+
+        let uri = Uri::try_from(new_uri).map_err(|e| {
+            tracing::error!("Error when building the destination uri: {}", e);
+            ApiError::new_with_status(StatusCode::BAD_GATEWAY, FORWARD_ERR_INVALID_DESTINATION)
+        })?;
+
+        if uri.scheme().is_none() || uri.authority().is_none() {
+            return Err(ApiError::new_with_status(
+                StatusCode::BAD_GATEWAY,
+                FORWARD_ERR_INVALID_DESTINATION,
+            ));
+        }
+
+        Ok(uri)
+    }
 }
 
 #[async_trait]
 impl ForwardServiceTrait for ForwardService {
     async fn handle(&self, mut req: Request<Body>) -> Result<Response<Body>, ApiError> {
-        let path = req.uri().path();
+        let path = req.uri().path().to_owned();
+        let grpc = is_grpc(req.headers());
         tracing::info!("{}", path);
 
-        let mut path_segment = path.split('/').collect::<VecDeque<&str>>();
-        path_segment = path_segment.into_iter().filter(|segment| !segment.trim().is_empty() || segment.eq(&"/")).collect::<VecDeque<&str>>();
-        if path_segment.is_empty() {
-            return Err(ApiError::new(FORWARD_ERR_PATH_IS_REQUIRED));
+        let snapshot = self.routing_service.snapshot();
+        let (application, consumer, rate_limit, result) = match snapshot.resolve(&path) {
+            Ok(target) => {
+                let application = snapshot.find_application(target.id_application());
+                match self
+                    .authenticate(target.auth(), snapshot.consumers(), &mut req)
+                    .await
+                {
+                    Ok(principal) => {
+                        add_principal_headers(req.headers_mut(), &principal);
+                        let rate_limit = self.check_rate_limit(&target, &principal, &req).await;
+                        let result = match &rate_limit {
+                            Some(rate_limit) if !rate_limit.allowed => {
+                                tracing::warn!("rate limit exceeded for {}", path);
+                                Metrics::global().increment(
+                                    &GATEWAY_RATE_LIMITED,
+                                    &[("application", application_label(&application))],
+                                );
+                                Err(rate_limited_error())
+                            }
+                            _ => self.dispatch(target, application.clone(), req).await,
+                        };
+                        (application, principal.consumer, rate_limit, result)
+                    }
+                    Err((reason, api_error)) => {
+                        tracing::warn!("authentication {} for {}", reason, path);
+                        Metrics::global().increment(
+                            &GATEWAY_AUTH_FAILURES,
+                            &[
+                                ("application", application_label(&application)),
+                                ("reason", reason),
+                            ],
+                        );
+                        (application, None, None, Err(api_error))
+                    }
+                }
+            }
+            Err(api_error) => (None, None, None, Err(api_error)),
+        };
+
+        let status = match &result {
+            Ok(response) => response.status().as_u16(),
+            Err(api_error) => api_error.status_code,
+        };
+        Metrics::global().increment(
+            &GATEWAY_REQUESTS,
+            &[
+                ("application", application_label(&application)),
+                ("status", &status.to_string()),
+            ],
+        );
+        if let Some(consumer) = &consumer {
+            Metrics::global().increment(
+                &GATEWAY_CONSUMER_REQUESTS,
+                &[
+                    ("application", application_label(&application)),
+                    ("consumer", &consumer.name),
+                    ("status", &status.to_string()),
+                ],
+            );
+        }
+
+        let mut response = match result {
+            Ok(response) => response,
+            Err(api_error) if grpc => grpc_error_response(&api_error),
+            // limited requests are answered here, with their headers.
+            Err(api_error) if rate_limit.is_some() => api_error_response(&api_error),
+            Err(api_error) => return Err(api_error),
+        };
+        if let Some(rate_limit) = &rate_limit {
+            add_rate_limit_headers(response.headers_mut(), rate_limit);
+        }
+        Ok(response)
+    }
+}
+
+pub(crate) fn circuit_open_error() -> ApiError {
+    ApiError::new_with_status(StatusCode::SERVICE_UNAVAILABLE, FORWARD_ERR_CIRCUIT_OPEN)
+}
+
+fn application_label(application: &Option<Arc<Application>>) -> &str {
+    application
+        .as_ref()
+        .map_or("", |application| application.path.as_str())
+}
+
+impl ForwardService {
+    // the peer is known only when the server is built with connect info.
+    fn peer(&self, req: &Request<Body>) -> (Option<IpAddr>, bool) {
+        let client_ip = req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        let trusted = client_ip.is_some_and(|ip| self.trusted_proxies.contains(&ip));
+        (client_ip, trusted)
+    }
+
+    // the client the limits and the hashing by ip apply to, a forged x-forwarded-for can't
+    // change it.
+    fn original_client_ip(&self, req: &Request<Body>) -> Option<IpAddr> {
+        let (client_ip, _) = self.peer(req);
+        original_client_ip(req.headers(), client_ip, &self.trusted_proxies)
+    }
+
+    async fn dispatch(
+        &self,
+        target: RoutingTarget,
+        application: Option<Arc<Application>>,
+        mut req: Request<Body>,
+    ) -> Result<Response<Body>, ApiError> {
+        match target {
+            RoutingTarget::Forward(target) => self.forward(target, application, req).await,
+            RoutingTarget::Orchestrate {
+                orchestration,
+                path_params,
+            } => {
+                tracing::info!("orchestrating {}", req.uri().path());
+                self.orchestration_service
+                    .execute(orchestration, path_params, req)
+                    .await
+            }
+            RoutingTarget::Transcode {
+                mut target,
+                transcoder,
+            } => {
+                match balance(
+                    target.balancer.as_ref(),
+                    &target.url_destination,
+                    req.headers(),
+                    self.original_client_ip(&req),
+                ) {
+                    Ok((url_destination, active)) => {
+                        target.url_destination = url_destination;
+                        self.credential_injector
+                            .inject(
+                                target.balancer.as_deref().map(UpstreamBalancer::upstream),
+                                req.headers_mut(),
+                            )
+                            .await?;
+                        let result = self
+                            .transcoding_service
+                            .execute(target, transcoder, req)
+                            .await;
+                        if let Some(active) = active {
+                            active.record(
+                                !matches!(&result, Err(api_error) if api_error.status_code >= 500),
+                            );
+                        }
+                        result
+                    }
+                    Err(api_error) => Err(api_error),
+                }
+            }
         }
+    }
 
-        if let Some(path_application) = path_segment.pop_front() {
-            tracing::info!("{:?}", path_application);
+    // with an api key too, only the requests carrying a bearer token are checked with the jwt or
+    // the introspection. the error is the reason of the failure and the error answered.
+    async fn authenticate(
+        &self,
+        auth: Option<&AuthPolicy>,
+        consumers: &ConsumerKeys,
+        req: &mut Request<Body>,
+    ) -> Result<Principal, (&'static str, ApiError)> {
+        let auth = match auth {
+            Some(auth) => auth,
+            None => return Ok(Principal::default()),
+        };
 
-            // let application = self.application_service.find_by_path((String::from("/") + path_application).as_str()).await?;
-            // tracing::info!("{:?}", application.path);
+        // with no other method the certificate alone tells the caller.
+        if let Some(client_certificate) = &auth.client_certificate {
+            let certificate = verify_client_certificate(client_certificate, req)
+                .map_err(|reason| (reason, client_certificate_error(reason)))?;
+            if auth.api_key.is_none() && auth.jwt.is_none() && auth.introspection.is_none() {
+                return Ok(Principal {
+                    consumer: consumers.find_by_certificate(&certificate),
+                    subject: certificate.subject().map(str::to_owned),
+                });
+            }
+        }
 
-            // let mut new_path_and_query = Vec::from(path_segment).join("/");
-            // if let Some(query) = req.uri().query() {
-            //     new_path_and_query += query;
-            // }
+        let jwt = auth.jwt.as_ref();
+        let introspection = auth.introspection.as_ref();
+        for forward_claims in jwt
+            .map(|jwt| &jwt.forward_claims)
+            .into_iter()
+            .chain(introspection.map(|introspection| &introspection.forward_claims))
+        {
+            add_claim_headers(req.headers_mut(), forward_claims, None);
+        }
 
-            // let new_uri = format!("{}/{}", application.url_destination, new_path_and_query);
+        let token = match bearer_token(req.headers()) {
+            Some(token) if jwt.is_some() || introspection.is_some() => token.to_owned(),
+            None if auth.api_key.is_none() && (jwt.is_some() || introspection.is_some()) => {
+                return Err(("missing", token_error("missing")))
+            }
+            _ => {
+                return authenticate(Some(auth), consumers, req)
+                    .map(|consumer| Principal {
+                        consumer,
+                        subject: None,
+                    })
+                    .map_err(|reason| (reason, unauthorized_error()))
+            }
+        };
 
-            // *req.uri_mut() = Uri::try_from(new_uri).unwrap();
-            // req.headers_mut().remove(HeaderName::from_static("host"));
+        // the authorization header reaches the upstream, which may check the token again.
+        let introspection = introspection.filter(|_| jwt.is_none() || !is_jwt(&token));
+        let (claims, forward_claims) = match (jwt, introspection) {
+            (_, Some(introspection)) => (
+                self.token_introspector
+                    .introspect(introspection, &token)
+                    .await,
+                &introspection.forward_claims,
+            ),
+            (Some(jwt), None) => (
+                self.jwt_keys.verify(jwt, &token).await.map(Arc::new),
+                &jwt.forward_claims,
+            ),
+            (None, None) => return Ok(Principal::default()),
+        };
+        let claims = claims.map_err(|reason| (reason, token_error(reason)))?;
 
-            // tracing::info!("{:?}", req);
+        add_claim_headers(req.headers_mut(), forward_claims, Some(&claims));
+        Ok(Principal {
+            consumer: None,
+            subject: claims.get("sub").and_then(Value::as_str).map(str::to_owned),
+        })
+    }
 
-            // let response = self.client.request(req).await.map_err(|e| {
-            //     tracing::error!("{:?}", e);
-            //     e
-            // })?;
+    // the limits of the consumer apply on top of the ones of the target.
+    async fn check_rate_limit(
+        &self,
+        target: &RoutingTarget,
+        principal: &Principal,
+        req: &Request<Body>,
+    ) -> Option<RateLimitDecision> {
+        let rate_limits: Vec<Arc<ScopedRateLimit>> = target
+            .rate_limits()
+            .iter()
+            .chain(
+                principal
+                    .consumer
+                    .as_ref()
+                    .and_then(|consumer| consumer.rate_limit.as_ref()),
+            )
+            .cloned()
+            .collect();
+        self.rate_limiter
+            .check(
+                &rate_limits,
+                &self.client_key(req),
+                principal.rate_limit_key().as_deref(),
+            )
+            .await
+    }
+
+    // the original client counts against the limits by client.
+    fn client_key(&self, req: &Request<Body>) -> String {
+        self.original_client_ip(req)
+            .map_or_else(|| String::from("unknown"), |ip| ip.to_string())
+    }
+
+    async fn forward(
+        &self,
+        target: ForwardTarget,
+        application: Option<Arc<Application>>,
+        mut req: Request<Body>,
+    ) -> Result<Response<Body>, ApiError> {
+        let (client_ip, trusted) = self.peer(&req);
+        let (url_destination, active) = balance(
+            target.balancer.as_ref(),
+            &target.url_destination,
+            req.headers(),
+            self.original_client_ip(&req),
+        )?;
 
-            // tracing::info!("{:?}", response);
+        // a grpc method is addressed by its full path, only the destination is replaced.
+        let grpc = is_grpc(req.headers());
+        let new_uri = ForwardService::forward_uri(
+            &url_destination,
+            if grpc {
+                req.uri().path()
+            } else {
+                &target.remaining_path
+            },
+            req.uri().query(),
+        )?;
+        tracing::info!("forwarding {} to {}", req.uri().path(), new_uri);
 
-            Ok(Response::new("Hello world".into()))
+        // the uri of a request received by the server has no scheme, only the tls listener tells.
+        let proto = if req.extensions().get::<TlsConnection>().is_some() {
+            "https"
         } else {
-            Err(ApiError::new(FORWARD_ERR_PATH_IS_REQUIRED))
+            "http"
+        };
+        let upgrade = is_websocket_upgrade(req.headers());
+
+        remove_hop_by_hop_headers(req.headers_mut());
+        add_forwarded_headers(req.headers_mut(), client_ip, trusted, proto);
+        self.credential_injector
+            .inject(
+                target.balancer.as_deref().map(UpstreamBalancer::upstream),
+                req.headers_mut(),
+            )
+            .await?;
+
+        *req.uri_mut() = new_uri;
+        if !target.preserve_host {
+            req.headers_mut().remove(HOST);
         }
+
+        // the listener also accepts http/2, only grpc goes to the upstream over it.
+        *req.version_mut() = if grpc {
+            Version::HTTP_2
+        } else {
+            Version::HTTP_11
+        };
+
+        if grpc {
+            return self
+                .forward_grpc(
+                    &target,
+                    application_label(&application),
+                    active.as_ref(),
+                    req,
+                )
+                .await;
+        }
+
+        if upgrade {
+            return self.upgrade(target, application, active, req).await;
+        }
+
+        // a compressed stream would be held back by the encoder until it fills a block.
+        if target.streaming {
+            req.headers_mut().remove(ACCEPT_ENCODING);
+        }
+
+        let deadline = target
+            .policy
+            .total_timeout()
+            .map(|total_timeout| Instant::now() + total_timeout);
+        let (mut response, active) = self
+            .send_with_retries(
+                &target,
+                application_label(&application),
+                active,
+                req,
+                deadline,
+            )
+            .await?;
+        remove_hop_by_hop_headers(response.headers_mut());
+
+        // an event stream has no end, the total timeout doesn't apply to its body.
+        let streaming = target.streaming || is_event_stream(response.headers());
+        let (idle_timeout, deadline) = if streaming {
+            response
+                .headers_mut()
+                .insert(X_ACCEL_BUFFERING, HeaderValue::from_static("no"));
+            (self.streaming_idle_timeout, None)
+        } else {
+            (self.proxy_idle_timeout, deadline)
+        };
+
+        // both bodies are streamed chunk by chunk, hyper only polls the client for more of the
+        // request while the upstream keeps reading it and writes every chunk of the response as
+        // soon as it arrives.
+        let (parts, body) = response.into_parts();
+        Ok(Response::from_parts(
+            parts,
+            release_after(with_body_timeouts(body, idle_timeout, deadline), active),
+        ))
+    }
+
+    // every attempt waits for the response headers up to the response timeout and all of them
+    // fit in the total timeout. a failed attempt is retried when the policy of the route allows
+    // it for the method, the application still has retry budget and the body could be buffered
+    // to be sent again; otherwise it's streamed once. a retry goes to another target of the
+    // upstream group while there is one not tried yet. no attempt is sent while the circuit of
+    // the upstream is open. the request stays in flight on the target that answered.
+    async fn send_with_retries(
+        &self,
+        target: &ForwardTarget,
+        label: &str,
+        mut active: Option<ActiveRequest>,
+        req: Request<Body>,
+        deadline: Option<Instant>,
+    ) -> Result<(Response<Body>, Option<ActiveRequest>), ApiError> {
+        let policy = &target.policy;
+        let client = self.clients.get_with_tls(
+            policy.connect_timeout(),
+            false,
+            target.balancer.as_deref().and_then(UpstreamBalancer::tls),
+        )?;
+
+        // long-poll routes may hold the response headers as long as any chunk of an event stream.
+        let response_timeout = policy.response_timeout().unwrap_or(if target.streaming {
+            self.streaming_idle_timeout
+        } else {
+            self.proxy_idle_timeout
+        });
+
+        let retry = policy
+            .retry
+            .as_ref()
+            .filter(|retry| retries_request(retry, req.method(), req.headers()))
+            .filter(|_| {
+                req.body()
+                    .size_hint()
+                    .upper()
+                    .is_some_and(|size| size <= self.max_body_size as u64)
+            });
+
+        let circuit = policy.circuit_breaker.as_ref();
+        let client_ip = self.original_client_ip(&req);
+
+        let (mut parts, body) = req.into_parts();
+        let (buffered, mut body) = match retry {
+            Some(_) => {
+                self.retry_budget.record_request(target.id_application);
+                (Some(buffer_body(body, self.max_body_size).await?), None)
+            }
+            None => (None, Some(body)),
+        };
+
+        let mut tried = Vec::new();
+        let mut attempt = 1;
+        loop {
+            if attempt > 1 {
+                if let Some((balancer, previous)) = target.balancer.as_ref().zip(active.as_ref()) {
+                    tried.push(previous.target());
+                    if let Some(selection) =
+                        balancer.pick_excluding(&parts.headers, client_ip, &tried)
+                    {
+                        parts.uri = selection
+                            .destination(&parts.uri.to_string())
+                            .parse()
+                            .unwrap_or(parts.uri);
+                        active = Some(selection.active);
+                    }
+                }
+            }
+
+            let upstream = upstream_key(&parts.uri);
+            if !self.circuit_allows(&upstream, circuit, label) {
+                return match circuit.and_then(|circuit| circuit.fallback.as_ref()) {
+                    Some(fallback) => Ok((fallback_response(fallback), active)),
+                    None => Err(circuit_open_error()),
+                };
+            }
+
+            let mut request = Request::new(match &buffered {
+                Some(buffered) => Body::from(Bytes::clone(buffered)),
+                None => body.take().unwrap_or_default(),
+            });
+            *request.method_mut() = parts.method.clone();
+            *request.uri_mut() = parts.uri.clone();
+            *request.version_mut() = parts.version;
+            *request.headers_mut() = parts.headers.clone();
+
+            let timeout = deadline.map_or(response_timeout, |deadline| {
+                response_timeout.min(deadline.saturating_duration_since(Instant::now()))
+            });
+            let result = tokio::time::timeout(timeout, client.request(request)).await;
+            self.record_outcome(
+                &upstream,
+                circuit,
+                active.as_ref(),
+                matches!(&result, Ok(Ok(response)) if !response.status().is_server_error()),
+            );
+
+            let reason = match (&result, retry) {
+                (_, None) => None,
+                (Ok(Ok(response)), Some(retry)) => retry
+                    .retries_status(response.status().as_u16())
+                    .then_some("status"),
+                (Ok(Err(e)), _) if e.is_connect() => Some("connect"),
+                (Ok(Err(_)), _) => Some("error"),
+                (Err(_), _) => Some("timeout"),
+            };
+
+            if let Some((retry, reason)) = retry.zip(reason) {
+                let wait = backoff(retry, attempt as u32);
+                let in_time = deadline.is_none_or(|deadline| Instant::now() + wait < deadline);
+
+                if attempt < retry.max_attempts && in_time {
+                    if self.retry_budget.try_retry(target.id_application) {
+                        tracing::warn!(
+                            "attempt {} to {} has failed ({}), retrying in {:?}",
+                            attempt,
+                            parts.uri,
+                            reason,
+                            wait
+                        );
+                        Metrics::global().increment(
+                            &GATEWAY_UPSTREAM_RETRIES,
+                            &[("application", label), ("reason", reason)],
+                        );
+
+                        tokio::time::sleep(wait).await;
+                        attempt += 1;
+                        continue;
+                    }
+
+                    tracing::warn!("retry budget exhausted for {}", label);
+                    Metrics::global()
+                        .increment(&GATEWAY_RETRY_BUDGET_EXHAUSTED, &[("application", label)]);
+                }
+            }
+
+            return match result {
+                Ok(Ok(response)) => Ok((response, active)),
+                Ok(Err(e)) => {
+                    tracing::error!("Error when forwarding a request: {:?}", e);
+                    Err(ApiError::new_with_status(
+                        StatusCode::BAD_GATEWAY,
+                        ERR_HYPER_ERROR,
+                    ))
+                }
+                Err(_) => {
+                    tracing::error!("upstream has not answered in {:?}", timeout);
+                    Err(ApiError::new_with_status(
+                        StatusCode::GATEWAY_TIMEOUT,
+                        FORWARD_ERR_UPSTREAM_TIMEOUT,
+                    ))
+                }
+            };
+        }
+    }
+
+    // the body is handed over untouched, rewrapping it would drop the trailers with the
+    // grpc-status. calls carry their own deadline, the streaming timeout only bounds the wait
+    // for the response headers.
+    async fn forward_grpc(
+        &self,
+        target: &ForwardTarget,
+        label: &str,
+        active: Option<&ActiveRequest>,
+        mut req: Request<Body>,
+    ) -> Result<Response<Body>, ApiError> {
+        add_grpc_headers(req.headers_mut());
+
+        let response_timeout = target
+            .policy
+            .response_timeout()
+            .unwrap_or(self.streaming_idle_timeout);
+        let mut response = self
+            .send(target, label, active, req, Some(response_timeout))
+            .await?;
+        remove_hop_by_hop_headers(response.headers_mut());
+
+        Ok(response)
+    }
+
+    async fn send(
+        &self,
+        target: &ForwardTarget,
+        label: &str,
+        active: Option<&ActiveRequest>,
+        req: Request<Body>,
+        response_timeout: Option<Duration>,
+    ) -> Result<Response<Body>, ApiError> {
+        let policy = &target.policy;
+        let circuit = policy.circuit_breaker.as_ref();
+        let upstream = upstream_key(req.uri());
+        if !self.circuit_allows(&upstream, circuit, label) {
+            return Err(circuit_open_error());
+        }
+
+        let client = self.clients.get_with_tls(
+            policy.connect_timeout(),
+            req.version() == Version::HTTP_2,
+            target.balancer.as_deref().and_then(UpstreamBalancer::tls),
+        )?;
+        let result = match response_timeout {
+            Some(response_timeout) => {
+                tokio::time::timeout(response_timeout, client.request(req)).await
+            }
+            None => Ok(client.request(req).await),
+        };
+        self.record_outcome(
+            &upstream,
+            circuit,
+            active,
+            matches!(&result, Ok(Ok(response)) if !response.status().is_server_error()),
+        );
+
+        match result {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(e)) => {
+                tracing::error!("Error when forwarding a request: {:?}", e);
+                Err(ApiError::new_with_status(
+                    StatusCode::BAD_GATEWAY,
+                    ERR_HYPER_ERROR,
+                ))
+            }
+            Err(_) => {
+                tracing::error!("upstream has not answered in {:?}", response_timeout);
+                Err(ApiError::new_with_status(
+                    StatusCode::GATEWAY_TIMEOUT,
+                    FORWARD_ERR_UPSTREAM_TIMEOUT,
+                ))
+            }
+        }
+    }
+
+    // routes without a circuit breaker in their policy always go through.
+    fn circuit_allows(
+        &self,
+        upstream: &str,
+        circuit: Option<&CircuitBreakerPolicy>,
+        label: &str,
+    ) -> bool {
+        let allowed =
+            circuit.is_none_or(|circuit| self.circuit_breakers.try_acquire(upstream, circuit));
+        if !allowed {
+            tracing::warn!(
+                "circuit of {} is open, request of {} rejected",
+                upstream,
+                label
+            );
+            Metrics::global()
+                .increment(&GATEWAY_CIRCUIT_BREAKER_REJECTED, &[("application", label)]);
+        }
+        allowed
+    }
+
+    // transport errors, timeouts and server errors count as failures of the upstream, and of
+    // the target of its group the request was sent to.
+    fn record_outcome(
+        &self,
+        upstream: &str,
+        circuit: Option<&CircuitBreakerPolicy>,
+        active: Option<&ActiveRequest>,
+        success: bool,
+    ) {
+        if let Some(circuit) = circuit {
+            self.circuit_breakers.record(upstream, circuit, success);
+        }
+        if let Some(active) = active {
+            active.record(success);
+        }
+    }
+
+    // the handshake is forwarded to the upstream, when it switches protocols both upgraded
+    // connections are spliced in background until one of them closes or stays idle.
+    async fn upgrade(
+        &self,
+        target: ForwardTarget,
+        application: Option<Arc<Application>>,
+        active: Option<ActiveRequest>,
+        mut req: Request<Body>,
+    ) -> Result<Response<Body>, ApiError> {
+        let label = application_label(&application).to_owned();
+        let max_connections = application
+            .as_ref()
+            .and_then(|application| application.websocket_max_connections);
+
+        let permit = WebSocketConnections::acquire(
+            &self.websocket_connections,
+            target.id_application,
+            max_connections,
+        )
+        .ok_or_else(|| {
+            tracing::warn!("websocket limit reached for {}", label);
+            Metrics::global().increment(&GATEWAY_WEBSOCKET_REJECTED, &[("application", &label)]);
+            ApiError::new_with_status(StatusCode::SERVICE_UNAVAILABLE, FORWARD_ERR_WEBSOCKET_LIMIT)
+        })?;
+
+        let client_upgrade = hyper::upgrade::on(&mut req);
+        req.headers_mut()
+            .insert(CONNECTION, HeaderValue::from_static("upgrade"));
+        req.headers_mut()
+            .insert(UPGRADE, HeaderValue::from_static("websocket"));
+
+        // the handshake waits for the upstream like the response headers of any other request.
+        let policy = &target.policy;
+        let response_timeout = policy.response_timeout().unwrap_or(self.proxy_idle_timeout);
+        let handshake_timeout = policy
+            .total_timeout()
+            .map_or(response_timeout, |total_timeout| {
+                response_timeout.min(total_timeout)
+            });
+        let mut response = self
+            .send(
+                &target,
+                &label,
+                active.as_ref(),
+                req,
+                Some(handshake_timeout),
+            )
+            .await?;
+        if response.status() != StatusCode::SWITCHING_PROTOCOLS {
+            remove_hop_by_hop_headers(response.headers_mut());
+            return Ok(response);
+        }
+
+        let upstream_upgrade = hyper::upgrade::on(&mut response);
+        let idle_timeout = self.websocket_idle_timeout;
+        let span = tracing::info_span!("websocket", application = %label);
+
+        tokio::spawn(
+            async move {
+                let _permit = permit;
+                let _active = active;
+                let (client, upstream) = match tokio::try_join!(client_upgrade, upstream_upgrade) {
+                    Ok(upgraded) => upgraded,
+                    Err(e) => {
+                        tracing::error!("Error when upgrading a websocket connection: {:?}", e);
+                        return;
+                    }
+                };
+
+                let metrics = Metrics::global();
+                let labels = [("application", label.as_str())];
+                metrics.increment(&GATEWAY_WEBSOCKET_CONNECTIONS, &labels);
+                metrics.add(&GATEWAY_WEBSOCKET_ACTIVE_CONNECTIONS, &labels, 1);
+                tracing::info!("websocket connection opened");
+
+                let started = Instant::now();
+                match splice(client, upstream, idle_timeout).await {
+                    Ok(spliced) => {
+                        for (direction, bytes) in [
+                            ("upstream", spliced.to_upstream),
+                            ("client", spliced.to_client),
+                        ] {
+                            metrics.add(
+                                &GATEWAY_WEBSOCKET_BYTES,
+                                &[("application", label.as_str()), ("direction", direction)],
+                                bytes as i64,
+                            );
+                        }
+                        tracing::info!(
+                            "websocket connection closed after {:?}, idle: {}, sent: {}, received: {}",
+                            started.elapsed(),
+                            spliced.idle,
+                            spliced.to_upstream,
+                            spliced.to_client
+                        );
+                    }
+                    Err(e) => tracing::error!("Error when splicing a websocket connection: {}", e),
+                }
+
+                metrics.add(&GATEWAY_WEBSOCKET_ACTIVE_CONNECTIONS, &labels, -1);
+            }
+            .instrument(span),
+        );
+
+        let mut client_response = Response::new(Body::empty());
+        *client_response.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+        *client_response.headers_mut() = response.headers().clone();
+
+        Ok(client_response)
     }
 }
@@ -0,0 +1,1571 @@
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use axum::{routing::any, Router};
+use chrono::Utc;
+use hyper::{
+    body::{Bytes, HttpBody},
+    header::{RETRY_AFTER, TE},
+    service::{make_service_fn, service_fn},
+    HeaderMap,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use crate::{
+    config::{TlsConnection, TrustedProxies},
+    exception::{
+        FORWARD_ERR_CIRCUIT_OPEN, FORWARD_ERR_FORBIDDEN, FORWARD_ERR_INVALID_DESTINATION,
+        FORWARD_ERR_INVALID_TOKEN, FORWARD_ERR_NO_UPSTREAM_TARGET, FORWARD_ERR_PATH_IS_REQUIRED,
+        FORWARD_ERR_PATH_NOT_FOUND, FORWARD_ERR_RATE_LIMITED, FORWARD_ERR_UNAUTHORIZED,
+        FORWARD_ERR_UPSTREAM_TIMEOUT,
+    },
+    model::{
+        hash_api_key, Application, ApplicationRoute, ApplicationWorkflow, Consumer, ConsumerApiKey,
+        Upstream, UpstreamTarget, ALGORITHM_ROUND_ROBIN,
+    },
+    repository::{MockRoutingRepositoryTrait, RoutingRows},
+    rest::ForwardController,
+    secret::SecretCipher,
+    service::{CircuitState, RoutingService},
+};
+
+use super::*;
+
+async fn start_upstream() -> SocketAddr {
+    let make_service = make_service_fn(|_| async {
+        Ok::<_, Infallible>(service_fn(|req: Request<Body>| async move {
+            let body = format!(
+                "{} {} {}",
+                req.method(),
+                req.uri(),
+                req.headers().get(HOST).map_or("", |host| host.to_str().unwrap())
+            );
+            Ok::<_, Infallible>(Response::new(Body::from(body)))
+        }))
+    });
+
+    let server = hyper::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+// "/download" sends a first chunk and never finishes, "/events" sends two events apart,
+// "/poll" answers late with the accept-encoding received, "/headers", "/consumer" and "/claims"
+// answer with the headers they have received and anything else with the first chunk of the body, without
+// waiting the rest.
+async fn start_streaming_upstream() -> SocketAddr {
+    let make_service = make_service_fn(|_| async {
+        Ok::<_, Infallible>(service_fn(|req: Request<Body>| async move {
+            let response = match req.uri().path() {
+                "/download" => {
+                    let (mut sender, body) = Body::channel();
+                    tokio::spawn(async move {
+                        sender.send_data(Bytes::from("first")).await.unwrap();
+                        tokio::time::sleep(Duration::from_secs(30)).await;
+                    });
+                    Response::new(body)
+                }
+                "/events" => {
+                    let (mut sender, body) = Body::channel();
+                    tokio::spawn(async move {
+                        sender
+                            .send_data(Bytes::from("data: first\n\n"))
+                            .await
+                            .unwrap();
+                        tokio::time::sleep(Duration::from_millis(300)).await;
+                        sender
+                            .send_data(Bytes::from("data: second\n\n"))
+                            .await
+                            .unwrap();
+                    });
+                    Response::builder()
+                        .header("content-type", "text/event-stream")
+                        .body(body)
+                        .unwrap()
+                }
+                "/poll" => {
+                    tokio::time::sleep(Duration::from_millis(300)).await;
+                    let accept_encoding = req
+                        .headers()
+                        .get(ACCEPT_ENCODING)
+                        .map_or("", |value| value.to_str().unwrap())
+                        .to_owned();
+                    Response::new(Body::from(accept_encoding))
+                }
+                "/headers" => {
+                    let header = |name: &str| {
+                        req.headers()
+                            .get(name)
+                            .map_or("", |value| value.to_str().unwrap())
+                            .to_owned()
+                    };
+                    let body = format!(
+                        "{}|{}|{}|{}",
+                        header("host"),
+                        header("x-forwarded-for"),
+                        header("forwarded"),
+                        header("x-session")
+                    );
+                    Response::builder()
+                        .header("proxy-authenticate", "Basic")
+                        .body(Body::from(body))
+                        .unwrap()
+                }
+                "/consumer" => {
+                    let header = |name: &str| {
+                        req.headers()
+                            .get(name)
+                            .map_or("", |value| value.to_str().unwrap())
+                            .to_owned()
+                    };
+                    let body = format!(
+                        "{}|{}|{}|{}",
+                        req.uri(),
+                        header("x-consumer-id"),
+                        header("x-consumer-name"),
+                        header("x-api-key")
+                    );
+                    Response::new(Body::from(body))
+                }
+                "/claims" => {
+                    let header = |name: &str| {
+                        req.headers()
+                            .get(name)
+                            .map_or("", |value| value.to_str().unwrap())
+                            .to_owned()
+                    };
+                    let body = format!(
+                        "{}|{}|{}|{}",
+                        header("x-user-id"),
+                        header("x-auth-subject"),
+                        header("x-consumer-id"),
+                        header("authorization")
+                    );
+                    Response::new(Body::from(body))
+                }
+                _ => {
+                    let mut body = req.into_body();
+                    let chunk = body.data().await.unwrap().unwrap();
+                    Response::new(Body::from(chunk))
+                }
+            };
+            Ok::<_, Infallible>(response)
+        }))
+    });
+
+    let server = hyper::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+// answers 503 to the first requests and then echoes the body, "/slow" answers late. all of
+// them are counted.
+async fn start_flaky_upstream(failures: usize) -> (SocketAddr, Arc<AtomicUsize>) {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&attempts);
+    let make_service = make_service_fn(move |_| {
+        let attempts = Arc::clone(&attempts);
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+                async move {
+                    if req.uri().path() == "/slow" {
+                        tokio::time::sleep(Duration::from_millis(300)).await;
+                    }
+
+                    let mut response = Response::new(req.into_body());
+                    if attempt <= failures {
+                        *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+                    }
+                    Ok::<_, Infallible>(response)
+                }
+            }))
+        }
+    });
+
+    let server = hyper::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    (addr, counter)
+}
+
+// switches to any protocol asked and echoes what it receives over the upgraded connection.
+async fn start_websocket_upstream() -> SocketAddr {
+    let make_service = make_service_fn(|_| async {
+        Ok::<_, Infallible>(service_fn(|mut req: Request<Body>| async move {
+            let upgrade = hyper::upgrade::on(&mut req);
+            tokio::spawn(async move {
+                if let Ok(mut upgraded) = upgrade.await {
+                    let mut buffer = [0u8; 1024];
+                    while let Ok(read) = upgraded.read(&mut buffer).await {
+                        if read == 0 || upgraded.write_all(&buffer[..read]).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+            });
+
+            let response = Response::builder()
+                .status(StatusCode::SWITCHING_PROTOCOLS)
+                .header(CONNECTION, "upgrade")
+                .header(UPGRADE, "websocket")
+                .header("sec-websocket-accept", "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=")
+                .body(Body::empty())
+                .unwrap();
+            Ok::<_, Infallible>(response)
+        }))
+    });
+
+    let server = hyper::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+// answers grpc calls over http/2 only, with the path and te received in the message and the
+// status in the trailers.
+async fn start_grpc_upstream() -> SocketAddr {
+    let make_service = make_service_fn(|_| async {
+        Ok::<_, Infallible>(service_fn(|req: Request<Body>| async move {
+            let message = format!(
+                "{}|{}",
+                req.uri().path(),
+                req.headers()
+                    .get(TE)
+                    .map_or("", |value| value.to_str().unwrap())
+            );
+
+            let (mut sender, body) = Body::channel();
+            tokio::spawn(async move {
+                sender.send_data(Bytes::from(message)).await.unwrap();
+                let mut trailers = HeaderMap::new();
+                trailers.insert("grpc-status", HeaderValue::from_static("0"));
+                sender.send_trailers(trailers).await.unwrap();
+            });
+
+            let response = Response::builder()
+                .header("content-type", "application/grpc")
+                .body(body)
+                .unwrap();
+            Ok::<_, Infallible>(response)
+        }))
+    });
+
+    let server = hyper::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+        .http2_only(true)
+        .serve(make_service);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+fn grpc_request(gateway: SocketAddr, path: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("http://{}{}", gateway, path))
+        .header("content-type", "application/grpc")
+        .header(TE, "trailers")
+        .body(Body::from("hello"))
+        .unwrap()
+}
+
+// serves the forward service like main does, upgrades need a real connection.
+fn start_gateway(service: ForwardService) -> SocketAddr {
+    let forward_service: Arc<dyn ForwardServiceTrait + Send + Sync> = Arc::new(service);
+    let app = Router::new().route(
+        "/*path",
+        any(ForwardController::handle).with_state(forward_service),
+    );
+
+    let server = axum::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>());
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+// sends the handshake and returns the connection with the status line of the response.
+async fn open_websocket(addr: SocketAddr) -> (TcpStream, String) {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(
+            b"GET /socket/chat HTTP/1.1\r\nHost: anothergtw.com\r\nConnection: Upgrade\r\n\
+              Upgrade: websocket\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+              Sec-WebSocket-Version: 13\r\n\r\n",
+        )
+        .await
+        .unwrap();
+
+    let mut head = Vec::new();
+    let mut byte = [0u8; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        if stream.read(&mut byte).await.unwrap() == 0 {
+            break;
+        }
+        head.push(byte[0]);
+    }
+
+    let head = String::from_utf8(head).unwrap();
+    let status = head.lines().next().unwrap_or("").to_owned();
+    (stream, status)
+}
+
+async fn routing_service(applications: Vec<Application>) -> Arc<RoutingService> {
+    routing_service_with_routes(applications, Vec::new(), Vec::new()).await
+}
+
+async fn routing_service_with_routes(
+    applications: Vec<Application>,
+    workflows: Vec<ApplicationWorkflow>,
+    routes: Vec<ApplicationRoute>,
+) -> Arc<RoutingService> {
+    routing_service_with_consumers(applications, workflows, routes, Vec::new(), Vec::new()).await
+}
+
+async fn routing_service_with_consumers(
+    applications: Vec<Application>,
+    workflows: Vec<ApplicationWorkflow>,
+    routes: Vec<ApplicationRoute>,
+    consumers: Vec<Consumer>,
+    api_keys: Vec<ConsumerApiKey>,
+) -> Arc<RoutingService> {
+    let mut mock_repo = MockRoutingRepositoryTrait::new();
+    mock_repo.expect_find_routing_rows().return_once(move || {
+        Ok(RoutingRows {
+            applications,
+            workflows,
+            routes,
+            consumers,
+            consumer_api_keys: api_keys,
+            ..RoutingRows::default()
+        })
+    });
+
+    let routing_service = Arc::new(RoutingService::new_with_cipher(
+        Arc::new(mock_repo),
+        Some(cipher()),
+    ));
+    routing_service.reload().await.unwrap();
+    routing_service
+}
+
+fn cipher() -> SecretCipher {
+    SecretCipher::from_base64("MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=").unwrap()
+}
+
+// the auth of the application as the admin api stores it, with its secrets sealed.
+fn sealed_auth(mut auth: Value) -> Option<Value> {
+    let context = Application::auth_sealing_context(1);
+    AuthPolicy::seal_secrets(&mut auth, &context, &cipher()).unwrap();
+    Some(auth)
+}
+
+fn headers_request() -> Request<Body> {
+    let mut request = Request::builder()
+        .uri("/teste/headers")
+        .header(HOST, "anothergtw.com")
+        .header("x-forwarded-for", "10.0.0.1")
+        .header("connection", "x-session")
+        .header("x-session", "1")
+        .body(Body::empty())
+        .unwrap();
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from(([192, 168, 0, 7], 5000))));
+    request
+}
+
+fn application(url_destination: String) -> Application {
+    Application {
+        id: 1,
+        name: String::from("Teste"),
+        path: String::from("/teste"),
+        url_destination,
+        preserve_host: false,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn handle() {
+    let addr = start_upstream().await;
+
+    let routing_service =
+        routing_service(vec![application(format!("http://{}/base/", addr))]).await;
+    let service = ForwardService::new(routing_service);
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("http://anothergtw.com/teste/orders/1?status=open")
+        .header(HOST, "anothergtw.com")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = service.handle(request).await;
+    assert!(response.is_ok());
+
+    let response = response.unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(
+        format!("POST /base/orders/1?status=open {}", addr),
+        String::from_utf8(body.to_vec()).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn handle_without_path() {
+    let service = ForwardService::new(routing_service(Vec::new()).await);
+
+    let request = Request::builder().uri("/").body(Body::empty()).unwrap();
+
+    let response = service.handle(request).await;
+    assert!(response.is_err());
+    assert_eq!(FORWARD_ERR_PATH_IS_REQUIRED.0, response.unwrap_err().code);
+}
+
+#[tokio::test]
+async fn handle_application_not_found() {
+    let routing_service =
+        routing_service(vec![application(String::from("http://anothergtw.com"))]).await;
+    let service = ForwardService::new(routing_service);
+
+    let request = Request::builder()
+        .uri("/unknown/orders")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = service.handle(request).await;
+    assert!(response.is_err());
+
+    let api_error = response.unwrap_err();
+    assert_eq!(404, api_error.status_code);
+    assert_eq!(FORWARD_ERR_PATH_NOT_FOUND.0, api_error.code);
+}
+
+#[tokio::test]
+async fn handle_invalid_destination() {
+    let routing_service = routing_service(vec![application(String::from("anothergtw"))]).await;
+    let service = ForwardService::new(routing_service);
+
+    let request = Request::builder()
+        .uri("/teste/orders")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = service.handle(request).await;
+    assert!(response.is_err());
+    assert_eq!(FORWARD_ERR_INVALID_DESTINATION.0, response.unwrap_err().code);
+}
+
+#[tokio::test]
+async fn handle_with_path_params() {
+    let addr = start_upstream().await;
+
+    let mut mock_repo = MockRoutingRepositoryTrait::new();
+    mock_repo.expect_find_routing_rows().returning(move || {
+        Ok(RoutingRows {
+            applications: vec![application(String::from("http://anothergtw.com"))],
+            workflows: vec![ApplicationWorkflow {
+                id: 1,
+                id_application: 1,
+                path: String::from("/:version(v\\d+)"),
+                forward_to: format!("http://{}/{{{{request.path.version}}}}/", addr),
+                status: String::from("ACTIVE"),
+                priority: 0,
+                upstream_policy: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            }],
+            ..RoutingRows::default()
+        })
+    });
+
+    let routing_service = Arc::new(RoutingService::new_with_repo(Arc::new(mock_repo)));
+    routing_service.reload().await.unwrap();
+    let service = ForwardService::new(routing_service);
+
+    let request = Request::builder()
+        .uri("/teste/v2/orders")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = service.handle(request).await.unwrap();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(
+        format!("GET /v2/orders {}", addr),
+        String::from_utf8(body.to_vec()).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn handle_streaming_download() {
+    let addr = start_streaming_upstream().await;
+
+    let routing_service = routing_service(vec![application(format!("http://{}", addr))]).await;
+    let service = ForwardService::new(routing_service);
+
+    let request = Request::builder()
+        .uri("/teste/download")
+        .body(Body::empty())
+        .unwrap();
+
+    // the first chunk arrives while the upstream is still sending the body.
+    let response = service.handle(request).await.unwrap();
+    let chunk = tokio::time::timeout(Duration::from_secs(5), response.into_body().data()).await;
+    assert!(chunk.is_ok());
+    assert_eq!(Bytes::from("first"), chunk.unwrap().unwrap().unwrap());
+}
+
+#[tokio::test]
+async fn handle_idle_response() {
+    let addr = start_streaming_upstream().await;
+
+    let routing_service = routing_service(vec![application(format!("http://{}", addr))]).await;
+    let mut service = ForwardService::new(routing_service);
+    service.proxy_idle_timeout = Duration::from_millis(100);
+
+    let request = Request::builder()
+        .uri("/teste/download")
+        .body(Body::empty())
+        .unwrap();
+
+    // the body fails once the upstream stops sending it.
+    let mut body = service.handle(request).await.unwrap().into_body();
+    assert_eq!(Bytes::from("first"), body.data().await.unwrap().unwrap());
+    let chunk = tokio::time::timeout(Duration::from_secs(5), body.data()).await;
+    assert!(chunk.unwrap().unwrap().is_err());
+}
+
+#[tokio::test]
+async fn handle_event_stream() {
+    let addr = start_streaming_upstream().await;
+
+    let routing_service = routing_service(vec![application(format!("http://{}", addr))]).await;
+    let mut service = ForwardService::new(routing_service);
+    service.proxy_idle_timeout = Duration::from_millis(100);
+
+    let request = Request::builder()
+        .uri("/teste/events")
+        .body(Body::empty())
+        .unwrap();
+
+    // detected by its content type, each event is flushed and the pause between them is allowed.
+    let response = service.handle(request).await.unwrap();
+    assert_eq!("no", response.headers().get("x-accel-buffering").unwrap());
+
+    let mut body = response.into_body();
+    assert_eq!(
+        Bytes::from("data: first\n\n"),
+        body.data().await.unwrap().unwrap()
+    );
+    assert_eq!(
+        Bytes::from("data: second\n\n"),
+        body.data().await.unwrap().unwrap()
+    );
+}
+
+#[tokio::test]
+async fn handle_long_poll() {
+    let addr = start_streaming_upstream().await;
+
+    let workflow = ApplicationWorkflow {
+        id: 1,
+        id_application: 1,
+        path: String::from("/v1"),
+        forward_to: format!("http://{}", addr),
+        status: String::from("ACTIVE"),
+        priority: 0,
+        upstream_policy: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    };
+    let route = ApplicationRoute {
+        id: 1,
+        id_application_workflow: Some(1),
+        path: String::from("/poll"),
+        forward_to: None,
+        priority: 0,
+        streaming: true,
+        grpc_descriptor: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    };
+
+    let routing_service = routing_service_with_routes(
+        vec![application(format!("http://{}", addr))],
+        vec![workflow],
+        vec![route],
+    )
+    .await;
+    let mut service = ForwardService::new(routing_service);
+    service.proxy_idle_timeout = Duration::from_millis(100);
+
+    // the streaming route waits for the late answer and asks for an uncompressed one.
+    let request = Request::builder()
+        .uri("/teste/v1/poll")
+        .header(ACCEPT_ENCODING, "gzip")
+        .body(Body::empty())
+        .unwrap();
+    let response = service.handle(request).await.unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert!(body.is_empty());
+
+    // the same upstream path through the application times out.
+    let request = Request::builder()
+        .uri("/teste/poll")
+        .body(Body::empty())
+        .unwrap();
+    let api_error = service.handle(request).await.unwrap_err();
+    assert_eq!(504, api_error.status_code);
+    assert_eq!(FORWARD_ERR_UPSTREAM_TIMEOUT.0, api_error.code);
+}
+
+#[tokio::test]
+async fn handle_streaming_upload() {
+    let addr = start_streaming_upstream().await;
+
+    let routing_service = routing_service(vec![application(format!("http://{}", addr))]).await;
+    let service = ForwardService::new(routing_service);
+
+    let (mut sender, body) = Body::channel();
+    sender.send_data(Bytes::from("first")).await.unwrap();
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/teste/upload")
+        .body(body)
+        .unwrap();
+
+    // the upstream answers while the client is still uploading.
+    let response = tokio::time::timeout(Duration::from_secs(5), service.handle(request)).await;
+    assert!(response.is_ok());
+
+    let body = hyper::body::to_bytes(response.unwrap().unwrap().into_body())
+        .await
+        .unwrap();
+    assert_eq!(Bytes::from("first"), body);
+    drop(sender);
+}
+
+#[tokio::test]
+async fn handle_with_proxy_headers() {
+    let addr = start_streaming_upstream().await;
+
+    let routing_service = routing_service(vec![application(format!("http://{}", addr))]).await;
+    let service = ForwardService::new_with_trusted_proxies(
+        routing_service.clone(),
+        TrustedProxies::parse("10.0.0.0/8"),
+    );
+
+    // the client isn't a trusted proxy, its x-forwarded-for is replaced.
+    let response = service.handle(headers_request()).await.unwrap();
+    assert!(response.headers().get("proxy-authenticate").is_none());
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(
+        format!(
+            "{}|192.168.0.7|for=192.168.0.7;proto=http;host=anothergtw.com|",
+            addr
+        ),
+        String::from_utf8(body.to_vec()).unwrap()
+    );
+
+    let service = ForwardService::new_with_trusted_proxies(
+        routing_service,
+        TrustedProxies::parse("192.168.0.0/16"),
+    );
+    let response = service.handle(headers_request()).await.unwrap();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(
+        format!(
+            "{}|10.0.0.1, 192.168.0.7|for=192.168.0.7;proto=http;host=anothergtw.com|",
+            addr
+        ),
+        String::from_utf8(body.to_vec()).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn handle_with_proxy_headers_over_tls() {
+    let addr = start_streaming_upstream().await;
+
+    let routing_service = routing_service(vec![application(format!("http://{}", addr))]).await;
+    let service =
+        ForwardService::new_with_trusted_proxies(routing_service, TrustedProxies::default());
+
+    // the tls listener marks the requests it receives, their uri has no scheme.
+    let mut request = headers_request();
+    request.extensions_mut().insert(TlsConnection::default());
+    let response = service.handle(request).await.unwrap();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(
+        format!(
+            "{}|192.168.0.7|for=192.168.0.7;proto=https;host=anothergtw.com|",
+            addr
+        ),
+        String::from_utf8(body.to_vec()).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn handle_with_preserve_host() {
+    let addr = start_streaming_upstream().await;
+
+    let mut application = application(format!("http://{}", addr));
+    application.preserve_host = true;
+    let service = ForwardService::new(routing_service(vec![application]).await);
+
+    let response = service.handle(headers_request()).await.unwrap();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert!(String::from_utf8(body.to_vec())
+        .unwrap()
+        .starts_with("anothergtw.com|"));
+}
+
+#[tokio::test]
+async fn handle_websocket() {
+    let addr = start_websocket_upstream().await;
+
+    let mut application = application(format!("http://{}", addr));
+    application.path = String::from("/socket");
+    application.websocket_max_connections = Some(1);
+
+    let mut service = ForwardService::new(routing_service(vec![application]).await);
+    service.websocket_idle_timeout = Duration::from_millis(300);
+    let gateway = start_gateway(service);
+
+    let (mut stream, status) = open_websocket(gateway).await;
+    assert_eq!("HTTP/1.1 101 Switching Protocols", status);
+
+    stream.write_all(b"ping").await.unwrap();
+    let mut buffer = [0u8; 4];
+    stream.read_exact(&mut buffer).await.unwrap();
+    assert_eq!(b"ping", &buffer);
+
+    // only one connection is allowed for the application.
+    let (_, status) = open_websocket(gateway).await;
+    assert_eq!("HTTP/1.1 503 Service Unavailable", status);
+
+    // the idle connection is closed and its slot released.
+    let read = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buffer)).await;
+    assert_eq!(0, read.unwrap().unwrap());
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let (_, status) = open_websocket(gateway).await;
+    assert_eq!("HTTP/1.1 101 Switching Protocols", status);
+
+    let metrics = Metrics::global();
+    assert!(
+        metrics.get(
+            &GATEWAY_WEBSOCKET_CONNECTIONS,
+            &[("application", "/socket")]
+        ) >= 1
+    );
+    assert!(metrics.get(&GATEWAY_WEBSOCKET_REJECTED, &[("application", "/socket")]) >= 1);
+    assert!(
+        metrics.get(
+            &GATEWAY_WEBSOCKET_BYTES,
+            &[("application", "/socket"), ("direction", "client")]
+        ) >= 4
+    );
+}
+
+#[tokio::test]
+async fn handle_grpc() {
+    let addr = start_grpc_upstream().await;
+
+    let mut application = application(format!("http://{}", addr));
+    application.path = String::from("/helloworld.Greeter");
+    let service = ForwardService::new(routing_service(vec![application]).await);
+    let gateway = start_gateway(service);
+
+    // the gateway listener accepts http/2 with prior knowledge.
+    let client = hyper::Client::builder()
+        .http2_only(true)
+        .build_http::<Body>();
+
+    let response = client
+        .request(grpc_request(gateway, "/helloworld.Greeter/SayHello"))
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+
+    // the full method path is kept and the trailers reach the client.
+    let mut body = response.into_body();
+    assert_eq!(
+        Bytes::from("/helloworld.Greeter/SayHello|trailers"),
+        body.data().await.unwrap().unwrap()
+    );
+    assert!(body.data().await.is_none());
+    let trailers = body.trailers().await.unwrap().unwrap();
+    assert_eq!("0", trailers["grpc-status"]);
+}
+
+#[tokio::test]
+async fn handle_grpc_not_found() {
+    let service = ForwardService::new(routing_service(Vec::new()).await);
+    let gateway = start_gateway(service);
+
+    let client = hyper::Client::builder()
+        .http2_only(true)
+        .build_http::<Body>();
+
+    // gateway failures are answered with a grpc status instead of a json body.
+    let response = client
+        .request(grpc_request(gateway, "/helloworld.Greeter/SayHello"))
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!("application/grpc", response.headers()["content-type"]);
+    assert_eq!("12", response.headers()["grpc-status"]);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert!(body.is_empty());
+}
+
+fn application_with_policy(addr: SocketAddr, upstream_policy: serde_json::Value) -> Application {
+    Application {
+        upstream_policy: Some(upstream_policy),
+        ..application(format!("http://{}", addr))
+    }
+}
+
+#[tokio::test]
+async fn handle_with_retries() {
+    let (addr, attempts) = start_flaky_upstream(2).await;
+
+    let policy = serde_json::json!({"retry": {"maxAttempts": 3, "baseBackoffMs": 1}});
+    let routing_service = routing_service(vec![application_with_policy(addr, policy)]).await;
+    let service = ForwardService::new(routing_service);
+
+    let request = Request::builder()
+        .method("PUT")
+        .uri("/teste/orders/1")
+        .body(Body::from("order"))
+        .unwrap();
+
+    // the buffered body is sent again on every attempt.
+    let response = service.handle(request).await.unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(Bytes::from("order"), body);
+    assert_eq!(3, attempts.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn handle_with_retries_exhausted() {
+    let (addr, attempts) = start_flaky_upstream(5).await;
+
+    let policy = serde_json::json!({"retry": {"maxAttempts": 2, "baseBackoffMs": 1}});
+    let routing_service = routing_service(vec![application_with_policy(addr, policy)]).await;
+    let service = ForwardService::new(routing_service);
+
+    let request = Request::builder()
+        .uri("/teste/orders")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = service.handle(request).await.unwrap();
+    assert_eq!(StatusCode::SERVICE_UNAVAILABLE, response.status());
+    assert_eq!(2, attempts.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn handle_non_idempotent_request() {
+    let (addr, attempts) = start_flaky_upstream(2).await;
+
+    let policy = serde_json::json!({"retry": {"maxAttempts": 3, "baseBackoffMs": 1}});
+    let routing_service = routing_service(vec![application_with_policy(addr, policy)]).await;
+    let service = ForwardService::new(routing_service);
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/teste/orders")
+        .body(Body::from("order"))
+        .unwrap();
+
+    let response = service.handle(request).await.unwrap();
+    assert_eq!(StatusCode::SERVICE_UNAVAILABLE, response.status());
+    assert_eq!(1, attempts.load(Ordering::SeqCst));
+
+    // an idempotency key makes it safe to send it again.
+    let request = Request::builder()
+        .method("POST")
+        .uri("/teste/orders")
+        .header("idempotency-key", "b4c7")
+        .body(Body::from("order"))
+        .unwrap();
+
+    let response = service.handle(request).await.unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!(3, attempts.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn handle_with_exhausted_retry_budget() {
+    let (addr, attempts) = start_flaky_upstream(10).await;
+
+    let policy = serde_json::json!({"retry": {"maxAttempts": 5, "baseBackoffMs": 1}});
+    let routing_service = routing_service(vec![application_with_policy(addr, policy)]).await;
+    let mut service = ForwardService::new(routing_service);
+    service.retry_budget = RetryBudget::new(0.0, 2, Duration::from_secs(60));
+
+    for expected_attempts in [3, 4] {
+        let request = Request::builder()
+            .uri("/teste/orders")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = service.handle(request).await.unwrap();
+        assert_eq!(StatusCode::SERVICE_UNAVAILABLE, response.status());
+        assert_eq!(expected_attempts, attempts.load(Ordering::SeqCst));
+    }
+}
+
+#[tokio::test]
+async fn handle_with_response_timeout() {
+    let (addr, attempts) = start_flaky_upstream(0).await;
+
+    let policy = serde_json::json!({
+        "responseTimeoutMs": 50,
+        "retry": {"maxAttempts": 2, "baseBackoffMs": 1}
+    });
+    let routing_service = routing_service(vec![application_with_policy(addr, policy)]).await;
+    let service = ForwardService::new(routing_service);
+
+    let request = Request::builder()
+        .uri("/teste/slow")
+        .body(Body::empty())
+        .unwrap();
+
+    let api_error = service.handle(request).await.unwrap_err();
+    assert_eq!(504, api_error.status_code);
+    assert_eq!(FORWARD_ERR_UPSTREAM_TIMEOUT.0, api_error.code);
+    assert_eq!(2, attempts.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn handle_with_total_timeout() {
+    let (addr, attempts) = start_flaky_upstream(0).await;
+
+    let policy = serde_json::json!({
+        "responseTimeoutMs": 100,
+        "totalTimeoutMs": 150,
+        "retry": {"maxAttempts": 5, "baseBackoffMs": 1}
+    });
+    let routing_service = routing_service(vec![application_with_policy(addr, policy)]).await;
+    let service = ForwardService::new(routing_service);
+
+    let request = Request::builder()
+        .uri("/teste/slow")
+        .body(Body::empty())
+        .unwrap();
+
+    let started = Instant::now();
+    let api_error = service.handle(request).await.unwrap_err();
+    assert_eq!(504, api_error.status_code);
+    assert!(started.elapsed() < Duration::from_millis(300));
+    assert_eq!(2, attempts.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn handle_websocket_with_response_timeout() {
+    let (addr, _) = start_flaky_upstream(0).await;
+
+    let policy = serde_json::json!({"responseTimeoutMs": 50});
+    let routing_service = routing_service(vec![application_with_policy(addr, policy)]).await;
+    let service = ForwardService::new(routing_service);
+
+    let request = Request::builder()
+        .uri("/teste/slow")
+        .header(CONNECTION, "upgrade")
+        .header(UPGRADE, "websocket")
+        .body(Body::empty())
+        .unwrap();
+
+    let api_error = service.handle(request).await.unwrap_err();
+    assert_eq!(504, api_error.status_code);
+    assert_eq!(FORWARD_ERR_UPSTREAM_TIMEOUT.0, api_error.code);
+}
+
+#[tokio::test]
+async fn handle_with_open_circuit() {
+    let (addr, attempts) = start_flaky_upstream(2).await;
+
+    let policy = serde_json::json!({"circuitBreaker": {"consecutiveFailures": 2, "openMs": 100}});
+    let routing_service = routing_service(vec![application_with_policy(addr, policy)]).await;
+    let circuit_breakers = Arc::new(CircuitBreakers::default());
+    let service =
+        ForwardService::new_with_circuit_breakers(routing_service, Arc::clone(&circuit_breakers));
+
+    let request = || {
+        Request::builder()
+            .uri("/teste/orders")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    for _ in 0..2 {
+        let response = service.handle(request()).await.unwrap();
+        assert_eq!(StatusCode::SERVICE_UNAVAILABLE, response.status());
+    }
+
+    // the upstream isn't called while the circuit is open.
+    let api_error = service.handle(request()).await.unwrap_err();
+    assert_eq!(503, api_error.status_code);
+    assert_eq!(FORWARD_ERR_CIRCUIT_OPEN.0, api_error.code);
+    assert_eq!(2, attempts.load(Ordering::SeqCst));
+
+    let snapshot = circuit_breakers.snapshot();
+    assert_eq!(format!("http://{}", addr), snapshot[0].upstream);
+    assert_eq!(CircuitState::Open, snapshot[0].state);
+
+    // a successful trial closes it.
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    let response = service.handle(request()).await.unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!(CircuitState::Closed, circuit_breakers.snapshot()[0].state);
+}
+
+#[tokio::test]
+async fn handle_with_circuit_fallback() {
+    let (addr, attempts) = start_flaky_upstream(10).await;
+
+    let policy = serde_json::json!({
+        "retry": {"maxAttempts": 3, "baseBackoffMs": 1},
+        "circuitBreaker": {
+            "consecutiveFailures": 2,
+            "fallback": {"status": 200, "headers": {"x-fallback": "true"}, "body": {"orders": []}}
+        }
+    });
+    let routing_service = routing_service(vec![application_with_policy(addr, policy)]).await;
+    let service = ForwardService::new(routing_service);
+
+    // the retries stop as soon as the circuit opens.
+    let request = Request::builder()
+        .uri("/teste/orders")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = service.handle(request).await.unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!("true", response.headers()["x-fallback"]);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(Bytes::from(r#"{"orders":[]}"#), body);
+    assert_eq!(2, attempts.load(Ordering::SeqCst));
+}
+
+async fn routing_service_with_upstream(
+    application: Application,
+    targets: Vec<UpstreamTarget>,
+) -> Arc<RoutingService> {
+    let mut mock_repo = MockRoutingRepositoryTrait::new();
+    mock_repo.expect_find_routing_rows().return_once(move || {
+        Ok(RoutingRows {
+            applications: vec![application],
+            upstreams: vec![Upstream {
+                id: 1,
+                name: String::from("teste"),
+                algorithm: String::from(ALGORITHM_ROUND_ROBIN),
+                hash_on: None,
+                hash_key: None,
+                health_check: None,
+                credentials_type: None,
+                credentials: None,
+                tls: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            }],
+            upstream_targets: targets,
+            ..RoutingRows::default()
+        })
+    });
+
+    let routing_service = Arc::new(RoutingService::new_with_repo(Arc::new(mock_repo)));
+    routing_service.reload().await.unwrap();
+    routing_service
+}
+
+fn upstream_target(id: i64, addr: SocketAddr, weight: i32) -> UpstreamTarget {
+    UpstreamTarget {
+        id,
+        id_upstream: 1,
+        url: format!("http://{}", addr),
+        weight,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn handle_with_upstream_targets() {
+    let first = start_upstream().await;
+    let second = start_upstream().await;
+
+    // the destination keeps its path, the targets replace its host.
+    let mut application = application(String::from("http://orders.internal/base"));
+    application.id_upstream = Some(1);
+    let routing_service = routing_service_with_upstream(
+        application,
+        vec![
+            upstream_target(1, first, 100),
+            upstream_target(2, second, 100),
+        ],
+    )
+    .await;
+    let service = ForwardService::new(routing_service);
+
+    let mut bodies = Vec::new();
+    for _ in 0..2 {
+        let request = Request::builder()
+            .uri("/teste/orders")
+            .body(Body::empty())
+            .unwrap();
+        let response = service.handle(request).await.unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        bodies.push(String::from_utf8(body.to_vec()).unwrap());
+    }
+
+    assert_eq!(
+        vec![
+            format!("GET /base/orders {}", first),
+            format!("GET /base/orders {}", second)
+        ],
+        bodies
+    );
+}
+
+#[tokio::test]
+async fn handle_with_retries_on_another_target() {
+    let (failing, attempts) = start_flaky_upstream(usize::MAX).await;
+    let healthy = start_upstream().await;
+
+    let mut application = application_with_policy(
+        failing,
+        serde_json::json!({"retry": {"maxAttempts": 3, "baseBackoffMs": 1}}),
+    );
+    application.url_destination = String::from("http://orders.internal/base");
+    application.id_upstream = Some(1);
+    let routing_service = routing_service_with_upstream(
+        application,
+        vec![
+            upstream_target(1, failing, 100),
+            upstream_target(2, healthy, 100),
+        ],
+    )
+    .await;
+    let service = ForwardService::new(routing_service);
+
+    // the first target answers 503, the retry is sent to the other one.
+    let request = Request::builder()
+        .uri("/teste/orders")
+        .body(Body::empty())
+        .unwrap();
+    let response = service.handle(request).await.unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(format!("GET /base/orders {}", healthy), body);
+    assert_eq!(1, attempts.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn handle_without_upstream_targets() {
+    let addr = start_upstream().await;
+
+    let mut application = application(format!("http://{}", addr));
+    application.id_upstream = Some(1);
+    let routing_service =
+        routing_service_with_upstream(application, vec![upstream_target(1, addr, 0)]).await;
+    let service = ForwardService::new(routing_service);
+
+    let request = Request::builder()
+        .uri("/teste/orders")
+        .body(Body::empty())
+        .unwrap();
+
+    let api_error = service.handle(request).await.unwrap_err();
+    assert_eq!(503, api_error.status_code);
+    assert_eq!(FORWARD_ERR_NO_UPSTREAM_TARGET.0, api_error.code);
+}
+
+#[tokio::test]
+async fn handle_with_rate_limit() {
+    let addr = start_upstream().await;
+
+    let application = Application {
+        rate_limit: Some(serde_json::json!({"limits": [{"requests": 1, "period": "minute"}]})),
+        ..application(format!("http://{}", addr))
+    };
+    let routing_service = routing_service(vec![application]).await;
+    let service = ForwardService::new_with_rate_limiter(routing_service, RateLimiter::local());
+
+    let request = || {
+        Request::builder()
+            .uri("/teste/orders")
+            .body(Body::empty())
+            .unwrap()
+    };
+    let response = service.handle(request()).await.unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!("1", response.headers()["ratelimit-limit"]);
+    assert_eq!("0", response.headers()["ratelimit-remaining"]);
+
+    let response = service.handle(request()).await.unwrap();
+    assert_eq!(StatusCode::TOO_MANY_REQUESTS, response.status());
+    assert!(response.headers().contains_key(RETRY_AFTER));
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(FORWARD_ERR_RATE_LIMITED.0, api_error["code"]);
+}
+
+#[tokio::test]
+async fn handle_with_rate_limit_and_forged_forwarded_for() {
+    let addr = start_upstream().await;
+
+    let application = Application {
+        rate_limit: Some(
+            serde_json::json!({"limits": [{"requests": 1, "period": "minute", "by": "client"}]}),
+        ),
+        ..application(format!("http://{}", addr))
+    };
+    let routing_service = routing_service(vec![application]).await;
+    let service = ForwardService::new_with_trusted_proxies(
+        routing_service,
+        TrustedProxies::parse("192.168.0.0/16"),
+    );
+
+    // the client rotates what it sends, the trusted proxy appends the address it saw.
+    let request = |forwarded_for: &str| {
+        let mut request = Request::builder()
+            .uri("/teste/orders")
+            .header("x-forwarded-for", forwarded_for)
+            .body(Body::empty())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([192, 168, 0, 7], 5000))));
+        request
+    };
+    let response = service
+        .handle(request("1.1.1.1, 203.0.113.9"))
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+
+    let response = service
+        .handle(request("2.2.2.2, 203.0.113.9"))
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::TOO_MANY_REQUESTS, response.status());
+
+    let response = service
+        .handle(request("2.2.2.2, 203.0.113.10"))
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+}
+
+#[tokio::test]
+async fn handle_with_api_key() {
+    let addr = start_streaming_upstream().await;
+
+    let application = Application {
+        auth: Some(serde_json::json!({"apiKey": {"query": "apikey", "header": "x-api-key"}})),
+        rate_limit: Some(serde_json::json!({
+            "limits": [{"requests": 1, "period": "minute", "by": "consumer"}]
+        })),
+        ..application(format!("http://{}", addr))
+    };
+    let consumers = vec![
+        Consumer {
+            id: 7,
+            name: String::from("mobile-app"),
+            rate_limit: None,
+            certificate_subject: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        },
+        Consumer {
+            id: 8,
+            name: String::from("partner"),
+            rate_limit: None,
+            certificate_subject: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        },
+    ];
+    let api_key = |id: i64, id_consumer: i64, key: &str| ConsumerApiKey {
+        id,
+        id_consumer,
+        key_hash: hash_api_key(key),
+        key_prefix: key[..8].to_owned(),
+        expires_at: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    };
+    let routing_service = routing_service_with_consumers(
+        vec![application],
+        Vec::new(),
+        Vec::new(),
+        consumers,
+        vec![
+            api_key(1, 7, "agk_mobile-app"),
+            api_key(2, 8, "agk_partner"),
+        ],
+    )
+    .await;
+    let service = ForwardService::new_with_rate_limiter(routing_service, RateLimiter::local());
+
+    // the identity sent by the client is dropped, the key never reaches the upstream.
+    let response = service
+        .handle(
+            Request::builder()
+                .uri("/teste/consumer?apikey=agk_mobile-app&page=2")
+                .header("x-consumer-id", "1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!("/consumer?page=2|7|mobile-app|", body);
+
+    // each consumer has its own limit.
+    let response = service
+        .handle(
+            Request::builder()
+                .uri("/teste/consumer")
+                .header("x-api-key", "agk_partner")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!("/consumer|8|partner|", body);
+
+    let response = service
+        .handle(
+            Request::builder()
+                .uri("/teste/consumer")
+                .header("x-api-key", "agk_partner")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::TOO_MANY_REQUESTS, response.status());
+
+    for request in [
+        Request::builder().uri("/teste/consumer"),
+        Request::builder()
+            .uri("/teste/consumer")
+            .header("x-api-key", "agk_unknown"),
+    ] {
+        let api_error = service
+            .handle(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap_err();
+        assert_eq!(401, api_error.status_code);
+        assert_eq!(FORWARD_ERR_UNAUTHORIZED.0, api_error.code);
+    }
+}
+
+#[tokio::test]
+async fn handle_with_jwt() {
+    let addr = start_streaming_upstream().await;
+
+    let application = Application {
+        auth: sealed_auth(serde_json::json!({
+            "apiKey": {},
+            "jwt": {
+                "algorithms": ["HS256"],
+                "secret": "a-secret-of-the-tests",
+                "requiredClaims": {"scope": "orders:read"},
+                "forwardClaims": {"sub": "x-user-id"}
+            }
+        })),
+        ..application(format!("http://{}", addr))
+    };
+    let consumers = vec![Consumer {
+        id: 7,
+        name: String::from("mobile-app"),
+        rate_limit: None,
+        certificate_subject: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }];
+    let api_keys = vec![ConsumerApiKey {
+        id: 1,
+        id_consumer: 7,
+        key_hash: hash_api_key("agk_mobile-app"),
+        key_prefix: String::from("agk_mobi"),
+        expires_at: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }];
+    let routing_service = routing_service_with_consumers(
+        vec![application],
+        Vec::new(),
+        Vec::new(),
+        consumers,
+        api_keys,
+    )
+    .await;
+    let service = ForwardService::new(routing_service);
+
+    let token = |scope: &str| {
+        jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &serde_json::json!({
+                "sub": "user-1",
+                "scope": scope,
+                "exp": jsonwebtoken::get_current_timestamp() + 60,
+            }),
+            &jsonwebtoken::EncodingKey::from_secret(b"a-secret-of-the-tests"),
+        )
+        .unwrap()
+    };
+    let request = |authorization: Option<String>| {
+        let mut request = Request::builder()
+            .uri("/teste/claims")
+            .header("x-user-id", "forged")
+            .header("x-auth-subject", "forged");
+        if let Some(authorization) = authorization {
+            request = request.header("authorization", authorization);
+        }
+        request.body(Body::empty()).unwrap()
+    };
+
+    // the claim replaces the header sent by the client, the token reaches the upstream.
+    let token_read = token("orders:read");
+    let response = service
+        .handle(request(Some(format!("Bearer {}", token_read))))
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(format!("user-1|user-1||Bearer {}", token_read), body);
+
+    let api_error = service
+        .handle(request(Some(format!("Bearer {}", token("orders:write")))))
+        .await
+        .unwrap_err();
+    assert_eq!(403, api_error.status_code);
+    assert_eq!(FORWARD_ERR_FORBIDDEN.0, api_error.code);
+
+    let api_error = service
+        .handle(request(Some(String::from("Bearer invalid"))))
+        .await
+        .unwrap_err();
+    assert_eq!(401, api_error.status_code);
+    assert_eq!(FORWARD_ERR_INVALID_TOKEN.0, api_error.code);
+
+    // without a bearer token the api key is checked.
+    let mut with_api_key = request(None);
+    with_api_key
+        .headers_mut()
+        .insert("x-api-key", HeaderValue::from_static("agk_mobile-app"));
+    let response = service.handle(with_api_key).await.unwrap();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!("||7|", body);
+
+    let api_error = service.handle(request(None)).await.unwrap_err();
+    assert_eq!(401, api_error.status_code);
+    assert_eq!(FORWARD_ERR_UNAUTHORIZED.0, api_error.code);
+}
+
+// active for the "opaque" tokens, with the rest of the token as subject.
+async fn start_introspection_server() -> SocketAddr {
+    let make_service = make_service_fn(|_| async {
+        Ok::<_, Infallible>(service_fn(|req: Request<Body>| async move {
+            let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+            let form: Vec<(String, String)> = serde_urlencoded::from_bytes(&body).unwrap();
+            let response = match form[0].1.strip_prefix("opaque-") {
+                Some(subject) => serde_json::json!({
+                    "active": true,
+                    "sub": subject,
+                    "scope": "orders:read",
+                }),
+                None => serde_json::json!({"active": false}),
+            };
+            Ok::<_, Infallible>(Response::new(Body::from(response.to_string())))
+        }))
+    });
+
+    let server = hyper::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+#[tokio::test]
+async fn handle_with_introspection() {
+    let addr = start_streaming_upstream().await;
+    let introspection_addr = start_introspection_server().await;
+
+    let application = Application {
+        auth: sealed_auth(serde_json::json!({
+            "introspection": {
+                "url": format!("http://{}/introspect", introspection_addr),
+                "clientId": "gateway",
+                "clientSecret": "secret",
+                "scopes": ["orders:read"]
+            }
+        })),
+        rate_limit: Some(serde_json::json!({
+            "limits": [{"requests": 1, "period": "minute", "by": "consumer"}]
+        })),
+        ..application(format!("http://{}", addr))
+    };
+    let service = ForwardService::new_with_rate_limiter(
+        routing_service(vec![application]).await,
+        RateLimiter::local(),
+    );
+    let request = |token: &str| {
+        Request::builder()
+            .uri("/teste/claims")
+            .header("authorization", format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    // the subject reaches the upstream and has its own limit.
+    let response = service.handle(request("opaque-user-1")).await.unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!("|user-1||Bearer opaque-user-1", body);
+
+    let response = service.handle(request("opaque-user-1")).await.unwrap();
+    assert_eq!(StatusCode::TOO_MANY_REQUESTS, response.status());
+    let response = service.handle(request("opaque-user-2")).await.unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+
+    let api_error = service.handle(request("revoked")).await.unwrap_err();
+    assert_eq!(401, api_error.status_code);
+    assert_eq!(FORWARD_ERR_INVALID_TOKEN.0, api_error.code);
+}
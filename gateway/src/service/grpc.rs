@@ -0,0 +1,76 @@
+#[cfg(test)]
+#[path = "grpc_test.rs"]
+mod grpc_test;
+
+use axum::http::Response;
+use hyper::{
+    header::{HeaderValue, CONTENT_TYPE, TE},
+    Body, HeaderMap, StatusCode,
+};
+use tonic::{Code, Status};
+
+use crate::exception::ApiError;
+
+// "application/grpc", "application/grpc+proto" and so on.
+pub fn is_grpc(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            let value = value.to_ascii_lowercase();
+            value == "application/grpc"
+                || value.starts_with("application/grpc+")
+                || value.starts_with("application/grpc;")
+        })
+}
+
+// te is hop-by-hop but grpc servers refuse requests without "te: trailers".
+pub fn add_grpc_headers(headers: &mut HeaderMap) {
+    headers.insert(TE, HeaderValue::from_static("trailers"));
+}
+
+pub fn grpc_code(status_code: u16) -> Code {
+    match StatusCode::from_u16(status_code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR) {
+        StatusCode::BAD_REQUEST => Code::InvalidArgument,
+        StatusCode::UNAUTHORIZED => Code::Unauthenticated,
+        StatusCode::FORBIDDEN => Code::PermissionDenied,
+        StatusCode::NOT_FOUND => Code::Unimplemented,
+        StatusCode::CONFLICT => Code::AlreadyExists,
+        StatusCode::PAYLOAD_TOO_LARGE | StatusCode::TOO_MANY_REQUESTS => Code::ResourceExhausted,
+        StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT => Code::DeadlineExceeded,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE => Code::Unavailable,
+        StatusCode::NOT_IMPLEMENTED => Code::Unimplemented,
+        StatusCode::INTERNAL_SERVER_ERROR => Code::Internal,
+        _ => Code::Unknown,
+    }
+}
+
+// grpc clients only understand a trailers-only response, the error goes in grpc-status and
+// grpc-message instead of a json body.
+pub fn grpc_error_response(api_error: &ApiError) -> Response<Body> {
+    let status = Status::new(
+        grpc_code(api_error.status_code),
+        format!("[{}] {}", api_error.code, api_error.message),
+    );
+    let (parts, _) = status.to_http().into_parts();
+    Response::from_parts(parts, Body::empty())
+}
+
+// google.rpc.Code to http, as grpc-gateway and envoy map them.
+pub fn http_status(code: Code) -> StatusCode {
+    match code {
+        Code::Ok => StatusCode::OK,
+        Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => {
+            StatusCode::BAD_REQUEST
+        }
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
@@ -0,0 +1,56 @@
+use crate::exception::{FORWARD_ERR_PATH_NOT_FOUND, FORWARD_ERR_UPSTREAM_TIMEOUT};
+
+use super::*;
+
+#[test]
+fn detect_grpc() {
+    let mut headers = HeaderMap::new();
+    assert!(!is_grpc(&headers));
+
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/grpc"));
+    assert!(is_grpc(&headers));
+
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/grpc+proto"),
+    );
+    assert!(is_grpc(&headers));
+
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/grpc-web"),
+    );
+    assert!(!is_grpc(&headers));
+}
+
+#[test]
+fn map_status_codes() {
+    assert_eq!(Code::InvalidArgument, grpc_code(400));
+    assert_eq!(Code::Unimplemented, grpc_code(404));
+    assert_eq!(Code::ResourceExhausted, grpc_code(413));
+    assert_eq!(Code::Unavailable, grpc_code(502));
+    assert_eq!(Code::Unavailable, grpc_code(503));
+    assert_eq!(Code::DeadlineExceeded, grpc_code(504));
+    assert_eq!(Code::Unknown, grpc_code(418));
+}
+
+#[test]
+fn error_response() {
+    let api_error = ApiError::new_with_status(StatusCode::NOT_FOUND, FORWARD_ERR_PATH_NOT_FOUND);
+    let response = grpc_error_response(&api_error);
+
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!("application/grpc", response.headers()[CONTENT_TYPE]);
+    assert_eq!("12", response.headers()["grpc-status"]);
+    assert_eq!(
+        "[FWD0002] Main path could not be found.",
+        percent_encoding::percent_decode(response.headers()["grpc-message"].as_bytes())
+            .decode_utf8()
+            .unwrap()
+    );
+
+    let api_error =
+        ApiError::new_with_status(StatusCode::GATEWAY_TIMEOUT, FORWARD_ERR_UPSTREAM_TIMEOUT);
+    let response = grpc_error_response(&api_error);
+    assert_eq!("4", response.headers()["grpc-status"]);
+}
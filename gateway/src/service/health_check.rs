@@ -0,0 +1,286 @@
+#[cfg(test)]
+#[path = "health_check_test.rs"]
+mod health_check_test;
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures::future;
+use hyper::{Body, Request};
+use serde::Serialize;
+
+use crate::{
+    config::HttpClients,
+    model::{ActiveHealthCheck, PassiveHealthCheck, UpstreamTls},
+};
+
+use super::{
+    Metrics, RoutingServiceTrait, GATEWAY_UPSTREAM_HEALTH_CHECKS,
+    GATEWAY_UPSTREAM_TARGET_EJECTIONS, GATEWAY_UPSTREAM_TARGET_HEALTHY,
+};
+
+// how often the due probes are looked for, the shortest interval of the active checks.
+const HEALTH_CHECK_TICK: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetHealthSnapshot {
+    pub upstream: String,
+    pub target: String,
+    pub healthy: bool,
+    pub weight: i32,
+    pub active_requests: usize,
+    // time in the current state.
+    pub since_ms: u128,
+}
+
+struct HealthState {
+    healthy: bool,
+    since: Instant,
+    probe_successes: i32,
+    probe_failures: i32,
+    traffic_failures: i32,
+    // an ejected target comes back at this time when nothing probes it.
+    readmit_at: Option<Instant>,
+}
+
+// health of a target, fed by the probes and by the live traffic. it outlives the balancer of
+// its upstream while the target stays in it.
+pub struct TargetHealth {
+    upstream: String,
+    url: String,
+    state: Mutex<HealthState>,
+}
+
+impl std::fmt::Debug for TargetHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TargetHealth({}, {})", self.url, self.is_healthy())
+    }
+}
+
+impl TargetHealth {
+    // targets are healthy until proven otherwise.
+    pub fn new(upstream: &str, url: &str) -> TargetHealth {
+        let health = TargetHealth {
+            upstream: upstream.to_owned(),
+            url: url.to_owned(),
+            state: Mutex::new(HealthState {
+                healthy: true,
+                since: Instant::now(),
+                probe_successes: 0,
+                probe_failures: 0,
+                traffic_failures: 0,
+                readmit_at: None,
+            }),
+        };
+        health.set_gauge(true);
+        health
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.state.lock().unwrap().healthy
+    }
+
+    // false while unhealthy, an ejection over brings the target back.
+    pub fn is_available(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if !state.healthy
+            && state
+                .readmit_at
+                .is_some_and(|readmit_at| Instant::now() >= readmit_at)
+        {
+            tracing::info!("target {} of {} readmitted", self.url, self.upstream);
+            self.transition(&mut state, true, None);
+        }
+        state.healthy
+    }
+
+    pub fn record_probe(&self, check: &ActiveHealthCheck, success: bool) {
+        let mut state = self.state.lock().unwrap();
+        if success {
+            state.probe_failures = 0;
+            state.probe_successes += 1;
+            if !state.healthy && state.probe_successes >= check.healthy_threshold() {
+                tracing::info!("target {} of {} is healthy again", self.url, self.upstream);
+                self.transition(&mut state, true, None);
+            }
+        } else {
+            state.probe_successes = 0;
+            state.probe_failures += 1;
+            if state.healthy && state.probe_failures >= check.unhealthy_threshold() {
+                tracing::warn!(
+                    "target {} of {} has failed {} probes, it's unhealthy",
+                    self.url,
+                    self.upstream,
+                    state.probe_failures
+                );
+                self.transition(&mut state, false, Some("active"));
+            }
+        }
+    }
+
+    // without probes the ejected target is readmitted after the ejection time.
+    pub fn record_traffic(&self, check: &PassiveHealthCheck, probed: bool, success: bool) {
+        let mut state = self.state.lock().unwrap();
+        if !state.healthy {
+            return;
+        }
+
+        if success {
+            state.traffic_failures = 0;
+        } else {
+            state.traffic_failures += 1;
+            if state.traffic_failures >= check.unhealthy_threshold() {
+                tracing::warn!(
+                    "target {} of {} has failed {} requests in a row, it's ejected",
+                    self.url,
+                    self.upstream,
+                    state.traffic_failures
+                );
+                self.transition(&mut state, false, Some("passive"));
+                if !probed {
+                    state.readmit_at = Some(Instant::now() + check.ejection());
+                }
+            }
+        }
+    }
+
+    pub fn snapshot(&self, weight: i32, active_requests: usize) -> TargetHealthSnapshot {
+        let state = self.state.lock().unwrap();
+        TargetHealthSnapshot {
+            upstream: self.upstream.clone(),
+            target: self.url.clone(),
+            healthy: state.healthy,
+            weight,
+            active_requests,
+            since_ms: state.since.elapsed().as_millis(),
+        }
+    }
+
+    // every state starts with its counters over.
+    fn transition(&self, state: &mut HealthState, healthy: bool, check: Option<&str>) {
+        *state = HealthState {
+            healthy,
+            since: Instant::now(),
+            probe_successes: 0,
+            probe_failures: 0,
+            traffic_failures: 0,
+            readmit_at: None,
+        };
+        self.set_gauge(healthy);
+        if let Some(check) = check {
+            Metrics::global().increment(
+                &GATEWAY_UPSTREAM_TARGET_EJECTIONS,
+                &[
+                    ("upstream", &self.upstream),
+                    ("target", &self.url),
+                    ("check", check),
+                ],
+            );
+        }
+    }
+
+    fn set_gauge(&self, healthy: bool) {
+        Metrics::global().set(
+            &GATEWAY_UPSTREAM_TARGET_HEALTHY,
+            &[("upstream", &self.upstream), ("target", &self.url)],
+            healthy as i64,
+        );
+    }
+}
+
+// probes the targets of the upstreams with an active health check, each one at its interval.
+pub struct HealthChecker {
+    routing_service: Arc<dyn RoutingServiceTrait + Send + Sync>,
+    clients: HttpClients,
+}
+
+impl HealthChecker {
+    pub fn new(routing_service: Arc<dyn RoutingServiceTrait + Send + Sync>) -> Self {
+        HealthChecker {
+            routing_service,
+            clients: HttpClients::config(),
+        }
+    }
+
+    pub fn start(health_checker: Arc<HealthChecker>) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(HEALTH_CHECK_TICK);
+            loop {
+                interval.tick().await;
+
+                // a slow round doesn't hold back the upstreams due next.
+                let health_checker = Arc::clone(&health_checker);
+                tokio::spawn(async move { health_checker.check().await });
+            }
+        });
+    }
+
+    // a round over the upstreams due for a probe, the routing table being the one in use.
+    pub async fn check(&self) {
+        let snapshot = self.routing_service.snapshot();
+        let probes = snapshot
+            .balancers()
+            .filter_map(|balancer| Some(balancer).zip(balancer.active_check_due()))
+            .flat_map(|(balancer, check)| {
+                balancer
+                    .target_healths()
+                    .map(move |health| self.probe(health, check, balancer.tls()))
+            });
+
+        future::join_all(probes).await;
+    }
+
+    async fn probe(
+        &self,
+        health: &TargetHealth,
+        check: &ActiveHealthCheck,
+        tls: Option<&UpstreamTls>,
+    ) {
+        let url = format!("{}{}", health.url().trim_end_matches('/'), check.path);
+        let request = match Request::get(&url).body(Body::empty()) {
+            Ok(request) => request,
+            Err(e) => {
+                tracing::error!("Error when building the probe {}: {}", url, e);
+                return;
+            }
+        };
+
+        // a tls that can't be loaded is logged and fails the probe.
+        let client = match self.clients.get_with_tls(None, false, tls) {
+            Ok(client) => client,
+            Err(_) => {
+                health.record_probe(check, false);
+                return;
+            }
+        };
+
+        let success = match tokio::time::timeout(check.timeout(), client.request(request)).await {
+            Ok(Ok(response)) => check.expects(response.status().as_u16()),
+            Ok(Err(e)) => {
+                tracing::debug!("probe {} has failed: {}", url, e);
+                false
+            }
+            Err(_) => {
+                tracing::debug!("probe {} has not answered in {:?}", url, check.timeout());
+                false
+            }
+        };
+
+        Metrics::global().increment(
+            &GATEWAY_UPSTREAM_HEALTH_CHECKS,
+            &[
+                ("upstream", &health.upstream),
+                ("target", health.url()),
+                ("result", if success { "success" } else { "failure" }),
+            ],
+        );
+        health.record_probe(check, success);
+    }
+}
@@ -0,0 +1,220 @@
+use std::{collections::HashMap, convert::Infallible, net::SocketAddr};
+
+use chrono::Utc;
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Response, StatusCode,
+};
+use serde_json::json;
+
+use crate::{
+    model::{HealthCheck, Upstream, UpstreamTarget, ALGORITHM_ROUND_ROBIN},
+    service::{MockRoutingServiceTrait, RoutingSnapshot, UpstreamBalancer},
+};
+
+use super::*;
+
+fn active(healthy_threshold: i32, unhealthy_threshold: i32) -> ActiveHealthCheck {
+    ActiveHealthCheck {
+        path: String::from("/health"),
+        interval_ms: None,
+        timeout_ms: None,
+        expected_statuses: None,
+        healthy_threshold: Some(healthy_threshold),
+        unhealthy_threshold: Some(unhealthy_threshold),
+    }
+}
+
+fn passive(unhealthy_threshold: i32, ejection_ms: i64) -> PassiveHealthCheck {
+    PassiveHealthCheck {
+        unhealthy_threshold: Some(unhealthy_threshold),
+        ejection_ms: Some(ejection_ms),
+    }
+}
+
+// "/health" answers 200 on the healthy upstream and 503 on the other.
+async fn start_upstream(healthy: bool) -> SocketAddr {
+    let make_service = make_service_fn(move |_| async move {
+        Ok::<_, Infallible>(service_fn(move |_| async move {
+            let status = if healthy {
+                StatusCode::OK
+            } else {
+                StatusCode::SERVICE_UNAVAILABLE
+            };
+            Ok::<_, Infallible>(
+                Response::builder()
+                    .status(status)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        }))
+    });
+
+    let server = hyper::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+fn target(id: i64, addr: SocketAddr) -> UpstreamTarget {
+    UpstreamTarget {
+        id,
+        id_upstream: 1,
+        url: format!("http://{}", addr),
+        weight: 100,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+#[test]
+fn parse_health_check() {
+    let health_check = HealthCheck::parse(&json!({
+        "active": {"path": "/health", "intervalMs": 5000},
+        "passive": {"unhealthyThreshold": 3}
+    }))
+    .unwrap();
+
+    let active = health_check.active.unwrap();
+    assert_eq!(Duration::from_secs(5), active.interval());
+    assert_eq!(Duration::from_secs(2), active.timeout());
+    assert!(active.expects(204));
+    assert!(!active.expects(500));
+    let passive = health_check.passive.unwrap();
+    assert_eq!(3, passive.unhealthy_threshold());
+    assert_eq!(Duration::from_secs(30), passive.ejection());
+
+    let field_errors = HealthCheck::validate(
+        &json!({"active": {"path": "health", "intervalMs": 100}}),
+        "upstream.healthCheck",
+    );
+    let fields: Vec<&str> = field_errors.iter().map(|e| e.field.as_str()).collect();
+    assert_eq!(
+        vec![
+            "upstream.healthCheck.active.path",
+            "upstream.healthCheck.active.intervalMs"
+        ],
+        fields
+    );
+}
+
+#[test]
+fn active_thresholds() {
+    let check = active(2, 3);
+    let health = TargetHealth::new("orders", "http://10.0.0.1:8080");
+
+    // a success in between starts the count over.
+    health.record_probe(&check, false);
+    health.record_probe(&check, false);
+    health.record_probe(&check, true);
+    health.record_probe(&check, false);
+    health.record_probe(&check, false);
+    assert!(health.is_healthy());
+    health.record_probe(&check, false);
+    assert!(!health.is_healthy());
+    assert!(!health.is_available());
+
+    health.record_probe(&check, true);
+    assert!(!health.is_healthy());
+    health.record_probe(&check, true);
+    assert!(health.is_healthy());
+}
+
+#[tokio::test]
+async fn passive_ejection() {
+    let check = passive(2, 50);
+    let health = TargetHealth::new("orders", "http://10.0.0.1:8080");
+
+    health.record_traffic(&check, false, false);
+    health.record_traffic(&check, false, true);
+    health.record_traffic(&check, false, false);
+    assert!(health.is_available());
+    health.record_traffic(&check, false, false);
+    assert!(!health.is_available());
+
+    // back after the ejection time.
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert!(health.is_available());
+    assert!(health.is_healthy());
+}
+
+#[tokio::test]
+async fn passive_ejection_with_probes() {
+    let health = TargetHealth::new("orders", "http://10.0.0.1:8080");
+
+    health.record_traffic(&passive(1, 10), true, false);
+    assert!(!health.is_available());
+
+    // only the probes bring it back.
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(!health.is_available());
+    health.record_probe(&active(1, 1), true);
+    assert!(health.is_available());
+}
+
+#[tokio::test]
+async fn check_targets() {
+    let healthy = start_upstream(true).await;
+    let unhealthy = start_upstream(false).await;
+
+    let upstream = Upstream {
+        id: 1,
+        name: String::from("orders"),
+        algorithm: String::from(ALGORITHM_ROUND_ROBIN),
+        hash_on: None,
+        hash_key: None,
+        health_check: Some(json!({"active": {"path": "/health", "unhealthyThreshold": 1}})),
+        credentials_type: None,
+        credentials: None,
+        tls: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    };
+    let balancers = UpstreamBalancer::build_all(
+        vec![upstream],
+        &[target(1, healthy), target(2, unhealthy)],
+        &HashMap::new(),
+    );
+    let snapshot = Arc::new(RoutingSnapshot::build(
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        balancers,
+    ));
+
+    let mut routing_service = MockRoutingServiceTrait::new();
+    routing_service
+        .expect_snapshot()
+        .returning(move || Arc::clone(&snapshot));
+    let routing_service = Arc::new(routing_service);
+    let health_checker = HealthChecker::new(routing_service.clone());
+
+    health_checker.check().await;
+
+    let healths: Vec<(String, bool)> = routing_service
+        .snapshot()
+        .balancers()
+        .flat_map(|balancer| balancer.health_snapshot())
+        .map(|health| (health.target, health.healthy))
+        .collect();
+    assert_eq!(
+        vec![
+            (format!("http://{}", healthy), true),
+            (format!("http://{}", unhealthy), false)
+        ],
+        healths
+    );
+
+    // the unhealthy target receives no requests.
+    let balancer = routing_service
+        .snapshot()
+        .balancers()
+        .next()
+        .cloned()
+        .unwrap();
+    assert!((0..3).all(|_| {
+        balancer.pick(&hyper::HeaderMap::new(), None).unwrap().url == format!("http://{}", healthy)
+    }));
+}
@@ -0,0 +1,291 @@
+#[cfg(test)]
+#[path = "jwt_auth_test.rs"]
+mod jwt_auth_test;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::http::Request;
+use hyper::{
+    header::{HeaderName, HeaderValue, ACCEPT, AUTHORIZATION},
+    Body, HeaderMap, StatusCode,
+};
+use jsonwebtoken::{decode, decode_header, jwk::Jwk, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use tokio::time::Instant;
+
+use crate::{
+    config::{HttpClient, HttpsClient},
+    exception::{
+        ApiError, FORWARD_ERR_FORBIDDEN, FORWARD_ERR_INTROSPECTION_FAILED,
+        FORWARD_ERR_INVALID_TOKEN,
+    },
+    model::{JwtAuth, JWT_ALGORITHM_ES256, JWT_ALGORITHM_HS256, JWT_ALGORITHM_RS256},
+};
+
+// a jwks that couldn't be loaded is tried again after this, and a token signed by an unknown
+// kid loads the jwks again at most this often.
+const JWKS_RETRY_INTERVAL: Duration = Duration::from_secs(10);
+const JWKS_FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct JwtKey {
+    kid: Option<String>,
+    key: DecodingKey,
+}
+
+struct LoadedKeys {
+    keys: Arc<Vec<JwtKey>>,
+    loaded_at: Instant,
+    // the pem public key and the secret never change.
+    reload_at: Option<Instant>,
+}
+
+#[derive(Deserialize)]
+struct Jwks {
+    keys: Vec<Value>,
+}
+
+// keys of the jwt policies by source. they outlive the routing snapshots, so a reload of the
+// routing table doesn't fetch every jwks again.
+pub struct JwtKeys {
+    client: HttpsClient,
+    keys: Mutex<HashMap<String, LoadedKeys>>,
+}
+
+impl Default for JwtKeys {
+    fn default() -> Self {
+        JwtKeys {
+            client: HttpClient::config(),
+            keys: Mutex::default(),
+        }
+    }
+}
+
+impl JwtKeys {
+    // the claims of a valid token. the error is the reason of the failure, "invalid" or
+    // "forbidden" when the token lacks a required claim.
+    pub async fn verify(
+        &self,
+        jwt: &JwtAuth,
+        token: &str,
+    ) -> Result<Map<String, Value>, &'static str> {
+        let header = decode_header(token).map_err(|_| "invalid")?;
+        if !jwt
+            .algorithms()
+            .into_iter()
+            .any(|algorithm| parse_algorithm(algorithm) == Some(header.alg))
+        {
+            return Err("invalid");
+        }
+
+        let mut validation = Validation::new(header.alg);
+        validation.leeway = jwt.clock_skew().as_secs();
+        validation.validate_nbf = true;
+        if !jwt.issuers.is_empty() {
+            validation.set_issuer(&jwt.issuers);
+        }
+        if !jwt.audiences.is_empty() {
+            validation.set_audience(&jwt.audiences);
+        }
+
+        let keys = self.keys(jwt, header.kid.as_deref()).await;
+        let claims = keys
+            .iter()
+            .filter(|key| match (&key.kid, &header.kid) {
+                (Some(kid), Some(token_kid)) => kid == token_kid,
+                _ => true,
+            })
+            .find_map(|key| decode::<Map<String, Value>>(token, &key.key, &validation).ok())
+            .ok_or("invalid")?
+            .claims;
+
+        if jwt
+            .required_claims
+            .iter()
+            .all(|(name, value)| has_claim(&claims, name, value))
+        {
+            Ok(claims)
+        } else {
+            Err("forbidden")
+        }
+    }
+
+    async fn keys(&self, jwt: &JwtAuth, kid: Option<&str>) -> Arc<Vec<JwtKey>> {
+        let source = key_source(jwt);
+        let now = Instant::now();
+        let cached = self.keys.lock().unwrap().get(&source).map(|loaded| {
+            let unknown_kid = kid.is_some_and(|kid| {
+                !loaded
+                    .keys
+                    .iter()
+                    .any(|key| key.kid.as_deref() == Some(kid))
+            });
+            let fresh = loaded.reload_at.is_none_or(|reload_at| reload_at > now)
+                && !(unknown_kid && loaded.loaded_at + JWKS_RETRY_INTERVAL <= now);
+            (Arc::clone(&loaded.keys), fresh)
+        });
+
+        let cached = match cached {
+            Some((keys, true)) => return keys,
+            cached => cached,
+        };
+
+        let (keys, reload_at) = match self.load(jwt).await {
+            Ok(keys) => {
+                let reload_at = (jwt.jwks_file.is_some() || jwt.jwks_url.is_some())
+                    .then(|| now + jwt.jwks_refresh());
+                (Arc::new(keys), reload_at)
+            }
+            // the previous keys are kept until the jwks can be loaded again.
+            Err(e) => {
+                tracing::error!("Error when loading the jwt keys: {}", e);
+                let keys = cached.map_or_else(Arc::default, |(keys, _)| keys);
+                (keys, Some(now + JWKS_RETRY_INTERVAL))
+            }
+        };
+
+        self.keys.lock().unwrap().insert(
+            source,
+            LoadedKeys {
+                keys: Arc::clone(&keys),
+                loaded_at: now,
+                reload_at,
+            },
+        );
+        keys
+    }
+
+    async fn load(&self, jwt: &JwtAuth) -> Result<Vec<JwtKey>, Box<dyn Error + Send + Sync>> {
+        if let Some(secret) = &jwt.secret {
+            return Ok(vec![JwtKey {
+                kid: None,
+                key: DecodingKey::from_secret(secret.as_bytes()),
+            }]);
+        }
+
+        if let Some(public_key) = &jwt.public_key {
+            let key = DecodingKey::from_rsa_pem(public_key.as_bytes())
+                .or_else(|_| DecodingKey::from_ec_pem(public_key.as_bytes()))?;
+            return Ok(vec![JwtKey { kid: None, key }]);
+        }
+
+        let jwks = match (&jwt.jwks_file, &jwt.jwks_url) {
+            (Some(jwks_file), _) => tokio::fs::read(jwks_file).await?,
+            (None, Some(jwks_url)) => self.fetch(jwks_url).await?,
+            (None, None) => return Ok(Vec::new()),
+        };
+
+        // a key of an unsupported type doesn't invalidate the others.
+        let jwks: Jwks = serde_json::from_slice(&jwks)?;
+        Ok(jwks
+            .keys
+            .into_iter()
+            .filter_map(|jwk| {
+                let jwk = serde_json::from_value::<Jwk>(jwk).ok()?;
+                match DecodingKey::from_jwk(&jwk) {
+                    Ok(key) => Some(JwtKey {
+                        kid: jwk.common.key_id,
+                        key,
+                    }),
+                    Err(e) => {
+                        tracing::warn!("Ignoring an invalid jwk: {}", e);
+                        None
+                    }
+                }
+            })
+            .collect())
+    }
+
+    async fn fetch(&self, jwks_url: &str) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let request = Request::get(jwks_url)
+            .header(ACCEPT, "application/json")
+            .body(Body::empty())?;
+        let response =
+            tokio::time::timeout(JWKS_FETCH_TIMEOUT, self.client.request(request)).await??;
+        if !response.status().is_success() {
+            return Err(format!("{} answered {}", jwks_url, response.status()).into());
+        }
+
+        Ok(hyper::body::to_bytes(response.into_body()).await?.to_vec())
+    }
+}
+
+// the token of an "authorization: bearer" header.
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    let value = headers.get(AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
+
+// opaque tokens don't even have a jwt header.
+pub fn is_jwt(token: &str) -> bool {
+    decode_header(token).is_ok()
+}
+
+pub fn token_error(reason: &str) -> ApiError {
+    match reason {
+        "forbidden" => ApiError::new_with_status(StatusCode::FORBIDDEN, FORWARD_ERR_FORBIDDEN),
+        "unavailable" => ApiError::new_with_status(
+            StatusCode::SERVICE_UNAVAILABLE,
+            FORWARD_ERR_INTROSPECTION_FAILED,
+        ),
+        _ => ApiError::new_with_status(StatusCode::UNAUTHORIZED, FORWARD_ERR_INVALID_TOKEN),
+    }
+}
+
+// the headers of the forwarded claims sent by the client are always dropped.
+pub fn add_claim_headers(
+    headers: &mut HeaderMap,
+    forward_claims: &BTreeMap<String, String>,
+    claims: Option<&Map<String, Value>>,
+) {
+    for (claim, header) in forward_claims {
+        headers.remove(header.as_str());
+
+        let value = match claims.and_then(|claims| claims.get(claim)) {
+            Some(Value::String(value)) => HeaderValue::from_str(value),
+            Some(value) => HeaderValue::from_str(&value.to_string()),
+            None => continue,
+        };
+        if let (Ok(name), Ok(value)) = (HeaderName::try_from(header.as_str()), value) {
+            headers.insert(name, value);
+        }
+    }
+}
+
+fn parse_algorithm(algorithm: &str) -> Option<Algorithm> {
+    match algorithm {
+        JWT_ALGORITHM_RS256 => Some(Algorithm::RS256),
+        JWT_ALGORITHM_ES256 => Some(Algorithm::ES256),
+        JWT_ALGORITHM_HS256 => Some(Algorithm::HS256),
+        _ => None,
+    }
+}
+
+fn key_source(jwt: &JwtAuth) -> String {
+    match (&jwt.jwks_file, &jwt.jwks_url, &jwt.public_key, &jwt.secret) {
+        (Some(jwks_file), ..) => format!("file:{}", jwks_file),
+        (_, Some(jwks_url), ..) => format!("url:{}", jwks_url),
+        (.., Some(public_key), _) => format!("pem:{}", public_key),
+        (.., Some(secret)) => format!("secret:{}", secret),
+        _ => String::new(),
+    }
+}
+
+pub(crate) fn has_claim(claims: &Map<String, Value>, name: &str, expected: &Value) -> bool {
+    match (claims.get(name), expected) {
+        (None, _) => false,
+        (Some(_), Value::Null) => true,
+        (Some(Value::Array(values)), expected) => values.contains(expected),
+        (Some(Value::String(value)), Value::String(expected)) => {
+            value.split_whitespace().any(|word| word == expected)
+        }
+        (Some(value), expected) => value == expected,
+    }
+}
@@ -0,0 +1,490 @@
+#[cfg(test)]
+#[path = "load_balancer_test.rs"]
+mod load_balancer_test;
+
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+
+use futures::StreamExt;
+use hyper::{header::COOKIE, Body, HeaderMap, StatusCode};
+use rand::Rng;
+
+use crate::{
+    exception::{ApiError, FORWARD_ERR_NO_UPSTREAM_TARGET},
+    model::{
+        ActiveHealthCheck, HealthCheck, Upstream, UpstreamTarget, UpstreamTls,
+        ALGORITHM_CONSISTENT_HASH, ALGORITHM_LEAST_CONNECTIONS, ALGORITHM_RANDOM_TWO_CHOICES,
+        ALGORITHM_WEIGHTED_ROUND_ROBIN, HASH_ON_COOKIE, HASH_ON_HEADER, HASH_ON_IP,
+    },
+};
+
+use super::{TargetHealth, TargetHealthSnapshot};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Algorithm {
+    RoundRobin,
+    WeightedRoundRobin,
+    LeastConnections,
+    RandomTwoChoices,
+    ConsistentHash,
+}
+
+#[derive(Debug)]
+struct BalancedTarget {
+    url: String,
+    weight: i32,
+    // requests in flight, released when their response is over.
+    active: AtomicUsize,
+    health: Arc<TargetHealth>,
+}
+
+// holds a request in flight on its target until it's dropped, the outcome of the request feeds
+// the passive health check of the target.
+#[derive(Debug)]
+pub struct ActiveRequest {
+    balancer: Arc<UpstreamBalancer>,
+    index: usize,
+}
+
+impl ActiveRequest {
+    // the target of the group the request was sent to.
+    pub fn target(&self) -> usize {
+        self.index
+    }
+
+    // transport errors, timeouts and server errors count as failures of the target.
+    pub fn record(&self, success: bool) {
+        if let Some(passive) = &self.balancer.health_check.passive {
+            let probed = self.balancer.health_check.active.is_some();
+            self.balancer.targets[self.index]
+                .health
+                .record_traffic(passive, probed, success);
+        }
+    }
+}
+
+impl Drop for ActiveRequest {
+    fn drop(&mut self) {
+        self.balancer.targets[self.index]
+            .active
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug)]
+pub struct Selection {
+    pub url: String,
+    pub active: ActiveRequest,
+}
+
+impl Selection {
+    // the target replaces the scheme and authority of the destination, its path is kept.
+    pub fn destination(&self, url_destination: &str) -> String {
+        let authority_start = url_destination.find("://").map_or(0, |index| index + 3);
+        let path = url_destination[authority_start..]
+            .find('/')
+            .map_or("", |index| &url_destination[authority_start + index..]);
+        format!("{}{}", self.url.trim_end_matches('/'), path)
+    }
+}
+
+// the targets of an upstream group and the state of its balancing strategy. it's kept across
+// routing reloads while the group doesn't change, so counters and connections carry on.
+#[derive(Debug)]
+pub struct UpstreamBalancer {
+    upstream: Upstream,
+    algorithm: Algorithm,
+    targets: Vec<BalancedTarget>,
+    next: AtomicUsize,
+    // current weights of the smooth weighted round-robin.
+    current_weights: Mutex<Vec<i64>>,
+    // sorted points of the targets, by hash.
+    ring: Vec<(u32, usize)>,
+    health_check: HealthCheck,
+    tls: Option<UpstreamTls>,
+    last_probe: Mutex<Option<Instant>>,
+}
+
+impl UpstreamBalancer {
+    // targets with no weight are drained, they receive no new requests. an invalid health check
+    // is ignored, every target is then taken as healthy, and an invalid tls falls back to the
+    // default one.
+    pub fn new(upstream: Upstream, targets: &[UpstreamTarget]) -> UpstreamBalancer {
+        let algorithm = match upstream.algorithm.as_str() {
+            ALGORITHM_WEIGHTED_ROUND_ROBIN => Algorithm::WeightedRoundRobin,
+            ALGORITHM_LEAST_CONNECTIONS => Algorithm::LeastConnections,
+            ALGORITHM_RANDOM_TWO_CHOICES => Algorithm::RandomTwoChoices,
+            ALGORITHM_CONSISTENT_HASH => Algorithm::ConsistentHash,
+            _ => Algorithm::RoundRobin,
+        };
+
+        let targets: Vec<BalancedTarget> = targets
+            .iter()
+            .filter(|target| target.id_upstream == upstream.id && target.weight > 0)
+            .map(|target| BalancedTarget {
+                url: target.url.clone(),
+                weight: target.weight,
+                active: AtomicUsize::new(0),
+                health: Arc::new(TargetHealth::new(&upstream.name, &target.url)),
+            })
+            .collect();
+
+        let health_check = match upstream.health_check.as_ref().map(HealthCheck::parse) {
+            Some(Ok(health_check)) => health_check,
+            Some(Err(e)) => {
+                tracing::error!(
+                    "Ignoring the health check of the upstream {}: {}",
+                    upstream.id,
+                    e
+                );
+                HealthCheck::default()
+            }
+            None => HealthCheck::default(),
+        };
+
+        let tls = match upstream.tls.as_ref().map(UpstreamTls::parse) {
+            Some(Ok(tls)) => Some(tls),
+            Some(Err(e)) => {
+                tracing::error!("Ignoring the tls of the upstream {}: {}", upstream.id, e);
+                None
+            }
+            None => None,
+        };
+
+        // a point of the ring for every unit of weight.
+        let mut ring = Vec::new();
+        if algorithm == Algorithm::ConsistentHash {
+            for (index, target) in targets.iter().enumerate() {
+                for node in 0..target.weight {
+                    ring.push((fnv1a(format!("{}#{}", target.url, node).as_bytes()), index));
+                }
+            }
+            ring.sort_unstable();
+        }
+
+        UpstreamBalancer {
+            upstream,
+            algorithm,
+            current_weights: Mutex::new(vec![0; targets.len()]),
+            targets,
+            next: AtomicUsize::new(0),
+            ring,
+            health_check,
+            tls,
+            last_probe: Mutex::new(None),
+        }
+    }
+
+    // balancers of the groups that haven't changed since the previous routing table are reused,
+    // the others keep the health of the targets they still have.
+    pub fn build_all(
+        upstreams: Vec<Upstream>,
+        targets: &[UpstreamTarget],
+        previous: &HashMap<i64, Arc<UpstreamBalancer>>,
+    ) -> HashMap<i64, Arc<UpstreamBalancer>> {
+        upstreams
+            .into_iter()
+            .map(|upstream| {
+                let mut balancer = UpstreamBalancer::new(upstream, targets);
+                let balancer = match previous.get(&balancer.upstream.id) {
+                    Some(previous) if previous.same_as(&balancer) => Arc::clone(previous),
+                    Some(previous) => {
+                        balancer.keep_health(previous);
+                        Arc::new(balancer)
+                    }
+                    None => Arc::new(balancer),
+                };
+                (balancer.upstream.id, balancer)
+            })
+            .collect()
+    }
+
+    pub fn id(&self) -> i64 {
+        self.upstream.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.upstream.name
+    }
+
+    pub fn upstream(&self) -> &Upstream {
+        &self.upstream
+    }
+
+    pub fn tls(&self) -> Option<&UpstreamTls> {
+        self.tls.as_ref()
+    }
+
+    // the upstream with an active health check, when its interval has passed since the last probe.
+    pub fn active_check_due(&self) -> Option<&ActiveHealthCheck> {
+        let active = self.health_check.active.as_ref()?;
+        let mut last_probe = self.last_probe.lock().unwrap();
+        if last_probe.is_some_and(|last_probe| last_probe.elapsed() < active.interval()) {
+            return None;
+        }
+        *last_probe = Some(Instant::now());
+        Some(active)
+    }
+
+    pub fn target_healths(&self) -> impl Iterator<Item = &TargetHealth> {
+        self.targets.iter().map(|target| target.health.as_ref())
+    }
+
+    pub fn health_snapshot(&self) -> Vec<TargetHealthSnapshot> {
+        self.targets
+            .iter()
+            .map(|target| {
+                target
+                    .health
+                    .snapshot(target.weight, target.active.load(Ordering::Relaxed))
+            })
+            .collect()
+    }
+
+    fn keep_health(&mut self, previous: &UpstreamBalancer) {
+        if self.upstream.name != previous.upstream.name {
+            return;
+        }
+        for target in &mut self.targets {
+            if let Some(previous) = previous
+                .targets
+                .iter()
+                .find(|other| other.url == target.url)
+            {
+                target.health = Arc::clone(&previous.health);
+            }
+        }
+    }
+
+    fn same_as(&self, other: &UpstreamBalancer) -> bool {
+        self.upstream.name == other.upstream.name
+            && self.upstream.algorithm == other.upstream.algorithm
+            && self.upstream.health_check == other.upstream.health_check
+            && self.upstream.hash_on == other.upstream.hash_on
+            && self.upstream.hash_key == other.upstream.hash_key
+            && self.upstream.credentials == other.upstream.credentials
+            && self.tls == other.tls
+            && self.targets.len() == other.targets.len()
+            && self
+                .targets
+                .iter()
+                .zip(&other.targets)
+                .all(|(target, other)| target.url == other.url && target.weight == other.weight)
+    }
+
+    // none when the group has no healthy target to send to.
+    pub fn pick(
+        self: &Arc<Self>,
+        headers: &HeaderMap,
+        client_ip: Option<IpAddr>,
+    ) -> Option<Selection> {
+        self.pick_excluding(headers, client_ip, &[])
+    }
+
+    // the excluded targets, the ones a request was already sent to, are only picked again when
+    // no other healthy target is left.
+    pub fn pick_excluding(
+        self: &Arc<Self>,
+        headers: &HeaderMap,
+        client_ip: Option<IpAddr>,
+        excluded: &[usize],
+    ) -> Option<Selection> {
+        let healthy: Vec<bool> = self
+            .targets
+            .iter()
+            .map(|target| target.health.is_available())
+            .collect();
+        let not_excluded: Vec<bool> = healthy
+            .iter()
+            .enumerate()
+            .map(|(index, healthy)| *healthy && !excluded.contains(&index))
+            .collect();
+        let available = if not_excluded.contains(&true) {
+            not_excluded
+        } else {
+            healthy
+        };
+        let candidates: Vec<usize> = (0..self.targets.len())
+            .filter(|index| available[*index])
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+
+        let index = match self.algorithm {
+            Algorithm::RoundRobin => self.round_robin(&candidates),
+            Algorithm::WeightedRoundRobin => self.weighted_round_robin(&candidates),
+            Algorithm::LeastConnections => self.least_connections(&candidates),
+            Algorithm::RandomTwoChoices => self.random_two_choices(&candidates),
+            // requests without the key are spread like the others.
+            Algorithm::ConsistentHash => match self.hash_key(headers, client_ip) {
+                Some(key) => self.consistent_hash(&key, &available),
+                None => self.round_robin(&candidates),
+            },
+        };
+
+        let target = &self.targets[index];
+        target.active.fetch_add(1, Ordering::Relaxed);
+        Some(Selection {
+            url: target.url.clone(),
+            active: ActiveRequest {
+                balancer: Arc::clone(self),
+                index,
+            },
+        })
+    }
+
+    fn round_robin(&self, candidates: &[usize]) -> usize {
+        candidates[self.next.fetch_add(1, Ordering::Relaxed) % candidates.len()]
+    }
+
+    // every target gains its weight, the heaviest one is picked and loses the total, so the
+    // heavier targets are interleaved with the others instead of being picked in a row.
+    fn weighted_round_robin(&self, candidates: &[usize]) -> usize {
+        let mut current_weights = self.current_weights.lock().unwrap();
+        let mut total = 0;
+        let mut picked = candidates[0];
+        for &index in candidates {
+            current_weights[index] += self.targets[index].weight as i64;
+            total += self.targets[index].weight as i64;
+            if current_weights[index] > current_weights[picked] {
+                picked = index;
+            }
+        }
+        current_weights[picked] -= total;
+        picked
+    }
+
+    // the fewest requests in flight for its weight, the ties go round.
+    fn least_connections(&self, candidates: &[usize]) -> usize {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        (0..candidates.len())
+            .map(|offset| candidates[(start + offset) % candidates.len()])
+            .reduce(|picked, index| {
+                if self.less_loaded(index, picked) {
+                    index
+                } else {
+                    picked
+                }
+            })
+            .unwrap_or(candidates[0])
+    }
+
+    fn random_two_choices(&self, candidates: &[usize]) -> usize {
+        let len = candidates.len();
+        if len == 1 {
+            return candidates[0];
+        }
+
+        let mut rng = rand::thread_rng();
+        let first = rng.gen_range(0..len);
+        let second = (first + rng.gen_range(1..len)) % len;
+        if self.less_loaded(candidates[second], candidates[first]) {
+            candidates[second]
+        } else {
+            candidates[first]
+        }
+    }
+
+    fn less_loaded(&self, index: usize, other: usize) -> bool {
+        let (target, other) = (&self.targets[index], &self.targets[other]);
+        let load = target.active.load(Ordering::Relaxed) * other.weight as usize;
+        let other_load = other.active.load(Ordering::Relaxed) * target.weight as usize;
+        load < other_load
+    }
+
+    // the first point of the ring after the hash of the key, the keys of an unhealthy target
+    // move to the next ones.
+    fn consistent_hash(&self, key: &str, available: &[bool]) -> usize {
+        let hash = fnv1a(key.as_bytes());
+        let position = self.ring.partition_point(|(point, _)| *point < hash);
+        (0..self.ring.len())
+            .map(|offset| self.ring[(position + offset) % self.ring.len()].1)
+            .find(|index| available[*index])
+            .unwrap_or_default()
+    }
+
+    fn hash_key(&self, headers: &HeaderMap, client_ip: Option<IpAddr>) -> Option<String> {
+        let hash_key = self.upstream.hash_key.as_deref().unwrap_or_default();
+        match self.upstream.hash_on.as_deref() {
+            Some(HASH_ON_HEADER) => headers
+                .get(hash_key)
+                .and_then(|value| value.to_str().ok())
+                .map(String::from),
+            Some(HASH_ON_COOKIE) => headers
+                .get_all(COOKIE)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(';'))
+                .filter_map(|cookie| cookie.trim().split_once('='))
+                .find(|(name, _)| *name == hash_key)
+                .map(|(_, value)| value.to_owned()),
+            Some(HASH_ON_IP) => client_ip.map(|ip| ip.to_string()),
+            _ => None,
+        }
+    }
+}
+
+// the destination of a request towards the target picked for it, unchanged without upstream.
+pub fn balance(
+    balancer: Option<&Arc<UpstreamBalancer>>,
+    url_destination: &str,
+    headers: &HeaderMap,
+    client_ip: Option<IpAddr>,
+) -> Result<(String, Option<ActiveRequest>), ApiError> {
+    let balancer = match balancer {
+        Some(balancer) => balancer,
+        None => return Ok((url_destination.to_owned(), None)),
+    };
+
+    let selection = balancer.pick(headers, client_ip).ok_or_else(|| {
+        tracing::error!("upstream {} has no target available", balancer.name());
+        ApiError::new_with_status(
+            StatusCode::SERVICE_UNAVAILABLE,
+            FORWARD_ERR_NO_UPSTREAM_TARGET,
+        )
+    })?;
+    Ok((
+        selection.destination(url_destination),
+        Some(selection.active),
+    ))
+}
+
+// the request stays in flight on its target until the response body is over.
+pub fn release_after(body: Body, active: Option<ActiveRequest>) -> Body {
+    match active {
+        Some(active) => Body::wrap_stream(body.map(move |chunk| {
+            let _active = &active;
+            chunk
+        })),
+        None => body,
+    }
+}
+
+// the same upstream group.
+impl PartialEq for UpstreamBalancer {
+    fn eq(&self, other: &Self) -> bool {
+        self.upstream.id == other.upstream.id
+    }
+}
+
+impl Eq for UpstreamBalancer {}
+
+// fnv-1a, mixed at the end so that keys differing in the last byte land far apart.
+fn fnv1a(bytes: &[u8]) -> u32 {
+    let mut hash = bytes.iter().fold(0x811c9dc5u32, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x01000193)
+    });
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85ebca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2ae35);
+    hash ^ (hash >> 16)
+}
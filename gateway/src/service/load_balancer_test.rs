@@ -0,0 +1,364 @@
+use chrono::Utc;
+use hyper::header::HeaderValue;
+
+use crate::model::{ALGORITHM_ROUND_ROBIN, DEFAULT_TARGET_WEIGHT};
+
+use super::*;
+
+fn upstream(algorithm: &str) -> Upstream {
+    Upstream {
+        id: 1,
+        name: String::from("orders"),
+        algorithm: String::from(algorithm),
+        hash_on: None,
+        hash_key: None,
+        health_check: None,
+        credentials_type: None,
+        credentials: None,
+        tls: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+fn target(id: i64, url: &str, weight: i32) -> UpstreamTarget {
+    UpstreamTarget {
+        id,
+        id_upstream: 1,
+        url: String::from(url),
+        weight,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+fn targets() -> Vec<UpstreamTarget> {
+    vec![
+        target(1, "http://10.0.0.1:8080", DEFAULT_TARGET_WEIGHT),
+        target(2, "http://10.0.0.2:8080", DEFAULT_TARGET_WEIGHT),
+        target(3, "http://10.0.0.3:8080", DEFAULT_TARGET_WEIGHT),
+    ]
+}
+
+fn pick(balancer: &Arc<UpstreamBalancer>) -> String {
+    balancer.pick(&HeaderMap::new(), None).unwrap().url
+}
+
+fn header(name: &'static str, value: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(name, HeaderValue::try_from(value).unwrap());
+    headers
+}
+
+fn hashing(hash_on: &str, hash_key: Option<&str>) -> Arc<UpstreamBalancer> {
+    let upstream = Upstream {
+        hash_on: Some(String::from(hash_on)),
+        hash_key: hash_key.map(String::from),
+        ..upstream(ALGORITHM_CONSISTENT_HASH)
+    };
+    Arc::new(UpstreamBalancer::new(upstream, &targets()))
+}
+
+#[test]
+fn round_robin() {
+    let balancer = Arc::new(UpstreamBalancer::new(
+        upstream(ALGORITHM_ROUND_ROBIN),
+        &targets(),
+    ));
+
+    let picked: Vec<String> = (0..4).map(|_| pick(&balancer)).collect();
+    assert_eq!(
+        vec![
+            "http://10.0.0.1:8080",
+            "http://10.0.0.2:8080",
+            "http://10.0.0.3:8080",
+            "http://10.0.0.1:8080"
+        ],
+        picked
+    );
+}
+
+#[test]
+fn weighted_round_robin() {
+    let balancer = Arc::new(UpstreamBalancer::new(
+        upstream(ALGORITHM_WEIGHTED_ROUND_ROBIN),
+        &[
+            target(1, "http://a", 5),
+            target(2, "http://b", 1),
+            target(3, "http://c", 1),
+        ],
+    ));
+
+    // the heavier target is interleaved with the others.
+    let picked: Vec<String> = (0..7).map(|_| pick(&balancer)).collect();
+    assert_eq!(
+        vec!["http://a", "http://a", "http://b", "http://a", "http://c", "http://a", "http://a"],
+        picked
+    );
+}
+
+#[test]
+fn skip_drained_targets() {
+    let balancer = Arc::new(UpstreamBalancer::new(
+        upstream(ALGORITHM_ROUND_ROBIN),
+        &[target(1, "http://a", 0), target(2, "http://b", 10)],
+    ));
+    assert!((0..3).all(|_| pick(&balancer) == "http://b"));
+
+    let balancer = Arc::new(UpstreamBalancer::new(
+        upstream(ALGORITHM_ROUND_ROBIN),
+        &[target(1, "http://a", 0)],
+    ));
+    assert!(balancer.pick(&HeaderMap::new(), None).is_none());
+
+    let response = balance(Some(&balancer), "http://orders/v1", &HeaderMap::new(), None);
+    let api_error = response.unwrap_err();
+    assert_eq!(FORWARD_ERR_NO_UPSTREAM_TARGET.0, api_error.code);
+    assert_eq!(503, api_error.status_code);
+}
+
+#[test]
+fn least_connections() {
+    let balancer = Arc::new(UpstreamBalancer::new(
+        upstream(ALGORITHM_LEAST_CONNECTIONS),
+        &[target(1, "http://a", 100), target(2, "http://b", 200)],
+    ));
+
+    // b has twice the weight, it takes two requests for each one of a.
+    let first = balancer.pick(&HeaderMap::new(), None).unwrap();
+    let second = balancer.pick(&HeaderMap::new(), None).unwrap();
+    let third = balancer.pick(&HeaderMap::new(), None).unwrap();
+    let mut picked = vec![first.url.clone(), second.url.clone(), third.url.clone()];
+    picked.sort();
+    assert_eq!(vec!["http://a", "http://b", "http://b"], picked);
+
+    // finished requests are released.
+    drop(first);
+    drop(second);
+    drop(third);
+    let held = balancer.pick(&HeaderMap::new(), None).unwrap();
+    assert_ne!(held.url, pick(&balancer));
+}
+
+#[test]
+fn random_two_choices() {
+    let balancer = Arc::new(UpstreamBalancer::new(
+        upstream(ALGORITHM_RANDOM_TWO_CHOICES),
+        &[target(1, "http://a", 100), target(2, "http://b", 100)],
+    ));
+
+    let held = balancer.pick(&HeaderMap::new(), None).unwrap();
+    let other = if held.url == "http://a" {
+        "http://b"
+    } else {
+        "http://a"
+    };
+
+    // with two targets both are compared, the idle one always wins.
+    assert!((0..10).all(|_| pick(&balancer) == other));
+}
+
+#[test]
+fn consistent_hash_on_header() {
+    let balancer = hashing(HASH_ON_HEADER, Some("x-user"));
+
+    let picked = balancer.pick(&header("x-user", "42"), None).unwrap().url;
+    assert!((0..10).all(|_| balancer.pick(&header("x-user", "42"), None).unwrap().url == picked));
+
+    // the keys are spread over the targets.
+    let mut urls: Vec<String> = (0..100)
+        .map(|user| {
+            balancer
+                .pick(&header("x-user", &user.to_string()), None)
+                .unwrap()
+                .url
+        })
+        .collect();
+    urls.sort();
+    urls.dedup();
+    assert_eq!(3, urls.len());
+
+    // requests without the key still go somewhere.
+    assert!(balancer.pick(&HeaderMap::new(), None).is_some());
+}
+
+#[test]
+fn consistent_hash_keeps_keys_of_remaining_targets() {
+    let balancer = hashing(HASH_ON_HEADER, Some("x-user"));
+    let upstream = Upstream {
+        hash_on: Some(String::from(HASH_ON_HEADER)),
+        hash_key: Some(String::from("x-user")),
+        ..upstream(ALGORITHM_CONSISTENT_HASH)
+    };
+    let shrunk = Arc::new(UpstreamBalancer::new(upstream, &targets()[..2]));
+
+    for user in 0..100 {
+        let headers = header("x-user", &user.to_string());
+        let before = balancer.pick(&headers, None).unwrap().url;
+        if before != "http://10.0.0.3:8080" {
+            assert_eq!(before, shrunk.pick(&headers, None).unwrap().url);
+        }
+    }
+}
+
+#[test]
+fn consistent_hash_on_cookie_and_ip() {
+    let balancer = hashing(HASH_ON_COOKIE, Some("session"));
+    let cookie = |session: &str| header("cookie", &format!("theme=dark; session={}", session));
+    let picked = balancer.pick(&cookie("abc"), None).unwrap().url;
+    assert!((0..10).all(|_| balancer.pick(&cookie("abc"), None).unwrap().url == picked));
+
+    let balancer = hashing(HASH_ON_IP, None);
+    let client_ip = Some("203.0.113.9".parse().unwrap());
+    let picked = balancer.pick(&HeaderMap::new(), client_ip).unwrap().url;
+    assert!((0..10).all(|_| balancer.pick(&HeaderMap::new(), client_ip).unwrap().url == picked));
+}
+
+#[test]
+fn skip_excluded_targets() {
+    let balancer = Arc::new(UpstreamBalancer::new(
+        upstream(ALGORITHM_ROUND_ROBIN),
+        &targets(),
+    ));
+    let pick_excluding = |excluded: &[usize]| {
+        let selection = balancer
+            .pick_excluding(&HeaderMap::new(), None, excluded)
+            .unwrap();
+        (selection.url, selection.active.target())
+    };
+
+    assert_eq!(
+        (String::from("http://10.0.0.2:8080"), 1),
+        pick_excluding(&[0])
+    );
+    assert_eq!(
+        (String::from("http://10.0.0.3:8080"), 2),
+        pick_excluding(&[0, 1])
+    );
+
+    // every target tried, they can all be picked again.
+    assert!(balancer
+        .pick_excluding(&HeaderMap::new(), None, &[0, 1, 2])
+        .is_some());
+
+    // the keys of an excluded target move to the next one, like for an unhealthy one.
+    let balancer = hashing(HASH_ON_HEADER, Some("x-customer"));
+    let headers = header("x-customer", "42");
+    let first = balancer.pick(&headers, None).unwrap().active.target();
+    let next = balancer
+        .pick_excluding(&headers, None, &[first])
+        .unwrap()
+        .active
+        .target();
+    assert_ne!(first, next);
+}
+
+#[test]
+fn replace_destination_authority() {
+    let balancer = Arc::new(UpstreamBalancer::new(
+        upstream(ALGORITHM_ROUND_ROBIN),
+        &targets(),
+    ));
+
+    let (url_destination, active) = balance(
+        Some(&balancer),
+        "http://orders.internal/api/v1",
+        &HeaderMap::new(),
+        None,
+    )
+    .unwrap();
+    assert_eq!("http://10.0.0.1:8080/api/v1", url_destination);
+    assert!(active.is_some());
+
+    let (url_destination, _) = balance(
+        Some(&balancer),
+        "http://orders.internal",
+        &HeaderMap::new(),
+        None,
+    )
+    .unwrap();
+    assert_eq!("http://10.0.0.2:8080", url_destination);
+
+    let (url_destination, active) =
+        balance(None, "http://orders.internal/api", &HeaderMap::new(), None).unwrap();
+    assert_eq!("http://orders.internal/api", url_destination);
+    assert!(active.is_none());
+}
+
+#[test]
+fn keep_unchanged_balancers() {
+    let first = UpstreamBalancer::build_all(
+        vec![upstream(ALGORITHM_ROUND_ROBIN)],
+        &targets(),
+        &HashMap::new(),
+    );
+    pick(&first[&1]);
+
+    let unchanged =
+        UpstreamBalancer::build_all(vec![upstream(ALGORITHM_ROUND_ROBIN)], &targets(), &first);
+    assert!(Arc::ptr_eq(&first[&1], &unchanged[&1]));
+    // the rotation carries on.
+    assert_eq!("http://10.0.0.2:8080", pick(&unchanged[&1]));
+
+    let changed = UpstreamBalancer::build_all(
+        vec![upstream(ALGORITHM_ROUND_ROBIN)],
+        &targets()[..2],
+        &first,
+    );
+    assert!(!Arc::ptr_eq(&first[&1], &changed[&1]));
+}
+
+#[test]
+fn skip_unhealthy_targets() {
+    let upstream = Upstream {
+        health_check: Some(serde_json::json!({"passive": {"unhealthyThreshold": 1}})),
+        ..upstream(ALGORITHM_ROUND_ROBIN)
+    };
+    let balancer = Arc::new(UpstreamBalancer::new(
+        upstream,
+        &[target(1, "http://a", 100), target(2, "http://b", 100)],
+    ));
+
+    // a failed request ejects its target.
+    let failed = balancer.pick(&HeaderMap::new(), None).unwrap();
+    assert_eq!("http://a", failed.url);
+    failed.active.record(false);
+    assert!((0..3).all(|_| pick(&balancer) == "http://b"));
+
+    balancer
+        .pick(&HeaderMap::new(), None)
+        .unwrap()
+        .active
+        .record(false);
+    assert!(balancer.pick(&HeaderMap::new(), None).is_none());
+
+    let healths: Vec<bool> = balancer
+        .health_snapshot()
+        .iter()
+        .map(|health| health.healthy)
+        .collect();
+    assert_eq!(vec![false, false], healths);
+}
+
+#[test]
+fn keep_health_of_remaining_targets() {
+    let upstream = || Upstream {
+        health_check: Some(serde_json::json!({"passive": {"unhealthyThreshold": 1}})),
+        ..upstream(ALGORITHM_ROUND_ROBIN)
+    };
+    let first = UpstreamBalancer::build_all(vec![upstream()], &targets(), &HashMap::new());
+    first[&1]
+        .pick(&HeaderMap::new(), None)
+        .unwrap()
+        .active
+        .record(false);
+
+    let changed = UpstreamBalancer::build_all(vec![upstream()], &targets()[..2], &first);
+    assert!(!Arc::ptr_eq(&first[&1], &changed[&1]));
+    let healths: Vec<bool> = changed[&1]
+        .health_snapshot()
+        .iter()
+        .map(|health| health.healthy)
+        .collect();
+    assert_eq!(vec![false, true], healths);
+}
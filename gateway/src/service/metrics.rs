@@ -0,0 +1,231 @@
+#[cfg(test)]
+#[path = "metrics_test.rs"]
+mod metrics_test;
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{Mutex, OnceLock},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+}
+
+pub struct Metric {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub kind: MetricKind,
+}
+
+pub const GATEWAY_REQUESTS: Metric = Metric {
+    name: "gateway_requests_total",
+    help: "Requests handled by the gateway.",
+    kind: MetricKind::Counter,
+};
+
+pub const GATEWAY_WEBSOCKET_CONNECTIONS: Metric = Metric {
+    name: "gateway_websocket_connections_total",
+    help: "Websocket connections upgraded by the gateway.",
+    kind: MetricKind::Counter,
+};
+
+pub const GATEWAY_WEBSOCKET_ACTIVE_CONNECTIONS: Metric = Metric {
+    name: "gateway_websocket_active_connections",
+    help: "Websocket connections currently open.",
+    kind: MetricKind::Gauge,
+};
+
+pub const GATEWAY_WEBSOCKET_REJECTED: Metric = Metric {
+    name: "gateway_websocket_rejected_total",
+    help: "Websocket connections rejected by the limit of the application.",
+    kind: MetricKind::Counter,
+};
+
+pub const GATEWAY_WEBSOCKET_BYTES: Metric = Metric {
+    name: "gateway_websocket_bytes_total",
+    help: "Bytes spliced between websocket clients and upstreams.",
+    kind: MetricKind::Counter,
+};
+
+pub const GATEWAY_UPSTREAM_RETRIES: Metric = Metric {
+    name: "gateway_upstream_retries_total",
+    help: "Requests sent again to an upstream, by reason of the failed attempt.",
+    kind: MetricKind::Counter,
+};
+
+pub const GATEWAY_RETRY_BUDGET_EXHAUSTED: Metric = Metric {
+    name: "gateway_retry_budget_exhausted_total",
+    help: "Retries refused because the application has spent its retry budget.",
+    kind: MetricKind::Counter,
+};
+
+pub const GATEWAY_CIRCUIT_BREAKER_STATE: Metric = Metric {
+    name: "gateway_circuit_breaker_state",
+    help: "State of the circuit breaker of an upstream: 0 closed, 1 half open, 2 open.",
+    kind: MetricKind::Gauge,
+};
+
+pub const GATEWAY_CIRCUIT_BREAKER_REJECTED: Metric = Metric {
+    name: "gateway_circuit_breaker_rejected_total",
+    help: "Requests not sent because the circuit of their upstream is open.",
+    kind: MetricKind::Counter,
+};
+
+pub const GATEWAY_UPSTREAM_TARGET_HEALTHY: Metric = Metric {
+    name: "gateway_upstream_target_healthy",
+    help: "Health of an upstream target: 1 healthy, 0 unhealthy.",
+    kind: MetricKind::Gauge,
+};
+
+pub const GATEWAY_UPSTREAM_HEALTH_CHECKS: Metric = Metric {
+    name: "gateway_upstream_health_checks_total",
+    help: "Active health check probes sent to the upstream targets.",
+    kind: MetricKind::Counter,
+};
+
+pub const GATEWAY_UPSTREAM_TARGET_EJECTIONS: Metric = Metric {
+    name: "gateway_upstream_target_ejections_total",
+    help: "Upstream targets taken out of the load balancing by a health check.",
+    kind: MetricKind::Counter,
+};
+
+pub const GATEWAY_RATE_LIMITED: Metric = Metric {
+    name: "gateway_rate_limited_total",
+    help: "Requests rejected because they exceed one of their rate limits.",
+    kind: MetricKind::Counter,
+};
+
+pub const GATEWAY_RATE_LIMIT_FALLBACKS: Metric = Metric {
+    name: "gateway_rate_limit_fallbacks_total",
+    help: "Rate limits counted locally because redis could not be reached.",
+    kind: MetricKind::Counter,
+};
+
+pub const GATEWAY_CONSUMER_REQUESTS: Metric = Metric {
+    name: "gateway_consumer_requests_total",
+    help: "Requests of authenticated consumers.",
+    kind: MetricKind::Counter,
+};
+
+pub const GATEWAY_AUTH_FAILURES: Metric = Metric {
+    name: "gateway_auth_failures_total",
+    help: "Requests rejected because their api key is missing or invalid.",
+    kind: MetricKind::Counter,
+};
+
+pub const GATEWAY_CERTIFICATE_EXPIRY_SECONDS: Metric = Metric {
+    name: "gateway_certificate_expiry_seconds",
+    help: "Seconds until a certificate served by the gateway expires, negative once expired.",
+    kind: MetricKind::Gauge,
+};
+
+pub const GATEWAY_CERTIFICATE_EXPIRING: Metric = Metric {
+    name: "gateway_certificate_expiring",
+    help: "Certificates within the expiry warning period: 1 expiring or expired, 0 otherwise.",
+    kind: MetricKind::Gauge,
+};
+
+type Labels = Vec<(&'static str, String)>;
+
+struct Family {
+    help: &'static str,
+    kind: MetricKind,
+    series: BTreeMap<Labels, i64>,
+}
+
+// in memory registry rendered in the prometheus text format by /api/metrics.
+#[derive(Default)]
+pub struct Metrics {
+    families: Mutex<BTreeMap<&'static str, Family>>,
+}
+
+impl Metrics {
+    pub fn global() -> &'static Metrics {
+        static METRICS: OnceLock<Metrics> = OnceLock::new();
+        METRICS.get_or_init(Metrics::default)
+    }
+
+    pub fn increment(&self, metric: &Metric, labels: &[(&'static str, &str)]) {
+        self.add(metric, labels, 1);
+    }
+
+    pub fn add(&self, metric: &Metric, labels: &[(&'static str, &str)], value: i64) {
+        self.update(metric, labels, |current| *current += value);
+    }
+
+    pub fn set(&self, metric: &Metric, labels: &[(&'static str, &str)], value: i64) {
+        self.update(metric, labels, |current| *current = value);
+    }
+
+    pub fn get(&self, metric: &Metric, labels: &[(&'static str, &str)]) -> i64 {
+        let families = self.families.lock().unwrap();
+        families
+            .get(metric.name)
+            .and_then(|family| family.series.get(&owned_labels(labels)))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    // drops a series that no longer exists, like the gauge of a removed certificate.
+    pub fn remove(&self, metric: &Metric, labels: &[(&'static str, &str)]) {
+        let mut families = self.families.lock().unwrap();
+        if let Some(family) = families.get_mut(metric.name) {
+            family.series.remove(&owned_labels(labels));
+        }
+    }
+
+    fn update<F: FnOnce(&mut i64)>(&self, metric: &Metric, labels: &[(&'static str, &str)], f: F) {
+        let mut families = self.families.lock().unwrap();
+        let family = families.entry(metric.name).or_insert_with(|| Family {
+            help: metric.help,
+            kind: metric.kind,
+            series: BTreeMap::new(),
+        });
+        f(family.series.entry(owned_labels(labels)).or_default());
+    }
+
+    pub fn render(&self) -> String {
+        let families = self.families.lock().unwrap();
+        let mut output = String::new();
+
+        for (name, family) in families.iter() {
+            let kind = match family.kind {
+                MetricKind::Counter => "counter",
+                MetricKind::Gauge => "gauge",
+            };
+            let _ = writeln!(output, "# HELP {} {}", name, family.help);
+            let _ = writeln!(output, "# TYPE {} {}", name, kind);
+
+            for (labels, value) in &family.series {
+                if labels.is_empty() {
+                    let _ = writeln!(output, "{} {}", name, value);
+                } else {
+                    let labels: Vec<String> = labels
+                        .iter()
+                        .map(|(label, value)| format!("{}=\"{}\"", label, escape(value)))
+                        .collect();
+                    let _ = writeln!(output, "{}{{{}}} {}", name, labels.join(","), value);
+                }
+            }
+        }
+
+        output
+    }
+}
+
+fn owned_labels(labels: &[(&'static str, &str)]) -> Labels {
+    labels
+        .iter()
+        .map(|(label, value)| (*label, (*value).to_owned()))
+        .collect()
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,72 @@
+use super::*;
+
+#[test]
+fn render() {
+    let metrics = Metrics::default();
+    metrics.increment(
+        &GATEWAY_REQUESTS,
+        &[("application", "/orders"), ("status", "200")],
+    );
+    metrics.increment(
+        &GATEWAY_REQUESTS,
+        &[("application", "/orders"), ("status", "200")],
+    );
+    metrics.increment(
+        &GATEWAY_REQUESTS,
+        &[("application", "/us\"ers"), ("status", "404")],
+    );
+    metrics.add(
+        &GATEWAY_WEBSOCKET_ACTIVE_CONNECTIONS,
+        &[("application", "/orders")],
+        1,
+    );
+    metrics.add(
+        &GATEWAY_WEBSOCKET_ACTIVE_CONNECTIONS,
+        &[("application", "/orders")],
+        -1,
+    );
+
+    assert_eq!(
+        2,
+        metrics.get(
+            &GATEWAY_REQUESTS,
+            &[("application", "/orders"), ("status", "200")]
+        )
+    );
+    assert_eq!(
+        "# HELP gateway_requests_total Requests handled by the gateway.\n\
+         # TYPE gateway_requests_total counter\n\
+         gateway_requests_total{application=\"/orders\",status=\"200\"} 2\n\
+         gateway_requests_total{application=\"/us\\\"ers\",status=\"404\"} 1\n\
+         # HELP gateway_websocket_active_connections Websocket connections currently open.\n\
+         # TYPE gateway_websocket_active_connections gauge\n\
+         gateway_websocket_active_connections{application=\"/orders\"} 0\n",
+        metrics.render()
+    );
+}
+
+#[test]
+fn remove() {
+    let metrics = Metrics::default();
+    metrics.set(
+        &GATEWAY_CERTIFICATE_EXPIRING,
+        &[("source", "database"), ("name", "orders")],
+        1,
+    );
+    metrics.set(
+        &GATEWAY_CERTIFICATE_EXPIRING,
+        &[("source", "database"), ("name", "users")],
+        0,
+    );
+    metrics.remove(
+        &GATEWAY_CERTIFICATE_EXPIRING,
+        &[("source", "database"), ("name", "orders")],
+    );
+
+    assert_eq!(
+        "# HELP gateway_certificate_expiring Certificates within the expiry warning period: 1 expiring or expired, 0 otherwise.\n\
+         # TYPE gateway_certificate_expiring gauge\n\
+         gateway_certificate_expiring{source=\"database\",name=\"users\"} 0\n",
+        metrics.render()
+    );
+}
@@ -1,5 +1,55 @@
+mod application_orchestration_service;
+mod application_orchestration_route_service;
+mod application_route_service;
 mod application_service;
+mod application_workflow_service;
+mod body_buffer;
+mod certificate_store;
+mod circuit_breaker;
+mod consumer_auth;
+mod credential_injector;
 mod forward_service;
+mod grpc;
+mod health_check;
+mod jwt_auth;
+mod load_balancer;
+mod metrics;
+mod orchestration_service;
+mod path_matcher;
+mod proxy_headers;
+mod rate_limiter;
+mod retry;
+mod routing_service;
+mod streaming;
+mod token_introspection;
+mod transcoder;
+mod transcoding_service;
+mod websocket;
 
+pub use application_orchestration_service::*;
+pub use application_orchestration_route_service::*;
+pub use application_route_service::*;
 pub use application_service::*;
-pub use forward_service::*;
\ No newline at end of file
+pub use application_workflow_service::*;
+pub use body_buffer::*;
+pub use certificate_store::*;
+pub use circuit_breaker::*;
+pub use consumer_auth::*;
+pub use credential_injector::*;
+pub use forward_service::*;
+pub use grpc::*;
+pub use health_check::*;
+pub use jwt_auth::*;
+pub use load_balancer::*;
+pub use metrics::*;
+pub use orchestration_service::*;
+pub use path_matcher::*;
+pub use proxy_headers::*;
+pub use rate_limiter::*;
+pub use retry::*;
+pub use routing_service::*;
+pub use streaming::*;
+pub use token_introspection::*;
+pub use transcoder::*;
+pub use transcoding_service::*;
+pub use websocket::*;
\ No newline at end of file
@@ -0,0 +1,492 @@
+#[cfg(test)]
+#[path = "orchestration_service_test.rs"]
+mod orchestration_service_test;
+
+use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
+
+use axum::{
+    async_trait,
+    extract::ConnectInfo,
+    http::{
+        header::{
+            HeaderName, HeaderValue, ACCEPT_ENCODING, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE,
+            COOKIE, PROXY_AUTHORIZATION,
+        },
+        request::Parts,
+        Request, Response,
+    },
+};
+use futures::future;
+use hyper::{body::Bytes, header::HOST, Body, StatusCode};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde_json::{Map, Value};
+
+use crate::{
+    config::{HttpClients, HttpsClient},
+    exception::{
+        ApiError, ApiErrorCode, ApiFieldError, FORWARD_ERR_ORCHESTRATION_FAILED,
+        FORWARD_ERR_ORCHESTRATION_TIMEOUT, TEMPLATE_ERR_INVALID_HEADER,
+        TEMPLATE_ERR_REQUEST_REFERENCE, TEMPLATE_ERR_RESPONSE_REFERENCE,
+    },
+    model::{
+        CircuitBreakerPolicy, FAILURE_POLICY_ERROR, FAILURE_POLICY_FALLBACK, FAILURE_POLICY_OMIT,
+    },
+    template::TEMPLATE_REQUEST,
+};
+
+use super::{
+    balance, buffer_body, circuit_open_error, max_buffered_body_size, proxy_idle_timeout,
+    remove_hop_by_hop_headers, upstream_key, ActiveRequest, CircuitBreakers, CredentialInjector,
+    ForwardService, OrchestrationStep, RoutingOrchestration, UpstreamBalancer,
+};
+
+// not copied from the incoming request, the step has to set them in its headers. the steps are
+// read as json, the credentials of the client aren't meant for every upstream and the length
+// is the one of the step's body.
+const STEP_STRIPPED_HEADERS: [HeaderName; 6] = [
+    HOST,
+    ACCEPT_ENCODING,
+    CONTENT_LENGTH,
+    AUTHORIZATION,
+    PROXY_AUTHORIZATION,
+    COOKIE,
+];
+
+// values rendered into urls keep only the unreserved characters.
+const URL_VALUE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+#[async_trait]
+pub trait OrchestrationServiceTrait {
+    async fn execute(
+        &self,
+        orchestration: Arc<RoutingOrchestration>,
+        path_params: HashMap<String, String>,
+        req: Request<Body>,
+    ) -> Result<Response<Body>, ApiError>;
+}
+
+pub struct OrchestrationService {
+    clients: Arc<HttpClients>,
+    credential_injector: Arc<CredentialInjector>,
+    circuit_breakers: Arc<CircuitBreakers>,
+    max_body_size: usize,
+    step_timeout: Duration,
+}
+
+// what templates can reference: "request.path.id", "request.query.page",
+// "request.headers.x-customer" and "<response_key>.field.0.name" of the previous steps.
+struct OrchestrationContext {
+    parts: Parts,
+    body: Bytes,
+    query: Vec<(String, String)>,
+    path_params: HashMap<String, String>,
+    responses: Map<String, Value>,
+}
+
+impl OrchestrationContext {
+    fn resolve(&self, reference: &[String]) -> Result<String, ApiError> {
+        // a missing request value is a client error, a missing response value is an upstream one.
+        if reference[0] == TEMPLATE_REQUEST {
+            self.resolve_request(reference).ok_or_else(|| {
+                unresolved(
+                    StatusCode::BAD_REQUEST,
+                    TEMPLATE_ERR_REQUEST_REFERENCE,
+                    reference,
+                )
+            })
+        } else {
+            self.resolve_response(reference).ok_or_else(|| {
+                unresolved(
+                    StatusCode::BAD_GATEWAY,
+                    TEMPLATE_ERR_RESPONSE_REFERENCE,
+                    reference,
+                )
+            })
+        }
+    }
+
+    fn resolve_request(&self, reference: &[String]) -> Option<String> {
+        match reference {
+            [_, source, name] if source == "path" => self.path_params.get(name).cloned(),
+            [_, source, name] if source == "query" => self
+                .query
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone()),
+            [_, source, name] if source == "headers" => self
+                .parts
+                .headers
+                .get(name.as_str())
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_owned()),
+            _ => None,
+        }
+    }
+
+    fn resolve_response(&self, reference: &[String]) -> Option<String> {
+        let mut value = self.responses.get(&reference[0])?;
+        for segment in &reference[1..] {
+            value = match value {
+                Value::Object(fields) => fields.get(segment)?,
+                Value::Array(elements) => elements.get(segment.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+
+        match value {
+            Value::Null => None,
+            Value::String(value) => Some(value.clone()),
+            value => Some(value.to_string()),
+        }
+    }
+}
+
+pub(crate) fn unresolved(
+    status: StatusCode,
+    api_error_code: ApiErrorCode,
+    reference: &[String],
+) -> ApiError {
+    let field_error = ApiFieldError::new(
+        ApiErrorCode(api_error_code.0, api_error_code.1),
+        reference.join("."),
+    );
+
+    let mut api_error = ApiError::new_with_status(status, api_error_code);
+    api_error.field_errors = Some(vec![field_error]);
+    api_error
+}
+
+fn step_timeout_error() -> ApiError {
+    ApiError::new_with_status(
+        StatusCode::GATEWAY_TIMEOUT,
+        FORWARD_ERR_ORCHESTRATION_TIMEOUT,
+    )
+}
+
+impl OrchestrationService {
+    // the breakers are the ones of the forwarded requests, a step counts for its upstream.
+    pub fn new(
+        clients: Arc<HttpClients>,
+        credential_injector: Arc<CredentialInjector>,
+        circuit_breakers: Arc<CircuitBreakers>,
+    ) -> Self {
+        OrchestrationService {
+            clients,
+            credential_injector,
+            circuit_breakers,
+            max_body_size: max_buffered_body_size(),
+            step_timeout: proxy_idle_timeout(),
+        }
+    }
+
+    // every step receives the incoming method, headers, query and body, the step's templates
+    // are applied over them, "authorization": "{{request.headers.authorization}}" passes the
+    // client's credentials on. a step whose route has an upstream is sent to one of its targets.
+    fn build_request(
+        step: &OrchestrationStep,
+        context: &OrchestrationContext,
+    ) -> Result<(Request<Body>, Option<ActiveRequest>), ApiError> {
+        let url_destination = step.url_destination.render(|reference| {
+            let value = context.resolve(reference)?;
+            Ok(utf8_percent_encode(&value, URL_VALUE).to_string())
+        })?;
+        let client_ip = context
+            .parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        let (url_destination, active) = balance(
+            step.balancer.as_ref(),
+            &url_destination,
+            &context.parts.headers,
+            client_ip,
+        )?;
+        let uri = ForwardService::forward_uri(&url_destination, "", context.parts.uri.query())?;
+
+        let mut request = Request::new(Body::empty());
+        *request.method_mut() = context.parts.method.clone();
+        *request.uri_mut() = uri;
+        *request.headers_mut() = context.parts.headers.clone();
+        for name in &STEP_STRIPPED_HEADERS {
+            request.headers_mut().remove(name);
+        }
+        remove_hop_by_hop_headers(request.headers_mut());
+
+        for (name, value) in &step.request_headers {
+            let value = value.render(|reference| context.resolve(reference))?;
+            match HeaderValue::try_from(value) {
+                Ok(value) => {
+                    request.headers_mut().insert(name, value);
+                }
+                Err(_) => {
+                    return Err(ApiError::new_with_status(
+                        StatusCode::BAD_GATEWAY,
+                        TEMPLATE_ERR_INVALID_HEADER,
+                    ))
+                }
+            }
+        }
+
+        match &step.request_body {
+            Some(request_body) => {
+                let body = request_body.render(|reference| context.resolve(reference))?;
+                *request.body_mut() = Body::from(body);
+            }
+            None => *request.body_mut() = Body::from(context.body.clone()),
+        }
+
+        Ok((request, active))
+    }
+
+    // the step's failure policy decides what a failed step turns into, None omits the response
+    // key.
+    fn step_failed(
+        step: &OrchestrationStep,
+        api_error: ApiError,
+    ) -> Result<Option<Value>, ApiError> {
+        match step.failure_policy.as_str() {
+            FAILURE_POLICY_OMIT => Ok(None),
+            FAILURE_POLICY_FALLBACK => Ok(Some(step.fallback.clone().unwrap_or(Value::Null))),
+            FAILURE_POLICY_ERROR => Ok(Some(
+                serde_json::to_value(&api_error).unwrap_or(Value::Null),
+            )),
+            _ => Err(api_error),
+        }
+    }
+
+    // a failed or timed out call goes to the step's failure policy. the route policy gives the
+    // connect and response timeouts and the circuit breaker, a step without a timeout is bounded
+    // by the total timeout of the policy or PROXY_IDLE_TIMEOUT.
+    async fn run_step(
+        &self,
+        step: &OrchestrationStep,
+        (request, active): (Request<Body>, Option<ActiveRequest>),
+    ) -> Result<Option<Value>, ApiError> {
+        let policy = &step.policy;
+        let circuit = policy.circuit_breaker.as_ref();
+        let upstream = upstream_key(request.uri());
+        let record = |success: bool| {
+            if let Some(circuit) = circuit {
+                self.circuit_breakers.record(&upstream, circuit, success);
+            }
+            if let Some(active) = &active {
+                active.record(success);
+            }
+        };
+
+        // the credentials and the tls of the upstream are part of the step, a failure goes to its
+        // policy.
+        let call = async {
+            if !self.circuit_allows(&upstream, circuit, &step.response_key) {
+                return Err(circuit_open_error());
+            }
+
+            let mut request = request;
+            let client = self.clients.get_with_tls(
+                policy.connect_timeout(),
+                false,
+                step.balancer.as_deref().and_then(UpstreamBalancer::tls),
+            )?;
+            self.credential_injector
+                .inject(
+                    step.balancer.as_deref().map(UpstreamBalancer::upstream),
+                    request.headers_mut(),
+                )
+                .await?;
+            self.call(
+                &client,
+                &step.response_key,
+                request,
+                policy.response_timeout(),
+                &record,
+            )
+            .await
+        };
+        let timeout = step
+            .timeout
+            .or_else(|| policy.total_timeout())
+            .unwrap_or(self.step_timeout);
+        let result = tokio::time::timeout(timeout, call)
+            .await
+            .unwrap_or_else(|_| {
+                tracing::error!("The step {} has timed out", step.response_key);
+                record(false);
+                Err(step_timeout_error())
+            });
+
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(api_error) => OrchestrationService::step_failed(step, api_error),
+        }
+    }
+
+    // the outcome of the call feeds the circuit breaker of the upstream and the passive health
+    // check of the target it was sent to.
+    async fn call(
+        &self,
+        client: &HttpsClient,
+        response_key: &str,
+        request: Request<Body>,
+        response_timeout: Option<Duration>,
+        record: &(dyn Fn(bool) + Sync),
+    ) -> Result<Value, ApiError> {
+        tracing::info!("orchestrating {} to {}", response_key, request.uri());
+
+        let response = match response_timeout {
+            Some(response_timeout) => {
+                match tokio::time::timeout(response_timeout, client.request(request)).await {
+                    Ok(response) => response,
+                    Err(_) => {
+                        tracing::error!(
+                            "The step {} has not answered in {:?}",
+                            response_key,
+                            response_timeout
+                        );
+                        record(false);
+                        return Err(step_timeout_error());
+                    }
+                }
+            }
+            None => client.request(request).await,
+        };
+        record(matches!(&response, Ok(response) if !response.status().is_server_error()));
+        let response = response.map_err(|e| {
+            tracing::error!("Error when calling the step {}: {:?}", response_key, e);
+            ApiError::new_with_status(StatusCode::BAD_GATEWAY, FORWARD_ERR_ORCHESTRATION_FAILED)
+        })?;
+
+        let status = response.status();
+        let body = buffer_body(response.into_body(), self.max_body_size)
+            .await
+            .map_err(|e| {
+                tracing::error!(
+                    "Error when reading the response of the step {}: {}",
+                    response_key,
+                    e
+                );
+                ApiError::new_with_status(StatusCode::BAD_GATEWAY, FORWARD_ERR_ORCHESTRATION_FAILED)
+            })?;
+
+        if !status.is_success() {
+            tracing::error!(
+                "The step {} has answered with status {}",
+                response_key,
+                status
+            );
+            return Err(ApiError::new_with_status(
+                StatusCode::BAD_GATEWAY,
+                FORWARD_ERR_ORCHESTRATION_FAILED,
+            ));
+        }
+
+        // non json bodies are returned as plain strings.
+        Ok(serde_json::from_slice(&body)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&body).into_owned())))
+    }
+
+    // steps without a circuit breaker in their policy always go through.
+    fn circuit_allows(
+        &self,
+        upstream: &str,
+        circuit: Option<&CircuitBreakerPolicy>,
+        response_key: &str,
+    ) -> bool {
+        let allowed =
+            circuit.is_none_or(|circuit| self.circuit_breakers.try_acquire(upstream, circuit));
+        if !allowed {
+            tracing::warn!(
+                "circuit of {} is open, step {} rejected",
+                upstream,
+                response_key
+            );
+        }
+        allowed
+    }
+}
+
+#[async_trait]
+impl OrchestrationServiceTrait for OrchestrationService {
+    async fn execute(
+        &self,
+        orchestration: Arc<RoutingOrchestration>,
+        path_params: HashMap<String, String>,
+        req: Request<Body>,
+    ) -> Result<Response<Body>, ApiError> {
+        let (parts, body) = req.into_parts();
+        let body = if orchestration.forwards_request_body() {
+            buffer_body(body, self.max_body_size).await?
+        } else {
+            Bytes::new()
+        };
+
+        let query = parts
+            .uri
+            .query()
+            .and_then(|query| serde_urlencoded::from_str(query).ok())
+            .unwrap_or_default();
+
+        let mut context = OrchestrationContext {
+            parts,
+            body,
+            query,
+            path_params,
+            responses: Map::new(),
+        };
+
+        if orchestration.orchestration.is_parallel() {
+            // parallel steps can't reference each other, only the request. a step that can't be
+            // built goes to its failure policy before any is sent.
+            let requests = orchestration
+                .steps
+                .iter()
+                .map(
+                    |step| match OrchestrationService::build_request(step, &context) {
+                        Ok(request) => Ok(Ok(request)),
+                        Err(api_error) => {
+                            OrchestrationService::step_failed(step, api_error).map(Err)
+                        }
+                    },
+                )
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let values = future::try_join_all(orchestration.steps.iter().zip(requests).map(
+                |(step, request)| async move {
+                    match request {
+                        Ok(request) => self.run_step(step, request).await,
+                        Err(value) => Ok(value),
+                    }
+                },
+            ))
+            .await?;
+
+            for (step, value) in orchestration.steps.iter().zip(values) {
+                if let Some(value) = value {
+                    context.responses.insert(step.response_key.clone(), value);
+                }
+            }
+        } else {
+            // an omitted step can't be referenced by the next ones.
+            for step in &orchestration.steps {
+                let value = match OrchestrationService::build_request(step, &context) {
+                    Ok(request) => self.run_step(step, request).await?,
+                    Err(api_error) => OrchestrationService::step_failed(step, api_error)?,
+                };
+                if let Some(value) = value {
+                    context.responses.insert(step.response_key.clone(), value);
+                }
+            }
+        }
+
+        let response = Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(Value::Object(context.responses).to_string()))
+            .unwrap();
+
+        Ok(response)
+    }
+}
@@ -0,0 +1,706 @@
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use chrono::Utc;
+use hyper::{
+    header::CONTENT_ENCODING,
+    service::{make_service_fn, service_fn},
+};
+use serde_json::json;
+
+use crate::config::HttpClients;
+use crate::exception::{FORWARD_ERR_BODY_TOO_LARGE, FORWARD_ERR_CIRCUIT_OPEN};
+use crate::model::{
+    ApplicationOrchestration, UpstreamPolicy, FAILURE_POLICY_FAIL, ORCHESTRATION_TYPE_PARALLEL,
+    ORCHESTRATION_TYPE_SEQUENTIAL,
+};
+use crate::template::Template;
+
+use super::*;
+
+// {"compressed":true} gzipped.
+const GZIP_BODY: [u8; 39] = [
+    31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 171, 86, 74, 206, 207, 45, 40, 74, 45, 46, 78, 77, 81, 178,
+    42, 41, 42, 77, 173, 5, 0, 241, 234, 57, 149, 19, 0, 0, 0,
+];
+
+// answers json on /orders and /user, plain text on /text, the calls order on /count, what it
+// has received on /echo and /headers, gzip on /compressed when it's accepted, late on /slow and
+// 500 on anything else.
+async fn start_upstream() -> SocketAddr {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let make_service = make_service_fn(move |_| {
+        let counter = Arc::clone(&counter);
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                let counter = Arc::clone(&counter);
+                async move {
+                    let response = match req.uri().path() {
+                        "/orders" => Response::new(Body::from(format!(
+                            r#"{{"method":"{}","query":"{}"}}"#,
+                            req.method(),
+                            req.uri().query().unwrap_or("")
+                        ))),
+                        "/user" => Response::new(Body::from(
+                            r#"{"id":7,"name":"Ana Maria","tags":["vip"]}"#,
+                        )),
+                        "/text" => Response::new(Body::from("plain")),
+                        "/compressed" => match req.headers().get(ACCEPT_ENCODING) {
+                            Some(accept_encoding) if accept_encoding == "gzip" => {
+                                Response::builder()
+                                    .header(CONTENT_ENCODING, "gzip")
+                                    .body(Body::from(GZIP_BODY.to_vec()))
+                                    .unwrap()
+                            }
+                            _ => Response::new(Body::from(r#"{"compressed":false}"#)),
+                        },
+                        "/headers" => {
+                            let header = |name| {
+                                req.headers()
+                                    .get(name)
+                                    .map_or("", |value: &HeaderValue| value.to_str().unwrap())
+                            };
+                            let headers = json!({
+                                "host": header(HOST),
+                                "authorization": header(AUTHORIZATION),
+                                "cookie": header(COOKIE),
+                                "content_length": header(CONTENT_LENGTH),
+                            });
+                            Response::new(Body::from(headers.to_string()))
+                        }
+                        "/slow" => {
+                            tokio::time::sleep(Duration::from_millis(500)).await;
+                            Response::new(Body::from("late"))
+                        }
+                        "/count" => Response::new(Body::from(
+                            (counter.fetch_add(1, Ordering::SeqCst) + 1).to_string(),
+                        )),
+                        path if path.starts_with("/echo") => {
+                            let path = path.to_owned();
+                            let query = req.uri().query().unwrap_or("").to_owned();
+                            let customer = req
+                                .headers()
+                                .get("x-customer")
+                                .map_or("", |value| value.to_str().unwrap())
+                                .to_owned();
+                            let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+                            let echo = json!({
+                                "path": path,
+                                "query": query,
+                                "customer": customer,
+                                "body": String::from_utf8(body.to_vec()).unwrap(),
+                            });
+                            Response::new(Body::from(echo.to_string()))
+                        }
+                        _ => Response::builder()
+                            .status(StatusCode::INTERNAL_SERVER_ERROR)
+                            .body(Body::empty())
+                            .unwrap(),
+                    };
+                    Ok::<_, Infallible>(response)
+                }
+            }))
+        }
+    });
+
+    let server = hyper::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+fn template(source: &str) -> Template {
+    Template::parse(source).unwrap()
+}
+
+fn step(response_key: &str, addr: SocketAddr, path: &str) -> OrchestrationStep {
+    OrchestrationStep {
+        response_key: String::from(response_key),
+        url_destination: template(&format!("http://{}{}", addr, path)),
+        request_headers: Vec::new(),
+        request_body: None,
+        failure_policy: String::from(FAILURE_POLICY_FAIL),
+        fallback: None,
+        timeout: None,
+        policy: Arc::default(),
+        balancer: None,
+    }
+}
+
+fn step_with_policy(
+    response_key: &str,
+    addr: SocketAddr,
+    path: &str,
+    failure_policy: &str,
+) -> OrchestrationStep {
+    let mut step = step(response_key, addr, path);
+    step.failure_policy = String::from(failure_policy);
+    step
+}
+
+fn orchestration(
+    orchestration_type: &str,
+    steps: Vec<OrchestrationStep>,
+) -> Arc<RoutingOrchestration> {
+    Arc::new(RoutingOrchestration {
+        orchestration: ApplicationOrchestration {
+            id: 1,
+            id_application_workflow: 1,
+            path: String::from("/home"),
+            orchestration_type: String::from(orchestration_type),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        },
+        id_application: 1,
+        steps,
+        rate_limits: Vec::new(),
+        auth: None,
+    })
+}
+
+fn service() -> OrchestrationService {
+    OrchestrationService::new(
+        Arc::new(HttpClients::config()),
+        Arc::default(),
+        Arc::default(),
+    )
+}
+
+async fn body_json(response: Response<Body>) -> Value {
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn execute_parallel() {
+    let addr = start_upstream().await;
+    let orchestration = orchestration(
+        ORCHESTRATION_TYPE_PARALLEL,
+        vec![
+            step("orders", addr, "/orders"),
+            step("banner", addr, "/text"),
+        ],
+    );
+
+    let request = Request::builder()
+        .method("GET")
+        .uri("/shop/v1/home?customer=1")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = service()
+        .execute(orchestration, HashMap::new(), request)
+        .await;
+    assert!(response.is_ok());
+
+    let response = response.unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!(
+        "application/json",
+        response.headers().get(CONTENT_TYPE).unwrap()
+    );
+
+    let body = body_json(response).await;
+    assert_eq!("GET", body["orders"]["method"]);
+    assert_eq!("customer=1", body["orders"]["query"]);
+    assert_eq!("plain", body["banner"]);
+}
+
+#[tokio::test]
+async fn execute_sequential() {
+    let addr = start_upstream().await;
+    let orchestration = orchestration(
+        ORCHESTRATION_TYPE_SEQUENTIAL,
+        vec![
+            step("first", addr, "/count"),
+            step("second", addr, "/count"),
+            step("third", addr, "/count"),
+        ],
+    );
+
+    let request = Request::builder()
+        .uri("/shop/v1/home")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = service()
+        .execute(orchestration, HashMap::new(), request)
+        .await;
+    assert!(response.is_ok());
+
+    let body = body_json(response.unwrap()).await;
+    assert_eq!(1, body["first"]);
+    assert_eq!(2, body["second"]);
+    assert_eq!(3, body["third"]);
+}
+
+#[tokio::test]
+async fn execute_sequential_with_templates() {
+    let addr = start_upstream().await;
+
+    let mut echo = step(
+        "echo",
+        addr,
+        "/echo/{{request.path.id}}?customer={{user.id}}&name={{user.name}}",
+    );
+    echo.request_headers = vec![(
+        HeaderName::from_static("x-customer"),
+        template("{{user.tags.0}}-{{request.headers.x-tenant}}"),
+    )];
+    echo.request_body = Some(template(
+        r#"{"id": {{user.id}}, "page": "{{request.query.page}}"}"#,
+    ));
+
+    let orchestration = orchestration(
+        ORCHESTRATION_TYPE_SEQUENTIAL,
+        vec![step("user", addr, "/user"), echo],
+    );
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/shop/v1/home/42?page=2")
+        .header("x-tenant", "acme")
+        .header(CONTENT_LENGTH, 2)
+        .body(Body::from("{}"))
+        .unwrap();
+
+    let path_params = HashMap::from([(String::from("id"), String::from("42"))]);
+
+    let response = service().execute(orchestration, path_params, request).await;
+    assert!(response.is_ok());
+
+    let body = body_json(response.unwrap()).await;
+    assert_eq!("Ana Maria", body["user"]["name"]);
+    assert_eq!("/echo/42", body["echo"]["path"]);
+    assert_eq!("customer=7&name=Ana%20Maria&page=2", body["echo"]["query"]);
+    assert_eq!("vip-acme", body["echo"]["customer"]);
+    assert_eq!(r#"{"id": 7, "page": "2"}"#, body["echo"]["body"]);
+}
+
+#[tokio::test]
+async fn execute_with_compression_accepted_by_the_client() {
+    let addr = start_upstream().await;
+    let orchestration = orchestration(
+        ORCHESTRATION_TYPE_PARALLEL,
+        vec![step("compressed", addr, "/compressed")],
+    );
+
+    let request = Request::builder()
+        .uri("/shop/v1/home")
+        .header(ACCEPT_ENCODING, "gzip")
+        .body(Body::empty())
+        .unwrap();
+
+    // the step is read as json, it's asked for an uncompressed body.
+    let response = service()
+        .execute(orchestration, HashMap::new(), request)
+        .await;
+    let body = body_json(response.unwrap()).await;
+    assert_eq!(json!({"compressed": false}), body["compressed"]);
+}
+
+#[tokio::test]
+async fn execute_without_the_client_credentials() {
+    let addr = start_upstream().await;
+    let mut forwarded = step("forwarded", addr, "/headers");
+    forwarded.request_headers =
+        vec![(AUTHORIZATION, template("{{request.headers.authorization}}"))];
+    let orchestration = orchestration(
+        ORCHESTRATION_TYPE_PARALLEL,
+        vec![step("stripped", addr, "/headers"), forwarded],
+    );
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/shop/v1/home")
+        .header(HOST, "shop.example.org")
+        .header(AUTHORIZATION, "Bearer client")
+        .header(COOKIE, "session=1")
+        .header(CONTENT_LENGTH, 2)
+        .body(Body::from("{}"))
+        .unwrap();
+
+    // only the step that asks for them gets the credentials, the host is the upstream's.
+    let response = service()
+        .execute(orchestration, HashMap::new(), request)
+        .await;
+    let body = body_json(response.unwrap()).await;
+    assert_eq!(
+        json!({
+            "host": addr.to_string(),
+            "authorization": "",
+            "cookie": "",
+            "content_length": "2",
+        }),
+        body["stripped"]
+    );
+    assert_eq!("Bearer client", body["forwarded"]["authorization"]);
+    assert_eq!("", body["forwarded"]["cookie"]);
+}
+
+#[tokio::test]
+async fn execute_with_unresolved_response_reference() {
+    let addr = start_upstream().await;
+
+    let orchestration = orchestration(
+        ORCHESTRATION_TYPE_SEQUENTIAL,
+        vec![
+            step("user", addr, "/user"),
+            step("echo", addr, "/echo?customer={{user.document}}"),
+        ],
+    );
+
+    let request = Request::builder()
+        .uri("/shop/v1/home")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = service()
+        .execute(orchestration, HashMap::new(), request)
+        .await;
+    assert!(response.is_err());
+
+    let api_error = response.unwrap_err();
+    assert_eq!(502, api_error.status_code);
+    assert_eq!(TEMPLATE_ERR_RESPONSE_REFERENCE.0, api_error.code);
+    assert_eq!("user.document", api_error.field_errors.unwrap()[0].field);
+}
+
+#[tokio::test]
+async fn execute_with_unresolved_request_reference() {
+    let addr = start_upstream().await;
+
+    // parallel steps only see the request.
+    let orchestration = orchestration(
+        ORCHESTRATION_TYPE_PARALLEL,
+        vec![step("echo", addr, "/echo?page={{request.query.page}}")],
+    );
+
+    let request = Request::builder()
+        .uri("/shop/v1/home")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = service()
+        .execute(orchestration, HashMap::new(), request)
+        .await;
+    assert!(response.is_err());
+
+    let api_error = response.unwrap_err();
+    assert_eq!(400, api_error.status_code);
+    assert_eq!(TEMPLATE_ERR_REQUEST_REFERENCE.0, api_error.code);
+    assert_eq!(
+        "request.query.page",
+        api_error.field_errors.unwrap()[0].field
+    );
+}
+
+#[tokio::test]
+async fn execute_with_unresolved_reference_in_omitted_step() {
+    let addr = start_upstream().await;
+
+    // the step that can't be built is omitted like a failed one, the next ones still run.
+    let orchestration = orchestration(
+        ORCHESTRATION_TYPE_SEQUENTIAL,
+        vec![
+            step("user", addr, "/user"),
+            step_with_policy(
+                "customer",
+                addr,
+                "/echo?customer={{user.document}}",
+                FAILURE_POLICY_OMIT,
+            ),
+            step("orders", addr, "/orders"),
+        ],
+    );
+
+    let request = Request::builder()
+        .uri("/shop/v1/home")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = service()
+        .execute(orchestration, HashMap::new(), request)
+        .await;
+    assert!(response.is_ok());
+
+    let body = body_json(response.unwrap()).await;
+    assert!(body.get("user").is_some());
+    assert!(body.get("customer").is_none());
+    assert_eq!("GET", body["orders"]["method"]);
+}
+
+#[tokio::test]
+async fn execute_with_unresolved_reference_in_fallback_step() {
+    let addr = start_upstream().await;
+
+    let mut fallback = step_with_policy(
+        "echo",
+        addr,
+        "/echo?page={{request.query.page}}",
+        FAILURE_POLICY_FALLBACK,
+    );
+    fallback.fallback = Some(json!({"page": 1}));
+    let orchestration = orchestration(ORCHESTRATION_TYPE_PARALLEL, vec![fallback]);
+
+    let request = Request::builder()
+        .uri("/shop/v1/home")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = service()
+        .execute(orchestration, HashMap::new(), request)
+        .await;
+    assert!(response.is_ok());
+    assert_eq!(1, body_json(response.unwrap()).await["echo"]["page"]);
+}
+
+#[tokio::test]
+async fn execute_with_failed_step() {
+    let addr = start_upstream().await;
+    let orchestration = orchestration(
+        ORCHESTRATION_TYPE_PARALLEL,
+        vec![
+            step("orders", addr, "/orders"),
+            step("broken", addr, "/fail"),
+        ],
+    );
+
+    let request = Request::builder()
+        .uri("/shop/v1/home")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = service()
+        .execute(orchestration, HashMap::new(), request)
+        .await;
+    assert!(response.is_err());
+
+    let api_error = response.unwrap_err();
+    assert_eq!(502, api_error.status_code);
+    assert_eq!(FORWARD_ERR_ORCHESTRATION_FAILED.0, api_error.code);
+}
+
+#[tokio::test]
+async fn execute_with_failure_policies() {
+    let addr = start_upstream().await;
+
+    let mut fallback = step_with_policy("banner", addr, "/fail", FAILURE_POLICY_FALLBACK);
+    fallback.fallback = Some(json!({"title": "default"}));
+
+    let mut slow = step_with_policy("recommendations", addr, "/slow", FAILURE_POLICY_ERROR);
+    slow.timeout = Some(Duration::from_millis(50));
+
+    let orchestration = orchestration(
+        ORCHESTRATION_TYPE_PARALLEL,
+        vec![
+            step("orders", addr, "/orders"),
+            step_with_policy("broken", addr, "/fail", FAILURE_POLICY_OMIT),
+            fallback,
+            step_with_policy("errors", addr, "/fail", FAILURE_POLICY_ERROR),
+            slow,
+        ],
+    );
+
+    let request = Request::builder()
+        .uri("/shop/v1/home")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = service()
+        .execute(orchestration, HashMap::new(), request)
+        .await;
+    assert!(response.is_ok());
+
+    let body = body_json(response.unwrap()).await;
+    assert_eq!("GET", body["orders"]["method"]);
+    assert!(body.get("broken").is_none());
+    assert_eq!("default", body["banner"]["title"]);
+    assert_eq!(502, body["errors"]["status"]);
+    assert_eq!(FORWARD_ERR_ORCHESTRATION_FAILED.0, body["errors"]["code"]);
+    assert_eq!(504, body["recommendations"]["status"]);
+    assert_eq!(
+        FORWARD_ERR_ORCHESTRATION_TIMEOUT.0,
+        body["recommendations"]["code"]
+    );
+}
+
+#[tokio::test]
+async fn execute_with_timeout() {
+    let addr = start_upstream().await;
+
+    let mut slow = step("recommendations", addr, "/slow");
+    slow.timeout = Some(Duration::from_millis(50));
+
+    let orchestration = orchestration(
+        ORCHESTRATION_TYPE_SEQUENTIAL,
+        vec![step("orders", addr, "/orders"), slow],
+    );
+
+    let request = Request::builder()
+        .uri("/shop/v1/home")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = service()
+        .execute(orchestration, HashMap::new(), request)
+        .await;
+    assert!(response.is_err());
+
+    let api_error = response.unwrap_err();
+    assert_eq!(504, api_error.status_code);
+    assert_eq!(FORWARD_ERR_ORCHESTRATION_TIMEOUT.0, api_error.code);
+}
+
+#[tokio::test]
+async fn execute_with_route_policy() {
+    let addr = start_upstream().await;
+
+    let mut slow = step("recommendations", addr, "/slow");
+    slow.policy = Arc::new(
+        UpstreamPolicy::parse(&json!({"connectTimeoutMs": 100, "responseTimeoutMs": 50})).unwrap(),
+    );
+    let orchestration = orchestration(ORCHESTRATION_TYPE_PARALLEL, vec![slow]);
+
+    let request = Request::builder()
+        .uri("/shop/v1/home")
+        .body(Body::empty())
+        .unwrap();
+    let api_error = service()
+        .execute(orchestration, HashMap::new(), request)
+        .await
+        .unwrap_err();
+    assert_eq!(504, api_error.status_code);
+    assert_eq!(FORWARD_ERR_ORCHESTRATION_TIMEOUT.0, api_error.code);
+}
+
+#[tokio::test]
+async fn execute_with_default_timeout() {
+    let addr = start_upstream().await;
+    let orchestration = orchestration(
+        ORCHESTRATION_TYPE_PARALLEL,
+        vec![step("recommendations", addr, "/slow")],
+    );
+
+    // a step without a timeout of its own nor in the policy still has one.
+    let mut service = service();
+    service.step_timeout = Duration::from_millis(50);
+    let request = Request::builder()
+        .uri("/shop/v1/home")
+        .body(Body::empty())
+        .unwrap();
+    let api_error = service
+        .execute(orchestration, HashMap::new(), request)
+        .await
+        .unwrap_err();
+    assert_eq!(FORWARD_ERR_ORCHESTRATION_TIMEOUT.0, api_error.code);
+}
+
+#[tokio::test]
+async fn execute_with_open_circuit() {
+    let addr = start_upstream().await;
+
+    let mut failing = step("failing", addr, "/failing");
+    failing.policy = Arc::new(
+        UpstreamPolicy::parse(&json!({"circuitBreaker": {"consecutiveFailures": 1}})).unwrap(),
+    );
+    let orchestration = orchestration(ORCHESTRATION_TYPE_PARALLEL, vec![failing]);
+    let service = service();
+    let request = || {
+        Request::builder()
+            .uri("/shop/v1/home")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let api_error = service
+        .execute(Arc::clone(&orchestration), HashMap::new(), request())
+        .await
+        .unwrap_err();
+    assert_eq!(FORWARD_ERR_ORCHESTRATION_FAILED.0, api_error.code);
+
+    // the failure opened the circuit of the upstream, the step isn't sent anymore.
+    let api_error = service
+        .execute(orchestration, HashMap::new(), request())
+        .await
+        .unwrap_err();
+    assert_eq!(503, api_error.status_code);
+    assert_eq!(FORWARD_ERR_CIRCUIT_OPEN.0, api_error.code);
+}
+
+#[tokio::test]
+async fn execute_without_buffering() {
+    let addr = start_upstream().await;
+    let mut user = step("user", addr, "/user");
+    user.request_body = Some(template("{}"));
+
+    // the incoming body never ends, it isn't read when no step forwards it.
+    let (_sender, body) = Body::channel();
+    let request = Request::builder().uri("/home").body(body).unwrap();
+
+    let response = tokio::time::timeout(
+        Duration::from_secs(5),
+        service().execute(
+            orchestration(ORCHESTRATION_TYPE_PARALLEL, vec![user]),
+            HashMap::new(),
+            request,
+        ),
+    )
+    .await;
+    assert!(response.is_ok());
+    assert_eq!(7, body_json(response.unwrap().unwrap()).await["user"]["id"]);
+}
+
+#[tokio::test]
+async fn execute_with_body_too_large() {
+    let addr = start_upstream().await;
+    let service = OrchestrationService {
+        clients: Arc::new(HttpClients::config()),
+        credential_injector: Arc::default(),
+        circuit_breakers: Arc::default(),
+        max_body_size: 16,
+        step_timeout: proxy_idle_timeout(),
+    };
+
+    let request = Request::builder()
+        .uri("/home")
+        .body(Body::from("a body larger than the limit"))
+        .unwrap();
+    let response = service
+        .execute(
+            orchestration(
+                ORCHESTRATION_TYPE_PARALLEL,
+                vec![step("echo", addr, "/echo")],
+            ),
+            HashMap::new(),
+            request,
+        )
+        .await;
+    assert!(response.is_err());
+
+    let api_error = response.unwrap_err();
+    assert_eq!(413, api_error.status_code);
+    assert_eq!(FORWARD_ERR_BODY_TOO_LARGE.0, api_error.code);
+
+    // a response larger than the limit fails the step.
+    let request = Request::builder().uri("/home").body(Body::empty()).unwrap();
+    let response = service
+        .execute(
+            orchestration(
+                ORCHESTRATION_TYPE_PARALLEL,
+                vec![step("user", addr, "/user")],
+            ),
+            HashMap::new(),
+            request,
+        )
+        .await;
+    assert!(response.is_err());
+    assert_eq!(
+        FORWARD_ERR_ORCHESTRATION_FAILED.0,
+        response.unwrap_err().code
+    );
+}
@@ -0,0 +1,273 @@
+#[cfg(test)]
+#[path = "path_matcher_test.rs"]
+mod path_matcher_test;
+
+use std::{cmp::Reverse, collections::HashMap};
+
+use regex::Regex;
+
+use crate::model::{PathPattern, PathSegment};
+
+pub struct PathMatch<'a, T> {
+    pub value: &'a T,
+    pub params: HashMap<String, String>,
+    pub matched_segments: usize,
+}
+
+struct Entry<T> {
+    // exact entries only match when the whole path is consumed, the others are prefixes.
+    exact: bool,
+    priority: i32,
+    sequence: usize,
+    value: T,
+}
+
+struct ParamNode<T> {
+    name: String,
+    regex: Option<Regex>,
+    node: Node<T>,
+}
+
+struct MultiWildcardEntry<T> {
+    name: Option<String>,
+    rank: u8,
+    entry: Entry<T>,
+}
+
+// the rank of the segment leading to the node, how specific it is.
+struct Node<T> {
+    rank: u8,
+    literals: HashMap<String, Node<T>>,
+    params: Vec<ParamNode<T>>,
+    wildcard: Option<Box<Node<T>>>,
+    multi_wildcards: Vec<MultiWildcardEntry<T>>,
+    entries: Vec<Entry<T>>,
+}
+
+impl<T> Node<T> {
+    fn new(rank: u8) -> Self {
+        Node {
+            rank,
+            literals: HashMap::new(),
+            params: Vec::new(),
+            wildcard: None,
+            multi_wildcards: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+}
+
+// the best candidate so far, compared by specificity, exactness, priority and insertion order.
+struct Candidate<'a, T> {
+    key: (Vec<u8>, bool, i32, Reverse<usize>),
+    value: &'a T,
+    params: Vec<(String, String)>,
+    matched_segments: usize,
+}
+
+struct Search<'a, 'p, T> {
+    segments: &'p [&'p str],
+    ranks: Vec<u8>,
+    params: Vec<(String, String)>,
+    best: Option<Candidate<'a, T>>,
+}
+
+impl<'a, 'p, T> Search<'a, 'p, T> {
+    fn offer(&mut self, entry: &'a Entry<T>, matched_segments: usize) {
+        let key = (
+            self.ranks.clone(),
+            entry.exact,
+            entry.priority,
+            Reverse(entry.sequence),
+        );
+
+        if self.best.as_ref().is_none_or(|best| key > best.key) {
+            self.best = Some(Candidate {
+                key,
+                value: &entry.value,
+                params: self.params.clone(),
+                matched_segments,
+            });
+        }
+    }
+
+    fn visit(&mut self, node: &'a Node<T>, depth: usize) {
+        for entry in &node.entries {
+            if !entry.exact || depth == self.segments.len() {
+                self.offer(entry, depth);
+            }
+        }
+
+        if depth == self.segments.len() {
+            return;
+        }
+
+        let segment = self.segments[depth];
+
+        if let Some(child) = node.literals.get(segment) {
+            self.visit_child(child, depth);
+        }
+
+        for param in &node.params {
+            if param
+                .regex
+                .as_ref()
+                .is_some_and(|regex| !regex.is_match(segment))
+            {
+                continue;
+            }
+
+            self.params.push((param.name.clone(), segment.to_owned()));
+            self.visit_child(&param.node, depth);
+            self.params.pop();
+        }
+
+        if let Some(child) = &node.wildcard {
+            self.visit_child(child, depth);
+        }
+
+        if !node.multi_wildcards.is_empty() {
+            let rest = self.segments[depth..].join("/");
+            for multi_wildcard in &node.multi_wildcards {
+                self.ranks.push(multi_wildcard.rank);
+                if let Some(name) = &multi_wildcard.name {
+                    self.params.push((name.clone(), rest.clone()));
+                }
+                self.offer(&multi_wildcard.entry, self.segments.len());
+                if multi_wildcard.name.is_some() {
+                    self.params.pop();
+                }
+                self.ranks.pop();
+            }
+        }
+    }
+
+    fn visit_child(&mut self, child: &'a Node<T>, depth: usize) {
+        self.ranks.push(child.rank);
+        self.visit(child, depth + 1);
+        self.ranks.pop();
+    }
+}
+
+// a trie over path segments, a lookup walks only the branches that can match the path.
+pub struct PathMatcher<T> {
+    root: Node<T>,
+    len: usize,
+}
+
+impl<T> Default for PathMatcher<T> {
+    fn default() -> Self {
+        PathMatcher {
+            root: Node::new(0),
+            len: 0,
+        }
+    }
+}
+
+impl<T> PathMatcher<T> {
+    pub fn insert(&mut self, pattern: &PathPattern, exact: bool, priority: i32, value: T) {
+        let entry = Entry {
+            exact,
+            priority,
+            sequence: self.len,
+            value,
+        };
+        self.len += 1;
+
+        let mut node = &mut self.root;
+        for segment in &pattern.segments {
+            let rank = segment.rank();
+            node = match segment {
+                PathSegment::Literal(literal) => node
+                    .literals
+                    .entry(literal.clone())
+                    .or_insert_with(|| Node::new(rank)),
+                PathSegment::Param { name, regex } => {
+                    let source = regex.as_ref().map(|regex| regex.as_str());
+                    let index = node.params.iter().position(|param| {
+                        param.name == *name
+                            && param.regex.as_ref().map(|regex| regex.as_str()) == source
+                    });
+
+                    let index = match index {
+                        Some(index) => index,
+                        None => {
+                            node.params.push(ParamNode {
+                                name: name.clone(),
+                                regex: regex.clone(),
+                                node: Node::new(rank),
+                            });
+                            node.params.len() - 1
+                        }
+                    };
+                    &mut node.params[index].node
+                }
+                PathSegment::Wildcard => node
+                    .wildcard
+                    .get_or_insert_with(|| Box::new(Node::new(rank))),
+                PathSegment::MultiWildcard(name) => {
+                    node.multi_wildcards.push(MultiWildcardEntry {
+                        name: name.clone(),
+                        rank,
+                        entry,
+                    });
+                    return;
+                }
+            };
+        }
+
+        node.entries.push(entry);
+    }
+
+    pub fn find(&self, path: &str) -> Option<PathMatch<'_, T>> {
+        let segments: Vec<&str> = path_segments(path).collect();
+        let mut search = Search {
+            segments: &segments,
+            ranks: Vec::new(),
+            params: Vec::new(),
+            best: None,
+        };
+
+        search.visit(&self.root, 0);
+
+        search.best.map(|best| PathMatch {
+            value: best.value,
+            params: best.params.into_iter().collect(),
+            matched_segments: best.matched_segments,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+fn path_segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
+
+// what is left of the path after its first segments, "/orders/1/" after 1 segment is "/1/".
+pub fn remaining_path(path: &str, segments: usize) -> &str {
+    if segments == 0 {
+        return path;
+    }
+
+    let mut consumed = 0;
+    let mut offset = 0;
+    for segment in path.split('/') {
+        offset += segment.len();
+        if !segment.is_empty() {
+            consumed += 1;
+            if consumed == segments {
+                return &path[offset..];
+            }
+        }
+        offset += 1;
+    }
+
+    ""
+}
@@ -0,0 +1,113 @@
+use super::*;
+
+fn build(paths: &[(&str, bool, i32)]) -> PathMatcher<usize> {
+    let mut matcher = PathMatcher::default();
+    for (index, (path, exact, priority)) in paths.iter().enumerate() {
+        matcher.insert(&PathPattern::parse(path).unwrap(), *exact, *priority, index);
+    }
+    matcher
+}
+
+fn find(matcher: &PathMatcher<usize>, path: &str) -> Option<usize> {
+    matcher.find(path).map(|path_match| *path_match.value)
+}
+
+#[test]
+fn find_literal() {
+    let matcher = build(&[("/orders", false, 0), ("/orders/v1", false, 0)]);
+
+    assert_eq!(2, matcher.len());
+    assert_eq!(Some(0), find(&matcher, "/orders"));
+    assert_eq!(Some(0), find(&matcher, "/orders/v2/items"));
+    assert_eq!(Some(1), find(&matcher, "/orders/v1/items/"));
+    assert_eq!(None, find(&matcher, "/users"));
+
+    let path_match = matcher.find("/orders/v1/items").unwrap();
+    assert_eq!(2, path_match.matched_segments);
+    assert!(path_match.params.is_empty());
+}
+
+#[test]
+fn find_with_params() {
+    let matcher = build(&[
+        ("/users/:id", true, 0),
+        ("/users/:id(\\d+)/orders/:order", true, 0),
+    ]);
+
+    let path_match = matcher.find("/users/ana").unwrap();
+    assert_eq!(0, *path_match.value);
+    assert_eq!(Some(&String::from("ana")), path_match.params.get("id"));
+
+    let path_match = matcher.find("/users/42/orders/7").unwrap();
+    assert_eq!(1, *path_match.value);
+    assert_eq!(Some(&String::from("42")), path_match.params.get("id"));
+    assert_eq!(Some(&String::from("7")), path_match.params.get("order"));
+
+    // the regex has to match the whole segment.
+    assert_eq!(None, find(&matcher, "/users/42a/orders/7"));
+    // exact entries don't match longer paths.
+    assert_eq!(None, find(&matcher, "/users/ana/orders"));
+}
+
+#[test]
+fn find_with_wildcards() {
+    let matcher = build(&[("/files/*/raw", true, 0), ("/files/**path", true, 0)]);
+
+    assert_eq!(Some(0), find(&matcher, "/files/report/raw"));
+
+    let path_match = matcher.find("/files/2022/12/report.pdf").unwrap();
+    assert_eq!(1, *path_match.value);
+    assert_eq!(
+        Some(&String::from("2022/12/report.pdf")),
+        path_match.params.get("path")
+    );
+    assert_eq!(4, path_match.matched_segments);
+
+    // "**" needs at least one segment.
+    assert_eq!(None, find(&matcher, "/files"));
+}
+
+#[test]
+fn find_most_specific() {
+    let matcher = build(&[
+        ("/users/**", false, 0),
+        ("/users/*", false, 0),
+        ("/users/:id", false, 0),
+        ("/users/:id(\\d+)", false, 0),
+        ("/users/me", false, 0),
+    ]);
+
+    assert_eq!(Some(4), find(&matcher, "/users/me"));
+    assert_eq!(Some(3), find(&matcher, "/users/42"));
+    assert_eq!(Some(2), find(&matcher, "/users/ana"));
+    // the longer match is more specific than the multi segment wildcard.
+    assert_eq!(Some(4), find(&matcher, "/users/me/orders"));
+    assert_eq!(Some(2), find(&matcher, "/users/ana/orders"));
+
+    let matcher = build(&[("/users/*", false, 0), ("/users/**", false, 0)]);
+    assert_eq!(Some(0), find(&matcher, "/users/ana"));
+    assert_eq!(Some(0), find(&matcher, "/users/ana/orders"));
+}
+
+#[test]
+fn find_with_priority() {
+    let matcher = build(&[
+        ("/users/:id", false, 0),
+        ("/users/:name", false, 10),
+        ("/users/:other", false, 10),
+        ("/users/:id", true, 0),
+    ]);
+
+    // exact entries win, then the highest priority, then the first inserted.
+    assert_eq!(Some(3), find(&matcher, "/users/ana"));
+    assert_eq!(Some(1), find(&matcher, "/users/ana/orders"));
+}
+
+#[test]
+fn remaining() {
+    assert_eq!("/1/", remaining_path("/orders/1/", 1));
+    assert_eq!("/items", remaining_path("//orders//v1/items", 2));
+    assert_eq!("", remaining_path("/orders", 1));
+    assert_eq!("/orders", remaining_path("/orders", 0));
+    assert_eq!("", remaining_path("/orders", 3));
+}
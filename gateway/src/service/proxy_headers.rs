@@ -0,0 +1,173 @@
+#[cfg(test)]
+#[path = "proxy_headers_test.rs"]
+mod proxy_headers_test;
+
+use std::net::IpAddr;
+
+use hyper::{
+    header::{
+        HeaderName, HeaderValue, CONNECTION, FORWARDED, HOST, PROXY_AUTHENTICATE,
+        PROXY_AUTHORIZATION, TE, TRAILER, TRANSFER_ENCODING, UPGRADE,
+    },
+    HeaderMap,
+};
+
+use crate::config::TrustedProxies;
+
+pub const X_FORWARDED_FOR: &str = "x-forwarded-for";
+pub const X_FORWARDED_PROTO: &str = "x-forwarded-proto";
+pub const X_FORWARDED_HOST: &str = "x-forwarded-host";
+
+const HOP_BY_HOP_HEADERS: [HeaderName; 7] = [
+    CONNECTION,
+    HeaderName::from_static("keep-alive"),
+    PROXY_AUTHENTICATE,
+    PROXY_AUTHORIZATION,
+    TE,
+    TRAILER,
+    UPGRADE,
+];
+
+// rfc 7230 hop-by-hop headers only make sense for a single connection, the ones listed in
+// connection included. transfer-encoding is left to hyper, it frames the body again.
+pub fn remove_hop_by_hop_headers(headers: &mut HeaderMap) {
+    let listed: Vec<HeaderName> = headers
+        .get_all(CONNECTION)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|name| HeaderName::try_from(name.trim()).ok())
+        .collect();
+
+    for name in listed.iter().chain(HOP_BY_HOP_HEADERS.iter()) {
+        if *name != TRANSFER_ENCODING {
+            headers.remove(name);
+        }
+    }
+
+    let proxy_headers: Vec<HeaderName> = headers
+        .keys()
+        .filter(|name| name.as_str().starts_with("proxy-"))
+        .cloned()
+        .collect();
+    for name in proxy_headers {
+        headers.remove(name);
+    }
+}
+
+// the client is appended to the chain of x-forwarded-for and forwarded, what the client sent
+// is dropped first unless it is a trusted proxy.
+pub fn add_forwarded_headers(
+    headers: &mut HeaderMap,
+    client_ip: Option<IpAddr>,
+    trusted: bool,
+    proto: &str,
+) {
+    if !trusted {
+        headers.remove(X_FORWARDED_FOR);
+        headers.remove(X_FORWARDED_PROTO);
+        headers.remove(X_FORWARDED_HOST);
+        headers.remove(FORWARDED);
+    }
+
+    let host = headers
+        .get(HOST)
+        .and_then(|host| host.to_str().ok())
+        .map(|host| host.to_owned());
+
+    if let Some(client_ip) = client_ip {
+        append_header(headers, X_FORWARDED_FOR, &client_ip.to_string());
+    }
+
+    if !headers.contains_key(X_FORWARDED_PROTO) {
+        set_header(headers, X_FORWARDED_PROTO, proto);
+    }
+
+    if let Some(host) = &host {
+        if !headers.contains_key(X_FORWARDED_HOST) {
+            set_header(headers, X_FORWARDED_HOST, host);
+        }
+    }
+
+    let mut forwarded = Vec::new();
+    if let Some(client_ip) = client_ip {
+        let client = match client_ip {
+            IpAddr::V4(ip) => ip.to_string(),
+            IpAddr::V6(ip) => format!("[{}]", ip),
+        };
+        forwarded.push(format!("for={}", forwarded_value(&client)));
+    }
+    forwarded.push(format!("proto={}", forwarded_value(proto)));
+    if let Some(host) = &host {
+        forwarded.push(format!("host={}", forwarded_value(host)));
+    }
+    append_header(headers, FORWARDED.as_str(), &forwarded.join(";"));
+}
+
+// the original client behind the trusted proxies. x-forwarded-for is read from the right, each
+// trusted proxy appended the address it received from, the first address that isn't a trusted
+// proxy is the client. what's left of it may have been sent by the client and is ignored.
+pub fn original_client_ip(
+    headers: &HeaderMap,
+    client_ip: Option<IpAddr>,
+    trusted_proxies: &TrustedProxies,
+) -> Option<IpAddr> {
+    let mut original = client_ip?;
+    if !trusted_proxies.contains(&original) {
+        return Some(original);
+    }
+
+    let forwarded_for = headers
+        .get_all(X_FORWARDED_FOR)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .collect::<Vec<&str>>();
+    for hop in forwarded_for.into_iter().rev() {
+        match hop.trim().parse::<IpAddr>() {
+            Ok(hop) => {
+                original = hop;
+                if !trusted_proxies.contains(&hop) {
+                    break;
+                }
+            }
+            // nothing past an invalid hop can be trusted.
+            Err(_) => break,
+        }
+    }
+
+    Some(original)
+}
+
+// values that aren't a token, like "[::1]" or "host:8080", are quoted.
+fn forwarded_value(value: &str) -> String {
+    let is_token = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c));
+
+    if is_token {
+        value.to_owned()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+fn set_header(headers: &mut HeaderMap, name: &'static str, value: &str) {
+    if let Ok(value) = HeaderValue::try_from(value) {
+        headers.insert(name, value);
+    }
+}
+
+// a list header is kept as a single line, "client, proxy1, proxy2".
+fn append_header(headers: &mut HeaderMap, name: &'static str, value: &str) {
+    let mut values: Vec<&str> = headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect();
+    values.push(value);
+
+    let value = values.join(", ");
+    set_header(headers, name, &value);
+}
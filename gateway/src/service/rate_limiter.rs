@@ -0,0 +1,545 @@
+#[cfg(test)]
+#[path = "rate_limiter_test.rs"]
+mod rate_limiter_test;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use hyper::{
+    header::{HeaderName, HeaderValue, CONTENT_TYPE, RETRY_AFTER},
+    Body, HeaderMap, Response, StatusCode,
+};
+use redis::{aio::ConnectionManager, Script};
+use tokio::sync::OnceCell;
+
+use crate::{
+    exception::{ApiError, FORWARD_ERR_RATE_LIMITED},
+    model::{
+        RateLimit, RateLimitPolicy, RATE_LIMIT_ALGORITHM_TOKEN_BUCKET, RATE_LIMIT_BY_CLIENT,
+        RATE_LIMIT_BY_CONSUMER,
+    },
+};
+
+use super::{Metrics, GATEWAY_RATE_LIMIT_FALLBACKS};
+
+const RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+const RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+const RATELIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+// the local counters forget the keys idle for longer than their period, once in a while.
+const LOCAL_SWEEP_INTERVAL_MS: u64 = 60_000;
+
+// every limit of a request is checked before any is counted, a request rejected by one limit
+// doesn't take from the others. ARGV has the algorithm, the period in ms, the requests and the
+// burst of each key. the requests of the previous window count for the part of it still inside
+// the sliding window, the ones of the current window count in full. tokens are refilled at
+// requests per period up to the burst, a request takes one. the state of each limit before the
+// request is returned.
+const RATE_LIMIT_SCRIPT: &str = r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local states = {}
+local allowed = true
+for i, key in ipairs(KEYS) do
+  local period = tonumber(ARGV[i * 4 - 2])
+  local requests = tonumber(ARGV[i * 4 - 1])
+  local burst = tonumber(ARGV[i * 4])
+  if ARGV[i * 4 - 3] == 'token_bucket' then
+    local bucket = redis.call('HMGET', key, 'tokens', 'at')
+    local tokens = tonumber(bucket[1]) or burst
+    local at = tonumber(bucket[2]) or now
+    tokens = math.min(burst, tokens + math.max(0, now - at) * requests / period)
+    allowed = allowed and tokens >= 1
+    states[i] = {tokens}
+  else
+    local index = math.floor(now / period)
+    local elapsed = now - index * period
+    local current = tonumber(redis.call('GET', key .. ':' .. index) or '0')
+    local previous = tonumber(redis.call('GET', key .. ':' .. (index - 1)) or '0')
+    allowed = allowed and previous * (period - elapsed) + (current + 1) * period <= requests * period
+    states[i] = {previous, current, elapsed, index}
+  end
+end
+local results = {}
+for i, key in ipairs(KEYS) do
+  local period = tonumber(ARGV[i * 4 - 2])
+  local requests = tonumber(ARGV[i * 4 - 1])
+  local burst = tonumber(ARGV[i * 4])
+  local state = states[i]
+  if ARGV[i * 4 - 3] == 'token_bucket' then
+    local tokens = state[1]
+    if allowed then
+      tokens = tokens - 1
+    end
+    redis.call('HSET', key, 'tokens', tostring(tokens), 'at', now)
+    redis.call('PEXPIRE', key, math.ceil(burst * period / requests) + period)
+    results[i] = {tostring(state[1])}
+  else
+    if allowed then
+      local current_key = key .. ':' .. state[4]
+      redis.call('INCR', current_key)
+      redis.call('PEXPIRE', current_key, period * 2)
+    end
+    results[i] = {tostring(state[1]), tostring(state[2]), tostring(state[3])}
+  end
+end
+return results
+"#;
+
+// redis isn't called again for this long after it failed, the requests are counted locally.
+const REDIS_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+// a policy with the element it belongs to, the counters of its limits are shared by every
+// request to the element.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ScopedRateLimit {
+    pub scope: String,
+    pub policy: RateLimitPolicy,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: i64,
+    pub remaining: i64,
+    // until the counters are back to the full limit.
+    pub reset: Duration,
+    pub retry_after: Option<Duration>,
+}
+
+impl RateLimitDecision {
+    // the closest to be exceeded is the one the client sees.
+    fn tighter(self, other: RateLimitDecision) -> RateLimitDecision {
+        match (self.allowed, other.allowed) {
+            (true, false) => other,
+            (false, true) => self,
+            _ if other.remaining < self.remaining => other,
+            _ => self,
+        }
+    }
+}
+
+enum LocalCounter {
+    Window {
+        index: u64,
+        previous: i64,
+        current: i64,
+    },
+    Bucket {
+        tokens: f64,
+        at: u64,
+    },
+}
+
+impl LocalCounter {
+    fn new(limit: &RateLimit, now: u64) -> LocalCounter {
+        match limit.algorithm() {
+            RATE_LIMIT_ALGORITHM_TOKEN_BUCKET => LocalCounter::Bucket {
+                tokens: limit.burst() as f64,
+                at: now,
+            },
+            _ => LocalCounter::Window {
+                index: now / period_ms(limit),
+                previous: 0,
+                current: 0,
+            },
+        }
+    }
+
+    // the windows slide and the tokens refill up to now, the decision doesn't count the request.
+    fn decide(&mut self, limit: &RateLimit, now: u64) -> RateLimitDecision {
+        let period = period_ms(limit);
+        match self {
+            LocalCounter::Window {
+                index,
+                previous,
+                current,
+            } => {
+                let now_index = now / period;
+                if *index != now_index {
+                    *previous = if now_index == *index + 1 { *current } else { 0 };
+                    *current = 0;
+                    *index = now_index;
+                }
+                sliding_window(limit, *previous, *current, now % period)
+            }
+            LocalCounter::Bucket { tokens, at } => {
+                *tokens = refill(limit, *tokens, now.saturating_sub(*at));
+                *at = now;
+                let allowed = *tokens >= 1.0;
+                token_bucket(
+                    limit,
+                    allowed,
+                    if allowed { *tokens - 1.0 } else { *tokens },
+                )
+            }
+        }
+    }
+
+    fn count(&mut self) {
+        match self {
+            LocalCounter::Window { current, .. } => *current += 1,
+            LocalCounter::Bucket { tokens, .. } => *tokens -= 1.0,
+        }
+    }
+
+    fn expires_at(&self, limit: &RateLimit, now: u64) -> u64 {
+        let period = period_ms(limit);
+        match self {
+            LocalCounter::Window { .. } => now + 2 * period,
+            LocalCounter::Bucket { .. } => {
+                now + (limit.burst() as u64 * period).div_ceil(limit.requests as u64)
+            }
+        }
+    }
+}
+
+// counters of this instance only, used without redis and while it can't be reached.
+#[derive(Default)]
+pub struct LocalCounters {
+    counters: Mutex<HashMap<String, (LocalCounter, u64)>>,
+    last_sweep: Mutex<u64>,
+}
+
+impl LocalCounters {
+    // a decision by limit, the request is counted by all of them or by none.
+    pub fn acquire(&self, limits: &[(String, &RateLimit)], now: u64) -> Vec<RateLimitDecision> {
+        self.sweep(now);
+
+        let mut counters = self.counters.lock().unwrap();
+        let decisions: Vec<RateLimitDecision> = limits
+            .iter()
+            .map(|(key, limit)| {
+                let (counter, expires_at) = counters
+                    .entry(key.clone())
+                    .or_insert_with(|| (LocalCounter::new(limit, now), now));
+                *expires_at = counter.expires_at(limit, now);
+                counter.decide(limit, now)
+            })
+            .collect();
+
+        if decisions.iter().all(|decision| decision.allowed) {
+            for (key, _) in limits {
+                if let Some((counter, _)) = counters.get_mut(key) {
+                    counter.count();
+                }
+            }
+        }
+        decisions
+    }
+
+    fn sweep(&self, now: u64) {
+        let mut last_sweep = self.last_sweep.lock().unwrap();
+        if now < *last_sweep + LOCAL_SWEEP_INTERVAL_MS {
+            return;
+        }
+        *last_sweep = now;
+        self.counters
+            .lock()
+            .unwrap()
+            .retain(|_, (_, expires_at)| *expires_at > now);
+    }
+}
+
+// counters shared by every instance of the gateway, a single script checks and counts all the
+// limits of a request atomically.
+pub struct RedisCounters {
+    client: redis::Client,
+    connection: OnceCell<ConnectionManager>,
+    timeout: Duration,
+    script: Script,
+    // while it's in the future redis isn't even dialed.
+    failed_until: Mutex<Option<Instant>>,
+}
+
+impl RedisCounters {
+    pub fn new(client: redis::Client, timeout: Duration) -> RedisCounters {
+        RedisCounters {
+            client,
+            connection: OnceCell::new(),
+            timeout,
+            script: Script::new(RATE_LIMIT_SCRIPT),
+            failed_until: Mutex::new(None),
+        }
+    }
+
+    // none while redis is failing, the failure is logged once until it's retried.
+    pub async fn acquire(&self, limits: &[(String, &RateLimit)]) -> Option<Vec<RateLimitDecision>> {
+        if self
+            .failed_until
+            .lock()
+            .unwrap()
+            .is_some_and(|failed_until| Instant::now() < failed_until)
+        {
+            return None;
+        }
+
+        let acquire = async {
+            let connection = self
+                .connection
+                .get_or_try_init(|| ConnectionManager::new(self.client.clone()))
+                .await?;
+            let mut connection = connection.clone();
+
+            let mut invocation = self.script.prepare_invoke();
+            for (key, limit) in limits {
+                invocation
+                    .key(key)
+                    .arg(limit.algorithm())
+                    .arg(period_ms(limit))
+                    .arg(limit.requests)
+                    .arg(limit.burst());
+            }
+            let states: Vec<Vec<String>> = invocation.invoke_async(&mut connection).await?;
+            Ok::<_, redis::RedisError>(states)
+        };
+
+        let result = match tokio::time::timeout(self.timeout, acquire).await {
+            Ok(Ok(states)) if states.len() == limits.len() => Ok(states),
+            Ok(Ok(_)) => Err(String::from("unexpected answer")),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => Err(format!("no answer in {:?}", self.timeout)),
+        };
+        match result {
+            Ok(states) => Some(
+                limits
+                    .iter()
+                    .zip(states)
+                    .map(|((_, limit), state)| redis_decision(limit, &state))
+                    .collect(),
+            ),
+            Err(e) => {
+                tracing::warn!(
+                    "rate limits counted locally for {:?}, redis has failed: {}",
+                    REDIS_RETRY_INTERVAL,
+                    e
+                );
+                *self.failed_until.lock().unwrap() = Some(Instant::now() + REDIS_RETRY_INTERVAL);
+                None
+            }
+        }
+    }
+}
+
+// checks the limits of the application and route of a request, in redis when it's configured.
+pub struct RateLimiter {
+    redis: Option<RedisCounters>,
+    local: LocalCounters,
+}
+
+impl RateLimiter {
+    // RATE_LIMIT_STORE=local keeps the counters in each instance, as without a REDIS_URL.
+    pub fn config() -> RateLimiter {
+        let store = std::env::var("RATE_LIMIT_STORE").unwrap_or_default();
+        let redis_url = std::env::var("REDIS_URL").ok();
+        let redis = match redis_url {
+            Some(redis_url) if store != "local" => match redis::Client::open(redis_url) {
+                Ok(client) => {
+                    let timeout = std::env::var("REDIS_TIMEOUT_MS")
+                        .ok()
+                        .and_then(|timeout| timeout.parse().ok())
+                        .unwrap_or(200);
+                    Some(RedisCounters::new(client, Duration::from_millis(timeout)))
+                }
+                Err(e) => {
+                    tracing::error!("Ignoring the invalid REDIS_URL: {}", e);
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        if redis.is_none() {
+            tracing::info!("rate limit counters are local to this instance");
+        }
+
+        RateLimiter {
+            redis,
+            local: LocalCounters::default(),
+        }
+    }
+
+    pub fn local() -> RateLimiter {
+        RateLimiter {
+            redis: None,
+            local: LocalCounters::default(),
+        }
+    }
+
+    // a request is counted by every limit or, when one of them rejects it, by none. there's no
+    // decision when there's no limit at all. the consumer is the key of the authenticated
+    // caller, anonymous requests count against the limits by consumer as their client.
+    pub async fn check(
+        &self,
+        rate_limits: &[Arc<ScopedRateLimit>],
+        client: &str,
+        consumer: Option<&str>,
+    ) -> Option<RateLimitDecision> {
+        let limits = limit_keys(rate_limits, client, consumer);
+        if limits.is_empty() {
+            return None;
+        }
+        self.acquire(&limits)
+            .await
+            .into_iter()
+            .reduce(RateLimitDecision::tighter)
+    }
+
+    async fn acquire(&self, limits: &[(String, &RateLimit)]) -> Vec<RateLimitDecision> {
+        if let Some(redis) = &self.redis {
+            if let Some(decisions) = redis.acquire(limits).await {
+                return decisions;
+            }
+            Metrics::global().increment(&GATEWAY_RATE_LIMIT_FALLBACKS, &[]);
+        }
+        self.local.acquire(limits, now_ms())
+    }
+}
+
+// every key has the same hash tag, so the ones of a request are in a single slot of a redis
+// cluster for the script. a tag of the application would split the limits of a consumer, they're
+// shared by all its applications.
+fn limit_keys<'a>(
+    rate_limits: &'a [Arc<ScopedRateLimit>],
+    client: &str,
+    consumer: Option<&str>,
+) -> Vec<(String, &'a RateLimit)> {
+    let mut limits = Vec::new();
+    for rate_limit in rate_limits {
+        for (index, limit) in rate_limit.policy.limits.iter().enumerate() {
+            let mut key = format!(
+                "{{ratelimit}}:{}:{}:{}:{}",
+                rate_limit.scope,
+                index,
+                limit.period,
+                limit.algorithm()
+            );
+            match (limit.by(), consumer) {
+                (RATE_LIMIT_BY_CONSUMER, Some(consumer)) => {
+                    key = format!("{}:{}", key, consumer);
+                }
+                (RATE_LIMIT_BY_CLIENT | RATE_LIMIT_BY_CONSUMER, _) => {
+                    key = format!("{}:{}", key, client);
+                }
+                _ => {}
+            }
+            limits.push((key, limit));
+        }
+    }
+    limits
+}
+
+// 429 with the api error as json, the caller adds the rate limit headers.
+pub fn rate_limited_error() -> ApiError {
+    ApiError::new_with_status(StatusCode::TOO_MANY_REQUESTS, FORWARD_ERR_RATE_LIMITED)
+}
+
+pub fn api_error_response(api_error: &ApiError) -> Response<Body> {
+    let status =
+        StatusCode::from_u16(api_error.status_code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::to_vec(api_error).unwrap_or_default(),
+        ))
+        .unwrap_or_default()
+}
+
+// seconds are rounded up, a client waiting what it's told isn't rejected again.
+pub fn add_rate_limit_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    let seconds =
+        |duration: Duration| HeaderValue::from(duration.as_millis().div_ceil(1000) as u64);
+    headers.insert(RATELIMIT_LIMIT, HeaderValue::from(decision.limit));
+    headers.insert(RATELIMIT_REMAINING, HeaderValue::from(decision.remaining));
+    headers.insert(RATELIMIT_RESET, seconds(decision.reset));
+    if let Some(retry_after) = decision.retry_after {
+        headers.insert(RETRY_AFTER, seconds(retry_after));
+    }
+}
+
+fn sliding_window(
+    limit: &RateLimit,
+    previous: i64,
+    current: i64,
+    elapsed: u64,
+) -> RateLimitDecision {
+    let period = period_ms(limit) as i64;
+    let elapsed = elapsed as i64;
+    // the weighted count is scaled by the period to stay in integers, as in the redis script.
+    let count = previous * (period - elapsed) + current * period;
+    let allowed = count + period <= limit.requests * period;
+    let remaining = (limit.requests * period - count - if allowed { period } else { 0 }) / period;
+
+    // enough of the previous window has to slide out, or the current one has to end and slide
+    // out in turn.
+    let retry_after = (!allowed).then(|| {
+        let slid = |requests: i64, count: i64| period - requests * period / count;
+        let wait = if current < limit.requests && previous > 0 {
+            (slid(limit.requests - current - 1, previous) - elapsed).max(1)
+        } else {
+            period - elapsed + slid(limit.requests - 1, current).max(0)
+        };
+        Duration::from_millis(wait as u64)
+    });
+
+    let reset = match (previous, current) {
+        (_, 1..) => 2 * period - elapsed,
+        (1.., _) => period - elapsed,
+        _ => 0,
+    };
+    RateLimitDecision {
+        allowed,
+        limit: limit.requests,
+        remaining: remaining.max(0),
+        reset: Duration::from_millis(reset as u64),
+        retry_after,
+    }
+}
+
+// the state of a limit in redis before the request, as the local counters decide on it.
+fn redis_decision(limit: &RateLimit, state: &[String]) -> RateLimitDecision {
+    let number = |index: usize| {
+        state
+            .get(index)
+            .and_then(|value| value.parse::<f64>().ok())
+            .unwrap_or_default()
+    };
+    if limit.algorithm() == RATE_LIMIT_ALGORITHM_TOKEN_BUCKET {
+        let tokens = number(0);
+        let allowed = tokens >= 1.0;
+        token_bucket(limit, allowed, if allowed { tokens - 1.0 } else { tokens })
+    } else {
+        sliding_window(limit, number(0) as i64, number(1) as i64, number(2) as u64)
+    }
+}
+
+fn token_bucket(limit: &RateLimit, allowed: bool, tokens: f64) -> RateLimitDecision {
+    let rate = limit.requests as f64 / period_ms(limit) as f64;
+    let millis = |tokens: f64| Duration::from_millis((tokens.max(0.0) / rate).ceil() as u64);
+    RateLimitDecision {
+        allowed,
+        limit: limit.burst(),
+        remaining: tokens.floor() as i64,
+        reset: millis(limit.burst() as f64 - tokens),
+        retry_after: (!allowed).then(|| millis(1.0 - tokens)),
+    }
+}
+
+fn refill(limit: &RateLimit, tokens: f64, elapsed: u64) -> f64 {
+    let refilled = elapsed as f64 * limit.requests as f64 / period_ms(limit) as f64;
+    (tokens + refilled).min(limit.burst() as f64)
+}
+
+fn period_ms(limit: &RateLimit) -> u64 {
+    limit.period().as_millis() as u64
+}
+
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_millis() as u64)
+}
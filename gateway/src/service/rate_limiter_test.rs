@@ -0,0 +1,283 @@
+use serde_json::json;
+
+use crate::model::{
+    RATE_LIMIT_ALGORITHM_SLIDING_WINDOW, RATE_LIMIT_PERIOD_MINUTE, RATE_LIMIT_PERIOD_SECOND,
+};
+
+use super::*;
+
+fn limit(requests: i64, algorithm: &str) -> RateLimit {
+    RateLimit {
+        requests,
+        period: String::from(RATE_LIMIT_PERIOD_SECOND),
+        algorithm: Some(String::from(algorithm)),
+        burst: None,
+        by: None,
+    }
+}
+
+fn acquire(counters: &LocalCounters, key: &str, limit: &RateLimit, now: u64) -> RateLimitDecision {
+    counters
+        .acquire(&[(String::from(key), limit)], now)
+        .remove(0)
+}
+
+fn scoped(scope: &str, policy: serde_json::Value) -> Arc<ScopedRateLimit> {
+    Arc::new(ScopedRateLimit {
+        scope: String::from(scope),
+        policy: RateLimitPolicy::parse(&policy).unwrap(),
+    })
+}
+
+#[test]
+fn sliding_window_counts_the_previous_window() {
+    let counters = LocalCounters::default();
+    let limit = limit(3, RATE_LIMIT_ALGORITHM_SLIDING_WINDOW);
+
+    let remaining: Vec<i64> = (0..3)
+        .map(|_| acquire(&counters, "key", &limit, 10_000).remaining)
+        .collect();
+    assert_eq!(vec![2, 1, 0], remaining);
+
+    let decision = acquire(&counters, "key", &limit, 10_000);
+    assert!(!decision.allowed);
+    assert_eq!(Some(Duration::from_millis(1_334)), decision.retry_after);
+
+    // half of the previous window still counts, 1.5 requests.
+    assert!(acquire(&counters, "key", &limit, 11_500).allowed);
+    let decision = acquire(&counters, "key", &limit, 11_500);
+    assert!(!decision.allowed);
+    assert_eq!(Some(Duration::from_millis(167)), decision.retry_after);
+    assert!(acquire(&counters, "key", &limit, 11_667).allowed);
+
+    // other keys have their own counters.
+    assert!(acquire(&counters, "other", &limit, 11_667).allowed);
+}
+
+#[test]
+fn sliding_window_retry_after_is_honored() {
+    let counters = LocalCounters::default();
+    let limit = limit(2, RATE_LIMIT_ALGORITHM_SLIDING_WINDOW);
+
+    acquire(&counters, "key", &limit, 20_000);
+    acquire(&counters, "key", &limit, 20_000);
+    let retry_after = acquire(&counters, "key", &limit, 20_000)
+        .retry_after
+        .unwrap();
+    assert_eq!(Duration::from_millis(1_500), retry_after);
+
+    assert!(!acquire(&counters, "key", &limit, 21_499).allowed);
+    assert!(acquire(&counters, "key", &limit, 21_500).allowed);
+}
+
+#[test]
+fn token_bucket_refills() {
+    let counters = LocalCounters::default();
+    let limit = RateLimit {
+        burst: Some(2),
+        ..limit(1, RATE_LIMIT_ALGORITHM_TOKEN_BUCKET)
+    };
+
+    // the burst is taken at once, then one token a second.
+    assert!(acquire(&counters, "key", &limit, 0).allowed);
+    let decision = acquire(&counters, "key", &limit, 0);
+    assert!(decision.allowed);
+    assert_eq!(2, decision.limit);
+    assert_eq!(0, decision.remaining);
+    assert_eq!(Duration::from_secs(2), decision.reset);
+
+    let decision = acquire(&counters, "key", &limit, 500);
+    assert!(!decision.allowed);
+    assert_eq!(Some(Duration::from_millis(500)), decision.retry_after);
+    assert!(acquire(&counters, "key", &limit, 1_000).allowed);
+    assert!(!acquire(&counters, "key", &limit, 1_000).allowed);
+}
+
+#[test]
+fn sweep_idle_counters() {
+    let counters = LocalCounters::default();
+    let limit = limit(1, RATE_LIMIT_ALGORITHM_SLIDING_WINDOW);
+
+    acquire(&counters, "idle", &limit, LOCAL_SWEEP_INTERVAL_MS);
+    acquire(&counters, "busy", &limit, 2 * LOCAL_SWEEP_INTERVAL_MS);
+    assert_eq!(1, counters.counters.lock().unwrap().len());
+}
+
+#[tokio::test]
+async fn check_every_scope() {
+    let rate_limiter = RateLimiter::local();
+    let rate_limits = vec![
+        scoped(
+            "application:1",
+            json!({"limits": [{"requests": 10, "period": RATE_LIMIT_PERIOD_MINUTE}]}),
+        ),
+        scoped(
+            "route:1",
+            json!({"limits": [{"requests": 1, "period": RATE_LIMIT_PERIOD_MINUTE, "by": "client"}]}),
+        ),
+    ];
+
+    // the route limit is the tighter one.
+    let decision = rate_limiter
+        .check(&rate_limits, "10.0.0.1", None)
+        .await
+        .unwrap();
+    assert!(decision.allowed);
+    assert_eq!(1, decision.limit);
+    assert_eq!(0, decision.remaining);
+    assert!(
+        !rate_limiter
+            .check(&rate_limits, "10.0.0.1", None)
+            .await
+            .unwrap()
+            .allowed
+    );
+
+    // each client has its own count on the route, the application counts the allowed requests.
+    let decision = rate_limiter
+        .check(&rate_limits, "10.0.0.2", None)
+        .await
+        .unwrap();
+    assert!(decision.allowed);
+    let decision = rate_limiter
+        .check(&rate_limits[..1], "10.0.0.3", None)
+        .await
+        .unwrap();
+    assert_eq!(7, decision.remaining);
+
+    assert!(rate_limiter.check(&[], "10.0.0.1", None).await.is_none());
+}
+
+#[tokio::test]
+async fn check_without_counting_a_rejected_request() {
+    let rate_limiter = RateLimiter::local();
+    let application = scoped(
+        "application:1",
+        json!({"limits": [{"requests": 2, "period": RATE_LIMIT_PERIOD_MINUTE}]}),
+    );
+    let route = scoped(
+        "route:1",
+        json!({"limits": [{"requests": 1, "period": RATE_LIMIT_PERIOD_MINUTE, "by": "client"}]}),
+    );
+    let rate_limits = vec![Arc::clone(&application), route];
+
+    assert!(
+        rate_limiter
+            .check(&rate_limits, "10.0.0.1", None)
+            .await
+            .unwrap()
+            .allowed
+    );
+    for _ in 0..3 {
+        assert!(
+            !rate_limiter
+                .check(&rate_limits, "10.0.0.1", None)
+                .await
+                .unwrap()
+                .allowed
+        );
+    }
+
+    // the rejections by the route took nothing from the application.
+    let decision = rate_limiter
+        .check(&[application], "10.0.0.2", None)
+        .await
+        .unwrap();
+    assert!(decision.allowed);
+    assert_eq!(0, decision.remaining);
+}
+
+#[tokio::test]
+async fn check_locally_without_redis() {
+    let rate_limiter = RateLimiter {
+        redis: Some(RedisCounters::new(
+            redis::Client::open("redis://127.0.0.1:1").unwrap(),
+            Duration::from_millis(100),
+        )),
+        local: LocalCounters::default(),
+    };
+    let rate_limits = vec![scoped(
+        "application:1",
+        json!({"limits": [{"requests": 1, "period": RATE_LIMIT_PERIOD_MINUTE}]}),
+    )];
+
+    assert!(
+        rate_limiter
+            .check(&rate_limits, "", None)
+            .await
+            .unwrap()
+            .allowed
+    );
+    assert!(
+        !rate_limiter
+            .check(&rate_limits, "", None)
+            .await
+            .unwrap()
+            .allowed
+    );
+
+    // redis isn't dialed again until the retry interval is over.
+    let failed_until = rate_limiter
+        .redis
+        .as_ref()
+        .unwrap()
+        .failed_until
+        .lock()
+        .unwrap()
+        .unwrap();
+    assert!(failed_until > Instant::now() + REDIS_RETRY_INTERVAL - Duration::from_secs(1));
+    let started = Instant::now();
+    rate_limiter.check(&rate_limits, "", None).await;
+    assert!(started.elapsed() < Duration::from_millis(50));
+}
+
+#[test]
+fn limit_keys_share_the_hash_tag() {
+    let rate_limits = vec![
+        scoped(
+            "application:1",
+            json!({"limits": [{"requests": 10, "period": RATE_LIMIT_PERIOD_MINUTE}]}),
+        ),
+        scoped(
+            "route:2",
+            json!({"limits": [{"requests": 1, "period": RATE_LIMIT_PERIOD_SECOND, "by": "client"}]}),
+        ),
+        scoped(
+            "consumer:7",
+            json!({"limits": [{"requests": 5, "period": RATE_LIMIT_PERIOD_MINUTE, "by": "consumer"}]}),
+        ),
+    ];
+
+    let keys: Vec<String> = limit_keys(&rate_limits, "10.0.0.1", Some("7"))
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+    assert_eq!(
+        vec![
+            "{ratelimit}:application:1:0:minute:sliding_window",
+            "{ratelimit}:route:2:0:second:sliding_window:10.0.0.1",
+            "{ratelimit}:consumer:7:0:minute:sliding_window:7",
+        ],
+        keys
+    );
+}
+
+#[test]
+fn rate_limit_headers() {
+    let mut headers = HeaderMap::new();
+    add_rate_limit_headers(
+        &mut headers,
+        &RateLimitDecision {
+            allowed: false,
+            limit: 10,
+            remaining: 0,
+            reset: Duration::from_millis(30_001),
+            retry_after: Some(Duration::from_millis(200)),
+        },
+    );
+
+    assert_eq!("10", headers["ratelimit-limit"]);
+    assert_eq!("0", headers["ratelimit-remaining"]);
+    assert_eq!("31", headers["ratelimit-reset"]);
+    assert_eq!("1", headers[RETRY_AFTER]);
+}
@@ -0,0 +1,119 @@
+#[cfg(test)]
+#[path = "retry_test.rs"]
+mod retry_test;
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use hyper::{header::HeaderName, HeaderMap, Method};
+use rand::Rng;
+
+use crate::model::RetryPolicy;
+
+const DEFAULT_RETRY_BUDGET_RATIO: f64 = 0.2;
+const DEFAULT_RETRY_BUDGET_MIN_RETRIES: u64 = 10;
+const RETRY_BUDGET_WINDOW: Duration = Duration::from_secs(10);
+
+// clients send it to make a non idempotent request safe to repeat.
+const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+
+pub fn retries_request(retry: &RetryPolicy, method: &Method, headers: &HeaderMap) -> bool {
+    retry.max_attempts > 1
+        && (retry.retries_method(method) || headers.contains_key(IDEMPOTENCY_KEY))
+}
+
+// "full jitter": a random wait up to the exponential backoff of the retry, the first one is 1.
+pub fn backoff(retry: &RetryPolicy, retry_number: u32) -> Duration {
+    let ceiling = retry
+        .base_backoff()
+        .saturating_mul(1 << retry_number.saturating_sub(1).min(20))
+        .min(retry.max_backoff())
+        .max(0) as u64;
+    Duration::from_millis(rand::thread_rng().gen_range(0..=ceiling))
+}
+
+struct BudgetWindow {
+    started: Instant,
+    requests: u64,
+    retries: u64,
+}
+
+// in every window, the retries of an application may only add a fraction of its requests (and
+// a few more, so that quiet applications can retry too): they can't multiply the load of an
+// upstream that is already failing.
+pub struct RetryBudget {
+    ratio: f64,
+    min_retries: u64,
+    window: Duration,
+    windows: Mutex<HashMap<i64, BudgetWindow>>,
+}
+
+impl RetryBudget {
+    pub fn config() -> RetryBudget {
+        RetryBudget::new(
+            std::env::var("RETRY_BUDGET_RATIO")
+                .ok()
+                .and_then(|ratio| ratio.parse().ok())
+                .unwrap_or(DEFAULT_RETRY_BUDGET_RATIO),
+            std::env::var("RETRY_BUDGET_MIN_RETRIES")
+                .ok()
+                .and_then(|min_retries| min_retries.parse().ok())
+                .unwrap_or(DEFAULT_RETRY_BUDGET_MIN_RETRIES),
+            RETRY_BUDGET_WINDOW,
+        )
+    }
+
+    pub fn new(ratio: f64, min_retries: u64, window: Duration) -> RetryBudget {
+        RetryBudget {
+            ratio,
+            min_retries,
+            window,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn record_request(&self, id_application: i64) {
+        self.update(id_application, |window| {
+            window.requests += 1;
+            true
+        });
+    }
+
+    // spends a retry when there's one left.
+    pub fn try_retry(&self, id_application: i64) -> bool {
+        let (ratio, min_retries) = (self.ratio, self.min_retries);
+        self.update(id_application, |window| {
+            let allowed = min_retries + (window.requests as f64 * ratio) as u64;
+            if window.retries < allowed {
+                window.retries += 1;
+                true
+            } else {
+                false
+            }
+        })
+    }
+
+    fn update(&self, id_application: i64, f: impl FnOnce(&mut BudgetWindow) -> bool) -> bool {
+        let mut windows = self.windows.lock().unwrap();
+        let window = windows
+            .entry(id_application)
+            .or_insert_with(|| BudgetWindow {
+                started: Instant::now(),
+                requests: 0,
+                retries: 0,
+            });
+
+        if window.started.elapsed() >= self.window {
+            *window = BudgetWindow {
+                started: Instant::now(),
+                requests: 0,
+                retries: 0,
+            };
+        }
+
+        f(window)
+    }
+}
@@ -0,0 +1,76 @@
+use hyper::header::HeaderValue;
+
+use super::*;
+
+fn retry_policy(max_attempts: i32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        base_backoff_ms: Some(100),
+        max_backoff_ms: Some(300),
+        status_codes: None,
+        methods: None,
+    }
+}
+
+#[test]
+fn retry_idempotent_requests() {
+    let mut headers = HeaderMap::new();
+    let retry = retry_policy(3);
+
+    assert!(retries_request(&retry, &Method::GET, &headers));
+    assert!(retries_request(&retry, &Method::PUT, &headers));
+    assert!(!retries_request(&retry, &Method::POST, &headers));
+    assert!(!retries_request(&retry_policy(1), &Method::GET, &headers));
+
+    headers.insert(IDEMPOTENCY_KEY, HeaderValue::from_static("b4c7"));
+    assert!(retries_request(&retry, &Method::POST, &headers));
+
+    let retry = RetryPolicy {
+        methods: Some(vec![String::from("POST")]),
+        ..retry_policy(3)
+    };
+    assert!(retries_request(&retry, &Method::POST, &HeaderMap::new()));
+    assert!(!retries_request(&retry, &Method::GET, &HeaderMap::new()));
+}
+
+#[test]
+fn backoff_with_jitter() {
+    let retry = retry_policy(5);
+
+    for _ in 0..100 {
+        assert!(backoff(&retry, 1) <= Duration::from_millis(100));
+        assert!(backoff(&retry, 2) <= Duration::from_millis(200));
+        assert!(backoff(&retry, 3) <= Duration::from_millis(300));
+        assert!(backoff(&retry, 30) <= Duration::from_millis(300));
+    }
+}
+
+#[test]
+fn spend_retry_budget() {
+    let budget = RetryBudget::new(0.5, 1, Duration::from_secs(60));
+
+    // the minimum is there without requests.
+    assert!(budget.try_retry(1));
+    assert!(!budget.try_retry(1));
+
+    for _ in 0..4 {
+        budget.record_request(1);
+    }
+    assert!(budget.try_retry(1));
+    assert!(budget.try_retry(1));
+    assert!(!budget.try_retry(1));
+
+    // every application has its own budget.
+    assert!(budget.try_retry(2));
+}
+
+#[test]
+fn renew_retry_budget() {
+    let budget = RetryBudget::new(0.0, 1, Duration::from_millis(20));
+
+    assert!(budget.try_retry(1));
+    assert!(!budget.try_retry(1));
+
+    std::thread::sleep(Duration::from_millis(30));
+    assert!(budget.try_retry(1));
+}
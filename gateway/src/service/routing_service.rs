@@ -0,0 +1,994 @@
+#[cfg(test)]
+#[path = "routing_service_test.rs"]
+mod routing_service_test;
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use arc_swap::ArcSwap;
+use axum::async_trait;
+use hyper::{header::HeaderName, StatusCode};
+use serde_json::Value;
+use sqlx::{postgres::PgListener, PgPool};
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+use crate::{
+    exception::{
+        ApiError, FORWARD_ERR_PATH_IS_REQUIRED, FORWARD_ERR_PATH_NOT_FOUND,
+        FORWARD_ERR_WORKFLOW_INACTIVE, TEMPLATE_ERR_REQUEST_REFERENCE,
+    },
+    model::{
+        ApiKeyAuth, Application, ApplicationOrchestration, ApplicationOrchestrationRoute,
+        ApplicationRoute, ApplicationWorkflow, AuthPolicy, PathPattern, RateLimitPolicy,
+        UpstreamPolicy,
+    },
+    notification::ROUTING_CHANNEL,
+    repository::{RoutingRepository, RoutingRepositoryTrait, RoutingRows},
+    secret::SecretCipher,
+    template::{Template, TEMPLATE_REQUEST},
+    transcoding::TranscodingDescriptor,
+};
+
+use super::{
+    orchestration_service::unresolved, remaining_path, ConsumerKeys, CredentialInjector,
+    PathMatcher, ScopedRateLimit, Transcoder, UpstreamBalancer,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ForwardTarget {
+    pub url_destination: String,
+    pub remaining_path: String,
+    pub id_application: i64,
+    pub preserve_host: bool,
+    pub streaming: bool,
+    pub policy: Arc<UpstreamPolicy>,
+    // picks the target that replaces the scheme and authority of the destination.
+    pub balancer: Option<Arc<UpstreamBalancer>>,
+    // the application ones, then the route ones.
+    pub rate_limits: Vec<Arc<ScopedRateLimit>>,
+    pub auth: Option<Arc<AuthPolicy>>,
+}
+
+// url, headers and body are templates parsed with the snapshot, rendered for each request.
+#[derive(Debug)]
+pub struct OrchestrationStep {
+    pub response_key: String,
+    pub url_destination: Template,
+    pub request_headers: Vec<(HeaderName, Template)>,
+    pub request_body: Option<Template>,
+    pub failure_policy: String,
+    pub fallback: Option<Value>,
+    pub timeout: Option<Duration>,
+    // the one of the route, for the connection, the response and the circuit of the upstream.
+    pub policy: Arc<UpstreamPolicy>,
+    pub balancer: Option<Arc<UpstreamBalancer>>,
+}
+
+#[derive(Debug)]
+pub struct RoutingOrchestration {
+    pub orchestration: ApplicationOrchestration,
+    pub id_application: i64,
+    pub steps: Vec<OrchestrationStep>,
+    pub rate_limits: Vec<Arc<ScopedRateLimit>>,
+    pub auth: Option<Arc<AuthPolicy>>,
+}
+
+impl RoutingOrchestration {
+    // steps without a body template receive the incoming body, only then it has to be buffered.
+    pub fn forwards_request_body(&self) -> bool {
+        self.steps.iter().any(|step| step.request_body.is_none())
+    }
+}
+
+#[derive(Debug)]
+pub enum RoutingTarget {
+    Forward(ForwardTarget),
+    Orchestrate {
+        orchestration: Arc<RoutingOrchestration>,
+        path_params: HashMap<String, String>,
+    },
+    Transcode {
+        target: ForwardTarget,
+        transcoder: Arc<Transcoder>,
+    },
+}
+
+impl RoutingTarget {
+    pub fn id_application(&self) -> i64 {
+        match self {
+            RoutingTarget::Forward(target) | RoutingTarget::Transcode { target, .. } => {
+                target.id_application
+            }
+            RoutingTarget::Orchestrate { orchestration, .. } => orchestration.id_application,
+        }
+    }
+
+    pub fn rate_limits(&self) -> &[Arc<ScopedRateLimit>] {
+        match self {
+            RoutingTarget::Forward(target) | RoutingTarget::Transcode { target, .. } => {
+                &target.rate_limits
+            }
+            RoutingTarget::Orchestrate { orchestration, .. } => &orchestration.rate_limits,
+        }
+    }
+
+    pub fn auth(&self) -> Option<&AuthPolicy> {
+        match self {
+            RoutingTarget::Forward(target) | RoutingTarget::Transcode { target, .. } => {
+                target.auth.as_deref()
+            }
+            RoutingTarget::Orchestrate { orchestration, .. } => orchestration.auth.as_deref(),
+        }
+    }
+}
+
+enum RoutingEntry {
+    // without a fixed number of segments, what comes after the matched ones is kept.
+    Forward {
+        url_destination: Template,
+        matched_segments: Option<usize>,
+        id_application: i64,
+        preserve_host: bool,
+        streaming: bool,
+        policy: Arc<UpstreamPolicy>,
+        balancer: Option<Arc<UpstreamBalancer>>,
+        rate_limits: Vec<Arc<ScopedRateLimit>>,
+        auth: Option<Arc<AuthPolicy>>,
+    },
+    WorkflowInactive,
+    Orchestrate(Arc<RoutingOrchestration>),
+    // the http rules are matched against what comes after the route path.
+    Transcode {
+        url_destination: Template,
+        id_application: i64,
+        transcoder: Arc<Transcoder>,
+        policy: Arc<UpstreamPolicy>,
+        balancer: Option<Arc<UpstreamBalancer>>,
+        rate_limits: Vec<Arc<ScopedRateLimit>>,
+        auth: Option<Arc<AuthPolicy>>,
+    },
+}
+
+fn parse_path(path: &str) -> Option<PathPattern> {
+    match PathPattern::parse(path) {
+        Ok(pattern) => Some(pattern),
+        Err(_) => {
+            tracing::error!("Ignoring the invalid path pattern {}", path);
+            None
+        }
+    }
+}
+
+fn parse_descriptor(route: &ApplicationRoute) -> Option<Option<Arc<Transcoder>>> {
+    match route
+        .grpc_descriptor
+        .as_deref()
+        .map(TranscodingDescriptor::decode)
+    {
+        None => Some(None),
+        Some(Ok(descriptor)) => Some(Some(Arc::new(Transcoder::new(descriptor)))),
+        Some(Err(_)) => {
+            tracing::error!("Ignoring the route {}, its descriptor is invalid", route.id);
+            None
+        }
+    }
+}
+
+// an invalid policy is dropped, the element inherits the one of its parent.
+fn parse_policy(element: &str, id: i64, policy: &Option<Value>) -> UpstreamPolicy {
+    match policy.as_ref().map(UpstreamPolicy::parse) {
+        Some(Ok(policy)) => policy,
+        Some(Err(e)) => {
+            tracing::error!(
+                "Ignoring the upstream policy of the {} {}: {}",
+                element,
+                id,
+                e
+            );
+            UpstreamPolicy::default()
+        }
+        None => UpstreamPolicy::default(),
+    }
+}
+
+// an invalid policy is dropped, the element has no limit of its own. it's validated like the
+// admin api does, a limit without requests can't be counted.
+pub(crate) fn parse_rate_limit(
+    element: &str,
+    id: i64,
+    policy: &Option<Value>,
+) -> Option<Arc<ScopedRateLimit>> {
+    let value = policy.as_ref()?;
+    let field_errors = RateLimitPolicy::validate(value, "rateLimit");
+    if !field_errors.is_empty() {
+        let fields: Vec<&str> = field_errors
+            .iter()
+            .map(|field_error| field_error.field.as_str())
+            .collect();
+        tracing::error!(
+            "Ignoring the rate limit of the {} {}, invalid {}",
+            element,
+            id,
+            fields.join(", ")
+        );
+        return None;
+    }
+
+    match RateLimitPolicy::parse(value) {
+        Ok(policy) if !policy.limits.is_empty() => Some(Arc::new(ScopedRateLimit {
+            scope: format!("{}:{}", element, id),
+            policy,
+        })),
+        _ => None,
+    }
+}
+
+// an invalid auth doesn't open the element, it requires an api key in the default header.
+fn parse_auth(element: &str, id: i64, auth: &Option<Value>) -> Option<Arc<AuthPolicy>> {
+    match auth.as_ref().map(AuthPolicy::parse) {
+        Some(Ok(auth)) => Some(Arc::new(auth)),
+        Some(Err(e)) => {
+            tracing::error!(
+                "Invalid auth of the {} {}, requiring an api key: {}",
+                element,
+                id,
+                e
+            );
+            Some(Arc::new(AuthPolicy {
+                api_key: Some(ApiKeyAuth::default()),
+                jwt: None,
+                introspection: None,
+                client_certificate: None,
+            }))
+        }
+        None => None,
+    }
+}
+
+// the secrets of an auth are opened with the snapshot. an auth whose secrets can't be opened is
+// left invalid, so it requires an api key.
+fn open_auth(
+    element: &str,
+    id: i64,
+    context: &str,
+    auth: &mut Option<Value>,
+    cipher: Option<&SecretCipher>,
+) {
+    if let Some(value) = auth.as_mut().filter(|value| AuthPolicy::has_secrets(value)) {
+        let opened = cipher.map(|cipher| AuthPolicy::open_secrets(value, context, cipher));
+        if !matches!(opened, Some(Ok(()))) {
+            tracing::error!(
+                "Error when opening the secrets of the auth of the {} {}",
+                element,
+                id
+            );
+            *auth = Some(Value::Null);
+        }
+    }
+}
+
+// the url, headers and body of a step, none when any of them is invalid.
+type StepTemplates = (Template, Vec<(HeaderName, Template)>, Option<Template>);
+
+fn parse_step_templates(
+    url_destination: &str,
+    request_headers: &[(String, String)],
+    request_body: Option<&str>,
+) -> Option<StepTemplates> {
+    let url_destination = Template::parse(url_destination).ok()?;
+    let request_headers = request_headers
+        .iter()
+        .map(|(name, value)| {
+            let name = HeaderName::try_from(name.as_str()).ok()?;
+            Some((name, Template::parse(value).ok()?))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let request_body = match request_body {
+        Some(request_body) => Some(Template::parse(request_body).ok()?),
+        None => None,
+    };
+    Some((url_destination, request_headers, request_body))
+}
+
+fn parse_destination(url_destination: &str) -> Option<Template> {
+    match Template::parse(url_destination) {
+        Ok(template) => Some(template),
+        Err(_) => {
+            tracing::error!("Ignoring the invalid destination {}", url_destination);
+            None
+        }
+    }
+}
+
+// the destination may reference the captured params, "http://{{request.path.tenant}}.com".
+fn render_destination(
+    url_destination: &Template,
+    path_params: &HashMap<String, String>,
+) -> Result<String, ApiError> {
+    url_destination.render(|reference| match reference {
+        [request, source, name] if request == TEMPLATE_REQUEST && source == "path" => {
+            path_params.get(name).cloned().ok_or_else(|| {
+                unresolved(
+                    StatusCode::BAD_REQUEST,
+                    TEMPLATE_ERR_REQUEST_REFERENCE,
+                    reference,
+                )
+            })
+        }
+        _ => Err(unresolved(
+            StatusCode::BAD_REQUEST,
+            TEMPLATE_ERR_REQUEST_REFERENCE,
+            reference,
+        )),
+    })
+}
+
+// immutable view of the routing tables, it's replaced as a whole when something changes.
+#[derive(Default)]
+pub struct RoutingSnapshot {
+    matcher: PathMatcher<RoutingEntry>,
+    applications: HashMap<i64, Arc<Application>>,
+    balancers: HashMap<i64, Arc<UpstreamBalancer>>,
+    consumers: ConsumerKeys,
+}
+
+impl RoutingSnapshot {
+    // every application, workflow, route and orchestration becomes a full path in the matcher.
+    // routes are inserted first so they win the ties against workflows and applications.
+    pub fn build(
+        applications: Vec<Application>,
+        workflows: Vec<ApplicationWorkflow>,
+        routes: Vec<ApplicationRoute>,
+        orchestrations: Vec<ApplicationOrchestration>,
+        orchestration_routes: Vec<ApplicationOrchestrationRoute>,
+        balancers: HashMap<i64, Arc<UpstreamBalancer>>,
+    ) -> RoutingSnapshot {
+        // the upstream of an application or route only balances its own destination.
+        let balancer =
+            |id_upstream: Option<i64>| id_upstream.and_then(|id| balancers.get(&id)).cloned();
+        let routes_by_id: HashMap<i64, &ApplicationRoute> =
+            routes.iter().map(|route| (route.id, route)).collect();
+        let workflows_by_id: HashMap<i64, &ApplicationWorkflow> = workflows
+            .iter()
+            .map(|workflow| (workflow.id, workflow))
+            .collect();
+
+        // policies are merged top-down, each element only overrides what it sets.
+        let application_policies: HashMap<i64, Arc<UpstreamPolicy>> = applications
+            .iter()
+            .map(|application| {
+                let policy =
+                    parse_policy("application", application.id, &application.upstream_policy);
+                (application.id, Arc::new(policy))
+            })
+            .collect();
+        let workflow_policies: HashMap<i64, Arc<UpstreamPolicy>> = workflows
+            .iter()
+            .map(|workflow| {
+                let application_policy = application_policies
+                    .get(&workflow.id_application)
+                    .cloned()
+                    .unwrap_or_default();
+                let policy = parse_policy("workflow", workflow.id, &workflow.upstream_policy)
+                    .merge(&application_policy);
+                (workflow.id, Arc::new(policy))
+            })
+            .collect();
+        let workflow_policy = |workflow: &ApplicationWorkflow| {
+            workflow_policies
+                .get(&workflow.id)
+                .cloned()
+                .unwrap_or_default()
+        };
+        let route_policy = |route: &ApplicationRoute| {
+            let workflow_policy = route
+                .id_application_workflow
+                .and_then(|id| workflow_policies.get(&id))
+                .cloned()
+                .unwrap_or_default();
+            match &route.upstream_policy {
+                Some(_) => Arc::new(
+                    parse_policy("route", route.id, &route.upstream_policy).merge(&workflow_policy),
+                ),
+                None => workflow_policy,
+            }
+        };
+
+        // steps keep the insertion order, it's the calling order of sequential orchestrations.
+        // an orchestration with an invalid template in any of its steps isn't served.
+        let mut steps_by_orchestration = HashMap::<i64, Vec<OrchestrationStep>>::new();
+        let mut invalid_orchestrations = HashSet::<i64>::new();
+        for orchestration_route in orchestration_routes {
+            if let Some(route) = routes_by_id.get(&orchestration_route.id_application_route) {
+                let url_destination = match &route.forward_to {
+                    Some(forward_to) => forward_to.clone(),
+                    None => match route
+                        .id_application_workflow
+                        .and_then(|id| workflows_by_id.get(&id))
+                    {
+                        Some(workflow) => format!(
+                            "{}{}",
+                            workflow.forward_to.trim_end_matches('/'),
+                            route.path
+                        ),
+                        None => continue,
+                    },
+                };
+
+                let request_headers = match orchestration_route.request_headers {
+                    Some(Value::Object(headers)) => headers
+                        .into_iter()
+                        .filter_map(|(name, value)| match value {
+                            Value::String(value) => Some((name, value)),
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                };
+
+                let templates = parse_step_templates(
+                    &url_destination,
+                    &request_headers,
+                    orchestration_route.request_body.as_deref(),
+                );
+                let (url_destination, request_headers, request_body) = match templates {
+                    Some(templates) => templates,
+                    None => {
+                        tracing::error!(
+                            "Ignoring the orchestration {}, the step {} has an invalid template",
+                            orchestration_route.id_application_orchestration,
+                            orchestration_route.id
+                        );
+                        invalid_orchestrations
+                            .insert(orchestration_route.id_application_orchestration);
+                        continue;
+                    }
+                };
+
+                steps_by_orchestration
+                    .entry(orchestration_route.id_application_orchestration)
+                    .or_default()
+                    .push(OrchestrationStep {
+                        response_key: orchestration_route.response_key,
+                        url_destination,
+                        request_headers,
+                        request_body,
+                        failure_policy: orchestration_route.failure_policy,
+                        fallback: orchestration_route.fallback,
+                        timeout: orchestration_route
+                            .timeout_ms
+                            .map(|timeout_ms| Duration::from_millis(timeout_ms as u64)),
+                        policy: route_policy(route),
+                        balancer: balancer(route.id_upstream),
+                    });
+            }
+        }
+
+        let application_paths: HashMap<i64, PathPattern> = applications
+            .iter()
+            .filter_map(|application| {
+                parse_path(&application.path).map(|pattern| (application.id, pattern))
+            })
+            .collect();
+
+        let workflow_paths: HashMap<i64, PathPattern> = workflows
+            .iter()
+            .filter_map(|workflow| {
+                let application_path = application_paths.get(&workflow.id_application)?;
+                let pattern = application_path.join(&parse_path(&workflow.path)?).ok()?;
+                Some((workflow.id, pattern))
+            })
+            .collect();
+
+        // workflows and routes follow the host option of their application.
+        let preserve_hosts: HashMap<i64, bool> = applications
+            .iter()
+            .map(|application| (application.id, application.preserve_host))
+            .collect();
+        let preserve_host = |workflow: &ApplicationWorkflow| {
+            preserve_hosts
+                .get(&workflow.id_application)
+                .copied()
+                .unwrap_or_default()
+        };
+
+        // the limits of the application apply to everything under it, a route adds its own.
+        let application_rate_limits: HashMap<i64, Arc<ScopedRateLimit>> = applications
+            .iter()
+            .filter_map(|application| {
+                let rate_limit =
+                    parse_rate_limit("application", application.id, &application.rate_limit)?;
+                Some((application.id, rate_limit))
+            })
+            .collect();
+        let rate_limits = |id_application: i64, route: Option<&ApplicationRoute>| {
+            application_rate_limits
+                .get(&id_application)
+                .cloned()
+                .into_iter()
+                .chain(
+                    route.and_then(|route| parse_rate_limit("route", route.id, &route.rate_limit)),
+                )
+                .collect::<Vec<_>>()
+        };
+
+        // workflows and orchestrations follow the auth of their application, a route replaces it.
+        let application_auths: HashMap<i64, Arc<AuthPolicy>> = applications
+            .iter()
+            .filter_map(|application| {
+                let auth = parse_auth("application", application.id, &application.auth)?;
+                Some((application.id, auth))
+            })
+            .collect();
+        let auth = |id_application: i64, route: Option<&ApplicationRoute>| match route
+            .and_then(|route| parse_auth("route", route.id, &route.auth))
+        {
+            Some(auth) => Some(auth),
+            None => application_auths.get(&id_application).cloned(),
+        };
+
+        let mut matcher = PathMatcher::default();
+
+        for route in &routes {
+            let workflow = match route
+                .id_application_workflow
+                .and_then(|id| workflows_by_id.get(&id))
+            {
+                Some(workflow) if workflow.is_active() => workflow,
+                _ => continue,
+            };
+
+            let workflow_path = match workflow_paths.get(&workflow.id) {
+                Some(workflow_path) => workflow_path,
+                None => continue,
+            };
+
+            let pattern = match parse_path(&route.path)
+                .and_then(|route_path| workflow_path.join(&route_path).ok())
+            {
+                Some(pattern) => pattern,
+                None => {
+                    tracing::error!("Ignoring the route {}, its path can't be matched", route.id);
+                    continue;
+                }
+            };
+
+            // without its own forward_to the route path is kept after the workflow destination.
+            let (url_destination, matched_segments) = match &route.forward_to {
+                Some(forward_to) => (forward_to, None),
+                None => (&workflow.forward_to, Some(workflow_path.len())),
+            };
+
+            let policy = route_policy(route);
+
+            let destination = parse_destination(url_destination).zip(parse_descriptor(route));
+            if let Some((url_destination, transcoder)) = destination {
+                let entry = match transcoder {
+                    Some(transcoder) => RoutingEntry::Transcode {
+                        url_destination,
+                        id_application: workflow.id_application,
+                        transcoder,
+                        policy,
+                        balancer: balancer(route.id_upstream),
+                        rate_limits: rate_limits(workflow.id_application, Some(route)),
+                        auth: auth(workflow.id_application, Some(route)),
+                    },
+                    None => RoutingEntry::Forward {
+                        url_destination,
+                        matched_segments,
+                        id_application: workflow.id_application,
+                        preserve_host: preserve_host(workflow),
+                        streaming: route.streaming,
+                        policy,
+                        balancer: balancer(route.id_upstream),
+                        rate_limits: rate_limits(workflow.id_application, Some(route)),
+                        auth: auth(workflow.id_application, Some(route)),
+                    },
+                };
+                matcher.insert(&pattern, false, route.priority, entry);
+            }
+        }
+
+        // an orchestration answers only its exact path.
+        for orchestration in orchestrations {
+            let workflow = match workflows_by_id.get(&orchestration.id_application_workflow) {
+                Some(workflow) if workflow.is_active() => workflow,
+                _ => continue,
+            };
+            if invalid_orchestrations.contains(&orchestration.id) {
+                continue;
+            }
+
+            let pattern = workflow_paths
+                .get(&workflow.id)
+                .zip(parse_path(&orchestration.path))
+                .and_then(|(workflow_path, path)| workflow_path.join(&path).ok());
+
+            if let Some(pattern) = pattern {
+                let steps = steps_by_orchestration
+                    .remove(&orchestration.id)
+                    .unwrap_or_default();
+                matcher.insert(
+                    &pattern,
+                    true,
+                    0,
+                    RoutingEntry::Orchestrate(Arc::new(RoutingOrchestration {
+                        orchestration,
+                        id_application: workflow.id_application,
+                        steps,
+                        rate_limits: rate_limits(workflow.id_application, None),
+                        auth: auth(workflow.id_application, None),
+                    })),
+                );
+            }
+        }
+
+        for workflow in &workflows {
+            let pattern = match workflow_paths.get(&workflow.id) {
+                Some(pattern) => pattern,
+                None => continue,
+            };
+
+            // nothing under an inactive workflow is reachable.
+            let entry = if workflow.is_active() {
+                match parse_destination(&workflow.forward_to) {
+                    Some(url_destination) => RoutingEntry::Forward {
+                        url_destination,
+                        matched_segments: None,
+                        id_application: workflow.id_application,
+                        preserve_host: preserve_host(workflow),
+                        streaming: false,
+                        policy: workflow_policy(workflow),
+                        balancer: None,
+                        rate_limits: rate_limits(workflow.id_application, None),
+                        auth: auth(workflow.id_application, None),
+                    },
+                    None => continue,
+                }
+            } else {
+                RoutingEntry::WorkflowInactive
+            };
+
+            matcher.insert(pattern, false, workflow.priority, entry);
+        }
+
+        for application in &applications {
+            let pattern = application_paths.get(&application.id);
+            let url_destination = parse_destination(&application.url_destination);
+
+            if let Some((pattern, url_destination)) = pattern.zip(url_destination) {
+                matcher.insert(
+                    pattern,
+                    false,
+                    0,
+                    RoutingEntry::Forward {
+                        url_destination,
+                        matched_segments: None,
+                        id_application: application.id,
+                        preserve_host: application.preserve_host,
+                        streaming: false,
+                        policy: application_policies
+                            .get(&application.id)
+                            .cloned()
+                            .unwrap_or_default(),
+                        balancer: balancer(application.id_upstream),
+                        rate_limits: rate_limits(application.id, None),
+                        auth: auth(application.id, None),
+                    },
+                );
+            }
+        }
+
+        let applications = applications
+            .into_iter()
+            .filter(|application| application_paths.contains_key(&application.id))
+            .map(|application| (application.id, Arc::new(application)))
+            .collect();
+
+        RoutingSnapshot {
+            matcher,
+            applications,
+            balancers,
+            consumers: ConsumerKeys::default(),
+        }
+    }
+
+    pub fn with_consumers(mut self, consumers: ConsumerKeys) -> RoutingSnapshot {
+        self.consumers = consumers;
+        self
+    }
+
+    // the matched prefix is replaced by the forward_to of the most specific element that
+    // matches: route, then workflow, then the application itself.
+    pub fn resolve(&self, path: &str) -> Result<RoutingTarget, ApiError> {
+        if path.split('/').all(|segment| segment.trim().is_empty()) {
+            return Err(ApiError::new(FORWARD_ERR_PATH_IS_REQUIRED));
+        }
+
+        let path_match = self.matcher.find(path).ok_or_else(|| {
+            ApiError::new_with_status(StatusCode::NOT_FOUND, FORWARD_ERR_PATH_NOT_FOUND)
+        })?;
+
+        match path_match.value {
+            RoutingEntry::Forward {
+                url_destination,
+                matched_segments,
+                id_application,
+                preserve_host,
+                streaming,
+                policy,
+                balancer,
+                rate_limits,
+                auth,
+            } => Ok(RoutingTarget::Forward(ForwardTarget {
+                url_destination: render_destination(url_destination, &path_match.params)?,
+                remaining_path: remaining_path(
+                    path,
+                    matched_segments.unwrap_or(path_match.matched_segments),
+                )
+                .to_owned(),
+                id_application: *id_application,
+                preserve_host: *preserve_host,
+                streaming: *streaming,
+                policy: Arc::clone(policy),
+                balancer: balancer.clone(),
+                rate_limits: rate_limits.clone(),
+                auth: auth.clone(),
+            })),
+            RoutingEntry::WorkflowInactive => Err(ApiError::new_with_status(
+                StatusCode::SERVICE_UNAVAILABLE,
+                FORWARD_ERR_WORKFLOW_INACTIVE,
+            )),
+            RoutingEntry::Transcode {
+                url_destination,
+                id_application,
+                transcoder,
+                policy,
+                balancer,
+                rate_limits,
+                auth,
+            } => Ok(RoutingTarget::Transcode {
+                target: ForwardTarget {
+                    url_destination: render_destination(url_destination, &path_match.params)?,
+                    remaining_path: remaining_path(path, path_match.matched_segments).to_owned(),
+                    id_application: *id_application,
+                    preserve_host: false,
+                    streaming: false,
+                    policy: Arc::clone(policy),
+                    balancer: balancer.clone(),
+                    rate_limits: rate_limits.clone(),
+                    auth: auth.clone(),
+                },
+                transcoder: Arc::clone(transcoder),
+            }),
+            RoutingEntry::Orchestrate(orchestration) => Ok(RoutingTarget::Orchestrate {
+                orchestration: Arc::clone(orchestration),
+                path_params: path_match.params,
+            }),
+        }
+    }
+
+    pub fn find_application(&self, id: i64) -> Option<Arc<Application>> {
+        self.applications.get(&id).cloned()
+    }
+
+    pub fn balancers(&self) -> impl Iterator<Item = &Arc<UpstreamBalancer>> {
+        self.balancers.values()
+    }
+
+    pub fn consumers(&self) -> &ConsumerKeys {
+        &self.consumers
+    }
+
+    // number of applications.
+    pub fn len(&self) -> usize {
+        self.applications.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.applications.is_empty()
+    }
+
+    // number of matchable paths, applications included.
+    pub fn paths(&self) -> usize {
+        self.matcher.len()
+    }
+}
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait RoutingServiceTrait {
+    fn snapshot(&self) -> Arc<RoutingSnapshot>;
+
+    // the credentials of the upstreams of the routing table, opened with its key.
+    fn credential_injector(&self) -> Arc<CredentialInjector>;
+
+    async fn reload(&self) -> Result<(), ApiError>;
+}
+
+pub struct RoutingService {
+    routing_repository: Arc<dyn RoutingRepositoryTrait + Send + Sync>,
+    snapshot: ArcSwap<RoutingSnapshot>,
+    cipher: Option<SecretCipher>,
+    credential_injector: Arc<CredentialInjector>,
+}
+
+#[async_trait]
+impl RoutingServiceTrait for RoutingService {
+    fn snapshot(&self) -> Arc<RoutingSnapshot> {
+        self.snapshot.load_full()
+    }
+
+    fn credential_injector(&self) -> Arc<CredentialInjector> {
+        Arc::clone(&self.credential_injector)
+    }
+
+    async fn reload(&self) -> Result<(), ApiError> {
+        let RoutingRows {
+            mut applications,
+            workflows,
+            mut routes,
+            orchestrations,
+            orchestration_routes,
+            upstreams,
+            upstream_targets: targets,
+            consumers,
+            consumer_api_keys: api_keys,
+        } = self.routing_repository.find_routing_rows().await?;
+
+        for application in &mut applications {
+            let context = Application::auth_sealing_context(application.id);
+            open_auth(
+                "application",
+                application.id,
+                &context,
+                &mut application.auth,
+                self.cipher.as_ref(),
+            );
+        }
+        for route in &mut routes {
+            let context = ApplicationRoute::auth_sealing_context(route.id);
+            open_auth(
+                "route",
+                route.id,
+                &context,
+                &mut route.auth,
+                self.cipher.as_ref(),
+            );
+        }
+
+        let balancers =
+            UpstreamBalancer::build_all(upstreams, &targets, &self.snapshot.load().balancers);
+        // the credentials of the upstreams that are gone aren't kept.
+        self.credential_injector
+            .retain(|id_upstream| balancers.contains_key(&id_upstream));
+        let snapshot = RoutingSnapshot::build(
+            applications,
+            workflows,
+            routes,
+            orchestrations,
+            orchestration_routes,
+            balancers,
+        )
+        .with_consumers(ConsumerKeys::build(consumers, api_keys));
+        tracing::info!(
+            "routing table loaded with {} applications, {} paths and {} api keys",
+            snapshot.len(),
+            snapshot.paths(),
+            snapshot.consumers().len()
+        );
+
+        self.snapshot.store(Arc::new(snapshot));
+        Ok(())
+    }
+}
+
+pub(crate) enum RoutingEvent {
+    Changed,
+    Reconnected,
+}
+
+impl RoutingService {
+    pub fn new(pg_pool: Arc<PgPool>) -> Self {
+        RoutingService::new_with_repo(Arc::new(RoutingRepository { pg_pool }))
+    }
+
+    pub fn new_with_repo(repository: Arc<dyn RoutingRepositoryTrait + Send + Sync>) -> Self {
+        RoutingService::new_with_cipher(repository, SecretCipher::config())
+    }
+
+    pub fn new_with_cipher(
+        repository: Arc<dyn RoutingRepositoryTrait + Send + Sync>,
+        cipher: Option<SecretCipher>,
+    ) -> Self {
+        RoutingService {
+            routing_repository: repository,
+            snapshot: ArcSwap::from_pointee(RoutingSnapshot::default()),
+            credential_injector: Arc::new(CredentialInjector::new(cipher.clone())),
+            cipher,
+        }
+    }
+
+    // keeps the snapshot up to date: reloads when the admin api notifies a change, when the
+    // listener reconnects (notifications may have been lost) and periodically as a safety net.
+    pub fn listen(routing_service: Arc<RoutingService>, pg_pool: Arc<PgPool>) {
+        let reload_interval = Duration::from_secs(
+            std::env::var("ROUTING_RELOAD_INTERVAL")
+                .unwrap_or(String::from("60"))
+                .parse()
+                .unwrap_or(60),
+        );
+
+        let (sender, mut receiver) = mpsc::unbounded_channel::<RoutingEvent>();
+        tokio::spawn(RoutingService::receive_notifications(pg_pool, sender));
+
+        tokio::spawn(async move {
+            loop {
+                match tokio::time::timeout(reload_interval, receiver.recv()).await {
+                    Ok(Some(event)) => {
+                        if let RoutingEvent::Reconnected = event {
+                            tracing::info!("routing listener (re)connected");
+                        }
+
+                        // coalesces a burst of changes into a single reload.
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        while receiver.try_recv().is_ok() {}
+                    }
+                    Ok(None) => break,
+                    Err(_) => tracing::debug!("reloading routing table periodically"),
+                }
+
+                if let Err(e) = routing_service.reload().await {
+                    tracing::error!("Error when reloading the routing table: {}", e);
+                }
+            }
+        });
+    }
+
+    pub(crate) async fn receive_notifications(
+        pg_pool: Arc<PgPool>,
+        sender: UnboundedSender<RoutingEvent>,
+    ) {
+        loop {
+            match PgListener::connect_with(&pg_pool).await {
+                Ok(mut listener) => {
+                    if let Err(e) = listener.listen(ROUTING_CHANNEL).await {
+                        tracing::error!("Error when listening routing notifications: {}", e);
+                    } else if sender.send(RoutingEvent::Reconnected).is_ok() {
+                        loop {
+                            let event = match listener.try_recv().await {
+                                Ok(Some(notification)) => {
+                                    tracing::debug!(
+                                        "routing notification received: {}",
+                                        notification.payload()
+                                    );
+                                    RoutingEvent::Changed
+                                }
+                                Ok(None) => RoutingEvent::Reconnected,
+                                Err(e) => {
+                                    tracing::error!(
+                                        "Error when receiving routing notifications: {}",
+                                        e
+                                    );
+                                    break;
+                                }
+                            };
+
+                            if sender.send(event).is_err() {
+                                return;
+                            }
+                        }
+                    } else {
+                        return;
+                    }
+                }
+                Err(e) => tracing::error!("Error when connecting the routing listener: {}", e),
+            }
+
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+}
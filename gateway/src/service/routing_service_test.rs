@@ -0,0 +1,813 @@
+use chrono::Utc;
+use hyper::Method;
+
+use crate::{
+    exception::{ROUTING_ERR_LOADING, TEMPLATE_ERR_REQUEST_REFERENCE},
+    model::{
+        ApiKeyAuth, RetryPolicy, Upstream, UpstreamTarget, ALGORITHM_ROUND_ROBIN,
+        FAILURE_POLICY_FAIL, ORCHESTRATION_TYPE_PARALLEL, WORKFLOW_STATUS_INACTIVE,
+    },
+    repository::MockRoutingRepositoryTrait,
+};
+
+use super::*;
+
+const GREETER: &[u8] = include_bytes!("../../../common/fixtures/greeter.pb");
+
+fn application(id: i64, path: &str) -> Application {
+    Application {
+        id,
+        name: String::from("Teste"),
+        path: String::from(path),
+        url_destination: String::from("http://anothergtw.com"),
+        preserve_host: false,
+        websocket_max_connections: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+fn workflow(id: i64, id_application: i64, path: &str) -> ApplicationWorkflow {
+    ApplicationWorkflow {
+        id,
+        id_application,
+        path: String::from(path),
+        forward_to: format!("http://workflow{}.anothergtw.com", id),
+        status: String::from("ACTIVE"),
+        priority: 0,
+        upstream_policy: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+fn route(id: i64, id_application_workflow: Option<i64>, path: &str) -> ApplicationRoute {
+    ApplicationRoute {
+        id,
+        id_application_workflow,
+        path: String::from(path),
+        forward_to: None,
+        priority: 0,
+        streaming: false,
+        grpc_descriptor: None,
+        upstream_policy: None,
+        id_upstream: None,
+        rate_limit: None,
+        auth: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+fn orchestration(id: i64, id_application_workflow: i64, path: &str) -> ApplicationOrchestration {
+    ApplicationOrchestration {
+        id,
+        id_application_workflow,
+        path: String::from(path),
+        orchestration_type: String::from(ORCHESTRATION_TYPE_PARALLEL),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+fn orchestration_route(
+    id: i64,
+    id_application_orchestration: i64,
+    id_application_route: i64,
+    response_key: &str,
+) -> ApplicationOrchestrationRoute {
+    ApplicationOrchestrationRoute {
+        id,
+        id_application_orchestration,
+        id_application_route,
+        response_key: String::from(response_key),
+        request_headers: None,
+        request_body: None,
+        failure_policy: String::from(FAILURE_POLICY_FAIL),
+        fallback: None,
+        timeout_ms: None,
+    }
+}
+
+fn target(url_destination: &str, remaining_path: &str) -> ForwardTarget {
+    ForwardTarget {
+        url_destination: String::from(url_destination),
+        remaining_path: String::from(remaining_path),
+        id_application: 1,
+        preserve_host: false,
+        streaming: false,
+        policy: Arc::default(),
+        balancer: None,
+        rate_limits: Vec::new(),
+        auth: None,
+    }
+}
+
+fn resolve_forward(snapshot: &RoutingSnapshot, path: &str) -> ForwardTarget {
+    match snapshot.resolve(path).unwrap() {
+        RoutingTarget::Forward(target) => target,
+        _ => panic!("{} did not resolve to a forward", path),
+    }
+}
+
+#[test]
+fn build_snapshot() {
+    let mut invalid = route(4, Some(1), "/**/items");
+    invalid.forward_to = Some(String::from("http://anothergtw.com"));
+
+    let snapshot = RoutingSnapshot::build(
+        vec![application(1, "/orders"), application(2, "/users")],
+        vec![
+            workflow(1, 1, "/v1"),
+            workflow(2, 1, "/v1/beta"),
+            workflow(3, 99, "/v1"),
+        ],
+        vec![
+            route(1, Some(1), "/items"),
+            route(2, Some(2), "/items"),
+            route(3, None, "/orphan"),
+            invalid,
+        ],
+        Vec::new(),
+        Vec::new(),
+        HashMap::new(),
+    );
+
+    assert_eq!(2, snapshot.len());
+    // routes without a workflow, workflows without an application and invalid paths are left out.
+    assert_eq!(6, snapshot.paths());
+
+    let users = resolve_forward(&snapshot, "/users/orphan");
+    assert_eq!(2, users.id_application);
+    assert_eq!("/orphan", users.remaining_path);
+    assert_eq!("/users", snapshot.find_application(2).unwrap().path);
+}
+
+#[test]
+fn resolve() {
+    let mut legacy = route(2, Some(1), "/legacy");
+    legacy.forward_to = Some(String::from("http://legacy.anothergtw.com/items"));
+
+    let snapshot = RoutingSnapshot::build(
+        vec![application(1, "/orders")],
+        vec![workflow(1, 1, "/v1"), workflow(2, 1, "/v1/beta")],
+        vec![route(1, Some(1), "/items"), legacy],
+        Vec::new(),
+        Vec::new(),
+        HashMap::new(),
+    );
+
+    assert_eq!(
+        target("http://workflow1.anothergtw.com", "/items/1"),
+        resolve_forward(&snapshot, "/orders/v1/items/1")
+    );
+    assert_eq!(
+        target("http://legacy.anothergtw.com/items", "/1"),
+        resolve_forward(&snapshot, "/orders/v1/legacy/1")
+    );
+    assert_eq!(
+        target("http://workflow2.anothergtw.com", "/items"),
+        resolve_forward(&snapshot, "/orders/v1/beta/items")
+    );
+    assert_eq!(
+        target("http://workflow1.anothergtw.com", ""),
+        resolve_forward(&snapshot, "/orders/v1")
+    );
+    assert_eq!(
+        target("http://anothergtw.com", "/v10/items"),
+        resolve_forward(&snapshot, "/orders/v10/items")
+    );
+}
+
+#[test]
+fn resolve_orchestration() {
+    let mut legacy = route(2, Some(1), "/legacy");
+    legacy.forward_to = Some(String::from("http://legacy.anothergtw.com/items"));
+
+    let snapshot = RoutingSnapshot::build(
+        vec![application(1, "/orders")],
+        vec![workflow(1, 1, "/v1")],
+        vec![route(1, Some(1), "/items"), legacy],
+        vec![orchestration(1, 1, "/home")],
+        vec![
+            orchestration_route(2, 1, 2, "legacy"),
+            orchestration_route(1, 1, 1, "items"),
+            orchestration_route(3, 1, 99, "unknown"),
+        ],
+        HashMap::new(),
+    );
+
+    match snapshot.resolve("/orders/v1/home/").unwrap() {
+        RoutingTarget::Orchestrate { orchestration, .. } => {
+            assert_eq!(1, orchestration.orchestration.id);
+            assert_eq!(2, orchestration.steps.len());
+            assert_eq!("legacy", orchestration.steps[0].response_key);
+            assert_eq!(
+                Template::parse("http://legacy.anothergtw.com/items").unwrap(),
+                orchestration.steps[0].url_destination
+            );
+            assert_eq!("items", orchestration.steps[1].response_key);
+            assert_eq!(
+                Template::parse("http://workflow1.anothergtw.com/items").unwrap(),
+                orchestration.steps[1].url_destination
+            );
+        }
+        _ => panic!("expected an orchestration"),
+    }
+
+    // only the exact path is orchestrated.
+    assert_eq!(
+        target("http://workflow1.anothergtw.com", "/home/1"),
+        resolve_forward(&snapshot, "/orders/v1/home/1")
+    );
+}
+
+#[test]
+fn resolve_orchestration_with_invalid_template() {
+    let mut invalid_body = orchestration_route(2, 1, 1, "broken");
+    invalid_body.request_body = Some(String::from("{{user.id"));
+    let mut invalid_header = orchestration_route(3, 2, 1, "header");
+    invalid_header.request_headers = Some(serde_json::json!({"x customer": "1"}));
+
+    let snapshot = RoutingSnapshot::build(
+        vec![application(1, "/orders")],
+        vec![workflow(1, 1, "/v1")],
+        vec![route(1, Some(1), "/items")],
+        vec![
+            orchestration(1, 1, "/home"),
+            orchestration(2, 1, "/summary"),
+        ],
+        vec![
+            orchestration_route(1, 1, 1, "items"),
+            invalid_body,
+            invalid_header,
+        ],
+        HashMap::new(),
+    );
+
+    // the orchestrations are left out when the snapshot is built, their paths are forwarded.
+    assert_eq!(
+        target("http://workflow1.anothergtw.com", "/home"),
+        resolve_forward(&snapshot, "/orders/v1/home")
+    );
+    assert_eq!(
+        target("http://workflow1.anothergtw.com", "/summary"),
+        resolve_forward(&snapshot, "/orders/v1/summary")
+    );
+}
+
+#[test]
+fn resolve_orchestration_with_path_params() {
+    let mut with_headers = orchestration_route(1, 2, 1, "orders");
+    with_headers.timeout_ms = Some(250);
+    with_headers.request_headers = Some(serde_json::json!({"x-customer": "{{request.path.id}}"}));
+
+    let snapshot = RoutingSnapshot::build(
+        vec![application(1, "/orders")],
+        vec![workflow(1, 1, "/v1")],
+        vec![route(1, Some(1), "/items")],
+        vec![
+            orchestration(2, 1, "/customers/:id/summary"),
+            orchestration(1, 1, "/customers/me/summary"),
+        ],
+        vec![with_headers],
+        HashMap::new(),
+    );
+
+    match snapshot.resolve("/orders/v1/customers/42/summary").unwrap() {
+        RoutingTarget::Orchestrate {
+            orchestration,
+            path_params,
+        } => {
+            assert_eq!(2, orchestration.orchestration.id);
+            assert_eq!(Some(&String::from("42")), path_params.get("id"));
+            assert_eq!(
+                vec![(
+                    HeaderName::from_static("x-customer"),
+                    Template::parse("{{request.path.id}}").unwrap()
+                )],
+                orchestration.steps[0].request_headers
+            );
+            assert_eq!(
+                Some(Duration::from_millis(250)),
+                orchestration.steps[0].timeout
+            );
+        }
+        _ => panic!("expected an orchestration"),
+    }
+
+    // literal paths win over params.
+    match snapshot.resolve("/orders/v1/customers/me/summary").unwrap() {
+        RoutingTarget::Orchestrate {
+            orchestration,
+            path_params,
+        } => {
+            assert_eq!(1, orchestration.orchestration.id);
+            assert!(path_params.is_empty());
+        }
+        _ => panic!("expected an orchestration"),
+    }
+
+    assert_eq!(
+        target("http://workflow1.anothergtw.com", "/customers/42"),
+        resolve_forward(&snapshot, "/orders/v1/customers/42")
+    );
+}
+
+#[test]
+fn resolve_with_path_patterns() {
+    let mut tenant = workflow(1, 1, "/:tenant");
+    tenant.forward_to = String::from("http://{{request.path.tenant}}.anothergtw.com/api");
+
+    let mut files = route(1, Some(1), "/files/**path");
+    files.forward_to = Some(String::from(
+        "http://files.anothergtw.com/{{request.path.path}}",
+    ));
+
+    let mut by_id = route(2, Some(1), "/users/:id(\\d+)");
+    by_id.forward_to = Some(String::from(
+        "http://users.anothergtw.com/v2/users/{{request.path.id}}",
+    ));
+
+    let mut by_name = route(3, Some(1), "/users/:name");
+    by_name.priority = 10;
+
+    let mut by_login = route(4, Some(1), "/users/:login");
+    by_login.forward_to = Some(String::from("http://login.anothergtw.com"));
+
+    let snapshot = RoutingSnapshot::build(
+        vec![application(1, "/orders")],
+        vec![tenant],
+        vec![files, by_id, by_name, by_login],
+        Vec::new(),
+        Vec::new(),
+        HashMap::new(),
+    );
+
+    assert_eq!(
+        target("http://files.anothergtw.com/2022/report.pdf", ""),
+        resolve_forward(&snapshot, "/orders/acme/files/2022/report.pdf")
+    );
+    assert_eq!(
+        target("http://users.anothergtw.com/v2/users/42", "/orders"),
+        resolve_forward(&snapshot, "/orders/acme/users/42/orders")
+    );
+    // params without a regex, the explicit priority breaks the tie.
+    assert_eq!(
+        target("http://acme.anothergtw.com/api", "/users/ana"),
+        resolve_forward(&snapshot, "/orders/acme/users/ana")
+    );
+    assert_eq!(
+        target("http://acme.anothergtw.com/api", "/items"),
+        resolve_forward(&snapshot, "/orders/acme/items")
+    );
+}
+
+#[test]
+fn resolve_with_unknown_reference() {
+    let mut tenant = workflow(1, 1, "/v1");
+    tenant.forward_to = String::from("http://{{request.path.tenant}}.anothergtw.com");
+
+    let snapshot = RoutingSnapshot::build(
+        vec![application(1, "/orders")],
+        vec![tenant],
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        HashMap::new(),
+    );
+
+    let response = snapshot.resolve("/orders/v1/items");
+    assert!(response.is_err());
+
+    let api_error = response.unwrap_err();
+    assert_eq!(400, api_error.status_code);
+    assert_eq!(TEMPLATE_ERR_REQUEST_REFERENCE.0, api_error.code);
+}
+
+#[test]
+fn resolve_inactive_workflow() {
+    let mut inactive = workflow(2, 1, "/v2");
+    inactive.status = String::from(WORKFLOW_STATUS_INACTIVE);
+
+    let snapshot = RoutingSnapshot::build(
+        vec![application(1, "/orders")],
+        vec![workflow(1, 1, "/v1"), inactive],
+        vec![route(1, Some(2), "/items")],
+        Vec::new(),
+        Vec::new(),
+        HashMap::new(),
+    );
+
+    let response = snapshot.resolve("/orders/v2/items");
+    assert!(response.is_err());
+
+    let api_error = response.unwrap_err();
+    assert_eq!(503, api_error.status_code);
+    assert_eq!(FORWARD_ERR_WORKFLOW_INACTIVE.0, api_error.code);
+
+    assert!(snapshot.resolve("/orders/v1/items").is_ok());
+}
+
+#[test]
+fn resolve_with_preserve_host() {
+    let mut orders = application(1, "/orders");
+    orders.preserve_host = true;
+
+    let snapshot = RoutingSnapshot::build(
+        vec![orders, application(2, "/users")],
+        vec![workflow(1, 1, "/v1")],
+        vec![route(1, Some(1), "/items")],
+        Vec::new(),
+        Vec::new(),
+        HashMap::new(),
+    );
+
+    assert!(resolve_forward(&snapshot, "/orders/v1/items").preserve_host);
+    assert!(resolve_forward(&snapshot, "/orders/v1").preserve_host);
+    assert!(resolve_forward(&snapshot, "/orders").preserve_host);
+    assert!(!resolve_forward(&snapshot, "/users").preserve_host);
+}
+
+#[test]
+fn resolve_not_found() {
+    let snapshot = RoutingSnapshot::build(
+        vec![application(1, "/orders")],
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        HashMap::new(),
+    );
+
+    let response = snapshot.resolve("/users/1");
+    assert!(response.is_err());
+    assert_eq!(FORWARD_ERR_PATH_NOT_FOUND.0, response.unwrap_err().code);
+
+    let response = snapshot.resolve("/");
+    assert!(response.is_err());
+    assert_eq!(FORWARD_ERR_PATH_IS_REQUIRED.0, response.unwrap_err().code);
+}
+
+#[tokio::test]
+async fn reload() {
+    let mut mock_repo = MockRoutingRepositoryTrait::new();
+    mock_repo.expect_find_routing_rows().returning(|| {
+        Ok(RoutingRows {
+            applications: vec![application(1, "/orders")],
+            workflows: vec![workflow(1, 1, "/v1")],
+            ..RoutingRows::default()
+        })
+    });
+
+    let service = RoutingService::new_with_repo(Arc::new(mock_repo));
+    assert!(service.snapshot().is_empty());
+
+    let old_snapshot = service.snapshot();
+    let response = service.reload().await;
+    assert!(response.is_ok());
+
+    let snapshot = service.snapshot();
+    assert_eq!(1, snapshot.len());
+    assert!(snapshot.resolve("/orders").is_ok());
+    assert!(old_snapshot.is_empty());
+}
+
+#[tokio::test]
+async fn reload_with_repository_error() {
+    let mut mock_repo = MockRoutingRepositoryTrait::new();
+    let mut sequence = mockall::Sequence::new();
+    mock_repo
+        .expect_find_routing_rows()
+        .times(1)
+        .in_sequence(&mut sequence)
+        .returning(|| {
+            Ok(RoutingRows {
+                applications: vec![application(1, "/orders")],
+                ..RoutingRows::default()
+            })
+        });
+    mock_repo
+        .expect_find_routing_rows()
+        .times(1)
+        .in_sequence(&mut sequence)
+        .returning(|| Err(ApiError::new(ROUTING_ERR_LOADING)));
+
+    let service = RoutingService::new_with_repo(Arc::new(mock_repo));
+    assert!(service.reload().await.is_ok());
+
+    let response = service.reload().await;
+    assert!(response.is_err());
+    assert_eq!(ROUTING_ERR_LOADING.0, response.unwrap_err().code);
+
+    // the last valid snapshot keeps being served.
+    assert!(service.snapshot().resolve("/orders").is_ok());
+}
+
+#[tokio::test]
+async fn reload_opens_the_auth_secrets() {
+    let cipher = SecretCipher::from_base64("MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=").unwrap();
+    let sealing_cipher = cipher.clone();
+    let sealed_application = move |id: i64, path: &str, context: &str| {
+        let mut auth = serde_json::json!({"jwt": {"algorithms": ["HS256"], "secret": "s3cr3t"}});
+        AuthPolicy::seal_secrets(&mut auth, context, &sealing_cipher).unwrap();
+        Application {
+            auth: Some(auth),
+            ..application(id, path)
+        }
+    };
+
+    let mut mock_repo = MockRoutingRepositoryTrait::new();
+    mock_repo.expect_find_routing_rows().returning(move || {
+        let context = Application::auth_sealing_context(1);
+        Ok(RoutingRows {
+            applications: vec![
+                sealed_application(1, "/orders", &context),
+                // sealed for another row, it can't be opened.
+                sealed_application(2, "/users", &context),
+            ],
+            ..RoutingRows::default()
+        })
+    });
+
+    let service = RoutingService::new_with_cipher(Arc::new(mock_repo), Some(cipher.clone()));
+    assert!(service.reload().await.is_ok());
+
+    let snapshot = service.snapshot();
+    let orders = snapshot.resolve("/orders").unwrap();
+    let jwt = orders.auth().unwrap().jwt.as_ref().unwrap();
+    assert_eq!(Some("s3cr3t"), jwt.secret.as_deref());
+
+    let users = snapshot.resolve("/users").unwrap();
+    let auth = users.auth().unwrap();
+    assert!(auth.jwt.is_none());
+    assert_eq!(Some(ApiKeyAuth::default()), auth.api_key);
+}
+
+#[test]
+fn parse_invalid_rate_limit() {
+    let rate_limit = parse_rate_limit(
+        "route",
+        1,
+        &Some(serde_json::json!({"limits": [{"requests": 10, "period": "minute"}]})),
+    );
+    assert_eq!("route:1", rate_limit.unwrap().scope);
+
+    // a limit without requests or burst would divide by zero.
+    for limit in [
+        serde_json::json!({"requests": 0, "period": "minute"}),
+        serde_json::json!({"requests": 10, "period": "minute", "algorithm": "token_bucket", "burst": 0}),
+        serde_json::json!({"requests": 10, "period": "day"}),
+    ] {
+        let policy = Some(serde_json::json!({ "limits": [limit] }));
+        assert!(parse_rate_limit("route", 1, &policy).is_none());
+    }
+    assert!(parse_rate_limit("route", 1, &Some(serde_json::json!({"limits": []}))).is_none());
+    assert!(parse_rate_limit("route", 1, &None).is_none());
+}
+
+#[test]
+fn resolve_with_streaming() {
+    let mut events = route(2, Some(1), "/events");
+    events.streaming = true;
+
+    let snapshot = RoutingSnapshot::build(
+        vec![application(1, "/orders")],
+        vec![workflow(1, 1, "/v1")],
+        vec![route(1, Some(1), "/items"), events],
+        Vec::new(),
+        Vec::new(),
+        HashMap::new(),
+    );
+
+    assert!(resolve_forward(&snapshot, "/orders/v1/events").streaming);
+    assert!(!resolve_forward(&snapshot, "/orders/v1/items").streaming);
+    assert!(!resolve_forward(&snapshot, "/orders/v1").streaming);
+    assert!(!resolve_forward(&snapshot, "/orders").streaming);
+}
+
+#[test]
+fn resolve_with_grpc_descriptor() {
+    let mut greeter = route(2, Some(1), "/greeter");
+    greeter.grpc_descriptor = Some(GREETER.to_vec());
+    let mut invalid = route(3, Some(1), "/invalid");
+    invalid.grpc_descriptor = Some(b"not a descriptor".to_vec());
+
+    let snapshot = RoutingSnapshot::build(
+        vec![application(1, "/orders")],
+        vec![workflow(1, 1, "/v1")],
+        vec![route(1, Some(1), "/items"), greeter, invalid],
+        Vec::new(),
+        Vec::new(),
+        HashMap::new(),
+    );
+
+    match snapshot.resolve("/orders/v1/greeter/hello/ana").unwrap() {
+        RoutingTarget::Transcode {
+            target: forward,
+            transcoder,
+        } => {
+            assert_eq!(
+                target("http://workflow1.anothergtw.com", "/hello/ana"),
+                forward
+            );
+            assert!(transcoder
+                .find(&Method::GET, &forward.remaining_path)
+                .is_ok());
+        }
+        _ => panic!("expected a transcoding"),
+    }
+
+    // a route with an invalid descriptor set is left out.
+    assert_eq!(
+        target("http://workflow1.anothergtw.com", "/invalid"),
+        resolve_forward(&snapshot, "/orders/v1/invalid")
+    );
+}
+
+#[test]
+fn resolve_with_upstream_policy() {
+    let mut application = application(1, "/orders");
+    application.upstream_policy = Some(serde_json::json!({
+        "connectTimeoutMs": 1000,
+        "responseTimeoutMs": 5000,
+        "retry": {"maxAttempts": 3}
+    }));
+    let mut workflow = workflow(1, 1, "/v1");
+    workflow.upstream_policy = Some(serde_json::json!({"responseTimeoutMs": 2000}));
+    let mut items = route(1, Some(1), "/items");
+    items.upstream_policy = Some(serde_json::json!({
+        "totalTimeoutMs": 3000,
+        "retry": {"maxAttempts": 2, "methods": ["GET"]}
+    }));
+    let mut invalid = route(2, Some(1), "/invalid");
+    invalid.upstream_policy = Some(serde_json::json!({"retries": 3}));
+
+    let snapshot = RoutingSnapshot::build(
+        vec![application],
+        vec![workflow],
+        vec![items, invalid],
+        vec![orchestration(1, 1, "/home")],
+        vec![
+            orchestration_route(1, 1, 1, "items"),
+            orchestration_route(2, 1, 2, "invalid"),
+        ],
+        HashMap::new(),
+    );
+
+    let retry = |max_attempts: i32, methods: Option<Vec<String>>| RetryPolicy {
+        max_attempts,
+        base_backoff_ms: None,
+        max_backoff_ms: None,
+        status_codes: None,
+        methods,
+    };
+
+    // each element overrides only what it sets.
+    assert_eq!(
+        UpstreamPolicy {
+            connect_timeout_ms: Some(1000),
+            response_timeout_ms: Some(2000),
+            total_timeout_ms: Some(3000),
+            retry: Some(retry(2, Some(vec![String::from("GET")]))),
+            circuit_breaker: None,
+        },
+        *resolve_forward(&snapshot, "/orders/v1/items").policy
+    );
+
+    // an invalid policy is ignored, the one of the workflow applies.
+    let workflow_policy = UpstreamPolicy {
+        connect_timeout_ms: Some(1000),
+        response_timeout_ms: Some(2000),
+        total_timeout_ms: None,
+        retry: Some(retry(3, None)),
+        circuit_breaker: None,
+    };
+    assert_eq!(
+        workflow_policy,
+        *resolve_forward(&snapshot, "/orders/v1/invalid").policy
+    );
+    assert_eq!(
+        workflow_policy,
+        *resolve_forward(&snapshot, "/orders/v1/customers").policy
+    );
+    assert_eq!(
+        Some(5000),
+        resolve_forward(&snapshot, "/orders/customers")
+            .policy
+            .response_timeout_ms
+    );
+
+    // the steps of an orchestration follow the policy of their route.
+    match snapshot.resolve("/orders/v1/home").unwrap() {
+        RoutingTarget::Orchestrate { orchestration, .. } => {
+            assert_eq!(Some(3000), orchestration.steps[0].policy.total_timeout_ms);
+            assert_eq!(workflow_policy, *orchestration.steps[1].policy);
+        }
+        _ => panic!("expected an orchestration"),
+    }
+}
+
+#[test]
+fn resolve_with_upstream() {
+    let upstream = |id: i64| Upstream {
+        id,
+        name: format!("upstream{}", id),
+        algorithm: String::from(ALGORITHM_ROUND_ROBIN),
+        hash_on: None,
+        hash_key: None,
+        health_check: None,
+        credentials_type: None,
+        credentials: None,
+        tls: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    };
+    let balancers = UpstreamBalancer::build_all(
+        vec![upstream(1), upstream(2)],
+        &Vec::<UpstreamTarget>::new(),
+        &HashMap::new(),
+    );
+
+    let mut application = application(1, "/orders");
+    application.id_upstream = Some(1);
+    let mut items = route(1, Some(1), "/items");
+    items.id_upstream = Some(2);
+
+    let snapshot = RoutingSnapshot::build(
+        vec![application],
+        vec![workflow(1, 1, "/v1")],
+        vec![items, route(2, Some(1), "/customers")],
+        Vec::new(),
+        Vec::new(),
+        balancers,
+    );
+
+    let balancer = |path: &str| {
+        resolve_forward(&snapshot, path)
+            .balancer
+            .map(|balancer| balancer.id())
+    };
+
+    // the upstream of an element doesn't apply to the ones under it.
+    assert_eq!(Some(1), balancer("/orders/customers"));
+    assert_eq!(None, balancer("/orders/v1/customers"));
+    assert_eq!(Some(2), balancer("/orders/v1/items"));
+}
+
+#[test]
+fn resolve_with_auth() {
+    let mut application = application(1, "/orders");
+    application.auth = Some(serde_json::json!({"apiKey": {"header": "x-orders-key"}}));
+    let mut public = route(1, Some(1), "/public");
+    public.auth = Some(serde_json::json!({}));
+    let mut query = route(2, Some(1), "/query");
+    query.auth = Some(serde_json::json!({"apiKey": {"query": "key"}}));
+    let mut invalid = route(3, Some(1), "/invalid");
+    invalid.auth = Some(serde_json::json!({"basic": {}}));
+
+    let snapshot = RoutingSnapshot::build(
+        vec![application],
+        vec![workflow(1, 1, "/v1")],
+        vec![public, query, invalid],
+        Vec::new(),
+        Vec::new(),
+        HashMap::new(),
+    );
+
+    let api_key = |target: &ForwardTarget| target.auth.as_ref().unwrap().api_key.clone();
+
+    // the workflow follows its application, a route replaces it.
+    let application_key = Some(ApiKeyAuth {
+        header: Some(String::from("x-orders-key")),
+        query: None,
+    });
+    assert_eq!(
+        application_key,
+        api_key(&resolve_forward(&snapshot, "/orders/items"))
+    );
+    assert_eq!(
+        application_key,
+        api_key(&resolve_forward(&snapshot, "/orders/v1/items"))
+    );
+    assert_eq!(
+        None,
+        api_key(&resolve_forward(&snapshot, "/orders/v1/public"))
+    );
+    assert_eq!(
+        Some(String::from("key")),
+        api_key(&resolve_forward(&snapshot, "/orders/v1/query"))
+            .unwrap()
+            .query
+    );
+
+    // an invalid auth still requires a key.
+    assert_eq!(
+        Some(ApiKeyAuth::default()),
+        api_key(&resolve_forward(&snapshot, "/orders/v1/invalid"))
+    );
+}
@@ -0,0 +1,95 @@
+#[cfg(test)]
+#[path = "streaming_test.rs"]
+mod streaming_test;
+
+use std::time::Duration;
+
+use futures::{stream, StreamExt};
+use hyper::{header::CONTENT_TYPE, Body, HeaderMap};
+use tokio::time::Instant;
+
+const DEFAULT_PROXY_IDLE_TIMEOUT: u64 = 60;
+const DEFAULT_STREAMING_IDLE_TIMEOUT: u64 = 3600;
+
+const EVENT_STREAM: &str = "text/event-stream";
+
+fn timeout_from_env(name: &str, default: u64) -> Duration {
+    Duration::from_secs(
+        std::env::var(name)
+            .ok()
+            .and_then(|timeout| timeout.parse().ok())
+            .unwrap_or(default),
+    )
+}
+
+// longest wait for the response headers or the next chunk of a response.
+pub fn proxy_idle_timeout() -> Duration {
+    timeout_from_env("PROXY_IDLE_TIMEOUT", DEFAULT_PROXY_IDLE_TIMEOUT)
+}
+
+// same for server-sent events and long-poll routes, where the upstream may stay quiet for long.
+pub fn streaming_idle_timeout() -> Duration {
+    timeout_from_env("STREAMING_IDLE_TIMEOUT", DEFAULT_STREAMING_IDLE_TIMEOUT)
+}
+
+// "text/event-stream; charset=utf-8".
+pub fn is_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|value| value.trim().eq_ignore_ascii_case(EVENT_STREAM))
+}
+
+#[derive(Debug)]
+pub struct IdleTimeout(Duration);
+
+impl std::fmt::Display for IdleTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no data received for {:?}", self.0)
+    }
+}
+
+impl std::error::Error for IdleTimeout {}
+
+#[derive(Debug)]
+pub struct DeadlineExceeded;
+
+impl std::fmt::Display for DeadlineExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "total timeout exceeded")
+    }
+}
+
+impl std::error::Error for DeadlineExceeded {}
+
+// every chunk is handed over as soon as it arrives, the body fails when the upstream stays
+// quiet for longer than the timeout.
+pub fn with_idle_timeout(body: Body, timeout: Duration) -> Body {
+    with_body_timeouts(body, timeout, None)
+}
+
+// same, the body also fails when it isn't over at the deadline.
+pub fn with_body_timeouts(body: Body, timeout: Duration, deadline: Option<Instant>) -> Body {
+    let chunks = stream::unfold(Some(body), move |body| async move {
+        let mut body = body?;
+        let wait = deadline.map_or(timeout, |deadline| {
+            timeout.min(deadline.saturating_duration_since(Instant::now()))
+        });
+        match tokio::time::timeout(wait, body.next()).await {
+            Ok(Some(Ok(chunk))) => Some((Ok(chunk), Some(body))),
+            Ok(Some(Err(e))) => Some((Err(Box::new(e) as Box<dyn std::error::Error + Send + Sync>), None)),
+            Ok(None) => None,
+            Err(_) if wait < timeout => {
+                tracing::warn!("closing a response still incomplete at its deadline");
+                Some((Err(Box::new(DeadlineExceeded) as _), None))
+            }
+            Err(_) => {
+                tracing::warn!("closing a response idle for {:?}", timeout);
+                Some((Err(Box::new(IdleTimeout(timeout)) as _), None))
+            }
+        }
+    });
+
+    Body::wrap_stream(chunks)
+}
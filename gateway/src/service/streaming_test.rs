@@ -0,0 +1,80 @@
+use hyper::{body::Bytes, header::HeaderValue};
+
+use super::*;
+
+#[test]
+fn detect_event_stream() {
+    let mut headers = HeaderMap::new();
+    assert!(!is_event_stream(&headers));
+
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("Text/Event-Stream; charset=utf-8"),
+    );
+    assert!(is_event_stream(&headers));
+
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    assert!(!is_event_stream(&headers));
+}
+
+#[tokio::test]
+async fn forward_chunks_as_they_arrive() {
+    let (mut sender, body) = Body::channel();
+    let mut body = with_idle_timeout(body, Duration::from_secs(5));
+
+    sender
+        .send_data(Bytes::from("data: first\n\n"))
+        .await
+        .unwrap();
+    assert_eq!(
+        Bytes::from("data: first\n\n"),
+        body.next().await.unwrap().unwrap()
+    );
+
+    sender
+        .send_data(Bytes::from("data: second\n\n"))
+        .await
+        .unwrap();
+    assert_eq!(
+        Bytes::from("data: second\n\n"),
+        body.next().await.unwrap().unwrap()
+    );
+
+    drop(sender);
+    assert!(body.next().await.is_none());
+}
+
+#[tokio::test]
+async fn close_when_idle() {
+    let (mut sender, body) = Body::channel();
+    let mut body = with_idle_timeout(body, Duration::from_millis(50));
+
+    sender
+        .send_data(Bytes::from("data: first\n\n"))
+        .await
+        .unwrap();
+    assert!(body.next().await.unwrap().is_ok());
+
+    let chunk = body.next().await.unwrap();
+    assert!(chunk.is_err());
+    assert!(body.next().await.is_none());
+    drop(sender);
+}
+
+#[tokio::test]
+async fn close_at_deadline() {
+    let (mut sender, body) = Body::channel();
+    let deadline = Instant::now() + Duration::from_millis(100);
+    let mut body = with_body_timeouts(body, Duration::from_secs(5), Some(deadline));
+
+    sender
+        .send_data(Bytes::from("data: first\n\n"))
+        .await
+        .unwrap();
+    assert!(body.next().await.unwrap().is_ok());
+
+    let error = body.next().await.unwrap().unwrap_err();
+    assert!(Instant::now() >= deadline);
+    assert!(error.into_cause().unwrap().is::<DeadlineExceeded>());
+    assert!(body.next().await.is_none());
+}
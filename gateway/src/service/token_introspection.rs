@@ -0,0 +1,254 @@
+#[cfg(test)]
+#[path = "token_introspection_test.rs"]
+mod token_introspection_test;
+
+use std::{
+    collections::{HashMap, VecDeque},
+    error::Error,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use axum::http::Request;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hyper::{
+    header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
+    Body,
+};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde_json::{Map, Value};
+use tokio::time::Instant;
+
+use crate::{
+    config::{HttpClient, HttpsClient},
+    model::{
+        hash_api_key, IntrospectionAuth, RateLimit, RATE_LIMIT_ALGORITHM_TOKEN_BUCKET,
+        RATE_LIMIT_PERIOD_SECOND,
+    },
+};
+
+use super::{has_claim, now_ms, LocalCounters};
+
+const INTROSPECTION_TIMEOUT: Duration = Duration::from_secs(5);
+// the oldest results are dropped when the cache grows past this.
+const MAX_CACHED_INTROSPECTIONS: usize = 10_000;
+// tokens that aren't cached are introspected at most this often by endpoint and client, the
+// others are rejected as if the endpoint had failed.
+const MAX_INTROSPECTIONS_PER_SECOND: i64 = 50;
+const MAX_INTROSPECTIONS_BURST: i64 = 100;
+
+struct CachedIntrospection {
+    // none when the token isn't active.
+    claims: Option<Arc<Map<String, Value>>>,
+    expires_at: Instant,
+    // tells the current result of a key from the ones it replaced in the order.
+    sequence: u64,
+}
+
+// results in the order they were stored, the order may still have keys replaced or dropped since.
+#[derive(Default)]
+struct IntrospectionCache {
+    results: HashMap<String, CachedIntrospection>,
+    order: VecDeque<(String, u64)>,
+    sequence: u64,
+}
+
+// results of the introspections by endpoint, client and hash of the token, the tokens themselves
+// aren't kept.
+pub struct TokenIntrospector {
+    client: HttpsClient,
+    cache: Mutex<IntrospectionCache>,
+    misses: LocalCounters,
+    max_cached: usize,
+    misses_limit: RateLimit,
+}
+
+impl Default for TokenIntrospector {
+    fn default() -> Self {
+        TokenIntrospector::new(
+            MAX_CACHED_INTROSPECTIONS,
+            MAX_INTROSPECTIONS_PER_SECOND,
+            MAX_INTROSPECTIONS_BURST,
+        )
+    }
+}
+
+impl TokenIntrospector {
+    pub fn new(max_cached: usize, misses_per_second: i64, misses_burst: i64) -> Self {
+        TokenIntrospector {
+            client: HttpClient::config(),
+            cache: Mutex::default(),
+            misses: LocalCounters::default(),
+            max_cached,
+            misses_limit: RateLimit {
+                requests: misses_per_second,
+                period: String::from(RATE_LIMIT_PERIOD_SECOND),
+                algorithm: Some(String::from(RATE_LIMIT_ALGORITHM_TOKEN_BUCKET)),
+                burst: Some(misses_burst),
+                by: None,
+            },
+        }
+    }
+
+    // the fields of an active token. the error is the reason of the failure, "invalid",
+    // "forbidden" when the token lacks a scope or "unavailable" when the endpoint has failed,
+    // which isn't cached.
+    pub async fn introspect(
+        &self,
+        introspection: &IntrospectionAuth,
+        token: &str,
+    ) -> Result<Arc<Map<String, Value>>, &'static str> {
+        let endpoint = format!("{}:{}", introspection.url, introspection.client_id);
+        let key = format!("{}:{}", endpoint, hash_api_key(token));
+        let now = Instant::now();
+        let cached = self
+            .cache
+            .lock()
+            .unwrap()
+            .results
+            .get(&key)
+            .filter(|cached| cached.expires_at > now)
+            .map(|cached| cached.claims.clone());
+
+        let claims = match cached {
+            Some(claims) => claims,
+            None => {
+                let allowed = self
+                    .misses
+                    .acquire(&[(endpoint, &self.misses_limit)], now_ms())
+                    .iter()
+                    .all(|decision| decision.allowed);
+                if !allowed {
+                    tracing::warn!(
+                        "Too many tokens to introspect at {}, rejecting them",
+                        introspection.url
+                    );
+                    return Err("unavailable");
+                }
+
+                let claims = self.request(introspection, token).await.map_err(|e| {
+                    tracing::error!(
+                        "Error when introspecting a token at {}: {}",
+                        introspection.url,
+                        e
+                    );
+                    "unavailable"
+                })?;
+
+                let claims = Some(claims).filter(is_active).map(Arc::new);
+                let ttl = match &claims {
+                    Some(claims) => expires_in(claims)
+                        .map_or(introspection.cache(), |expires_in| {
+                            expires_in.min(introspection.cache())
+                        }),
+                    None => introspection.negative_cache(),
+                };
+                self.store(key, claims.clone(), now + ttl);
+                claims
+            }
+        };
+
+        let claims = claims.ok_or("invalid")?;
+        if !introspection.audiences.is_empty()
+            && !introspection
+                .audiences
+                .iter()
+                .any(|audience| has_claim(&claims, "aud", &Value::String(audience.clone())))
+        {
+            return Err("invalid");
+        }
+
+        if !introspection
+            .scopes
+            .iter()
+            .all(|scope| has_claim(&claims, "scope", &Value::String(scope.clone())))
+        {
+            return Err("forbidden");
+        }
+
+        Ok(claims)
+    }
+
+    // the client credentials go in the basic authorization, form encoded as rfc 6749 asks.
+    async fn request(
+        &self,
+        introspection: &IntrospectionAuth,
+        token: &str,
+    ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+        let credentials = STANDARD.encode(format!(
+            "{}:{}",
+            utf8_percent_encode(&introspection.client_id, NON_ALPHANUMERIC),
+            utf8_percent_encode(&introspection.client_secret, NON_ALPHANUMERIC)
+        ));
+        let body =
+            serde_urlencoded::to_string([("token", token), ("token_type_hint", "access_token")])?;
+
+        let request = Request::post(&introspection.url)
+            .header(AUTHORIZATION, format!("Basic {}", credentials))
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .header(ACCEPT, "application/json")
+            .body(Body::from(body))?;
+        let response =
+            tokio::time::timeout(INTROSPECTION_TIMEOUT, self.client.request(request)).await??;
+        if !response.status().is_success() {
+            return Err(format!("the endpoint answered {}", response.status()).into());
+        }
+
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    // the oldest results make room for the new one, whether they have expired or not.
+    fn store(&self, key: String, claims: Option<Arc<Map<String, Value>>>, expires_at: Instant) {
+        let mut cache = self.cache.lock().unwrap();
+        let IntrospectionCache {
+            results,
+            order,
+            sequence,
+        } = &mut *cache;
+
+        results.remove(&key);
+        while results.len() >= self.max_cached {
+            match order.pop_front() {
+                Some((oldest, stored)) => {
+                    if results
+                        .get(&oldest)
+                        .is_some_and(|cached| cached.sequence == stored)
+                    {
+                        results.remove(&oldest);
+                    }
+                }
+                None => break,
+            }
+        }
+        if order.len() >= 2 * self.max_cached {
+            order.retain(|(key, stored)| {
+                results
+                    .get(key)
+                    .is_some_and(|cached| cached.sequence == *stored)
+            });
+        }
+
+        *sequence += 1;
+        order.push_back((key.clone(), *sequence));
+        results.insert(
+            key,
+            CachedIntrospection {
+                claims,
+                expires_at,
+                sequence: *sequence,
+            },
+        );
+    }
+}
+
+// endpoints may answer active for a token that has just expired.
+fn is_active(claims: &Map<String, Value>) -> bool {
+    claims.get("active") == Some(&Value::Bool(true)) && expires_in(claims) != Some(Duration::ZERO)
+}
+
+fn expires_in(claims: &Map<String, Value>) -> Option<Duration> {
+    let exp = claims.get("exp")?.as_u64()?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+    Some(Duration::from_secs(exp.saturating_sub(now)))
+}
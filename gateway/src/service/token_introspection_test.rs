@@ -0,0 +1,263 @@
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Response, StatusCode,
+};
+use serde_json::json;
+
+use super::*;
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+// answers for the client "gateway" with the secret "s3cr3t:!", other credentials are refused.
+// "active" and "expired" tokens are active, "broken" fails, anything else is inactive. every
+// introspection is counted.
+async fn start_introspection_server() -> (SocketAddr, Arc<AtomicUsize>) {
+    let introspections = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&introspections);
+    let make_service = make_service_fn(move |_| {
+        let introspections = Arc::clone(&introspections);
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                let introspections = Arc::clone(&introspections);
+                async move {
+                    introspections.fetch_add(1, Ordering::SeqCst);
+                    let credentials = format!("Basic {}", STANDARD.encode("gateway:s3cr3t%3A%21"));
+                    if req.headers().get(AUTHORIZATION).unwrap() != credentials.as_str() {
+                        return Ok::<_, Infallible>(
+                            Response::builder()
+                                .status(StatusCode::UNAUTHORIZED)
+                                .body(Body::empty())
+                                .unwrap(),
+                        );
+                    }
+
+                    let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+                    let form: HashMap<String, String> =
+                        serde_urlencoded::from_bytes(&body).unwrap();
+                    let response = match form["token"].as_str() {
+                        "active" => json!({
+                            "active": true,
+                            "sub": "user-1",
+                            "scope": "orders:read orders:write",
+                            "aud": ["orders", "payments"],
+                            "exp": now() + 600,
+                        }),
+                        "expired" => json!({"active": true, "sub": "user-1", "exp": now() - 10}),
+                        "broken" => {
+                            return Ok(Response::builder()
+                                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                                .body(Body::empty())
+                                .unwrap())
+                        }
+                        _ => json!({"active": false}),
+                    };
+                    Ok(Response::new(Body::from(response.to_string())))
+                }
+            }))
+        }
+    });
+
+    let server = hyper::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    (addr, counter)
+}
+
+fn introspection(addr: SocketAddr) -> IntrospectionAuth {
+    IntrospectionAuth {
+        url: format!("http://{}/oauth2/introspect", addr),
+        client_id: String::from("gateway"),
+        client_secret: String::from("s3cr3t:!"),
+        ..IntrospectionAuth::default()
+    }
+}
+
+#[tokio::test]
+async fn introspect_active_token() {
+    let (addr, introspections) = start_introspection_server().await;
+    let introspector = TokenIntrospector::default();
+    let introspection = introspection(addr);
+
+    for _ in 0..3 {
+        let claims = introspector
+            .introspect(&introspection, "active")
+            .await
+            .unwrap();
+        assert_eq!(Some(&json!("user-1")), claims.get("sub"));
+    }
+    assert_eq!(1, introspections.load(Ordering::SeqCst));
+
+    // without a cache every request asks the endpoint.
+    let introspector = TokenIntrospector::default();
+    let introspection = IntrospectionAuth {
+        cache_seconds: Some(0),
+        ..introspection
+    };
+    for _ in 0..2 {
+        assert!(introspector
+            .introspect(&introspection, "active")
+            .await
+            .is_ok());
+    }
+    assert_eq!(3, introspections.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn introspect_inactive_token() {
+    let (addr, introspections) = start_introspection_server().await;
+    let introspector = TokenIntrospector::default();
+    let introspection = introspection(addr);
+
+    for token in ["revoked", "expired", "revoked", "expired"] {
+        assert_eq!(
+            Err("invalid"),
+            introspector.introspect(&introspection, token).await
+        );
+    }
+    assert_eq!(2, introspections.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn introspect_scopes_and_audiences() {
+    let (addr, _) = start_introspection_server().await;
+    let introspector = TokenIntrospector::default();
+    let introspection = |scopes: &[&str], audiences: &[&str]| IntrospectionAuth {
+        scopes: scopes.iter().map(|scope| scope.to_string()).collect(),
+        audiences: audiences
+            .iter()
+            .map(|audience| audience.to_string())
+            .collect(),
+        ..introspection(addr)
+    };
+
+    assert!(introspector
+        .introspect(
+            &introspection(&["orders:read", "orders:write"], &["billing", "orders"]),
+            "active"
+        )
+        .await
+        .is_ok());
+    assert_eq!(
+        Err("forbidden"),
+        introspector
+            .introspect(&introspection(&["orders:delete"], &[]), "active")
+            .await
+    );
+    assert_eq!(
+        Err("invalid"),
+        introspector
+            .introspect(&introspection(&[], &["billing"]), "active")
+            .await
+    );
+}
+
+#[tokio::test]
+async fn introspect_with_failing_endpoint() {
+    let (addr, introspections) = start_introspection_server().await;
+    let introspector = TokenIntrospector::default();
+
+    // failures aren't cached.
+    for _ in 0..2 {
+        assert_eq!(
+            Err("unavailable"),
+            introspector
+                .introspect(&introspection(addr), "broken")
+                .await
+        );
+    }
+    assert_eq!(2, introspections.load(Ordering::SeqCst));
+
+    let introspection = IntrospectionAuth {
+        client_secret: String::from("wrong"),
+        ..introspection(addr)
+    };
+    assert_eq!(
+        Err("unavailable"),
+        introspector.introspect(&introspection, "active").await
+    );
+}
+
+#[tokio::test]
+async fn introspect_with_bounded_cache() {
+    let (addr, introspections) = start_introspection_server().await;
+    let introspector = TokenIntrospector::new(2, 100, 100);
+    let introspection = introspection(addr);
+
+    // random tokens push the oldest results out, the cache never grows past its bound.
+    for token in ["active", "random-1", "random-2", "random-3"] {
+        let _ = introspector.introspect(&introspection, token).await;
+    }
+    assert_eq!(2, introspector.cache.lock().unwrap().results.len());
+    assert_eq!(4, introspections.load(Ordering::SeqCst));
+
+    assert!(introspector
+        .introspect(&introspection, "active")
+        .await
+        .is_ok());
+    assert_eq!(5, introspections.load(Ordering::SeqCst));
+    assert_eq!(
+        Err("invalid"),
+        introspector.introspect(&introspection, "random-3").await
+    );
+    assert_eq!(5, introspections.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn introspect_with_limited_misses() {
+    let (addr, introspections) = start_introspection_server().await;
+    let introspector = TokenIntrospector::new(100, 1, 2);
+    let introspection = introspection(addr);
+
+    assert_eq!(
+        Err("invalid"),
+        introspector.introspect(&introspection, "random-1").await
+    );
+    assert!(introspector
+        .introspect(&introspection, "active")
+        .await
+        .is_ok());
+    assert_eq!(
+        Err("unavailable"),
+        introspector.introspect(&introspection, "random-2").await
+    );
+    assert_eq!(2, introspections.load(Ordering::SeqCst));
+
+    // the cached results are still answered.
+    assert!(introspector
+        .introspect(&introspection, "active")
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn introspect_cached_by_client() {
+    let (addr, introspections) = start_introspection_server().await;
+    let introspector = TokenIntrospector::default();
+
+    assert!(introspector
+        .introspect(&introspection(addr), "active")
+        .await
+        .is_ok());
+
+    // another client of the same endpoint doesn't get the result cached for the first one.
+    let other = IntrospectionAuth {
+        client_id: String::from("other"),
+        ..introspection(addr)
+    };
+    assert_eq!(
+        Err("unavailable"),
+        introspector.introspect(&other, "active").await
+    );
+    assert_eq!(2, introspections.load(Ordering::SeqCst));
+}
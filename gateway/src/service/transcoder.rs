@@ -0,0 +1,272 @@
+#[cfg(test)]
+#[path = "transcoder_test.rs"]
+mod transcoder_test;
+
+use std::{collections::HashMap, fmt};
+
+use hyper::{body::Bytes, HeaderMap, Method, StatusCode};
+use prost::Message;
+use prost_reflect::{DynamicMessage, FieldDescriptor, Kind, MessageDescriptor, SerializeOptions};
+use serde_json::{Map, Value};
+use tonic::{Code, Status};
+
+use crate::{
+    exception::{
+        ApiError, TRANSCODING_ERR_GRPC_FAILED, TRANSCODING_ERR_INVALID_REQUEST,
+        TRANSCODING_ERR_INVALID_RESPONSE, TRANSCODING_ERR_METHOD_NOT_FOUND,
+    },
+    transcoding::{TranscodingDescriptor, TranscodingRule},
+};
+
+use super::{http_status, PathMatcher};
+
+// compressed flag and big endian length before every grpc message.
+const GRPC_FRAME_HEADER: usize = 5;
+
+// the http rules of a route, matched against what comes after the route path.
+pub struct Transcoder {
+    matchers: HashMap<Method, PathMatcher<TranscodingRule>>,
+}
+
+impl fmt::Debug for Transcoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transcoder")
+            .field("methods", &self.matchers.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Transcoder {
+    pub fn new(descriptor: TranscodingDescriptor) -> Transcoder {
+        let mut matchers = HashMap::<Method, PathMatcher<TranscodingRule>>::new();
+        for rule in descriptor.rules {
+            let path = rule.path.clone();
+            matchers
+                .entry(rule.http_method.clone())
+                .or_default()
+                .insert(&path, true, 0, rule);
+        }
+
+        Transcoder { matchers }
+    }
+
+    pub fn find(
+        &self,
+        method: &Method,
+        path: &str,
+    ) -> Result<(&TranscodingRule, HashMap<String, String>), ApiError> {
+        self.matchers
+            .get(method)
+            .and_then(|matcher| matcher.find(path))
+            .map(|path_match| (path_match.value, path_match.params))
+            .ok_or_else(|| {
+                ApiError::new_with_status(StatusCode::NOT_FOUND, TRANSCODING_ERR_METHOD_NOT_FOUND)
+            })
+    }
+}
+
+fn invalid_request(e: impl fmt::Display) -> ApiError {
+    tracing::info!("Error when transcoding a request: {}", e);
+    ApiError::new_with_status(StatusCode::BAD_REQUEST, TRANSCODING_ERR_INVALID_REQUEST)
+}
+
+fn invalid_response(e: impl fmt::Display) -> ApiError {
+    tracing::error!("Error when transcoding a response: {}", e);
+    ApiError::new_with_status(StatusCode::BAD_GATEWAY, TRANSCODING_ERR_INVALID_RESPONSE)
+}
+
+// the json body goes where the rule says, then path params and, when the body isn't the whole
+// message, query params fill the fields they name ("greeting.id").
+pub fn grpc_request(
+    rule: &TranscodingRule,
+    path_params: &HashMap<String, String>,
+    query: Option<&str>,
+    body: &[u8],
+) -> Result<Bytes, ApiError> {
+    let input = rule.method.input();
+    let body = match rule.body.as_deref() {
+        Some(_) if body.is_empty() => Value::Object(Map::new()),
+        Some(_) => serde_json::from_slice(body).map_err(invalid_request)?,
+        None => Value::Object(Map::new()),
+    };
+
+    let mut json = match rule.body.as_deref() {
+        Some("*") => body,
+        Some(field) => {
+            let mut json = Value::Object(Map::new());
+            set_json_path(&mut json, field, body)?;
+            json
+        }
+        None => Value::Object(Map::new()),
+    };
+
+    for (name, value) in path_params {
+        let field = find_field(&input, name).ok_or_else(|| invalid_request(name))?;
+        set_json_path(&mut json, name, json_value(&field, value))?;
+    }
+
+    if rule.body.as_deref() != Some("*") {
+        let query: Vec<(String, String)> =
+            serde_urlencoded::from_str(query.unwrap_or_default()).map_err(invalid_request)?;
+        for (name, value) in query {
+            if path_params.contains_key(&name) {
+                continue;
+            }
+
+            // unknown parameters are left to the other layers of the request, like cache busters.
+            if let Some(field) = find_field(&input, &name) {
+                let value = json_value(&field, &value);
+                if field.is_list() {
+                    push_json_path(&mut json, &name, value)?;
+                } else {
+                    set_json_path(&mut json, &name, value)?;
+                }
+            }
+        }
+    }
+
+    let message = DynamicMessage::deserialize(input, json).map_err(invalid_request)?;
+    Ok(grpc_frame(&message.encode_to_vec()))
+}
+
+// a single message becomes a json object, the messages of a server stream an array.
+pub fn json_response(rule: &TranscodingRule, body: &[u8]) -> Result<Bytes, ApiError> {
+    let output = rule.method.output();
+    let options = SerializeOptions::new().skip_default_fields(false);
+
+    let mut responses = Vec::new();
+    for frame in grpc_messages(body)? {
+        let message = DynamicMessage::decode(output.clone(), frame).map_err(invalid_response)?;
+        let mut json = message
+            .serialize_with_options(serde_json::value::Serializer, &options)
+            .map_err(invalid_response)?;
+
+        if let Some(field) = rule.response_body.as_deref() {
+            let name = output
+                .get_field_by_name(field)
+                .map(|field| field.json_name().to_owned())
+                .ok_or_else(|| invalid_response(field))?;
+            json = json.get_mut(&name).map(Value::take).unwrap_or(Value::Null);
+        }
+        responses.push(json);
+    }
+
+    let json = if responses.len() == 1 {
+        responses.remove(0)
+    } else {
+        Value::Array(responses)
+    };
+    serde_json::to_vec(&json)
+        .map(Bytes::from)
+        .map_err(invalid_response)
+}
+
+pub fn grpc_frame(message: &[u8]) -> Bytes {
+    let mut frame = Vec::with_capacity(GRPC_FRAME_HEADER + message.len());
+    frame.push(0);
+    frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    frame.extend_from_slice(message);
+    Bytes::from(frame)
+}
+
+pub fn grpc_messages(mut body: &[u8]) -> Result<Vec<&[u8]>, ApiError> {
+    let mut messages = Vec::new();
+    while !body.is_empty() {
+        if body.len() < GRPC_FRAME_HEADER {
+            return Err(invalid_response("truncated grpc frame"));
+        }
+        // no grpc-accept-encoding is sent, a compressed message is unexpected.
+        if body[0] != 0 {
+            return Err(invalid_response("compressed grpc message"));
+        }
+
+        let length = u32::from_be_bytes([body[1], body[2], body[3], body[4]]) as usize;
+        let end = GRPC_FRAME_HEADER + length;
+        if body.len() < end {
+            return Err(invalid_response("truncated grpc message"));
+        }
+
+        messages.push(&body[GRPC_FRAME_HEADER..end]);
+        body = &body[end..];
+    }
+
+    Ok(messages)
+}
+
+// the grpc-status comes in the trailers, or in the headers of a trailers-only response.
+pub fn grpc_failure(headers: &HeaderMap, trailers: Option<&HeaderMap>) -> Option<ApiError> {
+    let status = trailers
+        .and_then(Status::from_header_map)
+        .or_else(|| Status::from_header_map(headers));
+
+    let status = match status {
+        Some(status) if status.code() == Code::Ok => return None,
+        Some(status) => status,
+        None => Status::new(Code::Unknown, ""),
+    };
+
+    let mut api_error =
+        ApiError::new_with_status(http_status(status.code()), TRANSCODING_ERR_GRPC_FAILED);
+    if !status.message().is_empty() {
+        api_error.message = status.message().to_owned();
+    }
+    Some(api_error)
+}
+
+fn find_field(message: &MessageDescriptor, path: &str) -> Option<FieldDescriptor> {
+    let mut message = message.clone();
+    let mut names = path.split('.').peekable();
+
+    while let Some(name) = names.next() {
+        let field = message
+            .get_field_by_name(name)
+            .or_else(|| message.get_field_by_json_name(name))?;
+        if names.peek().is_none() {
+            return Some(field);
+        }
+
+        message = match field.kind() {
+            Kind::Message(message) if !field.is_list() => message,
+            _ => return None,
+        };
+    }
+
+    None
+}
+
+// path and query values are strings, proto3 json accepts them for numbers but not for bools.
+fn json_value(field: &FieldDescriptor, value: &str) -> Value {
+    match (field.kind(), value) {
+        (Kind::Bool, "true") => Value::Bool(true),
+        (Kind::Bool, "false") => Value::Bool(false),
+        _ => Value::String(value.to_owned()),
+    }
+}
+
+fn json_path<'a>(json: &'a mut Value, path: &str) -> Result<&'a mut Value, ApiError> {
+    let mut current = json;
+    for name in path.split('.') {
+        current = match current {
+            Value::Object(fields) => fields
+                .entry(name.to_owned())
+                .or_insert_with(|| Value::Object(Map::new())),
+            _ => return Err(invalid_request(path)),
+        };
+    }
+
+    Ok(current)
+}
+
+fn set_json_path(json: &mut Value, path: &str, value: Value) -> Result<(), ApiError> {
+    *json_path(json, path)? = value;
+    Ok(())
+}
+
+fn push_json_path(json: &mut Value, path: &str, value: Value) -> Result<(), ApiError> {
+    let current = json_path(json, path)?;
+    match current {
+        Value::Array(values) => values.push(value),
+        _ => *current = Value::Array(vec![value]),
+    }
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use hyper::header::HeaderValue;
+
+use crate::exception::TRANSCODING_ERR_INVALID_REQUEST;
+
+use super::*;
+
+const GREETER: &[u8] = include_bytes!("../../../common/fixtures/greeter.pb");
+
+fn transcoder() -> Transcoder {
+    Transcoder::new(TranscodingDescriptor::decode(GREETER).unwrap())
+}
+
+fn decode_request(rule: &TranscodingRule, frame: &[u8]) -> Value {
+    let messages = grpc_messages(frame).unwrap();
+    assert_eq!(1, messages.len());
+
+    let message = DynamicMessage::decode(rule.method.input(), messages[0]).unwrap();
+    serde_json::to_value(&message).unwrap()
+}
+
+fn reply(rule: &TranscodingRule, field: &str, value: prost_reflect::Value) -> Bytes {
+    let mut message = DynamicMessage::new(rule.method.output());
+    message.set_field_by_name(field, value);
+    grpc_frame(&message.encode_to_vec())
+}
+
+#[test]
+fn find() {
+    let transcoder = transcoder();
+
+    let (rule, params) = transcoder.find(&Method::GET, "/hello/ana").unwrap();
+    assert_eq!("/greeter.v1.Greeter/SayHello", rule.grpc_path());
+    assert_eq!(Some(&String::from("ana")), params.get("name"));
+
+    let (rule, params) = transcoder.find(&Method::PUT, "/greetings/7").unwrap();
+    assert_eq!("/greeter.v1.Greeter/CreateGreeting", rule.grpc_path());
+    assert_eq!(Some(&String::from("7")), params.get("greeting.id"));
+
+    let api_error = transcoder.find(&Method::POST, "/hello/ana").unwrap_err();
+    assert_eq!(404, api_error.status_code);
+    assert_eq!(TRANSCODING_ERR_METHOD_NOT_FOUND.0, api_error.code);
+    assert!(transcoder.find(&Method::GET, "/hello/ana/more").is_err());
+}
+
+#[test]
+fn request_from_path_and_query() {
+    let transcoder = transcoder();
+    let (rule, params) = transcoder.find(&Method::GET, "/hello/ana").unwrap();
+
+    // the path wins over the query, unknown params are ignored.
+    let frame = grpc_request(
+        rule,
+        &params,
+        Some("name=bia&times=3&shout=true&cache=1"),
+        b"ignored",
+    )
+    .unwrap();
+    assert_eq!(
+        serde_json::json!({"name": "ana", "times": 3, "shout": true}),
+        decode_request(rule, &frame)
+    );
+}
+
+#[test]
+fn request_from_body() {
+    let transcoder = transcoder();
+    let (rule, params) = transcoder.find(&Method::PUT, "/greetings/7").unwrap();
+
+    let frame = grpc_request(
+        rule,
+        &params,
+        Some("greeting.tags=a&greeting.tags=b"),
+        br#"{"text": "hi"}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        serde_json::json!({"greeting": {"id": "7", "text": "hi", "tags": ["a", "b"]}}),
+        decode_request(rule, &frame)
+    );
+}
+
+#[test]
+fn request_invalid() {
+    let transcoder = transcoder();
+
+    let (rule, params) = transcoder.find(&Method::PUT, "/greetings/7").unwrap();
+    let api_error = grpc_request(rule, &params, None, b"{").unwrap_err();
+    assert_eq!(400, api_error.status_code);
+    assert_eq!(TRANSCODING_ERR_INVALID_REQUEST.0, api_error.code);
+
+    let (rule, params) = transcoder.find(&Method::GET, "/hello/ana").unwrap();
+    let api_error = grpc_request(rule, &params, Some("times=many"), b"").unwrap_err();
+    assert_eq!(TRANSCODING_ERR_INVALID_REQUEST.0, api_error.code);
+}
+
+#[test]
+fn response() {
+    let transcoder = transcoder();
+
+    let (rule, _) = transcoder.find(&Method::GET, "/hello/ana").unwrap();
+    let frame = reply(rule, "message", prost_reflect::Value::String("hi".into()));
+    assert_eq!(
+        Bytes::from(r#"{"message":"hi"}"#),
+        json_response(rule, &frame).unwrap()
+    );
+
+    // the messages of a stream are returned as an array.
+    let frames = [frame.clone(), frame].concat();
+    assert_eq!(
+        Bytes::from(r#"[{"message":"hi"},{"message":"hi"}]"#),
+        json_response(rule, &frames).unwrap()
+    );
+
+    // only the field named by response_body.
+    let (rule, _) = transcoder.find(&Method::GET, "/greetings/7").unwrap();
+    let frame = reply(rule, "text", prost_reflect::Value::String("hi".into()));
+    assert_eq!(Bytes::from(r#""hi""#), json_response(rule, &frame).unwrap());
+
+    let api_error = json_response(rule, &[0, 0, 0, 0, 9, 1]).unwrap_err();
+    assert_eq!(502, api_error.status_code);
+    assert_eq!(TRANSCODING_ERR_INVALID_RESPONSE.0, api_error.code);
+}
+
+#[test]
+fn failure() {
+    let headers = HeaderMap::new();
+    let mut trailers = HeaderMap::new();
+    trailers.insert("grpc-status", HeaderValue::from_static("0"));
+    assert!(grpc_failure(&headers, Some(&trailers)).is_none());
+
+    trailers.insert("grpc-status", HeaderValue::from_static("5"));
+    trailers.insert(
+        "grpc-message",
+        HeaderValue::from_static("greeting%20not%20found"),
+    );
+    let api_error = grpc_failure(&headers, Some(&trailers)).unwrap();
+    assert_eq!(404, api_error.status_code);
+    assert_eq!(TRANSCODING_ERR_GRPC_FAILED.0, api_error.code);
+    assert_eq!("greeting not found", api_error.message);
+
+    // trailers-only responses carry the status in the headers.
+    let api_error = grpc_failure(&trailers, None).unwrap();
+    assert_eq!(404, api_error.status_code);
+
+    let api_error = grpc_failure(&headers, None).unwrap();
+    assert_eq!(500, api_error.status_code);
+}
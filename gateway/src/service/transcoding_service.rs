@@ -0,0 +1,165 @@
+#[cfg(test)]
+#[path = "transcoding_service_test.rs"]
+mod transcoding_service_test;
+
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    async_trait,
+    http::{Request, Response},
+};
+use hyper::{
+    body::{Bytes, HttpBody},
+    header::{HeaderValue, ACCEPT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, HOST},
+    Body, Method, StatusCode, Version,
+};
+
+use crate::{
+    config::{HttpClients, HttpsClient},
+    exception::{
+        ApiError, ERR_HYPER_ERROR, FORWARD_ERR_UPSTREAM_TIMEOUT, TRANSCODING_ERR_GRPC_FAILED,
+    },
+};
+
+use super::{
+    add_grpc_headers, buffer_body, grpc_failure, grpc_request, json_response,
+    max_buffered_body_size, proxy_idle_timeout, remove_hop_by_hop_headers, ForwardService,
+    ForwardTarget, Transcoder, UpstreamBalancer,
+};
+
+#[async_trait]
+pub trait TranscodingServiceTrait {
+    async fn execute(
+        &self,
+        target: ForwardTarget,
+        transcoder: Arc<Transcoder>,
+        req: Request<Body>,
+    ) -> Result<Response<Body>, ApiError>;
+}
+
+// json requests of a route with a descriptor set become unary grpc calls.
+pub struct TranscodingService {
+    clients: Arc<HttpClients>,
+    max_body_size: usize,
+    timeout: Duration,
+}
+
+impl TranscodingService {
+    pub fn new(clients: Arc<HttpClients>) -> Self {
+        TranscodingService {
+            clients,
+            max_body_size: max_buffered_body_size(),
+            timeout: proxy_idle_timeout(),
+        }
+    }
+
+    async fn call(
+        &self,
+        client: Arc<HttpsClient>,
+        req: Request<Body>,
+    ) -> Result<(Response<Body>, Bytes), ApiError> {
+        let mut response = client.request(req).await.map_err(|e| {
+            tracing::error!("Error when calling a grpc upstream: {:?}", e);
+            ApiError::new_with_status(StatusCode::BAD_GATEWAY, ERR_HYPER_ERROR)
+        })?;
+
+        let body = buffer_body(response.body_mut(), self.max_body_size)
+            .await
+            .map_err(|e| {
+                tracing::error!("Error when reading a grpc response: {}", e);
+                ApiError::new_with_status(StatusCode::BAD_GATEWAY, TRANSCODING_ERR_GRPC_FAILED)
+            })?;
+
+        Ok((response, body))
+    }
+}
+
+#[async_trait]
+impl TranscodingServiceTrait for TranscodingService {
+    async fn execute(
+        &self,
+        target: ForwardTarget,
+        transcoder: Arc<Transcoder>,
+        req: Request<Body>,
+    ) -> Result<Response<Body>, ApiError> {
+        let (parts, body) = req.into_parts();
+        let (rule, path_params) = transcoder.find(&parts.method, &target.remaining_path)?;
+        tracing::info!(
+            "transcoding {} {} to {}",
+            parts.method,
+            parts.uri.path(),
+            rule.grpc_path()
+        );
+
+        let body = if rule.body.is_some() {
+            buffer_body(body, self.max_body_size).await?
+        } else {
+            Bytes::new()
+        };
+        let message = grpc_request(rule, &path_params, parts.uri.query(), &body)?;
+
+        // the incoming headers go as grpc metadata, the ones describing the json body don't.
+        let mut headers = parts.headers;
+        remove_hop_by_hop_headers(&mut headers);
+        for name in [HOST, CONTENT_LENGTH, CONTENT_TYPE, ACCEPT_ENCODING] {
+            headers.remove(name);
+        }
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/grpc"));
+        add_grpc_headers(&mut headers);
+
+        let mut request = Request::new(Body::from(message));
+        *request.method_mut() = Method::POST;
+        *request.version_mut() = Version::HTTP_2;
+        *request.uri_mut() =
+            ForwardService::forward_uri(&target.url_destination, &rule.grpc_path(), None)?;
+        *request.headers_mut() = headers;
+
+        // unary calls are answered at once, the whole call is bounded by the response timeout.
+        let policy = &target.policy;
+        let client = self.clients.get_with_tls(
+            policy.connect_timeout(),
+            true,
+            target.balancer.as_deref().and_then(UpstreamBalancer::tls),
+        )?;
+        let timeout = policy
+            .response_timeout()
+            .unwrap_or(self.timeout)
+            .min(policy.total_timeout().unwrap_or(Duration::MAX));
+        let (mut response, body) = tokio::time::timeout(timeout, self.call(client, request))
+            .await
+            .map_err(|_| {
+                tracing::error!("grpc upstream has not answered in {:?}", timeout);
+                ApiError::new_with_status(StatusCode::GATEWAY_TIMEOUT, FORWARD_ERR_UPSTREAM_TIMEOUT)
+            })??;
+
+        let trailers = response.body_mut().trailers().await.map_err(|e| {
+            tracing::error!(
+                "Error when reading the trailers of a grpc response: {:?}",
+                e
+            );
+            ApiError::new_with_status(StatusCode::BAD_GATEWAY, TRANSCODING_ERR_GRPC_FAILED)
+        })?;
+
+        if response.status() != StatusCode::OK {
+            tracing::error!(
+                "grpc upstream has answered with status {}",
+                response.status()
+            );
+            return Err(ApiError::new_with_status(
+                StatusCode::BAD_GATEWAY,
+                TRANSCODING_ERR_GRPC_FAILED,
+            ));
+        }
+        if let Some(api_error) = grpc_failure(response.headers(), trailers.as_ref()) {
+            return Err(api_error);
+        }
+
+        let response = Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(json_response(rule, &body)?))
+            .unwrap();
+
+        Ok(response)
+    }
+}
@@ -0,0 +1,170 @@
+use std::{convert::Infallible, net::SocketAddr};
+
+use hyper::{
+    service::{make_service_fn, service_fn},
+    HeaderMap,
+};
+use prost::Message;
+use prost_reflect::{DescriptorPool, DynamicMessage, Value};
+
+use crate::{
+    config::HttpClients, exception::TRANSCODING_ERR_METHOD_NOT_FOUND, service::grpc_frame,
+    transcoding::TranscodingDescriptor,
+};
+
+use super::*;
+
+const GREETER: &[u8] = include_bytes!("../../../common/fixtures/greeter.pb");
+
+// SayHello greets with the name, times and x-tenant received, GetGreeting fails with NOT_FOUND.
+async fn start_greeter_upstream() -> SocketAddr {
+    let make_service = make_service_fn(|_| async {
+        Ok::<_, Infallible>(service_fn(|req: Request<Body>| async move {
+            let pool = DescriptorPool::decode(GREETER).unwrap();
+            let mut trailers = HeaderMap::new();
+
+            if req.uri().path() != "/greeter.v1.Greeter/SayHello" {
+                let response = Response::builder()
+                    .header(CONTENT_TYPE, "application/grpc")
+                    .header("grpc-status", "5")
+                    .header("grpc-message", "greeting%20not%20found")
+                    .body(Body::empty())
+                    .unwrap();
+                return Ok::<_, Infallible>(response);
+            }
+
+            let tenant = req.headers()["x-tenant"].to_str().unwrap().to_owned();
+            let te = req.headers()["te"].to_str().unwrap().to_owned();
+            let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+            let request = DynamicMessage::decode(
+                pool.get_message_by_name("greeter.v1.HelloRequest").unwrap(),
+                &body[5..],
+            )
+            .unwrap();
+
+            let mut reply =
+                DynamicMessage::new(pool.get_message_by_name("greeter.v1.HelloReply").unwrap());
+            reply.set_field_by_name(
+                "message",
+                Value::String(format!(
+                    "hello {} x{} from {} with {}",
+                    request.get_field_by_name("name").unwrap().as_str().unwrap(),
+                    request
+                        .get_field_by_name("times")
+                        .unwrap()
+                        .as_i32()
+                        .unwrap(),
+                    tenant,
+                    te
+                )),
+            );
+
+            let (mut sender, body) = Body::channel();
+            tokio::spawn(async move {
+                sender
+                    .send_data(grpc_frame(&reply.encode_to_vec()))
+                    .await
+                    .unwrap();
+                trailers.insert("grpc-status", HeaderValue::from_static("0"));
+                sender.send_trailers(trailers).await.unwrap();
+            });
+
+            let response = Response::builder()
+                .header(CONTENT_TYPE, "application/grpc")
+                .body(body)
+                .unwrap();
+            Ok::<_, Infallible>(response)
+        }))
+    });
+
+    let server = hyper::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+        .http2_only(true)
+        .serve(make_service);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+fn target(addr: SocketAddr, remaining_path: &str) -> ForwardTarget {
+    ForwardTarget {
+        url_destination: format!("http://{}", addr),
+        remaining_path: remaining_path.to_owned(),
+        id_application: 1,
+        preserve_host: false,
+        streaming: false,
+        policy: Arc::default(),
+        balancer: None,
+        rate_limits: Vec::new(),
+        auth: None,
+    }
+}
+
+fn transcoder() -> Arc<Transcoder> {
+    Arc::new(Transcoder::new(
+        TranscodingDescriptor::decode(GREETER).unwrap(),
+    ))
+}
+
+fn service() -> TranscodingService {
+    TranscodingService::new(Arc::new(HttpClients::config()))
+}
+
+#[tokio::test]
+async fn execute() {
+    let addr = start_greeter_upstream().await;
+
+    let request = Request::builder()
+        .uri("/greeter/hello/ana?times=2")
+        .header("x-tenant", "acme")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = service()
+        .execute(target(addr, "/hello/ana"), transcoder(), request)
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!("application/json", response.headers()[CONTENT_TYPE]);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(
+        serde_json::json!({"message": "hello ana x2 from acme with trailers"}),
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn execute_with_grpc_error() {
+    let addr = start_greeter_upstream().await;
+
+    let request = Request::builder()
+        .uri("/greeter/greetings/7")
+        .body(Body::empty())
+        .unwrap();
+
+    let api_error = service()
+        .execute(target(addr, "/greetings/7"), transcoder(), request)
+        .await
+        .unwrap_err();
+    assert_eq!(404, api_error.status_code);
+    assert_eq!(TRANSCODING_ERR_GRPC_FAILED.0, api_error.code);
+    assert_eq!("greeting not found", api_error.message);
+}
+
+#[tokio::test]
+async fn execute_without_http_rule() {
+    let addr = start_greeter_upstream().await;
+
+    let request = Request::builder()
+        .method("DELETE")
+        .uri("/greeter/hello/ana")
+        .body(Body::empty())
+        .unwrap();
+
+    let api_error = service()
+        .execute(target(addr, "/hello/ana"), transcoder(), request)
+        .await
+        .unwrap_err();
+    assert_eq!(404, api_error.status_code);
+    assert_eq!(TRANSCODING_ERR_METHOD_NOT_FOUND.0, api_error.code);
+}
@@ -0,0 +1,141 @@
+#[cfg(test)]
+#[path = "websocket_test.rs"]
+mod websocket_test;
+
+use std::{
+    collections::HashMap,
+    io,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use hyper::{
+    header::{CONNECTION, UPGRADE},
+    HeaderMap,
+};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+const DEFAULT_WEBSOCKET_IDLE_TIMEOUT: u64 = 300;
+
+pub fn websocket_idle_timeout() -> Duration {
+    Duration::from_secs(
+        std::env::var("WEBSOCKET_IDLE_TIMEOUT")
+            .ok()
+            .and_then(|timeout| timeout.parse().ok())
+            .unwrap_or(DEFAULT_WEBSOCKET_IDLE_TIMEOUT),
+    )
+}
+
+// "connection: keep-alive, Upgrade" and "upgrade: websocket".
+pub fn is_websocket_upgrade(headers: &HeaderMap) -> bool {
+    let has_token = |name, token: &str| {
+        headers
+            .get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|value| value.trim().eq_ignore_ascii_case(token))
+    };
+
+    has_token(CONNECTION, "upgrade") && has_token(UPGRADE, "websocket")
+}
+
+// open websocket connections by application.
+#[derive(Default)]
+pub struct WebSocketConnections {
+    counts: Mutex<HashMap<i64, usize>>,
+}
+
+// releases its connection when dropped.
+pub struct WebSocketPermit {
+    connections: Arc<WebSocketConnections>,
+    id_application: i64,
+}
+
+impl WebSocketConnections {
+    pub fn acquire(
+        connections: &Arc<WebSocketConnections>,
+        id_application: i64,
+        max_connections: Option<i32>,
+    ) -> Option<WebSocketPermit> {
+        let mut counts = connections.counts.lock().unwrap();
+        let count = counts.entry(id_application).or_default();
+
+        if max_connections.is_some_and(|max_connections| *count >= max_connections as usize) {
+            return None;
+        }
+
+        *count += 1;
+        Some(WebSocketPermit {
+            connections: Arc::clone(connections),
+            id_application,
+        })
+    }
+
+    pub fn count(&self, id_application: i64) -> usize {
+        let counts = self.counts.lock().unwrap();
+        counts.get(&id_application).copied().unwrap_or_default()
+    }
+}
+
+impl Drop for WebSocketPermit {
+    fn drop(&mut self) {
+        let mut counts = self.connections.counts.lock().unwrap();
+        if let Some(count) = counts.get_mut(&self.id_application) {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Spliced {
+    pub to_upstream: u64,
+    pub to_client: u64,
+    pub idle: bool,
+}
+
+// copies both directions until both sides are closed or nothing passes for idle_timeout. a
+// side that closes is propagated as a shutdown of the other one.
+pub async fn splice<C, U>(client: C, upstream: U, idle_timeout: Duration) -> io::Result<Spliced>
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+    U: AsyncRead + AsyncWrite + Unpin,
+{
+    let (mut client_read, mut client_write) = tokio::io::split(client);
+    let (mut upstream_read, mut upstream_write) = tokio::io::split(upstream);
+    let mut client_buffer = vec![0u8; 8 * 1024];
+    let mut upstream_buffer = vec![0u8; 8 * 1024];
+    let (mut client_open, mut upstream_open) = (true, true);
+    let mut spliced = Spliced::default();
+
+    while client_open || upstream_open {
+        tokio::select! {
+            read = client_read.read(&mut client_buffer), if client_open => {
+                let read = read?;
+                if read == 0 {
+                    client_open = false;
+                    upstream_write.shutdown().await?;
+                } else {
+                    upstream_write.write_all(&client_buffer[..read]).await?;
+                    spliced.to_upstream += read as u64;
+                }
+            }
+            read = upstream_read.read(&mut upstream_buffer), if upstream_open => {
+                let read = read?;
+                if read == 0 {
+                    upstream_open = false;
+                    client_write.shutdown().await?;
+                } else {
+                    client_write.write_all(&upstream_buffer[..read]).await?;
+                    spliced.to_client += read as u64;
+                }
+            }
+            _ = tokio::time::sleep(idle_timeout) => {
+                spliced.idle = true;
+                break;
+            }
+        }
+    }
+
+    Ok(spliced)
+}
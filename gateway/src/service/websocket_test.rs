@@ -0,0 +1,84 @@
+use hyper::header::HeaderValue;
+use tokio::io::duplex;
+
+use super::*;
+
+#[test]
+fn detect_upgrade() {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONNECTION, HeaderValue::from_static("keep-alive, Upgrade"));
+    headers.insert(UPGRADE, HeaderValue::from_static("WebSocket"));
+    assert!(is_websocket_upgrade(&headers));
+
+    headers.insert(UPGRADE, HeaderValue::from_static("h2c"));
+    assert!(!is_websocket_upgrade(&headers));
+
+    headers.remove(CONNECTION);
+    headers.insert(UPGRADE, HeaderValue::from_static("websocket"));
+    assert!(!is_websocket_upgrade(&headers));
+}
+
+#[test]
+fn acquire() {
+    let connections = Arc::new(WebSocketConnections::default());
+
+    let first = WebSocketConnections::acquire(&connections, 1, Some(2));
+    let second = WebSocketConnections::acquire(&connections, 1, Some(2));
+    assert!(first.is_some());
+    assert!(second.is_some());
+    assert!(WebSocketConnections::acquire(&connections, 1, Some(2)).is_none());
+    // the limit is by application.
+    assert!(WebSocketConnections::acquire(&connections, 2, Some(2)).is_some());
+    assert_eq!(2, connections.count(1));
+
+    drop(first);
+    assert_eq!(1, connections.count(1));
+    assert!(WebSocketConnections::acquire(&connections, 1, Some(2)).is_some());
+    assert!(WebSocketConnections::acquire(&connections, 1, None).is_some());
+}
+
+#[tokio::test]
+async fn splice_both_directions() {
+    let (client, mut client_peer) = duplex(64);
+    let (upstream, mut upstream_peer) = duplex(64);
+    let splice = tokio::spawn(splice(client, upstream, Duration::from_secs(5)));
+
+    client_peer.write_all(b"ping").await.unwrap();
+    let mut buffer = [0u8; 4];
+    upstream_peer.read_exact(&mut buffer).await.unwrap();
+    assert_eq!(b"ping", &buffer);
+
+    upstream_peer.write_all(b"pong!").await.unwrap();
+    let mut buffer = [0u8; 5];
+    client_peer.read_exact(&mut buffer).await.unwrap();
+    assert_eq!(b"pong!", &buffer);
+
+    // closing one side closes the other.
+    drop(client_peer);
+    assert_eq!(0, upstream_peer.read(&mut buffer).await.unwrap());
+    drop(upstream_peer);
+
+    let spliced = splice.await.unwrap().unwrap();
+    assert_eq!(
+        Spliced {
+            to_upstream: 4,
+            to_client: 5,
+            idle: false
+        },
+        spliced
+    );
+}
+
+#[tokio::test]
+async fn splice_idle() {
+    let (client, _client_peer) = duplex(64);
+    let (upstream, _upstream_peer) = duplex(64);
+
+    let spliced = tokio::time::timeout(
+        Duration::from_secs(5),
+        splice(client, upstream, Duration::from_millis(100)),
+    )
+    .await;
+    assert!(spliced.is_ok());
+    assert!(spliced.unwrap().unwrap().idle);
+}